@@ -69,6 +69,7 @@ pub struct Parser<const OSC_RAW_BUF_SIZE: usize = MAX_OSC_RAW> {
     ignoring: bool,
     partial_utf8: [u8; 4],
     partial_utf8_len: usize,
+    latin1_fallback: bool,
 }
 
 impl Parser {
@@ -92,6 +93,16 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
         Default::default()
     }
 
+    /// Decode the bytes which aren't valid UTF-8 as Latin-1 instead of
+    /// printing a replacement character for them.
+    ///
+    /// Valid UTF-8 is still decoded as UTF-8, so output of legacy programs
+    /// can be mixed with the output of the others.
+    #[inline]
+    pub fn set_latin1_fallback(&mut self, enabled: bool) {
+        self.latin1_fallback = enabled;
+    }
+
     #[inline]
     fn params(&self) -> &Params {
         &self.params
@@ -675,6 +686,13 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
                 Self::ground_dispatch(performer, parsed);
 
                 match err.error_len() {
+                    Some(len) if self.latin1_fallback => {
+                        Self::latin1_dispatch(
+                            performer,
+                            &bytes[valid_bytes..valid_bytes + len],
+                        );
+                        valid_bytes + len
+                    }
                     Some(len) => {
                         // Execute C1 escapes or emit replacement character.
                         if len == 1 && bytes[valid_bytes] <= 0x9F {
@@ -693,7 +711,14 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
                     None => {
                         if plain_chars < num_bytes {
                             // Process bytes cut off by escape.
-                            performer.print('�');
+                            if self.latin1_fallback {
+                                Self::latin1_dispatch(
+                                    performer,
+                                    &bytes[valid_bytes..plain_chars],
+                                );
+                            } else {
+                                performer.print('�');
+                            }
                             self.state = State::Escape;
                             self.reset_params();
                             plain_chars + 1
@@ -760,7 +785,14 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
                     // If the partial character was also invalid, emit the replacement
                     // character.
                     Some(invalid_len) => {
-                        performer.print('�');
+                        if self.latin1_fallback {
+                            Self::latin1_dispatch(
+                                performer,
+                                &self.partial_utf8[..invalid_len],
+                            );
+                        } else {
+                            performer.print('�');
+                        }
 
                         self.partial_utf8_len = 0;
                         invalid_len - old_bytes
@@ -782,6 +814,18 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
             }
         }
     }
+
+    /// Handle bytes of an invalid UTF-8 sequence as Latin-1 characters, the
+    /// bytes in the C1 range are executed like their UTF-8 encoding is.
+    #[inline]
+    fn latin1_dispatch<P: Perform>(performer: &mut P, bytes: &[u8]) {
+        for &byte in bytes {
+            match byte {
+                0x80..=0x9F => performer.execute(byte),
+                _ => performer.print(byte as char),
+            }
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
@@ -1769,6 +1813,58 @@ mod tests {
         assert_eq!(dispatcher.dispatched[2], Sequence::Print('b'));
     }
 
+    #[test]
+    fn invalid_utf8_latin1_fallback() {
+        const INPUT: &[u8] = b"caf\xE9 \xC3\xA9\x85\xFF\x1b0";
+
+        let mut dispatcher = Dispatcher::default();
+        let mut parser = Parser::new();
+        parser.set_latin1_fallback(true);
+
+        parser.advance(&mut dispatcher, INPUT);
+
+        assert_eq!(
+            dispatcher.dispatched,
+            vec![
+                Sequence::Print('c'),
+                Sequence::Print('a'),
+                Sequence::Print('f'),
+                Sequence::Print('é'),
+                Sequence::Print(' '),
+                // Valid UTF-8 is still decoded as UTF-8
+                Sequence::Print('é'),
+                Sequence::Execute(0x85),
+                Sequence::Print('ÿ'),
+                Sequence::Esc(Vec::new(), false, b'0'),
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_utf8_latin1_fallback() {
+        const INPUT: &[u8] = b"\xE4\xBFa\xE9";
+
+        let mut dispatcher = Dispatcher::default();
+        let mut parser = Parser::new();
+        parser.set_latin1_fallback(true);
+
+        parser.advance(&mut dispatcher, &INPUT[..1]);
+        parser.advance(&mut dispatcher, &INPUT[1..2]);
+        parser.advance(&mut dispatcher, &INPUT[2..]);
+        parser.advance(&mut dispatcher, b"\x1b0");
+
+        assert_eq!(
+            dispatcher.dispatched,
+            vec![
+                Sequence::Print('ä'),
+                Sequence::Print('¿'),
+                Sequence::Print('a'),
+                Sequence::Print('é'),
+                Sequence::Esc(Vec::new(), false, b'0'),
+            ]
+        );
+    }
+
     #[test]
     fn partial_utf8() {
         const INPUT: &[u8] = b"\xF0\x9F\x9A\x80";
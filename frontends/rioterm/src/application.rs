@@ -1,13 +1,19 @@
+use crate::crash;
 use crate::event::{ClickState, EventPayload, EventProxy, RioEvent, RioEventType};
 use crate::ime::Preedit;
+use crate::platform;
 use crate::renderer::utils::update_colors_based_on_theme;
 use crate::router::{routes::RoutePath, Router};
 use crate::scheduler::{Scheduler, TimerId, Topic};
+use crate::screen::osc52::ClipboardRequest;
 use crate::screen::touch::on_touch;
+use crate::startup::StartupTimings;
 use crate::watcher::configuration_file_updates;
 use raw_window_handle::HasDisplayHandle;
 use rio_backend::clipboard::{Clipboard, ClipboardType};
-use rio_backend::config::colors::ColorRgb;
+use rio_backend::config::colors::{set_accent_color, ColorRgb};
+use rio_backend::config::renderer::BatterySaver;
+use rio_backend::error::{RioError, RioErrorLevel, RioErrorType};
 use rio_window::application::ApplicationHandler;
 use rio_window::event::{
     ElementState, Hook, Ime, MouseButton, MouseScrollDelta, StartCause, TouchPhase,
@@ -21,39 +27,73 @@ use rio_window::platform::macos::ActiveEventLoopExtMacOS;
 #[cfg(target_os = "macos")]
 use rio_window::platform::macos::WindowExtMacOS;
 use rio_window::window::WindowId;
-use rio_window::window::{CursorIcon, Fullscreen};
+use rio_window::window::{CursorIcon, Fullscreen, Theme, UserAttentionType};
 use std::error::Error;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Timers are unscheduled by route id when a route closes,
+// so the battery saver and theme schedule timers use an id no route will have
+const BATTERY_SAVER_TIMER_ID: usize = usize::MAX;
+const BATTERY_SAVER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const THEME_SCHEDULE_TIMER_ID: usize = usize::MAX;
 
 pub struct Application<'a> {
     config: rio_backend::config::Config,
     event_proxy: EventProxy,
     router: Router<'a>,
     scheduler: Scheduler,
+    // Set when the adaptive theme follows a schedule instead of the system
+    scheduled_theme: Option<Theme>,
+    // The configuration file is neither loaded nor watched
+    safe_mode: bool,
+    // Taken once the first window is created
+    startup: Option<StartupTimings>,
+    // Toggled by the application menu, only active while focused
+    #[cfg(target_os = "macos")]
+    secure_keyboard_entry: bool,
 }
 
 impl Application<'_> {
     pub fn new<'app>(
         config: rio_backend::config::Config,
         config_error: Option<rio_backend::config::ConfigError>,
+        safe_mode: bool,
+        mut startup: StartupTimings,
         event_loop: &EventLoop<EventPayload>,
     ) -> Application<'app> {
+        let span = tracing::span!(tracing::Level::INFO, "application_new");
+        let _guard = span.enter();
+
         // SAFETY: Since this takes a pointer to the winit event loop, it MUST be dropped first,
         // which is done in `loop_exiting`.
         let clipboard =
             unsafe { Clipboard::new(event_loop.display_handle().unwrap().as_raw()) };
+        startup.phase("clipboard");
 
         let mut router = Router::new(config.fonts.to_owned(), clipboard);
-        if let Some(error) = config_error {
+        startup.phase("fonts");
+        if safe_mode {
+            router.propagate_error_to_next_route(RioError {
+                report: RioErrorType::SafeMode,
+                level: RioErrorLevel::Warning,
+            });
+        } else if let Some(error) = config_error {
             router.propagate_error_to_next_route(error.into());
+        } else if let Some(message) = crash::take_last_crash() {
+            router.propagate_error_to_next_route(RioError {
+                report: RioErrorType::Crashed(message),
+                level: RioErrorLevel::Warning,
+            });
         }
 
         let proxy = event_loop.create_proxy();
         let event_proxy = EventProxy::new(proxy.clone());
-        let _ = configuration_file_updates(
-            rio_backend::config::config_dir_path(),
-            event_proxy.clone(),
-        );
+        if !safe_mode {
+            let _ = configuration_file_updates(
+                rio_backend::config::config_dir_path(),
+                event_proxy.clone(),
+            );
+        }
         let scheduler = Scheduler::new(proxy);
         event_loop.listen_device_events(DeviceEvents::Never);
 
@@ -61,10 +101,15 @@ impl Application<'_> {
         event_loop.set_confirm_before_quit(config.confirm_before_quit);
 
         Application {
+            #[cfg(target_os = "macos")]
+            secure_keyboard_entry: config.window.macos_secure_keyboard_entry,
             config,
             event_proxy,
             router,
             scheduler,
+            scheduled_theme: None,
+            safe_mode,
+            startup: Some(startup),
         }
     }
 
@@ -89,6 +134,100 @@ impl Application<'_> {
         )
     }
 
+    /// Applies the battery saver state resulting from the configuration,
+    /// `force` re-applies it even if the state did not change.
+    fn update_battery_saver(&mut self, force: bool) {
+        let enabled = match self.config.renderer.battery_saver {
+            BatterySaver::Never => false,
+            BatterySaver::Always => true,
+            BatterySaver::Auto => platform::is_on_battery(),
+        };
+
+        if force || enabled != self.router.battery_saver {
+            tracing::info!("battery saver enabled: {enabled}");
+            self.router.set_battery_saver(enabled, &self.config);
+        }
+    }
+
+    /// The power source is only polled when the battery saver is set to auto.
+    fn schedule_battery_saver(&mut self) {
+        let timer_id = TimerId::new(Topic::BatterySaver, BATTERY_SAVER_TIMER_ID);
+        if self.config.renderer.battery_saver != BatterySaver::Auto {
+            self.scheduler.unschedule(timer_id);
+            return;
+        }
+
+        if self.scheduler.scheduled(timer_id) {
+            return;
+        }
+
+        if let Some(window_id) = self.router.routes.keys().next() {
+            let event = EventPayload::new(
+                RioEventType::Rio(RioEvent::UpdateBatterySaver),
+                *window_id,
+            );
+            self.scheduler
+                .schedule(event, BATTERY_SAVER_POLL_INTERVAL, true, timer_id);
+        }
+    }
+
+    /// Appearance from the adaptive theme schedule and how long until it
+    /// should be checked again, `None` when it follows the system.
+    fn scheduled_appearance(&self) -> Option<(Theme, Duration)> {
+        let adaptive_theme = self.config.adaptive_theme.as_ref()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        adaptive_theme.scheduled_appearance(now, platform::local_utc_offset())
+    }
+
+    /// Applies the colors of the scheduled appearance if it changed.
+    fn update_theme_schedule(&mut self) {
+        let Some((appearance, _)) = self.scheduled_appearance() else {
+            return;
+        };
+
+        if self.scheduled_theme == Some(appearance) {
+            return;
+        }
+
+        tracing::info!("scheduled theme: {appearance:?}");
+        self.scheduled_theme = Some(appearance);
+        update_colors_based_on_theme(&mut self.config, Some(appearance));
+        for route in self.router.routes.values_mut() {
+            route.window.screen.update_config(
+                &self.config,
+                &self.router.font_library,
+                false,
+            );
+            route.window.configure_window(&self.config);
+            route
+                .window
+                .screen
+                .context_manager
+                .reset_profile(&self.config.profiles);
+            route.request_redraw();
+        }
+    }
+
+    /// The next check happens when the appearance is expected to change.
+    fn schedule_theme_schedule(&mut self) {
+        let timer_id = TimerId::new(Topic::ThemeSchedule, THEME_SCHEDULE_TIMER_ID);
+        self.scheduler.unschedule(timer_id);
+
+        let Some((_, interval)) = self.scheduled_appearance() else {
+            return;
+        };
+
+        if let Some(window_id) = self.router.routes.keys().next() {
+            let event = EventPayload::new(
+                RioEventType::Rio(RioEvent::UpdateThemeSchedule),
+                *window_id,
+            );
+            self.scheduler.schedule(event, interval, false, timer_id);
+        }
+    }
+
     pub fn run(
         &mut self,
         event_loop: EventLoop<EventPayload>,
@@ -96,6 +235,49 @@ impl Application<'_> {
         let result = event_loop.run_app(self);
         result.map_err(Into::into)
     }
+
+    /// Drops the route of the window, which shuts down its terminals and
+    /// destroys the native window. Rio exits with the last window, apps
+    /// keep running without windows on macOS unless configured otherwise.
+    fn close_window(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId) {
+        self.router.routes.remove(&window_id);
+        if self.router.routes.is_empty()
+            && (!cfg!(target_os = "macos")
+                || self
+                    .config
+                    .window
+                    .macos_quit_on_last_window_closed
+                    .unwrap_or(!self.config.confirm_before_quit))
+        {
+            event_loop.exit();
+        }
+    }
+
+    /// Secure keyboard entry is only on while a window has the focus, the
+    /// menu item follows the toggle. Focus can move between windows in
+    /// any order.
+    #[cfg(target_os = "macos")]
+    fn update_secure_keyboard_entry(&self, event_loop: &ActiveEventLoop) {
+        let has_focused_window = self
+            .router
+            .routes
+            .values()
+            .any(|route| route.window.is_focused);
+        event_loop
+            .set_secure_keyboard_entry(self.secure_keyboard_entry && has_focused_window);
+        event_loop.set_secure_keyboard_entry_menu_state(self.secure_keyboard_entry);
+    }
+}
+
+/// Whether quitting, or closing a window, with these programs running waits
+/// for a confirmation. Programs aren't known on Windows, so it's always
+/// asked there.
+#[inline]
+fn needs_quit_confirmation(
+    config: &rio_backend::config::Config,
+    programs: &[String],
+) -> bool {
+    config.confirm_before_quit && (cfg!(windows) || !programs.is_empty())
 }
 
 impl ApplicationHandler<EventPayload> for Application<'_> {
@@ -126,7 +308,11 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
             return;
         }
 
-        update_colors_based_on_theme(&mut self.config, event_loop.system_theme());
+        self.scheduled_theme = self.scheduled_appearance().map(|(theme, _)| theme);
+        update_colors_based_on_theme(
+            &mut self.config,
+            self.scheduled_theme.or(event_loop.system_theme()),
+        );
 
         self.router.create_window(
             event_loop,
@@ -135,6 +321,15 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
             None,
         );
 
+        if let Some(mut startup) = self.startup.take() {
+            startup.phase("window");
+            startup.report(Duration::from_millis(self.config.developer.startup_budget));
+        }
+
+        self.update_battery_saver(false);
+        self.schedule_battery_saver();
+        self.schedule_theme_schedule();
+
         tracing::info!("Initialisation complete");
     }
 
@@ -143,8 +338,9 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
         match event.payload {
             RioEventType::Rio(RioEvent::Render) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
-                    if self.config.renderer.disable_unfocused_render
-                        && !route.window.is_focused
+                    if route.window.is_hidden()
+                        || (self.config.renderer.disable_unfocused_render
+                            && !route.window.is_focused)
                     {
                         return;
                     }
@@ -153,11 +349,22 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 }
             }
             RioEventType::Rio(RioEvent::RenderRoute(route_id)) => {
+                // The PTY has new output, the program may have changed echo
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    if let Some(context) =
+                        route.window.screen.ctx_mut().context_by_route_mut(route_id)
+                    {
+                        context.update_password_input();
+                    }
+                }
+
                 if self.config.renderer.strategy.is_event_based() {
                     if let Some(route) = self.router.routes.get_mut(&window_id) {
-                        // Skip rendering for unfocused windows if configured
-                        if self.config.renderer.disable_unfocused_render
-                            && !route.window.is_focused
+                        // Skip rendering for hidden windows and for unfocused
+                        // windows if configured
+                        if route.window.is_hidden()
+                            || (self.config.renderer.disable_unfocused_render
+                                && !route.window.is_focused)
                         {
                             return;
                         }
@@ -211,6 +418,8 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.report_error(&error);
                 }
             }
+            // Loading the configuration would leave the safe mode
+            RioEventType::Rio(RioEvent::UpdateConfig) if self.safe_mode => {}
             RioEventType::Rio(RioEvent::UpdateConfig) => {
                 let (config, config_error) = match rio_backend::config::Config::try_load()
                 {
@@ -220,6 +429,15 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
 
                 let has_font_updates = self.config.fonts != config.fonts;
 
+                #[cfg(target_os = "macos")]
+                if self.config.window.macos_secure_keyboard_entry
+                    != config.window.macos_secure_keyboard_entry
+                {
+                    self.secure_keyboard_entry =
+                        config.window.macos_secure_keyboard_entry;
+                    self.update_secure_keyboard_entry(event_loop);
+                }
+
                 let font_library_errors = if has_font_updates {
                     let new_font_library = rio_backend::sugarloaf::font::FontLibrary::new(
                         config.fonts.to_owned(),
@@ -231,6 +449,12 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 };
 
                 self.config = config;
+                self.scheduled_theme =
+                    self.scheduled_appearance().map(|(theme, _)| theme);
+                update_colors_based_on_theme(
+                    &mut self.config,
+                    self.scheduled_theme.or(event_loop.system_theme()),
+                );
                 for (_id, route) in self.router.routes.iter_mut() {
                     if has_font_updates {
                         if let Some(ref err) = font_library_errors {
@@ -250,6 +474,11 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                         has_font_updates,
                     );
                     route.window.configure_window(&self.config);
+                    route
+                        .window
+                        .screen
+                        .context_manager
+                        .reset_profile(&self.config.profiles);
 
                     if let Some(error) = &config_error {
                         route.report_error(&error.to_owned().into());
@@ -257,11 +486,61 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                         route.clear_errors();
                     }
                 }
+
+                self.update_battery_saver(true);
+                self.schedule_battery_saver();
+                self.schedule_theme_schedule();
+            }
+            RioEventType::Rio(RioEvent::UpdateBatterySaver) => {
+                self.update_battery_saver(false);
+            }
+            RioEventType::Rio(RioEvent::UpdateThemeSchedule) => {
+                self.update_theme_schedule();
+                self.schedule_theme_schedule();
+            }
+            RioEventType::Rio(RioEvent::ConfirmPaste) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.confirm_paste();
+                    route.request_redraw();
+                }
+            }
+            RioEventType::Rio(RioEvent::ClipboardHistory) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.open_clipboard_history();
+                    route.request_redraw();
+                }
+            }
+            RioEventType::Rio(RioEvent::CommandPalette) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.open_command_palette();
+                    route.request_redraw();
+                }
+            }
+            // The settings are written to a config file that isn't loaded
+            RioEventType::Rio(RioEvent::Settings) if self.safe_mode => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.report_error(&RioError {
+                        report: RioErrorType::SafeMode,
+                        level: RioErrorLevel::Warning,
+                    });
+                    route.request_redraw();
+                }
+            }
+            RioEventType::Rio(RioEvent::Settings) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.open_settings(&self.config);
+                    route.request_redraw();
+                }
             }
             RioEventType::Rio(RioEvent::Exit) => {
+                let programs = if self.config.confirm_before_quit {
+                    self.router.running_programs()
+                } else {
+                    Vec::new()
+                };
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
-                    if cfg!(target_os = "macos") && self.config.confirm_before_quit {
-                        route.confirm_quit();
+                    if needs_quit_confirmation(&self.config, &programs) {
+                        route.confirm_quit(false, programs);
                         route.request_redraw();
                     } else {
                         route.quit();
@@ -353,6 +632,32 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     );
                 }
             }
+            RioEventType::Rio(RioEvent::PrepareDelayedSearch(millis)) => {
+                if let Some(route) = self.router.routes.get(&window_id) {
+                    let timer_id = TimerId::new(
+                        Topic::DelayedSearch,
+                        route.window.screen.ctx().current_route(),
+                    );
+                    let event = EventPayload::new(
+                        RioEventType::Rio(RioEvent::DelayedSearch),
+                        window_id,
+                    );
+
+                    if !self.scheduler.scheduled(timer_id) {
+                        self.scheduler.schedule(
+                            event,
+                            Duration::from_millis(millis),
+                            false,
+                            timer_id,
+                        );
+                    }
+                }
+            }
+            RioEventType::Rio(RioEvent::DelayedSearch) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.window.screen.delayed_search();
+                }
+            }
             RioEventType::Rio(RioEvent::Title(title)) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     route.set_window_title(&title);
@@ -364,6 +669,50 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.set_window_subtitle(&subtitle);
                 }
             }
+            RioEventType::Rio(RioEvent::UserVarChanged(route_id, name, value)) => {
+                tracing::debug!(
+                    "user var {name} changed to {value:?} on route {route_id}"
+                );
+
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    // User vars may be used by title templates
+                    let context_manager = &mut route.window.screen.context_manager;
+                    context_manager.titles.last_title_update = None;
+                    context_manager.update_titles();
+                }
+            }
+            RioEventType::Rio(RioEvent::Bell(route_id)) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    if route.window.screen.ring_bell(route_id) && !route.window.is_focused
+                    {
+                        route.window.winit_window.request_user_attention(Some(
+                            UserAttentionType::Informational,
+                        ));
+                    }
+                }
+            }
+            RioEventType::Rio(RioEvent::UpdateProfile(route_id, name)) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    let profile = name.and_then(|name| {
+                        self.config.profiles.iter().find(|p| p.name == name)
+                    });
+
+                    match profile {
+                        Some(profile) => route.update_profile(
+                            &self.config.with_profile(profile),
+                            &self.router.font_library,
+                            route_id,
+                        ),
+                        None => route.update_profile(
+                            &self.config,
+                            &self.router.font_library,
+                            route_id,
+                        ),
+                    }
+
+                    route.request_redraw();
+                }
+            }
             RioEventType::Rio(RioEvent::MouseCursorDirty) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     route.window.screen.reset_mouse();
@@ -382,71 +731,63 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     drop(terminal);
                 }
             }
-            RioEventType::Rio(RioEvent::ClipboardLoad(clipboard_type, format)) => {
+            RioEventType::Rio(RioEvent::ClipboardLoad(
+                route_id,
+                clipboard_type,
+                format,
+            )) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    let request =
+                        ClipboardRequest::Load(route_id, clipboard_type, format);
                     if route.window.is_focused {
-                        let text = format(
-                            self.router
-                                .clipboard
-                                .borrow_mut()
-                                .get(clipboard_type)
-                                .as_str(),
-                        );
-                        route
-                            .window
-                            .screen
-                            .ctx_mut()
-                            .current_mut()
-                            .messenger
-                            .send_bytes(text.into_bytes());
+                        route.clipboard_request(request);
+                    } else {
+                        route.window.screen.reject_clipboard_request(request);
                     }
                 }
             }
             RioEventType::Rio(RioEvent::ClipboardStore(clipboard_type, content)) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     if route.window.is_focused {
-                        self.router
-                            .clipboard
-                            .borrow_mut()
-                            .set(clipboard_type, content);
+                        route.clipboard_request(ClipboardRequest::Store(
+                            clipboard_type,
+                            content,
+                        ));
                     }
                 }
             }
-            RioEventType::Rio(RioEvent::PtyWrite(text)) => {
-                if let Some(route) = self.router.routes.get_mut(&window_id) {
-                    route
-                        .window
-                        .screen
-                        .ctx_mut()
-                        .current_mut()
-                        .messenger
-                        .send_bytes(text.into_bytes());
+            // Replies go to the terminal which asked, even in a background
+            // tab or split
+            RioEventType::Rio(RioEvent::PtyWrite(route_id, text)) => {
+                if let Some(context) =
+                    self.router.routes.get_mut(&window_id).and_then(|route| {
+                        route.window.screen.ctx_mut().context_by_route_mut(route_id)
+                    })
+                {
+                    context.messenger.send_bytes(text.into_bytes());
                 }
             }
-            RioEventType::Rio(RioEvent::TextAreaSizeRequest(format)) => {
-                if let Some(route) = self.router.routes.get_mut(&window_id) {
-                    let dimension =
-                        route.window.screen.context_manager.current().dimension;
-                    let text =
-                        format(crate::renderer::utils::terminal_dimensions(&dimension));
-                    route
-                        .window
-                        .screen
-                        .ctx_mut()
-                        .current_mut()
-                        .messenger
-                        .send_bytes(text.into_bytes());
+            RioEventType::Rio(RioEvent::TextAreaSizeRequest(route_id, format)) => {
+                if let Some(context) =
+                    self.router.routes.get_mut(&window_id).and_then(|route| {
+                        route.window.screen.ctx_mut().context_by_route_mut(route_id)
+                    })
+                {
+                    let text = format(crate::renderer::utils::terminal_dimensions(
+                        &context.dimension,
+                    ));
+                    context.messenger.send_bytes(text.into_bytes());
                 }
             }
-            RioEventType::Rio(RioEvent::ColorRequest(index, format)) => {
+            RioEventType::Rio(RioEvent::ColorRequest(route_id, index, format)) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
-                    let terminal = route
-                        .window
-                        .screen
-                        .context_manager
-                        .current()
-                        .terminal
-                        .lock();
+                    let default_color = route.window.screen.renderer.colors[index];
+                    let Some(context) =
+                        route.window.screen.ctx_mut().context_by_route_mut(route_id)
+                    else {
+                        return;
+                    };
+                    let terminal = context.terminal.lock();
                     let color: ColorRgb = match terminal.colors()[index] {
                         Some(color) => ColorRgb::from_color_arr(color),
                         // Ignore cursor color requests unless it was changed.
@@ -455,22 +796,17 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                         {
                             return
                         }
-                        None => ColorRgb::from_color_arr(
-                            route.window.screen.renderer.colors[index],
-                        ),
+                        None => ColorRgb::from_color_arr(default_color),
                     };
 
                     drop(terminal);
 
-                    route
-                        .window
-                        .screen
-                        .ctx_mut()
-                        .current_mut()
-                        .messenger
-                        .send_bytes(format(color).into_bytes());
+                    context.messenger.send_bytes(format(color).into_bytes());
                 }
             }
+            RioEventType::Rio(RioEvent::SelectWindow(next)) => {
+                self.router.select_window(window_id, next);
+            }
             RioEventType::Rio(RioEvent::CreateWindow) => {
                 self.router.create_window(
                     event_loop,
@@ -479,6 +815,24 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     None,
                 );
             }
+            RioEventType::Rio(RioEvent::MoveTabToNewWindow) => {
+                let tab = self
+                    .router
+                    .routes
+                    .get_mut(&window_id)
+                    .and_then(|route| route.window.screen.detach_tab());
+                if let Some(tab) = tab {
+                    self.router.create_window_with_tab(
+                        event_loop,
+                        self.event_proxy.clone(),
+                        &self.config,
+                        tab,
+                    );
+                }
+            }
+            RioEventType::Rio(RioEvent::MergeWindows) => {
+                self.router.merge_windows(window_id);
+            }
             #[cfg(target_os = "macos")]
             RioEventType::Rio(RioEvent::CreateNativeTab(working_dir_overwrite)) => {
                 if let Some(route) = self.router.routes.get(&window_id) {
@@ -516,12 +870,8 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     );
                 }
             }
-            #[cfg(target_os = "macos")]
             RioEventType::Rio(RioEvent::CloseWindow) => {
-                self.router.routes.remove(&window_id);
-                if self.router.routes.is_empty() && !self.config.confirm_before_quit {
-                    event_loop.exit();
-                }
+                self.close_window(event_loop, window_id);
             }
             #[cfg(target_os = "macos")]
             RioEventType::Rio(RioEvent::SelectNativeTabByIndex(tab_index)) => {
@@ -558,11 +908,21 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
             RioEventType::Rio(RioEvent::HideOtherApplications) => {
                 event_loop.hide_other_applications();
             }
+            #[cfg(target_os = "macos")]
+            RioEventType::Rio(RioEvent::HapticFeedback) => {
+                event_loop.perform_haptic_feedback();
+            }
             RioEventType::Rio(RioEvent::Minimize(set_minimize)) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     route.window.winit_window.set_minimized(set_minimize);
                 }
             }
+            RioEventType::Rio(RioEvent::UpdateOpacity(opacity)) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.set_window_opacity(&self.config, opacity);
+                    route.request_redraw();
+                }
+            }
             RioEventType::Rio(RioEvent::ToggleFullScreen) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     match route.window.winit_window.fullscreen() {
@@ -639,28 +999,29 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
 
         match event {
             WindowEvent::CloseRequested => {
-                // MacOS doesn't exit the loop
-                if cfg!(target_os = "macos") && self.config.confirm_before_quit {
-                    self.router.routes.remove(&window_id);
-                    return;
-                }
-
-                if self.config.confirm_before_quit {
-                    route.confirm_quit();
+                let programs = if self.config.confirm_before_quit {
+                    route.window.screen.context_manager.running_programs()
+                } else {
+                    Vec::new()
+                };
+                if needs_quit_confirmation(&self.config, &programs) {
+                    route.confirm_quit(true, programs);
                     route.request_redraw();
                     return;
-                } else {
-                    self.router.routes.remove(&window_id);
                 }
 
-                if self.router.routes.is_empty() {
-                    event_loop.exit();
-                }
+                self.close_window(event_loop, window_id);
             }
 
             WindowEvent::ModifiersChanged(modifiers) => {
                 route.window.screen.set_modifiers(modifiers);
 
+                let was_hovered = route
+                    .window
+                    .screen
+                    .context_manager
+                    .current()
+                    .has_hyperlink_range();
                 if route.window.screen.search_nearest_hyperlink_from_pos() {
                     if self.config.hide_cursor_when_typing {
                         route.window.winit_window.set_cursor_visible(true);
@@ -668,10 +1029,21 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
 
                     route.window.winit_window.set_cursor(CursorIcon::Pointer);
                     route.window.screen.context_manager.request_render();
+                } else if was_hovered {
+                    // The hover modifiers were released
+                    route.window.winit_window.set_cursor(CursorIcon::Text);
+                    route.window.screen.context_manager.request_render();
                 }
             }
 
             WindowEvent::MouseInput { state, button, .. } => {
+                if button == MouseButton::Left
+                    && state == ElementState::Pressed
+                    && route.window.screen.trigger_hovered_link()
+                {
+                    return;
+                }
+
                 if route.path != RoutePath::Terminal {
                     return;
                 }
@@ -702,6 +1074,12 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
 
                 match state {
                     ElementState::Pressed => {
+                        if button == MouseButton::Left
+                            && route.window.screen.trigger_output_indicator()
+                        {
+                            return;
+                        }
+
                         // In case need to switch grid current
                         route.window.screen.select_current_based_on_mouse();
 
@@ -803,6 +1181,19 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.window.winit_window.set_cursor_visible(true);
                 }
 
+                if route
+                    .window
+                    .screen
+                    .update_hovered_link(position.x, position.y)
+                {
+                    route.request_redraw();
+                }
+
+                if route.window.screen.has_hovered_link() {
+                    route.window.winit_window.set_cursor(CursorIcon::Pointer);
+                    return;
+                }
+
                 if route.path != RoutePath::Terminal {
                     route.window.winit_window.set_cursor(CursorIcon::Default);
                     return;
@@ -849,6 +1240,11 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 route.window.screen.mouse.x = x;
                 route.window.screen.mouse.y = y;
 
+                if !lmb_pressed && route.window.screen.is_over_output_indicator() {
+                    route.window.winit_window.set_cursor(CursorIcon::Pointer);
+                    return;
+                }
+
                 let point = route.window.screen.mouse_position(display_offset);
 
                 let square_changed = old_point != point;
@@ -864,6 +1260,12 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     return;
                 }
 
+                let was_hovered = route
+                    .window
+                    .screen
+                    .context_manager
+                    .current()
+                    .has_hyperlink_range();
                 if route.window.screen.search_nearest_hyperlink_from_pos() {
                     route.window.winit_window.set_cursor(CursorIcon::Pointer);
                     route.window.screen.context_manager.request_render();
@@ -880,19 +1282,13 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.window.winit_window.set_cursor(cursor_icon);
 
                     // In case hyperlink range has cleaned trigger one more render
-                    if route
-                        .window
-                        .screen
-                        .context_manager
-                        .current()
-                        .has_hyperlink_range()
-                    {
+                    if was_hovered {
                         route
                             .window
                             .screen
                             .context_manager
                             .current_mut()
-                            .set_hyperlink_range(None);
+                            .set_hovered_hyperlink(None);
                         route.window.screen.context_manager.request_render();
                     }
                 }
@@ -934,6 +1330,22 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.window.winit_window.set_cursor_visible(true);
                 }
 
+                if route.window.screen.is_opacity_gesture() {
+                    let scroll_y = match delta {
+                        MouseScrollDelta::LineDelta(_, lines) => {
+                            let layout =
+                                route.window.screen.sugarloaf.rich_text_layout(&0);
+                            (lines * layout.font_size) as f64
+                        }
+                        MouseScrollDelta::PixelDelta(lpos) => lpos.y,
+                    };
+                    if let Some(opacity) = route.window.screen.scroll_opacity(scroll_y) {
+                        route.set_window_opacity(&self.config, opacity);
+                        route.request_redraw();
+                    }
+                    return;
+                }
+
                 match delta {
                     MouseScrollDelta::LineDelta(columns, lines) => {
                         let layout = route.window.screen.sugarloaf.rich_text_layout(&0);
@@ -989,6 +1401,18 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 route.window.screen.context_manager.set_last_typing();
                 route.window.screen.process_key_event(&key_event);
 
+                // Reading a secret without a prompt prints nothing
+                if key_event.state == ElementState::Pressed
+                    && route
+                        .window
+                        .screen
+                        .context_manager
+                        .current_mut()
+                        .update_password_input()
+                {
+                    route.request_redraw();
+                }
+
                 if key_event.state == ElementState::Released
                     && self.config.hide_cursor_when_typing
                 {
@@ -1001,6 +1425,22 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     return;
                 }
 
+                // Text composed with the input method is typed in the query
+                if route.path == RoutePath::CommandPalette {
+                    if let Ime::Commit(text) = ime {
+                        route.command_palette.push(&text);
+                        route.request_redraw();
+                    }
+                    return;
+                }
+                if route.path == RoutePath::Settings {
+                    if let Ime::Commit(text) = ime {
+                        route.settings.push(&text);
+                        route.request_redraw();
+                    }
+                    return;
+                }
+
                 match ime {
                     Ime::Commit(text) => {
                         // Don't use bracketed paste for single char input.
@@ -1055,21 +1495,42 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.window.winit_window.set_cursor_visible(true);
                 }
 
-                let has_regained_focus = !route.window.is_focused && focused;
+                // The cursor is drawn hollow while the window is unfocused
+                let has_focus_changed = route.window.is_focused != focused;
                 route.window.is_focused = focused;
 
-                if has_regained_focus {
+                if has_focus_changed {
                     route.request_redraw();
                 }
 
                 route.window.screen.on_focus_change(focused);
+
+                #[cfg(target_os = "macos")]
+                if self.secure_keyboard_entry {
+                    self.update_secure_keyboard_entry(event_loop);
+                }
             }
 
             WindowEvent::Occluded(occluded) => {
                 route.window.is_occluded = occluded;
+                // Content may have changed while the window was hidden
+                if !occluded {
+                    route.request_redraw();
+                }
             }
 
             WindowEvent::ThemeChanged(new_theme) => {
+                // The colors using `{accent}` are read again with the config
+                if set_accent_color(platform::accent_color()) {
+                    self.event_proxy
+                        .send_event(RioEventType::Rio(RioEvent::UpdateConfig), window_id);
+                }
+
+                // The system appearance is ignored while following a schedule
+                if self.scheduled_theme.is_some() {
+                    return;
+                }
+
                 update_colors_based_on_theme(&mut self.config, Some(new_theme));
                 route.window.screen.update_config(
                     &self.config,
@@ -1077,6 +1538,11 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     false,
                 );
                 route.window.configure_window(&self.config);
+                route
+                    .window
+                    .screen
+                    .context_manager
+                    .reset_profile(&self.config.profiles);
             }
 
             WindowEvent::DroppedFile(path) => {
@@ -1109,6 +1575,12 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
             }
 
             WindowEvent::RedrawRequested => {
+                // Terminals keep processing the PTY output, only the
+                // render passes are skipped while the window is hidden
+                if route.window.is_hidden() {
+                    return;
+                }
+
                 // let start = std::time::Instant::now();
                 route.window.winit_window.pre_present_notify();
 
@@ -1119,17 +1591,58 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                         route.window.screen.render_assistant(&route.assistant);
                     }
                     RoutePath::Welcome => {
-                        route.window.screen.render_welcome();
+                        route.window.screen.render_welcome(&route.settings);
                     }
                     RoutePath::Terminal => {
                         route.window.screen.render();
+                        route.window.update_ime_cursor_area();
+                        #[cfg(any(target_os = "macos", windows))]
+                        route.window.update_accessibility_caret();
+
+                        // Animated graphics ask for a render when their next
+                        // frame is due, instead of rendering continuously
+                        if let Some(deadline) =
+                            route.window.screen.renderer.next_animation_frame()
+                        {
+                            let timer_id = TimerId::new(
+                                Topic::Animation,
+                                route.window.screen.ctx().current_route(),
+                            );
+                            self.scheduler.unschedule(timer_id);
+                            self.scheduler.schedule(
+                                EventPayload::new(
+                                    RioEventType::Rio(RioEvent::Render),
+                                    window_id,
+                                ),
+                                deadline.saturating_duration_since(Instant::now()),
+                                false,
+                                timer_id,
+                            );
+                        }
                     }
                     RoutePath::ConfirmQuit => {
-                        route.window.screen.render_dialog(
-                            "Quit Rio?",
-                            "Continue -> press escape key",
-                            "Quit -> press enter key",
-                        );
+                        route.window.screen.render_confirm_quit(&route.pending_quit);
+                    }
+                    RoutePath::ConfirmPaste => {
+                        route.window.screen.render_confirm_paste();
+                    }
+                    RoutePath::ConfirmClipboardRequest => {
+                        route.window.screen.render_confirm_clipboard_request();
+                    }
+                    RoutePath::ClipboardHistory => {
+                        route
+                            .window
+                            .screen
+                            .render_clipboard_history(&route.clipboard_history);
+                    }
+                    RoutePath::CommandPalette => {
+                        route
+                            .window
+                            .screen
+                            .render_command_palette(&route.command_palette);
+                    }
+                    RoutePath::Settings => {
+                        route.window.screen.render_settings(&route.settings);
                     }
                 }
 
@@ -1138,7 +1651,26 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 // }
 
                 if self.config.renderer.strategy.is_game() {
-                    route.request_redraw();
+                    if route.window.is_frame_rate_capped() {
+                        // Throttle continuous rendering to the capped frame rate
+                        let timer_id = TimerId::new(
+                            Topic::Render,
+                            route.window.screen.ctx().current_route(),
+                        );
+                        if !self.scheduler.scheduled(timer_id) {
+                            self.scheduler.schedule(
+                                EventPayload::new(
+                                    RioEventType::Rio(RioEvent::Render),
+                                    window_id,
+                                ),
+                                route.window.vblank_interval,
+                                false,
+                                timer_id,
+                            );
+                        }
+                    } else {
+                        route.request_redraw();
+                    }
                 }
 
                 event_loop.set_control_flow(ControlFlow::Wait);
@@ -1171,11 +1703,27 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
     }
 
     fn hook_event(&mut self, _event_loop: &ActiveEventLoop, hook: &Hook) {
+        if hook == &Hook::ToggleSecureKeyboardEntry {
+            #[cfg(target_os = "macos")]
+            {
+                self.secure_keyboard_entry = !self.secure_keyboard_entry;
+                self.update_secure_keyboard_entry(_event_loop);
+            }
+
+            return;
+        }
+
         let window_id = match self.router.get_focused_route() {
             Some(window_id) => window_id,
             None => return,
         };
 
+        if let Hook::NextWindow | Hook::PreviousWindow = hook {
+            self.router
+                .select_window(window_id, hook == &Hook::NextWindow);
+            return;
+        }
+
         let route = match self.router.routes.get_mut(&window_id) {
             Some(window) => window,
             None => return,
@@ -1185,6 +1733,12 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
             Hook::Copy => {
                 route.window.screen.copy_selection(ClipboardType::Clipboard);
             }
+            Hook::CopyLink => {
+                route.window.screen.copy_selection_links();
+            }
+            Hook::CopyFormatted => {
+                route.window.screen.copy_selection_formatted();
+            }
             Hook::Paste => {
                 let content = route
                     .window
@@ -1194,6 +1748,15 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     .get(ClipboardType::Selection);
                 route.window.screen.paste(&content, true);
             }
+            Hook::SelectAll => {
+                route.window.screen.select_all();
+                route.request_redraw();
+            }
+            Hook::SelectLastCommandOutput => {
+                if route.window.screen.select_last_command_output() {
+                    route.request_redraw();
+                }
+            }
             Hook::CreateTab => {
                 if self.config.navigation.has_navigation_key_bindings() {
                     route.window.screen.create_tab();
@@ -1212,6 +1775,8 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.window.screen.split_right();
                 }
             }
+            Hook::NextWindow | Hook::PreviousWindow | Hook::ToggleSecureKeyboardEntry => {
+            }
         }
     }
 
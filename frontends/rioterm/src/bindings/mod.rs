@@ -3,11 +3,15 @@
 // which is licensed under Apache 2.0 license.
 
 pub mod kitty_keyboard;
+pub mod modify_other_keys;
+pub mod win32_input;
 
+use crate::crosswords::export::ExportFormat;
 use crate::crosswords::vi_mode::ViMotion;
 use crate::crosswords::Mode;
 use bitflags::bitflags;
-use rio_backend::config::bindings::KeyBinding as ConfigKeyBinding;
+use rio_backend::config::bindings::{BindingCommand, KeyBinding as ConfigKeyBinding};
+use rio_backend::config::hints::HintAction;
 use rio_backend::config::keyboard::Keyboard as ConfigKeyboard;
 use rio_window::event::MouseButton;
 use rio_window::keyboard::Key::*;
@@ -16,6 +20,9 @@ use rio_window::keyboard::{Key, KeyLocation, ModifiersState, PhysicalKey};
 use std::fmt::Debug;
 // use rio_window::platform::scancode::PhysicalKeyExtScancode;
 
+/// Lines used by the recent output actions when no count is given.
+const DEFAULT_RECENT_OUTPUT_LINES: usize = 10;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FontSizeAction {
     Increase,
@@ -56,6 +63,10 @@ pub enum SearchAction {
     SearchHistoryPrevious,
     /// Go to the next regex in the search history.
     SearchHistoryNext,
+    /// Toggle between smart case and case sensitive search.
+    SearchToggleSmartCase,
+    /// Toggle matching whole words only.
+    SearchToggleWholeWord,
 }
 
 impl From<SearchAction> for Action {
@@ -82,9 +93,19 @@ pub struct Binding<T> {
     ///
     /// For example, this might be a key like "G", or a mouse button.
     pub trigger: T,
+
+    /// Keys that have to be pressed before the trigger, for multi-step
+    /// bindings like `control+a > c`.
+    pub leader: Vec<KeyStep>,
 }
 
 impl<T: Eq> Binding<T> {
+    /// Whether the binding can be triggered in the mode.
+    #[inline]
+    pub fn is_active_in(&self, mode: &BindingMode) -> bool {
+        mode.contains(self.mode.clone()) && !mode.intersects(self.notmode.clone())
+    }
+
     #[inline]
     pub fn is_triggered_by(
         &self,
@@ -95,16 +116,16 @@ impl<T: Eq> Binding<T> {
         // Check input first since bindings are stored in one big list. This is
         // the most likely item to fail so prioritizing it here allows more
         // checks to be short circuited.
-        self.trigger == *input
-            && self.mods == mods
-            && mode.contains(self.mode.clone())
-            && !mode.intersects(self.notmode.clone())
+        self.trigger == *input && self.mods == mods && self.is_active_in(&mode)
     }
 
     #[inline]
     pub fn triggers_match(&self, binding: &Binding<T>) -> bool {
         // Check the binding's key and modifiers.
-        if self.trigger != binding.trigger || self.mods != binding.mods {
+        if self.trigger != binding.trigger
+            || self.mods != binding.mods
+            || self.leader != binding.leader
+        {
             return false;
         }
 
@@ -148,6 +169,43 @@ pub enum BindingKey {
 pub type KeyBinding = Binding<BindingKey>;
 pub type KeyBindings = Vec<KeyBinding>;
 
+/// Key and modifiers of one step of a multi-step binding.
+pub type KeyStep = (BindingKey, ModifiersState);
+
+/// Text of the steps shown while waiting for the next key, e.g. `ctrl+a`.
+pub fn format_key_steps(steps: &[KeyStep]) -> String {
+    let steps: Vec<String> = steps
+        .iter()
+        .map(|(trigger, mods)| {
+            let mut text = String::new();
+            for (modifier, name) in [
+                (ModifiersState::SUPER, "super+"),
+                (ModifiersState::CONTROL, "ctrl+"),
+                (ModifiersState::ALT, "alt+"),
+                (ModifiersState::SHIFT, "shift+"),
+            ] {
+                if mods.contains(modifier) {
+                    text.push_str(name);
+                }
+            }
+
+            match trigger {
+                BindingKey::Keycode {
+                    key: Key::Character(c),
+                    ..
+                } => text.push_str(c),
+                BindingKey::Keycode {
+                    key: Key::Named(named),
+                    ..
+                } => text.push_str(&format!("{named:?}").to_lowercase()),
+                _ => text.push('?'),
+            }
+            text
+        })
+        .collect();
+    steps.join(" > ")
+}
+
 /// Bindings that are triggered by a mouse button.
 pub type MouseBinding = Binding<MouseButton>;
 
@@ -214,8 +272,20 @@ impl From<String> for Action {
 
         let action_from_string = match action.as_str() {
             "paste" => Some(Action::Paste),
+            "clipboardhistory" => Some(Action::ClipboardHistory),
+            "commandpalette" => Some(Action::CommandPalette),
             "quit" => Some(Action::Quit),
             "copy" => Some(Action::Copy),
+            "copylink" => Some(Action::CopyLink),
+            "copyformatted" => Some(Action::CopyFormatted),
+            "selectall" => Some(Action::SelectAll),
+            "selectlastcommandoutput" => Some(Action::SelectLastCommandOutput),
+            "copyrecentoutput" => {
+                Some(Action::CopyRecentOutput(DEFAULT_RECENT_OUTPUT_LINES))
+            }
+            "speakrecentoutput" => {
+                Some(Action::SpeakRecentOutput(DEFAULT_RECENT_OUTPUT_LINES))
+            }
             "searchforward" => Some(Action::SearchForward),
             "searchbackward" => Some(Action::SearchBackward),
             "searchconfirm" => Some(Action::Search(SearchAction::SearchConfirm)),
@@ -230,18 +300,29 @@ impl From<String> for Action {
             "searchhistoryprevious" => {
                 Some(Action::Search(SearchAction::SearchHistoryPrevious))
             }
+            "searchtogglesmartcase" => {
+                Some(Action::Search(SearchAction::SearchToggleSmartCase))
+            }
+            "searchtogglewholeword" => {
+                Some(Action::Search(SearchAction::SearchToggleWholeWord))
+            }
             "clearhistory" => Some(Action::ClearHistory),
             "resetfontsize" => Some(Action::ResetFontSize),
             "increasefontsize" => Some(Action::IncreaseFontSize),
             "decreasefontsize" => Some(Action::DecreaseFontSize),
             "createwindow" => Some(Action::WindowCreateNew),
+            "nextwindow" => Some(Action::NextWindow),
+            "previouswindow" => Some(Action::PreviousWindow),
             "createtab" => Some(Action::TabCreateNew),
             "movecurrenttabtoprev" => Some(Action::MoveCurrentTabToPrev),
             "movecurrenttabtonext" => Some(Action::MoveCurrentTabToNext),
+            "movecurrenttabtonewwindow" => Some(Action::MoveCurrentTabToNewWindow),
+            "mergewindows" => Some(Action::MergeWindows),
             "closetab" => Some(Action::TabCloseCurrent),
             "closesplitortab" => Some(Action::CloseCurrentSplitOrTab),
             "closeunfocusedtabs" => Some(Action::TabCloseUnfocused),
             "openconfigeditor" => Some(Action::ConfigEditor),
+            "opensettings" => Some(Action::OpenSettings),
             "selectprevtab" => Some(Action::SelectPrevTab),
             "selectnexttab" => Some(Action::SelectNextTab),
             "selectlasttab" => Some(Action::SelectLastTab),
@@ -258,6 +339,19 @@ impl From<String> for Action {
             "selectprevsplitortab" => Some(Action::SelectPrevSplitOrTab),
             "togglevimode" => Some(Action::ToggleViMode),
             "togglefullscreen" => Some(Action::ToggleFullscreen),
+            "increaseopacity" => Some(Action::IncreaseOpacity),
+            "decreaseopacity" => Some(Action::DecreaseOpacity),
+            "toggleopacity" => Some(Action::ToggleOpacity),
+            "toggledebugoverlay" => Some(Action::ToggleDebugOverlay),
+            "dumpmetrics" => Some(Action::DumpMetrics),
+            "togglecontrolpictures" => Some(Action::ToggleControlPictures),
+            "togglelatin1fallback" => Some(Action::ToggleLatin1Fallback),
+            "hintopen" => Some(Action::Hint(HintAction::Open)),
+            "hintcopy" => Some(Action::Hint(HintAction::Copy)),
+            "hintpaste" => Some(Action::Hint(HintAction::Paste)),
+            "screenshot" => Some(Action::Screenshot),
+            "exportscreen" => Some(Action::ExportScreen(ExportFormat::Ansi)),
+            "exportscrollback" => Some(Action::ExportScrollback(ExportFormat::Ansi)),
             "none" => Some(Action::None),
             _ => None,
         };
@@ -299,6 +393,36 @@ impl From<String> for Action {
             }
         }
 
+        let re = regex::Regex::new(r"(copy|speak)recentoutput\(([^()]+)\)").unwrap();
+        for capture in re.captures_iter(&action) {
+            if let (Some(kind), Some(matched)) = (capture.get(1), capture.get(2)) {
+                let lines: usize = matched
+                    .as_str()
+                    .parse()
+                    .unwrap_or(DEFAULT_RECENT_OUTPUT_LINES);
+                return match kind.as_str() {
+                    "copy" => Action::CopyRecentOutput(lines),
+                    _ => Action::SpeakRecentOutput(lines),
+                };
+            }
+        }
+
+        let re =
+            regex::Regex::new(r"export(screen|scrollback)\((ansi|html|rtf)\)").unwrap();
+        for capture in re.captures_iter(&action) {
+            if let (Some(range), Some(format)) = (capture.get(1), capture.get(2)) {
+                let format = match format.as_str() {
+                    "html" => ExportFormat::Html,
+                    "rtf" => ExportFormat::Rtf,
+                    _ => ExportFormat::Ansi,
+                };
+                return match range.as_str() {
+                    "screen" => Action::ExportScreen(format),
+                    _ => Action::ExportScrollback(format),
+                };
+            }
+        }
+
         let re = regex::Regex::new(r"scroll\(([^()]+)\)").unwrap();
         for capture in re.captures_iter(&action) {
             if let Some(matched) = capture.get(1) {
@@ -320,11 +444,14 @@ pub enum Action {
     /// Run given command.
     Run(Program),
 
+    /// Run the program of the binding, in the background or a new tab.
+    RunCommand(BindingCommand),
+
     /// Scroll
     Scroll(i32),
 
-    // Regex keyboard hints.
-    // Hint(Hint),
+    /// Label the links and paths on the screen, typing a label picks it.
+    Hint(HintAction),
 
     // Move vi mode cursor.
     ViMotion(ViMotion),
@@ -337,9 +464,24 @@ pub enum Action {
     /// Paste contents of system clipboard.
     Paste,
 
+    /// Pick a text copied before to paste it.
+    ClipboardHistory,
+
+    /// Search the actions by name to run one.
+    CommandPalette,
+
+    /// Change common options in the settings screen.
+    OpenSettings,
+
     /// Store current selection into clipboard.
     Copy,
 
+    /// Store the targets of the hyperlinks in the selection into clipboard.
+    CopyLink,
+
+    /// Store current selection into clipboard with its colors and styles.
+    CopyFormatted,
+
     #[cfg(not(any(target_os = "macos", windows)))]
     #[allow(dead_code)]
     /// Store current selection into selection buffer.
@@ -405,6 +547,12 @@ pub enum Action {
     #[allow(dead_code)]
     WindowCreateNew,
 
+    /// Focus the next Rio window.
+    NextWindow,
+
+    /// Focus the previous Rio window.
+    PreviousWindow,
+
     /// Create config editor.
     ConfigEditor,
 
@@ -417,6 +565,12 @@ pub enum Action {
     /// Move current tab to next slot.
     MoveCurrentTabToNext,
 
+    /// Move current tab to a new window, keeping its terminals running.
+    MoveCurrentTabToNewWindow,
+
+    /// Move the tabs of every other window to the current one.
+    MergeWindows,
+
     /// Switch to next tab.
     SelectNextTab,
 
@@ -439,6 +593,15 @@ pub enum Action {
     #[allow(dead_code)]
     ToggleMaximized,
 
+    /// Make the window background more opaque.
+    IncreaseOpacity,
+
+    /// Make the window background more transparent.
+    DecreaseOpacity,
+
+    /// Switch between an opaque background and the configured opacity.
+    ToggleOpacity,
+
     /// Toggle simple fullscreen on macOS.
     #[cfg(target_os = "macos")]
     #[allow(dead_code)]
@@ -447,9 +610,43 @@ pub enum Action {
     /// Clear active selection.
     ClearSelection,
 
+    /// Select the whole scrollback history and screen.
+    SelectAll,
+
+    /// Select the output of the last command, requires the shell to emit
+    /// semantic prompt marks (OSC 133).
+    SelectLastCommandOutput,
+
+    /// Copy the last lines of output to the clipboard.
+    CopyRecentOutput(usize),
+
+    /// Read the last lines of output with the system text to speech.
+    SpeakRecentOutput(usize),
+
     /// Toggle vi mode.
     ToggleViMode,
 
+    /// Toggle the overlay with frame time, glyph cache and PTY statistics.
+    ToggleDebugOverlay,
+
+    /// Write the cache hit rates and rebuilds recorded per minute to the log directory.
+    DumpMetrics,
+
+    /// Toggle showing C0 control characters as control pictures.
+    ToggleControlPictures,
+
+    /// Toggle decoding the output which isn't valid UTF-8 as Latin-1.
+    ToggleLatin1Fallback,
+
+    /// Save the current frame as a PNG.
+    Screenshot,
+
+    /// Save the visible screen with its styling.
+    ExportScreen(ExportFormat),
+
+    /// Save the scrollback history and screen with its styling.
+    ExportScrollback(ExportFormat),
+
     // Tab selections
     SelectTab(usize),
     SelectLastTab,
@@ -517,6 +714,18 @@ pub enum ViAction {
     ToggleSemanticSelection,
     /// Centers the screen around the vi mode cursor.
     CenterAroundViCursor,
+    /// Search forward within the current line.
+    InlineSearchForward,
+    /// Search backward within the current line.
+    InlineSearchBackward,
+    /// Search forward within the current line, stopping just short of the character.
+    InlineSearchForwardShort,
+    /// Search backward within the current line, stopping just short of the character.
+    InlineSearchBackwardShort,
+    /// Jump to the next inline search match.
+    InlineSearchNext,
+    /// Jump to the previous inline search match.
+    InlineSearchPrevious,
 }
 
 macro_rules! bindings {
@@ -548,6 +757,7 @@ macro_rules! bindings {
                 mode: _mode,
                 notmode: _notmode,
                 action: $action.into(),
+                leader: Vec::new(),
             });
         )*
 
@@ -621,6 +831,7 @@ pub fn default_key_bindings(
         // VI Mode
         Key::Named(Space), ModifiersState::ALT | ModifiersState::SHIFT; Action::ToggleViMode;
         "/", +BindingMode::VI, ~BindingMode::SEARCH; Action::SearchForward;
+        "/", ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH; Action::SearchBackward;
         "n", +BindingMode::VI, ~BindingMode::SEARCH; SearchAction::SearchFocusNext;
         "n",  ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH; SearchAction::SearchFocusPrevious;
         Key::Named(Enter), +BindingMode::SEARCH, ~BindingMode::VI; SearchAction::SearchFocusNext;
@@ -646,6 +857,12 @@ pub fn default_key_bindings(
         "v", ModifiersState::CONTROL, +BindingMode::VI, ~BindingMode::SEARCH; ViAction::ToggleBlockSelection;
         "v", ModifiersState::ALT, +BindingMode::VI, ~BindingMode::SEARCH; ViAction::ToggleSemanticSelection;
         "z", +BindingMode::VI, ~BindingMode::SEARCH; ViAction::CenterAroundViCursor;
+        "f", +BindingMode::VI, ~BindingMode::SEARCH; ViAction::InlineSearchForward;
+        "f", ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH; ViAction::InlineSearchBackward;
+        "t", +BindingMode::VI, ~BindingMode::SEARCH; ViAction::InlineSearchForwardShort;
+        "t", ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH; ViAction::InlineSearchBackwardShort;
+        ";", +BindingMode::VI, ~BindingMode::SEARCH; ViAction::InlineSearchNext;
+        ",", +BindingMode::VI, ~BindingMode::SEARCH; ViAction::InlineSearchPrevious;
         "k", +BindingMode::VI, ~BindingMode::SEARCH; ViMotion::Up;
         "j", +BindingMode::VI, ~BindingMode::SEARCH; ViMotion::Down;
         "h", +BindingMode::VI, ~BindingMode::SEARCH; ViMotion::Left;
@@ -722,15 +939,44 @@ pub struct ModeWrapper {
     pub not_mode: BindingMode,
 }
 
-#[inline]
-fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
-    let (key, location) = if config_key_binding.key.chars().count() == 1 {
+/// Modifiers of a `"super | shift"` list, `"none"` is no modifier.
+pub fn modifiers_from_config(modifiers: &str) -> ModifiersState {
+    let mut res = ModifiersState::empty();
+    for modifier in modifiers.split('|') {
+        match modifier.trim().to_lowercase().as_str() {
+            "command" | "super" => res.insert(ModifiersState::SUPER),
+            "shift" => res.insert(ModifiersState::SHIFT),
+            "alt" | "option" => res.insert(ModifiersState::ALT),
+            "control" | "ctrl" => res.insert(ModifiersState::CONTROL),
+            "none" => (),
+            _ => (),
+        }
+    }
+    res
+}
+
+/// Parses a step of the `leader` of a binding, like `control+a`, the last
+/// part is the key and the others are modifiers.
+fn key_step_from_config(step: &str) -> Result<KeyStep, String> {
+    let step = step.trim();
+    // `+` can be bound too, e.g. `control++`
+    let (mods, key) = match step.strip_suffix("++") {
+        Some(mods) => (mods, "+"),
+        None => step.rsplit_once('+').unwrap_or(("", step)),
+    };
+    let mods = modifiers_from_config(&mods.replace('+', "|"));
+    let (key, location) = key_from_config(key.trim())?;
+    Ok((BindingKey::Keycode { key, location }, mods))
+}
+
+fn key_from_config(config_key: &str) -> Result<(Key, KeyLocation), String> {
+    let key_and_location = if config_key.chars().count() == 1 {
         (
-            Key::Character(config_key_binding.key.to_lowercase().into()),
+            Key::Character(config_key.to_lowercase().into()),
             KeyLocation::Standard,
         )
     } else {
-        match config_key_binding.key.to_lowercase().as_str() {
+        match config_key.to_lowercase().as_str() {
             "home" => (Key::Named(Home), KeyLocation::Standard),
             "space" => (Key::Named(Space), KeyLocation::Standard),
             "delete" => (Key::Named(Delete), KeyLocation::Standard),
@@ -796,21 +1042,23 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
             _ => return Err("Unable to find defined 'keycode'".to_string()),
         }
     };
+    Ok(key_and_location)
+}
 
+#[inline]
+fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
+    let (key, location) = key_from_config(&config_key_binding.key)?;
     let trigger = BindingKey::Keycode { key, location };
 
-    let mut res = ModifiersState::empty();
-    for modifier in config_key_binding.with.split('|') {
-        match modifier.trim().to_lowercase().as_str() {
-            "command" | "super" => res.insert(ModifiersState::SUPER),
-            "shift" => res.insert(ModifiersState::SHIFT),
-            "alt" | "option" => res.insert(ModifiersState::ALT),
-            "control" => res.insert(ModifiersState::CONTROL),
-            "none" => (),
-            _ => (),
+    let mut leader = Vec::new();
+    if !config_key_binding.leader.trim().is_empty() {
+        for step in config_key_binding.leader.split('>') {
+            leader.push(key_step_from_config(step)?);
         }
     }
 
+    let res = modifiers_from_config(&config_key_binding.with);
+
     let mut action: Action = config_key_binding.action.into();
     if !config_key_binding.text.is_empty() {
         action = Action::Esc(config_key_binding.text);
@@ -822,6 +1070,10 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         }
     }
 
+    if let Some(command) = config_key_binding.command {
+        action = Action::RunCommand(command);
+    }
+
     let mut res_mode = ModeWrapper {
         mode: BindingMode::empty(),
         not_mode: BindingMode::empty(),
@@ -850,6 +1102,7 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         action,
         mode: res_mode.mode,
         notmode: res_mode.not_mode,
+        leader,
     })
 }
 
@@ -920,9 +1173,15 @@ pub fn platform_key_bindings(
             Action::Esc("\x0c".into());
         "k", ModifiersState::SUPER, ~BindingMode::VI;  Action::ClearHistory;
         "v", ModifiersState::SUPER, ~BindingMode::VI; Action::Paste;
+        "v", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::VI; Action::ClipboardHistory;
+        "p", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::CommandPalette;
         "f", ModifiersState::CONTROL | ModifiersState::SUPER; Action::ToggleFullscreen;
         "c", ModifiersState::SUPER; Action::Copy;
         "c", ModifiersState::SUPER, +BindingMode::VI; Action::ClearSelection;
+        "a", ModifiersState::SUPER, ~BindingMode::SEARCH; Action::SelectAll;
+        "a", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::SelectLastCommandOutput;
+        "e", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::Hint(HintAction::Open);
+        "y", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::Hint(HintAction::Copy);
         "h", ModifiersState::SUPER; Action::Hide;
         "h", ModifiersState::SUPER | ModifiersState::ALT; Action::HideOtherApplications;
         "m", ModifiersState::SUPER; Action::Minimize;
@@ -940,6 +1199,8 @@ pub fn platform_key_bindings(
         "n", ModifiersState::CONTROL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Key::Named(ArrowUp), +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Key::Named(ArrowDown), +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        "c", ModifiersState::SUPER | ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleSmartCase;
+        "w", ModifiersState::SUPER | ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
     );
 
     if use_navigation_key_bindings {
@@ -996,9 +1257,15 @@ pub fn platform_key_bindings(
     let mut key_bindings = bindings!(
         KeyBinding;
         "v", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::Paste;
+        "h", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::ClipboardHistory;
+        "p", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::CommandPalette;
         "c", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::Copy;
         "c", ModifiersState::CONTROL | ModifiersState::SHIFT,
             +BindingMode::VI; Action::ClearSelection;
+        "a", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::SelectAll;
+        "o", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::SelectLastCommandOutput;
+        "e", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::Hint(HintAction::Open);
+        "y", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::Hint(HintAction::Copy);
         Key::Named(Insert),   ModifiersState::SHIFT, ~BindingMode::VI; Action::PasteSelection;
         "0", ModifiersState::CONTROL;  Action::ResetFontSize;
         "=", ModifiersState::CONTROL;  Action::IncreaseFontSize;
@@ -1019,6 +1286,8 @@ pub fn platform_key_bindings(
         "n", ModifiersState::CONTROL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Key::Named(ArrowUp), +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Key::Named(ArrowDown), +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        "c", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleSmartCase;
+        "w", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
     );
 
     if use_navigation_key_bindings {
@@ -1056,8 +1325,14 @@ pub fn platform_key_bindings(
     let mut key_bindings = bindings!(
         KeyBinding;
         "v", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::Paste;
+        "h", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::ClipboardHistory;
+        "p", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::CommandPalette;
         "c", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::Copy;
         "c", ModifiersState::CONTROL | ModifiersState::SHIFT, +BindingMode::VI; Action::ClearSelection;
+        "a", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::SelectAll;
+        "o", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::SelectLastCommandOutput;
+        "e", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::Hint(HintAction::Open);
+        "y", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::Hint(HintAction::Copy);
         Key::Named(Insert), ModifiersState::SHIFT, ~BindingMode::VI; Action::PasteSelection;
         "0", ModifiersState::CONTROL; Action::ResetFontSize;
         "=", ModifiersState::CONTROL; Action::IncreaseFontSize;
@@ -1084,6 +1359,8 @@ pub fn platform_key_bindings(
         "n", ModifiersState::CONTROL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Key::Named(ArrowUp), +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Key::Named(ArrowDown), +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        "c", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleSmartCase;
+        "w", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
     );
 
     if use_navigation_key_bindings {
@@ -1132,6 +1409,7 @@ mod tests {
                 mode: BindingMode::empty(),
                 notmode: BindingMode::empty(),
                 trigger: Default::default(),
+                leader: Vec::new(),
             }
         }
     }
@@ -1385,6 +1663,8 @@ mod tests {
             bytes: vec![],
             text: String::from(""),
             mode: String::from(""),
+            leader: String::from(""),
+            command: None,
         }];
 
         let new_bindings = config_key_bindings(config_bindings, bindings);
@@ -1392,4 +1672,48 @@ mod tests {
         assert_eq!(new_bindings.len(), 2);
         assert_eq!(new_bindings[1].action, Action::ReceiveChar);
     }
+
+    #[test]
+    fn leader_bindings() {
+        let config_bindings = vec![ConfigKeyBinding {
+            key: String::from("c"),
+            action: String::from("tabcreatenew"),
+            with: String::from(""),
+            bytes: vec![],
+            text: String::from(""),
+            mode: String::from(""),
+            leader: String::from("ctrl+a > shift+g"),
+            command: None,
+        }];
+
+        let bindings = config_key_bindings(config_bindings, vec![]);
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(
+            bindings[0].leader,
+            vec![
+                (
+                    BindingKey::Keycode {
+                        key: Key::Character("a".into()),
+                        location: KeyLocation::Standard,
+                    },
+                    ModifiersState::CONTROL
+                ),
+                (
+                    BindingKey::Keycode {
+                        key: Key::Character("g".into()),
+                        location: KeyLocation::Standard,
+                    },
+                    ModifiersState::SHIFT
+                ),
+            ]
+        );
+        assert_eq!(format_key_steps(&bindings[0].leader), "ctrl+a > shift+g");
+
+        // A binding with the same key but without the leader is kept
+        let plain = bindings!(
+            KeyBinding;
+            "c", ModifiersState::empty(); Action::Copy;
+        );
+        assert!(!plain[0].triggers_match(&bindings[0]));
+    }
 }
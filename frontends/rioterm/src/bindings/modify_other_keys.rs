@@ -0,0 +1,139 @@
+// Encoding of the modified keys when xterm's modifyOtherKeys is enabled,
+// the kitty keyboard protocol takes precedence when both are requested.
+//
+// https://invisible-island.net/xterm/modified-keys.html
+
+use rio_backend::crosswords::Mode;
+use rio_window::event::KeyEvent;
+use rio_window::keyboard::{Key, ModifiersState, NamedKey};
+
+/// Builds `CSI 27 ; modifiers ; code ~` for the key, `None` when the key
+/// should be sent as usual.
+pub fn build_sequence(
+    key: &KeyEvent,
+    mods: ModifiersState,
+    mode: Mode,
+) -> Option<Vec<u8>> {
+    if mode.intersects(Mode::KITTY_KEYBOARD_PROTOCOL)
+        || !mode.intersects(Mode::MODIFY_OTHER_KEYS | Mode::MODIFY_OTHER_KEYS_ALL)
+    {
+        return None;
+    }
+
+    let encode_all = mode.contains(Mode::MODIFY_OTHER_KEYS_ALL);
+    let code = key_code(key.logical_key.as_ref(), mods, encode_all)?;
+    Some(format!("\x1b[27;{};{code}~", encode_modifiers(mods)).into_bytes())
+}
+
+/// Code reported for the key, with `encode_all` unset the keys with a
+/// well-known encoding, like `ctrl` + letter or `alt` + key, are left out.
+fn key_code(key: Key<&str>, mods: ModifiersState, encode_all: bool) -> Option<u32> {
+    if mods.is_empty() {
+        return None;
+    }
+
+    match key {
+        Key::Named(named) if encode_all => match named {
+            NamedKey::Tab => Some(9),
+            NamedKey::Enter => Some(13),
+            NamedKey::Escape => Some(27),
+            NamedKey::Space => Some(32),
+            NamedKey::Backspace => Some(127),
+            _ => None,
+        },
+        Key::Character(text) => {
+            let mut chars = text.chars();
+            let character = chars.next()?;
+            if chars.next().is_some() || mods == ModifiersState::SHIFT {
+                return None;
+            }
+
+            let is_well_defined = !mods.control_key() || has_control_code(character);
+            if !encode_all && is_well_defined {
+                return None;
+            }
+
+            Some(u32::from(character))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `ctrl` with the character produces a C0 control code.
+#[inline]
+fn has_control_code(character: char) -> bool {
+    character.is_ascii_lowercase()
+        || matches!(character, '@' | '[' | '\\' | ']' | '^' | '_' | '?' | ' ')
+}
+
+/// Same value as the modifiers parameter of the xterm function keys.
+#[inline]
+fn encode_modifiers(mods: ModifiersState) -> u8 {
+    let mut value = 1;
+    if mods.shift_key() {
+        value += 1;
+    }
+    if mods.alt_key() {
+        value += 2;
+    }
+    if mods.control_key() {
+        value += 4;
+    }
+    if mods.super_key() {
+        value += 8;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL: ModifiersState = ModifiersState::CONTROL;
+    const CTRL_SHIFT: ModifiersState =
+        ModifiersState::CONTROL.union(ModifiersState::SHIFT);
+
+    #[test]
+    fn test_key_code_except_well_defined() {
+        assert_eq!(key_code(Key::Character("a"), CTRL, false), None);
+        assert_eq!(key_code(Key::Character("A"), CTRL_SHIFT, false), Some(65));
+        assert_eq!(key_code(Key::Character("1"), CTRL, false), Some(49));
+        assert_eq!(key_code(Key::Character(";"), CTRL, false), Some(59));
+        assert_eq!(
+            key_code(Key::Character("a"), ModifiersState::ALT, false),
+            None
+        );
+        assert_eq!(key_code(Key::Named(NamedKey::Enter), CTRL, false), None);
+        assert_eq!(
+            key_code(Key::Character("a"), ModifiersState::empty(), false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_key_code_all() {
+        assert_eq!(key_code(Key::Character("a"), CTRL, true), Some(97));
+        assert_eq!(
+            key_code(Key::Character("a"), ModifiersState::ALT, true),
+            Some(97)
+        );
+        assert_eq!(key_code(Key::Named(NamedKey::Enter), CTRL, true), Some(13));
+        assert_eq!(
+            key_code(Key::Named(NamedKey::Tab), ModifiersState::SHIFT, true),
+            Some(9)
+        );
+        // Shifted characters are sent as text
+        assert_eq!(
+            key_code(Key::Character("A"), ModifiersState::SHIFT, true),
+            None
+        );
+        assert_eq!(key_code(Key::Named(NamedKey::ArrowUp), CTRL, true), None);
+    }
+
+    #[test]
+    fn test_encode_modifiers() {
+        assert_eq!(encode_modifiers(ModifiersState::SHIFT), 2);
+        assert_eq!(encode_modifiers(CTRL), 5);
+        assert_eq!(encode_modifiers(CTRL_SHIFT | ModifiersState::ALT), 8);
+    }
+}
@@ -0,0 +1,321 @@
+// Encoding of the key events when win32-input-mode (DECSET 9001) is
+// enabled, ConPTY requests it to receive the key releases and the
+// modifier combinations that can't be expressed as VT sequences.
+//
+// https://github.com/microsoft/terminal/blob/main/doc/specs/%234999%20-%20Improved%20keyboard%20handling%20in%20Conpty.md
+
+use rio_window::event::{ElementState, KeyEvent, Modifiers};
+use rio_window::keyboard::{KeyCode, ModifiersKeyState, PhysicalKey};
+use rio_window::platform::modifier_supplement::KeyEventExtModifierSupplement;
+
+// Flags of the `dwControlKeyState` field of `KEY_EVENT_RECORD`.
+const RIGHT_ALT_PRESSED: u16 = 0x0001;
+const LEFT_ALT_PRESSED: u16 = 0x0002;
+const RIGHT_CTRL_PRESSED: u16 = 0x0004;
+const LEFT_CTRL_PRESSED: u16 = 0x0008;
+const SHIFT_PRESSED: u16 = 0x0010;
+const ENHANCED_KEY: u16 = 0x0100;
+
+/// Builds `CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _` for the key press or
+/// release, `None` when the key has no virtual key code.
+pub fn build_sequence(key: &KeyEvent, modifiers: &Modifiers) -> Option<Vec<u8>> {
+    let PhysicalKey::Code(code) = key.physical_key else {
+        return None;
+    };
+    let virtual_key = virtual_key_code(code)?;
+    let is_pressed = key.state == ElementState::Pressed;
+
+    let character = key
+        .text_with_all_modifiers()
+        .and_then(|text| text.chars().next())
+        .map_or(0, u32::from);
+
+    let sequence = encode(
+        virtual_key,
+        scan_code(key.physical_key),
+        character,
+        is_pressed,
+        control_key_state(code, modifiers, is_pressed),
+    );
+    Some(sequence.into_bytes())
+}
+
+#[inline]
+fn encode(
+    virtual_key: u16,
+    scan_code: u16,
+    character: u32,
+    is_pressed: bool,
+    control_key_state: u16,
+) -> String {
+    format!(
+        "\x1b[{virtual_key};{scan_code};{character};{};{control_key_state};1_",
+        is_pressed as u8
+    )
+}
+
+/// Scan code without the extended prefix, which is reported with
+/// [`ENHANCED_KEY`] instead. Only Windows gives the console scan codes.
+#[cfg(target_os = "windows")]
+fn scan_code(physical_key: PhysicalKey) -> u16 {
+    use rio_window::platform::scancode::PhysicalKeyExtScancode;
+
+    physical_key
+        .to_scancode()
+        .map_or(0, |scan_code| (scan_code & 0xff) as u16)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn scan_code(_physical_key: PhysicalKey) -> u16 {
+    0
+}
+
+fn control_key_state(code: KeyCode, modifiers: &Modifiers, is_pressed: bool) -> u16 {
+    let mut state = 0;
+    let mods = modifiers.state();
+
+    if mods.shift_key() {
+        state |= SHIFT_PRESSED;
+    }
+    if mods.control_key() {
+        state |= if modifiers.rcontrol_state() == ModifiersKeyState::Pressed {
+            RIGHT_CTRL_PRESSED
+        } else {
+            LEFT_CTRL_PRESSED
+        };
+    }
+    if mods.alt_key() {
+        state |= if modifiers.ralt_state() == ModifiersKeyState::Pressed {
+            RIGHT_ALT_PRESSED
+        } else {
+            LEFT_ALT_PRESSED
+        };
+    }
+
+    // The modifiers are updated after their own key event, so they're
+    // applied from the key like the console does
+    let own_flag = match code {
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => SHIFT_PRESSED,
+        KeyCode::ControlLeft => LEFT_CTRL_PRESSED,
+        KeyCode::ControlRight => RIGHT_CTRL_PRESSED,
+        KeyCode::AltLeft => LEFT_ALT_PRESSED,
+        KeyCode::AltRight => RIGHT_ALT_PRESSED,
+        _ => 0,
+    };
+    if is_pressed {
+        state |= own_flag;
+    } else {
+        state &= !own_flag;
+    }
+
+    if is_enhanced_key(code) {
+        state |= ENHANCED_KEY;
+    }
+
+    state
+}
+
+/// Keys sent with the extended scan code prefix.
+#[inline]
+fn is_enhanced_key(code: KeyCode) -> bool {
+    matches!(
+        code,
+        KeyCode::ArrowUp
+            | KeyCode::ArrowDown
+            | KeyCode::ArrowLeft
+            | KeyCode::ArrowRight
+            | KeyCode::Insert
+            | KeyCode::Delete
+            | KeyCode::Home
+            | KeyCode::End
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::ControlRight
+            | KeyCode::AltRight
+            | KeyCode::NumpadEnter
+            | KeyCode::NumpadDivide
+            | KeyCode::NumLock
+            | KeyCode::PrintScreen
+            | KeyCode::SuperLeft
+            | KeyCode::SuperRight
+            | KeyCode::ContextMenu
+    )
+}
+
+/// Windows virtual key code of the physical key.
+fn virtual_key_code(code: KeyCode) -> Option<u16> {
+    let letters = [
+        KeyCode::KeyA,
+        KeyCode::KeyB,
+        KeyCode::KeyC,
+        KeyCode::KeyD,
+        KeyCode::KeyE,
+        KeyCode::KeyF,
+        KeyCode::KeyG,
+        KeyCode::KeyH,
+        KeyCode::KeyI,
+        KeyCode::KeyJ,
+        KeyCode::KeyK,
+        KeyCode::KeyL,
+        KeyCode::KeyM,
+        KeyCode::KeyN,
+        KeyCode::KeyO,
+        KeyCode::KeyP,
+        KeyCode::KeyQ,
+        KeyCode::KeyR,
+        KeyCode::KeyS,
+        KeyCode::KeyT,
+        KeyCode::KeyU,
+        KeyCode::KeyV,
+        KeyCode::KeyW,
+        KeyCode::KeyX,
+        KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+    let digits = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    let numpad_digits = [
+        KeyCode::Numpad0,
+        KeyCode::Numpad1,
+        KeyCode::Numpad2,
+        KeyCode::Numpad3,
+        KeyCode::Numpad4,
+        KeyCode::Numpad5,
+        KeyCode::Numpad6,
+        KeyCode::Numpad7,
+        KeyCode::Numpad8,
+        KeyCode::Numpad9,
+    ];
+    let function_keys = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+        KeyCode::F10,
+        KeyCode::F11,
+        KeyCode::F12,
+        KeyCode::F13,
+        KeyCode::F14,
+        KeyCode::F15,
+        KeyCode::F16,
+        KeyCode::F17,
+        KeyCode::F18,
+        KeyCode::F19,
+        KeyCode::F20,
+        KeyCode::F21,
+        KeyCode::F22,
+        KeyCode::F23,
+        KeyCode::F24,
+    ];
+
+    for (keys, first) in [
+        (&letters[..], 0x41),
+        (&digits[..], 0x30),
+        (&numpad_digits[..], 0x60),
+        (&function_keys[..], 0x70),
+    ] {
+        if let Some(index) = keys.iter().position(|key| *key == code) {
+            return Some(first + index as u16);
+        }
+    }
+
+    let virtual_key = match code {
+        KeyCode::Backspace => 0x08,
+        KeyCode::Tab => 0x09,
+        KeyCode::Enter | KeyCode::NumpadEnter => 0x0d,
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => 0x10,
+        KeyCode::ControlLeft | KeyCode::ControlRight => 0x11,
+        KeyCode::AltLeft | KeyCode::AltRight => 0x12,
+        KeyCode::Pause => 0x13,
+        KeyCode::CapsLock => 0x14,
+        KeyCode::Escape => 0x1b,
+        KeyCode::Space => 0x20,
+        KeyCode::PageUp => 0x21,
+        KeyCode::PageDown => 0x22,
+        KeyCode::End => 0x23,
+        KeyCode::Home => 0x24,
+        KeyCode::ArrowLeft => 0x25,
+        KeyCode::ArrowUp => 0x26,
+        KeyCode::ArrowRight => 0x27,
+        KeyCode::ArrowDown => 0x28,
+        KeyCode::PrintScreen => 0x2c,
+        KeyCode::Insert => 0x2d,
+        KeyCode::Delete => 0x2e,
+        KeyCode::SuperLeft => 0x5b,
+        KeyCode::SuperRight => 0x5c,
+        KeyCode::ContextMenu => 0x5d,
+        KeyCode::NumpadMultiply => 0x6a,
+        KeyCode::NumpadAdd => 0x6b,
+        KeyCode::NumpadSubtract => 0x6d,
+        KeyCode::NumpadDecimal => 0x6e,
+        KeyCode::NumpadDivide => 0x6f,
+        KeyCode::NumLock => 0x90,
+        KeyCode::ScrollLock => 0x91,
+        KeyCode::Semicolon => 0xba,
+        KeyCode::Equal => 0xbb,
+        KeyCode::Comma => 0xbc,
+        KeyCode::Minus => 0xbd,
+        KeyCode::Period => 0xbe,
+        KeyCode::Slash => 0xbf,
+        KeyCode::Backquote => 0xc0,
+        KeyCode::BracketLeft => 0xdb,
+        KeyCode::Backslash => 0xdc,
+        KeyCode::BracketRight => 0xdd,
+        KeyCode::Quote => 0xde,
+        KeyCode::IntlBackslash => 0xe2,
+        _ => return None,
+    };
+    Some(virtual_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_key_code() {
+        assert_eq!(virtual_key_code(KeyCode::KeyA), Some(0x41));
+        assert_eq!(virtual_key_code(KeyCode::KeyZ), Some(0x5a));
+        assert_eq!(virtual_key_code(KeyCode::Digit7), Some(0x37));
+        assert_eq!(virtual_key_code(KeyCode::Numpad3), Some(0x63));
+        assert_eq!(virtual_key_code(KeyCode::F12), Some(0x7b));
+        assert_eq!(virtual_key_code(KeyCode::ArrowUp), Some(0x26));
+        assert_eq!(virtual_key_code(KeyCode::Fn), None);
+    }
+
+    #[test]
+    fn test_control_key_state() {
+        let modifiers = Modifiers::default();
+        assert_eq!(
+            control_key_state(KeyCode::ShiftLeft, &modifiers, true),
+            SHIFT_PRESSED
+        );
+        assert_eq!(control_key_state(KeyCode::ShiftLeft, &modifiers, false), 0);
+        assert_eq!(
+            control_key_state(KeyCode::ArrowLeft, &modifiers, true),
+            ENHANCED_KEY
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        // Key down and up of `a`
+        assert_eq!(encode(0x41, 0x1e, 97, true, 0), "\x1b[65;30;97;1;0;1_");
+        assert_eq!(encode(0x41, 0x1e, 97, false, 0), "\x1b[65;30;97;0;0;1_");
+    }
+}
@@ -1,7 +1,7 @@
 // cli.rs was retired originally from https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty/src/cli.rs
 // which is licensed under Apache 2.0 license.
 
-use clap::{Args, Parser, ValueHint};
+use clap::{Args, Parser, Subcommand, ValueHint};
 use rio_backend::config::Shell;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -12,6 +12,28 @@ pub struct Cli {
     /// Options which can be passed via IPC.
     #[clap(flatten)]
     pub window_options: WindowOptions,
+
+    /// Ignore the configuration file, themes and shaders, to recover
+    /// from a configuration that makes the windows unusable.
+    #[clap(long)]
+    pub safe_mode: bool,
+
+    #[clap(subcommand)]
+    pub subcommand: Option<Subcommands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Inspect the configuration.
+    #[clap(subcommand)]
+    Config(ConfigSubcommands),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigSubcommands {
+    /// Print the JSON Schema of the configuration file, for editors to
+    /// complete and validate it.
+    Schema,
 }
 
 #[derive(Serialize, Deserialize, Args, Default, Clone, Debug, PartialEq, Eq)]
@@ -1,7 +1,7 @@
 use crate::context::Context;
 use crate::mouse::Mouse;
 use rio_backend::crosswords::grid::Dimensions;
-use rio_backend::event::EventListener;
+use rio_backend::event::{EventListener, WindowId};
 use rio_backend::sugarloaf::{
     layout::SugarDimensions, Object, Quad, RichText, Sugarloaf,
 };
@@ -404,6 +404,23 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
         }
     }
 
+    /// Moves the terminals to another window, `rich_text_ids` has a rich
+    /// text of that window for each of them and their routes follow
+    /// `first_route`.
+    pub fn migrate(
+        &mut self,
+        window_id: WindowId,
+        first_route: usize,
+        rich_text_ids: &[usize],
+    ) {
+        for (index, (item, rich_text_id)) in
+            self.inner.iter_mut().zip(rich_text_ids).enumerate()
+        {
+            item.val
+                .migrate(window_id, first_route + index, *rich_text_id);
+        }
+    }
+
     pub fn update_line_height(&mut self, line_height: f32) {
         for context in &mut self.inner {
             context.val.dimension.update_line_height(line_height);
@@ -413,7 +430,10 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
     pub fn update_dimensions(&mut self, sugarloaf: &Sugarloaf) {
         for context in &mut self.inner {
             let layout = sugarloaf.rich_text_layout(&context.val.rich_text_id);
-            context.val.dimension.update_dimensions(layout.dimensions);
+            context
+                .val
+                .dimension
+                .update_dimensions(layout.dimensions, layout.effective_line_height());
         }
     }
 
@@ -975,9 +995,12 @@ impl ContextDimension {
         self.update();
     }
 
+    /// Updates the cell dimensions with the line height the rich text
+    /// draws them at, which can be snapped to whole pixels.
     #[inline]
-    pub fn update_dimensions(&mut self, dimensions: SugarDimensions) {
+    pub fn update_dimensions(&mut self, dimensions: SugarDimensions, line_height: f32) {
         self.dimension = dimensions;
+        self.line_height = line_height;
         self.update();
     }
 
@@ -16,7 +16,11 @@ use crate::messenger::Messenger;
 use crate::performer::Machine;
 use renderable::Cursor;
 use renderable::RenderableContent;
+use rio_backend::config::hyperlinks::HyperlinkPattern;
+use rio_backend::config::profiles::{find_profile, Profile, ProfileState};
+use rio_backend::config::unicode::{CharWidth, InvalidUtf8};
 use rio_backend::config::Shell;
+use rio_backend::crosswords::square::Hyperlink;
 use rio_backend::crosswords::{Crosswords, MIN_COLUMNS, MIN_LINES};
 use rio_backend::error::{RioError, RioErrorLevel, RioErrorType};
 use rio_backend::event::EventListener;
@@ -47,16 +51,46 @@ pub struct Context<T: EventListener> {
     pub rich_text_id: usize,
     pub dimension: ContextDimension,
     pub ime: Ime,
+    /// Variables set by the profile matched when the terminal was spawned.
+    pub env: Vec<(String, String)>,
+    /// Output which isn't valid UTF-8 is decoded as Latin-1.
+    latin1_fallback: bool,
+    /// Echo was disabled when the terminal last had input or output.
+    password_input: bool,
 }
 
 impl<T: rio_backend::event::EventListener> Drop for Context<T> {
     fn drop(&mut self) {
+        // The reader thread owns the PTY, dropping it hangs up the shell
+        self.messenger.send_shutdown();
         #[cfg(not(target_os = "windows"))]
         teletypewriter::kill_pid(self.shell_pid as i32);
     }
 }
 
 impl<T: EventListener> Context<T> {
+    /// Moves the terminal to another window, its PTY keeps running.
+    pub fn migrate(&mut self, window_id: WindowId, route_id: usize, rich_text_id: usize) {
+        self.route_id = route_id;
+        self.rich_text_id = rich_text_id;
+
+        let mut terminal = self.terminal.lock();
+        terminal.window_id = window_id;
+        terminal.route_id = route_id;
+        drop(terminal);
+
+        // Events of the PTY reader are sent to the new window from now on
+        self.messenger.send_migrate(window_id, route_id);
+    }
+
+    /// Switches between Latin-1 and replacement characters for the output
+    /// which isn't valid UTF-8.
+    #[inline]
+    pub fn toggle_latin1_fallback(&mut self) {
+        self.latin1_fallback = !self.latin1_fallback;
+        self.messenger.send_latin1_fallback(self.latin1_fallback);
+    }
+
     #[inline]
     pub fn set_selection(&mut self, selection_range: Option<SelectionRange>) {
         let has_updated = (self.renderable_content.selection_range.is_none()
@@ -71,8 +105,13 @@ impl<T: EventListener> Context<T> {
     }
 
     #[inline]
-    pub fn set_hyperlink_range(&mut self, hyperlink_range: Option<SelectionRange>) {
+    pub fn set_hovered_hyperlink(
+        &mut self,
+        hovered: Option<(SelectionRange, Hyperlink)>,
+    ) {
+        let (hyperlink_range, hyperlink) = hovered.unzip();
         self.renderable_content.hyperlink_range = hyperlink_range;
+        self.renderable_content.hovered_hyperlink = hyperlink;
         self.renderable_content.has_pending_updates = true;
     }
 
@@ -81,6 +120,27 @@ impl<T: EventListener> Context<T> {
         self.renderable_content.hyperlink_range.is_some()
     }
 
+    /// Whether the foreground program is reading a secret, like a password
+    /// prompt, as of the last `update_password_input`.
+    #[inline]
+    pub fn is_password_input(&self) -> bool {
+        self.password_input
+    }
+
+    /// Checks again whether echo is disabled on the terminal, which is how
+    /// password prompts are detected. It's called on input and when the
+    /// PTY has new output rather than on every frame, returns whether it
+    /// changed.
+    pub fn update_password_input(&mut self) -> bool {
+        #[cfg(not(target_os = "windows"))]
+        let password_input = teletypewriter::is_echo_disabled(*self.main_fd);
+
+        #[cfg(target_os = "windows")]
+        let password_input = false;
+
+        std::mem::replace(&mut self.password_input, password_input) != password_input
+    }
+
     #[inline]
     pub fn cursor_from_ref(&self) -> Cursor {
         Cursor {
@@ -92,7 +152,7 @@ impl<T: EventListener> Context<T> {
     }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct ContextManagerConfig {
     pub shell: Shell,
     #[cfg(not(target_os = "windows"))]
@@ -104,6 +164,40 @@ pub struct ContextManagerConfig {
     pub should_update_title_extra: bool,
     pub split_color: [f32; 4],
     pub title: rio_backend::config::title::Title,
+    pub profiles: Vec<Profile>,
+    pub scroll_history: usize,
+    pub copy_visual_layout: bool,
+    pub scroll_on_output: bool,
+    pub char_width: CharWidth,
+    pub graphics_max_memory: usize,
+    pub hyperlink_patterns: Vec<HyperlinkPattern>,
+    pub latin1_fallback: bool,
+}
+
+impl Default for ContextManagerConfig {
+    fn default() -> ContextManagerConfig {
+        ContextManagerConfig {
+            shell: Shell::default(),
+            #[cfg(not(target_os = "windows"))]
+            use_fork: false,
+            working_dir: None,
+            spawn_performer: false,
+            use_current_path: false,
+            is_native: false,
+            should_update_title_extra: false,
+            split_color: [0.0; 4],
+            title: rio_backend::config::title::Title::default(),
+            profiles: Vec::default(),
+            scroll_history: rio_backend::config::Scroll::default().history,
+            copy_visual_layout: false,
+            scroll_on_output: false,
+            char_width: CharWidth::default(),
+            graphics_max_memory: rio_backend::config::graphics::Graphics::default()
+                .max_memory_bytes(),
+            hyperlink_patterns: Vec::new(),
+            latin1_fallback: false,
+        }
+    }
 }
 
 pub struct ContextManager<T: EventListener> {
@@ -117,6 +211,7 @@ pub struct ContextManager<T: EventListener> {
     window_id: WindowId,
     pub config: ContextManagerConfig,
     pub titles: ContextManagerTitles,
+    pub active_profile: Option<String>,
 }
 
 pub fn create_dead_context<T: rio_backend::event::EventListener>(
@@ -148,9 +243,39 @@ pub fn create_dead_context<T: rio_backend::event::EventListener>(
         rich_text_id,
         dimension,
         ime: Ime::new(),
+        env: Vec::new(),
+        latin1_fallback: false,
+        password_input: false,
     }
 }
 
+/// Variables of the profile matching the directory and the program the
+/// terminal is spawned with.
+fn spawn_env(config: &ContextManagerConfig) -> Vec<(String, String)> {
+    if config.profiles.is_empty() {
+        return Vec::new();
+    }
+
+    let cwd = config.working_dir.clone().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .map(|path| path.to_string_lossy().to_string())
+    });
+    let command = std::path::Path::new(&config.shell.program)
+        .file_name()
+        .and_then(|name| name.to_str());
+    let state = ProfileState {
+        cwd: cwd.as_deref(),
+        hostname: None,
+        command,
+        user_vars: None,
+    };
+
+    find_profile(&config.profiles, &state)
+        .map(Profile::env)
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 pub fn create_mock_context<
     T: rio_backend::event::EventListener + Clone + std::marker::Send + 'static,
@@ -209,12 +334,25 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             route_id,
         );
         terminal.blinking_cursor = cursor_state.1;
+        terminal.default_blinking_cursor = cursor_state.1;
+        terminal.update_history(config.scroll_history);
+        terminal.copy_visual_layout = config.copy_visual_layout;
+        terminal.scroll_on_output = config.scroll_on_output;
+        terminal.char_width = config.char_width;
+        terminal.set_graphics_max_memory(config.graphics_max_memory);
+        terminal.set_hyperlink_patterns(&config.hyperlink_patterns);
         let terminal: Arc<FairMutex<Crosswords<T>>> = Arc::new(FairMutex::new(terminal));
 
+        let env = spawn_env(config);
+        if !env.is_empty() {
+            tracing::info!("rio -> teletypewriter: profile env {env:?}");
+        }
+
         let pty;
         #[cfg(not(target_os = "windows"))]
         {
-            if config.use_fork {
+            // The fork only runs the program, without variables
+            if config.use_fork && env.is_empty() {
                 tracing::info!("rio -> teletypewriter: create_pty_with_fork");
                 pty = match create_pty_with_fork(
                     &Cow::Borrowed(&config.shell.program),
@@ -233,6 +371,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                     &Cow::Borrowed(&config.shell.program),
                     config.shell.args.clone(),
                     &config.working_dir,
+                    &env,
                     cols,
                     rows,
                 ) {
@@ -256,6 +395,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                 &Cow::Borrowed(&config.shell.program),
                 config.shell.args.clone(),
                 &config.working_dir,
+                &env,
                 cols,
                 rows,
             ) {
@@ -267,13 +407,14 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             }
         }
 
-        let machine = Machine::new(
+        let mut machine = Machine::new(
             Arc::clone(&terminal),
             pty,
             event_proxy.clone(),
             window_id,
             route_id,
         )?;
+        machine.set_latin1_fallback(config.latin1_fallback);
         let channel = machine.channel();
         if config.spawn_performer {
             machine.spawn();
@@ -293,6 +434,9 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             renderable_content: RenderableContent::new(cursor_state.0.clone()),
             dimension,
             ime: Ime::new(),
+            env,
+            latin1_fallback: config.latin1_fallback,
+            password_input: false,
         })
     }
 
@@ -373,9 +517,40 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             window_id,
             config: ctx_config,
             titles,
+            active_profile: None,
         })
     }
 
+    /// Starts with a tab moved from another window instead of spawning a
+    /// terminal, see [`ContextGrid::migrate`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_with_grid(
+        mut grid: ContextGrid<T>,
+        event_proxy: T,
+        window_id: WindowId,
+        rich_text_ids: &[usize],
+        ctx_config: ContextManagerConfig,
+        size: ContextDimension,
+        margin: Delta<f32>,
+    ) -> Self {
+        grid.migrate(window_id, 0, rich_text_ids);
+        grid.update_margin((margin.x, margin.top_y, margin.bottom_y));
+        grid.resize(size.width, size.height);
+
+        ContextManager {
+            current_index: 0,
+            current_route: grid.current().route_id,
+            acc_current_route: grid.len() - 1,
+            contexts: vec![grid],
+            capacity: DEFAULT_CONTEXT_CAPACITY,
+            event_proxy,
+            window_id,
+            config: ctx_config,
+            titles: ContextManagerTitles::new(0, String::from("tab"), None),
+            active_profile: None,
+        }
+    }
+
     #[cfg(test)]
     pub fn start_with_capacity(
         capacity: usize,
@@ -422,6 +597,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             window_id,
             config,
             titles,
+            active_profile: None,
         })
     }
 
@@ -491,6 +667,14 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         );
     }
 
+    #[inline]
+    pub fn delay_search(&mut self, scheduled_time: u64) {
+        self.event_proxy.send_event(
+            RioEvent::PrepareDelayedSearch(scheduled_time),
+            self.window_id,
+        );
+    }
+
     #[inline]
     pub fn report_error_fonts_not_found(&mut self, fonts_not_found: Vec<SugarloafFont>) {
         if !fonts_not_found.is_empty() {
@@ -512,6 +696,24 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             .send_event(RioEvent::CreateWindow, self.window_id);
     }
 
+    #[inline]
+    pub fn move_tab_to_new_window(&self) {
+        self.event_proxy
+            .send_event(RioEvent::MoveTabToNewWindow, self.window_id);
+    }
+
+    #[inline]
+    pub fn merge_windows(&self) {
+        self.event_proxy
+            .send_event(RioEvent::MergeWindows, self.window_id);
+    }
+
+    #[inline]
+    pub fn select_window(&self, next: bool) {
+        self.event_proxy
+            .send_event(RioEvent::SelectWindow(next), self.window_id);
+    }
+
     #[inline]
     pub fn close_unfocused_tabs(&mut self) {
         let current_route_id = self.current().route_id;
@@ -582,6 +784,12 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             .send_event(RioEvent::ToggleFullScreen, self.window_id);
     }
 
+    #[inline]
+    pub fn update_window_opacity(&mut self, opacity: f32) {
+        self.event_proxy
+            .send_event(RioEvent::UpdateOpacity(opacity), self.window_id);
+    }
+
     #[inline]
     pub fn minimize(&mut self) {
         self.event_proxy
@@ -598,6 +806,37 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         self.event_proxy.send_event(RioEvent::Quit, self.window_id);
     }
 
+    #[inline]
+    pub fn confirm_paste(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::ConfirmPaste, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_clipboard_history(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::ClipboardHistory, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_command_palette(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::CommandPalette, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_settings(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::Settings, self.window_id);
+    }
+
+    /// Loads the config file again, like when it's changed on disk.
+    #[inline]
+    pub fn reload_config(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::PrepareUpdateConfig, self.window_id);
+    }
+
     #[cfg(target_os = "macos")]
     #[inline]
     pub fn hide_other_apps(&mut self) {
@@ -605,6 +844,13 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             .send_event(RioEvent::HideOtherApplications, self.window_id);
     }
 
+    #[cfg(target_os = "macos")]
+    #[inline]
+    pub fn haptic_feedback(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::HapticFeedback, self.window_id);
+    }
+
     #[inline]
     pub fn select_last_tab(&mut self) {
         if self.config.is_native {
@@ -646,9 +892,18 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             .unwrap_or(true)
         {
             self.titles.last_title_update = Some(Instant::now());
+            self.update_profile();
+
+            let template = self
+                .active_profile
+                .as_ref()
+                .and_then(|name| self.config.profiles.iter().find(|p| &p.name == name))
+                .and_then(|profile| profile.title.as_ref())
+                .unwrap_or(&self.config.title.content);
+
             let mut id = String::default();
             for (i, context) in self.contexts.iter_mut().enumerate() {
-                let content = update_title(&self.config.title.content, context.current());
+                let content = update_title(template, context.current());
 
                 self.event_proxy
                     .send_event(RioEvent::Title(content.to_owned()), self.window_id);
@@ -670,6 +925,70 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    /// Evaluates the profile rules against the current context and
+    /// requests the window to apply (or revert) a profile if it changed.
+    fn update_profile(&mut self) {
+        if self.config.profiles.is_empty() && self.active_profile.is_none() {
+            return;
+        }
+
+        let context = self.current();
+        let (cwd, hostname, user_vars) = {
+            let terminal = context.terminal.lock();
+            let cwd = terminal
+                .current_directory
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string());
+            // Remote shells can report the host through `SetUserVar`
+            let hostname = terminal
+                .user_vars
+                .get("hostname")
+                .or(terminal.current_hostname.as_ref())
+                .cloned();
+            (cwd, hostname, terminal.user_vars.clone())
+        };
+
+        #[cfg(unix)]
+        let cwd = cwd.or_else(|| {
+            teletypewriter::foreground_process_path(*context.main_fd, context.shell_pid)
+                .map(|path| path.to_string_lossy().to_string())
+                .ok()
+        });
+
+        #[cfg(unix)]
+        let command = Some(teletypewriter::foreground_process_name(
+            *context.main_fd,
+            context.shell_pid,
+        ));
+
+        #[cfg(not(unix))]
+        let command: Option<String> = None;
+
+        let state = ProfileState {
+            cwd: cwd.as_deref(),
+            hostname: hostname.as_deref(),
+            command: command.as_deref(),
+            user_vars: Some(&user_vars),
+        };
+        let profile = find_profile(&self.config.profiles, &state)
+            .map(|profile| profile.name.clone());
+        let route_id = context.route_id;
+
+        if profile != self.active_profile {
+            self.active_profile = profile.clone();
+            self.event_proxy
+                .send_event(RioEvent::UpdateProfile(route_id, profile), self.window_id);
+        }
+    }
+
+    /// Forces the profile rules to be evaluated again on next title update.
+    #[inline]
+    pub fn reset_profile(&mut self, profiles: &[Profile]) {
+        self.config.profiles = profiles.to_vec();
+        self.active_profile = None;
+        self.titles.last_title_update = None;
+    }
+
     #[inline]
     pub fn contexts_mut(&mut self) -> &mut Vec<ContextGrid<T>> {
         &mut self.contexts
@@ -709,6 +1028,39 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    /// Names of the programs running in the terminals of the window, other
+    /// than their shells. Always empty on Windows, where they aren't known.
+    pub fn running_programs(&mut self) -> Vec<String> {
+        #[cfg(unix)]
+        {
+            let mut programs: Vec<String> = self
+                .contexts
+                .iter_mut()
+                .flat_map(|grid| grid.contexts_mut().iter())
+                .map(|item| item.context())
+                .filter_map(|context| {
+                    teletypewriter::foreground_child_process_name(
+                        *context.main_fd,
+                        context.shell_pid,
+                    )
+                })
+                .collect();
+            programs.sort();
+            programs.dedup();
+            programs
+        }
+
+        #[cfg(not(unix))]
+        Vec::new()
+    }
+
+    /// Closes the window, after its quit confirmation.
+    #[inline]
+    pub fn close_window(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::CloseWindow, self.window_id);
+    }
+
     #[inline]
     pub fn close_current_context(&mut self) {
         if self.contexts.len() == 1 {
@@ -738,6 +1090,60 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    /// Takes the current tab out of the window, its terminals keep running
+    /// to be moved to another one. `None` for the last tab.
+    pub fn take_current_grid(&mut self) -> Option<ContextGrid<T>> {
+        if self.config.is_native || self.contexts.len() == 1 {
+            return None;
+        }
+
+        let index_to_remove = self.current_index;
+        let mut should_set_current = false;
+        if index_to_remove > 1 {
+            self.set_current(self.current_index - 1);
+        } else {
+            should_set_current = true;
+        }
+
+        self.titles.titles.remove(&index_to_remove);
+        let grid = self.contexts.remove(index_to_remove);
+
+        if should_set_current {
+            self.set_current(0);
+        }
+
+        Some(grid)
+    }
+
+    /// Takes every tab out of the window to merge them into another one,
+    /// the window is left without tabs and has to be closed.
+    pub fn take_grids(&mut self) -> Vec<ContextGrid<T>> {
+        self.titles.titles.clear();
+        self.current_index = 0;
+        std::mem::take(&mut self.contexts)
+    }
+
+    /// Whether `count` tabs moved from other windows fit in this one.
+    #[inline]
+    pub fn has_capacity(&self, count: usize) -> bool {
+        !self.config.is_native && self.contexts.len() + count <= self.capacity
+    }
+
+    /// Adds a tab moved from another window and selects it, see
+    /// [`ContextGrid::migrate`].
+    pub fn adopt_grid(&mut self, mut grid: ContextGrid<T>, rich_text_ids: &[usize]) {
+        grid.migrate(self.window_id, self.acc_current_route + 1, rich_text_ids);
+        self.acc_current_route += grid.len();
+
+        let current = self.current_grid();
+        let (width, height, margin) = (current.width, current.height, current.margin);
+        grid.update_margin((margin.x, margin.top_y, margin.bottom_y));
+        grid.resize(width, height);
+
+        self.contexts.push(grid);
+        self.set_current(self.contexts.len() - 1);
+    }
+
     #[inline]
     pub fn current_index(&self) -> usize {
         self.current_index
@@ -758,6 +1164,15 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         self.contexts[self.current_index].current_mut()
     }
 
+    /// Terminal of the route, in any tab or split of the window.
+    pub fn context_by_route_mut(&mut self, route_id: usize) -> Option<&mut Context<T>> {
+        self.contexts
+            .iter_mut()
+            .flat_map(|grid| grid.contexts_mut().iter_mut())
+            .map(|item| item.context_mut())
+            .find(|context| context.route_id == route_id)
+    }
+
     #[inline]
     pub fn switch_to_next(&mut self) {
         if self.config.is_native {
@@ -901,6 +1316,14 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             should_update_title_extra: !config.navigation.color_automation.is_empty(),
             split_color: config.colors.split,
             title: config.title,
+            profiles: config.profiles,
+            scroll_history: config.scroll.history,
+            copy_visual_layout: config.copy_visual_layout,
+            scroll_on_output: config.scroll.on_output,
+            char_width: CharWidth::new(&config.unicode),
+            graphics_max_memory: config.graphics.max_memory_bytes(),
+            hyperlink_patterns: config.hyperlinks.patterns.clone(),
+            latin1_fallback: config.unicode.invalid_utf8 == InvalidUtf8::Latin1,
         };
 
         self.acc_current_route += 1;
@@ -931,8 +1354,14 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    /// Adds a tab, running `shell` instead of the configured one when set.
     #[inline]
-    pub fn add_context(&mut self, redirect: bool, rich_text_id: usize) {
+    pub fn add_context(
+        &mut self,
+        redirect: bool,
+        rich_text_id: usize,
+        shell: Option<Shell>,
+    ) {
         let mut working_dir = self.config.working_dir.clone();
         if self.config.use_current_path {
             #[cfg(not(target_os = "windows"))]
@@ -957,6 +1386,9 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
 
         if self.config.is_native {
+            if shell.is_some() {
+                tracing::warn!("native tabs can't run a program, ignoring it");
+            }
             self.event_proxy
                 .send_event(RioEvent::CreateNativeTab(working_dir), self.window_id);
             return;
@@ -970,6 +1402,14 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             if working_dir.is_some() {
                 cloned_config.working_dir = working_dir;
             }
+            if let Some(shell) = shell {
+                // The fork only runs the program, without the arguments
+                #[cfg(not(target_os = "windows"))]
+                {
+                    cloned_config.use_fork = false;
+                }
+                cloned_config.shell = shell;
+            }
 
             self.acc_current_route += 1;
             let current = self.current();
@@ -1069,12 +1509,12 @@ pub mod test {
         assert_eq!(context_manager.current_index, 0);
 
         let should_redirect = false;
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.capacity, 5);
         assert_eq!(context_manager.current_index, 0);
 
         let should_redirect = true;
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.capacity, 5);
         assert_eq!(context_manager.current_index, 2);
     }
@@ -1088,13 +1528,13 @@ pub mod test {
         assert_eq!(context_manager.capacity, 3);
         assert_eq!(context_manager.current_index, 0);
         let should_redirect = false;
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.len(), 2);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.len(), 3);
 
         for _ in 0..20 {
-            context_manager.add_context(should_redirect, 0);
+            context_manager.add_context(should_redirect, 0, None);
         }
 
         assert_eq!(context_manager.len(), 3);
@@ -1109,7 +1549,7 @@ pub mod test {
             ContextManager::start_with_capacity(8, VoidListener {}, window_id).unwrap();
         let should_redirect = true;
 
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.current_index, 1);
         context_manager.set_current(0);
         assert_eq!(context_manager.current_index, 0);
@@ -1117,8 +1557,8 @@ pub mod test {
         assert_eq!(context_manager.capacity, 8);
 
         let should_redirect = false;
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.set_current(3);
         assert_eq!(context_manager.current_index, 3);
 
@@ -1134,8 +1574,8 @@ pub mod test {
             ContextManager::start_with_capacity(3, VoidListener {}, window_id).unwrap();
         let should_redirect = false;
 
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.len(), 3);
 
         assert_eq!(context_manager.current_index, 0);
@@ -1157,10 +1597,10 @@ pub mod test {
             ContextManager::start_with_capacity(5, VoidListener {}, window_id).unwrap();
         let should_redirect = false;
 
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
 
         context_manager.close_current_context();
         context_manager.close_current_context();
@@ -1170,7 +1610,7 @@ pub mod test {
         assert_eq!(context_manager.len(), 1);
         assert_eq!(context_manager.current_index, 0);
 
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
 
         assert_eq!(context_manager.len(), 2);
         context_manager.set_current(1);
@@ -1188,8 +1628,8 @@ pub mod test {
             ContextManager::start_with_capacity(2, VoidListener {}, window_id).unwrap();
         let should_redirect = false;
 
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.len(), 2);
         assert_eq!(context_manager.current_index, 0);
 
@@ -1209,11 +1649,11 @@ pub mod test {
             ContextManager::start_with_capacity(5, VoidListener {}, window_id).unwrap();
         let should_redirect = false;
 
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
         assert_eq!(context_manager.len(), 5);
         assert_eq!(context_manager.current_index, 0);
 
@@ -1240,13 +1680,13 @@ pub mod test {
         let should_redirect = true;
         let split_down = false;
 
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.split(0, split_down);
         context_manager.split(0, split_down);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.split(0, split_down);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.set_current(0);
         assert_eq!(context_manager.len(), 5);
         assert_eq!(context_manager.current_index, 0);
@@ -1303,13 +1743,13 @@ pub mod test {
         let should_redirect = true;
         let split_down = false;
 
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.split(0, split_down);
         context_manager.split(0, split_down);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.split(0, split_down);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.set_current(0);
         assert_eq!(context_manager.len(), 5);
         assert_eq!(context_manager.current_index, 0);
@@ -1366,10 +1806,10 @@ pub mod test {
         let should_redirect = true;
         let split_down = false;
 
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.split(0, split_down);
         context_manager.split(0, split_down);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
         context_manager.set_current(0);
         assert_eq!(context_manager.len(), 3);
         assert_eq!(context_manager.current_index, 0);
@@ -1428,10 +1868,10 @@ pub mod test {
         let should_redirect = false;
 
         context_manager.current_mut().rich_text_id = 1;
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
 
         assert_eq!(context_manager.len(), 5);
         assert_eq!(context_manager.current_index, 0);
@@ -1471,10 +1911,10 @@ pub mod test {
         let should_redirect = false;
 
         context_manager.current_mut().rich_text_id = 1;
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
-        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
+        context_manager.add_context(should_redirect, 0, None);
 
         assert_eq!(context_manager.len(), 5);
         assert_eq!(context_manager.current_index, 0);
@@ -1504,4 +1944,57 @@ pub mod test {
         assert_eq!(context_manager.current_index, 4);
         assert_eq!(context_manager.current().rich_text_id, 1);
     }
+
+    #[test]
+    fn test_context_by_route() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(5, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        context_manager.add_context(false, 1, None);
+        context_manager.add_context(false, 2, None);
+        assert_eq!(context_manager.current_route(), 0);
+
+        // Background tabs are found as well
+        let context = context_manager.context_by_route_mut(2).unwrap();
+        assert_eq!(context.rich_text_id, 2);
+        assert!(context_manager.context_by_route_mut(3).is_none());
+    }
+
+    #[test]
+    fn test_move_grid_between_windows() {
+        let mut source =
+            ContextManager::start_with_capacity(5, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        let target_window_id = WindowId::from(1);
+        let mut target =
+            ContextManager::start_with_capacity(5, VoidListener {}, target_window_id)
+                .unwrap();
+
+        // The last tab stays in its window
+        assert!(source.take_current_grid().is_none());
+
+        source.add_context(true, 1, None);
+        assert_eq!(source.len(), 2);
+        let grid = source.take_current_grid().unwrap();
+        assert_eq!(source.len(), 1);
+        assert_eq!(source.current_index, 0);
+
+        assert!(target.has_capacity(1));
+        target.add_context(false, 1, None);
+        target.adopt_grid(grid, &[7]);
+        assert_eq!(target.len(), 3);
+        assert_eq!(target.current_index, 2);
+        assert_eq!(target.current().rich_text_id, 7);
+        assert_eq!(target.current_route(), 2);
+
+        let terminal = target.current().terminal.lock();
+        assert_eq!(terminal.window_id, target_window_id);
+        assert_eq!(terminal.route_id, 2);
+        drop(terminal);
+
+        assert!(!target.has_capacity(3));
+        let grids = source.take_grids();
+        assert_eq!(grids.len(), 1);
+        assert_eq!(source.len(), 0);
+    }
 }
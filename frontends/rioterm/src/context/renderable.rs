@@ -1,5 +1,6 @@
 use rio_backend::config::CursorConfig;
 use rio_backend::crosswords::pos::CursorState;
+use rio_backend::crosswords::square::Hyperlink;
 use rio_backend::selection::SelectionRange;
 use std::time::Instant;
 
@@ -19,6 +20,8 @@ pub struct RenderableContent {
     pub is_blinking_cursor_visible: bool,
     pub selection_range: Option<SelectionRange>,
     pub hyperlink_range: Option<SelectionRange>,
+    /// Hyperlink underlined in every cell holding it.
+    pub hovered_hyperlink: Option<Hyperlink>,
     pub last_typing: Option<Instant>,
     pub has_pending_updates: bool,
 }
@@ -30,6 +33,7 @@ impl RenderableContent {
             has_blinking_enabled: false,
             selection_range: None,
             hyperlink_range: None,
+            hovered_hyperlink: None,
             last_typing: None,
             has_pending_updates: false,
             is_blinking_cursor_visible: false,
@@ -80,6 +80,7 @@ pub fn create_title_extra_from_context<T: rio_backend::event::EventListener>(
 // - `CANONICAL_PATH`: (e.g `.../Documents/a/rio`, `~/Documents/a`)
 // - `COLUMNS`: current columns
 // - `LINES`: current lines
+// - `USER.<NAME>`: user variable set via OSC 1337 SetUserVar (e.g `user.git_branch`)
 
 #[inline]
 pub fn update_title<T: rio_backend::event::EventListener>(
@@ -193,6 +194,37 @@ pub fn update_title<T: rio_backend::event::EventListener>(
                         }
                     }
                 }
+                user_var if user_var.starts_with("user.") => {
+                    // Variable names are kept as sent by the shell
+                    let name = &scoped_variable.trim()["user.".len()..];
+                    let value = {
+                        let terminal = context.terminal.lock();
+                        terminal
+                            .user_vars
+                            .get(name)
+                            .or_else(|| {
+                                terminal
+                                    .user_vars
+                                    .iter()
+                                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                                    .map(|(_, value)| value)
+                            })
+                            .cloned()
+                            .unwrap_or_default()
+                    };
+
+                    let is_only_one = variables.len() == 1;
+                    let is_last = i == variables.len() - 1;
+                    if is_only_one || is_last {
+                        new_template = new_template.replace(to_replace_str, &value);
+                        continue;
+                    }
+
+                    if !value.is_empty() {
+                        new_template = new_template.replace(to_replace_str, &value);
+                        matched = true;
+                    }
+                }
                 // TODO:
                 // "path_relative" => {
                 //     #[cfg(unix)]
@@ -345,4 +377,48 @@ pub mod test {
             String::from("/tmp"),
         );
     }
+
+    #[test]
+    fn test_update_title_with_user_vars() {
+        let context_dimension = ContextDimension::build(
+            1200.0,
+            800.0,
+            SugarDimensions {
+                scale: 2.,
+                width: 18.,
+                height: 9.,
+            },
+            1.0,
+            Delta::<f32>::default(),
+        );
+
+        let context = create_mock_context(
+            VoidListener {},
+            WindowId::from(0),
+            0,
+            0,
+            context_dimension,
+        );
+
+        assert_eq!(update_title("{{ user.git_branch }}", &context), "");
+        assert_eq!(
+            update_title("{{ user.git_branch || columns }}", &context),
+            String::from("66")
+        );
+
+        {
+            let mut term = context.terminal.lock();
+            term.user_vars
+                .insert(String::from("git_branch"), String::from("main"));
+        };
+
+        assert_eq!(
+            update_title("{{ user.git_branch }}", &context),
+            String::from("main")
+        );
+        assert_eq!(
+            update_title("{{ USER.GIT_BRANCH || columns }}", &context),
+            String::from("main")
+        );
+    }
 }
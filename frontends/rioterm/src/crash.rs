@@ -0,0 +1,28 @@
+//! Keeps the message of a panic, so the next session can point to the
+//! safe mode when the configuration or a shader makes Rio crash.
+
+use rio_backend::config::config_dir_path;
+use std::panic;
+use std::path::PathBuf;
+
+fn crash_file_path() -> PathBuf {
+    config_dir_path().join("crash.log")
+}
+
+/// Writes the panic message next to the configuration, the panic is then
+/// handled by the previous hook.
+pub fn attach_handler() {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        let _ = std::fs::write(crash_file_path(), panic_info.to_string());
+        previous_hook(panic_info);
+    }));
+}
+
+/// Message of the panic of the last session, it's only reported once.
+pub fn take_last_crash() -> Option<String> {
+    let path = crash_file_path();
+    let message = std::fs::read_to_string(&path).ok()?;
+    let _ = std::fs::remove_file(&path);
+    Some(message)
+}
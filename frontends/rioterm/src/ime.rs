@@ -73,4 +73,10 @@ impl Preedit {
             cursor_end_offset,
         }
     }
+
+    /// Width of the preedit text in cells.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.text.chars().map(|ch| ch.width().unwrap_or(1)).sum()
+    }
 }
@@ -9,6 +9,7 @@ mod bindings;
 mod cli;
 mod constants;
 mod context;
+mod crash;
 mod ime;
 mod messenger;
 mod mouse;
@@ -19,6 +20,7 @@ mod renderer;
 mod router;
 mod scheduler;
 mod screen;
+mod startup;
 mod watcher;
 
 use clap::Parser;
@@ -127,6 +129,7 @@ fn setup_logs_by_filter_level(
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(windows)]
     panic::attach_handler();
+    crash::attach_handler();
 
     // When linked with the windows subsystem windows won't automatically attach
     // to the console of the parent process, so we do it explicitly. This fails
@@ -136,9 +139,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         AttachConsole(ATTACH_PARENT_PROCESS);
     }
 
+    let mut startup = startup::StartupTimings::new();
+
     // Load command line options.
     let args = cli::Cli::parse();
 
+    if let Some(cli::Subcommands::Config(cli::ConfigSubcommands::Schema)) =
+        args.subcommand
+    {
+        println!("{}", rio_backend::config::config_schema());
+        return Ok(());
+    }
+
     let write_config_path = args.window_options.terminal_options.write_config.clone();
     if let Some(config_path) = write_config_path {
         let _ = setup_logs_by_filter_level("TRACE", false);
@@ -146,9 +158,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-    let (mut config, config_error) = match rio_backend::config::Config::try_load() {
-        Ok(config) => (config, None),
-        Err(err) => (rio_backend::config::Config::default(), Some(err)),
+    // Used by the `{accent}` variable of the colors
+    rio_backend::config::colors::set_accent_color(platform::accent_color());
+
+    let (mut config, config_error) = if args.safe_mode {
+        (rio_backend::config::Config::default(), None)
+    } else {
+        match rio_backend::config::Config::try_load() {
+            Ok(config) => (config, None),
+            Err(err) => (rio_backend::config::Config::default(), Some(err)),
+        }
     };
 
     // Read platform property and overwrite values per OS
@@ -195,12 +214,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     setup_environment_variables(&config);
+    startup.phase("config");
 
     let window_event_loop =
         rio_window::event_loop::EventLoop::<EventPayload>::with_user_event().build()?;
-
-    let mut application =
-        crate::application::Application::new(config, config_error, &window_event_loop);
+    startup.phase("event_loop");
+
+    let mut application = crate::application::Application::new(
+        config,
+        config_error,
+        args.safe_mode,
+        startup,
+        &window_event_loop,
+    );
     let _ = application.run(window_event_loop);
 
     #[cfg(windows)]
@@ -1,4 +1,5 @@
 use crate::event::Msg;
+use rio_backend::event::WindowId;
 use std::borrow::Cow;
 use teletypewriter::WinsizeBuilder;
 
@@ -27,6 +28,21 @@ impl Messenger {
         let _ = self.channel.send(Msg::Input(bytes));
     }
 
+    #[inline]
+    pub fn send_latin1_fallback(&self, enabled: bool) {
+        let _ = self.channel.send(Msg::Latin1Fallback(enabled));
+    }
+
+    #[inline]
+    pub fn send_migrate(&self, window_id: WindowId, route_id: usize) {
+        let _ = self.channel.send(Msg::Migrate(window_id, route_id));
+    }
+
+    #[inline]
+    pub fn send_shutdown(&self) {
+        let _ = self.channel.send(Msg::Shutdown);
+    }
+
     #[inline]
     pub fn send_resize(&self, new_size: WinsizeBuilder) -> Result<&str, String> {
         match self.channel.send(Msg::Resize(new_size)) {
@@ -2,13 +2,14 @@
 // https://github.com/alacritty/alacritty/blob/94ede16ee4af8869fd6415b3530c7e12c8681578/alacritty/src/macos/locale.rs#L50
 // which is licensed under Apache 2.0 license.
 
-use std::ffi::{CStr, CString};
+use std::ffi::{c_void, CStr, CString};
 use std::os::raw::c_char;
 use std::{env, slice, str};
 
 use libc::{setlocale, LC_ALL, LC_CTYPE};
 use objc::runtime::{Class, Object};
 use objc::{msg_send, sel, sel_impl};
+use rio_backend::config::colors::ColorRgb;
 use tracing::debug;
 const FALLBACK_LOCALE: &str = "UTF-8";
 
@@ -91,8 +92,130 @@ fn system_locale() -> String {
     }
 }
 
+/// Runs a modal critical `NSAlert`.
+pub fn show_error_alert(title: &str, message: &str) {
+    let Some(alert_class) = Class::get("NSAlert") else {
+        return;
+    };
+
+    unsafe {
+        let alert: *mut Object = msg_send![alert_class, new];
+        let title = nsstring_from_str(title);
+        let message = nsstring_from_str(message);
+
+        let _: () = msg_send![alert, setMessageText: title];
+        let _: () = msg_send![alert, setInformativeText: message];
+        // NSAlertStyleCritical
+        let _: () = msg_send![alert, setAlertStyle: 2usize];
+        let _: isize = msg_send![alert, runModal];
+
+        let _: () = msg_send![title, release];
+        let _: () = msg_send![message, release];
+        let _: () = msg_send![alert, release];
+    }
+}
+
+/// Stores the text along with its HTML and RTF flavors in the general
+/// pasteboard, returns false if any of them could not be stored.
+pub fn set_rich_clipboard(text: &str, html: &str, rtf: &str) -> bool {
+    let Some(pasteboard_class) = Class::get("NSPasteboard") else {
+        return false;
+    };
+
+    unsafe {
+        let pasteboard: *mut Object = msg_send![pasteboard_class, generalPasteboard];
+        let _: isize = msg_send![pasteboard, clearContents];
+
+        let mut stored = true;
+        for (contents, pasteboard_type) in [
+            (text, "public.utf8-plain-text"),
+            (html, "public.html"),
+            (rtf, "public.rtf"),
+        ] {
+            let contents = nsstring_from_str(contents);
+            let pasteboard_type = nsstring_from_str(pasteboard_type);
+            let is_set: bool =
+                msg_send![pasteboard, setString: contents forType: pasteboard_type];
+            stored &= is_set;
+
+            let _: () = msg_send![contents, release];
+            let _: () = msg_send![pasteboard_type, release];
+        }
+
+        stored
+    }
+}
+
+/// Accent color chosen in the system settings, available on macOS 10.14+.
+pub fn accent_color() -> Option<ColorRgb> {
+    let color_class = Class::get("NSColor")?;
+    let color_space_class = Class::get("NSColorSpace")?;
+
+    unsafe {
+        let is_supported: bool =
+            msg_send![color_class, respondsToSelector: sel!(controlAccentColor)];
+        if !is_supported {
+            return None;
+        }
+
+        // The components can only be read once converted to a RGB space
+        let color: *mut Object = msg_send![color_class, controlAccentColor];
+        let srgb: *mut Object = msg_send![color_space_class, sRGBColorSpace];
+        let color: *mut Object = msg_send![color, colorUsingColorSpace: srgb];
+        if color.is_null() {
+            return None;
+        }
+
+        let red: f64 = msg_send![color, redComponent];
+        let green: f64 = msg_send![color, greenComponent];
+        let blue: f64 = msg_send![color, blueComponent];
+        let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Some(ColorRgb {
+            r: channel(red),
+            g: channel(green),
+            b: channel(blue),
+        })
+    }
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> *const c_void;
+    fn IOPSGetProvidingPowerSourceType(snapshot: *const c_void) -> *const Object;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(cf: *const c_void);
+}
+
+/// Returns true if the power is provided by the battery, read from IOKit
+/// so it is cheap enough to be polled from the main thread.
+pub fn is_on_battery() -> bool {
+    unsafe {
+        let snapshot = IOPSCopyPowerSourcesInfo();
+        if snapshot.is_null() {
+            return false;
+        }
+
+        // The type is owned by the snapshot, "AC Power", "Battery Power"
+        // or "UPS Power"
+        let source_type = IOPSGetProvidingPowerSourceType(snapshot);
+        let on_battery =
+            !source_type.is_null() && nsstring_as_str(source_type) == "Battery Power";
+        CFRelease(snapshot);
+        on_battery
+    }
+}
+
 const UTF8_ENCODING: usize = 4;
 
+unsafe fn nsstring_from_str(value: &str) -> *mut Object {
+    let value = CString::new(value.replace('\0', "")).unwrap_or_default();
+    let nsstring: *mut Object = msg_send![Class::get("NSString").unwrap(), alloc];
+    msg_send![nsstring, initWithUTF8String: value.as_ptr()]
+}
+
 unsafe fn nsstring_as_str<'a>(nsstring: *const Object) -> &'a str {
     let cstr: *const c_char = msg_send![nsstring, UTF8String];
     let len: usize = msg_send![nsstring, lengthOfBytesUsingEncoding: UTF8_ENCODING];
@@ -1,2 +1,329 @@
 #[cfg(target_os = "macos")]
 pub mod macos;
+
+use rio_backend::config::colors::ColorRgb;
+
+/// Shows a blocking native error dialog, used when there is no renderer
+/// available to display the error inside a Rio window.
+pub fn show_error_dialog(title: &str, message: &str) {
+    tracing::error!("{title}: {message}");
+
+    #[cfg(target_os = "macos")]
+    macos::show_error_alert(title, message);
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            MessageBoxW, MB_ICONERROR, MB_OK, MB_SETFOREGROUND, MB_TASKMODAL,
+        };
+
+        unsafe {
+            MessageBoxW(
+                std::ptr::null_mut(),
+                crate::panic::win32_string(message).as_ptr(),
+                crate::panic::win32_string(title).as_ptr(),
+                MB_ICONERROR | MB_OK | MB_SETFOREGROUND | MB_TASKMODAL,
+            );
+        }
+    }
+
+    // There is no native dialog API on other unix systems, so the
+    // most common dialog utilities are tried in order.
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        use std::process::Command;
+
+        let shown = Command::new("zenity")
+            .args([
+                "--error",
+                "--no-markup",
+                "--title",
+                title,
+                "--text",
+                message,
+            ])
+            .status()
+            .or_else(|_| {
+                Command::new("kdialog")
+                    .args(["--title", title, "--error", message])
+                    .status()
+            })
+            .or_else(|_| Command::new("xmessage").args(["-center", message]).status())
+            .is_ok();
+
+        if !shown {
+            eprintln!("{title}: {message}");
+        }
+    }
+}
+
+/// Reads the text aloud with the system text to speech, the speech runs
+/// in the background.
+pub fn speak(text: &str) {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // The text is written to stdin, so it is never parsed as arguments
+    #[cfg(target_os = "macos")]
+    let spawned = Command::new("say").stdin(Stdio::piped()).spawn();
+
+    #[cfg(windows)]
+    let spawned = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "Add-Type -AssemblyName System.Speech; \
+             (New-Object System.Speech.Synthesis.SpeechSynthesizer)\
+             .Speak([Console]::In.ReadToEnd())",
+        ])
+        .stdin(Stdio::piped())
+        .spawn();
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let spawned = Command::new("spd-say")
+        .args(["--wait", "--pipe-mode"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .or_else(|_| {
+            Command::new("espeak")
+                .arg("--stdin")
+                .stdin(Stdio::piped())
+                .spawn()
+        });
+
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            tracing::error!("unable to start text to speech: {err}");
+            return;
+        }
+    };
+
+    let text = text.to_owned();
+    std::thread::spawn(move || {
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        let _ = child.wait();
+    });
+}
+
+/// Stores the text along with its HTML and RTF flavors in the clipboard,
+/// returns false if the platform clipboard only takes plain text.
+#[cfg(target_os = "macos")]
+pub fn set_rich_clipboard(text: &str, html: &str, rtf: &str) -> bool {
+    macos::set_rich_clipboard(text, html, rtf)
+}
+
+/// Stores the text along with its HTML and RTF flavors in the clipboard,
+/// returns false if the platform clipboard only takes plain text.
+#[cfg(windows)]
+pub fn set_rich_clipboard(text: &str, html: &str, rtf: &str) -> bool {
+    use crate::panic::win32_string;
+    use windows_sys::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, OpenClipboard, RegisterClipboardFormatW,
+        SetClipboardData,
+    };
+    use windows_sys::Win32::System::Memory::{
+        GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE,
+    };
+
+    // Predefined format of the UTF-16 text
+    const CF_UNICODETEXT: u32 = 13;
+
+    let text: Vec<u8> = text
+        .encode_utf16()
+        .chain(Some(0))
+        .flat_map(u16::to_ne_bytes)
+        .collect();
+    let html = cf_html(html) + "\0";
+    let rtf = format!("{rtf}\0");
+
+    unsafe {
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            return false;
+        }
+        EmptyClipboard();
+
+        let html_format = RegisterClipboardFormatW(win32_string("HTML Format").as_ptr());
+        let rtf_format =
+            RegisterClipboardFormatW(win32_string("Rich Text Format").as_ptr());
+
+        let mut stored = true;
+        for (format, data) in [
+            (CF_UNICODETEXT, text.as_slice()),
+            (html_format, html.as_bytes()),
+            (rtf_format, rtf.as_bytes()),
+        ] {
+            let memory = GlobalAlloc(GMEM_MOVEABLE, data.len());
+            if memory.is_null() {
+                stored = false;
+                continue;
+            }
+
+            let pointer = GlobalLock(memory) as *mut u8;
+            std::ptr::copy_nonoverlapping(data.as_ptr(), pointer, data.len());
+            GlobalUnlock(memory);
+
+            // The clipboard owns the memory once it's stored
+            if SetClipboardData(format, memory).is_null() {
+                GlobalFree(memory);
+                stored = false;
+            }
+        }
+
+        CloseClipboard();
+        stored
+    }
+}
+
+/// Adds the header of the Windows HTML clipboard format, which has the
+/// byte offsets of the document and of the fragment that was copied.
+#[cfg(windows)]
+fn cf_html(html: &str) -> String {
+    const START_FRAGMENT: &str = "<!--StartFragment-->";
+    const END_FRAGMENT: &str = "<!--EndFragment-->";
+    // Length of the header, the offsets are always written with 10 digits
+    const HEADER_LENGTH: usize = 105;
+
+    let body_start = html
+        .find("<body>")
+        .map_or(0, |index| index + "<body>".len());
+    let body_end = html.rfind("</body>").unwrap_or(html.len()).max(body_start);
+    let document = format!(
+        "{}{START_FRAGMENT}{}{END_FRAGMENT}{}",
+        &html[..body_start],
+        &html[body_start..body_end],
+        &html[body_end..]
+    );
+
+    let start_fragment = HEADER_LENGTH + body_start + START_FRAGMENT.len();
+    let end_fragment = start_fragment + body_end - body_start;
+    format!(
+        "Version:0.9\r\nStartHTML:{HEADER_LENGTH:010}\r\nEndHTML:{:010}\r\n\
+         StartFragment:{start_fragment:010}\r\nEndFragment:{end_fragment:010}\r\n\
+         {document}",
+        HEADER_LENGTH + document.len()
+    )
+}
+
+/// Stores the text along with its HTML and RTF flavors in the clipboard,
+/// returns false if the platform clipboard only takes plain text.
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn set_rich_clipboard(_text: &str, _html: &str, _rtf: &str) -> bool {
+    // The X11 and Wayland clipboards only get plain text from copypasta
+    false
+}
+
+/// Accent color chosen in the system settings.
+#[cfg(target_os = "macos")]
+pub fn accent_color() -> Option<ColorRgb> {
+    macos::accent_color()
+}
+
+/// Accent color chosen in the system settings.
+#[cfg(windows)]
+pub fn accent_color() -> Option<ColorRgb> {
+    use crate::panic::win32_string;
+    use windows_sys::Win32::System::Registry::{
+        RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD,
+    };
+
+    let mut value: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            win32_string("Software\\Microsoft\\Windows\\DWM").as_ptr(),
+            win32_string("AccentColor").as_ptr(),
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            &mut value as *mut u32 as *mut std::ffi::c_void,
+            &mut size,
+        )
+    };
+    // ERROR_SUCCESS
+    if status != 0 {
+        return None;
+    }
+
+    // Stored as 0xAABBGGRR
+    Some(ColorRgb {
+        r: value as u8,
+        g: (value >> 8) as u8,
+        b: (value >> 16) as u8,
+    })
+}
+
+/// Accent color chosen in the system settings.
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn accent_color() -> Option<ColorRgb> {
+    None
+}
+
+/// Returns true if the system is running on battery power.
+#[cfg(target_os = "macos")]
+pub fn is_on_battery() -> bool {
+    macos::is_on_battery()
+}
+
+/// Returns true if the system is running on battery power.
+#[cfg(windows)]
+pub fn is_on_battery() -> bool {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    // ACLineStatus is 0 when offline, 1 when online and 255 when unknown
+    unsafe { GetSystemPowerStatus(&mut status) != 0 && status.ACLineStatus == 0 }
+}
+
+/// Returns true if the system is running on battery power.
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn is_on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+
+    let read = |path: &std::path::Path, name: &str| {
+        std::fs::read_to_string(path.join(name))
+            .map(|value| value.trim().to_owned())
+            .unwrap_or_default()
+    };
+
+    supplies.flatten().any(|supply| {
+        let path = supply.path();
+        read(&path, "type") == "Battery" && read(&path, "status") == "Discharging"
+    })
+}
+
+/// Offset of the local time from UTC in seconds, including daylight saving.
+#[cfg(windows)]
+pub fn local_utc_offset() -> i64 {
+    use windows_sys::Win32::System::Time::{
+        GetTimeZoneInformation, TIME_ZONE_ID_INVALID, TIME_ZONE_INFORMATION,
+    };
+
+    // Returned when daylight saving is in effect
+    const TIME_ZONE_ID_DAYLIGHT: u32 = 2;
+
+    let mut info: TIME_ZONE_INFORMATION = unsafe { std::mem::zeroed() };
+    let bias = match unsafe { GetTimeZoneInformation(&mut info) } {
+        TIME_ZONE_ID_INVALID => return 0,
+        TIME_ZONE_ID_DAYLIGHT => info.Bias + info.DaylightBias,
+        _ => info.Bias + info.StandardBias,
+    };
+    // The bias is in minutes and goes from local time to UTC
+    -(bias as i64) * 60
+}
+
+/// Offset of the local time from UTC in seconds, including daylight saving.
+#[cfg(not(windows))]
+pub fn local_utc_offset() -> i64 {
+    let now = unsafe { libc::time(std::ptr::null_mut()) };
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+        return 0;
+    }
+    tm.tm_gmtoff as i64
+}
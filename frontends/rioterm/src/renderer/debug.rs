@@ -0,0 +1,177 @@
+use crate::screen::bell::BellStats;
+use rio_backend::ansi::graphics::GraphicsMemoryStats;
+use rio_backend::config::colors::Colors;
+use rio_backend::sugarloaf::{
+    FragmentStyle, FrameStats, GlyphCacheStats, Object, Quad, RichText, Sugarloaf,
+};
+use std::time::{Duration, Instant};
+
+/// Environment variable that enables the overlay at startup.
+pub const DEBUG_OVERLAY_ENV: &str = "RIO_DEBUG_OVERLAY";
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const OVERLAY_WIDTH: f32 = 200.0;
+const OVERLAY_HEIGHT: f32 = 156.0;
+const OVERLAY_MARGIN: f32 = 8.0;
+
+/// Frame time, frame count, drawn sections, glyph cache and PTY throughput,
+/// the values shown are the averages of the last second. The graphics memory is the
+/// sum over all terminals and the bells are counted since the window opened.
+/// The variables are the ones the profiles set on the focused terminal.
+pub struct DebugOverlay {
+    sample_started: Instant,
+    frames: u32,
+    frame_time: Duration,
+    glyph_stats: GlyphCacheStats,
+    pty_bytes_read: u64,
+    pub bells: BellStats,
+    lines: [String; 9],
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            sample_started: Instant::now(),
+            frames: 0,
+            frame_time: Duration::ZERO,
+            glyph_stats: GlyphCacheStats::default(),
+            pty_bytes_read: 0,
+            bells: BellStats::default(),
+            lines: [
+                String::from("frame: -"),
+                String::from("frames/s: -"),
+                String::from("sections: -"),
+                String::from("glyph cache: -"),
+                String::from("pty: -"),
+                String::from("graphics: -"),
+                String::from("images: -"),
+                String::from("bells: -"),
+                String::from("env: -"),
+            ],
+        }
+    }
+
+    /// Records a rendered frame, the PTY counter is a total since startup.
+    pub fn record_frame(
+        &mut self,
+        frame_time: Duration,
+        frame: FrameStats,
+        pty_bytes_read: u64,
+        graphics: GraphicsMemoryStats,
+        env: &[(String, String)],
+    ) {
+        self.frames += 1;
+        self.frame_time += frame_time;
+        self.glyph_stats.hits += frame.glyph_cache.hits;
+        self.glyph_stats.misses += frame.glyph_cache.misses;
+
+        let elapsed = self.sample_started.elapsed();
+        if elapsed < SAMPLE_INTERVAL {
+            return;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        let frame_time = self.frame_time.as_secs_f64() * 1000.0 / self.frames as f64;
+        let pty_bytes = pty_bytes_read.saturating_sub(self.pty_bytes_read);
+
+        self.lines = [
+            format!("frame: {frame_time:.2}ms"),
+            format!("frames/s: {:.0}", self.frames as f64 / seconds),
+            format!(
+                "sections: {} text, {} quads, {} images",
+                frame.rich_texts, frame.quads, frame.images
+            ),
+            format!(
+                "glyph cache: {:.1}% ({} misses)",
+                self.glyph_stats.hit_rate() * 100.0,
+                self.glyph_stats.misses
+            ),
+            format!("pty: {}/s", format_bytes(pty_bytes as f64 / seconds)),
+            format!(
+                "graphics: {} of {}",
+                format_bytes(graphics.used as f64),
+                format_bytes(graphics.limit as f64)
+            ),
+            format!(
+                "images: {} ({} evicted)",
+                graphics.graphics + graphics.images,
+                graphics.evicted
+            ),
+            format!(
+                "bells: {} ({} muted, {} limited)",
+                self.bells.rung, self.bells.muted, self.bells.limited
+            ),
+            format_env(env),
+        ];
+
+        self.sample_started = Instant::now();
+        self.frames = 0;
+        self.frame_time = Duration::ZERO;
+        self.glyph_stats = GlyphCacheStats::default();
+        self.pty_bytes_read = pty_bytes_read;
+    }
+
+    #[inline]
+    pub fn draw(
+        &self,
+        objects: &mut Vec<Object>,
+        sugarloaf: &mut Sugarloaf,
+        colors: &Colors,
+        dimensions: (f32, f32, f32),
+    ) {
+        let (width, _, scale) = dimensions;
+        let position_x = (width / scale) - OVERLAY_WIDTH - OVERLAY_MARGIN;
+        let position_y = OVERLAY_MARGIN;
+
+        let rich_text_id = sugarloaf.create_temp_rich_text();
+        sugarloaf.set_rich_text_font_size(&rich_text_id, 12.0);
+
+        let style = FragmentStyle {
+            color: colors.foreground,
+            ..FragmentStyle::default()
+        };
+        let content = sugarloaf.content().sel(rich_text_id).clear();
+        for line in &self.lines {
+            content.new_line().add_text(line, style);
+        }
+        content.build();
+
+        objects.push(Object::Quad(
+            Quad::solid(
+                [position_x, position_y],
+                [OVERLAY_WIDTH, OVERLAY_HEIGHT],
+                colors.bar,
+            )
+            .with_border(1.0, colors.tabs_active_highlight)
+            .with_border_radius(4.0),
+        ));
+
+        objects.push(Object::RichText(RichText {
+            id: rich_text_id,
+            position: [position_x + 6., position_y + 2.],
+            lines: None,
+        }));
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1}MiB", bytes / (1024.0 * 1024.0))
+    } else if bytes >= 1024.0 {
+        format!("{:.1}KiB", bytes / 1024.0)
+    } else {
+        format!("{bytes:.0}B")
+    }
+}
+
+fn format_env(env: &[(String, String)]) -> String {
+    if env.is_empty() {
+        return String::from("env: -");
+    }
+
+    let vars: Vec<String> = env
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    format!("env: {}", vars.join(" "))
+}
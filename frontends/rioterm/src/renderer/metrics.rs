@@ -0,0 +1,135 @@
+//! Cache hits and rebuilds of the rendered frames grouped by minute, enabled
+//! by `developer.enable-metrics`. They are only kept in memory and written to
+//! `log/metrics.log` in the configuration directory by the `DumpMetrics`
+//! action, nothing is sent anywhere.
+
+use rio_backend::config::config_dir_path;
+use rio_backend::sugarloaf::{CacheStats, FrameStats};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Minutes kept, the older ones are dropped.
+const MAX_MINUTES: usize = 60;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+struct MinuteMetrics {
+    minute: u64,
+    frames: u64,
+    glyphs: CacheStats,
+    shaped_lines: CacheStats,
+    drawn_lines: CacheStats,
+    full_rebuilds: u64,
+    partial_rebuilds: u64,
+    atlas_overflows: u64,
+}
+
+pub struct Metrics {
+    started: Instant,
+    minutes: VecDeque<MinuteMetrics>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            minutes: VecDeque::with_capacity(MAX_MINUTES),
+        }
+    }
+
+    pub fn record_frame(&mut self, frame: &FrameStats) {
+        let minute = self.started.elapsed().as_secs() / 60;
+        self.record(minute, frame);
+    }
+
+    fn record(&mut self, minute: u64, frame: &FrameStats) {
+        if self.minutes.back().is_none_or(|last| last.minute != minute) {
+            if self.minutes.len() == MAX_MINUTES {
+                self.minutes.pop_front();
+            }
+            self.minutes.push_back(MinuteMetrics {
+                minute,
+                ..MinuteMetrics::default()
+            });
+        }
+
+        if let Some(current) = self.minutes.back_mut() {
+            current.frames += 1;
+            current.glyphs += frame.glyph_cache;
+            current.shaped_lines += frame.shaped_lines;
+            current.drawn_lines += frame.drawn_lines;
+            current.full_rebuilds += frame.full_rebuilds as u64;
+            current.partial_rebuilds += frame.partial_rebuilds as u64;
+            current.atlas_overflows += frame.atlas_overflows;
+        }
+    }
+
+    /// Table with a row per minute since the metrics were enabled, the
+    /// caches are shown as hit rate and misses.
+    pub fn report(&self) -> String {
+        let mut report = String::from(
+            "minute frames glyph-cache shaped-lines drawn-lines full-rebuilds partial-rebuilds atlas-overflows\n",
+        );
+        for minute in &self.minutes {
+            report.push_str(&format!(
+                "{} {} {} {} {} {} {} {}\n",
+                minute.minute,
+                minute.frames,
+                format_cache(minute.glyphs),
+                format_cache(minute.shaped_lines),
+                format_cache(minute.drawn_lines),
+                minute.full_rebuilds,
+                minute.partial_rebuilds,
+                minute.atlas_overflows,
+            ));
+        }
+        report
+    }
+
+    /// Writes the report next to the log file.
+    pub fn dump(&self) -> std::io::Result<PathBuf> {
+        let log_dir_path = config_dir_path().join("log");
+        std::fs::create_dir_all(&log_dir_path)?;
+        let path = log_dir_path.join("metrics.log");
+        std::fs::write(&path, self.report())?;
+        Ok(path)
+    }
+}
+
+#[inline]
+fn format_cache(stats: CacheStats) -> String {
+    format!("{:.1}%/{}", stats.hit_rate() * 100.0, stats.misses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_are_grouped_by_minute() {
+        let mut metrics = Metrics::new();
+        let frame = FrameStats {
+            glyph_cache: CacheStats { hits: 3, misses: 1 },
+            drawn_lines: CacheStats { hits: 9, misses: 1 },
+            full_rebuilds: 1,
+            partial_rebuilds: 2,
+            ..FrameStats::default()
+        };
+        metrics.record(0, &frame);
+        metrics.record(0, &frame);
+        metrics.record(2, &frame);
+
+        assert_eq!(
+            metrics.report(),
+            "minute frames glyph-cache shaped-lines drawn-lines full-rebuilds partial-rebuilds atlas-overflows\n\
+             0 2 75.0%/2 0.0%/0 90.0%/2 2 4 0\n\
+             2 1 75.0%/1 0.0%/0 90.0%/1 1 2 0\n"
+        );
+
+        for minute in 3..MAX_MINUTES as u64 + 3 {
+            metrics.record(minute, &frame);
+        }
+        assert_eq!(metrics.minutes.len(), MAX_MINUTES);
+        assert_eq!(metrics.minutes[0].minute, 3);
+    }
+}
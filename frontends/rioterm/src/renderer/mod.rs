@@ -1,4 +1,10 @@
+mod debug;
+mod metrics;
 pub mod navigation;
+mod output_indicator;
+mod password;
+mod screenshot;
+mod scroll_hint;
 mod search;
 pub mod utils;
 
@@ -9,47 +15,62 @@ use crate::crosswords::grid::row::Row;
 use crate::crosswords::pos::{Column, Line, Pos};
 use crate::crosswords::square::{Flags, Square};
 use crate::screen::hint::HintMatches;
+use debug::{DebugOverlay, DEBUG_OVERLAY_ENV};
+use metrics::Metrics;
 use navigation::ScreenNavigation;
-use rio_backend::ansi::graphics::UpdateQueues;
+use rio_backend::ansi::graphics::{GraphicsMemoryStats, UpdateQueues};
+use rio_backend::ansi::kitty_graphics::PLACEHOLDER;
 use rio_backend::config::colors::term::TermColors;
 use rio_backend::config::colors::{
     term::{List, DIM_FACTOR},
     AnsiColor, ColorArray, Colors, NamedColor,
 };
+use rio_backend::config::keyboard::OptionAsAlt;
+use rio_backend::config::unicode::{is_invisible, is_regional_indicator, CharWidth};
 use rio_backend::config::Config;
-use rio_backend::crosswords::TermDamage;
+use rio_backend::crosswords::search::SearchOptions;
+use rio_backend::crosswords::{Mode, RegionScroll, TermDamage};
 use rio_backend::event::EventProxy;
+use rio_backend::locale::Locale;
+use rio_backend::selection::SelectionRange;
 use rio_backend::sugarloaf::{
-    drawable_character, Content, FragmentStyle, FragmentStyleDecoration, Graphic,
-    Stretch, Style, SugarCursor, Sugarloaf, UnderlineInfo, UnderlineShape, Weight,
+    drawable_character, Content, DrawableChar, FragmentStyle, FragmentStyleDecoration,
+    Graphic, Object, Quad, Stretch, Style, SugarCursor, Sugarloaf, UnderlineInfo,
+    UnderlineShape, Weight,
 };
 use std::collections::HashMap;
 use std::ops::RangeInclusive;
+use std::time::Instant;
 
 use rustc_hash::FxHashMap;
-use unicode_width::UnicodeWidthChar;
 
 #[derive(Default)]
 pub struct Search {
     rich_text_id: Option<usize>,
     active_search: Option<String>,
+    /// Total number of matches and the position of the focused one.
+    match_count: Option<(usize, Option<usize>)>,
+    options: SearchOptions,
 }
 
 pub struct Renderer {
     is_vi_mode_enabled: bool,
     draw_bold_text_with_light_colors: bool,
     use_drawable_chars: bool,
+    char_width: CharWidth,
     pub named_colors: Colors,
     pub colors: List,
     pub navigation: ScreenNavigation,
     unfocused_split_opacity: f32,
     last_active: usize,
-    pub config_has_blinking_enabled: bool,
     pub config_blinking_interval: u64,
+    // Battery saver pauses cursor blinking and the rendering of
+    // background panes without pending updates
+    pub battery_saver: bool,
     ignore_selection_fg_color: bool,
     pub search: Search,
     #[allow(unused)]
-    pub option_as_alt: String,
+    pub option_as_alt: OptionAsAlt,
     #[allow(unused)]
     pub macos_use_unified_titlebar: bool,
     // Dynamic background keep track of the original bg color and
@@ -60,12 +81,44 @@ pub struct Renderer {
         (char, rio_backend::sugarloaf::font_introspector::Attributes),
         (usize, f32),
     >,
+    flag_cache: FxHashMap<
+        (
+            [char; 2],
+            rio_backend::sugarloaf::font_introspector::Attributes,
+        ),
+        (usize, f32),
+    >,
+    // Buffer used to accumulate fragments content while creating lines
+    line_content: String,
+    password_input_indicator: bool,
+    alternate_screen_hint: Option<String>,
+    // Labels of the new output indicator, for one and several lines
+    output_indicator: Option<(String, String)>,
+    // Where the new output indicator was drawn in the last frame
+    output_indicator_bounds: Option<[f32; 4]>,
+    window_border_width: f32,
+    // Until when the alternate screen hint is shown
+    scroll_hint_until: Option<Instant>,
+    // Keys of the multi-step binding being typed, shown until it expires
+    pub pending_chord: Option<(String, Instant)>,
+    cursor_beam_width: f32,
+    cursor_underline_height: f32,
+    cursor_unfocused_glyph: Option<char>,
+    is_window_focused: bool,
+    pub debug_overlay: Option<DebugOverlay>,
+    pub metrics: Option<Metrics>,
+    screenshot_dir: Option<String>,
+    is_screenshot_requested: bool,
+    is_rtl: bool,
+    // When the next frame of an animated graphic is due
+    next_animation_frame: Option<Instant>,
 }
 
 impl Renderer {
     pub fn new(
         config: &Config,
         font_context: &rio_backend::sugarloaf::font::FontLibrary,
+        locale: &Locale,
     ) -> Renderer {
         let colors = List::from(&config.colors);
         let named_colors = config.colors;
@@ -94,12 +147,25 @@ impl Renderer {
             unfocused_split_opacity: config.navigation.unfocused_split_opacity,
             last_active: 0,
             use_drawable_chars: config.fonts.use_drawable_chars,
+            char_width: CharWidth::new(&config.unicode),
             draw_bold_text_with_light_colors: config.draw_bold_text_with_light_colors,
             macos_use_unified_titlebar: config.window.macos_use_unified_titlebar,
             config_blinking_interval: config.cursor.blinking_interval.clamp(350, 1200),
-            option_as_alt: config.option_as_alt.to_lowercase(),
+            option_as_alt: config.option_as_alt(),
             is_vi_mode_enabled: false,
-            config_has_blinking_enabled: config.cursor.blinking,
+            cursor_beam_width: config.cursor.beam_width,
+            cursor_underline_height: config.cursor.underline_height,
+            cursor_unfocused_glyph: config.cursor.unfocused_glyph,
+            is_window_focused: true,
+            debug_overlay: (config.developer.enable_fps_counter
+                || std::env::var_os(DEBUG_OVERLAY_ENV).is_some())
+            .then(DebugOverlay::new),
+            metrics: config.developer.enable_metrics.then(Metrics::new),
+            screenshot_dir: config.screenshot_dir.clone(),
+            is_screenshot_requested: false,
+            is_rtl: locale.is_rtl(),
+            next_animation_frame: None,
+            battery_saver: false,
             ignore_selection_fg_color: config.ignore_selection_fg_color,
             colors,
             navigation: ScreenNavigation::new(
@@ -111,15 +177,61 @@ impl Renderer {
             dynamic_background,
             search: Search::default(),
             font_cache: FxHashMap::default(),
+            flag_cache: FxHashMap::default(),
+            line_content: String::default(),
+            password_input_indicator: config.password_input_indicator,
+            alternate_screen_hint: config
+                .scroll
+                .alternate_screen_hint
+                .then(|| locale.get("alternate-screen-scroll-hint").to_owned()),
+            scroll_hint_until: None,
+            pending_chord: None,
+            output_indicator: config.scroll.output_indicator.then(|| {
+                (
+                    locale.get("new-output-line").to_owned(),
+                    locale.get("new-output-lines").to_owned(),
+                )
+            }),
+            output_indicator_bounds: None,
+            window_border_width: config.window.border_width.max(0.0),
             font_context: font_context.clone(),
         }
     }
 
+    /// Opacity of the background, `None` with a background image.
+    #[inline]
+    pub fn opacity(&self) -> Option<f32> {
+        if self.dynamic_background.2 && self.dynamic_background.1.a == 0.0 {
+            None
+        } else {
+            Some(self.dynamic_background.1.a as f32)
+        }
+    }
+
+    #[inline]
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.dynamic_background.1.a = opacity as f64;
+        self.dynamic_background.2 = opacity < 1.;
+    }
+
     #[inline]
     pub fn set_active_search(&mut self, active_search: Option<String>) {
         self.search.active_search = active_search;
     }
 
+    #[inline]
+    pub fn set_search_options(&mut self, options: SearchOptions) {
+        self.search.options = options;
+    }
+
+    #[inline]
+    pub fn set_search_match_count(
+        &mut self,
+        match_count: Option<(usize, Option<usize>)>,
+    ) {
+        self.search.match_count = match_count;
+    }
+
     #[inline]
     fn create_style(
         &mut self,
@@ -235,6 +347,29 @@ impl Renderer {
         (decoration, decoration_color)
     }
 
+    /// Font and width of each glyph of the flag. Emoji fonts shape the pair
+    /// into one glyph, otherwise the two letters share the cells of the flag.
+    fn flag_font(&mut self, flag: [char; 2], style: &FragmentStyle) -> (usize, f32) {
+        if let Some(cached) = self.flag_cache.get(&(flag, style.font_attrs)) {
+            return *cached;
+        }
+
+        let mut font_ctx = self.font_context.inner.lock();
+        let result = match font_ctx.find_flag_font_match(flag) {
+            Some(font_id) => (font_id, self.char_width.emoji_width().cells() as f32),
+            None => {
+                let font_id = font_ctx
+                    .find_best_font_match(flag[0], style)
+                    .map_or(0, |(font_id, _)| font_id);
+                (font_id, self.char_width.emoji_width().cells() as f32 / 2.0)
+            }
+        };
+        drop(font_ctx);
+
+        self.flag_cache.insert((flag, style.font_attrs), result);
+        result
+    }
+
     #[inline]
     #[allow(clippy::too_many_arguments)]
     fn create_line(
@@ -247,15 +382,18 @@ impl Renderer {
         renderable_content: &RenderableContent,
         search_hints: &mut Option<HintMatches>,
         focused_match: &Option<RangeInclusive<Pos>>,
+        hint_labels: &[(Pos, &[char])],
         term_colors: &TermColors,
         is_active: bool,
     ) {
         // let start = std::time::Instant::now();
         let cursor = &renderable_content.cursor;
-        let hyperlink_range = renderable_content.hyperlink_range;
+        let hovered_hyperlink = renderable_content.hovered_hyperlink.as_ref();
         let selection_range = renderable_content.selection_range;
         let columns: usize = row.len();
-        let mut content = String::default();
+        // Reuse the buffer from previous lines to avoid allocating on every line
+        let mut content = std::mem::take(&mut self.line_content);
+        content.clear();
         let mut last_char_was_space = false;
         let mut last_style = FragmentStyle::default();
 
@@ -273,11 +411,27 @@ impl Renderer {
                     self.create_style(square, term_colors)
                 };
 
-            if hyperlink_range.is_some()
-                && square.hyperlink().is_some()
-                && hyperlink_range
-                    .unwrap()
-                    .contains(Pos::new(line, Column(column)))
+            // Kitty placeholders only show the image attached to the cell
+            let square_content = if square_content == PLACEHOLDER {
+                ' '
+            } else {
+                square_content
+            };
+
+            // Hint labels are drawn over the start of their match
+            let hint_label = hint_labels.iter().find_map(|(pos, label)| {
+                if pos.row != line || column < pos.col.0 {
+                    return None;
+                }
+                label.get(column - pos.col.0).copied()
+            });
+            let square_content = hint_label.unwrap_or(square_content);
+
+            if hint_label.is_some() {
+                style.color = self.named_colors.hint_foreground;
+                style.background_color = Some(self.named_colors.hint_background);
+            } else if hovered_hyperlink.is_some()
+                && square.hyperlink().as_ref() == hovered_hyperlink
             {
                 style.decoration =
                     Some(FragmentStyleDecoration::Underline(UnderlineInfo {
@@ -340,6 +494,7 @@ impl Renderer {
                     id: graphic.texture.id,
                     offset_x: graphic.offset_x,
                     offset_y: graphic.offset_y,
+                    z_index: graphic.texture.z_index,
                 });
                 style.background_color = None;
             }
@@ -350,34 +505,59 @@ impl Renderer {
             //
             // TODO: In the future it should use same logic to render everything
             // at once.
-            if self.use_drawable_chars {
-                if let Some(character) = drawable_character(square_content) {
-                    style.drawable_char = Some(character);
-                    if !content.is_empty() {
-                        if let Some(line) = line_opt {
-                            builder.add_text_on_line(line, &content, last_style);
-                        } else {
-                            builder.add_text(&content, last_style);
-                        }
-                        content.clear();
+            //
+            // Invisible characters only get a cell of their own when
+            // `unicode.zero-width` shows them, as a box with the code point.
+            let drawable_char = if is_invisible(square_content) {
+                Some(DrawableChar::HexBox(square_content as u32))
+            } else if self.use_drawable_chars {
+                drawable_character(square_content)
+            } else {
+                None
+            };
+            if let Some(character) = drawable_char {
+                style.drawable_char = Some(character);
+                if !content.is_empty() {
+                    if let Some(line) = line_opt {
+                        builder.add_text_on_line(line, &content, last_style);
+                    } else {
+                        builder.add_text(&content, last_style);
                     }
+                    content.clear();
+                }
 
-                    last_style = style;
+                last_style = style;
 
-                    // Ignore font shaping
-                    content.push(' ');
-                }
+                // Ignore font shaping
+                content.push(' ');
             }
 
+            // A pair of regional indicators is a flag, kept in a single cell
+            let flag = match square.zerowidth() {
+                Some(&[second])
+                    if square_content == square.c
+                        && is_regional_indicator(square.c)
+                        && is_regional_indicator(second) =>
+                {
+                    Some([square.c, second])
+                }
+                _ => None,
+            };
+
             let has_drawable_char = style.drawable_char.is_some();
             if !has_drawable_char {
-                if let Some((font_id, width)) =
+                if let Some(flag) = flag {
+                    (style.font_id, style.width) = self.flag_font(flag, &style);
+                } else if let Some((font_id, width)) =
                     self.font_cache.get(&(square_content, style.font_attrs))
                 {
                     style.font_id = *font_id;
                     style.width = *width;
                 } else {
-                    let mut width = square.c.width().unwrap_or(1) as f32;
+                    // Every cell advances by at least one column, even when
+                    // its character has no width on its own
+                    let mut width =
+                        self.char_width.width(square.c).unwrap_or(1).max(1) as f32;
                     let mut font_ctx = self.font_context.inner.lock();
 
                     // There is no simple way to define what's emoji
@@ -388,13 +568,13 @@ impl Renderer {
                     // like "◼" would be valid emojis. For a terminal context,
                     // the character "◼" is not an emoji and should be treated as
                     // single width. So, we completely rely on what font is
-                    // being used and then set width 2 for it.
+                    // being used and then set the configured emoji width for it.
                     if let Some((font_id, is_emoji)) =
                         font_ctx.find_best_font_match(square_content, &style)
                     {
                         style.font_id = font_id;
                         if is_emoji {
-                            width = 2.0;
+                            width = self.char_width.emoji_cells(square.c) as f32;
                         }
                     }
                     style.width = width;
@@ -446,6 +626,9 @@ impl Renderer {
                 }
 
                 content.push(square_content);
+                if let Some(flag) = flag {
+                    content.push(flag[1]);
+                }
             }
 
             // Render last column and break row
@@ -468,6 +651,8 @@ impl Renderer {
             builder.new_line();
         }
 
+        self.line_content = content;
+
         // let duration = start.elapsed();
         // println!(
         //     "Time elapsed in --renderer.update.create_line() is: {:?}",
@@ -587,13 +772,15 @@ impl Renderer {
             std::mem::swap(&mut background_color, &mut color);
         }
 
+        // Unfocused panes and windows draw a hollow cursor
+        let is_focused = is_active && self.is_window_focused;
+        let is_filled_block = cursor.state.content == CursorShape::Block && is_focused;
+
         let has_dynamic_background = self.dynamic_background.2
             && background_color[0] == self.dynamic_background.0[0]
             && background_color[1] == self.dynamic_background.0[1]
             && background_color[2] == self.dynamic_background.0[2];
-        let background_color = if has_dynamic_background
-            && (cursor.state.content != CursorShape::Block && is_active)
-        {
+        let background_color = if has_dynamic_background && !is_filled_block {
             None
         } else {
             Some(background_color)
@@ -601,10 +788,7 @@ impl Renderer {
 
         // If IME is or cursor is block enabled, put background color
         // when cursor is over the character
-        match (
-            cursor.is_ime_enabled,
-            (cursor.state.content == CursorShape::Block || !is_active),
-        ) {
+        match (cursor.is_ime_enabled, is_filled_block) {
             (_, true) => {
                 color = self.named_colors.background.0;
             }
@@ -633,32 +817,74 @@ impl Renderer {
 
         match cursor.state.content {
             CursorShape::Underline => {
-                style.decoration =
-                    Some(FragmentStyleDecoration::Underline(UnderlineInfo {
-                        offset: 0.0,
-                        size: 3.0,
-                        is_doubled: false,
-                        shape: UnderlineShape::Regular,
-                    }));
-                style.decoration_color = Some(cursor_color);
+                style.decoration = None;
+                style.cursor = Some(SugarCursor::Underline(
+                    cursor_color,
+                    self.cursor_underline_height,
+                ));
             }
             CursorShape::Block => {
                 style.cursor = Some(SugarCursor::Block(cursor_color));
             }
             CursorShape::Beam => {
-                style.cursor = Some(SugarCursor::Caret(cursor_color));
+                style.cursor =
+                    Some(SugarCursor::Caret(cursor_color, self.cursor_beam_width));
             }
             CursorShape::Hidden => {}
         }
 
-        if !is_active {
+        if !is_focused {
             style.decoration = None;
+            if let Some(glyph) = self.cursor_unfocused_glyph {
+                style.cursor = None;
+                style.color = cursor_color;
+                return (style, glyph);
+            }
             style.cursor = Some(SugarCursor::HollowBlock(cursor_color));
         }
 
         (style, content)
     }
 
+    /// Unfocused windows draw the cursor of the active pane as hollow.
+    #[inline]
+    pub fn set_window_focused(&mut self, is_window_focused: bool) {
+        self.is_window_focused = is_window_focused;
+    }
+
+    // The hollow cursor of unfocused windows doesn't blink, so the
+    // blinking isn't scheduled until the focus is back
+    #[inline]
+    fn is_cursor_blinking(&self, blinking_cursor: bool) -> bool {
+        blinking_cursor && !self.battery_saver && self.is_window_focused
+    }
+
+    #[inline]
+    pub fn toggle_debug_overlay(&mut self) {
+        self.debug_overlay = match self.debug_overlay {
+            Some(_) => None,
+            None => Some(DebugOverlay::new()),
+        };
+    }
+
+    /// Saves the next rendered frame as a PNG.
+    #[inline]
+    pub fn request_screenshot(&mut self) {
+        self.is_screenshot_requested = true;
+    }
+
+    /// When the next frame of an animated graphic in the rendered panes is
+    /// due.
+    #[inline]
+    pub fn next_animation_frame(&self) -> Option<Instant> {
+        self.next_animation_frame
+    }
+
+    /// Unique path for a file written by the screenshot and export actions.
+    pub fn output_path(&self, extension: &str) -> std::path::PathBuf {
+        screenshot::unique_path(&screenshot::directory(&self.screenshot_dir), extension)
+    }
+
     #[inline]
     pub fn set_vi_mode(&mut self, is_vi_mode_enabled: bool) {
         self.is_vi_mode_enabled = is_vi_mode_enabled;
@@ -674,22 +900,31 @@ impl Renderer {
     fn update_search_rich_text(&mut self, content: &mut Content) {
         if let Some(active_search_content) = &self.search.active_search {
             if let Some(search_rich_text) = self.search.rich_text_id {
+                let muted_style = FragmentStyle {
+                    color: [
+                        self.named_colors.foreground[0],
+                        self.named_colors.foreground[1],
+                        self.named_colors.foreground[2],
+                        self.named_colors.foreground[3] - 0.3,
+                    ],
+                    ..FragmentStyle::default()
+                };
+                let mut modes = String::new();
+                if !self.search.options.smart_case {
+                    modes.push_str("  [case sensitive]");
+                }
+                if self.search.options.whole_word {
+                    modes.push_str("  [whole word]");
+                }
+
                 if active_search_content.is_empty() {
                     content
                         .sel(search_rich_text)
                         .clear()
                         .new_line()
                         .add_text(
-                            &String::from("Search: type something..."),
-                            FragmentStyle {
-                                color: [
-                                    self.named_colors.foreground[0],
-                                    self.named_colors.foreground[1],
-                                    self.named_colors.foreground[2],
-                                    self.named_colors.foreground[3] - 0.3,
-                                ],
-                                ..FragmentStyle::default()
-                            },
+                            &format!("Search: type something...{modes}"),
+                            muted_style,
                         )
                         .build();
                 } else {
@@ -707,7 +942,8 @@ impl Renderer {
                             style.font_id = *font_id;
                             style.width = *width;
                         } else {
-                            let mut width = character.width().unwrap_or(1) as f32;
+                            let mut width =
+                                self.char_width.width(character).unwrap_or(1) as f32;
                             let mut font_ctx = self.font_context.inner.lock();
 
                             // Note we don't update cache from search bar
@@ -716,7 +952,7 @@ impl Renderer {
                             {
                                 style.font_id = font_id;
                                 if is_emoji {
-                                    width = 2.0;
+                                    width = self.char_width.emoji_cells(character) as f32;
                                 }
                             }
                             style.width = width;
@@ -730,20 +966,113 @@ impl Renderer {
                         );
                     }
 
+                    let status = match self.search.match_count {
+                        Some((0, _)) => format!("  no matches{modes}"),
+                        Some((total, Some(index))) => {
+                            format!("  {index} of {total}{modes}")
+                        }
+                        Some((total, None)) => format!("  {total} matches{modes}"),
+                        None => modes,
+                    };
+                    if !status.is_empty() {
+                        line.add_text_on_line(1, &status, muted_style);
+                    }
+
                     line.build();
                 }
             }
         }
     }
 
+    /// Shows the alternate screen hint for a while, returns whether it
+    /// was hidden before.
+    pub fn show_scroll_hint(&mut self) -> bool {
+        if self.alternate_screen_hint.is_none() {
+            return false;
+        }
+
+        let was_hidden = self.scroll_hint_until.is_none();
+        self.scroll_hint_until = Some(Instant::now() + scroll_hint::SCROLL_HINT_DURATION);
+        was_hidden
+    }
+
+    /// Whether the new output indicator was drawn at the position, in
+    /// logical pixels.
+    pub fn is_output_indicator_at(&self, x: f32, y: f32) -> bool {
+        self.output_indicator_bounds
+            .is_some_and(|[left, top, width, height]| {
+                x >= left && x < left + width && y >= top && y < top + height
+            })
+    }
+
+    #[inline]
+    fn create_scroll_hint_rich_text(
+        &self,
+        sugarloaf: &mut Sugarloaf,
+        text: &str,
+    ) -> usize {
+        let rich_text_id = sugarloaf.create_temp_rich_text();
+        sugarloaf.set_rich_text_font_size(&rich_text_id, 12.0);
+
+        let style = FragmentStyle {
+            color: self.named_colors.foreground,
+            ..FragmentStyle::default()
+        };
+        sugarloaf
+            .content()
+            .sel(rich_text_id)
+            .clear()
+            .new_line()
+            .add_text(text, style)
+            .build();
+
+        rich_text_id
+    }
+
+    #[inline]
+    fn create_password_indicator_rich_text(
+        &mut self,
+        sugarloaf: &mut Sugarloaf,
+    ) -> usize {
+        let rich_text_id = sugarloaf.create_temp_rich_text();
+        sugarloaf.set_rich_text_font_size(&rich_text_id, 12.0);
+
+        let mut style = FragmentStyle {
+            color: self.named_colors.foreground,
+            width: 2.0,
+            ..FragmentStyle::default()
+        };
+        if let Some((font_id, _)) = self
+            .font_context
+            .inner
+            .lock()
+            .find_best_font_match(password::PASSWORD_INDICATOR, &style)
+        {
+            style.font_id = font_id;
+        }
+
+        sugarloaf
+            .content()
+            .sel(rich_text_id)
+            .clear()
+            .new_line()
+            .add_text(&password::PASSWORD_INDICATOR.to_string(), style)
+            .build();
+
+        rich_text_id
+    }
+
     #[inline]
     pub fn run(
         &mut self,
         sugarloaf: &mut Sugarloaf,
         context_manager: &mut ContextManager<EventProxy>,
         hints: &mut Option<HintMatches>,
-        focused_match: &Option<RangeInclusive<Pos>>,
+        focused_match: &mut Option<RangeInclusive<Pos>>,
+        hint_labels: &[(Pos, &[char])],
     ) {
+        let frame_started = Instant::now();
+
         // In case rich text for search was not created
         let has_search = self.search.active_search.is_some();
         if has_search && self.search.rich_text_id.is_none() {
@@ -753,6 +1082,7 @@ impl Renderer {
         }
 
         let mut graphic_queues: Option<Vec<UpdateQueues>> = None;
+        self.next_animation_frame = None;
 
         let grid = context_manager.current_grid_mut();
         let active_index = grid.current;
@@ -767,6 +1097,16 @@ impl Renderer {
             let is_active = active_index == index;
             let context = grid_context.context_mut();
 
+            // Damage is kept in the terminal, so the pane catches up once
+            // it gets focused or the battery saver is turned off
+            if self.battery_saver
+                && !is_active
+                && !has_active_changed
+                && !context.renderable_content.has_pending_updates
+            {
+                continue;
+            }
+
             let mut has_ime = false;
             if let Some(preedit) = context.ime.preedit() {
                 if let Some(content) = preedit.text.chars().next() {
@@ -789,9 +1129,12 @@ impl Renderer {
                 || context.renderable_content.has_pending_updates
                 || is_active
                     && (context.renderable_content.selection_range.is_some()
-                        || hints.is_some());
+                        || hints.is_some()
+                        || !hint_labels.is_empty());
+            let hint_labels = if is_active { hint_labels } else { &[] };
 
             let mut specific_lines = None;
+            let mut scrolled_lines = 0;
             let (colors, display_offset, blinking_cursor, visible_rows) = {
                 let mut terminal = context.terminal.lock();
                 let result = (
@@ -803,6 +1146,34 @@ impl Renderer {
 
                 context.renderable_content.cursor.state = terminal.cursor();
 
+                // Overlays kept outside of the grid follow the content
+                // scrolled within the scroll region
+                let region_scrolls = terminal.take_region_scrolls();
+                context.renderable_content.selection_range = terminal
+                    .selection
+                    .as_ref()
+                    .and_then(|selection| selection.to_range(&terminal));
+                if let Some(hyperlink_range) = context.renderable_content.hyperlink_range
+                {
+                    context.renderable_content.hyperlink_range = rotate_range(
+                        &region_scrolls,
+                        hyperlink_range.start,
+                        hyperlink_range.end,
+                    )
+                    .map(|(start, end)| SelectionRange {
+                        start,
+                        end,
+                        ..hyperlink_range
+                    });
+                }
+                if is_active {
+                    if let Some(range) = focused_match.as_ref() {
+                        *focused_match =
+                            rotate_range(&region_scrolls, *range.start(), *range.end())
+                                .map(|(start, end)| start..=end);
+                    }
+                }
+
                 if let Some(queues_to_add) = terminal.graphics_take_queues() {
                     if let Some(ref mut queues) = graphic_queues {
                         queues.push(queues_to_add);
@@ -810,8 +1181,15 @@ impl Renderer {
                         graphic_queues = Some(vec![queues_to_add]);
                     }
                 }
+                if let Some(deadline) = terminal.graphics.next_frame() {
+                    self.next_animation_frame = Some(
+                        self.next_animation_frame
+                            .map_or(deadline, |next| next.min(deadline)),
+                    );
+                }
 
                 if !force_full_damage && !terminal.is_fully_damaged() {
+                    scrolled_lines = terminal.scrolled_lines();
                     if let TermDamage::Partial(lines) = terminal.damage() {
                         let mut own_lines =
                             std::collections::HashSet::with_capacity(result.3.len());
@@ -835,12 +1213,32 @@ impl Renderer {
                 }
             }
 
+            // Scrolling moves the cached lines, only the ones scrolled into
+            // view are created while the frame itself is drawn again. Hovered hyperlinks don't follow the
+            // content, so those are drawn again.
+            if scrolled_lines != 0 {
+                if context.renderable_content.hyperlink_range.is_some() {
+                    specific_lines = None;
+                }
+
+                if let Some(ref mut lines) = specific_lines {
+                    let rows = visible_rows.len();
+                    let shift = (scrolled_lines.unsigned_abs() as usize).min(rows);
+                    if scrolled_lines > 0 {
+                        lines.extend(0..shift);
+                    } else {
+                        lines.extend(rows - shift..rows);
+                    }
+                }
+            }
+
             // let duration = start.elapsed();
             // println!("Time elapsed in antes-antes is: {:?}", duration);
             let rich_text_id = context.rich_text_id;
 
             let mut is_cursor_visible =
                 context.renderable_content.cursor.state.is_visible();
+            let blinking_cursor = self.is_cursor_blinking(blinking_cursor);
             context.renderable_content.has_blinking_enabled = blinking_cursor;
             if blinking_cursor {
                 let has_selection = context.renderable_content.selection_range.is_some();
@@ -871,7 +1269,10 @@ impl Renderer {
                 is_cursor_visible = context.renderable_content.is_blinking_cursor_visible;
             }
 
-            if !is_active && context.renderable_content.cursor.state.is_visible() {
+            // Hollow cursors don't blink
+            if !(is_active && self.is_window_focused)
+                && context.renderable_content.cursor.state.is_visible()
+            {
                 is_cursor_visible = true;
             }
 
@@ -893,6 +1294,7 @@ impl Renderer {
                             &context.renderable_content,
                             hints,
                             focused_match,
+                            hint_labels,
                             &colors,
                             is_active,
                         );
@@ -903,6 +1305,7 @@ impl Renderer {
                 }
                 Some(lines) => {
                     content.sel(rich_text_id);
+                    content.scroll_lines(scrolled_lines);
                     for line in lines {
                         let has_cursor = is_cursor_visible
                             && context.renderable_content.cursor.state.pos.row == line;
@@ -913,10 +1316,11 @@ impl Renderer {
                                 visible_row,
                                 has_cursor,
                                 Some(line),
-                                Line(line as i32),
+                                Line(line as i32 - display_offset as i32),
                                 &context.renderable_content,
                                 hints,
                                 focused_match,
+                                hint_labels,
                                 &colors,
                                 is_active,
                             );
@@ -932,6 +1336,10 @@ impl Renderer {
                     sugarloaf.graphics.insert(graphic_data);
                 }
 
+                for graphic_data in queues.frames {
+                    sugarloaf.graphics.update(graphic_data);
+                }
+
                 for graphic_data in queues.remove_queue {
                     sugarloaf.graphics.remove(&graphic_data);
                 }
@@ -964,13 +1372,216 @@ impl Renderer {
 
             self.search.active_search = None;
             self.search.rich_text_id = None;
+            self.search.match_count = None;
+        }
+
+        if let Some(until) = self.scroll_hint_until {
+            let is_visible = Instant::now() < until
+                && !has_search
+                && context_manager
+                    .current()
+                    .terminal
+                    .lock()
+                    .mode()
+                    .contains(Mode::ALT_SCREEN);
+            match &self.alternate_screen_hint {
+                Some(text) if is_visible => {
+                    let rich_text_id = self.create_scroll_hint_rich_text(sugarloaf, text);
+                    scroll_hint::draw_scroll_hint(
+                        &mut objects,
+                        rich_text_id,
+                        &self.named_colors,
+                        (window_size.width, window_size.height, scale_factor),
+                    );
+                    // Render again to hide it
+                    self.next_animation_frame = Some(
+                        self.next_animation_frame
+                            .map_or(until, |next| next.min(until)),
+                    );
+                }
+                _ => self.scroll_hint_until = None,
+            }
+        }
+
+        if let Some((text, until)) = &self.pending_chord {
+            let until = *until;
+            if Instant::now() < until && !has_search {
+                // Same bar as the scroll hint
+                let rich_text_id = self.create_scroll_hint_rich_text(sugarloaf, text);
+                scroll_hint::draw_scroll_hint(
+                    &mut objects,
+                    rich_text_id,
+                    &self.named_colors,
+                    (window_size.width, window_size.height, scale_factor),
+                );
+                // Render again to hide it
+                self.next_animation_frame = Some(
+                    self.next_animation_frame
+                        .map_or(until, |next| next.min(until)),
+                );
+            } else {
+                self.pending_chord = None;
+            }
+        }
+
+        let output_indicator_text = match &self.output_indicator {
+            Some((one, many)) if !has_search => {
+                match context_manager.current().terminal.lock().new_lines_below() {
+                    0 => None,
+                    1 => Some(one.to_owned()),
+                    count => Some(many.replace("{count}", &count.to_string())),
+                }
+            }
+            _ => None,
+        };
+        self.output_indicator_bounds = output_indicator_text.map(|text| {
+            // The hint uses the same text style
+            let rich_text_id = self.create_scroll_hint_rich_text(sugarloaf, &text);
+            let text_width = sugarloaf.rich_text_width(&rich_text_id);
+            output_indicator::draw_output_indicator(
+                &mut objects,
+                rich_text_id,
+                text_width,
+                &self.named_colors,
+                (window_size.width, window_size.height, scale_factor),
+            )
+        });
+
+        if self.password_input_indicator && context_manager.current().is_password_input()
+        {
+            let rich_text_id = self.create_password_indicator_rich_text(sugarloaf);
+            password::draw_password_indicator(
+                &mut objects,
+                rich_text_id,
+                &self.named_colors,
+                (window_size.width, window_size.height, scale_factor),
+            );
+        }
+
+        if self.window_border_width > 0.0 {
+            let color = if self.is_window_focused {
+                self.named_colors.window_border
+            } else {
+                self.named_colors.window_border_unfocused
+            };
+            objects.push(Object::Quad(
+                Quad::solid(
+                    [0.0, 0.0],
+                    [
+                        window_size.width / scale_factor,
+                        window_size.height / scale_factor,
+                    ],
+                    [0.0; 4],
+                )
+                .with_border(self.window_border_width, color),
+            ));
+        }
+
+        if let Some(debug_overlay) = &self.debug_overlay {
+            debug_overlay.draw(
+                &mut objects,
+                sugarloaf,
+                &self.named_colors,
+                (window_size.width, window_size.height, scale_factor),
+            );
+        }
+
+        // Only the interface is mirrored, the terminal content keeps its
+        // own direction
+        if self.is_rtl {
+            sugarloaf.mirror_objects(&mut objects);
         }
 
         context_manager.extend_with_grid_objects(&mut objects);
         sugarloaf.set_objects(objects);
 
-        sugarloaf.render();
+        if std::mem::take(&mut self.is_screenshot_requested) {
+            let pixels = sugarloaf.render_and_capture();
+            let size = sugarloaf.window_size();
+            screenshot::save(
+                self.output_path("png"),
+                size.width as u32,
+                size.height as u32,
+                pixels,
+            );
+        } else {
+            sugarloaf.render();
+        }
+
+        // Lines shaped in the background are drawn by the next render
+        if sugarloaf.has_pending_shaping() {
+            let until = Instant::now() + std::time::Duration::from_millis(8);
+            self.next_animation_frame = Some(
+                self.next_animation_frame
+                    .map_or(until, |next| next.min(until)),
+            );
+        }
+
+        if let Some(metrics) = &mut self.metrics {
+            metrics.record_frame(&sugarloaf.frame_stats());
+        }
+
+        if let Some(debug_overlay) = &mut self.debug_overlay {
+            let mut pty_bytes_read = 0;
+            let mut graphics = GraphicsMemoryStats::default();
+            for context_grid in context_manager.contexts_mut() {
+                for item in context_grid.contexts_mut() {
+                    let mut terminal = item.context_mut().terminal.lock();
+                    pty_bytes_read += terminal.pty_bytes_read;
+                    graphics += terminal.graphics.memory_stats();
+                }
+            }
+
+            debug_overlay.record_frame(
+                frame_started.elapsed(),
+                sugarloaf.frame_stats(),
+                pty_bytes_read,
+                graphics,
+                &context_manager.current().env,
+            );
+        }
         // let duration = start.elapsed();
         // println!("Time elapsed in -renderer.update() is: {:?}", duration);
     }
 }
+
+/// Applies the region scrolls to a range taken before them, `None` when
+/// the range can't be followed anymore.
+#[inline]
+fn rotate_range(
+    region_scrolls: &Option<Vec<RegionScroll>>,
+    start: Pos,
+    end: Pos,
+) -> Option<(Pos, Pos)> {
+    region_scrolls
+        .as_ref()?
+        .iter()
+        .try_fold((start, end), |(start, end), scroll| {
+            scroll.rotate(start, end)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+    use rio_backend::config::Config;
+    use rio_backend::locale::Locale;
+    use rio_backend::sugarloaf::font::FontLibrary;
+
+    #[test]
+    fn test_cursor_blinking_follows_window_focus() {
+        let mut renderer = Renderer::new(
+            &Config::default(),
+            &FontLibrary::default(),
+            &Locale::new(None),
+        );
+        assert!(renderer.is_cursor_blinking(true));
+        assert!(!renderer.is_cursor_blinking(false));
+
+        renderer.set_window_focused(false);
+        assert!(!renderer.is_cursor_blinking(true));
+
+        renderer.set_window_focused(true);
+        assert!(renderer.is_cursor_blinking(true));
+    }
+}
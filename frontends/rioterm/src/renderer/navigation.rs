@@ -232,12 +232,14 @@ impl ScreenNavigation {
                     position_y
                 };
 
-                objects.push(Object::Quad(Quad {
-                    position: [initial_position_x, position],
-                    color: colors.tabs_active_highlight,
-                    size: [125., PADDING_Y_BOTTOM_TABS / 10.],
-                    ..Quad::default()
-                }));
+                objects.push(Object::Quad(
+                    Quad::solid(
+                        [initial_position_x, position],
+                        [125., PADDING_Y_BOTTOM_TABS / 10.],
+                        colors.tabs_active_highlight,
+                    )
+                    .with_border_radius(PADDING_Y_BOTTOM_TABS / 20.),
+                ));
             }
 
             let text = if is_current {
@@ -0,0 +1,42 @@
+use crate::constants::*;
+use rio_backend::config::colors::Colors;
+use rio_backend::sugarloaf::{Object, Quad, RichText};
+
+const OUTPUT_INDICATOR_PADDING_X: f32 = 12.0;
+const OUTPUT_INDICATOR_MARGIN_BOTTOM: f32 = 8.0;
+
+/// Pill shown at the bottom center of the window while scrolled up and
+/// new output arrived below the viewport, returns its bounds as
+/// `[x, y, width, height]` in logical pixels.
+#[inline]
+pub fn draw_output_indicator(
+    objects: &mut Vec<Object>,
+    rich_text_id: usize,
+    text_width: f32,
+    colors: &Colors,
+    dimensions: (f32, f32, f32),
+) -> [f32; 4] {
+    let (width, height, scale) = dimensions;
+    let pill_width = text_width + OUTPUT_INDICATOR_PADDING_X * 2.0;
+    let position_x = ((width / scale) - pill_width) / 2.0;
+    let position_y =
+        (height / scale) - PADDING_Y_BOTTOM_TABS - OUTPUT_INDICATOR_MARGIN_BOTTOM;
+    let radius = PADDING_Y_BOTTOM_TABS / 2.0;
+
+    objects.push(Object::Quad(
+        Quad::solid(
+            [position_x, position_y],
+            [pill_width, PADDING_Y_BOTTOM_TABS],
+            colors.bar,
+        )
+        .with_border_radii([radius; 4]),
+    ));
+
+    objects.push(Object::RichText(RichText {
+        id: rich_text_id,
+        position: [position_x + OUTPUT_INDICATOR_PADDING_X, position_y],
+        lines: None,
+    }));
+
+    [position_x, position_y, pill_width, PADDING_Y_BOTTOM_TABS]
+}
@@ -0,0 +1,33 @@
+use crate::constants::*;
+use rio_backend::config::colors::Colors;
+use rio_backend::sugarloaf::{Object, Quad, RichText};
+
+pub const PASSWORD_INDICATOR: char = '🔒';
+const PASSWORD_INDICATOR_WIDTH: f32 = 30.0;
+
+#[inline]
+pub fn draw_password_indicator(
+    objects: &mut Vec<Object>,
+    rich_text_id: usize,
+    colors: &Colors,
+    dimensions: (f32, f32, f32),
+) {
+    let (width, height, scale) = dimensions;
+    let position_x = (width / scale) - PASSWORD_INDICATOR_WIDTH;
+    let position_y = (height / scale) - PADDING_Y_BOTTOM_TABS;
+
+    objects.push(Object::Quad(
+        Quad::solid(
+            [position_x, position_y],
+            [PASSWORD_INDICATOR_WIDTH, PADDING_Y_BOTTOM_TABS],
+            colors.bar,
+        )
+        .with_border_radii([4.0, 0.0, 0.0, 0.0]),
+    ));
+
+    objects.push(Object::RichText(RichText {
+        id: rich_text_id,
+        position: [position_x + 6., position_y],
+        lines: None,
+    }));
+}
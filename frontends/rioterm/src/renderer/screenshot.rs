@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory screenshots and exports are written to, the configured one
+/// or else the pictures directory of the user.
+pub fn directory(screenshot_dir: &Option<String>) -> PathBuf {
+    match screenshot_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::picture_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_default(),
+    }
+}
+
+/// File path in the directory made unique with the current time.
+pub fn unique_path(directory: &Path, extension: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    directory.join(format!("rio-{timestamp}.{extension}"))
+}
+
+/// Encodes the RGBA pixels as a PNG in the background.
+pub fn save(path: PathBuf, width: u32, height: u32, pixels: Vec<u8>) {
+    let Some(image) = image_rs::RgbaImage::from_raw(width, height, pixels) else {
+        tracing::error!("unable to take a screenshot, the frame size does not match");
+        return;
+    };
+
+    std::thread::spawn(move || {
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .map_err(image_rs::ImageError::IoError)
+            .and_then(|_| image.save_with_format(&path, image_rs::ImageFormat::Png));
+
+        match result {
+            Ok(()) => tracing::info!("screenshot saved to {}", path.display()),
+            Err(err) => tracing::error!("unable to save screenshot: {err}"),
+        }
+    });
+}
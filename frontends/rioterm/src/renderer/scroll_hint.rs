@@ -0,0 +1,32 @@
+use crate::constants::*;
+use rio_backend::config::colors::Colors;
+use rio_backend::sugarloaf::{Object, Quad, RichText};
+use std::time::Duration;
+
+/// How long the hint stays after the last scroll.
+pub const SCROLL_HINT_DURATION: Duration = Duration::from_secs(2);
+
+/// Shown when scrolling does nothing because an application owns the
+/// alternate screen, drawn over the bottom of the window like the search.
+#[inline]
+pub fn draw_scroll_hint(
+    objects: &mut Vec<Object>,
+    rich_text_id: usize,
+    colors: &Colors,
+    dimensions: (f32, f32, f32),
+) {
+    let (width, height, scale) = dimensions;
+    let position_y = (height / scale) - PADDING_Y_BOTTOM_TABS;
+
+    objects.push(Object::Quad(Quad::solid(
+        [0.0, position_y],
+        [width, PADDING_Y_BOTTOM_TABS],
+        colors.bar,
+    )));
+
+    objects.push(Object::RichText(RichText {
+        id: rich_text_id,
+        position: [8., position_y],
+        lines: None,
+    }));
+}
@@ -12,16 +12,26 @@ pub fn draw_search_bar(
     let (width, height, scale) = dimensions;
     let position_y = (height / scale) - PADDING_Y_BOTTOM_TABS;
 
-    objects.push(Object::Quad(Quad {
-        position: [0.0, position_y],
-        color: colors.bar,
-        size: [width, PADDING_Y_BOTTOM_TABS],
-        ..Quad::default()
-    }));
+    objects.push(Object::Quad(Quad::solid(
+        [0.0, position_y],
+        [width, PADDING_Y_BOTTOM_TABS],
+        colors.bar,
+    )));
+
+    // Search input box
+    objects.push(Object::Quad(
+        Quad::solid(
+            [4.0, position_y + 2.0],
+            [(width / scale) - 8.0, PADDING_Y_BOTTOM_TABS - 4.0],
+            colors.background.0,
+        )
+        .with_border(1.0, colors.tabs_active_highlight)
+        .with_border_radius(4.0),
+    ));
 
     objects.push(Object::RichText(RichText {
         id: rich_text_id,
-        position: [4., position_y],
+        position: [8., position_y],
         lines: None,
     }));
 }
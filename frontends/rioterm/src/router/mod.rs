@@ -1,13 +1,19 @@
 pub mod routes;
 mod window;
+use crate::context::grid::ContextGrid;
 use crate::event::EventProxy;
-use crate::router::window::{configure_window, create_window_builder};
+use crate::router::window::{
+    configure_window, create_window_builder, set_window_opacity,
+};
+use crate::screen::osc52::ClipboardRequest;
 use crate::screen::{Screen, ScreenWindowProperties};
 use assistant::Assistant;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use rio_backend::clipboard::Clipboard;
 use rio_backend::config::Config as RioConfig;
 use rio_backend::error::{RioError, RioErrorLevel, RioErrorType};
+use rio_backend::sugarloaf::ContextError;
+use rio_window::event::ElementState;
 use rio_window::event_loop::ActiveEventLoop;
 use rio_window::keyboard::{Key, NamedKey};
 #[cfg(not(any(target_os = "macos", windows)))]
@@ -15,7 +21,9 @@ use rio_window::platform::startup_notify::{
     self, EventLoopExtStartupNotify, WindowAttributesExtStartupNotify,
 };
 use rio_window::window::{Window, WindowId};
-use routes::{assistant, RoutePath};
+use routes::{
+    assistant, clipboard_history, command_palette, dialog, settings, RoutePath,
+};
 use rustc_hash::FxHashMap;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -28,8 +36,15 @@ use std::time::{Duration, Instant};
 // #[cfg(not(any(target_os = "macos", target_os = "windows")))]
 const RIO_TITLE: &str = "▲";
 
+// Frame interval used while the battery saver is active (30fps)
+const BATTERY_SAVER_FRAME_INTERVAL: Duration = Duration::from_micros(33_333);
+
 pub struct Route<'a> {
     pub assistant: assistant::Assistant,
+    pub clipboard_history: clipboard_history::ClipboardHistory,
+    pub command_palette: command_palette::CommandPalette,
+    pub settings: settings::Settings,
+    pub pending_quit: dialog::PendingQuit,
     pub path: RoutePath,
     pub window: RouteWindow<'a>,
 }
@@ -44,6 +59,10 @@ impl Route<'_> {
     ) -> Route {
         Route {
             assistant,
+            clipboard_history: clipboard_history::ClipboardHistory::default(),
+            command_palette: command_palette::CommandPalette::default(),
+            settings: settings::Settings::default(),
+            pending_quit: dialog::PendingQuit::default(),
             path,
             window,
         }
@@ -71,6 +90,19 @@ impl Route<'_> {
         self.window
             .screen
             .update_config(config, db, should_update_font);
+        self.window.update_frame_rate(config);
+    }
+
+    /// Applies the configuration of a profile matched for route_id.
+    #[inline]
+    pub fn update_profile(
+        &mut self,
+        config: &RioConfig,
+        db: &rio_backend::sugarloaf::font::FontLibrary,
+        route_id: usize,
+    ) {
+        self.window.screen.update_profile(config, db, route_id);
+        self.window.update_frame_rate(config);
     }
 
     #[inline]
@@ -80,6 +112,11 @@ impl Route<'_> {
         self.window.winit_window.set_subtitle(subtitle);
     }
 
+    #[inline]
+    pub fn set_window_opacity(&mut self, config: &RioConfig, opacity: f32) {
+        set_window_opacity(&self.window.winit_window, config, opacity);
+    }
+
     #[inline]
     pub fn set_window_title(&mut self, title: &str) {
         self.window.winit_window.set_title(title);
@@ -88,6 +125,8 @@ impl Route<'_> {
     #[inline]
     pub fn report_error(&mut self, error: &RioError) {
         if error.report == RioErrorType::ConfigurationNotFound {
+            // Without a config file the defaults are in use
+            self.settings = settings::Settings::first_run(&RioConfig::default());
             self.path = RoutePath::Welcome;
             return;
         }
@@ -99,14 +138,117 @@ impl Route<'_> {
     #[inline]
     pub fn clear_errors(&mut self) {
         self.assistant.clear();
-        self.path = RoutePath::Terminal;
+        // The other screens stay, the settings reload the config on changes
+        if matches!(self.path, RoutePath::Assistant | RoutePath::Welcome) {
+            self.path = RoutePath::Terminal;
+        }
     }
 
+    /// Asks to confirm quitting, or closing the window, which stops the
+    /// running programs.
     #[inline]
-    pub fn confirm_quit(&mut self) {
+    pub fn confirm_quit(&mut self, close_window: bool, programs: Vec<String>) {
+        self.pending_quit = dialog::PendingQuit {
+            close_window,
+            programs,
+        };
         self.path = RoutePath::ConfirmQuit;
     }
 
+    #[inline]
+    pub fn confirm_paste(&mut self) {
+        if self.path == RoutePath::Terminal {
+            self.path = RoutePath::ConfirmPaste;
+        } else {
+            self.window.screen.resolve_pending_paste(false);
+        }
+    }
+
+    /// Handles an OSC 52 request, asking to confirm it when configured.
+    pub fn clipboard_request(&mut self, request: ClipboardRequest) {
+        if self.window.screen.clipboard_request(request) {
+            if self.path == RoutePath::Terminal {
+                self.path = RoutePath::ConfirmClipboardRequest;
+                self.request_redraw();
+            } else {
+                self.window.screen.resolve_clipboard_request(false);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn open_clipboard_history(&mut self) {
+        if self.path == RoutePath::Terminal {
+            self.clipboard_history = clipboard_history::ClipboardHistory::default();
+            self.path = RoutePath::ClipboardHistory;
+        }
+    }
+
+    #[inline]
+    pub fn open_command_palette(&mut self) {
+        if self.path == RoutePath::Terminal {
+            self.command_palette = command_palette::CommandPalette::default();
+            self.path = RoutePath::CommandPalette;
+        }
+    }
+
+    #[inline]
+    pub fn open_settings(&mut self, config: &RioConfig) {
+        if self.path == RoutePath::Terminal {
+            self.settings = settings::Settings::new(config);
+            self.path = RoutePath::Settings;
+        }
+    }
+
+    /// Writes the option changed in the settings screen to the config
+    /// file and loads it again to apply it.
+    fn write_setting(&mut self, change: (&str, settings::SettingValue)) {
+        let path = rio_backend::config::config_file_path();
+        if self.settings.save(&path, change) {
+            crate::watcher::ignore_own_write(&path);
+            self.window.screen.context_manager.reload_config();
+        }
+    }
+
+    /// Creates the config file with the answers of the welcome screen.
+    fn finish_welcome(&mut self) {
+        rio_backend::config::create_config_file(None);
+
+        let path = rio_backend::config::config_file_path();
+        let result = self
+            .settings
+            .values()
+            .try_for_each(|(key, value)| value.write(&path, key));
+        if let Err(err) = result {
+            tracing::error!("could not write the config file: {err}");
+            self.settings.error = Some(err.to_string());
+            return;
+        }
+
+        self.settings.error = None;
+        self.path = RoutePath::Terminal;
+        crate::watcher::ignore_own_write(&path);
+        self.window.screen.context_manager.reload_config();
+    }
+
+    /// Pastes an entry of the clipboard history and goes back to the
+    /// terminal.
+    fn paste_from_clipboard_history(&mut self, index: usize) {
+        let text = self
+            .window
+            .screen
+            .clipboard
+            .borrow()
+            .history()
+            .get(index)
+            .cloned();
+
+        self.path = RoutePath::Terminal;
+        if let Some(text) = text {
+            self.window.screen.paste(&text, true);
+        }
+    }
+
     #[inline]
     pub fn quit(&mut self) {
         std::process::exit(0);
@@ -132,15 +274,193 @@ impl Route<'_> {
             if key_event.logical_key == Key::Named(NamedKey::Escape) {
                 self.path = RoutePath::Terminal;
             } else if is_enter {
-                self.quit();
+                if self.pending_quit.close_window {
+                    self.window.screen.context_manager.close_window();
+                } else {
+                    self.quit();
+                }
 
                 return true;
             }
         }
 
-        if self.path == RoutePath::Welcome && is_enter {
-            rio_backend::config::create_config_file(None);
-            self.path = RoutePath::Terminal;
+        if self.path == RoutePath::ConfirmClipboardRequest {
+            if key_event.state == ElementState::Pressed {
+                let confirmed = match key_event.logical_key {
+                    Key::Named(NamedKey::Enter) => Some(true),
+                    Key::Named(NamedKey::Escape) => Some(false),
+                    _ => None,
+                };
+                if let Some(confirmed) = confirmed {
+                    self.path = RoutePath::Terminal;
+                    self.window.screen.resolve_clipboard_request(confirmed);
+                    self.request_redraw();
+                }
+            }
+            return true;
+        }
+
+        if self.path == RoutePath::ConfirmPaste {
+            if key_event.state == ElementState::Pressed {
+                let confirmed = match key_event.logical_key {
+                    Key::Named(NamedKey::Enter) => Some(true),
+                    Key::Named(NamedKey::Escape) => Some(false),
+                    _ => None,
+                };
+                if let Some(confirmed) = confirmed {
+                    self.path = RoutePath::Terminal;
+                    self.window.screen.resolve_pending_paste(confirmed);
+                    self.request_redraw();
+                }
+            }
+            return true;
+        }
+
+        if self.path == RoutePath::ClipboardHistory {
+            if key_event.state == ElementState::Released {
+                return true;
+            }
+
+            let entries = self.window.screen.clipboard.borrow().history().len();
+            match &key_event.logical_key {
+                Key::Named(NamedKey::Escape) => self.path = RoutePath::Terminal,
+                Key::Named(NamedKey::Enter) => {
+                    self.paste_from_clipboard_history(self.clipboard_history.selected)
+                }
+                Key::Named(NamedKey::ArrowUp) => self.clipboard_history.select_previous(),
+                Key::Named(NamedKey::ArrowDown) => {
+                    self.clipboard_history.select_next(entries)
+                }
+                Key::Character(c) => {
+                    if let Some(number) = c.parse::<usize>().ok().filter(|n| *n > 0) {
+                        self.paste_from_clipboard_history(number - 1);
+                    }
+                }
+                _ => {}
+            }
+
+            self.request_redraw();
+            return true;
+        }
+
+        if self.path == RoutePath::CommandPalette {
+            if key_event.state == ElementState::Released {
+                return true;
+            }
+
+            let entries = self.command_palette.matches().len();
+            match &key_event.logical_key {
+                Key::Named(NamedKey::Escape) => self.path = RoutePath::Terminal,
+                Key::Named(NamedKey::Enter) => {
+                    self.path = RoutePath::Terminal;
+                    if let Some(action) = self.command_palette.selected_action() {
+                        self.window.screen.run_action(&action);
+                    }
+                }
+                Key::Named(NamedKey::ArrowUp) => self.command_palette.select_previous(),
+                Key::Named(NamedKey::ArrowDown) => {
+                    self.command_palette.select_next(entries)
+                }
+                Key::Named(NamedKey::Backspace) => self.command_palette.pop(),
+                _ => {
+                    if let Some(text) = key_event
+                        .text
+                        .as_ref()
+                        .filter(|text| !text.chars().any(char::is_control))
+                    {
+                        self.command_palette.push(text);
+                    }
+                }
+            }
+
+            self.request_redraw();
+            return true;
+        }
+
+        if self.path == RoutePath::Settings {
+            if key_event.state == ElementState::Released {
+                return true;
+            }
+
+            if self.settings.is_editing() {
+                match &key_event.logical_key {
+                    Key::Named(NamedKey::Escape) => self.settings.cancel_editing(),
+                    Key::Named(NamedKey::Enter) => {
+                        if let Some(change) = self.settings.finish_editing() {
+                            self.write_setting(change);
+                        }
+                    }
+                    Key::Named(NamedKey::Backspace) => self.settings.pop(),
+                    _ => {
+                        if let Some(text) = key_event
+                            .text
+                            .as_ref()
+                            .filter(|text| !text.chars().any(char::is_control))
+                        {
+                            self.settings.push(text);
+                        }
+                    }
+                }
+            } else {
+                let change = match &key_event.logical_key {
+                    Key::Named(NamedKey::Escape) => {
+                        self.path = RoutePath::Terminal;
+                        None
+                    }
+                    Key::Named(NamedKey::ArrowUp) => {
+                        self.settings.select_previous();
+                        None
+                    }
+                    Key::Named(NamedKey::ArrowDown) => {
+                        self.settings.select_next();
+                        None
+                    }
+                    Key::Named(NamedKey::ArrowLeft) => self.settings.change(false),
+                    Key::Named(NamedKey::ArrowRight) => self.settings.change(true),
+                    Key::Named(NamedKey::Enter) if !self.settings.start_editing() => {
+                        self.settings.change(true)
+                    }
+                    _ => None,
+                };
+                if let Some(change) = change {
+                    self.write_setting(change);
+                }
+            }
+
+            self.request_redraw();
+            return true;
+        }
+
+        if self.path == RoutePath::Welcome {
+            if key_event.state == ElementState::Released {
+                return true;
+            }
+
+            match &key_event.logical_key {
+                // Skips the questions, the config file is still created
+                Key::Named(NamedKey::Escape) => {
+                    rio_backend::config::create_config_file(None);
+                    self.path = RoutePath::Terminal;
+                }
+                Key::Named(NamedKey::ArrowUp) => self.settings.select_previous(),
+                Key::Named(NamedKey::ArrowLeft) => {
+                    self.settings.change(false);
+                }
+                Key::Named(NamedKey::ArrowRight) => {
+                    self.settings.change(true);
+                }
+                Key::Named(NamedKey::Enter) => {
+                    if self.settings.selected + 1 < self.settings.count() {
+                        self.settings.select_next();
+                    } else {
+                        self.finish_welcome();
+                    }
+                }
+                _ => {}
+            }
+
+            self.request_redraw();
+            return true;
         }
 
         false
@@ -153,6 +473,7 @@ pub struct Router<'a> {
     pub font_library: Box<rio_backend::sugarloaf::font::FontLibrary>,
     pub config_route: Option<WindowId>,
     pub clipboard: Rc<RefCell<Clipboard>>,
+    pub battery_saver: bool,
     current_tab_id: u64,
 }
 
@@ -181,10 +502,32 @@ impl Router<'_> {
             config_route: None,
             font_library: Box::new(font_library),
             clipboard,
+            battery_saver: false,
             current_tab_id: 0,
         }
     }
 
+    pub fn set_battery_saver(&mut self, enabled: bool, config: &RioConfig) {
+        self.battery_saver = enabled;
+        for route in self.routes.values_mut() {
+            route.window.set_battery_saver(enabled, config);
+            route.request_redraw();
+        }
+    }
+
+    /// Names of the programs running in the terminals of all the windows,
+    /// other than their shells.
+    pub fn running_programs(&mut self) -> Vec<String> {
+        let mut programs: Vec<String> = self
+            .routes
+            .values_mut()
+            .flat_map(|route| route.window.screen.context_manager.running_programs())
+            .collect();
+        programs.sort();
+        programs.dedup();
+        programs
+    }
+
     #[inline]
     pub fn propagate_error_to_next_route(&mut self, error: RioError) {
         self.propagated_report = Some(error);
@@ -213,6 +556,37 @@ impl Router<'_> {
             .copied()
     }
 
+    /// Focuses the window created after `window_id`, or before it when
+    /// `next` is false, wrapping around.
+    pub fn select_window(&self, window_id: WindowId, next: bool) {
+        let mut windows: Vec<(Instant, WindowId)> = self
+            .routes
+            .iter()
+            .map(|(id, route)| (route.window.created_at, *id))
+            .collect();
+        windows.sort_unstable();
+
+        let Some(index) = windows.iter().position(|(_, id)| *id == window_id) else {
+            return;
+        };
+        let index = if next {
+            (index + 1) % windows.len()
+        } else {
+            (index + windows.len() - 1) % windows.len()
+        };
+        if windows[index].1 == window_id {
+            return;
+        }
+
+        if let Some(route) = self.routes.get(&windows[index].1) {
+            let window = &route.window.winit_window;
+            if window.is_minimized().unwrap_or(false) {
+                window.set_minimized(false);
+            }
+            window.focus_window();
+        }
+    }
+
     pub fn open_config_window(
         &mut self,
         event_loop: &ActiveEventLoop,
@@ -243,7 +617,7 @@ impl Router<'_> {
             ..current_config
         };
 
-        let window = RouteWindow::from_target(
+        let mut window = RouteWindow::from_target(
             event_loop,
             event_proxy,
             &new_config,
@@ -252,7 +626,11 @@ impl Router<'_> {
             None,
             None,
             self.clipboard.clone(),
+            None,
         );
+        if self.battery_saver {
+            window.set_battery_saver(true, &new_config);
+        }
         let id = window.winit_window.id();
         let route = Route::new(Assistant::new(), RoutePath::Terminal, window);
         self.routes.insert(id, route);
@@ -305,7 +683,7 @@ impl Router<'_> {
             None
         };
 
-        let window = RouteWindow::from_target(
+        let mut window = RouteWindow::from_target(
             event_loop,
             event_proxy,
             config,
@@ -314,13 +692,21 @@ impl Router<'_> {
             tab_id.as_deref(),
             open_url,
             self.clipboard.clone(),
+            None,
         );
+        if self.battery_saver {
+            window.set_battery_saver(true, config);
+        }
         let id = window.winit_window.id();
 
         let mut route = Route {
             window,
             path: RoutePath::Terminal,
             assistant: Assistant::new(),
+            clipboard_history: clipboard_history::ClipboardHistory::default(),
+            command_palette: command_palette::CommandPalette::default(),
+            settings: settings::Settings::default(),
+            pending_quit: dialog::PendingQuit::default(),
         };
 
         if let Some(err) = &self.propagated_report {
@@ -331,6 +717,64 @@ impl Router<'_> {
         self.routes.insert(id, route);
     }
 
+    /// Opens a window with a tab moved from another one.
+    pub fn create_window_with_tab<'a>(
+        &'a mut self,
+        event_loop: &'a ActiveEventLoop,
+        event_proxy: EventProxy,
+        config: &'a rio_backend::config::Config,
+        tab: ContextGrid<EventProxy>,
+    ) {
+        let mut window = RouteWindow::from_target(
+            event_loop,
+            event_proxy,
+            config,
+            &self.font_library,
+            RIO_TITLE,
+            None,
+            None,
+            self.clipboard.clone(),
+            Some(tab),
+        );
+        if self.battery_saver {
+            window.set_battery_saver(true, config);
+        }
+        let id = window.winit_window.id();
+        let route = Route::new(Assistant::new(), RoutePath::Terminal, window);
+        self.routes.insert(id, route);
+    }
+
+    /// Moves the tabs of the other windows to this one and closes them,
+    /// the windows whose tabs don't fit are kept.
+    pub fn merge_windows(&mut self, window_id: WindowId) {
+        let others: Vec<WindowId> = self
+            .routes
+            .keys()
+            .filter(|id| **id != window_id && Some(**id) != self.config_route)
+            .copied()
+            .collect();
+
+        for id in others {
+            let Some(mut source) = self.routes.remove(&id) else {
+                continue;
+            };
+            let Some(target) = self.routes.get_mut(&window_id) else {
+                self.routes.insert(id, source);
+                return;
+            };
+
+            let count = source.window.screen.ctx().len();
+            if !target.window.screen.ctx().has_capacity(count) {
+                self.routes.insert(id, source);
+                continue;
+            }
+
+            let tabs = source.window.screen.context_manager.take_grids();
+            target.window.screen.attach_tabs(tabs);
+            // Dropping the route destroys the emptied window
+        }
+    }
+
     #[cfg(target_os = "macos")]
     #[inline]
     pub fn create_native_tab<'a>(
@@ -341,7 +785,7 @@ impl Router<'_> {
         tab_id: Option<&str>,
         open_url: Option<String>,
     ) {
-        let window = RouteWindow::from_target(
+        let mut window = RouteWindow::from_target(
             event_loop,
             event_proxy,
             config,
@@ -350,13 +794,21 @@ impl Router<'_> {
             tab_id,
             open_url,
             self.clipboard.clone(),
+            None,
         );
+        if self.battery_saver {
+            window.set_battery_saver(true, config);
+        }
         self.routes.insert(
             window.winit_window.id(),
             Route {
                 window,
                 path: RoutePath::Terminal,
                 assistant: Assistant::new(),
+                clipboard_history: clipboard_history::ClipboardHistory::default(),
+                command_palette: command_palette::CommandPalette::default(),
+                settings: settings::Settings::default(),
+                pending_quit: dialog::PendingQuit::default(),
             },
         );
     }
@@ -365,18 +817,118 @@ impl Router<'_> {
 pub struct RouteWindow<'a> {
     pub is_focused: bool,
     pub is_occluded: bool,
-    has_fps_target: bool,
+    fps_target: Option<Duration>,
+    fps_max: Option<Duration>,
+    battery_saver: bool,
     pub render_timestamp: Instant,
+    // Orders the windows when cycling through them
+    created_at: Instant,
     pub vblank_interval: Duration,
     pub winit_window: Window,
     pub screen: Screen<'a>,
     #[cfg(target_os = "macos")]
     pub is_macos_deadzone: bool,
+    // Last cursor cell reported to the accessibility clients
+    #[cfg(any(target_os = "macos", windows))]
+    accessibility_caret: Option<[f32; 4]>,
+    // Last cursor area reported to the input method
+    ime_cursor_area: Option<[f32; 4]>,
 }
 
 impl<'a> RouteWindow<'a> {
     pub fn configure_window(&mut self, config: &rio_backend::config::Config) {
         configure_window(&self.winit_window, config);
+        if self.battery_saver {
+            self.winit_window.set_blur(false);
+        }
+    }
+
+    pub fn set_battery_saver(
+        &mut self,
+        enabled: bool,
+        config: &rio_backend::config::Config,
+    ) {
+        self.battery_saver = enabled;
+        self.screen.renderer.battery_saver = enabled;
+        self.winit_window.set_blur(config.window.blur && !enabled);
+        self.update_vblank_interval();
+    }
+
+    /// Applies `renderer.target-fps` and `renderer.max-fps`.
+    pub fn update_frame_rate(&mut self, config: &rio_backend::config::Config) {
+        self.fps_target = config.renderer.target_fps.map(frame_interval);
+        self.fps_max = config.renderer.max_fps.map(frame_interval);
+        self.update_vblank_interval();
+    }
+
+    /// Returns true if the window is fully hidden, either occluded or
+    /// minimized, so render passes can be skipped.
+    #[inline]
+    pub fn is_hidden(&self) -> bool {
+        // A focused window can't be minimized, which also avoids querying
+        // the window state (a roundtrip on X11) for every frame.
+        self.is_occluded
+            || (!self.is_focused && self.winit_window.is_minimized().unwrap_or(false))
+    }
+
+    /// Reports the cursor cell of the focused window to the accessibility
+    /// clients, so screen magnifiers follow the cursor while typing.
+    #[cfg(any(target_os = "macos", windows))]
+    pub fn update_accessibility_caret(&mut self) {
+        if !self.is_focused {
+            self.accessibility_caret = None;
+            return;
+        }
+
+        let area = self.screen.cursor_area();
+        if area == self.accessibility_caret {
+            return;
+        }
+        self.accessibility_caret = area;
+
+        let Some([x, y, width, height]) = area else {
+            return;
+        };
+        let position = rio_window::dpi::PhysicalPosition::new(x, y);
+        let size = rio_window::dpi::PhysicalSize::new(width, height);
+
+        #[cfg(target_os = "macos")]
+        {
+            use rio_window::platform::macos::WindowExtMacOS;
+            self.winit_window
+                .set_accessibility_caret_area(position.into(), size.into());
+        }
+
+        #[cfg(windows)]
+        {
+            use rio_window::platform::windows::WindowExtWindows;
+            self.winit_window
+                .set_accessibility_caret_area(position.into(), size.into());
+        }
+    }
+
+    /// Places the candidate window of the input method next to the cursor,
+    /// the area is only sent again when the cursor or the preedit changes.
+    pub fn update_ime_cursor_area(&mut self) {
+        let area = self.screen.ime_cursor_area();
+        if area.is_none() || area == self.ime_cursor_area {
+            return;
+        }
+        self.ime_cursor_area = area;
+
+        let Some([x, y, width, height]) = area else {
+            return;
+        };
+        self.winit_window.set_ime_cursor_area(
+            rio_window::dpi::PhysicalPosition::new(x, y),
+            rio_window::dpi::PhysicalSize::new(width, height),
+        );
+    }
+
+    /// Returns true if continuous rendering needs to be throttled.
+    #[inline]
+    pub fn is_frame_rate_capped(&self) -> bool {
+        self.battery_saver || self.fps_max.is_some()
     }
 
     pub fn wait_until(&self) -> Option<Duration> {
@@ -422,19 +974,33 @@ impl<'a> RouteWindow<'a> {
     // }
 
     pub fn update_vblank_interval(&mut self) {
-        if !self.has_fps_target {
-            // Get the display refresh rate, default to 60Hz if unavailable
-            let refresh_rate_hz = self
-                .winit_window
-                .current_monitor()
-                .and_then(|monitor| monitor.refresh_rate_millihertz())
-                .unwrap_or(60_000) as f64
-                / 1000.0; // Convert millihertz to Hz
+        let interval = match self.fps_target {
+            Some(interval) => interval,
+            None => {
+                // Get the display refresh rate, default to 60Hz if unavailable
+                let refresh_rate_hz = self
+                    .winit_window
+                    .current_monitor()
+                    .and_then(|monitor| monitor.refresh_rate_millihertz())
+                    .unwrap_or(60_000) as f64
+                    / 1000.0; // Convert millihertz to Hz
 
-            // Calculate frame time in microseconds (1,000,000 µs / refresh_rate)
-            let frame_time_us = (1_000_000.0 / refresh_rate_hz) as u64;
-            self.vblank_interval = Duration::from_micros(frame_time_us);
-        }
+                // Calculate frame time in microseconds (1,000,000 µs / refresh_rate)
+                let frame_time_us = (1_000_000.0 / refresh_rate_hz) as u64;
+                Duration::from_micros(frame_time_us)
+            }
+        };
+
+        let interval = match self.fps_max {
+            Some(fps_max) => interval.max(fps_max),
+            None => interval,
+        };
+
+        self.vblank_interval = if self.battery_saver {
+            interval.max(BATTERY_SAVER_FRAME_INTERVAL)
+        } else {
+            interval
+        };
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -447,6 +1013,7 @@ impl<'a> RouteWindow<'a> {
         tab_id: Option<&str>,
         open_url: Option<String>,
         clipboard: Rc<RefCell<Clipboard>>,
+        tab: Option<ContextGrid<EventProxy>>,
     ) -> RouteWindow<'a> {
         #[allow(unused_mut)]
         let mut window_builder = create_window_builder(window_name, config, tab_id);
@@ -478,8 +1045,20 @@ impl<'a> RouteWindow<'a> {
             font_library,
             open_url,
             clipboard,
+            tab,
         )
-        .expect("Screen not created");
+        .unwrap_or_else(|err| {
+            // Without a renderer there is no way to show the assistant
+            if let Some(err) = err.downcast_ref::<ContextError>() {
+                crate::platform::show_error_dialog(
+                    "Rio: Unable to initialize the renderer",
+                    &renderer_error_message(err),
+                );
+                std::process::exit(1);
+            }
+
+            panic!("Screen not created: {err:?}")
+        });
 
         #[cfg(target_os = "windows")]
         {
@@ -499,24 +1078,50 @@ impl<'a> RouteWindow<'a> {
         // Now convert it to micro seconds.
         let mut monitor_vblank_interval =
             Duration::from_micros((1000. * monitor_vblank_interval) as u64);
-        let mut has_fps_target = false;
+        let fps_target = config.renderer.target_fps.map(frame_interval);
+        let fps_max = config.renderer.max_fps.map(frame_interval);
 
-        if let Some(target_fps) = config.renderer.target_fps {
-            monitor_vblank_interval =
-                Duration::from_millis(1000 / target_fps.clamp(1, 1000));
-            has_fps_target = true;
+        if let Some(fps_target) = fps_target {
+            monitor_vblank_interval = fps_target;
+        }
+
+        if let Some(fps_max) = fps_max {
+            monitor_vblank_interval = monitor_vblank_interval.max(fps_max);
         }
 
         Self {
             vblank_interval: monitor_vblank_interval,
-            has_fps_target,
+            fps_target,
+            fps_max,
+            battery_saver: false,
             render_timestamp: Instant::now(),
+            created_at: Instant::now(),
             is_focused: true,
             is_occluded: false,
             winit_window,
             screen,
             #[cfg(target_os = "macos")]
             is_macos_deadzone: false,
+            #[cfg(any(target_os = "macos", windows))]
+            accessibility_caret: None,
+            ime_cursor_area: None,
         }
     }
 }
+
+#[inline]
+fn frame_interval(fps: u64) -> Duration {
+    Duration::from_millis(1000 / fps.clamp(1, 1000))
+}
+
+fn renderer_error_message(err: &ContextError) -> String {
+    format!(
+        "{err}\n\n\
+        You can try:\n\
+        - Forcing the OpenGL backend with `backend = \"GL\"` under `[renderer]` \
+        in the configuration file, or with the WGPU_BACKEND=gl environment variable.\n\
+        - Using a software renderer, for example with LIBGL_ALWAYS_SOFTWARE=1 \
+        and the OpenGL backend.\n\
+        - Updating the graphics drivers."
+    )
+}
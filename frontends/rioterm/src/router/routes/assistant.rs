@@ -1,6 +1,7 @@
 use crate::context::grid::ContextDimension;
 use rio_backend::error::{RioError, RioErrorLevel};
-use rio_backend::sugarloaf::{FragmentStyle, Object, Quad, RichText, Sugarloaf};
+use rio_backend::locale::Locale;
+use rio_backend::sugarloaf::{Content, FragmentStyle, Object, Quad, RichText, Sugarloaf};
 
 pub struct Assistant {
     pub inner: Option<RioError>,
@@ -33,11 +34,37 @@ impl Assistant {
     }
 }
 
+/// Adds the text, turning http(s) urls into clickable links.
+fn add_text_with_links(content: &mut Content, text: &str, style: FragmentStyle) {
+    let mut rest = text;
+    while let Some(start) = [rest.find("https://"), rest.find("http://")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        let end = rest[start..]
+            .find(char::is_whitespace)
+            .map_or(rest.len(), |end| start + end);
+        let url = &rest[start..end];
+
+        if start > 0 {
+            content.add_text(&rest[..start], style);
+        }
+        content.add_link(url, url, style);
+        rest = &rest[end..];
+    }
+
+    if !rest.is_empty() {
+        content.add_text(rest, style);
+    }
+}
+
 #[inline]
 pub fn screen(
     sugarloaf: &mut Sugarloaf,
     context_dimension: &ContextDimension,
     assistant: &Assistant,
+    locale: &Locale,
 ) {
     let blue = [0.1764706, 0.6039216, 1.0, 1.0];
     let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
@@ -88,14 +115,14 @@ pub fn screen(
     let heading_line = content.sel(heading);
     heading_line
         .clear()
-        .add_text("Woops! Rio got errors", FragmentStyle::default())
+        .add_text(locale.get("assistant-heading"), FragmentStyle::default())
         .build();
 
     let paragraph_action_line = content.sel(paragraph_action);
     paragraph_action_line
         .clear()
         .add_text(
-            "> press enter to continue",
+            locale.get("press-enter-to-continue"),
             FragmentStyle {
                 color: yellow,
                 ..FragmentStyle::default()
@@ -107,7 +134,7 @@ pub fn screen(
         let paragraph_line = content.sel(paragraph).clear();
 
         for line in report.report.to_string().lines() {
-            paragraph_line.add_text(line, FragmentStyle::default());
+            add_text_with_links(paragraph_line, line, FragmentStyle::default());
         }
 
         paragraph_line.build();
@@ -131,5 +158,8 @@ pub fn screen(
         lines: None,
     }));
 
+    if locale.is_rtl() {
+        sugarloaf.mirror_objects(&mut objects);
+    }
     sugarloaf.set_objects(objects);
 }
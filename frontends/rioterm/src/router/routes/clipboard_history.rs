@@ -0,0 +1,178 @@
+use crate::context::grid::ContextDimension;
+use rio_backend::locale::Locale;
+use rio_backend::sugarloaf::{FragmentStyle, Object, Quad, RichText, Sugarloaf};
+
+/// Characters of an entry shown in the list.
+const PREVIEW_LENGTH: usize = 80;
+
+/// Entry picked in the clipboard history.
+#[derive(Debug, Default)]
+pub struct ClipboardHistory {
+    pub selected: usize,
+}
+
+impl ClipboardHistory {
+    #[inline]
+    pub fn select_next(&mut self, entries: usize) {
+        if self.selected + 1 < entries {
+            self.selected += 1;
+        }
+    }
+
+    #[inline]
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+/// Entry on a single line, the whitespace of multiline texts is collapsed.
+fn preview(entry: &str) -> String {
+    let text = entry.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > PREVIEW_LENGTH {
+        let mut text: String = text.chars().take(PREVIEW_LENGTH - 1).collect();
+        text.push('…');
+        text
+    } else {
+        text
+    }
+}
+
+#[inline]
+pub fn screen<'a>(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    entries: impl Iterator<Item = &'a String>,
+    selected: usize,
+) {
+    let blue = [0.1764706, 0.6039216, 1.0, 1.0];
+    let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
+    let gray = [0.5, 0.5, 0.5, 1.0];
+    let black = [0.0, 0.0, 0.0, 1.0];
+
+    let layout = sugarloaf.window_size();
+
+    let mut objects = Vec::with_capacity(5);
+
+    objects.push(Object::Quad(Quad {
+        position: [0., 0.0],
+        color: black,
+        size: [layout.width, layout.height],
+        ..Quad::default()
+    }));
+    objects.push(Object::Quad(Quad {
+        position: [0., 30.0],
+        color: blue,
+        size: [30., layout.height],
+        ..Quad::default()
+    }));
+
+    let heading = sugarloaf.create_temp_rich_text();
+    let help = sugarloaf.create_temp_rich_text();
+    let list = sugarloaf.create_temp_rich_text();
+
+    sugarloaf.set_rich_text_font_size(&heading, 28.0);
+    sugarloaf.set_rich_text_font_size(&help, 14.0);
+    sugarloaf.set_rich_text_font_size(&list, 16.0);
+
+    let content = sugarloaf.content();
+
+    content
+        .sel(heading)
+        .clear()
+        .add_text(
+            locale.get("clipboard-history-heading"),
+            FragmentStyle::default(),
+        )
+        .build();
+
+    content
+        .sel(help)
+        .clear()
+        .add_text(
+            locale.get("clipboard-history-help"),
+            FragmentStyle {
+                color: gray,
+                ..FragmentStyle::default()
+            },
+        )
+        .build();
+
+    let list_content = content.sel(list).clear();
+    let mut is_empty = true;
+    for (index, entry) in entries.enumerate() {
+        is_empty = false;
+        // The first entries can be picked by their number
+        let number = if index < 9 {
+            format!("{} ", index + 1)
+        } else {
+            String::from("  ")
+        };
+        let style = if index == selected {
+            FragmentStyle {
+                color: black,
+                background_color: Some(yellow),
+                ..FragmentStyle::default()
+            }
+        } else {
+            FragmentStyle::default()
+        };
+
+        list_content
+            .new_line()
+            .add_text(
+                &number,
+                FragmentStyle {
+                    color: gray,
+                    ..FragmentStyle::default()
+                },
+            )
+            .add_text(&format!(" {} ", preview(entry)), style);
+    }
+    if is_empty {
+        list_content.new_line().add_text(
+            locale.get("clipboard-history-empty"),
+            FragmentStyle::default(),
+        );
+    }
+    list_content.build();
+
+    objects.push(Object::RichText(RichText {
+        id: heading,
+        position: [70., context_dimension.margin.top_y + 30.],
+        lines: None,
+    }));
+    objects.push(Object::RichText(RichText {
+        id: help,
+        position: [70., context_dimension.margin.top_y + 70.],
+        lines: None,
+    }));
+    objects.push(Object::RichText(RichText {
+        id: list,
+        position: [70., context_dimension.margin.top_y + 110.],
+        lines: None,
+    }));
+
+    if locale.is_rtl() {
+        sugarloaf.mirror_objects(&mut objects);
+    }
+    sugarloaf.set_objects(objects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(
+            preview("cargo build\n  --release\n"),
+            "cargo build --release"
+        );
+
+        let long = "a".repeat(PREVIEW_LENGTH + 10);
+        let text = preview(&long);
+        assert_eq!(text.chars().count(), PREVIEW_LENGTH);
+        assert!(text.ends_with('…'));
+    }
+}
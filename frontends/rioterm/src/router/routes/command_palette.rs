@@ -0,0 +1,325 @@
+use crate::bindings::Action;
+use crate::context::grid::ContextDimension;
+use rio_backend::locale::Locale;
+use rio_backend::sugarloaf::{FragmentStyle, Object, Quad, RichText, Sugarloaf};
+
+/// Entries shown at once, the list scrolls to keep the selected one visible.
+const VISIBLE_ENTRIES: usize = 15;
+
+/// Actions listed in the palette, named like in the key bindings config.
+const ACTIONS: &[&str] = &[
+    "CreateTab",
+    "CloseTab",
+    "CloseSplitOrTab",
+    "CloseUnfocusedTabs",
+    "SelectNextTab",
+    "SelectPrevTab",
+    "SelectLastTab",
+    "MoveCurrentTabToNext",
+    "MoveCurrentTabToPrev",
+    "MoveCurrentTabToNewWindow",
+    "SplitRight",
+    "SplitDown",
+    "SelectNextSplit",
+    "SelectPrevSplit",
+    "SelectNextSplitOrTab",
+    "SelectPrevSplitOrTab",
+    "CreateWindow",
+    "NextWindow",
+    "PreviousWindow",
+    "MergeWindows",
+    "SearchForward",
+    "SearchBackward",
+    "Copy",
+    "CopyLink",
+    "CopyFormatted",
+    "Paste",
+    "ClipboardHistory",
+    "SelectAll",
+    "SelectLastCommandOutput",
+    "CopyRecentOutput",
+    "SpeakRecentOutput",
+    "HintOpen",
+    "HintCopy",
+    "HintPaste",
+    "ClearHistory",
+    "ScrollHalfPageUp",
+    "ScrollHalfPageDown",
+    "ScrollToTop",
+    "ScrollToBottom",
+    "ToggleViMode",
+    "IncreaseFontSize",
+    "DecreaseFontSize",
+    "ResetFontSize",
+    "ToggleFullscreen",
+    "IncreaseOpacity",
+    "DecreaseOpacity",
+    "ToggleOpacity",
+    "ToggleControlPictures",
+    "ToggleLatin1Fallback",
+    "ToggleDebugOverlay",
+    "DumpMetrics",
+    "Screenshot",
+    "ExportScreen",
+    "ExportScrollback",
+    "OpenConfigEditor",
+    "OpenSettings",
+    "Quit",
+];
+
+/// Query typed in the command palette and the entry picked.
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl CommandPalette {
+    /// Names of the actions matching the query, the best matches first.
+    pub fn matches(&self) -> Vec<&'static str> {
+        let mut matches: Vec<(usize, &'static str)> = ACTIONS
+            .iter()
+            .filter_map(|name| Some((fuzzy_score(name, &self.query)?, *name)))
+            .collect();
+        matches.sort_by_key(|(score, _)| *score);
+        matches.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Action of the selected entry, `None` when nothing matches.
+    pub fn selected_action(&self) -> Option<Action> {
+        self.matches()
+            .get(self.selected)
+            .map(|name| Action::from(name.to_string()))
+    }
+
+    #[inline]
+    pub fn push(&mut self, text: &str) {
+        self.query.push_str(text);
+        self.selected = 0;
+    }
+
+    #[inline]
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    #[inline]
+    pub fn select_next(&mut self, entries: usize) {
+        if self.selected + 1 < entries {
+            self.selected += 1;
+        }
+    }
+
+    #[inline]
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+/// Score of the query characters found in order in the name, lower is
+/// better, `None` when some of them are missing. The characters skipped
+/// before each match are added up, so prefixes and words typed in full
+/// come first.
+fn fuzzy_score(name: &str, query: &str) -> Option<usize> {
+    let mut chars = name.chars().enumerate();
+    let mut score = 0;
+    let mut next_index = 0;
+    for query_char in query.chars().filter(|c| !c.is_whitespace()) {
+        let (index, _) = chars.find(|(_, c)| c.eq_ignore_ascii_case(&query_char))?;
+        score += index - next_index;
+        next_index = index + 1;
+    }
+    Some(score)
+}
+
+#[inline]
+pub fn screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    query: &str,
+    entries: &[(&str, Option<String>)],
+    selected: usize,
+) {
+    let blue = [0.1764706, 0.6039216, 1.0, 1.0];
+    let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
+    let gray = [0.5, 0.5, 0.5, 1.0];
+    let black = [0.0, 0.0, 0.0, 1.0];
+
+    let layout = sugarloaf.window_size();
+
+    let mut objects = Vec::with_capacity(6);
+
+    objects.push(Object::Quad(Quad {
+        position: [0., 0.0],
+        color: black,
+        size: [layout.width, layout.height],
+        ..Quad::default()
+    }));
+    objects.push(Object::Quad(Quad {
+        position: [0., 30.0],
+        color: blue,
+        size: [30., layout.height],
+        ..Quad::default()
+    }));
+
+    let heading = sugarloaf.create_temp_rich_text();
+    let help = sugarloaf.create_temp_rich_text();
+    let input = sugarloaf.create_temp_rich_text();
+    let list = sugarloaf.create_temp_rich_text();
+
+    sugarloaf.set_rich_text_font_size(&heading, 28.0);
+    sugarloaf.set_rich_text_font_size(&help, 14.0);
+    sugarloaf.set_rich_text_font_size(&input, 18.0);
+    sugarloaf.set_rich_text_font_size(&list, 16.0);
+
+    let content = sugarloaf.content();
+
+    content
+        .sel(heading)
+        .clear()
+        .add_text(
+            locale.get("command-palette-heading"),
+            FragmentStyle::default(),
+        )
+        .build();
+
+    content
+        .sel(help)
+        .clear()
+        .add_text(
+            locale.get("command-palette-help"),
+            FragmentStyle {
+                color: gray,
+                ..FragmentStyle::default()
+            },
+        )
+        .build();
+
+    content
+        .sel(input)
+        .clear()
+        .add_text(
+            "> ",
+            FragmentStyle {
+                color: blue,
+                ..FragmentStyle::default()
+            },
+        )
+        .add_text(query, FragmentStyle::default())
+        .add_text(
+            "█",
+            FragmentStyle {
+                color: gray,
+                ..FragmentStyle::default()
+            },
+        )
+        .build();
+
+    let list_content = content.sel(list).clear();
+    let first = selected.saturating_sub(VISIBLE_ENTRIES - 1);
+    for (index, (name, keys)) in
+        entries.iter().enumerate().skip(first).take(VISIBLE_ENTRIES)
+    {
+        let style = if index == selected {
+            FragmentStyle {
+                color: black,
+                background_color: Some(yellow),
+                ..FragmentStyle::default()
+            }
+        } else {
+            FragmentStyle::default()
+        };
+
+        list_content
+            .new_line()
+            .add_text(&format!(" {name} "), style);
+        // The keys teach the binding of the action for the next time
+        if let Some(keys) = keys {
+            list_content.add_text(
+                &format!("  {keys}"),
+                FragmentStyle {
+                    color: gray,
+                    ..FragmentStyle::default()
+                },
+            );
+        }
+    }
+    if entries.is_empty() {
+        list_content.new_line().add_text(
+            locale.get("command-palette-empty"),
+            FragmentStyle::default(),
+        );
+    }
+    list_content.build();
+
+    objects.push(Object::RichText(RichText {
+        id: heading,
+        position: [70., context_dimension.margin.top_y + 30.],
+        lines: None,
+    }));
+    objects.push(Object::RichText(RichText {
+        id: help,
+        position: [70., context_dimension.margin.top_y + 70.],
+        lines: None,
+    }));
+    objects.push(Object::RichText(RichText {
+        id: input,
+        position: [70., context_dimension.margin.top_y + 100.],
+        lines: None,
+    }));
+    objects.push(Object::RichText(RichText {
+        id: list,
+        position: [70., context_dimension.margin.top_y + 135.],
+        lines: None,
+    }));
+
+    if locale.is_rtl() {
+        sugarloaf.mirror_objects(&mut objects);
+    }
+    sugarloaf.set_objects(objects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("CreateTab", ""), Some(0));
+        assert_eq!(fuzzy_score("CreateTab", "create"), Some(0));
+        assert_eq!(fuzzy_score("CreateTab", "ct"), Some(3));
+        assert_eq!(fuzzy_score("CreateTab", "new tab"), None);
+        assert_eq!(fuzzy_score("SplitRight", "split r"), Some(0));
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut palette = CommandPalette::default();
+        assert_eq!(palette.matches().len(), ACTIONS.len());
+
+        palette.push("split");
+        let matches = palette.matches();
+        assert_eq!(matches[0], "SplitRight");
+        assert!(matches.contains(&"SelectNextSplit"));
+        assert!(!matches.contains(&"CreateTab"));
+
+        palette.select_next(matches.len());
+        assert_eq!(palette.selected_action(), Some(Action::SplitDown));
+
+        // Editing the query picks the best match again
+        palette.pop();
+        assert_eq!(palette.selected, 0);
+
+        palette.push("xyz");
+        assert_eq!(palette.selected_action(), None);
+    }
+
+    #[test]
+    fn test_actions_are_bindable() {
+        for name in ACTIONS {
+            assert_ne!(Action::from(name.to_string()), Action::None, "{name}");
+        }
+    }
+}
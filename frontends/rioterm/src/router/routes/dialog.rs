@@ -1,60 +1,104 @@
 use crate::context::grid::ContextDimension;
-use rio_backend::sugarloaf::{FragmentStyle, Object, Quad, RichText, Sugarloaf};
+use crate::screen::paste::{shell_segments, PasteWarning};
+use rio_backend::locale::Locale;
+use rio_backend::sugarloaf::{
+    Content, FragmentStyle, Object, Quad, RichText, Sugarloaf, SugarloafWindowSize,
+};
 
-#[inline]
-pub fn screen(
-    sugarloaf: &mut Sugarloaf,
-    context_dimension: &ContextDimension,
-    heading_content: &str,
-    confirm_content: &str,
-    quit_content: &str,
-) {
-    let blue = [0.1764706, 0.6039216, 1.0, 1.0];
-    let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
-    let red = [1.0, 0.07058824, 0.38039216, 1.0];
-    let black = [0.0, 0.0, 0.0, 1.0];
-
-    let layout = sugarloaf.window_size();
+const BLUE: [f32; 4] = [0.1764706, 0.6039216, 1.0, 1.0];
+const YELLOW: [f32; 4] = [0.9882353, 0.7294118, 0.15686275, 1.0];
+const RED: [f32; 4] = [1.0, 0.07058824, 0.38039216, 1.0];
+const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const GRAY: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
 
-    let mut objects = Vec::with_capacity(7);
+/// Quit, or close of a window, waiting for a confirmation.
+#[derive(Debug, Default)]
+pub struct PendingQuit {
+    /// Only the window is closed.
+    pub close_window: bool,
+    /// Programs which would be stopped.
+    pub programs: Vec<String>,
+}
 
+/// Background of the dialogs, black with the colored bars on the left.
+fn push_background(
+    objects: &mut Vec<Object>,
+    layout: SugarloafWindowSize,
+    context_dimension: &ContextDimension,
+) {
     objects.push(Object::Quad(Quad {
         position: [0., 0.0],
-        color: black,
+        color: BLACK,
         size: [layout.width, layout.height],
         ..Quad::default()
     }));
     objects.push(Object::Quad(Quad {
         position: [0., 30.0],
-        color: blue,
+        color: BLUE,
         size: [30., layout.height],
         ..Quad::default()
     }));
     objects.push(Object::Quad(Quad {
         position: [15., context_dimension.margin.top_y + 60.],
-        color: yellow,
+        color: YELLOW,
         size: [30., layout.height],
         ..Quad::default()
     }));
     objects.push(Object::Quad(Quad {
         position: [30., context_dimension.margin.top_y + 120.],
-        color: red,
+        color: RED,
         size: [30., layout.height],
         ..Quad::default()
     }));
+}
+
+fn set_button(content: &mut Content, id: usize, text: &str, color: [f32; 4]) {
+    content
+        .sel(id)
+        .clear()
+        .add_text(
+            &format!(" {text} "),
+            FragmentStyle {
+                color: BLACK,
+                background_color: Some(color),
+                ..FragmentStyle::default()
+            },
+        )
+        .build();
+}
+
+#[inline]
+pub fn screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    pending_quit: &PendingQuit,
+) {
+    let layout = sugarloaf.window_size();
+
+    let mut objects = Vec::with_capacity(8);
+    push_background(&mut objects, layout, context_dimension);
 
     let heading = sugarloaf.create_temp_rich_text();
+    let programs = sugarloaf.create_temp_rich_text();
     let confirm = sugarloaf.create_temp_rich_text();
     let quit = sugarloaf.create_temp_rich_text();
 
     sugarloaf.set_rich_text_font_size(&heading, 28.0);
+    sugarloaf.set_rich_text_font_size(&programs, 16.0);
     sugarloaf.set_rich_text_font_size(&confirm, 18.0);
     sugarloaf.set_rich_text_font_size(&quit, 18.0);
 
+    let (heading_key, confirm_key) = if pending_quit.close_window {
+        ("close-window-heading", "close-window-confirm")
+    } else {
+        ("quit-heading", "quit-confirm")
+    };
+
     let content = sugarloaf.content();
 
     let heading_line = content.sel(heading).clear();
-    for line in heading_content.to_string().lines() {
+    for line in locale.get(heading_key).lines() {
         heading_line.add_text(line, FragmentStyle::default());
     }
     heading_line.build();
@@ -65,43 +109,200 @@ pub fn screen(
         lines: None,
     }));
 
-    let confirm_line = content.sel(confirm);
-    confirm_line
-        .clear()
-        .add_text(
-            &format!(" {} ", confirm_content),
+    let programs_line = content.sel(programs).clear();
+    if !pending_quit.programs.is_empty() {
+        programs_line.add_text(
+            &locale.format(
+                "quit-running-programs",
+                &[("programs", &pending_quit.programs.join(", "))],
+            ),
             FragmentStyle {
-                color: [0., 0., 0., 1.],
-                background_color: Some(yellow),
+                color: GRAY,
                 ..FragmentStyle::default()
             },
-        )
-        .build();
+        );
+    }
+    programs_line.build();
+
+    objects.push(Object::RichText(RichText {
+        id: programs,
+        position: [70., context_dimension.margin.top_y + 70.],
+        lines: None,
+    }));
 
+    set_button(content, confirm, locale.get("quit-cancel"), YELLOW);
     objects.push(Object::RichText(RichText {
         id: confirm,
-        position: [70., context_dimension.margin.top_y + 100.],
+        position: [70., context_dimension.margin.top_y + 110.],
         lines: None,
     }));
 
-    let quit_line = content.sel(quit);
-    quit_line
+    set_button(content, quit, locale.get(confirm_key), RED);
+    objects.push(Object::RichText(RichText {
+        id: quit,
+        position: [70., context_dimension.margin.top_y + 150.],
+        lines: None,
+    }));
+
+    if locale.is_rtl() {
+        sugarloaf.mirror_objects(&mut objects);
+    }
+    sugarloaf.set_objects(objects);
+}
+
+/// Asks to confirm a suspicious paste, showing why and its first lines,
+/// with the characters interpreted by the shell highlighted if requested.
+#[inline]
+pub fn paste_screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    warning: PasteWarning,
+    preview: &[String],
+    highlight_shell: bool,
+) {
+    let keys = ConfirmKeys {
+        heading: "paste-heading",
+        cancel: "paste-cancel",
+        confirm: "paste-confirm",
+        warning: warning.locale_key(),
+    };
+    let preview: Vec<Vec<(String, bool)>> = preview
+        .iter()
+        .map(|line| {
+            if highlight_shell {
+                shell_segments(line)
+            } else {
+                vec![(line.to_owned(), false)]
+            }
+        })
+        .collect();
+    confirm_screen(sugarloaf, context_dimension, locale, keys, &preview);
+}
+
+/// Asks to confirm the clipboard access of a program, showing the text
+/// it writes.
+#[inline]
+pub fn clipboard_screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    warning: &'static str,
+    preview: &[String],
+) {
+    let keys = ConfirmKeys {
+        heading: "clipboard-heading",
+        cancel: "clipboard-deny",
+        confirm: "clipboard-allow",
+        warning,
+    };
+    let preview: Vec<Vec<(String, bool)>> = preview
+        .iter()
+        .map(|line| vec![(line.to_owned(), false)])
+        .collect();
+    confirm_screen(sugarloaf, context_dimension, locale, keys, &preview);
+}
+
+/// Translation keys of the texts of a confirmation dialog.
+struct ConfirmKeys {
+    heading: &'static str,
+    cancel: &'static str,
+    confirm: &'static str,
+    warning: &'static str,
+}
+
+fn confirm_screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    keys: ConfirmKeys,
+    // Segments of the lines, the ones interpreted by the shell are marked
+    preview: &[Vec<(String, bool)>],
+) {
+    let layout = sugarloaf.window_size();
+
+    let mut objects = Vec::with_capacity(8);
+    push_background(&mut objects, layout, context_dimension);
+
+    let heading = sugarloaf.create_temp_rich_text();
+    let cancel = sugarloaf.create_temp_rich_text();
+    let confirm = sugarloaf.create_temp_rich_text();
+    let text = sugarloaf.create_temp_rich_text();
+
+    sugarloaf.set_rich_text_font_size(&heading, 28.0);
+    sugarloaf.set_rich_text_font_size(&cancel, 18.0);
+    sugarloaf.set_rich_text_font_size(&confirm, 18.0);
+    sugarloaf.set_rich_text_font_size(&text, 14.0);
+
+    let content = sugarloaf.content();
+
+    content
+        .sel(heading)
         .clear()
-        .add_text(
-            &format!(" {} ", quit_content),
-            FragmentStyle {
-                color: [0., 0., 0., 1.],
-                background_color: Some(red),
-                ..FragmentStyle::default()
-            },
-        )
+        .add_text(locale.get(keys.heading), FragmentStyle::default())
         .build();
+    objects.push(Object::RichText(RichText {
+        id: heading,
+        position: [70., context_dimension.margin.top_y + 30.],
+        lines: None,
+    }));
 
+    set_button(content, cancel, locale.get(keys.cancel), YELLOW);
     objects.push(Object::RichText(RichText {
-        id: quit,
+        id: cancel,
+        position: [70., context_dimension.margin.top_y + 100.],
+        lines: None,
+    }));
+
+    set_button(content, confirm, locale.get(keys.confirm), RED);
+    objects.push(Object::RichText(RichText {
+        id: confirm,
         position: [70., context_dimension.margin.top_y + 140.],
         lines: None,
     }));
 
+    let text_content = content.sel(text).clear();
+    text_content.new_line().add_text(
+        locale.get(keys.warning),
+        FragmentStyle {
+            color: YELLOW,
+            ..FragmentStyle::default()
+        },
+    );
+    if preview
+        .iter()
+        .any(|line| line.iter().any(|(_, is_special)| *is_special))
+    {
+        text_content.new_line().add_text(
+            locale.get("paste-shell-characters"),
+            FragmentStyle {
+                color: RED,
+                ..FragmentStyle::default()
+            },
+        );
+    }
+    text_content.new_line();
+    for line in preview {
+        text_content.new_line();
+        for (text, is_special) in line {
+            text_content.add_text(
+                text,
+                FragmentStyle {
+                    color: if *is_special { RED } else { GRAY },
+                    ..FragmentStyle::default()
+                },
+            );
+        }
+    }
+    text_content.build();
+    objects.push(Object::RichText(RichText {
+        id: text,
+        position: [70., context_dimension.margin.top_y + 190.],
+        lines: None,
+    }));
+
+    if locale.is_rtl() {
+        sugarloaf.mirror_objects(&mut objects);
+    }
     sugarloaf.set_objects(objects);
 }
@@ -1,5 +1,8 @@
 pub mod assistant;
+pub mod clipboard_history;
+pub mod command_palette;
 pub mod dialog;
+pub mod settings;
 pub mod welcome;
 
 #[derive(PartialEq)]
@@ -8,4 +11,9 @@ pub enum RoutePath {
     Terminal,
     Welcome,
     ConfirmQuit,
+    ClipboardHistory,
+    CommandPalette,
+    Settings,
+    ConfirmPaste,
+    ConfirmClipboardRequest,
 }
@@ -0,0 +1,601 @@
+use crate::context::grid::ContextDimension;
+use rio_backend::ansi::CursorShape;
+use rio_backend::config::Config;
+use rio_backend::locale::Locale;
+use rio_backend::sugarloaf::{FragmentStyle, Object, Quad, RichText, Sugarloaf};
+
+const CURSOR_SHAPES: [&str; 4] = ["block", "underline", "beam", "hidden"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl std::fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingValue::Number(number) => write!(f, "{number}"),
+            SettingValue::Bool(true) => write!(f, "on"),
+            SettingValue::Bool(false) => write!(f, "off"),
+            SettingValue::Text(text) if text.is_empty() => write!(f, "-"),
+            SettingValue::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+impl SettingValue {
+    /// Writes the value at the dotted `key` of the config file, an empty
+    /// text removes it so the default is used.
+    pub fn write(&self, path: &std::path::Path, key: &str) -> std::io::Result<()> {
+        match self {
+            SettingValue::Number(number) => {
+                rio_backend::config::write_config_value(path, key, *number)
+            }
+            SettingValue::Bool(value) => {
+                rio_backend::config::write_config_value(path, key, *value)
+            }
+            SettingValue::Text(text) if text.is_empty() => {
+                rio_backend::config::remove_config_value(path, key)
+            }
+            SettingValue::Text(text) => {
+                rio_backend::config::write_config_value(path, key, text.as_str())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum SettingKind {
+    Number {
+        step: f64,
+        min: f64,
+        max: f64,
+    },
+    Choice(Vec<String>),
+    Toggle,
+    /// Typed after pressing enter.
+    Text,
+}
+
+#[derive(Debug)]
+struct Setting {
+    /// Dotted key of the option in the config file.
+    key: &'static str,
+    kind: SettingKind,
+    value: SettingValue,
+}
+
+/// Options changed in the settings screen, each change is written to the
+/// config file right away.
+#[derive(Debug, Default)]
+pub struct Settings {
+    settings: Vec<Setting>,
+    pub selected: usize,
+    /// Text typed for the selected option, while editing it.
+    editing: Option<String>,
+    /// Error of the last write of the config file.
+    pub error: Option<String>,
+}
+
+impl Settings {
+    pub fn new(config: &Config) -> Self {
+        let cursor_shape = match config.cursor.shape {
+            CursorShape::Block => "block",
+            CursorShape::Underline => "underline",
+            CursorShape::Beam => "beam",
+            CursorShape::Hidden => "hidden",
+        };
+
+        let settings = vec![
+            Setting {
+                key: "fonts.family",
+                kind: SettingKind::Text,
+                value: SettingValue::Text(
+                    config.fonts.family.clone().unwrap_or_default(),
+                ),
+            },
+            font_size(config),
+            theme(config),
+            Setting {
+                key: "window.opacity",
+                kind: SettingKind::Number {
+                    step: 0.05,
+                    min: round(crate::screen::MIN_OPACITY as f64),
+                    max: 1.0,
+                },
+                value: SettingValue::Number(round(config.window.opacity as f64)),
+            },
+            Setting {
+                key: "cursor.shape",
+                kind: SettingKind::Choice(
+                    CURSOR_SHAPES
+                        .iter()
+                        .map(|shape| shape.to_string())
+                        .collect(),
+                ),
+                value: SettingValue::Text(cursor_shape.to_string()),
+            },
+            Setting {
+                key: "cursor.blinking",
+                kind: SettingKind::Toggle,
+                value: SettingValue::Bool(config.cursor.blinking),
+            },
+        ];
+
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Questions of the welcome screen, asked one at a time before the
+    /// config file is created.
+    pub fn first_run(config: &Config) -> Self {
+        let mut settings = Vec::with_capacity(3);
+        // Themes are only found in the config folder, which usually has
+        // none on a first run and then there is nothing to pick
+        let theme = theme(config);
+        if matches!(&theme.kind, SettingKind::Choice(themes) if themes.len() > 1) {
+            settings.push(theme);
+        }
+        settings.push(font_size(config));
+        settings.push(Setting {
+            key: "navigation.mode",
+            kind: SettingKind::Choice(
+                rio_backend::config::navigation::modes_as_vec_string(),
+            ),
+            value: SettingValue::Text(config.navigation.mode.to_string()),
+        });
+
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Number of options.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.settings.len()
+    }
+
+    /// Option selected and its value.
+    #[inline]
+    pub fn selected_setting(&self) -> Option<(&'static str, &SettingValue)> {
+        self.settings
+            .get(self.selected)
+            .map(|setting| (setting.key, &setting.value))
+    }
+
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = (&'static str, &SettingValue)> {
+        self.settings
+            .iter()
+            .map(|setting| (setting.key, &setting.value))
+    }
+
+    #[inline]
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    #[inline]
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.settings.len() {
+            self.selected += 1;
+        }
+    }
+
+    #[inline]
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Steps the number, choice or toggle selected, returns the option and
+    /// its new value to write.
+    pub fn change(&mut self, forward: bool) -> Option<(&'static str, SettingValue)> {
+        let setting = self.settings.get_mut(self.selected)?;
+        let value = match (&setting.kind, &setting.value) {
+            (SettingKind::Number { step, min, max }, SettingValue::Number(number)) => {
+                let step = if forward { *step } else { -step };
+                SettingValue::Number(round(number + step).clamp(*min, *max))
+            }
+            (SettingKind::Choice(choices), SettingValue::Text(text)) => {
+                let index = choices.iter().position(|choice| choice == text);
+                let index = match (index, forward) {
+                    (Some(index), true) => (index + 1) % choices.len(),
+                    (Some(index), false) => (index + choices.len() - 1) % choices.len(),
+                    (None, _) => 0,
+                };
+                SettingValue::Text(choices.get(index)?.clone())
+            }
+            (SettingKind::Toggle, SettingValue::Bool(value)) => {
+                SettingValue::Bool(!value)
+            }
+            _ => return None,
+        };
+
+        if value == setting.value {
+            return None;
+        }
+        setting.value = value.clone();
+        Some((setting.key, value))
+    }
+
+    /// Starts typing the text option selected, returns false for the other
+    /// kinds of option.
+    pub fn start_editing(&mut self) -> bool {
+        match self.settings.get(self.selected) {
+            Some(Setting {
+                kind: SettingKind::Text,
+                value: SettingValue::Text(text),
+                ..
+            }) => {
+                self.editing = Some(text.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, text: &str) {
+        if let Some(editing) = self.editing.as_mut() {
+            editing.push_str(text);
+        }
+    }
+
+    #[inline]
+    pub fn pop(&mut self) {
+        if let Some(editing) = self.editing.as_mut() {
+            editing.pop();
+        }
+    }
+
+    #[inline]
+    pub fn cancel_editing(&mut self) {
+        self.editing = None;
+    }
+
+    /// Keeps the text typed, returns the option and its new value to write.
+    pub fn finish_editing(&mut self) -> Option<(&'static str, SettingValue)> {
+        let text = self.editing.take()?;
+        let setting = self.settings.get_mut(self.selected)?;
+        let value = SettingValue::Text(text.trim().to_string());
+        if value == setting.value {
+            return None;
+        }
+        setting.value = value.clone();
+        Some((setting.key, value))
+    }
+
+    /// Writes a changed option to the config file at `path`, keeping the
+    /// error to show it. Returns whether the config has to be loaded again.
+    pub fn save(
+        &mut self,
+        path: &std::path::Path,
+        (key, value): (&str, SettingValue),
+    ) -> bool {
+        match value.write(path, key) {
+            Ok(()) => {
+                self.error = None;
+                true
+            }
+            Err(err) => {
+                tracing::error!("could not write {key} to the config file: {err}");
+                self.error = Some(err.to_string());
+                false
+            }
+        }
+    }
+}
+
+#[inline]
+fn font_size(config: &Config) -> Setting {
+    Setting {
+        key: "fonts.size",
+        kind: SettingKind::Number {
+            step: 1.0,
+            min: 6.0,
+            max: 100.0,
+        },
+        value: SettingValue::Number(config.fonts.size as f64),
+    }
+}
+
+#[inline]
+fn theme(config: &Config) -> Setting {
+    Setting {
+        key: "theme",
+        kind: SettingKind::Choice(themes(&config.theme)),
+        value: SettingValue::Text(config.theme.clone()),
+    }
+}
+
+/// Steps like `0.05` leave floating point noise behind.
+#[inline]
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Names of the themes in the themes folder of the config, the current
+/// one is kept even if its file is missing. An empty name means no theme.
+fn themes(current: &str) -> Vec<String> {
+    let mut themes: Vec<String> =
+        std::fs::read_dir(rio_backend::config::config_dir_path().join("themes"))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+    themes.sort();
+    if !themes.iter().any(|theme| theme == current) {
+        themes.push(current.to_string());
+    }
+    if !themes.iter().any(String::is_empty) {
+        themes.insert(0, String::new());
+    }
+    themes
+}
+
+#[inline]
+pub fn screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    settings: &Settings,
+) {
+    let blue = [0.1764706, 0.6039216, 1.0, 1.0];
+    let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
+    let red = [1.0, 0.07058824, 0.38039216, 1.0];
+    let gray = [0.5, 0.5, 0.5, 1.0];
+    let black = [0.0, 0.0, 0.0, 1.0];
+
+    let layout = sugarloaf.window_size();
+
+    let mut objects = Vec::with_capacity(5);
+
+    objects.push(Object::Quad(Quad {
+        position: [0., 0.0],
+        color: black,
+        size: [layout.width, layout.height],
+        ..Quad::default()
+    }));
+    objects.push(Object::Quad(Quad {
+        position: [0., 30.0],
+        color: blue,
+        size: [30., layout.height],
+        ..Quad::default()
+    }));
+
+    let heading = sugarloaf.create_temp_rich_text();
+    let help = sugarloaf.create_temp_rich_text();
+    let list = sugarloaf.create_temp_rich_text();
+
+    sugarloaf.set_rich_text_font_size(&heading, 28.0);
+    sugarloaf.set_rich_text_font_size(&help, 14.0);
+    sugarloaf.set_rich_text_font_size(&list, 16.0);
+
+    let content = sugarloaf.content();
+
+    content
+        .sel(heading)
+        .clear()
+        .add_text(locale.get("settings-heading"), FragmentStyle::default())
+        .build();
+
+    let help_content = content.sel(help).clear().add_text(
+        locale.get("settings-help"),
+        FragmentStyle {
+            color: gray,
+            ..FragmentStyle::default()
+        },
+    );
+    if let Some(error) = &settings.error {
+        help_content.new_line().add_text(
+            &format!("{} {error}", locale.get("settings-error")),
+            FragmentStyle {
+                color: red,
+                ..FragmentStyle::default()
+            },
+        );
+    }
+    help_content.build();
+
+    let key_width = settings
+        .settings
+        .iter()
+        .map(|setting| setting.key.len())
+        .max()
+        .unwrap_or_default();
+    let list_content = content.sel(list).clear();
+    for (index, setting) in settings.settings.iter().enumerate() {
+        let is_selected = index == settings.selected;
+        let value = match (&settings.editing, is_selected) {
+            (Some(editing), true) => format!("{editing}█"),
+            _ => setting.value.to_string(),
+        };
+        let value_style = if is_selected {
+            FragmentStyle {
+                color: black,
+                background_color: Some(yellow),
+                ..FragmentStyle::default()
+            }
+        } else {
+            FragmentStyle::default()
+        };
+
+        list_content
+            .new_line()
+            .add_text(
+                &format!("{:key_width$}  ", setting.key),
+                FragmentStyle {
+                    color: gray,
+                    ..FragmentStyle::default()
+                },
+            )
+            .add_text(&format!(" {value} "), value_style);
+    }
+    list_content.build();
+
+    objects.push(Object::RichText(RichText {
+        id: heading,
+        position: [70., context_dimension.margin.top_y + 30.],
+        lines: None,
+    }));
+    objects.push(Object::RichText(RichText {
+        id: help,
+        position: [70., context_dimension.margin.top_y + 70.],
+        lines: None,
+    }));
+    objects.push(Object::RichText(RichText {
+        id: list,
+        position: [70., context_dimension.margin.top_y + 130.],
+        lines: None,
+    }));
+
+    if locale.is_rtl() {
+        sugarloaf.mirror_objects(&mut objects);
+    }
+    sugarloaf.set_objects(objects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change() {
+        let mut settings = Settings::new(&Config::default());
+
+        // fonts.size
+        settings.select_next();
+        let (key, value) = settings.change(true).unwrap();
+        assert_eq!(key, "fonts.size");
+        assert_eq!(
+            value,
+            SettingValue::Number(Config::default().fonts.size as f64 + 1.0)
+        );
+
+        // window.opacity stops at 1
+        settings.select_next();
+        settings.select_next();
+        assert_eq!(settings.change(true), None);
+        assert_eq!(
+            settings.change(false),
+            Some(("window.opacity", SettingValue::Number(0.95)))
+        );
+
+        // and at the lowest opacity Rio uses
+        while settings.change(false).is_some() {}
+        assert_eq!(
+            settings.change(true),
+            Some(("window.opacity", SettingValue::Number(0.15)))
+        );
+        settings.change(false);
+
+        // cursor.shape cycles
+        settings.select_next();
+        assert_eq!(
+            settings.change(false),
+            Some(("cursor.shape", SettingValue::Text(String::from("hidden"))))
+        );
+        assert_eq!(
+            settings.change(true),
+            Some(("cursor.shape", SettingValue::Text(String::from("block"))))
+        );
+
+        settings.select_next();
+        assert_eq!(
+            settings.change(true),
+            Some(("cursor.blinking", SettingValue::Bool(true)))
+        );
+        settings.select_next();
+        assert_eq!(settings.selected, 5);
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut settings = Settings::new(&Config::default());
+        assert!(settings.start_editing());
+        settings.push("Fira Code");
+        settings.pop();
+        settings.push("e ");
+        assert_eq!(
+            settings.finish_editing(),
+            Some((
+                "fonts.family",
+                SettingValue::Text(String::from("Fira Code"))
+            ))
+        );
+        assert!(!settings.is_editing());
+
+        // Numbers aren't typed
+        settings.select_next();
+        assert!(!settings.start_editing());
+    }
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join("test-rio-settings-save-config.toml");
+        std::fs::write(&path, "[fonts]\nsize = 14\n").unwrap();
+
+        let mut settings = Settings::new(&Config::default());
+        settings.start_editing();
+        settings.push("Fira Code");
+        let change = settings.finish_editing().unwrap();
+        assert!(settings.save(&path, change.clone()));
+        assert_eq!(settings.error, None);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[fonts]\nsize = 14\nfamily = \"Fira Code\"\n"
+        );
+
+        // A failed write keeps the error and isn't applied
+        assert!(!settings.save(&path.join("config.toml"), change));
+        assert!(settings.error.is_some());
+    }
+
+    #[test]
+    fn test_first_run() {
+        let mut settings = Settings::first_run(&Config::default());
+        let has_themes = themes("").len() > 1;
+        assert_eq!(settings.count(), if has_themes { 3 } else { 2 });
+        assert_eq!(settings.values().any(|(key, _)| key == "theme"), has_themes);
+
+        if has_themes {
+            settings.select_next();
+        }
+        settings.change(false);
+        settings.select_next();
+        assert_eq!(settings.selected, settings.count() - 1);
+        assert_eq!(
+            settings.change(true),
+            Some((
+                "navigation.mode",
+                SettingValue::Text(String::from(
+                    // The choice after the default one
+                    if cfg!(target_os = "macos") {
+                        "Plain"
+                    } else {
+                        "TopTab"
+                    }
+                ))
+            ))
+        );
+
+        let values: Vec<_> = settings.values().collect();
+        assert_eq!(
+            values[values.len() - 2],
+            (
+                "fonts.size",
+                &SettingValue::Number(Config::default().fonts.size as f64 - 1.0)
+            )
+        );
+    }
+}
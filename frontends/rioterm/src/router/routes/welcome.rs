@@ -1,16 +1,24 @@
 use crate::context::grid::ContextDimension;
+use crate::router::routes::settings::Settings;
+use rio_backend::locale::Locale;
 use rio_backend::sugarloaf::{FragmentStyle, Object, Quad, RichText, Sugarloaf};
 
 #[inline]
-pub fn screen(sugarloaf: &mut Sugarloaf, context_dimension: &ContextDimension) {
+pub fn screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    locale: &Locale,
+    settings: &Settings,
+) {
     let blue = [0.1764706, 0.6039216, 1.0, 1.0];
     let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
     let red = [1.0, 0.07058824, 0.38039216, 1.0];
+    let gray = [0.5, 0.5, 0.5, 1.0];
     let black = [0.0, 0.0, 0.0, 1.0];
 
     let layout = sugarloaf.window_size();
 
-    let mut objects = Vec::with_capacity(7);
+    let mut objects = Vec::with_capacity(8);
 
     objects.push(Object::Quad(Quad {
         position: [0., 0.0],
@@ -42,44 +50,85 @@ pub fn screen(sugarloaf: &mut Sugarloaf, context_dimension: &ContextDimension) {
 
     let heading = sugarloaf.create_temp_rich_text();
     let paragraph_action = sugarloaf.create_temp_rich_text();
+    let question = sugarloaf.create_temp_rich_text();
     let paragraph = sugarloaf.create_temp_rich_text();
 
     sugarloaf.set_rich_text_font_size(&heading, 28.0);
     sugarloaf.set_rich_text_font_size(&paragraph_action, 18.0);
+    sugarloaf.set_rich_text_font_size(&question, 18.0);
     sugarloaf.set_rich_text_font_size(&paragraph, 16.0);
 
     let content = sugarloaf.content();
     let heading_line = content.sel(heading);
     heading_line
         .clear()
-        .add_text("Welcome to Rio Terminal", FragmentStyle::default())
+        .add_text(locale.get("welcome-heading"), FragmentStyle::default())
         .build();
 
+    let step = (settings.selected + 1).to_string();
+    let steps = settings.count().to_string();
     let paragraph_action_line = content.sel(paragraph_action);
     paragraph_action_line
         .clear()
         .add_text(
-            "> press enter to continue",
+            &locale.format("welcome-step", &[("step", &step), ("steps", &steps)]),
             FragmentStyle {
                 color: yellow,
                 ..FragmentStyle::default()
             },
         )
+        .new_line()
+        .add_text(
+            locale.get("welcome-help"),
+            FragmentStyle {
+                color: gray,
+                ..FragmentStyle::default()
+            },
+        )
         .build();
 
+    let question_line = content.sel(question).clear();
+    if let Some((key, value)) = settings.selected_setting() {
+        let label = match key {
+            "theme" => locale.get("welcome-theme"),
+            "fonts.size" => locale.get("welcome-font-size"),
+            "navigation.mode" => locale.get("welcome-navigation-mode"),
+            _ => key,
+        };
+        question_line
+            .add_text(&format!("{label}  "), FragmentStyle::default())
+            .add_text(
+                &format!(" < {value} > "),
+                FragmentStyle {
+                    background_color: Some(yellow),
+                    color: black,
+                    ..FragmentStyle::default()
+                },
+            );
+    }
+    if let Some(error) = &settings.error {
+        question_line.new_line().add_text(
+            &format!("{} {error}", locale.get("settings-error")),
+            FragmentStyle {
+                color: red,
+                ..FragmentStyle::default()
+            },
+        );
+    }
+    question_line.build();
+
     #[cfg(target_os = "macos")]
-    let shortcut = "\"Command\" + \",\" (comma)";
+    let shortcut = "\"Command\" + \",\"";
 
     #[cfg(not(target_os = "macos"))]
-    let shortcut = "\"Control\" + \"Shift\" + \",\" (comma)";
+    let shortcut = "\"Control\" + \"Shift\" + \",\"";
+
+    let shortcut = locale.format("welcome-settings-shortcut", &[("shortcut", shortcut)]);
 
     let paragraph_line = content.sel(paragraph);
     paragraph_line
         .clear()
-        .add_text(
-            "Your configuration file will be created in",
-            FragmentStyle::default(),
-        )
+        .add_text(locale.get("welcome-config-file"), FragmentStyle::default())
         .new_line()
         .add_text(
             &format!(" {} ", rio_backend::config::config_file_path().display()),
@@ -92,7 +141,7 @@ pub fn screen(sugarloaf: &mut Sugarloaf, context_dimension: &ContextDimension) {
         .new_line()
         .add_text("", FragmentStyle::default())
         .new_line()
-        .add_text("To open settings menu use", FragmentStyle::default())
+        .add_text(locale.get("welcome-settings"), FragmentStyle::default())
         .new_line()
         .add_text(
             &format!(" {} ", shortcut),
@@ -107,7 +156,15 @@ pub fn screen(sugarloaf: &mut Sugarloaf, context_dimension: &ContextDimension) {
         .new_line()
         .add_text("", FragmentStyle::default())
         .new_line()
-        .add_text("More info in rioterm.com", FragmentStyle::default())
+        .add_text(locale.get("welcome-more-info"), FragmentStyle::default())
+        .add_link(
+            "rioterm.com",
+            "https://rioterm.com",
+            FragmentStyle {
+                color: yellow,
+                ..FragmentStyle::default()
+            },
+        )
         .build();
 
     objects.push(Object::RichText(RichText {
@@ -123,10 +180,19 @@ pub fn screen(sugarloaf: &mut Sugarloaf, context_dimension: &ContextDimension) {
     }));
 
     objects.push(Object::RichText(RichText {
-        id: paragraph,
+        id: question,
         position: [70., context_dimension.margin.top_y + 140.],
         lines: None,
     }));
 
+    objects.push(Object::RichText(RichText {
+        id: paragraph,
+        position: [70., context_dimension.margin.top_y + 210.],
+        lines: None,
+    }));
+
+    if locale.is_rtl() {
+        sugarloaf.mirror_objects(&mut objects);
+    }
     sugarloaf.set_objects(objects);
 }
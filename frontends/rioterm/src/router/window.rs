@@ -139,6 +139,25 @@ pub fn create_window_builder(
     window_builder
 }
 
+/// Changes the opacity of the window background, used by the opacity
+/// gesture.
+#[allow(unused_variables)]
+pub fn set_window_opacity(winit_window: &Window, config: &Config, opacity: f32) {
+    winit_window.set_transparent(opacity < 1.);
+
+    #[cfg(target_os = "macos")]
+    {
+        use rio_window::platform::macos::WindowExtMacOS;
+        let bg_color = config.colors.background.1;
+        winit_window.set_background_color(
+            bg_color.r,
+            bg_color.g,
+            bg_color.b,
+            opacity as f64,
+        );
+    }
+}
+
 pub fn configure_window(winit_window: &Window, config: &Config) {
     let current_mouse_cursor = CursorIcon::Text;
     winit_window.set_cursor(current_mouse_cursor);
@@ -147,9 +166,6 @@ pub fn configure_window(winit_window: &Window, config: &Config) {
     winit_window.set_ime_purpose(ImePurpose::Terminal);
     winit_window.set_ime_allowed(true);
 
-    // TODO: Update ime position based on cursor
-    // winit_window.set_ime_cursor_area(rio_window::dpi::PhysicalPosition::new(500.0, 500.0), rio_window::dpi::LogicalSize::new(400, 400));
-
     // This will ignore diacritical marks and accent characters from
     // being processed as received characters. Instead, the input
     // device's raw character will be placed in event queues with the
@@ -160,14 +176,15 @@ pub fn configure_window(winit_window: &Window, config: &Config) {
         // OnlyRight - The right `Option` key is treated as `Alt`.
         // Both - Both `Option` keys are treated as `Alt`.
         // None - No special handling is applied for `Option` key.
+        use rio_backend::config::keyboard;
         use rio_window::platform::macos::{OptionAsAlt, WindowExtMacOS};
 
-        match config.option_as_alt.to_lowercase().as_str() {
-            "both" => winit_window.set_option_as_alt(OptionAsAlt::Both),
-            "left" => winit_window.set_option_as_alt(OptionAsAlt::OnlyLeft),
-            "right" => winit_window.set_option_as_alt(OptionAsAlt::OnlyRight),
-            _ => {}
-        }
+        winit_window.set_option_as_alt(match config.option_as_alt() {
+            keyboard::OptionAsAlt::Both => OptionAsAlt::Both,
+            keyboard::OptionAsAlt::Left => OptionAsAlt::OnlyLeft,
+            keyboard::OptionAsAlt::Right => OptionAsAlt::OnlyRight,
+            keyboard::OptionAsAlt::None => OptionAsAlt::None,
+        });
     }
 
     let is_transparent = config.window.opacity < 1.;
@@ -27,6 +27,10 @@ pub enum Topic {
     RenderRoute,
     UpdateConfig,
     CursorBlinking,
+    BatterySaver,
+    ThemeSchedule,
+    Animation,
+    DelayedSearch,
 }
 
 /// Event scheduled to be emitted at a specific time.
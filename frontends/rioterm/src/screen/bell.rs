@@ -0,0 +1,128 @@
+use rio_backend::config::bell::Bell;
+use std::time::{Duration, Instant};
+
+/// Bells of the window, shown by the debug overlay.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BellStats {
+    pub rung: u64,
+    /// Dropped because the foreground program is muted.
+    pub muted: u64,
+    /// Dropped because they came too close to the previous one.
+    pub limited: u64,
+}
+
+/// Decides which bells are rung, so a program ringing in a loop doesn't
+/// keep requesting attention.
+pub struct BellPolicy {
+    min_interval: Duration,
+    mute: Vec<String>,
+    last_rung: Option<Instant>,
+    pub stats: BellStats,
+}
+
+impl BellPolicy {
+    pub fn new(config: &Bell) -> Self {
+        Self {
+            min_interval: Duration::from_millis(config.min_interval),
+            mute: config.mute.clone(),
+            last_rung: None,
+            stats: BellStats::default(),
+        }
+    }
+
+    /// Keeps the counters and the time of the last bell.
+    pub fn set_config(&mut self, config: &Bell) {
+        self.min_interval = Duration::from_millis(config.min_interval);
+        self.mute = config.mute.clone();
+    }
+
+    /// Whether the bell is rung, `program` gives the name of the foreground
+    /// program of the terminal when known. It's only looked up for the
+    /// bells past the interval, and when some programs are muted.
+    pub fn ring(
+        &mut self,
+        now: Instant,
+        program: impl FnOnce() -> Option<String>,
+    ) -> bool {
+        if self
+            .last_rung
+            .is_some_and(|last| now.saturating_duration_since(last) < self.min_interval)
+        {
+            self.stats.limited += 1;
+            return false;
+        }
+
+        if !self.mute.is_empty()
+            && program().is_some_and(|program| self.mute.contains(&program))
+        {
+            self.stats.muted += 1;
+            return false;
+        }
+
+        self.last_rung = Some(now);
+        self.stats.rung += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bell_rate_limit() {
+        let mut policy = BellPolicy::new(&Bell::default());
+        let now = Instant::now();
+        let zsh = || Some(String::from("zsh"));
+
+        assert!(policy.ring(now, zsh));
+        assert!(!policy.ring(now + Duration::from_millis(50), zsh));
+        assert!(policy.ring(now + Duration::from_millis(100), zsh));
+        assert_eq!(
+            policy.stats,
+            BellStats {
+                rung: 2,
+                muted: 0,
+                limited: 1,
+            }
+        );
+
+        policy.set_config(&Bell {
+            min_interval: 0,
+            ..Bell::default()
+        });
+        assert!(policy.ring(now + Duration::from_millis(100), || None));
+    }
+
+    #[test]
+    fn test_bell_program_lookup() {
+        let mut policy = BellPolicy::new(&Bell::default());
+        let now = Instant::now();
+
+        // Nothing is muted, the foreground program isn't looked up
+        assert!(policy.ring(now, || unreachable!()));
+
+        // Neither for the bells dropped by the interval
+        policy.set_config(&Bell {
+            mute: vec![String::from("vim")],
+            ..Bell::default()
+        });
+        assert!(!policy.ring(now, || unreachable!()));
+        assert_eq!(policy.stats.limited, 1);
+    }
+
+    #[test]
+    fn test_bell_mute() {
+        let mut policy = BellPolicy::new(&Bell {
+            mute: vec![String::from("vim")],
+            ..Bell::default()
+        });
+        let now = Instant::now();
+
+        assert!(!policy.ring(now, || Some(String::from("vim"))));
+        assert!(policy.ring(now, || Some(String::from("nvim"))));
+        assert!(policy.ring(now + Duration::from_secs(1), || None));
+        assert_eq!(policy.stats.muted, 1);
+        assert_eq!(policy.stats.rung, 2);
+    }
+}
@@ -1,7 +1,9 @@
+use rio_backend::config::hints::{HintAction, Hints};
+use rio_backend::config::Shell;
 use rio_backend::crosswords::pos::{Column, Direction, Line, Pos};
 use rio_backend::crosswords::search::Match;
 use rio_backend::crosswords::search::{RegexIter, RegexSearch};
-use rio_backend::crosswords::Crosswords;
+use rio_backend::crosswords::{Crosswords, URL_REGEX};
 use std::borrow::Cow;
 use std::ops::Deref;
 
@@ -78,3 +80,446 @@ impl Deref for HintMatches<'_> {
         self.matches.deref()
     }
 }
+
+/// Paths with at least one separator, like `~/.config`, `./src/main.rs` or
+/// `/usr/bin`, without the trailing punctuation of a sentence.
+const PATH_REGEX: &str = r"[\w.~-]*/[\w.~@%+/-]*[\w~@%+/-]";
+
+/// Characters used when `hints.alphabet` has less than two distinct ones.
+const FALLBACK_ALPHABET: &str = "jfkdls;ahgurieowpq";
+
+/// Pattern of `hints.patterns`, searched on the screen with the DFAs and
+/// matched again with the regex to expand the capture groups of its command.
+struct HintPatternSearch {
+    dfas: RegexSearch,
+    regex: regex::Regex,
+    action: Option<HintAction>,
+    command: Option<Shell>,
+}
+
+/// Match labeled in hint mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HintMatch {
+    pub bounds: Match,
+    /// Index of the pattern it comes from, `None` for urls and paths.
+    pattern: Option<usize>,
+}
+
+/// Keyboard hint mode, labels the links, paths and configured patterns of
+/// the screen until one of the labels is typed.
+pub struct HintState {
+    alphabet: Vec<char>,
+    patterns: Vec<HintPatternSearch>,
+    links: Option<RegexSearch>,
+    action: Option<HintAction>,
+    matches: Vec<HintMatch>,
+    labels: Vec<Vec<char>>,
+    keys: Vec<char>,
+}
+
+impl HintState {
+    pub fn new(config: &Hints) -> Self {
+        let mut state = Self {
+            alphabet: Vec::new(),
+            patterns: Vec::new(),
+            links: RegexSearch::new(&format!("{URL_REGEX}|{PATH_REGEX}")).ok(),
+            action: None,
+            matches: Vec::new(),
+            labels: Vec::new(),
+            keys: Vec::new(),
+        };
+        state.set_config(config);
+        state
+    }
+
+    pub fn set_config(&mut self, config: &Hints) {
+        let mut alphabet: Vec<char> = Vec::with_capacity(config.alphabet.len());
+        for c in config.alphabet.chars() {
+            if !alphabet.contains(&c) {
+                alphabet.push(c);
+            }
+        }
+
+        if alphabet.len() < 2 {
+            alphabet = FALLBACK_ALPHABET.chars().collect();
+        }
+
+        self.alphabet = alphabet;
+        self.patterns = config
+            .patterns
+            .iter()
+            .filter_map(|pattern| {
+                // Same smart case as the search DFAs
+                let has_uppercase = pattern.regex.chars().any(|c| c.is_uppercase());
+                let search = RegexSearch::new(&pattern.regex)
+                    .map_err(|err| err.to_string())
+                    .and_then(|dfas| {
+                        let regex = regex::RegexBuilder::new(&pattern.regex)
+                            .case_insensitive(!has_uppercase)
+                            .build()
+                            .map_err(|err| err.to_string())?;
+                        Ok((dfas, regex))
+                    });
+                match search {
+                    Ok((dfas, regex)) => Some(HintPatternSearch {
+                        dfas,
+                        regex,
+                        action: pattern.action,
+                        command: pattern.command.clone(),
+                    }),
+                    Err(err) => {
+                        tracing::warn!("invalid hint pattern {:?}: {err}", pattern.regex);
+                        None
+                    }
+                }
+            })
+            .collect();
+    }
+
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.action.is_some()
+    }
+
+    /// Labels the links, paths and patterns on the screen, returns false
+    /// when there are none to pick from.
+    pub fn start<T: rio_backend::event::EventListener>(
+        &mut self,
+        action: HintAction,
+        term: &Crosswords<T>,
+    ) -> bool {
+        let mut matches = Vec::new();
+        for (index, pattern) in self.patterns.iter_mut().enumerate() {
+            matches.extend(visible_regex_match_iter(term, &mut pattern.dfas).map(
+                |bounds| HintMatch {
+                    bounds,
+                    pattern: Some(index),
+                },
+            ));
+        }
+        if let Some(links) = self.links.as_mut() {
+            matches.extend(visible_regex_match_iter(term, links).map(|bounds| {
+                HintMatch {
+                    bounds,
+                    pattern: None,
+                }
+            }));
+        }
+
+        // Patterns come first, so they win over the links they overlap
+        matches.sort_by(|a, b| {
+            a.bounds
+                .start()
+                .partial_cmp(b.bounds.start())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut last_end: Option<Pos> = None;
+        matches.retain(|hint| {
+            if last_end.is_some_and(|end| *hint.bounds.start() <= end) {
+                return false;
+            }
+            last_end = Some(*hint.bounds.end());
+            true
+        });
+
+        self.start_with_matches(action, matches)
+    }
+
+    fn start_with_matches(
+        &mut self,
+        action: HintAction,
+        matches: Vec<HintMatch>,
+    ) -> bool {
+        self.stop();
+        if matches.is_empty() {
+            return false;
+        }
+
+        self.labels = generate_labels(&self.alphabet, matches.len());
+        self.matches = matches;
+        self.action = Some(action);
+        true
+    }
+
+    pub fn stop(&mut self) {
+        self.action = None;
+        self.matches.clear();
+        self.labels.clear();
+        self.keys.clear();
+    }
+
+    /// Drops the last typed character of the label.
+    pub fn pop_key(&mut self) {
+        self.keys.pop();
+    }
+
+    /// Types a character of the label, returns the match and the action
+    /// of its pattern, or the one hint mode was started with, once a whole
+    /// label was typed. Characters which aren't part of any label are
+    /// ignored.
+    pub fn keyboard_input(&mut self, c: char) -> Option<(HintAction, HintMatch)> {
+        let action = self.action?;
+        self.keys.push(c);
+
+        let mut has_prefix = false;
+        for (index, label) in self.labels.iter().enumerate() {
+            if label == &self.keys {
+                let picked = self.matches[index].clone();
+                let action = picked
+                    .pattern
+                    .and_then(|pattern| self.patterns.get(pattern)?.action)
+                    .unwrap_or(action);
+                self.stop();
+                return Some((action, picked));
+            }
+
+            has_prefix |= label.starts_with(&self.keys);
+        }
+
+        if !has_prefix {
+            self.keys.pop();
+        }
+
+        None
+    }
+
+    /// Program and arguments of the pattern command run with the text of
+    /// the picked match, if it has one.
+    pub fn command(&self, hint: &HintMatch, text: &str) -> Option<Vec<String>> {
+        let pattern = self.patterns.get(hint.pattern?)?;
+        let command = pattern.command.as_ref()?;
+
+        let captures = pattern.regex.captures(text);
+        let mut args = vec![command.program.clone()];
+        for arg in &command.args {
+            let mut expanded = String::new();
+            match &captures {
+                Some(captures) => captures.expand(arg, &mut expanded),
+                None => expanded.push_str(&arg.replace("$0", text)),
+            }
+            args.push(expanded);
+        }
+        Some(args)
+    }
+
+    /// Position and characters left to type of the labels matching what
+    /// was typed so far, the labels start at their match or at the top of
+    /// the screen when the match begins above it.
+    pub fn visible_labels(&self, viewport_start: Line) -> Vec<(Pos, &[char])> {
+        self.matches
+            .iter()
+            .zip(&self.labels)
+            .filter(|(_, label)| label.starts_with(&self.keys))
+            .map(|(hint, label)| {
+                let top = Pos::new(viewport_start, Column(0));
+                let pos = if *hint.bounds.start() < top {
+                    top
+                } else {
+                    *hint.bounds.start()
+                };
+                (pos, &label[self.keys.len()..])
+            })
+            .collect()
+    }
+}
+
+/// Labels of the same length for `count` matches, so none of them is the
+/// start of another one.
+fn generate_labels(alphabet: &[char], count: usize) -> Vec<Vec<char>> {
+    let base = alphabet.len();
+    let mut length = 1;
+    let mut capacity = base;
+    while capacity < count {
+        length += 1;
+        capacity = capacity.saturating_mul(base);
+    }
+
+    (0..count)
+        .map(|mut index| {
+            let mut label = vec![alphabet[0]; length];
+            for slot in label.iter_mut().rev() {
+                *slot = alphabet[index % base];
+                index /= base;
+            }
+            label
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rio_backend::config::hints::HintPattern;
+
+    fn hints(alphabet: &str, patterns: Vec<HintPattern>) -> Hints {
+        Hints {
+            alphabet: alphabet.to_string(),
+            patterns,
+        }
+    }
+
+    fn matches(count: i32) -> Vec<HintMatch> {
+        (0..count)
+            .map(|row| HintMatch {
+                bounds: Pos::new(Line(row), Column(2))..=Pos::new(Line(row), Column(8)),
+                pattern: None,
+            })
+            .collect()
+    }
+
+    fn term(content: &str) -> Crosswords<rio_backend::event::VoidListener> {
+        use rio_backend::ansi::CursorShape;
+        use rio_backend::crosswords::CrosswordsSize;
+        use rio_backend::event::{VoidListener, WindowId};
+        use rio_backend::performer::handler::Handler;
+
+        let size = CrosswordsSize::new(60, 2);
+        let window_id = WindowId::from(0);
+        let mut term =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        for c in content.chars() {
+            term.input(c);
+        }
+        term
+    }
+
+    fn found<T: rio_backend::event::EventListener>(
+        state: &HintState,
+        term: &Crosswords<T>,
+    ) -> Vec<String> {
+        state
+            .matches
+            .iter()
+            .map(|hint| term.bounds_to_string(*hint.bounds.start(), *hint.bounds.end()))
+            .collect()
+    }
+
+    #[test]
+    fn labels_share_length() {
+        let alphabet = ['a', 'b', 'c'];
+        assert_eq!(generate_labels(&alphabet, 2), vec![vec!['a'], vec!['b']]);
+
+        let labels = generate_labels(&alphabet, 4);
+        assert_eq!(
+            labels,
+            vec![
+                vec!['a', 'a'],
+                vec!['a', 'b'],
+                vec!['a', 'c'],
+                vec!['b', 'a']
+            ]
+        );
+    }
+
+    #[test]
+    fn alphabet_needs_two_characters() {
+        let state = HintState::new(&hints("aaa", Vec::new()));
+        assert_eq!(
+            state.alphabet,
+            FALLBACK_ALPHABET.chars().collect::<Vec<_>>()
+        );
+
+        let state = HintState::new(&hints("abba", Vec::new()));
+        assert_eq!(state.alphabet, vec!['a', 'b']);
+    }
+
+    #[test]
+    fn typing_a_label_picks_its_match() {
+        let mut state = HintState::new(&hints("ab", Vec::new()));
+        assert!(state.start_with_matches(HintAction::Copy, matches(3)));
+        assert_eq!(state.visible_labels(Line(0)).len(), 3);
+
+        // Not part of any label
+        assert_eq!(state.keyboard_input('x'), None);
+        assert_eq!(state.keyboard_input('b'), None);
+        let labels = state.visible_labels(Line(0));
+        assert_eq!(labels, vec![(Pos::new(Line(2), Column(2)), &['a'][..])]);
+
+        state.pop_key();
+        assert_eq!(state.visible_labels(Line(0)).len(), 3);
+
+        assert_eq!(state.keyboard_input('a'), None);
+        let picked = state.keyboard_input('b');
+        assert_eq!(picked, Some((HintAction::Copy, matches(3)[1].clone())));
+        assert!(!state.is_active());
+    }
+
+    #[test]
+    fn labels_stay_on_screen() {
+        let mut state = HintState::new(&hints("ab", Vec::new()));
+        let above = HintMatch {
+            bounds: Pos::new(Line(-3), Column(4))..=Pos::new(Line(1), Column(0)),
+            pattern: None,
+        };
+        assert!(state.start_with_matches(HintAction::Open, vec![above]));
+        let labels = state.visible_labels(Line(-1));
+        assert_eq!(labels[0].0, Pos::new(Line(-1), Column(0)));
+    }
+
+    #[test]
+    fn finds_urls_and_paths() {
+        let term = term("see https://raphamorim.io and ~/.config/rio. or 1 / 2");
+
+        let mut state = HintState::new(&hints("ab", Vec::new()));
+        assert!(state.start(HintAction::Open, &term));
+        assert_eq!(
+            found(&state, &term),
+            vec!["https://raphamorim.io", "~/.config/rio"]
+        );
+    }
+
+    #[test]
+    fn patterns_have_their_own_actions() {
+        let term = term("at src/main.rs:42 from 1a2b3c4d");
+
+        let patterns = vec![
+            HintPattern {
+                regex: "([\\w./-]+):(\\d+)".to_string(),
+                action: None,
+                command: Some(Shell {
+                    program: "editor".to_string(),
+                    args: vec!["+$2".to_string(), "$1".to_string()],
+                }),
+            },
+            HintPattern {
+                regex: "[0-9a-f]{7,40}".to_string(),
+                action: Some(HintAction::Paste),
+                command: None,
+            },
+            HintPattern {
+                regex: "(invalid".to_string(),
+                action: None,
+                command: None,
+            },
+        ];
+        let mut state = HintState::new(&hints("ab", patterns));
+        assert_eq!(state.patterns.len(), 2);
+
+        // The pattern wins over the path it overlaps
+        assert!(state.start(HintAction::Open, &term));
+        assert_eq!(found(&state, &term), vec!["src/main.rs:42", "1a2b3c4d"]);
+
+        let (action, hint) = state.keyboard_input('a').unwrap();
+        assert_eq!(action, HintAction::Open);
+        assert_eq!(
+            state.command(&hint, "src/main.rs:42"),
+            Some(vec![
+                "editor".to_string(),
+                "+42".to_string(),
+                "src/main.rs".to_string()
+            ])
+        );
+
+        assert!(state.start(HintAction::Open, &term));
+        let (action, hint) = state.keyboard_input('b').unwrap();
+        assert_eq!(action, HintAction::Paste);
+        assert_eq!(state.command(&hint, "1a2b3c4d"), None);
+    }
+
+    #[test]
+    fn no_matches_keeps_hints_inactive() {
+        let mut state = HintState::new(&hints("ab", Vec::new()));
+        assert!(!state.start_with_matches(HintAction::Open, Vec::new()));
+        assert!(!state.is_active());
+    }
+}
@@ -6,45 +6,59 @@
 // were retired from https://github.com/alacritty/alacritty/blob/c39c3c97f1a1213418c3629cc59a1d46e34070e0/alacritty/src/input.rs
 // which is licensed under Apache 2.0 license.
 
+pub mod bell;
 pub mod hint;
+pub mod osc52;
+pub mod paste;
 pub mod touch;
 
 use crate::bindings::kitty_keyboard::build_key_sequence;
 use crate::bindings::{
-    Action as Act, BindingKey, BindingMode, FontSizeAction, MouseBinding, SearchAction,
-    ViAction,
+    format_key_steps, Action as Act, BindingKey, BindingMode, FontSizeAction, KeyStep,
+    MouseBinding, SearchAction, ViAction,
 };
 #[cfg(target_os = "macos")]
 use crate::constants::{DEADZONE_END_Y, DEADZONE_START_Y};
-use crate::context::grid::{ContextDimension, Delta};
+use crate::context::grid::{ContextDimension, ContextGrid, Delta};
 use crate::context::renderable::{Cursor, RenderableContent};
 use crate::context::{self, process_open_url, ContextManager};
 use crate::crosswords::{
+    export::ExportFormat,
     grid::{Dimensions, Scroll},
     pos::{Column, Pos, Side},
     square::Hyperlink,
     vi_mode::ViMotion,
     Mode,
 };
+use crate::ime::Preedit;
 use crate::mouse::{calculate_mouse_position, Mouse};
 use crate::renderer::{
     utils::{padding_bottom_from_config, padding_top_from_config},
     Renderer,
 };
-use crate::screen::hint::HintMatches;
+use crate::screen::bell::BellPolicy;
+use crate::screen::hint::{HintMatch, HintMatches, HintState};
+use crate::screen::osc52::ClipboardRequest;
+use crate::screen::paste::PendingPaste;
 use crate::selection::{Selection, SelectionType};
 use core::fmt::Debug;
 use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 use rio_backend::clipboard::Clipboard;
 use rio_backend::clipboard::ClipboardType;
+use rio_backend::config::bindings::CommandTarget;
+use rio_backend::config::hints::HintAction;
 use rio_backend::config::renderer::{
-    Backend as RendererBackend, Performance as RendererPerformance,
+    Backend as RendererBackend, Colorspace as RendererColorspace,
+    Performance as RendererPerformance, PresentMode as RendererPresentMode,
 };
+use rio_backend::config::unicode::{CharWidth, InvalidUtf8};
+use rio_backend::config::Shell;
 use rio_backend::crosswords::pos::{Boundary, CursorState, Direction, Line};
-use rio_backend::crosswords::search::RegexSearch;
-use rio_backend::event::{ClickState, EventProxy, SearchState};
+use rio_backend::crosswords::search::{Match, RegexSearch};
+use rio_backend::event::{ClickState, EventProxy, InlineSearchState, SearchState};
+use rio_backend::locale::Locale;
 use rio_backend::sugarloaf::{
-    layout::RootStyle, Sugarloaf, SugarloafErrors, SugarloafRenderer, SugarloafWindow,
+    layout::RootStyle, Colorspace, Sugarloaf, SugarloafRenderer, SugarloafWindow,
     SugarloafWindowSize,
 };
 use rio_window::event::ElementState;
@@ -52,15 +66,23 @@ use rio_window::event::Modifiers;
 use rio_window::event::MouseButton;
 #[cfg(target_os = "macos")]
 use rio_window::keyboard::ModifiersKeyState;
-use rio_window::keyboard::{Key, KeyLocation, ModifiersState, NamedKey};
+use rio_window::keyboard::{Key, KeyLocation, ModifiersState, NamedKey, PhysicalKey};
 use rio_window::platform::modifier_supplement::KeyEventExtModifierSupplement;
 use std::cell::RefCell;
 use std::cmp::{max, min};
+use std::collections::HashSet;
 use std::error::Error;
 use std::ffi::OsStr;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use touch::TouchPurpose;
 
+/// Opacity change of each step of the opacity gesture and key bindings.
+const OPACITY_STEP: f32 = 0.05;
+/// Lowest opacity the window can be set to from Rio, a fully transparent
+/// window would be lost.
+pub const MIN_OPACITY: f32 = 0.1;
+
 /// Minimum number of pixels at the bottom/top where selection scrolling is performed.
 const MIN_SELECTION_SCROLLING_HEIGHT: f32 = 5.;
 
@@ -70,20 +92,65 @@ const SELECTION_SCROLLING_STEP: f32 = 10.;
 /// Maximum number of lines for the blocking search while still typing the search regex.
 const MAX_SEARCH_WHILE_TYPING: Option<usize> = Some(1000);
 
+/// Milliseconds to wait before searching without the limit once typing stopped.
+const TYPING_SEARCH_DELAY: u64 = 100;
+
 /// Maximum number of search terms stored in the history.
 const MAX_SEARCH_HISTORY_SIZE: usize = 255;
 
+/// Total number of search matches and the position of the focused one.
+type SearchMatchCount = (usize, Option<usize>);
+
+/// What the search matches were counted for, they are counted again when
+/// the search, the focused match or the terminal content changes.
+#[derive(PartialEq)]
+struct SearchCountKey {
+    regex: String,
+    focused_match: Option<Match>,
+    pty_bytes_read: u64,
+    columns: usize,
+    screen_lines: usize,
+}
+
 pub struct Screen<'screen> {
     bindings: crate::bindings::KeyBindings,
     mouse_bindings: Vec<MouseBinding>,
     pub modifiers: Modifiers,
+    /// Modifiers held to underline the hovered hyperlink.
+    hyperlink_modifiers: ModifiersState,
+    /// Modifiers held when clicking to start a block selection.
+    block_selection_modifiers: ModifiersState,
+    save_selection_to_clipboard: bool,
+    opacity_modifiers: ModifiersState,
+    /// Opacity of the configuration, restored by `ToggleOpacity`.
+    window_opacity: f32,
+    paste_config: rio_backend::config::paste::Paste,
+    haptic_feedback: rio_backend::config::haptic_feedback::HapticFeedback,
+    clipboard_config: rio_backend::config::clipboard::Clipboard,
+    scroll_config: rio_backend::config::Scroll,
+    /// Keys of a multi-step binding waiting for the next one.
+    pending_chord: Option<(Vec<KeyStep>, Instant)>,
+    /// Keys whose press was handled by Rio, their release isn't reported
+    /// to the program either.
+    consumed_keys: HashSet<PhysicalKey>,
+    chord_timeout: Duration,
+    pub pending_paste: Option<PendingPaste>,
+    pub pending_clipboard_request: Option<ClipboardRequest>,
     pub mouse: Mouse,
     pub touchpurpose: TouchPurpose,
     pub search_state: SearchState,
+    /// Last search match count and what it was counted for.
+    search_match_count: Option<(SearchCountKey, SearchMatchCount)>,
+    /// The matches are counted again when the delayed search runs.
+    is_search_count_delayed: bool,
+    inline_search_state: InlineSearchState,
+    hint_state: HintState,
+    bell: BellPolicy,
     pub renderer: Renderer,
     pub sugarloaf: Sugarloaf<'screen>,
     pub context_manager: context::ContextManager<EventProxy>,
     pub clipboard: Rc<RefCell<Clipboard>>,
+    locale: Locale,
 }
 
 pub struct ScreenWindowProperties {
@@ -102,6 +169,7 @@ impl Screen<'_> {
         font_library: &rio_backend::sugarloaf::font::FontLibrary,
         open_url: Option<String>,
         clipboard: Rc<RefCell<Clipboard>>,
+        tab: Option<ContextGrid<EventProxy>>,
     ) -> Result<Screen<'screen>, Box<dyn Error>> {
         let size = window_properties.size;
         let scale = window_properties.scale;
@@ -118,10 +186,11 @@ impl Screen<'_> {
 
         let padding_y_bottom =
             padding_bottom_from_config(&config.navigation, config.padding_y[1], 1, false);
-        let sugarloaf_layout =
-            RootStyle::new(scale as f32, config.fonts.size, config.line_height);
-
-        let mut sugarloaf_errors: Option<SugarloafErrors> = None;
+        let mut sugarloaf_layout =
+            RootStyle::new(scale as f32, config.fonts.size_px(), config.line_height());
+        sugarloaf_layout.baseline_offset = config.fonts.baseline_offset;
+        sugarloaf_layout.letter_spacing = config.fonts.letter_spacing;
+        sugarloaf_layout.snap_to_pixels = config.fonts.snap_to_pixels;
 
         let sugarloaf_window = SugarloafWindow {
             handle: raw_window_handle,
@@ -156,25 +225,33 @@ impl Screen<'_> {
         let sugarloaf_renderer = SugarloafRenderer {
             power_preference,
             backend,
-            font_features: config.fonts.features.clone(),
+            adapter: config.renderer.adapter.clone(),
+            font_features: config.fonts.shaping_features(),
+            // Extended range surfaces are only supported on macOS and Windows
+            hdr: cfg!(any(target_os = "macos", windows)) && config.renderer.hdr,
+            colorspace: match config.renderer.colorspace {
+                RendererColorspace::Srgb => Colorspace::Srgb,
+                RendererColorspace::DisplayP3 => Colorspace::DisplayP3,
+            },
+            hdr_brightness: config.renderer.hdr_brightness.max(0.0),
+            present_mode: present_mode_from_config(config.renderer.present_mode),
         };
 
-        let mut sugarloaf: Sugarloaf = match Sugarloaf::new(
+        let mut sugarloaf: Sugarloaf = Sugarloaf::new(
             sugarloaf_window,
             sugarloaf_renderer,
             font_library,
             sugarloaf_layout,
-        ) {
-            Ok(instance) => instance,
-            Err(instance_with_errors) => {
-                sugarloaf_errors = Some(instance_with_errors.errors);
-                instance_with_errors.instance
-            }
-        };
+        )?;
 
         sugarloaf.update_filters(config.renderer.filters.as_slice());
 
-        let renderer = Renderer::new(config, font_library);
+        let locale = Locale::new(config.language.as_deref());
+        let renderer = Renderer::new(config, font_library, &locale);
+        clipboard
+            .borrow_mut()
+            .set_history_size(config.clipboard.history);
+        clipboard.borrow_mut().set_backend(&config.clipboard);
 
         let bindings = crate::bindings::default_key_bindings(
             config.bindings.keys.to_owned(),
@@ -205,6 +282,14 @@ impl Screen<'_> {
             should_update_title_extra: !config.navigation.color_automation.is_empty(),
             split_color: config.colors.split,
             title: config.title.clone(),
+            profiles: config.profiles.clone(),
+            scroll_history: config.scroll.history,
+            copy_visual_layout: config.copy_visual_layout,
+            scroll_on_output: config.scroll.on_output,
+            char_width: CharWidth::new(&config.unicode),
+            graphics_max_memory: config.graphics.max_memory_bytes(),
+            hyperlink_patterns: config.hyperlinks.patterns.clone(),
+            latin1_fallback: config.unicode.invalid_utf8 == InvalidUtf8::Latin1,
         };
 
         let rich_text_id = sugarloaf.create_rich_text();
@@ -218,7 +303,7 @@ impl Screen<'_> {
             size.width as f32,
             size.height as f32,
             sugarloaf.get_rich_text_dimensions(&rich_text_id),
-            config.line_height,
+            config.line_height(),
             margin,
         );
 
@@ -229,18 +314,34 @@ impl Screen<'_> {
             is_ime_enabled: false,
         };
 
-        let context_manager = context::ContextManager::start(
-            // config.cursor.blinking
-            (&cursor, config.cursor.blinking),
-            event_proxy,
-            window_id,
-            0,
-            rich_text_id,
-            context_manager_config,
-            context_dimension,
-            margin,
-            sugarloaf_errors,
-        )?;
+        let context_manager = match tab {
+            Some(grid) => {
+                let mut rich_text_ids = vec![rich_text_id];
+                rich_text_ids
+                    .extend((1..grid.len()).map(|_| sugarloaf.create_rich_text()));
+                context::ContextManager::start_with_grid(
+                    grid,
+                    event_proxy,
+                    window_id,
+                    &rich_text_ids,
+                    context_manager_config,
+                    context_dimension,
+                    margin,
+                )
+            }
+            None => context::ContextManager::start(
+                // config.cursor.blinking
+                (&cursor, config.cursor.blinking),
+                event_proxy,
+                window_id,
+                0,
+                rich_text_id,
+                context_manager_config,
+                context_dimension,
+                margin,
+                None,
+            )?,
+        };
 
         if cfg!(target_os = "macos") {
             sugarloaf.set_background_color(None);
@@ -255,15 +356,41 @@ impl Screen<'_> {
 
         Ok(Screen {
             search_state: SearchState::default(),
+            search_match_count: None,
+            is_search_count_delayed: false,
+            inline_search_state: InlineSearchState::default(),
+            hint_state: HintState::new(&config.hints),
+            bell: BellPolicy::new(&config.bell),
             mouse_bindings: crate::bindings::default_mouse_bindings(),
             modifiers: Modifiers::default(),
+            hyperlink_modifiers: crate::bindings::modifiers_from_config(
+                &config.hyperlinks.hover_modifiers,
+            ),
+            block_selection_modifiers: crate::bindings::modifiers_from_config(
+                &config.selection.block_modifiers,
+            ),
+            save_selection_to_clipboard: config.selection.save_to_clipboard,
+            window_opacity: config.window.opacity,
+            opacity_modifiers: crate::bindings::modifiers_from_config(
+                &config.window.opacity_modifiers,
+            ),
+            paste_config: config.paste.clone(),
+            haptic_feedback: config.haptic_feedback.clone(),
+            clipboard_config: config.clipboard.clone(),
+            scroll_config: config.scroll.clone(),
+            pending_chord: None,
+            chord_timeout: Duration::from_millis(config.keyboard.chord_timeout),
+            pending_paste: None,
+            pending_clipboard_request: None,
             context_manager,
             sugarloaf,
             mouse: Mouse::new(config.scroll.multiplier, config.scroll.divider),
             touchpurpose: TouchPurpose::default(),
             renderer,
             bindings,
+            consumed_keys: HashSet::new(),
             clipboard,
+            locale,
         })
     }
 
@@ -318,11 +445,60 @@ impl Screen<'_> {
             margin.top_y,
             (
                 context_dimension.dimension.width,
-                context_dimension.dimension.height * style.line_height,
+                context_dimension.dimension.height * context_dimension.line_height,
             ),
         )
     }
 
+    /// Cell of the terminal cursor in physical pixels as `[x, y, width, height]`,
+    /// `None` while the cursor is hidden or the display is scrolled.
+    pub fn cursor_area(&self) -> Option<[f32; 4]> {
+        let current_grid = self.context_manager.current_grid();
+        let (context, margin) = current_grid.current_context_with_computed_dimension();
+        let context_dimension = context.dimension;
+        let cell_width = context_dimension.dimension.width;
+        let cell_height =
+            context_dimension.dimension.height * context_dimension.line_height;
+        if cell_width == 0.0 || cell_height == 0.0 {
+            return None;
+        }
+
+        // The cursor of the last frame, so the terminal isn't locked
+        let cursor = &context.renderable_content.cursor.state;
+        if !cursor.is_visible() {
+            return None;
+        }
+
+        let pos = cursor.pos;
+        let row = pos.row.0;
+        if row < 0 || row as usize >= context_dimension.lines {
+            return None;
+        }
+
+        let scale_factor = self.sugarloaf.style().scale_factor;
+        Some([
+            margin.x * scale_factor + pos.col.0 as f32 * cell_width,
+            margin.top_y * scale_factor + row as f32 * cell_height,
+            cell_width,
+            cell_height,
+        ])
+    }
+
+    /// Area next to which the input method places its candidate window, the
+    /// cursor cell widened to the text being composed.
+    pub fn ime_cursor_area(&self) -> Option<[f32; 4]> {
+        let [x, y, width, height] = self.cursor_area()?;
+        let columns = self
+            .context_manager
+            .current()
+            .ime
+            .preedit()
+            .map(Preedit::width)
+            .unwrap_or_default()
+            .max(1);
+        Some([x, y, width * columns as f32, height])
+    }
+
     #[inline]
     pub fn touch_purpose(&mut self) -> &mut TouchPurpose {
         &mut self.touchpurpose
@@ -345,6 +521,28 @@ impl Screen<'_> {
         config: &rio_backend::config::Config,
         font_library: &rio_backend::sugarloaf::font::FontLibrary,
         should_update_font_library: bool,
+    ) {
+        self.apply_config(config, font_library, should_update_font_library, None);
+    }
+
+    /// Applies the configuration of the profile matched for route_id, the
+    /// scrollback history of the other terminals is left untouched.
+    #[inline]
+    pub fn update_profile(
+        &mut self,
+        config: &rio_backend::config::Config,
+        font_library: &rio_backend::sugarloaf::font::FontLibrary,
+        route_id: usize,
+    ) {
+        self.apply_config(config, font_library, false, Some(route_id));
+    }
+
+    fn apply_config(
+        &mut self,
+        config: &rio_backend::config::Config,
+        font_library: &rio_backend::sugarloaf::font::FontLibrary,
+        should_update_font_library: bool,
+        profile_route: Option<usize>,
     ) {
         let num_tabs = self.ctx().len();
         let padding_y_top = padding_top_from_config(
@@ -363,16 +561,69 @@ impl Screen<'_> {
         if should_update_font_library {
             self.sugarloaf.update_font(font_library);
         }
+        self.hyperlink_modifiers =
+            crate::bindings::modifiers_from_config(&config.hyperlinks.hover_modifiers);
+        self.block_selection_modifiers =
+            crate::bindings::modifiers_from_config(&config.selection.block_modifiers);
+        self.save_selection_to_clipboard = config.selection.save_to_clipboard;
+        self.opacity_modifiers =
+            crate::bindings::modifiers_from_config(&config.window.opacity_modifiers);
+        self.window_opacity = config.window.opacity;
+        self.paste_config = config.paste.clone();
+        self.haptic_feedback = config.haptic_feedback.clone();
+        self.clipboard_config = config.clipboard.clone();
+        self.scroll_config = config.scroll.clone();
+        self.chord_timeout = Duration::from_millis(config.keyboard.chord_timeout);
+        self.clipboard
+            .borrow_mut()
+            .set_history_size(config.clipboard.history);
+        self.clipboard.borrow_mut().set_backend(&config.clipboard);
+        self.hint_state.set_config(&config.hints);
+        self.bell.set_config(&config.bell);
+
         let s = self.sugarloaf.style_mut();
-        s.font_size = config.fonts.size;
-        s.line_height = config.line_height;
+        s.font_size = config.fonts.size_px();
+        s.line_height = config.line_height();
+        s.baseline_offset = config.fonts.baseline_offset;
+        s.letter_spacing = config.fonts.letter_spacing;
+        s.snap_to_pixels = config.fonts.snap_to_pixels;
 
         self.sugarloaf
             .update_filters(config.renderer.filters.as_slice());
-        self.renderer = Renderer::new(config, font_library);
+        self.sugarloaf
+            .set_present_mode(present_mode_from_config(config.renderer.present_mode));
+        self.locale = Locale::new(config.language.as_deref());
+        let battery_saver = self.renderer.battery_saver;
+        let debug_overlay = self.renderer.debug_overlay.take();
+        let metrics = self.renderer.metrics.take();
+        self.renderer = Renderer::new(config, font_library, &self.locale);
+        self.renderer.battery_saver = battery_saver;
+        // Keep the overlay toggled by the key binding
+        if debug_overlay.is_some() {
+            self.renderer.debug_overlay = debug_overlay;
+        }
+        // Keep the minutes recorded so far while metrics stay enabled
+        if self.renderer.metrics.is_some() && metrics.is_some() {
+            self.renderer.metrics = metrics;
+        }
 
         for context_grid in self.context_manager.contexts_mut() {
-            context_grid.update_line_height(config.line_height);
+            for current_context in context_grid.contexts_mut() {
+                let rich_text_id = current_context.context().rich_text_id;
+                self.sugarloaf.set_rich_text_letter_spacing(
+                    &rich_text_id,
+                    config.fonts.letter_spacing,
+                );
+                // Set before the dimensions, which take the line height
+                // snapped by the rich text
+                self.sugarloaf
+                    .set_rich_text_line_height(&rich_text_id, config.line_height());
+                self.sugarloaf.set_rich_text_snap_to_pixels(
+                    &rich_text_id,
+                    config.fonts.snap_to_pixels,
+                );
+            }
+            context_grid.update_line_height(config.line_height());
 
             context_grid.update_margin((
                 config.padding_x,
@@ -384,11 +635,15 @@ impl Screen<'_> {
 
             for current_context in context_grid.contexts_mut() {
                 let current_context = current_context.context_mut();
-                self.sugarloaf.set_rich_text_line_height(
+                self.sugarloaf.set_rich_text_baseline_offset(
                     &current_context.rich_text_id,
-                    current_context.dimension.line_height,
+                    config.fonts.baseline_offset,
                 );
 
+                // Resizing the history drops lines, only the terminal the
+                // profile was matched for follows it
+                let update_history = profile_route.is_none()
+                    || profile_route == Some(current_context.route_id);
                 let mut terminal = current_context.terminal.lock();
                 current_context.renderable_content =
                     RenderableContent::from_cursor_config(&config.cursor);
@@ -396,10 +651,31 @@ impl Screen<'_> {
                 terminal.cursor_shape = shape;
                 terminal.default_cursor_shape = shape;
                 terminal.blinking_cursor = config.cursor.blinking;
+                terminal.default_blinking_cursor = config.cursor.blinking;
+                if update_history {
+                    terminal.update_history(config.scroll.history);
+                }
+                terminal.copy_visual_layout = config.copy_visual_layout;
+                terminal.scroll_on_output = config.scroll.on_output;
+                terminal.char_width = CharWidth::new(&config.unicode);
+                terminal.set_graphics_max_memory(config.graphics.max_memory_bytes());
+                terminal.set_hyperlink_patterns(&config.hyperlinks.patterns);
                 drop(terminal);
             }
         }
 
+        if profile_route.is_none() {
+            self.context_manager.config.scroll_history = config.scroll.history;
+        }
+        self.context_manager.config.copy_visual_layout = config.copy_visual_layout;
+        self.context_manager.config.scroll_on_output = config.scroll.on_output;
+        self.context_manager.config.char_width = CharWidth::new(&config.unicode);
+        self.context_manager.config.graphics_max_memory =
+            config.graphics.max_memory_bytes();
+        self.context_manager.config.hyperlink_patterns =
+            config.hyperlinks.patterns.clone();
+        self.context_manager.config.latin1_fallback =
+            config.unicode.invalid_utf8 == InvalidUtf8::Latin1;
         self.mouse
             .set_multiplier_and_divider(config.scroll.multiplier, config.scroll.divider);
 
@@ -534,14 +810,40 @@ impl Screen<'_> {
 
     #[inline]
     pub fn process_key_event(&mut self, key: &rio_window::event::KeyEvent) {
+        // A press also forgets a release missed while unfocused
+        let is_consumed = self.consumed_keys.remove(&key.physical_key);
+        if key.state == ElementState::Released && is_consumed {
+            return;
+        }
+
         if self.context_manager.current().ime.preedit().is_some() {
             return;
         }
 
+        if self.hint_state.is_active() {
+            if key.state == ElementState::Pressed {
+                self.consumed_keys.insert(key.physical_key);
+                self.hint_input(key);
+            }
+            return;
+        }
+
         let mode = self.get_mode();
         let mods = self.modifiers.state();
 
         if key.state == ElementState::Released {
+            if mode.contains(Mode::WIN32_INPUT)
+                && !mode.contains(Mode::VI)
+                && !self.search_active()
+            {
+                if let Some(bytes) =
+                    crate::bindings::win32_input::build_sequence(key, &self.modifiers)
+                {
+                    self.ctx_mut().current_mut().messenger.send_write(bytes);
+                }
+                return;
+            }
+
             if !mode.contains(Mode::REPORT_EVENT_TYPES)
                 || mode.contains(Mode::VI)
                 || self.search_active()
@@ -573,14 +875,31 @@ impl Screen<'_> {
             return;
         }
 
+        // The character of a vi inline search is taken before the bindings
+        if self.inline_search_state.char_pending && mode.contains(Mode::VI) {
+            self.consumed_keys.insert(key.physical_key);
+            if let Key::Named(NamedKey::Escape) = key.logical_key.as_ref() {
+                self.inline_search_state.char_pending = false;
+            } else {
+                let text = key.text_with_all_modifiers().unwrap_or_default();
+                if let Some(c) = text.chars().next() {
+                    self.inline_search_input(c);
+                }
+            }
+            return;
+        }
+        self.inline_search_state.char_pending = false;
+
         let ignore_chars = self.process_key_bindings(key, &mode, mods);
         if ignore_chars {
+            self.consumed_keys.insert(key.physical_key);
             return;
         }
 
         let text = key.text_with_all_modifiers().unwrap_or_default();
 
         if self.search_active() {
+            self.consumed_keys.insert(key.physical_key);
             for character in text.chars() {
                 self.search_input(character);
             }
@@ -591,6 +910,7 @@ impl Screen<'_> {
 
         // Vi mode on its own doesn't have any input, the search input was done before.
         if mode.contains(Mode::VI) {
+            self.consumed_keys.insert(key.physical_key);
             return;
         }
 
@@ -603,7 +923,17 @@ impl Screen<'_> {
 
         let build_key_sequence = Self::should_build_sequence(key, text, mode, mods);
 
-        let bytes = if build_key_sequence {
+        let win32_input = mode
+            .contains(Mode::WIN32_INPUT)
+            .then(|| crate::bindings::win32_input::build_sequence(key, &self.modifiers))
+            .flatten();
+        let bytes = if let Some(bytes) = win32_input {
+            bytes
+        } else if let Some(bytes) =
+            crate::bindings::modify_other_keys::build_sequence(key, mods, mode)
+        {
+            bytes
+        } else if build_key_sequence {
             crate::bindings::kitty_keyboard::build_key_sequence(key, mods, mode)
         } else {
             let mut bytes = Vec::with_capacity(text.len() + 1);
@@ -616,7 +946,9 @@ impl Screen<'_> {
         };
 
         if !bytes.is_empty() {
-            self.scroll_bottom_when_cursor_not_visible();
+            if self.scroll_config.on_input {
+                self.scroll_bottom_when_cursor_not_visible();
+            }
             self.clear_selection();
 
             self.ctx_mut().current_mut().messenger.send_bytes(bytes);
@@ -681,34 +1013,74 @@ impl Screen<'_> {
         let binding_mode = BindingMode::new(mode, search_active);
         let mut ignore_chars = None;
 
+        // We don't want the key without modifier, because it means something else most of
+        // the time. However what we want is to manually lowercase the character to account
+        // for both small and capital letters on regular characters at the same time.
+        let logical_key = if let Key::Character(ch) = key.logical_key.as_ref() {
+            // Match `Alt` bindings without `Alt` being applied, otherwise they use the
+            // composed chars, which are not intuitive to bind.
+            //
+            // On Windows, the `Ctrl + Alt` mangles `logical_key` to unidentified values, thus
+            // preventing them from being used in bindings
+            //
+            // For more see https://github.com/rust-windowing/winit/issues/2945.
+            // if (cfg!(target_os = "macos") || (cfg!(windows) && mods.control_key()))
+            //     && mods.alt_key()
+            if (mods.shift_key() || mods.alt_key())
+                || mods.alt_key() && (cfg!(windows) && mods.control_key())
+            {
+                key.key_without_modifiers()
+            } else {
+                Key::Character(ch.to_lowercase().into())
+            }
+        } else {
+            key.logical_key.clone()
+        };
+
+        // Keys of a multi-step binding pressed before this one
+        let pending_chord = self
+            .pending_chord
+            .take()
+            .filter(|(_, until)| Instant::now() < *until);
+        if let Some(pending_chord) = pending_chord.as_ref() {
+            // The modifiers of the next step are pressed on their own first
+            if matches!(
+                key.logical_key,
+                Key::Named(
+                    NamedKey::Control | NamedKey::Shift | NamedKey::Alt | NamedKey::Super
+                )
+            ) {
+                self.pending_chord = Some(pending_chord.clone());
+                return true;
+            }
+        }
+        let leader = pending_chord.map(|(keys, _)| keys).unwrap_or_default();
+
+        let mut sequence = leader.clone();
+        sequence.push((
+            BindingKey::Keycode {
+                key: logical_key.clone(),
+                location: key.location,
+            },
+            mods,
+        ));
+        if self.bindings.iter().any(|binding| {
+            binding.leader.starts_with(&sequence) && binding.is_active_in(&binding_mode)
+        }) {
+            self.set_pending_chord(Some(sequence));
+            return true;
+        }
+        if !leader.is_empty() {
+            self.set_pending_chord(None);
+        }
+
         for i in 0..self.bindings.len() {
             let binding = &self.bindings[i];
+            if binding.leader != leader {
+                continue;
+            }
 
-            // We don't want the key without modifier, because it means something else most of
-            // the time. However what we want is to manually lowercase the character to account
-            // for both small and capital letters on regular characters at the same time.
-            let logical_key = if let Key::Character(ch) = key.logical_key.as_ref() {
-                // Match `Alt` bindings without `Alt` being applied, otherwise they use the
-                // composed chars, which are not intuitive to bind.
-                //
-                // On Windows, the `Ctrl + Alt` mangles `logical_key` to unidentified values, thus
-                // preventing them from being used in bindings
-                //
-                // For more see https://github.com/rust-windowing/winit/issues/2945.
-                // if (cfg!(target_os = "macos") || (cfg!(windows) && mods.control_key()))
-                //     && mods.alt_key()
-                if (mods.shift_key() || mods.alt_key())
-                    || mods.alt_key() && (cfg!(windows) && mods.control_key())
-                {
-                    key.key_without_modifiers()
-                } else {
-                    Key::Character(ch.to_lowercase().into())
-                }
-            } else {
-                key.logical_key.clone()
-            };
-
-            let key_match = match (&binding.trigger, logical_key) {
+            let key_match = match (&binding.trigger, logical_key.clone()) {
                 (BindingKey::Scancode(_), _) => BindingKey::Scancode(key.physical_key),
                 (_, code) => BindingKey::Keycode {
                     key: code,
@@ -719,342 +1091,513 @@ impl Screen<'_> {
             if binding.is_triggered_by(binding_mode.to_owned(), mods, &key_match) {
                 *ignore_chars.get_or_insert(true) &= binding.action != Act::ReceiveChar;
 
-                match &binding.action {
-                    Act::Run(program) => self.exec(program.program(), program.args()),
-                    Act::Esc(s) => {
-                        let current_context = self.context_manager.current_mut();
-                        current_context.set_selection(None);
-                        let mut terminal = current_context.terminal.lock();
-                        terminal.selection.take();
-                        terminal.scroll_display(Scroll::Bottom);
-                        drop(terminal);
-                        current_context
-                            .messenger
-                            .send_bytes(s.to_owned().into_bytes());
-                    }
-                    Act::Paste => {
-                        let content =
-                            self.clipboard.borrow_mut().get(ClipboardType::Clipboard);
-                        self.paste(&content, true);
-                    }
-                    Act::ClearSelection => {
-                        self.clear_selection();
-                    }
-                    Act::PasteSelection => {
-                        let content =
-                            self.clipboard.borrow_mut().get(ClipboardType::Selection);
-                        self.paste(&content, true);
-                    }
-                    Act::Copy => {
-                        self.copy_selection(ClipboardType::Clipboard);
-                    }
-                    Act::SearchForward => {
-                        self.start_search(Direction::Right);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::SearchBackward => {
-                        self.start_search(Direction::Left);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchConfirm) => {
-                        self.confirm_search();
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchCancel) => {
-                        self.cancel_search();
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchClear) => {
-                        let direction = self.search_state.direction;
-                        self.cancel_search();
-                        self.start_search(direction);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchFocusNext) => {
-                        self.advance_search_origin(self.search_state.direction);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchFocusPrevious) => {
-                        let direction = self.search_state.direction.opposite();
-                        self.advance_search_origin(direction);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchDeleteWord) => {
-                        self.search_pop_word();
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchHistoryPrevious) => {
-                        self.search_history_previous();
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchHistoryNext) => {
-                        self.search_history_next();
-                        self.render();
-                    }
-                    Act::ToggleViMode => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.toggle_vi_mode();
-                        let has_vi_mode_enabled = terminal.mode().contains(Mode::VI);
-                        drop(terminal);
-                        self.renderer.set_vi_mode(has_vi_mode_enabled);
-                        self.render();
-                    }
-                    Act::ViMotion(motion) => {
-                        let current_context = self.context_manager.current_mut();
-                        let mut terminal = current_context.terminal.lock();
-                        if terminal.mode().contains(Mode::VI) {
-                            terminal.vi_motion(*motion);
-                        }
-
-                        if let Some(selection) = &terminal.selection {
-                            current_context.renderable_content.selection_range =
-                                selection.to_range(&terminal);
-                        };
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::CenterAroundViCursor) => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let display_offset = terminal.display_offset() as i32;
-                        let target =
-                            -display_offset + terminal.grid.screen_lines() as i32 / 2 - 1;
-                        let line = terminal.vi_mode_cursor.pos.row;
-                        let scroll_lines = target - line.0;
-
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                    }
-                    Act::Vi(ViAction::ToggleNormalSelection) => {
-                        self.toggle_selection(SelectionType::Simple, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleLineSelection) => {
-                        self.toggle_selection(SelectionType::Lines, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleBlockSelection) => {
-                        self.toggle_selection(SelectionType::Block, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleSemanticSelection) => {
-                        self.toggle_selection(SelectionType::Semantic, Side::Left);
-                        self.render();
-                    }
-                    Act::SplitRight => {
-                        self.split_right();
-                    }
-                    Act::SplitDown => {
-                        self.split_down();
-                    }
-                    Act::ConfigEditor => {
-                        self.context_manager.switch_to_settings();
-                    }
-                    Act::WindowCreateNew => {
-                        self.context_manager.create_new_window();
-                    }
-                    Act::CloseCurrentSplitOrTab => {
-                        self.close_split_or_tab();
-                    }
-                    Act::TabCreateNew => {
-                        self.create_tab();
-                    }
-                    Act::TabCloseCurrent => {
-                        self.close_tab();
-                    }
-                    Act::TabCloseUnfocused => {
-                        self.clear_selection();
-                        self.cancel_search();
-                        if self.ctx().len() <= 1 {
-                            return true;
-                        }
-                        self.context_manager.close_unfocused_tabs();
-                        self.resize_top_or_bottom_line(1);
-                        self.render();
-                    }
-                    Act::Quit => {
-                        self.context_manager.quit();
-                    }
-                    Act::IncreaseFontSize => {
-                        self.change_font_size(FontSizeAction::Increase);
-                    }
-                    Act::DecreaseFontSize => {
-                        self.change_font_size(FontSizeAction::Decrease);
-                    }
-                    Act::ResetFontSize => {
-                        self.change_font_size(FontSizeAction::Reset);
-                    }
-                    Act::ScrollPageUp => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = terminal.grid.screen_lines() as i32;
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-                        terminal.scroll_display(Scroll::PageUp);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollPageDown => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = -(terminal.grid.screen_lines() as i32);
-
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-
-                        terminal.scroll_display(Scroll::PageDown);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollHalfPageUp => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = terminal.grid.screen_lines() as i32 / 2;
-
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollHalfPageDown => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = -(terminal.grid.screen_lines() as i32 / 2);
-
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollToTop => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Top);
-
-                        let topmost_line = terminal.grid.topmost_line();
-                        terminal.vi_mode_cursor.pos.row = topmost_line;
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollToBottom => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Bottom);
-
-                        // Move vi mode cursor.
-                        terminal.vi_mode_cursor.pos.row = terminal.grid.bottommost_line();
-
-                        // Move to beginning twice, to always jump across linewraps.
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::Scroll(delta) => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Delta(*delta));
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ClearHistory => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.clear_saved_history();
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ToggleFullscreen => self.context_manager.toggle_full_screen(),
-                    Act::Minimize => {
-                        self.context_manager.minimize();
-                    }
-                    Act::Hide => {
-                        self.context_manager.hide();
-                    }
-                    #[cfg(target_os = "macos")]
-                    Act::HideOtherApplications => {
-                        self.context_manager.hide_other_apps();
-                    }
-                    Act::SelectNextSplit => {
-                        self.cancel_search();
-                        self.context_manager.select_next_split();
-                        self.render();
-                    }
-                    Act::SelectPrevSplit => {
-                        self.cancel_search();
-                        self.context_manager.select_prev_split();
-                        self.render();
-                    }
-                    Act::SelectNextSplitOrTab => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.switch_to_next_split_or_tab();
-                        self.render();
-                    }
-                    Act::SelectPrevSplitOrTab => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.switch_to_prev_split_or_tab();
-                        self.render();
-                    }
-                    Act::SelectTab(tab_index) => {
-                        self.context_manager.select_tab(*tab_index);
-                        self.cancel_search();
-                        self.render();
-                    }
-                    Act::SelectLastTab => {
-                        self.cancel_search();
-                        self.context_manager.select_last_tab();
-                        self.render();
-                    }
-                    Act::SelectNextTab => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.switch_to_next();
-                        self.render();
-                    }
-                    Act::MoveCurrentTabToPrev => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.move_current_to_prev();
-                        self.render();
-                    }
-                    Act::MoveCurrentTabToNext => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.move_current_to_next();
-                        self.render();
-                    }
-                    Act::SelectPrevTab => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.switch_to_prev();
-                        self.render();
-                    }
-                    Act::ReceiveChar | Act::None => (),
-                    _ => (),
+                let action = binding.action.clone();
+                if self.run_action(&action) {
+                    return true;
                 }
             }
         }
 
-        ignore_chars.unwrap_or(false)
+        // Keys that don't continue a multi-step binding are dropped with it
+        ignore_chars.unwrap_or(!leader.is_empty())
+    }
+
+    /// Performs the action of a key binding or picked in the command palette.
+    /// Returns `true` when the key is consumed and no further bindings run.
+    pub fn run_action(&mut self, action: &Act) -> bool {
+        match action {
+            Act::Run(program) => self.exec(program.program(), program.args()),
+            Act::RunCommand(command) => {
+                let command = command.clone();
+                let selection = self
+                    .ctx()
+                    .current()
+                    .terminal
+                    .lock()
+                    .selection_to_string()
+                    .unwrap_or_default();
+                let args = command.args_with_selection(&selection);
+                match command.target {
+                    CommandTarget::Background => self.exec(&command.program, &args),
+                    CommandTarget::Tab => self.create_tab_with_shell(Some(Shell {
+                        program: command.program,
+                        args,
+                    })),
+                }
+            }
+            Act::Esc(s) => {
+                let current_context = self.context_manager.current_mut();
+                current_context.set_selection(None);
+                let mut terminal = current_context.terminal.lock();
+                terminal.selection.take();
+                if self.scroll_config.on_input {
+                    terminal.scroll_display(Scroll::Bottom);
+                }
+                drop(terminal);
+                current_context
+                    .messenger
+                    .send_bytes(s.to_owned().into_bytes());
+            }
+            Act::Paste => {
+                let content = self.clipboard.borrow_mut().get(ClipboardType::Clipboard);
+                self.paste(&content, true);
+            }
+            Act::ClipboardHistory => {
+                self.context_manager.open_clipboard_history();
+            }
+            Act::CommandPalette => {
+                self.context_manager.open_command_palette();
+            }
+            Act::OpenSettings => {
+                self.context_manager.open_settings();
+            }
+            Act::ClearSelection => {
+                self.clear_selection();
+            }
+            Act::PasteSelection => {
+                let content = self.clipboard.borrow_mut().get(ClipboardType::Selection);
+                self.paste(&content, true);
+            }
+            Act::Copy => {
+                self.copy_selection(ClipboardType::Clipboard);
+            }
+            Act::CopyLink => {
+                self.copy_selection_links();
+            }
+            Act::CopyFormatted => {
+                self.copy_selection_formatted();
+            }
+            Act::SelectAll => {
+                self.select_all();
+                self.render();
+            }
+            Act::SelectLastCommandOutput => {
+                self.select_last_command_output();
+                self.render();
+            }
+            Act::CopyRecentOutput(lines) => {
+                let text = self.recent_output(*lines);
+                if !text.is_empty() {
+                    self.copy(text);
+                }
+            }
+            Act::SpeakRecentOutput(lines) => {
+                let text = self.recent_output(*lines);
+                if !text.is_empty() {
+                    crate::platform::speak(&text);
+                }
+            }
+            Act::SearchForward => {
+                self.start_search(Direction::Right);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::SearchBackward => {
+                self.start_search(Direction::Left);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchConfirm) => {
+                self.confirm_search();
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchCancel) => {
+                self.cancel_search();
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchClear) => {
+                let direction = self.search_state.direction;
+                self.cancel_search();
+                self.start_search(direction);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchFocusNext) => {
+                self.advance_search_origin(self.search_state.direction);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchFocusPrevious) => {
+                let direction = self.search_state.direction.opposite();
+                self.advance_search_origin(direction);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchDeleteWord) => {
+                self.search_pop_word();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchHistoryPrevious) => {
+                self.search_history_previous();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchHistoryNext) => {
+                self.search_history_next();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchToggleSmartCase) => {
+                self.search_state.options.smart_case =
+                    !self.search_state.options.smart_case;
+                self.update_search();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchToggleWholeWord) => {
+                self.search_state.options.whole_word =
+                    !self.search_state.options.whole_word;
+                self.update_search();
+                self.render();
+            }
+            Act::Hint(action) => {
+                let terminal = self.context_manager.current().terminal.lock();
+                let has_hints = self.hint_state.start(*action, &terminal);
+                drop(terminal);
+                if has_hints {
+                    self.context_manager
+                        .current_mut()
+                        .renderable_content
+                        .has_pending_updates = true;
+                    self.render();
+                }
+            }
+            Act::ToggleViMode => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.toggle_vi_mode();
+                let has_vi_mode_enabled = terminal.mode().contains(Mode::VI);
+                drop(terminal);
+                self.renderer.set_vi_mode(has_vi_mode_enabled);
+                self.render();
+            }
+            Act::ViMotion(motion) => {
+                let current_context = self.context_manager.current_mut();
+                let mut terminal = current_context.terminal.lock();
+                if terminal.mode().contains(Mode::VI) {
+                    terminal.vi_motion(*motion);
+                }
+
+                if let Some(selection) = &terminal.selection {
+                    current_context.renderable_content.selection_range =
+                        selection.to_range(&terminal);
+                };
+                drop(terminal);
+                self.render();
+            }
+            Act::Vi(ViAction::CenterAroundViCursor) => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let display_offset = terminal.display_offset() as i32;
+                let target =
+                    -display_offset + terminal.grid.screen_lines() as i32 / 2 - 1;
+                let line = terminal.vi_mode_cursor.pos.row;
+                let scroll_lines = target - line.0;
+
+                terminal.scroll_display(Scroll::Delta(scroll_lines));
+                drop(terminal);
+            }
+            Act::Vi(ViAction::InlineSearchForward) => {
+                self.start_inline_search(Direction::Right, false);
+            }
+            Act::Vi(ViAction::InlineSearchBackward) => {
+                self.start_inline_search(Direction::Left, false);
+            }
+            Act::Vi(ViAction::InlineSearchForwardShort) => {
+                self.start_inline_search(Direction::Right, true);
+            }
+            Act::Vi(ViAction::InlineSearchBackwardShort) => {
+                self.start_inline_search(Direction::Left, true);
+            }
+            Act::Vi(ViAction::InlineSearchNext) => {
+                self.inline_search(self.inline_search_state.direction, true);
+            }
+            Act::Vi(ViAction::InlineSearchPrevious) => {
+                let direction = self.inline_search_state.direction.opposite();
+                self.inline_search(direction, true);
+            }
+            Act::Vi(ViAction::ToggleNormalSelection) => {
+                self.toggle_selection(SelectionType::Simple, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleLineSelection) => {
+                self.toggle_selection(SelectionType::Lines, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleBlockSelection) => {
+                self.toggle_selection(SelectionType::Block, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleSemanticSelection) => {
+                self.toggle_selection(SelectionType::Semantic, Side::Left);
+                self.render();
+            }
+            Act::SplitRight => {
+                self.split_right();
+            }
+            Act::SplitDown => {
+                self.split_down();
+            }
+            Act::ConfigEditor => {
+                self.context_manager.switch_to_settings();
+            }
+            Act::WindowCreateNew => {
+                self.context_manager.create_new_window();
+            }
+            Act::MoveCurrentTabToNewWindow => {
+                self.context_manager.move_tab_to_new_window();
+            }
+            Act::MergeWindows => {
+                self.context_manager.merge_windows();
+            }
+            Act::NextWindow => {
+                self.context_manager.select_window(true);
+            }
+            Act::PreviousWindow => {
+                self.context_manager.select_window(false);
+            }
+            Act::CloseCurrentSplitOrTab => {
+                self.close_split_or_tab();
+            }
+            Act::TabCreateNew => {
+                self.create_tab();
+            }
+            Act::TabCloseCurrent => {
+                self.close_tab();
+            }
+            Act::TabCloseUnfocused => {
+                self.clear_selection();
+                self.cancel_search();
+                if self.ctx().len() <= 1 {
+                    return true;
+                }
+                self.context_manager.close_unfocused_tabs();
+                self.resize_top_or_bottom_line(1);
+                self.render();
+            }
+            Act::Quit => {
+                self.context_manager.quit();
+            }
+            Act::IncreaseFontSize => {
+                self.change_font_size(FontSizeAction::Increase);
+            }
+            Act::DecreaseFontSize => {
+                self.change_font_size(FontSizeAction::Decrease);
+            }
+            Act::ResetFontSize => {
+                self.change_font_size(FontSizeAction::Reset);
+            }
+            Act::ScrollPageUp => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = terminal.grid.screen_lines() as i32;
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+                terminal.scroll_display(Scroll::PageUp);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollPageDown => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = -(terminal.grid.screen_lines() as i32);
+
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+
+                terminal.scroll_display(Scroll::PageDown);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollHalfPageUp => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = terminal.grid.screen_lines() as i32 / 2;
+
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+
+                terminal.scroll_display(Scroll::Delta(scroll_lines));
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollHalfPageDown => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = -(terminal.grid.screen_lines() as i32 / 2);
+
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+
+                terminal.scroll_display(Scroll::Delta(scroll_lines));
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollToTop => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Top);
+
+                let topmost_line = terminal.grid.topmost_line();
+                terminal.vi_mode_cursor.pos.row = topmost_line;
+                terminal.vi_motion(ViMotion::FirstOccupied);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollToBottom => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Bottom);
+
+                // Move vi mode cursor.
+                terminal.vi_mode_cursor.pos.row = terminal.grid.bottommost_line();
+
+                // Move to beginning twice, to always jump across linewraps.
+                terminal.vi_motion(ViMotion::FirstOccupied);
+                terminal.vi_motion(ViMotion::FirstOccupied);
+                drop(terminal);
+                self.render();
+            }
+            Act::Scroll(delta) => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Delta(*delta));
+                drop(terminal);
+                self.render();
+            }
+            Act::ClearHistory => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.clear_saved_history();
+                drop(terminal);
+                self.render();
+            }
+            Act::ToggleFullscreen => self.context_manager.toggle_full_screen(),
+            Act::IncreaseOpacity | Act::DecreaseOpacity | Act::ToggleOpacity => {
+                let Some(opacity) = self.renderer.opacity() else {
+                    return false;
+                };
+                let new_opacity = match action {
+                    Act::IncreaseOpacity => opacity + OPACITY_STEP,
+                    Act::DecreaseOpacity => opacity - OPACITY_STEP,
+                    _ if opacity < 1.0 => 1.0,
+                    _ => self.window_opacity,
+                };
+                if let Some(new_opacity) = self.set_opacity(new_opacity) {
+                    self.context_manager.update_window_opacity(new_opacity);
+                    self.render();
+                }
+            }
+            Act::ToggleDebugOverlay => {
+                self.renderer.toggle_debug_overlay();
+                if let Some(debug_overlay) = self.renderer.debug_overlay.as_mut() {
+                    debug_overlay.bells = self.bell.stats;
+                }
+                self.render();
+            }
+            Act::DumpMetrics => match &self.renderer.metrics {
+                Some(metrics) => match metrics.dump() {
+                    Ok(path) => tracing::info!("metrics written to {}", path.display()),
+                    Err(err) => tracing::error!("unable to write metrics: {err}"),
+                },
+                None => tracing::warn!(
+                    "metrics are disabled, set developer.enable-metrics to record them"
+                ),
+            },
+            Act::ToggleControlPictures => {
+                self.context_manager
+                    .current_mut()
+                    .terminal
+                    .lock()
+                    .toggle_control_pictures();
+            }
+            Act::ToggleLatin1Fallback => {
+                self.context_manager.current_mut().toggle_latin1_fallback();
+            }
+            Act::Screenshot => {
+                self.renderer.request_screenshot();
+                self.render();
+            }
+            Act::ExportScreen(format) => {
+                self.export(*format, false);
+            }
+            Act::ExportScrollback(format) => {
+                self.export(*format, true);
+            }
+            Act::Minimize => {
+                self.context_manager.minimize();
+            }
+            Act::Hide => {
+                self.context_manager.hide();
+            }
+            #[cfg(target_os = "macos")]
+            Act::HideOtherApplications => {
+                self.context_manager.hide_other_apps();
+            }
+            Act::SelectNextSplit => {
+                self.cancel_search();
+                self.context_manager.select_next_split();
+                self.render();
+            }
+            Act::SelectPrevSplit => {
+                self.cancel_search();
+                self.context_manager.select_prev_split();
+                self.render();
+            }
+            Act::SelectNextSplitOrTab => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.switch_to_next_split_or_tab();
+                self.render();
+            }
+            Act::SelectPrevSplitOrTab => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.switch_to_prev_split_or_tab();
+                self.render();
+            }
+            Act::SelectTab(tab_index) => {
+                self.context_manager.select_tab(*tab_index);
+                self.cancel_search();
+                self.render();
+            }
+            Act::SelectLastTab => {
+                self.cancel_search();
+                self.context_manager.select_last_tab();
+                self.render();
+            }
+            Act::SelectNextTab => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.switch_to_next();
+                self.render();
+            }
+            Act::MoveCurrentTabToPrev => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.move_current_to_prev();
+                self.render();
+            }
+            Act::MoveCurrentTabToNext => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.move_current_to_next();
+                self.render();
+            }
+            Act::SelectPrevTab => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.switch_to_prev();
+                self.render();
+            }
+            Act::ReceiveChar | Act::None => (),
+            _ => (),
+        }
+        false
+    }
+
+    /// Waits for the next key of a multi-step binding, shown at the bottom
+    /// of the window until the timeout.
+    fn set_pending_chord(&mut self, keys: Option<Vec<KeyStep>>) {
+        let until = Instant::now() + self.chord_timeout;
+        self.renderer.pending_chord = keys
+            .as_ref()
+            .map(|keys| (format!("{} >", format_key_steps(keys)), until));
+        self.pending_chord = keys.map(|keys| (keys, until));
+        self.render();
     }
 
     pub fn split_right_with_config(&mut self, config: rio_backend::config::Config) {
@@ -1079,7 +1622,39 @@ impl Screen<'_> {
         self.render();
     }
 
+    /// Whether the bell of the terminal is rung, see [`BellPolicy`].
+    pub fn ring_bell(&mut self, route_id: usize) -> bool {
+        let context = self.context_manager.context_by_route_mut(route_id);
+        let program = || {
+            #[cfg(unix)]
+            return context.map(|context| {
+                teletypewriter::foreground_process_name(
+                    *context.main_fd,
+                    context.shell_pid,
+                )
+            });
+
+            #[cfg(not(unix))]
+            {
+                let _ = context;
+                None
+            }
+        };
+
+        let is_rung = self.bell.ring(Instant::now(), program);
+        if let Some(debug_overlay) = self.renderer.debug_overlay.as_mut() {
+            debug_overlay.bells = self.bell.stats;
+        }
+        is_rung
+    }
+
     pub fn create_tab(&mut self) {
+        self.create_tab_with_shell(None);
+    }
+
+    /// Creates a tab running the program instead of the shell, closed when
+    /// the program exits.
+    pub fn create_tab_with_shell(&mut self, shell: Option<Shell>) {
         let redirect = true;
 
         // We resize the current tab ahead to prepare the
@@ -1088,9 +1663,40 @@ impl Screen<'_> {
         self.resize_top_or_bottom_line(num_tabs + 1);
 
         let rich_text_id = self.sugarloaf.create_rich_text();
-        self.context_manager.add_context(redirect, rich_text_id);
+        self.context_manager
+            .add_context(redirect, rich_text_id, shell);
+
+        self.cancel_search();
+        self.render();
+    }
+
+    /// Takes the current tab out of the window to move it to another one,
+    /// `None` when it's the only tab.
+    pub fn detach_tab(&mut self) -> Option<ContextGrid<EventProxy>> {
+        self.clear_selection();
+        self.cancel_search();
+        let grid = self.context_manager.take_current_grid()?;
+
+        self.resize_top_or_bottom_line(self.ctx().len());
+        self.render();
+        Some(grid)
+    }
 
+    /// Adds tabs moved from other windows, with their terminals running.
+    pub fn attach_tabs(&mut self, grids: Vec<ContextGrid<EventProxy>>) {
+        self.clear_selection();
         self.cancel_search();
+
+        let num_tabs = self.ctx().len() + grids.len();
+        self.resize_top_or_bottom_line(num_tabs);
+
+        for grid in grids {
+            let rich_text_ids: Vec<usize> = (0..grid.len())
+                .map(|_| self.sugarloaf.create_rich_text())
+                .collect();
+            self.context_manager.adopt_grid(grid, &rich_text_ids);
+        }
+
         self.render();
     }
 
@@ -1245,15 +1851,11 @@ impl Screen<'_> {
         let alt_send_esc = self.modifiers.state().alt_key();
 
         #[cfg(target_os = "macos")]
-        let alt_send_esc = {
-            let option_as_alt = &self.renderer.option_as_alt;
-            self.modifiers.state().alt_key()
-                && (option_as_alt == "both"
-                    || (option_as_alt == "left"
-                        && self.modifiers.lalt_state() == ModifiersKeyState::Pressed)
-                    || (option_as_alt == "right"
-                        && self.modifiers.ralt_state() == ModifiersKeyState::Pressed))
-        };
+        let alt_send_esc = self.modifiers.state().alt_key()
+            && self.renderer.option_as_alt.is_alt(
+                self.modifiers.lalt_state() == ModifiersKeyState::Pressed,
+                self.modifiers.ralt_state() == ModifiersKeyState::Pressed,
+            );
 
         match key.logical_key {
             Key::Named(named) => {
@@ -1268,6 +1870,42 @@ impl Screen<'_> {
         }
     }
 
+    /// Writes the content of the current terminal to a file next to the
+    /// screenshots.
+    fn export(&self, format: ExportFormat, include_history: bool) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let lines = if include_history {
+            terminal.history_lines()
+        } else {
+            terminal.viewport_lines()
+        };
+        let content = terminal.export(lines, format, &self.renderer.colors);
+        drop(terminal);
+
+        let path = self.renderer.output_path(format.extension());
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, content));
+        match result {
+            Ok(()) => tracing::info!("terminal content exported to {}", path.display()),
+            Err(err) => tracing::error!("unable to export terminal content: {err}"),
+        }
+    }
+
+    #[inline]
+    fn recent_output(&self, lines: usize) -> String {
+        let terminal = self.context_manager.current().terminal.lock();
+        terminal.recent_output(lines)
+    }
+
+    /// Stores a text copied by the user in the clipboard, it's left out
+    /// of the history while a secret is typed.
+    fn copy(&mut self, text: String) {
+        let keep_in_history = !self.context_manager.current().is_password_input();
+        self.clipboard.borrow_mut().copy(text, keep_in_history);
+    }
+
     pub fn copy_selection(&mut self, ty: ClipboardType) {
         let terminal = self.context_manager.current_mut().terminal.lock();
         let text = match terminal.selection_to_string().filter(|s| !s.is_empty()) {
@@ -1276,21 +1914,94 @@ impl Screen<'_> {
         };
         drop(terminal);
 
-        if ty == ClipboardType::Selection {
-            self.clipboard
-                .borrow_mut()
-                .set(ClipboardType::Clipboard, text.clone());
+        if ty == ClipboardType::Clipboard {
+            self.copy(text);
+            return;
+        }
+
+        // Selections go to the primary selection on X11 and Wayland, the
+        // clipboard is left for explicit copies unless asked otherwise
+        let mut clipboard = self.clipboard.borrow_mut();
+        if ty == ClipboardType::Selection
+            && (!clipboard.has_selection() || self.save_selection_to_clipboard)
+        {
+            clipboard.set(ClipboardType::Clipboard, text.clone());
+        }
+        clipboard.set(ty, text);
+    }
+
+    /// Copies the targets of the OSC 8 hyperlinks in the selection, one
+    /// per line, instead of their text.
+    pub fn copy_selection_links(&mut self) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let uris = terminal.selection_hyperlinks();
+        drop(terminal);
+
+        if !uris.is_empty() {
+            self.copy(uris.join("\n"));
+        }
+    }
+
+    /// Copies the selection with its colors and styles as HTML and RTF,
+    /// applications without rich text support paste the plain text.
+    pub fn copy_selection_formatted(&mut self) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let text = terminal.selection_to_string().filter(|s| !s.is_empty());
+        let html = terminal.export_selection(ExportFormat::Html, &self.renderer.colors);
+        let rtf = terminal.export_selection(ExportFormat::Rtf, &self.renderer.colors);
+        drop(terminal);
+
+        let (Some(text), Some(html), Some(rtf)) = (text, html, rtf) else {
+            return;
+        };
+
+        // The plain text is stored first so it's kept in the history, and
+        // in the clipboard if the platform only takes plain text
+        self.copy(text.clone());
+        crate::platform::set_rich_clipboard(&text, &html, &rtf);
+    }
+
+    #[inline]
+    pub fn clear_selection(&mut self) {
+        // Clear the selection on the terminal.
+        let mut terminal = self.context_manager.current_mut().terminal.lock();
+        terminal.selection.take();
+        drop(terminal);
+        self.context_manager.current_mut().set_selection(None);
+    }
+
+    /// Selects the whole scrollback history and screen, the selection is
+    /// copied like a mouse selection.
+    pub fn select_all(&mut self) {
+        let mut terminal = self.context_manager.current().terminal.lock();
+        terminal.select_all();
+        drop(terminal);
+        self.apply_terminal_selection();
+    }
+
+    /// Selects the output of the last command delimited by the semantic
+    /// prompt marks (OSC 133), returns false if there is none.
+    pub fn select_last_command_output(&mut self) -> bool {
+        let mut terminal = self.context_manager.current().terminal.lock();
+        let selected = terminal.select_last_command_output();
+        drop(terminal);
+
+        if selected {
+            self.apply_terminal_selection();
         }
-        self.clipboard.borrow_mut().set(ty, text);
+        selected
     }
 
     #[inline]
-    pub fn clear_selection(&mut self) {
-        // Clear the selection on the terminal.
-        let mut terminal = self.context_manager.current_mut().terminal.lock();
-        terminal.selection.take();
+    fn apply_terminal_selection(&mut self) {
+        let current = self.context_manager.current_mut();
+        let terminal = current.terminal.lock();
+        current.renderable_content.selection_range = terminal
+            .selection
+            .as_ref()
+            .and_then(|selection| selection.to_range(&terminal));
         drop(terminal);
-        self.context_manager.current_mut().set_selection(None);
+        self.copy_selection(ClipboardType::Selection);
     }
 
     #[inline]
@@ -1367,32 +2078,30 @@ impl Screen<'_> {
         drop(terminal);
     }
 
+    /// Underlines the hyperlink under the mouse while the hover modifiers
+    /// are held, every part of the hyperlink sharing its id is underlined.
     #[inline]
     pub fn search_nearest_hyperlink_from_pos(&mut self) -> bool {
-        #[cfg(target_os = "macos")]
-        let is_hyperlink_key_active = self.modifiers.state().super_key();
-
-        #[cfg(not(target_os = "macos"))]
-        let is_hyperlink_key_active = self.modifiers.state().shift_key();
-
-        if !is_hyperlink_key_active {
+        if !self.modifiers.state().contains(self.hyperlink_modifiers) {
+            let current = self.context_manager.current_mut();
+            if current.has_hyperlink_range() {
+                current.set_hovered_hyperlink(None);
+            }
             return false;
         }
 
         let mut terminal = self.context_manager.current().terminal.lock();
         let display_offset = terminal.display_offset();
         let pos = self.mouse_position(display_offset);
-        let search_result = terminal.search_nearest_hyperlink_from_pos(pos);
+        let search_result = terminal
+            .search_nearest_hyperlink_from_pos(pos)
+            .and_then(|range| Some((range, terminal.grid[pos].hyperlink()?)));
         drop(terminal);
 
         let current = self.context_manager.current_mut();
-        if let Some(hyperlink_range) = search_result {
-            current.set_hyperlink_range(Some(hyperlink_range));
-            return true;
-        }
-
-        current.set_hyperlink_range(None);
-        false
+        let is_hovered = search_result.is_some();
+        current.set_hovered_hyperlink(search_result);
+        is_hovered
     }
 
     #[inline]
@@ -1425,14 +2134,77 @@ impl Screen<'_> {
     }
 
     fn open_hyperlink(&self, hyperlink: Hyperlink) {
+        match hyperlink.command() {
+            Some([program, args @ ..]) => self.exec(program, args),
+            _ => self.open_url(hyperlink.uri()),
+        }
+    }
+
+    fn open_url(&self, url: &str) {
         #[cfg(not(any(target_os = "macos", windows)))]
-        self.exec("xdg-open", [hyperlink.uri()]);
+        self.exec("xdg-open", [url]);
 
         #[cfg(target_os = "macos")]
-        self.exec("open", [hyperlink.uri()]);
+        self.exec("open", [url]);
 
         #[cfg(windows)]
-        self.exec("cmd", ["/c", "start", "", hyperlink.uri()]);
+        self.exec("cmd", ["/c", "start", "", url]);
+    }
+
+    /// Updates the hovered link of the UI (assistant, welcome, ...) rich
+    /// texts, returns true if it changed and a redraw is needed.
+    #[inline]
+    pub fn update_hovered_link(&mut self, x: f64, y: f64) -> bool {
+        let url = self
+            .sugarloaf
+            .link_at(x as f32, y as f32)
+            .map(str::to_owned);
+        if url.as_deref() == self.sugarloaf.hovered_link() {
+            return false;
+        }
+
+        self.sugarloaf.set_hovered_link(url);
+        true
+    }
+
+    /// Whether the mouse is over the new output indicator.
+    pub fn is_over_output_indicator(&self) -> bool {
+        let scale = self.sugarloaf.scale_factor();
+        self.renderer.is_output_indicator_at(
+            self.mouse.x as f32 / scale,
+            self.mouse.y as f32 / scale,
+        )
+    }
+
+    /// Jumps to the bottom when the new output indicator is clicked.
+    pub fn trigger_output_indicator(&mut self) -> bool {
+        // The bounds are the ones of the last frame, the indicator is only
+        // drawn while scrolled up
+        if !self.is_over_output_indicator() || self.display_offset() == 0 {
+            return false;
+        }
+
+        self.scroll_bottom_when_cursor_not_visible();
+        self.render();
+        true
+    }
+
+    #[inline]
+    pub fn has_hovered_link(&self) -> bool {
+        self.sugarloaf.hovered_link().is_some()
+    }
+
+    /// Opens the hovered UI link with the same opener used by terminal
+    /// hyperlinks.
+    #[inline]
+    pub fn trigger_hovered_link(&self) -> bool {
+        match self.sugarloaf.hovered_link() {
+            Some(url) => {
+                self.open_url(url);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn exec<I, S>(&self, program: &str, args: I)
@@ -1549,7 +2321,12 @@ impl Screen<'_> {
                 self.clear_selection();
 
                 // Start new empty selection.
-                if self.modifiers.state().control_key() {
+                if !self.block_selection_modifiers.is_empty()
+                    && self
+                        .modifiers
+                        .state()
+                        .contains(self.block_selection_modifiers)
+                {
                     self.start_selection(SelectionType::Block, point, side);
                 } else {
                     self.start_selection(SelectionType::Simple, point, side);
@@ -1557,9 +2334,15 @@ impl Screen<'_> {
             }
             ClickState::DoubleClick => {
                 self.start_selection(SelectionType::Semantic, point, side);
+                if self.haptic_feedback.selection {
+                    self.perform_haptic_feedback();
+                }
             }
             ClickState::TripleClick => {
                 self.start_selection(SelectionType::Lines, point, side);
+                if self.haptic_feedback.selection {
+                    self.perform_haptic_feedback();
+                }
             }
             ClickState::None => (),
         };
@@ -1627,10 +2410,9 @@ impl Screen<'_> {
         }
 
         // Force unlimited search if the previous one was interrupted.
-        // let timer_id = TimerId::new(Topic::DelayedSearch, self.display.window.id());
-        // if self.scheduler.scheduled(timer_id) {
-        //     self.goto_match(None);
-        // }
+        if self.search_state.delayed_search {
+            self.goto_match(None);
+        }
 
         self.exit_search();
     }
@@ -1660,6 +2442,8 @@ impl Screen<'_> {
         // self.window().set_ime_allowed(!vi_mode);
 
         self.search_state.history_index = None;
+        self.search_state.delayed_search = false;
+        self.is_search_count_delayed = false;
 
         // Clear focused match.
         self.search_state.focused_match = None;
@@ -1667,6 +2451,108 @@ impl Screen<'_> {
         self.render();
     }
 
+    /// Runs the search which ran into its limit while typing and counts
+    /// the matches, unless the search was left since.
+    pub fn delayed_search(&mut self) {
+        if self.search_state.history_index.is_none()
+            || !(self.search_state.delayed_search || self.is_search_count_delayed)
+        {
+            return;
+        }
+
+        if self.search_state.delayed_search {
+            self.goto_match(None);
+        }
+        // After the search, which may focus another match
+        self.count_search_matches();
+        self.render();
+    }
+
+    /// Waits for the character of a vi inline search.
+    fn start_inline_search(&mut self, direction: Direction, stop_short: bool) {
+        self.inline_search_state.char_pending = true;
+        self.inline_search_state.direction = direction;
+        self.inline_search_state.stop_short = stop_short;
+    }
+
+    fn inline_search_input(&mut self, c: char) {
+        self.inline_search_state.char_pending = false;
+        self.inline_search_state.character = Some(c);
+        self.inline_search(self.inline_search_state.direction, false);
+    }
+
+    /// Moves the vi cursor to the character of the last inline search.
+    fn inline_search(&mut self, direction: Direction, repeat: bool) {
+        let Some(c) = self.inline_search_state.character else {
+            return;
+        };
+
+        let current_context = self.context_manager.current_mut();
+        let mut terminal = current_context.terminal.lock();
+        let stop_short = self.inline_search_state.stop_short;
+        let has_moved = terminal.vi_inline_search(c, direction, stop_short, repeat);
+        if let Some(selection) = &terminal.selection {
+            current_context.renderable_content.selection_range =
+                selection.to_range(&terminal);
+        };
+        drop(terminal);
+
+        if has_moved {
+            self.render();
+        }
+    }
+
+    /// Handles the keys typed in hint mode, escape leaves it.
+    fn hint_input(&mut self, key: &rio_window::event::KeyEvent) {
+        match key.logical_key.as_ref() {
+            Key::Named(NamedKey::Escape) => self.hint_state.stop(),
+            Key::Named(NamedKey::Backspace) => self.hint_state.pop_key(),
+            _ => {
+                let text = key.text_with_all_modifiers().unwrap_or_default();
+                for c in text.chars() {
+                    if let Some((action, hint)) = self.hint_state.keyboard_input(c) {
+                        self.trigger_hint(action, hint);
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.context_manager
+            .current_mut()
+            .renderable_content
+            .has_pending_updates = true;
+        self.render();
+    }
+
+    fn trigger_hint(&mut self, action: HintAction, hint: HintMatch) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let text = terminal.bounds_to_string(*hint.bounds.start(), *hint.bounds.end());
+        drop(terminal);
+
+        if let Some(command) = self.hint_state.command(&hint, &text) {
+            if let [program, args @ ..] = command.as_slice() {
+                self.exec(program, args);
+            }
+            return;
+        }
+
+        match action {
+            HintAction::Copy => self.copy(text),
+            HintAction::Open => {
+                // Only shells expand the home directory
+                let path = text
+                    .strip_prefix("~/")
+                    .zip(dirs::home_dir())
+                    .map(|(path, home)| home.join(path).to_string_lossy().to_string());
+                self.open_url(path.as_deref().unwrap_or(&text));
+            }
+            HintAction::Paste => {
+                self.paste(&text, true);
+            }
+        }
+    }
+
     #[inline]
     fn search_input(&mut self, c: char) {
         match self.search_state.history_index {
@@ -1701,7 +2587,53 @@ impl Screen<'_> {
         self.render();
     }
 
+    fn search_count_key(&self) -> Option<SearchCountKey> {
+        let regex = self.search_state.regex()?.clone();
+        let terminal = self.context_manager.current().terminal.lock();
+        Some(SearchCountKey {
+            regex,
+            focused_match: self.search_state.focused_match.clone(),
+            pty_bytes_read: terminal.pty_bytes_read,
+            columns: terminal.columns(),
+            screen_lines: terminal.screen_lines(),
+        })
+    }
+
+    /// Total number of matches for the current search and the position of the
+    /// focused one. Counting goes through the whole scrollback, so when the
+    /// search or the content changed it's delayed like the search while
+    /// typing and the previous count is shown until then.
+    fn search_match_count(&mut self) -> Option<SearchMatchCount> {
+        let key = self.search_count_key()?;
+        let (cached_key, count) = match &self.search_match_count {
+            Some((cached_key, count)) => (Some(cached_key), Some(*count)),
+            None => (None, None),
+        };
+        if cached_key != Some(&key) && !self.is_search_count_delayed {
+            self.is_search_count_delayed = true;
+            self.context_manager.delay_search(TYPING_SEARCH_DELAY);
+        }
+        count
+    }
+
+    fn count_search_matches(&mut self) {
+        self.is_search_count_delayed = false;
+        let Some(key) = self.search_count_key() else {
+            return;
+        };
+
+        let terminal = self.context_manager.current().terminal.lock();
+        let Some(dfas) = self.search_state.dfas_mut() else {
+            return;
+        };
+        let count = terminal.search_match_count(dfas, key.focused_match.as_ref());
+        drop(terminal);
+
+        self.search_match_count = Some((key, count));
+    }
+
     fn update_search(&mut self) {
+        self.search_match_count = None;
         let regex = match self.search_state.regex() {
             Some(regex) => regex,
             None => return,
@@ -1713,7 +2645,8 @@ impl Screen<'_> {
             self.search_state.dfas = None;
         } else {
             // Create search dfas for the new regex string.
-            self.search_state.dfas = RegexSearch::new(regex).ok();
+            self.search_state.dfas =
+                RegexSearch::with_options(regex, self.search_state.options).ok();
 
             // Update search highlighting.
             self.goto_match(MAX_SEARCH_WHILE_TYPING);
@@ -1722,9 +2655,8 @@ impl Screen<'_> {
 
     /// Reset terminal to the state before search was started.
     fn search_reset_state(&mut self) {
-        // Unschedule pending timers.
-        // let timer_id = TimerId::new(Topic::DelayedSearch, self.display.window.id());
-        // self.scheduler.unschedule(timer_id);
+        // Drop the pending search, the timer finds nothing to do.
+        self.search_state.delayed_search = false;
 
         // Clear focused match.
         self.search_state.focused_match = None;
@@ -1756,6 +2688,7 @@ impl Screen<'_> {
         };
 
         let mut should_reset_search_state = false;
+        let mut should_delay_search = false;
 
         // Jump to the next match.
         {
@@ -1789,8 +2722,7 @@ impl Screen<'_> {
                         old_offset - display_offset as i32;
 
                     // Since we found a result, we require no delayed re-search.
-                    // let timer_id = TimerId::new(Topic::DelayedSearch, self.display.window.id());
-                    // self.scheduler.unschedule(timer_id);
+                    self.search_state.delayed_search = false;
                 }
                 // Reset viewport only when we know there is no match, to prevent unnecessary jumping.
                 None if limit.is_none() => {
@@ -1798,11 +2730,7 @@ impl Screen<'_> {
                 }
                 None => {
                     // Schedule delayed search if we ran into our search limit.
-                    // let timer_id = TimerId::new(Topic::DelayedSearch, self.display.window.id());
-                    // if !self.scheduler.scheduled(timer_id) {
-                    // let event = Event::new(EventType::SearchNext, self.display.window.id());
-                    // self.scheduler.schedule(event, TYPING_SEARCH_DELAY, false, timer_id);
-                    // }
+                    should_delay_search = true;
 
                     // Clear focused match.
                     self.search_state.focused_match = None;
@@ -1814,6 +2742,11 @@ impl Screen<'_> {
         if should_reset_search_state {
             self.search_reset_state();
         }
+
+        if should_delay_search {
+            self.search_state.delayed_search = true;
+            self.context_manager.delay_search(TYPING_SEARCH_DELAY);
+        }
     }
 
     fn sgr_mouse_report(&mut self, pos: Pos, button: u8, state: ElementState) {
@@ -1914,6 +2847,12 @@ impl Screen<'_> {
 
     #[inline]
     pub fn on_focus_change(&mut self, is_focused: bool) {
+        self.renderer.set_window_focused(is_focused);
+        self.context_manager
+            .current_mut()
+            .renderable_content
+            .has_pending_updates = true;
+
         if self.get_mode().contains(Mode::FOCUS_IN_OUT) {
             let chr = if is_focused { "I" } else { "O" };
 
@@ -1925,6 +2864,61 @@ impl Screen<'_> {
         }
     }
 
+    /// Whether the wheel changes the opacity of the window instead of
+    /// scrolling.
+    #[inline]
+    pub fn is_opacity_gesture(&self) -> bool {
+        !self.opacity_modifiers.is_empty()
+            && self.modifiers.state() == self.opacity_modifiers
+            && self.renderer.opacity().is_some()
+    }
+
+    /// Changes the opacity of the window a step for each scrolled line,
+    /// returns the new opacity when it changed. It's kept until the
+    /// configuration is reloaded.
+    pub fn scroll_opacity(&mut self, new_scroll_y_px: f64) -> Option<f32> {
+        let opacity = self.renderer.opacity()?;
+        let height = self
+            .sugarloaf
+            .rich_text_layout(&self.context_manager.current().rich_text_id)
+            .dimensions
+            .height as f64;
+
+        self.mouse.accumulated_scroll.y += new_scroll_y_px;
+        let steps = (self.mouse.accumulated_scroll.y / height) as i32;
+        self.mouse.accumulated_scroll.y %= height;
+
+        if steps == 0 {
+            return None;
+        }
+        self.set_opacity(opacity + steps as f32 * OPACITY_STEP)
+    }
+
+    /// Changes the opacity of the background, returns the new opacity when
+    /// it changed. The window follows it with `RioEvent::UpdateOpacity`
+    /// or the returned value.
+    fn set_opacity(&mut self, opacity: f32) -> Option<f32> {
+        let current_opacity = self.renderer.opacity()?;
+        let new_opacity = opacity.clamp(MIN_OPACITY, 1.0);
+        if new_opacity == current_opacity {
+            return None;
+        }
+
+        self.renderer.set_opacity(new_opacity);
+        if !cfg!(target_os = "macos") {
+            self.sugarloaf
+                .set_background_color(Some(self.renderer.dynamic_background.1));
+        }
+        // The cells with the default background are drawn again
+        for context_grid in self.context_manager.contexts_mut() {
+            for item in context_grid.contexts_mut() {
+                item.context_mut().renderable_content.has_pending_updates = true;
+            }
+        }
+
+        Some(new_opacity)
+    }
+
     #[inline]
     pub fn scroll(&mut self, new_scroll_x_px: f64, new_scroll_y_px: f64) {
         let layout = self
@@ -2007,8 +3001,25 @@ impl Screen<'_> {
 
             if lines != 0 {
                 let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let display_offset = terminal.display_offset();
                 terminal.scroll_display(Scroll::Delta(lines));
+                let new_display_offset = terminal.display_offset();
+                let history_size = terminal.history_size();
                 drop(terminal);
+
+                // Felt once when the scrollback stops moving at either end
+                if self.haptic_feedback.scrollback
+                    && new_display_offset != display_offset
+                    && (new_display_offset == 0 || new_display_offset == history_size)
+                {
+                    self.perform_haptic_feedback();
+                }
+
+                // There is no history to scroll while an application owns
+                // the alternate screen
+                if mode.contains(Mode::ALT_SCREEN) && self.renderer.show_scroll_hint() {
+                    self.render();
+                }
             }
         }
 
@@ -2016,8 +3027,139 @@ impl Screen<'_> {
         self.mouse.accumulated_scroll.y %= height;
     }
 
+    /// Plays the trackpad haptic feedback, only available on macOS.
+    #[inline]
+    fn perform_haptic_feedback(&mut self) {
+        #[cfg(target_os = "macos")]
+        self.context_manager.haptic_feedback();
+    }
+
+    /// Pastes the text, suspicious pastes wait for a confirmation when
+    /// `paste.confirm` is enabled.
     #[inline]
     pub fn paste(&mut self, text: &str, bracketed: bool) {
+        if self.paste_config.confirm && !self.search_active() {
+            let is_bracketed =
+                bracketed && self.get_mode().contains(Mode::BRACKETED_PASTE);
+            if let Some(warning) =
+                paste::paste_warning(text, is_bracketed, self.paste_config.large_size)
+            {
+                self.pending_paste = Some(PendingPaste {
+                    text: text.to_owned(),
+                    bracketed,
+                    warning,
+                });
+                self.context_manager.confirm_paste();
+                return;
+            }
+        }
+
+        self.write_paste(text, bracketed);
+    }
+
+    /// Writes the paste waiting for a confirmation, `false` drops it.
+    pub fn resolve_pending_paste(&mut self, confirmed: bool) {
+        if let Some(pending) = self.pending_paste.take() {
+            if confirmed {
+                self.write_paste(&pending.text, pending.bracketed);
+            }
+        }
+    }
+
+    /// Handles an OSC 52 request following the `clipboard` config, returns
+    /// true when it waits for a confirmation.
+    pub fn clipboard_request(&mut self, request: ClipboardRequest) -> bool {
+        let osc52 = self.clipboard_config.osc52;
+        match &request {
+            // The prompt on screen stays bound to the first request
+            _ if self.pending_clipboard_request.is_some() => {
+                tracing::info!("OSC 52 request denied while another one is confirmed");
+                self.reject_clipboard_request(request);
+                return false;
+            }
+            ClipboardRequest::Store(..) if !osc52.can_write() => {
+                tracing::info!("OSC 52 clipboard write denied by clipboard.osc52");
+                return false;
+            }
+            ClipboardRequest::Store(_, text)
+                if text.len() > self.clipboard_config.osc52_max_size =>
+            {
+                tracing::warn!(
+                    "OSC 52 clipboard write of {} bytes is over clipboard.osc52-max-size",
+                    text.len()
+                );
+                return false;
+            }
+            ClipboardRequest::Load(..) if !osc52.can_read() => {
+                tracing::info!("OSC 52 clipboard read denied by clipboard.osc52");
+                self.reject_clipboard_request(request);
+                return false;
+            }
+            _ => {}
+        }
+
+        if self.clipboard_config.osc52_confirm {
+            self.pending_clipboard_request = Some(request);
+            return true;
+        }
+
+        self.run_clipboard_request(request);
+        false
+    }
+
+    /// Runs the OSC 52 request waiting for a confirmation, `false` drops it.
+    pub fn resolve_clipboard_request(&mut self, confirmed: bool) {
+        if let Some(request) = self.pending_clipboard_request.take() {
+            if confirmed {
+                self.run_clipboard_request(request);
+            } else {
+                self.reject_clipboard_request(request);
+            }
+        }
+    }
+
+    /// Drops an OSC 52 request, reads still get an empty answer so the
+    /// program doesn't wait for it.
+    pub fn reject_clipboard_request(&mut self, request: ClipboardRequest) {
+        if let ClipboardRequest::Load(route_id, _, response) = request {
+            self.answer_clipboard_load(route_id, response(""));
+        }
+    }
+
+    /// Answers a read to the terminal which asked, even in a background
+    /// tab or split.
+    fn answer_clipboard_load(&mut self, route_id: usize, response: String) {
+        if let Some(context) = self.ctx_mut().context_by_route_mut(route_id) {
+            context.messenger.send_bytes(response.into_bytes());
+        }
+    }
+
+    fn run_clipboard_request(&mut self, request: ClipboardRequest) {
+        match request {
+            ClipboardRequest::Store(clipboard_type, text) => {
+                self.clipboard.borrow_mut().set(clipboard_type, text);
+            }
+            ClipboardRequest::Load(route_id, clipboard_type, response) => {
+                let mut text = self.clipboard.borrow_mut().get(clipboard_type);
+                // The program still gets an answer, so it doesn't wait for it
+                if text.len() > self.clipboard_config.osc52_max_size {
+                    tracing::warn!(
+                        "OSC 52 clipboard read of {} bytes is over clipboard.osc52-max-size",
+                        text.len()
+                    );
+                    text.clear();
+                }
+
+                self.answer_clipboard_load(route_id, response(&text));
+            }
+        }
+    }
+
+    fn write_paste(&mut self, text: &str, bracketed: bool) {
+        if self.scroll_config.on_paste && !self.search_active() {
+            self.scroll_bottom_when_cursor_not_visible();
+        }
+
         if self.search_active() {
             for c in text.chars() {
                 self.search_input(c);
@@ -2060,27 +3202,140 @@ impl Screen<'_> {
             &mut self.sugarloaf,
             &self.context_manager.current().dimension,
             assistant,
+            &self.locale,
         );
         self.sugarloaf.render();
     }
 
-    pub fn render_welcome(&mut self) {
+    pub fn render_welcome(
+        &mut self,
+        settings: &crate::router::routes::settings::Settings,
+    ) {
         self.sugarloaf.clear();
         crate::router::routes::welcome::screen(
             &mut self.sugarloaf,
             &self.context_manager.current().dimension,
+            &self.locale,
+            settings,
+        );
+        self.sugarloaf.render();
+    }
+
+    pub fn render_clipboard_history(
+        &mut self,
+        picker: &crate::router::routes::clipboard_history::ClipboardHistory,
+    ) {
+        self.sugarloaf.clear();
+        let clipboard = self.clipboard.borrow();
+        crate::router::routes::clipboard_history::screen(
+            &mut self.sugarloaf,
+            &self.context_manager.current().dimension,
+            &self.locale,
+            clipboard.history().iter(),
+            picker.selected,
+        );
+        drop(clipboard);
+        self.sugarloaf.render();
+    }
+
+    pub fn render_command_palette(
+        &mut self,
+        palette: &crate::router::routes::command_palette::CommandPalette,
+    ) {
+        let entries: Vec<(&str, Option<String>)> = palette
+            .matches()
+            .into_iter()
+            .map(|name| (name, self.action_keys(&Act::from(name.to_string()))))
+            .collect();
+
+        self.sugarloaf.clear();
+        crate::router::routes::command_palette::screen(
+            &mut self.sugarloaf,
+            &self.context_manager.current().dimension,
+            &self.locale,
+            &palette.query,
+            &entries,
+            palette.selected,
+        );
+        self.sugarloaf.render();
+    }
+
+    pub fn render_settings(
+        &mut self,
+        settings: &crate::router::routes::settings::Settings,
+    ) {
+        self.sugarloaf.clear();
+        crate::router::routes::settings::screen(
+            &mut self.sugarloaf,
+            &self.context_manager.current().dimension,
+            &self.locale,
+            settings,
+        );
+        self.sugarloaf.render();
+    }
+
+    /// Keys of the binding running the action outside of the vi and
+    /// search modes, e.g. `ctrl+shift+h`.
+    fn action_keys(&self, action: &Act) -> Option<String> {
+        let mode = BindingMode::empty();
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == *action && binding.is_active_in(&mode))
+            .map(|binding| {
+                let mut steps = binding.leader.clone();
+                steps.push((binding.trigger.clone(), binding.mods));
+                format_key_steps(&steps)
+            })
+    }
+
+    pub fn render_confirm_paste(&mut self) {
+        let Some(pending) = &self.pending_paste else {
+            return;
+        };
+
+        self.sugarloaf.clear();
+        crate::router::routes::dialog::paste_screen(
+            &mut self.sugarloaf,
+            &self.context_manager.current().dimension,
+            &self.locale,
+            pending.warning,
+            &paste::preview(&pending.text),
+            self.paste_config.highlight_shell,
+        );
+        self.sugarloaf.render();
+    }
+
+    pub fn render_confirm_clipboard_request(&mut self) {
+        let Some(request) = &self.pending_clipboard_request else {
+            return;
+        };
+
+        let preview = match request {
+            ClipboardRequest::Store(_, text) => paste::preview(text),
+            ClipboardRequest::Load(..) => Vec::new(),
+        };
+
+        self.sugarloaf.clear();
+        crate::router::routes::dialog::clipboard_screen(
+            &mut self.sugarloaf,
+            &self.context_manager.current().dimension,
+            &self.locale,
+            request.locale_key(),
+            &preview,
         );
         self.sugarloaf.render();
     }
 
-    pub fn render_dialog(&mut self, content: &str, confirm: &str, close: &str) {
+    pub fn render_confirm_quit(
+        &mut self,
+        pending_quit: &crate::router::routes::dialog::PendingQuit,
+    ) {
         self.sugarloaf.clear();
         crate::router::routes::dialog::screen(
             &mut self.sugarloaf,
             &self.context_manager.current().dimension,
-            content,
-            confirm,
-            close,
+            &self.locale,
+            pending_quit,
         );
         self.sugarloaf.render();
     }
@@ -2095,8 +3350,20 @@ impl Screen<'_> {
                     self.search_state.history.get(history_index).cloned(),
                 );
             }
+            let match_count = self.search_match_count();
+            self.renderer.set_search_match_count(match_count);
+            self.renderer.set_search_options(self.search_state.options);
         }
 
+        let hint_labels = if self.hint_state.is_active() {
+            let terminal = self.context_manager.current().terminal.lock();
+            let viewport_start = Line(-(terminal.display_offset() as i32));
+            drop(terminal);
+            self.hint_state.visible_labels(viewport_start)
+        } else {
+            Vec::new()
+        };
+
         let mut search_hints = if is_search_active {
             let terminal = self.context_manager.current().terminal.lock();
             let hints = self
@@ -2113,13 +3380,13 @@ impl Screen<'_> {
             &mut self.sugarloaf,
             &mut self.context_manager,
             &mut search_hints,
-            &self.search_state.focused_match,
+            &mut self.search_state.focused_match,
+            &hint_labels,
         );
-        // In this case the configuration of blinking cursor is enabled
-        // and the terminal also have instructions of blinking enabled
+        // The terminal has blinking enabled, either by the configuration or
+        // by the program (DECSCUSR)
         // TODO: enable blinking for selection after adding debounce (https://github.com/raphamorim/rio/issues/437)
-        if self.renderer.config_has_blinking_enabled
-            && self.selection_is_empty()
+        if self.selection_is_empty()
             && self
                 .context_manager
                 .current()
@@ -2134,3 +3401,12 @@ impl Screen<'_> {
         // println!("Total whole render function is: {:?}\n", duration);
     }
 }
+
+#[inline]
+fn present_mode_from_config(present_mode: RendererPresentMode) -> wgpu::PresentMode {
+    match present_mode {
+        RendererPresentMode::Fifo => wgpu::PresentMode::Fifo,
+        RendererPresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+        RendererPresentMode::Immediate => wgpu::PresentMode::Immediate,
+    }
+}
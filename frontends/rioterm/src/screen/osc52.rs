@@ -0,0 +1,22 @@
+use rio_backend::clipboard::ClipboardType;
+use std::sync::Arc;
+
+/// Builds the OSC 52 response from the clipboard text.
+pub type ClipboardResponse = Arc<dyn Fn(&str) -> String + Sync + Send + 'static>;
+
+/// Clipboard access requested by a program with OSC 52.
+pub enum ClipboardRequest {
+    Store(ClipboardType, String),
+    /// Read answered to the PTY of the route.
+    Load(usize, ClipboardType, ClipboardResponse),
+}
+
+impl ClipboardRequest {
+    /// Key of the translation describing the request.
+    pub fn locale_key(&self) -> &'static str {
+        match self {
+            ClipboardRequest::Store(..) => "clipboard-warning-write",
+            ClipboardRequest::Load(..) => "clipboard-warning-read",
+        }
+    }
+}
@@ -0,0 +1,218 @@
+/// Lines of the paste shown when asking to confirm it.
+const PREVIEW_LINES: usize = 8;
+/// Characters of each line shown when asking to confirm a paste.
+const PREVIEW_LINE_LENGTH: usize = 80;
+
+/// Why a paste is confirmed before being written, pasted commands from
+/// untrusted pages may hide new lines or control characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteWarning {
+    /// New lines run the pasted commands, only without bracketed paste.
+    NewLines,
+    ControlCharacters,
+    Large,
+}
+
+impl PasteWarning {
+    /// Key of the translation explaining the warning.
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            PasteWarning::NewLines => "paste-warning-new-lines",
+            PasteWarning::ControlCharacters => "paste-warning-control-characters",
+            PasteWarning::Large => "paste-warning-large",
+        }
+    }
+}
+
+/// Text waiting for the paste to be confirmed.
+#[derive(Debug)]
+pub struct PendingPaste {
+    pub text: String,
+    pub bracketed: bool,
+    pub warning: PasteWarning,
+}
+
+pub fn paste_warning(
+    text: &str,
+    is_bracketed: bool,
+    large_size: usize,
+) -> Option<PasteWarning> {
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+    {
+        Some(PasteWarning::ControlCharacters)
+    } else if !is_bracketed && text.contains(['\n', '\r']) {
+        Some(PasteWarning::NewLines)
+    } else if large_size > 0 && text.chars().count() > large_size {
+        Some(PasteWarning::Large)
+    } else {
+        None
+    }
+}
+
+/// First lines of the paste, control characters are written in caret
+/// notation so they can be seen.
+pub fn preview(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = text
+        .lines()
+        .take(PREVIEW_LINES)
+        .map(|line| {
+            let mut preview = String::new();
+            for c in line.chars().take(PREVIEW_LINE_LENGTH) {
+                match c {
+                    '\t' => preview.push(' '),
+                    c if (c as u32) < 0x20 => {
+                        preview.push('^');
+                        preview.push((c as u8 + b'@') as char);
+                    }
+                    '\x7f' => preview.push_str("^?"),
+                    c if c.is_control() => {
+                        preview.push_str(&format!("\\u{{{:x}}}", c as u32))
+                    }
+                    c => preview.push(c),
+                }
+            }
+            if line.chars().count() > PREVIEW_LINE_LENGTH {
+                preview.push('…');
+            }
+            preview
+        })
+        .collect();
+
+    if text.lines().count() > PREVIEW_LINES {
+        lines.push(String::from("…"));
+    }
+
+    lines
+}
+
+/// Splits a line of the preview in segments, the ones interpreted by the
+/// shell are marked: quotes and the `` ` ``, `$(` and `${` substitutions.
+pub fn shell_segments(line: &str) -> Vec<(String, bool)> {
+    let mut segments: Vec<(String, bool)> = Vec::new();
+    let mut push = |c: char, is_special: bool| match segments.last_mut() {
+        Some((text, last_is_special)) if *last_is_special == is_special => text.push(c),
+        _ => segments.push((String::from(c), is_special)),
+    };
+
+    let mut chars = line.chars();
+    let mut in_single_quotes = false;
+    let mut in_double_quotes = false;
+    let mut in_backticks = false;
+    // Open brackets of the `$(` and `${` substitutions
+    let mut depth = 0usize;
+
+    while let Some(c) = chars.next() {
+        let in_substitution = in_backticks || depth > 0;
+        if in_single_quotes {
+            in_single_quotes = c != '\'';
+            push(c, in_substitution || c == '\'');
+            continue;
+        }
+
+        match c {
+            '\\' => {
+                push(c, in_substitution);
+                if let Some(escaped) = chars.next() {
+                    push(escaped, in_substitution);
+                }
+            }
+            '\'' if !in_double_quotes => {
+                in_single_quotes = true;
+                push(c, true);
+            }
+            '"' => {
+                in_double_quotes = !in_double_quotes;
+                push(c, true);
+            }
+            '`' => {
+                in_backticks = !in_backticks;
+                push(c, true);
+            }
+            '$' if chars.as_str().starts_with(['(', '{']) => {
+                depth += 1;
+                push(c, true);
+                push(chars.next().unwrap(), true);
+            }
+            '(' | '{' if depth > 0 => {
+                depth += 1;
+                push(c, true);
+            }
+            ')' | '}' if depth > 0 => {
+                depth -= 1;
+                push(c, true);
+            }
+            _ => push(c, in_substitution),
+        }
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_warning() {
+        assert_eq!(paste_warning("ls -la", false, 100), None);
+        assert_eq!(
+            paste_warning("ls\nrm -rf ~", false, 100),
+            Some(PasteWarning::NewLines)
+        );
+        assert_eq!(paste_warning("ls\nrm -rf ~", true, 100), None);
+        assert_eq!(
+            paste_warning("ls\x1b[201~rm", true, 100),
+            Some(PasteWarning::ControlCharacters)
+        );
+        assert_eq!(
+            paste_warning(&"a".repeat(101), true, 100),
+            Some(PasteWarning::Large)
+        );
+        assert_eq!(paste_warning(&"a".repeat(101), true, 0), None);
+    }
+
+    #[test]
+    fn test_shell_segments() {
+        assert_eq!(
+            shell_segments("ls -la"),
+            vec![(String::from("ls -la"), false)]
+        );
+        assert_eq!(
+            shell_segments("echo $(curl x | sh) `id` ${HOME}"),
+            vec![
+                (String::from("echo "), false),
+                (String::from("$(curl x | sh)"), true),
+                (String::from(" "), false),
+                (String::from("`id`"), true),
+                (String::from(" "), false),
+                (String::from("${HOME}"), true),
+            ]
+        );
+        assert_eq!(
+            shell_segments(r#"echo "it's" 'a $(b)' \$(c)"#),
+            vec![
+                (String::from("echo "), false),
+                (String::from("\""), true),
+                (String::from("it's"), false),
+                (String::from("\""), true),
+                (String::from(" "), false),
+                (String::from("'"), true),
+                (String::from("a $(b)"), false),
+                (String::from("'"), true),
+                (String::from(" \\$(c)"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("echo\x1bhi\ttab\nls"), vec!["echo^[hi tab", "ls"]);
+
+        let long = "a\n".repeat(PREVIEW_LINES + 2);
+        let lines = preview(&long);
+        assert_eq!(lines.len(), PREVIEW_LINES + 1);
+        assert_eq!(lines.last().unwrap(), "…");
+    }
+}
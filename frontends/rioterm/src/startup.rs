@@ -0,0 +1,92 @@
+//! Time spent in each phase of the startup, so reports of a slow font scan
+//! or GPU initialization come with the breakdown of where the time went.
+
+use std::time::{Duration, Instant};
+
+pub struct StartupTimings {
+    started: Instant,
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl StartupTimings {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last: now,
+            phases: Vec::new(),
+        }
+    }
+
+    /// Records the time since the previous phase ended as spent in `name`.
+    pub fn phase(&mut self, name: &'static str) {
+        let now = Instant::now();
+        self.phases.push((name, now - self.last));
+        self.last = now;
+    }
+
+    pub fn total(&self) -> Duration {
+        self.last - self.started
+    }
+
+    /// Phases and their milliseconds, e.g. `config=4ms fonts=120ms`.
+    pub fn breakdown(&self) -> String {
+        self.phases
+            .iter()
+            .map(|(name, duration)| format!("{name}={}ms", duration.as_millis()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn is_over_budget(&self, budget: Duration) -> bool {
+        self.total() > budget
+    }
+
+    /// Logs the breakdown, as a warning when the startup took longer
+    /// than `budget`.
+    pub fn report(&self, budget: Duration) {
+        let total_ms = self.total().as_millis() as u64;
+        let breakdown = self.breakdown();
+        if self.is_over_budget(budget) {
+            tracing::warn!(
+                total_ms,
+                budget_ms = budget.as_millis() as u64,
+                breakdown = %breakdown,
+                "startup took longer than the budget"
+            );
+        } else {
+            tracing::info!(total_ms, breakdown = %breakdown, "startup finished");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_breakdown() {
+        let mut timings = StartupTimings::new();
+        std::thread::sleep(Duration::from_millis(5));
+        timings.phase("config");
+        timings.phase("fonts");
+
+        let breakdown = timings.breakdown();
+        let phases: Vec<(&str, u128)> = breakdown
+            .split(' ')
+            .map(|phase| {
+                let (name, duration) = phase.split_once('=').unwrap();
+                (name, duration.strip_suffix("ms").unwrap().parse().unwrap())
+            })
+            .collect();
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases[0].0, "config");
+        assert!(phases[0].1 >= 5);
+        assert_eq!(phases[1].0, "fonts");
+
+        assert!(timings.total() >= Duration::from_millis(5));
+        assert!(timings.is_over_budget(Duration::from_millis(1)));
+        assert!(!timings.is_over_budget(Duration::from_secs(3600)));
+    }
+}
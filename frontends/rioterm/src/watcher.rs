@@ -1,10 +1,35 @@
 use crate::event::{EventListener, RioEvent};
 use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::time::Duration;
 
 const POLLING_TIMEOUT: Duration = Duration::from_secs(2);
 
+/// Content of the config file after Rio last wrote it. Rio applies its own
+/// writes right away, so the watcher skips the changes leading to it.
+static OWN_WRITE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Records the content of the config file Rio just wrote at `path`.
+pub fn ignore_own_write(path: &Path) {
+    *OWN_WRITE.lock().unwrap() = std::fs::read_to_string(path).ok();
+}
+
+/// Whether the changed `paths` are only the config file, holding what Rio
+/// last wrote to it.
+fn is_own_write(paths: &[PathBuf], config_file: &Path) -> bool {
+    let only_config_file = !paths.is_empty()
+        && paths
+            .iter()
+            .all(|path| path.file_name() == config_file.file_name());
+    if !only_config_file {
+        return false;
+    }
+
+    let own_write = OWN_WRITE.lock().unwrap();
+    own_write.is_some() && std::fs::read_to_string(config_file).ok() == *own_write
+}
+
 pub fn configuration_file_updates<
     P: AsRef<Path> + std::marker::Send + 'static,
     T: EventListener + std::marker::Send + 'static,
@@ -22,6 +47,8 @@ pub fn configuration_file_updates<
     )?;
 
     std::thread::spawn(move || {
+        let config_file = path.as_ref().join("config.toml");
+
         // Add a path to be watched. All files and directories at that path and
         // below will be monitored for changes.
         if let Err(err_message) =
@@ -37,6 +64,10 @@ pub fn configuration_file_updates<
                     | EventKind::Create(_)
                     | EventKind::Modify(_)
                     | EventKind::Other => {
+                        if is_own_write(&event.paths, &config_file) {
+                            continue;
+                        }
+
                         tracing::info!(
                             "config directory has dispatched an event {event:?}"
                         );
@@ -56,3 +87,29 @@ pub fn configuration_file_updates<
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_own_write() {
+        let dir = std::env::temp_dir().join("test-rio-watcher-own-write");
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_file = dir.join("config.toml");
+        std::fs::write(&config_file, "[fonts]\nsize = 14\n").unwrap();
+
+        let paths = vec![config_file.clone()];
+        assert!(!is_own_write(&paths, &config_file));
+
+        ignore_own_write(&config_file);
+        assert!(is_own_write(&paths, &config_file));
+        // Other files of the config directory, like themes, are applied
+        let theme = dir.join("themes").join("dracula.toml");
+        assert!(!is_own_write(&[theme], &config_file));
+
+        // Edited since Rio wrote it
+        std::fs::write(&config_file, "[fonts]\nsize = 16\n").unwrap();
+        assert!(!is_own_write(&paths, &config_file));
+    }
+}
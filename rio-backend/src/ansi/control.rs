@@ -96,3 +96,13 @@ pub mod C0 {
     #[allow(dead_code)]
     pub const DEL: u8 = 0x7f;
 }
+
+/// Glyph of the Unicode Control Pictures block (`U+2400..U+2421`) for a C0
+/// control character or DEL.
+pub fn control_picture(byte: u8) -> Option<char> {
+    match byte {
+        0x00..=0x1f => char::from_u32(0x2400 + byte as u32),
+        C0::DEL => Some('\u{2421}'),
+        _ => None,
+    }
+}
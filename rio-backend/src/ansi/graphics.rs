@@ -2,14 +2,21 @@
 // Alacritty is licensed under Apache 2.0 license.
 // https://github.com/alacritty/alacritty/pull/4763/files
 
+use crate::ansi::kitty_graphics::{KittyImageState, KittyPlacementId};
 use crate::ansi::sixel;
 use crate::config::colors::ColorRgb;
 use crate::crosswords::grid::Dimensions;
 use crate::sugarloaf::{GraphicData, GraphicId};
 use parking_lot::Mutex;
 use smallvec::SmallVec;
+use std::collections::BTreeMap;
 use std::mem;
+use std::ops::AddAssign;
 use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+/// Z-index of Sixel and iTerm2 graphics, which are drawn below the text.
+pub const BELOW_TEXT_Z_INDEX: i32 = -1;
 
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct ClearSubregion {
@@ -32,6 +39,10 @@ pub struct UpdateQueues {
 
     /// Subregions in a graphic to be clear.
     pub clear_subregions: Vec<ClearSubregion>,
+
+    /// Frames of animated graphics, shown in place of the graphics with
+    /// the same ids.
+    pub frames: Vec<GraphicData>,
 }
 
 #[derive(Clone, Debug)]
@@ -48,6 +59,12 @@ pub struct TextureRef {
     /// Height, in pixels, of the cell when the graphic was inserted.
     pub cell_height: usize,
 
+    /// Order of the graphic relative to the text and to other graphics.
+    pub z_index: i32,
+
+    /// Kitty image and placement the graphic was placed for.
+    pub kitty: Option<KittyPlacementId>,
+
     /// Queue to track removed references.
     pub texture_operations: Weak<Mutex<Vec<TextureOperation>>>,
 }
@@ -124,8 +141,149 @@ pub enum TextureOperation {
     ClearSubregion(ClearSubregion),
 }
 
+/// Frame of an animated graphic.
+#[derive(Clone, Debug)]
+pub struct AnimationFrame {
+    pub graphic: GraphicData,
+
+    /// Time the frame is shown before the next one.
+    pub delay: Duration,
+}
+
+/// Animated graphic, its frames are shown in turn by the same texture.
+#[derive(Debug)]
+pub struct Animation {
+    pub frames: Vec<AnimationFrame>,
+
+    /// Index of the frame shown.
+    pub current: usize,
+
+    /// Whether the frames advance.
+    pub running: bool,
+
+    /// Stop at the last frame instead of starting over, until more frames
+    /// are added.
+    pub loading: bool,
+
+    /// Times the animation starts over after the last frame, `None` to
+    /// loop forever.
+    pub loops: Option<u32>,
+
+    /// When the next frame is due.
+    deadline: Instant,
+}
+
+impl Animation {
+    /// Animation looping forever, starting at the first frame.
+    pub fn new(frames: Vec<AnimationFrame>) -> Self {
+        let delay = frames.first().map_or(Duration::ZERO, |frame| frame.delay);
+        Self {
+            frames,
+            current: 0,
+            running: true,
+            loading: false,
+            loops: None,
+            deadline: Instant::now() + delay,
+        }
+    }
+
+    /// Starts showing the current frame, the next one is due after its
+    /// delay.
+    pub fn restart(&mut self, now: Instant) {
+        self.current = self.current.min(self.frames.len().saturating_sub(1));
+        let delay = self
+            .frames
+            .get(self.current)
+            .map_or(Duration::ZERO, |frame| frame.delay);
+        self.deadline = now + delay;
+    }
+
+    /// Memory taken by the frames, in bytes.
+    pub fn memory(&self) -> usize {
+        self.frames
+            .iter()
+            .map(|frame| frame.graphic.pixels.len())
+            .sum()
+    }
+
+    #[inline]
+    pub fn current_frame(&self) -> Option<&GraphicData> {
+        self.frames.get(self.current).map(|frame| &frame.graphic)
+    }
+
+    /// When the next frame is due, `None` if the frames do not advance.
+    pub fn deadline(&self) -> Option<Instant> {
+        let is_last = self.current + 1 >= self.frames.len();
+        if !self.running || self.frames.len() < 2 || (is_last && self.loading) {
+            return None;
+        }
+
+        Some(self.deadline)
+    }
+
+    /// Moves to the next frame if it is due, returning it.
+    pub fn advance(&mut self, now: Instant) -> Option<&GraphicData> {
+        if self.deadline()? > now {
+            return None;
+        }
+
+        let mut next = self.current + 1;
+        if next >= self.frames.len() {
+            match self.loops {
+                Some(0) => {
+                    self.running = false;
+                    return None;
+                }
+                Some(loops) => self.loops = Some(loops - 1),
+                None => {}
+            }
+            next = 0;
+        }
+
+        // Frames are dropped instead of played faster when late, like
+        // after the terminal was hidden
+        let delay = self.frames[next].delay;
+        self.current = next;
+        self.deadline += delay;
+        if self.deadline < now {
+            self.deadline = now + delay;
+        }
+
+        self.current_frame()
+    }
+}
+
+/// Memory taken by the graphics of a terminal, shown in the debug overlay.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GraphicsMemoryStats {
+    /// Bytes taken by the graphics in the grid and the kitty images.
+    pub used: usize,
+
+    /// Configured limit, in bytes.
+    pub limit: usize,
+
+    /// Graphics in the grid, including the scrollback.
+    pub graphics: usize,
+
+    /// Images stored with the kitty graphics protocol.
+    pub images: usize,
+
+    /// Graphics and images evicted to stay under the limit.
+    pub evicted: u64,
+}
+
+impl AddAssign for GraphicsMemoryStats {
+    fn add_assign(&mut self, other: Self) {
+        self.used += other.used;
+        self.limit += other.limit;
+        self.graphics += other.graphics;
+        self.images += other.images;
+        self.evicted += other.evicted;
+    }
+}
+
 /// Track changes in the grid to add or to remove graphics.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Graphics {
     /// Last generated identifier.
     pub last_id: u64,
@@ -147,6 +305,49 @@ pub struct Graphics {
 
     /// Current Sixel parser.
     pub sixel_parser: Option<Box<sixel::Parser>>,
+
+    /// Images transmitted with the kitty graphics protocol.
+    pub kitty: KittyImageState,
+
+    /// Memory, in bytes, of each graphic referenced by the grid. The ids
+    /// grow with each graphic, so the oldest come first.
+    pub textures: BTreeMap<GraphicId, usize>,
+
+    /// Animated graphics in the grid, by the id of their texture.
+    pub animations: BTreeMap<GraphicId, Animation>,
+
+    /// Frames to show in place of the animated graphics.
+    pub frames: Vec<GraphicData>,
+
+    /// Sum of the memory in `textures`.
+    texture_memory: usize,
+
+    /// Memory limit for the graphics and the kitty images, in bytes.
+    pub max_memory: usize,
+
+    /// Graphics and images evicted to stay under `max_memory`.
+    pub evicted: u64,
+}
+
+impl Default for Graphics {
+    fn default() -> Self {
+        Self {
+            last_id: 0,
+            pending: Vec::new(),
+            texture_operations: Arc::default(),
+            sixel_shared_palette: None,
+            cell_height: 0.,
+            cell_width: 0.,
+            sixel_parser: None,
+            kitty: KittyImageState::default(),
+            textures: BTreeMap::new(),
+            animations: BTreeMap::new(),
+            frames: Vec::new(),
+            texture_memory: 0,
+            max_memory: crate::config::graphics::Graphics::default().max_memory_bytes(),
+            evicted: 0,
+        }
+    }
 }
 
 impl Graphics {
@@ -177,7 +378,10 @@ impl Graphics {
             }
         };
 
-        if texture_operations.is_empty() && self.pending.is_empty() {
+        if texture_operations.is_empty()
+            && self.pending.is_empty()
+            && self.frames.is_empty()
+        {
             return None;
         }
 
@@ -186,7 +390,10 @@ impl Graphics {
 
         for operation in texture_operations {
             match operation {
-                TextureOperation::Remove(id) => remove_queue.push(id),
+                TextureOperation::Remove(id) => {
+                    self.forget_texture(id);
+                    remove_queue.push(id);
+                }
                 TextureOperation::ClearSubregion(cs) => clear_subregions.push(cs),
             }
         }
@@ -195,9 +402,88 @@ impl Graphics {
             pending: mem::take(&mut self.pending),
             remove_queue,
             clear_subregions,
+            frames: mem::take(&mut self.frames),
         })
     }
 
+    /// Records the memory of a graphic added to the grid, replacing the
+    /// previous record of the same graphic.
+    pub fn track_texture(&mut self, id: GraphicId, memory: usize) {
+        self.texture_memory += memory;
+        if let Some(previous) = self.textures.insert(id, memory) {
+            self.texture_memory -= previous;
+        }
+    }
+
+    fn forget_texture(&mut self, id: GraphicId) {
+        if let Some(memory) = self.textures.remove(&id) {
+            self.texture_memory -= memory;
+        }
+        self.animations.remove(&id);
+        self.kitty.forget_texture(id);
+    }
+
+    /// Animates the graphic, its current frame is shown now.
+    pub fn animate(&mut self, id: GraphicId, animation: Animation) {
+        if let Some(frame) = animation.current_frame() {
+            self.frames.push(GraphicData {
+                id,
+                ..frame.clone()
+            });
+        }
+        self.track_texture(id, animation.memory());
+        self.animations.insert(id, animation);
+    }
+
+    /// Queues the frames of the animations that are due.
+    pub fn advance_animations(&mut self, now: Instant) {
+        for (id, animation) in self.animations.iter_mut() {
+            if let Some(frame) = animation.advance(now) {
+                self.frames.push(GraphicData {
+                    id: *id,
+                    ..frame.clone()
+                });
+            }
+        }
+    }
+
+    /// When the next frame of any animation is due.
+    pub fn next_frame(&self) -> Option<Instant> {
+        self.animations
+            .values()
+            .filter_map(Animation::deadline)
+            .min()
+    }
+
+    /// Memory taken by the graphics in the grid and the kitty images. The
+    /// graphics dropped from the grid since the last frame are not counted.
+    pub fn used_memory(&mut self) -> usize {
+        let removed: Vec<GraphicId> = self
+            .texture_operations
+            .lock()
+            .iter()
+            .filter_map(|operation| match operation {
+                TextureOperation::Remove(id) => Some(*id),
+                TextureOperation::ClearSubregion(_) => None,
+            })
+            .collect();
+        for id in removed {
+            self.forget_texture(id);
+        }
+
+        self.texture_memory + self.kitty.used_memory()
+    }
+
+    pub fn memory_stats(&mut self) -> GraphicsMemoryStats {
+        GraphicsMemoryStats {
+            used: self.used_memory(),
+            limit: self.max_memory,
+            graphics: self.textures.len(),
+            images: self.kitty.image_count(),
+            evicted: self.evicted,
+        }
+    }
+
     /// Update cell dimensions.
     pub fn resize<S: Dimensions>(&mut self, size: &S) {
         self.cell_height = size.square_height();
@@ -205,6 +491,64 @@ impl Graphics {
     }
 }
 
+#[test]
+fn animation_frames() {
+    use sugarloaf::ColorType;
+    let frame = |value: u8, delay: u64| AnimationFrame {
+        graphic: GraphicData {
+            id: GraphicId(0),
+            width: 1,
+            height: 1,
+            color_type: ColorType::Rgba,
+            pixels: vec![value; 4],
+            is_opaque: false,
+            resize: None,
+        },
+        delay: Duration::from_millis(delay),
+    };
+
+    let mut animation = Animation::new(vec![frame(1, 10), frame(2, 20)]);
+    animation.loops = Some(1);
+    let start = Instant::now();
+    animation.restart(start);
+    assert_eq!(animation.memory(), 8);
+    assert_eq!(
+        animation.deadline(),
+        Some(start + Duration::from_millis(10))
+    );
+
+    // Nothing is due before the delay of the frame
+    assert!(animation.advance(start).is_none());
+
+    let at = start + Duration::from_millis(10);
+    assert_eq!(animation.advance(at).unwrap().pixels[0], 2);
+    assert_eq!(
+        animation.deadline(),
+        Some(start + Duration::from_millis(30))
+    );
+
+    // Starts over once, then stops at the last frame
+    let at = start + Duration::from_millis(30);
+    assert_eq!(animation.advance(at).unwrap().pixels[0], 1);
+    let at = start + Duration::from_millis(40);
+    assert_eq!(animation.advance(at).unwrap().pixels[0], 2);
+    let at = start + Duration::from_millis(60);
+    assert!(animation.advance(at).is_none());
+    assert_eq!(animation.deadline(), None);
+    assert_eq!(animation.current, 1);
+
+    // Late frames are dropped
+    let mut animation = Animation::new(vec![frame(1, 10), frame(2, 20)]);
+    animation.restart(start);
+    let at = start + Duration::from_secs(1);
+    assert_eq!(animation.advance(at).unwrap().pixels[0], 2);
+    assert_eq!(animation.deadline(), Some(at + Duration::from_millis(20)));
+
+    // Loading animations wait at the last frame
+    animation.loading = true;
+    assert_eq!(animation.deadline(), None);
+}
+
 #[test]
 fn check_opaque_region() {
     use sugarloaf::ColorType;
@@ -3,20 +3,37 @@
 // This module implements support for the [iTerm2 images protocol](https://iterm2.com/documentation-images.html).
 //
 // iTerm2 uses the OSC 1337 for a many non-standard commands, but we only support
-// adding inline graphics.
+// adding inline graphics and setting user variables (`SetUserVar`).
 //
 // This implementation also supports `width` and `height` parameters to resize the image.
+//
+// Animated GIF and PNG images are played, looping forever like iTerm2 does.
 
+use crate::ansi::graphics::AnimationFrame;
 use sugarloaf::{GraphicData, GraphicId, ResizeCommand, ResizeParameter};
 
+use image_rs::codecs::gif::GifDecoder;
+use image_rs::codecs::png::PngDecoder;
+use image_rs::{AnimationDecoder, DynamicImage, ImageFormat, ImageResult};
 use rustc_hash::FxHashMap;
+use std::io::Cursor;
 use std::str;
+use std::time::Duration;
 
 use base64::engine::general_purpose::STANDARD as Base64;
 use base64::Engine;
 
-/// Parse the OSC 1337 parameters to add a graphic to the grid.
-pub fn parse(params: &[&[u8]]) -> Option<GraphicData> {
+/// Memory limit of the decoded frames of an animation, in bytes. The
+/// frames past it are dropped.
+const MAX_ANIMATION_MEMORY: usize = 0x1000_0000;
+
+/// Frames with a shorter delay use the default one, as browsers do.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// Parse the OSC 1337 parameters to add a graphic to the grid. Animated
+/// images give all of their frames, the others a single one.
+pub fn parse(params: &[&[u8]]) -> Option<Vec<AnimationFrame>> {
     let (params, contents) = param_values(params)?;
 
     if params.get("inline") != Some(&"1") {
@@ -31,17 +48,103 @@ pub fn parse(params: &[&[u8]]) -> Option<GraphicData> {
         }
     };
 
-    let image = match image_rs::load_from_memory(&buffer) {
-        Ok(image) => image,
+    let mut frames = match decode_frames(&buffer) {
+        Ok(frames) if !frames.is_empty() => frames,
+        Ok(_) => {
+            tracing::warn!("Can't load image: no frames");
+            return None;
+        }
         Err(err) => {
             tracing::warn!("Can't load image: {}", err);
             return None;
         }
     };
 
-    let mut graphics = GraphicData::from_dynamic_image(GraphicId(0), image);
-    graphics.resize = resize_param(&params);
-    Some(graphics)
+    let resize = resize_param(&params);
+    for frame in &mut frames {
+        frame.graphic.resize = resize;
+    }
+    Some(frames)
+}
+
+/// Decode the frames of animated GIF and PNG images, or the image itself.
+fn decode_frames(buffer: &[u8]) -> ImageResult<Vec<AnimationFrame>> {
+    let still = |image: DynamicImage| {
+        vec![AnimationFrame {
+            graphic: GraphicData::from_dynamic_image(GraphicId(0), image),
+            delay: Duration::ZERO,
+        }]
+    };
+
+    let decoded = match image_rs::guess_format(buffer) {
+        Ok(ImageFormat::Gif) => GifDecoder::new(Cursor::new(buffer))?.into_frames(),
+        Ok(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(Cursor::new(buffer))?;
+            if !decoder.is_apng()? {
+                return Ok(still(image_rs::load_from_memory(buffer)?));
+            }
+            decoder.apng()?.into_frames()
+        }
+        _ => return Ok(still(image_rs::load_from_memory(buffer)?)),
+    };
+
+    let mut frames = Vec::new();
+    let mut memory = 0;
+    for frame in decoded {
+        let frame = frame?;
+        let delay = match Duration::from(frame.delay()) {
+            delay if delay < MIN_FRAME_DELAY => DEFAULT_FRAME_DELAY,
+            delay => delay,
+        };
+
+        let image = frame.into_buffer();
+        memory += image.len();
+        if memory > MAX_ANIMATION_MEMORY && !frames.is_empty() {
+            tracing::warn!("Animation is too large, dropping the last frames");
+            break;
+        }
+
+        frames.push(AnimationFrame {
+            graphic: GraphicData::from_dynamic_image(
+                GraphicId(0),
+                DynamicImage::ImageRgba8(image),
+            ),
+            delay,
+        });
+    }
+
+    Ok(frames)
+}
+
+/// Parse the OSC 1337 `SetUserVar` parameters.
+///
+/// ```notrust
+/// ESC ] 1337 ; SetUserVar = name = base-64 encoded value ^G
+/// ```
+///
+/// Shells commonly use it to communicate state (e.g: git branch or k8s context).
+pub fn parse_set_user_var(params: &[&[u8]]) -> Option<(String, String)> {
+    let param = params.get(1)?.strip_prefix(b"SetUserVar=")?;
+    let separator = param.iter().position(|&b| b == b'=')?;
+    let (name, value) = param.split_at(separator);
+
+    let name = str::from_utf8(name).ok()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let value = match Base64.decode(&value[1..]) {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!("Can't decode base64 user var {}: {}", name, err);
+            return None;
+        }
+    };
+
+    Some((
+        name.to_owned(),
+        String::from_utf8_lossy(&value).into_owned(),
+    ))
 }
 
 /// Extract parameter values.
@@ -171,6 +274,65 @@ fn parse_osc1337_single_parameter() {
     assert_eq!(contents, b"AAAA".as_ref())
 }
 
+#[test]
+fn parse_osc1337_set_user_var() {
+    let params = [b"1337".as_ref(), b"SetUserVar=git_branch=bWFpbg==".as_ref()];
+    assert_eq!(
+        parse_set_user_var(&params),
+        Some((String::from("git_branch"), String::from("main")))
+    );
+
+    // Empty values are valid and used to unset a variable.
+    let params = [b"1337".as_ref(), b"SetUserVar=git_branch=".as_ref()];
+    assert_eq!(
+        parse_set_user_var(&params),
+        Some((String::from("git_branch"), String::new()))
+    );
+
+    let params = [b"1337".as_ref(), b"SetUserVar==bWFpbg==".as_ref()];
+    assert_eq!(parse_set_user_var(&params), None);
+
+    let params = [b"1337".as_ref(), b"File=inline=1:AAAA".as_ref()];
+    assert_eq!(parse_set_user_var(&params), None);
+}
+
+#[test]
+fn parse_osc1337_animated_gif() {
+    use base64::Engine;
+    use image_rs::codecs::gif::GifEncoder;
+    use image_rs::{Delay, Frame, RgbaImage};
+
+    let mut gif = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut gif);
+        for (value, delay) in [(0, 50), (255, 5)] {
+            let image = RgbaImage::from_pixel(2, 2, image_rs::Rgba([value, 0, 0, 255]));
+            let delay = Delay::from_numer_denom_ms(delay, 1);
+            encoder
+                .encode_frame(Frame::from_parts(image, 0, 0, delay))
+                .unwrap();
+        }
+    }
+
+    let contents = format!("width=4:{}", Base64.encode(&gif));
+    let params = [
+        b"1337".as_ref(),
+        b"File=name=a.gif".as_ref(),
+        b"inline=1".as_ref(),
+        contents.as_bytes(),
+    ];
+    let frames = parse(&params).unwrap();
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].delay, Duration::from_millis(50));
+    // Too short delays use the default one
+    assert_eq!(frames[1].delay, DEFAULT_FRAME_DELAY);
+    assert_eq!(frames[1].graphic.pixels[0], 255);
+    assert!(frames
+        .iter()
+        .all(|frame| frame.graphic.resize.unwrap().width == ResizeParameter::Cells(4)));
+}
+
 #[test]
 fn resize_params() {
     use ResizeParameter::{Auto, Cells, Pixels, WindowPercent};
@@ -1,21 +1,342 @@
-// Kitty graphics protocol uses OSC _G for encoding graphics data.
+// Kitty graphics protocol uses APC _G for encoding graphics data.
 // https://sw.kovidgoyal.net/kitty/graphics-protocol/
 // Much credit to WezTerm for the original implementation.
 // https://github.com/wezterm/wezterm/blob/main/term/src/terminalstate/kitty.rs
 
-use std::collections::{BTreeMap, HashMap};
+use crate::ansi::graphics::{Animation, AnimationFrame, TextureRef};
+use crate::config::colors::AnsiColor;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Display, Error as FmtError, Formatter};
 use std::io::{Read, Seek};
-use sugarloaf::{GraphicData, GraphicId, ResizeCommand, ResizeParameter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sugarloaf::{
+    ColorType, GraphicData, GraphicId, ResizeCommand, ResizeParameter,
+    MAX_GRAPHIC_DIMENSIONS,
+};
 
 use rustc_hash::FxHashMap;
-use std::str;
 
 use base64::engine;
 use base64::Engine;
 
+/// Maximum length of a transmission, including all of its chunks.
+pub const MAX_TRANSMISSION_LENGTH: usize = 0x800_0000;
+
+/// Character of the cells displaying a virtual placement, the image
+/// and the cell of the image it shows are encoded in its colors and
+/// diacritics.
+pub const PLACEHOLDER: char = '\u{10EEEE}';
+
+/// Diacritics encoding the row, column and most significant byte of the
+/// image id of a placeholder, the value is the index in the table.
+/// https://sw.kovidgoyal.net/kitty/graphics-protocol/#unicode-placeholders
+const ROW_COLUMN_DIACRITICS: [char; 297] = [
+    '\u{0305}',
+    '\u{030D}',
+    '\u{030E}',
+    '\u{0310}',
+    '\u{0312}',
+    '\u{033D}',
+    '\u{033E}',
+    '\u{033F}',
+    '\u{0346}',
+    '\u{034A}',
+    '\u{034B}',
+    '\u{034C}',
+    '\u{0350}',
+    '\u{0351}',
+    '\u{0352}',
+    '\u{0357}',
+    '\u{035B}',
+    '\u{0363}',
+    '\u{0364}',
+    '\u{0365}',
+    '\u{0366}',
+    '\u{0367}',
+    '\u{0368}',
+    '\u{0369}',
+    '\u{036A}',
+    '\u{036B}',
+    '\u{036C}',
+    '\u{036D}',
+    '\u{036E}',
+    '\u{036F}',
+    '\u{0483}',
+    '\u{0484}',
+    '\u{0485}',
+    '\u{0486}',
+    '\u{0487}',
+    '\u{0592}',
+    '\u{0593}',
+    '\u{0594}',
+    '\u{0595}',
+    '\u{0597}',
+    '\u{0598}',
+    '\u{0599}',
+    '\u{059C}',
+    '\u{059D}',
+    '\u{059E}',
+    '\u{059F}',
+    '\u{05A0}',
+    '\u{05A1}',
+    '\u{05A8}',
+    '\u{05A9}',
+    '\u{05AB}',
+    '\u{05AC}',
+    '\u{05AF}',
+    '\u{05C4}',
+    '\u{0610}',
+    '\u{0611}',
+    '\u{0612}',
+    '\u{0613}',
+    '\u{0614}',
+    '\u{0615}',
+    '\u{0616}',
+    '\u{0617}',
+    '\u{0657}',
+    '\u{0658}',
+    '\u{0659}',
+    '\u{065A}',
+    '\u{065B}',
+    '\u{065D}',
+    '\u{065E}',
+    '\u{06D6}',
+    '\u{06D7}',
+    '\u{06D8}',
+    '\u{06D9}',
+    '\u{06DA}',
+    '\u{06DB}',
+    '\u{06DC}',
+    '\u{06DF}',
+    '\u{06E0}',
+    '\u{06E1}',
+    '\u{06E2}',
+    '\u{06E4}',
+    '\u{06E7}',
+    '\u{06E8}',
+    '\u{06EB}',
+    '\u{06EC}',
+    '\u{0730}',
+    '\u{0732}',
+    '\u{0733}',
+    '\u{0735}',
+    '\u{0736}',
+    '\u{073A}',
+    '\u{073D}',
+    '\u{073F}',
+    '\u{0740}',
+    '\u{0741}',
+    '\u{0743}',
+    '\u{0745}',
+    '\u{0747}',
+    '\u{0749}',
+    '\u{074A}',
+    '\u{07EB}',
+    '\u{07EC}',
+    '\u{07ED}',
+    '\u{07EE}',
+    '\u{07EF}',
+    '\u{07F0}',
+    '\u{07F1}',
+    '\u{07F3}',
+    '\u{0816}',
+    '\u{0817}',
+    '\u{0818}',
+    '\u{0819}',
+    '\u{081B}',
+    '\u{081C}',
+    '\u{081D}',
+    '\u{081E}',
+    '\u{081F}',
+    '\u{0820}',
+    '\u{0821}',
+    '\u{0822}',
+    '\u{0823}',
+    '\u{0825}',
+    '\u{0826}',
+    '\u{0827}',
+    '\u{0829}',
+    '\u{082A}',
+    '\u{082B}',
+    '\u{082C}',
+    '\u{082D}',
+    '\u{0951}',
+    '\u{0953}',
+    '\u{0954}',
+    '\u{0F82}',
+    '\u{0F83}',
+    '\u{0F86}',
+    '\u{0F87}',
+    '\u{135D}',
+    '\u{135E}',
+    '\u{135F}',
+    '\u{17DD}',
+    '\u{193A}',
+    '\u{1A17}',
+    '\u{1A75}',
+    '\u{1A76}',
+    '\u{1A77}',
+    '\u{1A78}',
+    '\u{1A79}',
+    '\u{1A7A}',
+    '\u{1A7B}',
+    '\u{1A7C}',
+    '\u{1B6B}',
+    '\u{1B6D}',
+    '\u{1B6E}',
+    '\u{1B6F}',
+    '\u{1B70}',
+    '\u{1B71}',
+    '\u{1B72}',
+    '\u{1B73}',
+    '\u{1CD0}',
+    '\u{1CD1}',
+    '\u{1CD2}',
+    '\u{1CDA}',
+    '\u{1CDB}',
+    '\u{1CE0}',
+    '\u{1DC0}',
+    '\u{1DC1}',
+    '\u{1DC3}',
+    '\u{1DC4}',
+    '\u{1DC5}',
+    '\u{1DC6}',
+    '\u{1DC7}',
+    '\u{1DC8}',
+    '\u{1DC9}',
+    '\u{1DCB}',
+    '\u{1DCC}',
+    '\u{1DD1}',
+    '\u{1DD2}',
+    '\u{1DD3}',
+    '\u{1DD4}',
+    '\u{1DD5}',
+    '\u{1DD6}',
+    '\u{1DD7}',
+    '\u{1DD8}',
+    '\u{1DD9}',
+    '\u{1DDA}',
+    '\u{1DDB}',
+    '\u{1DDC}',
+    '\u{1DDD}',
+    '\u{1DDE}',
+    '\u{1DDF}',
+    '\u{1DE0}',
+    '\u{1DE1}',
+    '\u{1DE2}',
+    '\u{1DE3}',
+    '\u{1DE4}',
+    '\u{1DE5}',
+    '\u{1DE6}',
+    '\u{1DFE}',
+    '\u{20D0}',
+    '\u{20D1}',
+    '\u{20D4}',
+    '\u{20D5}',
+    '\u{20D6}',
+    '\u{20D7}',
+    '\u{20DB}',
+    '\u{20DC}',
+    '\u{20E1}',
+    '\u{20E7}',
+    '\u{20E9}',
+    '\u{20F0}',
+    '\u{2CEF}',
+    '\u{2CF0}',
+    '\u{2CF1}',
+    '\u{2DE0}',
+    '\u{2DE1}',
+    '\u{2DE2}',
+    '\u{2DE3}',
+    '\u{2DE4}',
+    '\u{2DE5}',
+    '\u{2DE6}',
+    '\u{2DE7}',
+    '\u{2DE8}',
+    '\u{2DE9}',
+    '\u{2DEA}',
+    '\u{2DEB}',
+    '\u{2DEC}',
+    '\u{2DED}',
+    '\u{2DEE}',
+    '\u{2DEF}',
+    '\u{2DF0}',
+    '\u{2DF1}',
+    '\u{2DF2}',
+    '\u{2DF3}',
+    '\u{2DF4}',
+    '\u{2DF5}',
+    '\u{2DF6}',
+    '\u{2DF7}',
+    '\u{2DF8}',
+    '\u{2DF9}',
+    '\u{2DFA}',
+    '\u{2DFB}',
+    '\u{2DFC}',
+    '\u{2DFD}',
+    '\u{2DFE}',
+    '\u{2DFF}',
+    '\u{A66F}',
+    '\u{A67C}',
+    '\u{A67D}',
+    '\u{A6F0}',
+    '\u{A6F1}',
+    '\u{A8E0}',
+    '\u{A8E1}',
+    '\u{A8E2}',
+    '\u{A8E3}',
+    '\u{A8E4}',
+    '\u{A8E5}',
+    '\u{A8E6}',
+    '\u{A8E7}',
+    '\u{A8E8}',
+    '\u{A8E9}',
+    '\u{A8EA}',
+    '\u{A8EB}',
+    '\u{A8EC}',
+    '\u{A8ED}',
+    '\u{A8EE}',
+    '\u{A8EF}',
+    '\u{A8F0}',
+    '\u{A8F1}',
+    '\u{AAB0}',
+    '\u{AAB2}',
+    '\u{AAB3}',
+    '\u{AAB7}',
+    '\u{AAB8}',
+    '\u{AABE}',
+    '\u{AABF}',
+    '\u{AAC1}',
+    '\u{FE20}',
+    '\u{FE21}',
+    '\u{FE22}',
+    '\u{FE23}',
+    '\u{FE24}',
+    '\u{FE25}',
+    '\u{FE26}',
+    '\u{10A0F}',
+    '\u{10A38}',
+    '\u{1D185}',
+    '\u{1D186}',
+    '\u{1D187}',
+    '\u{1D188}',
+    '\u{1D189}',
+    '\u{1D1AA}',
+    '\u{1D1AB}',
+    '\u{1D1AC}',
+    '\u{1D1AD}',
+    '\u{1D242}',
+    '\u{1D243}',
+    '\u{1D244}',
+];
+
+/// Image ids given to images transmitted only with a number, or with no
+/// id at all, start here to stay away from the ones picked by programs.
+const ASSIGNED_IMAGE_ID_START: u32 = 1 << 31;
+
 fn get<'a>(keys: &BTreeMap<&str, &'a str>, k: &str) -> Option<&'a str> {
-    keys.get(k).map(|&s| s)
+    keys.get(k).copied()
 }
 
 fn geti<T: std::str::FromStr>(keys: &BTreeMap<&str, &str>, k: &str) -> Option<T> {
@@ -32,6 +353,17 @@ fn set<T: std::string::ToString>(
     }
 }
 
+/// Standard base64, with the padding being optional like in kitty.
+const BASE64: engine::GeneralPurpose = engine::GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    engine::general_purpose::PAD
+        .with_decode_padding_mode(engine::DecodePaddingMode::Indifferent),
+);
+
+fn base64_decode(data: &[u8]) -> Result<Vec<u8>, base64::DecodeError> {
+    BASE64.decode(data)
+}
+
 #[derive(Clone, PartialEq, Eq)]
 pub enum KittyImageData {
     /// The data bytes, baes64-encoded fragments.
@@ -128,17 +460,17 @@ impl KittyImageData {
         match t {
             "d" => Some(Self::Direct(String::from_utf8(payload.to_vec()).ok()?)),
             "f" => Some(Self::File {
-                path: String::from_utf8(base64_decode(payload.to_vec()).ok()?).ok()?,
+                path: String::from_utf8(base64_decode(payload).ok()?).ok()?,
                 data_size: geti(keys, "S"),
                 data_offset: geti(keys, "O"),
             }),
             "t" => Some(Self::TemporaryFile {
-                path: String::from_utf8(base64_decode(payload.to_vec()).ok()?).ok()?,
+                path: String::from_utf8(base64_decode(payload).ok()?).ok()?,
                 data_size: geti(keys, "S"),
                 data_offset: geti(keys, "O"),
             }),
             "s" => Some(Self::SharedMem {
-                name: String::from_utf8(base64_decode(payload.to_vec()).ok()?).ok()?,
+                name: String::from_utf8(base64_decode(payload).ok()?).ok()?,
                 data_size: geti(keys, "S"),
                 data_offset: geti(keys, "O"),
             }),
@@ -160,9 +492,9 @@ impl KittyImageData {
                 data_size,
             } => {
                 keys.insert("t", "f".to_string());
-                keys.insert("payload", engine::general_purpose::STANDARD.encode(&path));
+                keys.insert("payload", engine::general_purpose::STANDARD.encode(path));
                 set(keys, "S", data_size);
-                set(keys, "S", data_offset);
+                set(keys, "O", data_offset);
             }
             Self::TemporaryFile {
                 path,
@@ -170,9 +502,9 @@ impl KittyImageData {
                 data_size,
             } => {
                 keys.insert("t", "t".to_string());
-                keys.insert("payload", engine::general_purpose::STANDARD.encode(&path));
+                keys.insert("payload", engine::general_purpose::STANDARD.encode(path));
                 set(keys, "S", data_size);
-                set(keys, "S", data_offset);
+                set(keys, "O", data_offset);
             }
             Self::SharedMem {
                 name,
@@ -180,9 +512,9 @@ impl KittyImageData {
                 data_size,
             } => {
                 keys.insert("t", "s".to_string());
-                keys.insert("payload", engine::general_purpose::STANDARD.encode(&name));
+                keys.insert("payload", engine::general_purpose::STANDARD.encode(name));
                 set(keys, "S", data_size);
-                set(keys, "S", data_offset);
+                set(keys, "O", data_offset);
             }
         }
     }
@@ -197,32 +529,24 @@ impl KittyImageData {
             data_offset: Option<u32>,
             data_size: Option<u32>,
         ) -> std::io::Result<Vec<u8>> {
-            let mut f = std::fs::File::open(path)?;
-            if let Some(offset) = data_offset {
-                f.seek(std::io::SeekFrom::Start(offset.into()))?;
-            }
-            if let Some(len) = data_size {
-                let mut res = vec![0u8; len as usize];
-                f.read_exact(&mut res)?;
-                Ok(res)
-            } else {
-                let mut res = vec![];
-                f.read_to_end(&mut res)?;
-                Ok(res)
+            if is_forbidden_path(path) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("{path} is not a regular file"),
+                ));
             }
+
+            let mut f = std::fs::File::open(path)?;
+            read_transmission(&mut f, data_offset, data_size)
         }
 
         match self {
-            Self::Direct(data) => {
-                engine::general_purpose::STANDARD
-                    .encode(data)
-                    .or_else(|err| {
-                        Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            format!("base64 decode: {err:#}"),
-                        ))
-                    })
-            }
+            Self::Direct(data) => base64_decode(data.as_bytes()).map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("base64 decode: {err:#}"),
+                )
+            }),
             Self::DirectBin(bin) => Ok(bin),
             Self::File {
                 path,
@@ -235,40 +559,23 @@ impl KittyImageData {
                 data_size,
             } => {
                 let data = read_from_file(&path, data_offset, data_size)?;
-                // need to sanity check that the path looks like a reasonable
-                // temporary directory path before blindly unlinking it here.
-
-                fn looks_like_temp_path(p: &str) -> bool {
-                    if p.starts_with("/tmp/")
-                        || p.starts_with("/var/tmp/")
-                        || p.starts_with("/dev/shm/")
-                    {
-                        return true;
-                    }
 
-                    if let Ok(t) = std::env::var("TMPDIR") {
-                        if p.starts_with(&t) {
-                            return true;
+                // Programs can send any path, so it's only unlinked when it
+                // resolves to a file created for the protocol in a temporary
+                // directory, like kitty does.
+                match temporary_file_path(&path) {
+                    Some(path) => {
+                        if let Err(err) = std::fs::remove_file(&path) {
+                            tracing::warn!(
+                                "Unable to remove kitty image protocol temporary file {}: {err:#}",
+                                path.display()
+                            );
                         }
                     }
-
-                    false
-                }
-
-                if looks_like_temp_path(&path) {
-                    if let Err(err) = std::fs::remove_file(&path) {
-                        eprintln!(
-                            "Unable to remove kitty image protocol temporary file {}: {:#}",
-                            path,
-                            err
-                        );
-                    }
-                } else {
-                    eprintln!(
-                        "kitty image protocol temporary file {} isn't in a known \
-                                temporary directory; won't try to remove it",
-                        path
-                    );
+                    None => tracing::warn!(
+                        "kitty image protocol temporary file {path} isn't a \
+                         tty-graphics-protocol file in a temporary directory; won't try to remove it"
+                    ),
                 }
 
                 Ok(data)
@@ -282,49 +589,121 @@ impl KittyImageData {
     }
 }
 
+/// Checks the `O=` and `S=` keys of a file or shared memory transmission,
+/// they come from the program and can't go over `MAX_TRANSMISSION_LENGTH`.
+fn check_transmission_range(
+    data_offset: Option<u32>,
+    data_size: Option<u32>,
+) -> std::io::Result<()> {
+    for (key, value) in [("O", data_offset), ("S", data_size)] {
+        if value.is_some_and(|value| value as usize > MAX_TRANSMISSION_LENGTH) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{key}={} exceeds {MAX_TRANSMISSION_LENGTH}", value.unwrap()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Reads `data_size` bytes from `data_offset`, or up to the end, without
+/// ever reading more than `MAX_TRANSMISSION_LENGTH`.
+fn read_transmission(
+    f: &mut (impl Read + Seek),
+    data_offset: Option<u32>,
+    data_size: Option<u32>,
+) -> std::io::Result<Vec<u8>> {
+    check_transmission_range(data_offset, data_size)?;
+    if let Some(offset) = data_offset {
+        f.seek(std::io::SeekFrom::Start(offset.into()))?;
+    }
+
+    // One byte more tells an oversized file apart
+    let limit = data_size.map_or(MAX_TRANSMISSION_LENGTH + 1, |len| len as usize);
+    let mut res = vec![];
+    f.take(limit as u64).read_to_end(&mut res)?;
+    match data_size {
+        Some(len) if res.len() < len as usize => {
+            Err(std::io::ErrorKind::UnexpectedEof.into())
+        }
+        None if res.len() > MAX_TRANSMISSION_LENGTH => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("data exceeds {MAX_TRANSMISSION_LENGTH}"),
+        )),
+        _ => Ok(res),
+    }
+}
+
+/// Canonical path of a temporary file that may be removed after it's read,
+/// it has to be inside a temporary directory and have
+/// `tty-graphics-protocol` in its name.
+fn temporary_file_path(path: &str) -> Option<std::path::PathBuf> {
+    let path = std::fs::canonicalize(path).ok()?;
+    let name = path.file_name()?.to_str()?;
+    if !name.contains("tty-graphics-protocol") {
+        return None;
+    }
+
+    let temp_dirs = [
+        std::path::PathBuf::from("/tmp"),
+        std::path::PathBuf::from("/var/tmp"),
+        std::path::PathBuf::from("/dev/shm"),
+        std::env::temp_dir(),
+    ];
+    temp_dirs
+        .iter()
+        .filter_map(|dir| std::fs::canonicalize(dir).ok())
+        .any(|dir| path.starts_with(dir))
+        .then_some(path)
+}
+
+/// Files the protocol may not read, only regular files are allowed and
+/// device or kernel files could block or leak information.
+fn is_forbidden_path(path: &str) -> bool {
+    let path = std::path::Path::new(path);
+    let is_regular_file = std::fs::metadata(path)
+        .map(|metadata| metadata.is_file())
+        .unwrap_or(false);
+
+    !is_regular_file
+        || ["/proc", "/sys", "/dev"]
+            .iter()
+            .any(|dir| path.starts_with(dir) && !path.starts_with("/dev/shm"))
+}
+
 #[cfg(all(unix, not(target_os = "android")))]
 fn read_shared_memory_data(
     name: &str,
     data_offset: Option<u32>,
     data_size: Option<u32>,
-) -> std::result::Result<std::vec::Vec<u8>, std::io::Error> {
-    use nix::sys::mman::{shm_open, shm_unlink};
+) -> std::io::Result<Vec<u8>> {
+    use std::ffi::CString;
     use std::fs::File;
+    use std::os::fd::FromRawFd;
+
+    let c_name = CString::new(name)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
 
-    let fd = shm_open(
-        name,
-        nix::fcntl::OFlag::O_RDONLY,
-        nix::sys::stat::Mode::empty(),
-    )
-    .map_err(|_| {
+    let fd = unsafe { libc::shm_open(c_name.as_ptr(), libc::O_RDONLY, 0) };
+    if fd < 0 {
         let err = std::io::Error::last_os_error();
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("shm_open {} failed: {:#}", name, err),
-        )
-    })?;
-    let mut f = File::from(fd);
-    if let Some(offset) = data_offset {
-        f.seek(std::io::SeekFrom::Start(offset.into()))?;
+        return Err(std::io::Error::other(format!(
+            "shm_open {name} failed: {err:#}"
+        )));
     }
-    let data = if let Some(len) = data_size {
-        let mut res = vec![0u8; len as usize];
-        f.read_exact(&mut res)?;
-        res
-    } else {
-        let mut res = vec![];
-        f.read_to_end(&mut res)?;
-        res
-    };
 
-    if let Err(err) = shm_unlink(name) {
-        log::warn!(
-            "Unable to unlink kitty image protocol shm file {}: {:#}",
-            name,
-            err
+    // The file takes the ownership of the descriptor and closes it
+    let mut f = unsafe { File::from_raw_fd(fd) };
+    let result = read_transmission(&mut f, data_offset, data_size);
+
+    if unsafe { libc::shm_unlink(c_name.as_ptr()) } != 0 {
+        tracing::warn!(
+            "Unable to unlink kitty image protocol shm file {name}: {:#}",
+            std::io::Error::last_os_error()
         );
     }
-    Ok(data)
+
+    result
 }
 
 #[cfg(all(unix, target_os = "android"))]
@@ -332,18 +711,17 @@ fn read_shared_memory_data(
     _name: &str,
     _data_offset: Option<u32>,
     _data_size: Option<u32>,
-) -> std::result::Result<std::vec::Vec<u8>, std::io::Error> {
+) -> std::io::Result<Vec<u8>> {
     Err(std::io::ErrorKind::Unsupported.into())
 }
 
 #[cfg(windows)]
 mod win {
-    use winapi::um::handleapi::CloseHandle;
-    use winapi::um::memoryapi::{
-        MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, VirtualQuery,
-        FILE_MAP_ALL_ACCESS,
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::Memory::{
+        MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, VirtualQuery, FILE_MAP_READ,
+        MEMORY_BASIC_INFORMATION, MEMORY_MAPPED_VIEW_ADDRESS,
     };
-    use winapi::um::winnt::{HANDLE, MEMORY_BASIC_INFORMATION};
 
     struct HandleWrapper {
         handle: HANDLE,
@@ -351,7 +729,7 @@ mod win {
 
     struct SharedMemObject {
         _handle: HandleWrapper,
-        buf: *mut u8,
+        view: MEMORY_MAPPED_VIEW_ADDRESS,
     }
 
     impl Drop for HandleWrapper {
@@ -365,7 +743,7 @@ mod win {
     impl Drop for SharedMemObject {
         fn drop(&mut self) {
             unsafe {
-                UnmapViewOfFile(self.buf as _);
+                UnmapViewOfFile(self.view);
             }
         }
     }
@@ -383,72 +761,64 @@ mod win {
         name: &str,
         data_offset: Option<u32>,
         data_size: Option<u32>,
-    ) -> std::result::Result<std::vec::Vec<u8>, std::io::Error> {
-        let wide_name = wide_string(&name);
+    ) -> std::io::Result<Vec<u8>> {
+        super::check_transmission_range(data_offset, data_size)?;
+        let wide_name = wide_string(name);
 
-        let handle =
-            unsafe { OpenFileMappingW(FILE_MAP_ALL_ACCESS, 0, wide_name.as_ptr()) };
+        let handle = unsafe { OpenFileMappingW(FILE_MAP_READ, 0, wide_name.as_ptr()) };
         if handle.is_null() {
             let err = std::io::Error::last_os_error();
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("OpenFileMappingW {} failed: {:#}", name, err),
-            ));
+            return Err(std::io::Error::other(format!(
+                "OpenFileMappingW {name} failed: {err:#}"
+            )));
         }
 
         let handle_wrapper = HandleWrapper { handle };
-        let buf =
-            unsafe { MapViewOfFile(handle_wrapper.handle, FILE_MAP_ALL_ACCESS, 0, 0, 0) };
-        if buf.is_null() {
+        let view =
+            unsafe { MapViewOfFile(handle_wrapper.handle, FILE_MAP_READ, 0, 0, 0) };
+        if view.Value.is_null() {
             let err = std::io::Error::last_os_error();
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("MapViewOfFile failed: {:#}", err),
-            ));
+            return Err(std::io::Error::other(format!(
+                "MapViewOfFile failed: {err:#}"
+            )));
         }
 
         let shm = SharedMemObject {
             _handle: handle_wrapper,
-            buf: buf as *mut u8,
+            view,
         };
+        let buf = shm.view.Value as *const u8;
 
-        let mut memory_info = MEMORY_BASIC_INFORMATION::default();
+        let mut memory_info: MEMORY_BASIC_INFORMATION = unsafe { std::mem::zeroed() };
         let res = unsafe {
             VirtualQuery(
-                shm.buf as _,
-                &mut memory_info as *mut MEMORY_BASIC_INFORMATION,
+                buf as _,
+                &mut memory_info,
                 std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
             )
         };
         if res == 0 {
             let err = std::io::Error::last_os_error();
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!(
-                    "Can't get the size of Shared Memory, VirtualQuery failed: {:#}",
-                    err
-                ),
-            ));
+            return Err(std::io::Error::other(format!(
+                "Can't get the size of Shared Memory, VirtualQuery failed: {err:#}"
+            )));
         }
         let mut size = memory_info.RegionSize;
         let offset = data_offset.unwrap_or(0) as usize;
         if offset >= size {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!(
-                    "offset {} bigger than or equal to shm region size {}",
-                    offset, size
-                ),
-            ));
+            return Err(std::io::Error::other(format!(
+                "offset {offset} bigger than or equal to shm region size {size}"
+            )));
         }
-        size = size.saturating_sub(offset);
+        size = size
+            .saturating_sub(offset)
+            .min(super::MAX_TRANSMISSION_LENGTH);
         if let Some(val) = data_size {
             size = size.min(val as usize);
         }
-        let buf_slice = unsafe { std::slice::from_raw_parts(shm.buf.add(offset), size) };
-        let data = buf_slice.to_vec();
+        let buf_slice = unsafe { std::slice::from_raw_parts(buf.add(offset), size) };
 
-        Ok(data)
+        Ok(buf_slice.to_vec())
     }
 }
 
@@ -472,7 +842,7 @@ impl KittyImageVerbosity {
         }
     }
 
-    fn to_keys(&self, keys: &mut BTreeMap<&'static str, String>) {
+    fn to_keys(self, keys: &mut BTreeMap<&'static str, String>) {
         match self {
             Self::Verbose => {}
             Self::OnlyErrors => {
@@ -631,6 +1001,10 @@ pub struct KittyImagePlacement {
     pub placement_id: Option<u32>,
     /// z=...
     pub z_index: Option<i32>,
+    /// Create a virtual placement, shown by the cells holding Unicode
+    /// placeholders.
+    /// U=1
+    pub unicode_placeholder: bool,
 }
 
 impl KittyImagePlacement {
@@ -651,6 +1025,11 @@ impl KittyImagePlacement {
                 _ => return None,
             },
             z_index: geti(keys, "z"),
+            unicode_placeholder: match get(keys, "U") {
+                None | Some("0") => false,
+                Some("1") => true,
+                _ => return None,
+            },
         })
     }
 
@@ -670,6 +1049,10 @@ impl KittyImagePlacement {
         }
 
         set(keys, "z", &self.z_index);
+
+        if self.unicode_placeholder {
+            keys.insert("U", "1".to_string());
+        }
     }
 }
 
@@ -829,7 +1212,7 @@ impl KittyImageDelete {
                 keys.insert("y", y.to_string());
             }
             Self::DeleteAtZ { x, y, z, delete } => {
-                keys.insert("d", d('p', delete));
+                keys.insert("d", d('q', delete));
                 keys.insert("x", x.to_string());
                 keys.insert("y", y.to_string());
                 keys.insert("z", z.to_string());
@@ -970,9 +1353,10 @@ pub struct KittyImageFrame {
     pub frame_number: Option<u32>,
 
     /// Gap in milliseconds of this frame from the next one.
-    /// Zero or omitted values are interpreted as 40ms.
+    /// Zero or omitted values are interpreted as 40ms, negative values
+    /// make a gapless frame, which is not shown.
     /// z=...
-    pub duration_ms: Option<u32>,
+    pub duration_ms: Option<i32>,
 
     /// Composition mode.
     /// Default is AlphaBlending
@@ -998,7 +1382,7 @@ impl KittyImageFrame {
                 None | Some(0) => None,
                 n => n,
             },
-            duration_ms: match geti(keys, "Z") {
+            duration_ms: match geti(keys, "z") {
                 None | Some(0) => None,
                 n => n,
             },
@@ -1016,7 +1400,7 @@ impl KittyImageFrame {
         set(keys, "y", &self.y);
         set(keys, "c", &self.base_frame);
         set(keys, "r", &self.frame_number);
-        set(keys, "Z", &self.duration_ms);
+        set(keys, "z", &self.duration_ms);
         match &self.composition_mode {
             KittyFrameCompositionMode::AlphaBlending => {}
             KittyFrameCompositionMode::Overwrite => {
@@ -1027,6 +1411,90 @@ impl KittyImageFrame {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KittyAnimationState {
+    /// s=1
+    Stopped,
+    /// Run, waiting at the last frame for more frames.
+    /// s=2
+    Loading,
+    /// Run, starting over after the last frame.
+    /// s=3
+    Running,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KittyImageAnimation {
+    /// i=...
+    pub image_id: Option<u32>,
+    /// I=...
+    pub image_number: Option<u32>,
+
+    /// s=...
+    pub state: Option<KittyAnimationState>,
+
+    /// 1-based number of the frame whose gap is changed.
+    /// r=...
+    pub frame_number: Option<u32>,
+
+    /// Gap in milliseconds of the frame from the next one.
+    /// z=...
+    pub duration_ms: Option<i32>,
+
+    /// 1-based number of the frame to show.
+    /// c=...
+    pub current_frame: Option<u32>,
+
+    /// Number of loops to play, 1 loops forever and larger numbers play
+    /// one loop less. Zero is ignored.
+    /// v=...
+    pub loops: Option<u32>,
+}
+
+impl KittyImageAnimation {
+    fn from_keys(keys: &BTreeMap<&str, &str>) -> Option<Self> {
+        Some(Self {
+            image_id: geti(keys, "i"),
+            image_number: geti(keys, "I"),
+            state: match geti(keys, "s") {
+                None | Some(0) => None,
+                Some(1) => Some(KittyAnimationState::Stopped),
+                Some(2) => Some(KittyAnimationState::Loading),
+                Some(3) => Some(KittyAnimationState::Running),
+                _ => return None,
+            },
+            frame_number: match geti(keys, "r") {
+                None | Some(0) => None,
+                n => n,
+            },
+            duration_ms: geti(keys, "z"),
+            current_frame: match geti(keys, "c") {
+                None | Some(0) => None,
+                n => n,
+            },
+            loops: match geti(keys, "v") {
+                None | Some(0) => None,
+                n => n,
+            },
+        })
+    }
+
+    fn to_keys(&self, keys: &mut BTreeMap<&'static str, String>) {
+        set(keys, "i", &self.image_id);
+        set(keys, "I", &self.image_number);
+        let state = self.state.map(|state| match state {
+            KittyAnimationState::Stopped => 1,
+            KittyAnimationState::Loading => 2,
+            KittyAnimationState::Running => 3,
+        });
+        set(keys, "s", &state);
+        set(keys, "r", &self.frame_number);
+        set(keys, "z", &self.duration_ms);
+        set(keys, "c", &self.current_frame);
+        set(keys, "v", &self.loops);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KittyImage {
     /// a='t'
@@ -1065,6 +1533,11 @@ pub enum KittyImage {
         frame: KittyImageFrameCompose,
         verbosity: KittyImageVerbosity,
     },
+    /// a='a'
+    AnimationControl {
+        animation: KittyImageAnimation,
+        verbosity: KittyImageVerbosity,
+    },
 }
 
 impl KittyImage {
@@ -1077,6 +1550,7 @@ impl KittyImage {
             Self::Delete { verbosity, .. } => *verbosity,
             Self::TransmitFrame { verbosity, .. } => *verbosity,
             Self::ComposeFrame { verbosity, .. } => *verbosity,
+            Self::AnimationControl { verbosity, .. } => *verbosity,
         }
     }
 
@@ -1088,10 +1562,8 @@ impl KittyImage {
         let keys = keys_payload_iter.next()?;
         let key_string = std::str::from_utf8(keys).ok()?;
         let mut keys: BTreeMap<&str, &str> = BTreeMap::new();
-        for k_v in key_string.split(',') {
-            let mut k_v = k_v.splitn(2, '=');
-            let k = k_v.next()?;
-            let v = k_v.next()?;
+        for k_v in key_string.split(',').filter(|k_v| !k_v.is_empty()) {
+            let (k, v) = k_v.split_once('=')?;
             keys.insert(k, v);
         }
 
@@ -1130,6 +1602,10 @@ impl KittyImage {
                 frame: KittyImageFrameCompose::from_keys(&keys)?,
                 verbosity,
             }),
+            "a" => Some(Self::AnimationControl {
+                animation: KittyImageAnimation::from_keys(&keys)?,
+                verbosity,
+            }),
             _ => None,
         }
     }
@@ -1153,7 +1629,7 @@ impl KittyImage {
                 verbosity,
                 placement,
             } => {
-                keys.insert("a", "Q".to_string());
+                keys.insert("a", "T".to_string());
                 verbosity.to_keys(keys);
                 placement.to_keys(keys);
                 transmit.to_keys(keys);
@@ -1194,6 +1670,14 @@ impl KittyImage {
                 frame.to_keys(keys);
                 verbosity.to_keys(keys);
             }
+            Self::AnimationControl {
+                animation,
+                verbosity,
+            } => {
+                keys.insert("a", "a".to_string());
+                animation.to_keys(keys);
+                verbosity.to_keys(keys);
+            }
         }
     }
 }
@@ -1227,6 +1711,930 @@ impl Display for KittyImage {
     }
 }
 
+impl KittyImage {
+    /// Image id of the command, zero means that it was not given.
+    pub fn image_id(&self) -> Option<u32> {
+        let image_id = match self {
+            Self::TransmitData { transmit, .. }
+            | Self::TransmitDataAndDisplay { transmit, .. }
+            | Self::Query { transmit }
+            | Self::TransmitFrame { transmit, .. } => transmit.image_id,
+            Self::Display { image_id, .. } => *image_id,
+            Self::ComposeFrame { frame, .. } => frame.image_id,
+            Self::AnimationControl { animation, .. } => animation.image_id,
+            Self::Delete { .. } => None,
+        };
+        image_id.filter(|id| *id != 0)
+    }
+
+    pub fn image_number(&self) -> Option<u32> {
+        let image_number = match self {
+            Self::TransmitData { transmit, .. }
+            | Self::TransmitDataAndDisplay { transmit, .. }
+            | Self::Query { transmit }
+            | Self::TransmitFrame { transmit, .. } => transmit.image_number,
+            Self::Display { image_number, .. } => *image_number,
+            Self::ComposeFrame { frame, .. } => frame.image_number,
+            Self::AnimationControl { animation, .. } => animation.image_number,
+            Self::Delete { .. } => None,
+        };
+        image_number.filter(|number| *number != 0)
+    }
+
+    pub fn placement_id(&self) -> Option<u32> {
+        match self {
+            Self::TransmitDataAndDisplay { placement, .. }
+            | Self::Display { placement, .. } => {
+                placement.placement_id.filter(|id| *id != 0)
+            }
+            _ => None,
+        }
+    }
+
+    fn transmit_mut(&mut self) -> Option<&mut KittyImageTransmit> {
+        match self {
+            Self::TransmitData { transmit, .. }
+            | Self::TransmitDataAndDisplay { transmit, .. }
+            | Self::Query { transmit }
+            | Self::TransmitFrame { transmit, .. } => Some(transmit),
+            Self::Display { .. }
+            | Self::Delete { .. }
+            | Self::ComposeFrame { .. }
+            | Self::AnimationControl { .. } => None,
+        }
+    }
+}
+
+/// Error reported to the program, the code is a POSIX error name like
+/// the ones used by kitty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KittyError {
+    code: &'static str,
+    message: String,
+}
+
+impl KittyError {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for KittyError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "{}:{}", self.code, self.message)
+    }
+}
+
+/// Image and placement ids a texture was placed with, the placement id is
+/// zero if the program did not give one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KittyPlacementId {
+    pub image_id: u32,
+    pub placement_id: u32,
+}
+
+/// Gap of the frames that were not given one, in milliseconds.
+const DEFAULT_FRAME_GAP: i32 = 40;
+
+/// Decoded image, kept to be placed any number of times.
+struct StoredImage {
+    width: usize,
+    height: usize,
+    /// RGBA pixels of the root frame.
+    pixels: Vec<u8>,
+    is_opaque: bool,
+    /// When the image was last transmitted or placed, the least recently
+    /// used images are evicted first.
+    last_used: u64,
+    /// Animation frames after the root one, of the same size.
+    frames: Vec<StoredFrame>,
+    /// Gap of the root frame, in milliseconds.
+    gap: i32,
+    animation: KittyAnimationState,
+    /// Index of the frame shown, the root frame is 0.
+    current_frame: usize,
+    /// Number of loops to play, as given by the program.
+    loops: Option<u32>,
+}
+
+struct StoredFrame {
+    /// RGBA pixels.
+    pixels: Vec<u8>,
+    /// Gap in milliseconds to the next frame, gapless frames are negative.
+    gap: i32,
+}
+
+impl StoredImage {
+    fn new(width: usize, height: usize, pixels: Vec<u8>, is_opaque: bool) -> Self {
+        Self {
+            width,
+            height,
+            pixels,
+            is_opaque,
+            last_used: 0,
+            frames: Vec::new(),
+            gap: DEFAULT_FRAME_GAP,
+            animation: KittyAnimationState::Stopped,
+            current_frame: 0,
+            loops: None,
+        }
+    }
+
+    /// Memory taken by all the frames, in bytes.
+    fn memory(&self) -> usize {
+        self.pixels.len()
+            + self
+                .frames
+                .iter()
+                .map(|frame| frame.pixels.len())
+                .sum::<usize>()
+    }
+
+    #[inline]
+    fn frame_count(&self) -> usize {
+        self.frames.len() + 1
+    }
+
+    fn frame(&self, index: usize) -> Option<(&[u8], i32)> {
+        match index {
+            0 => Some((&self.pixels, self.gap)),
+            index => self
+                .frames
+                .get(index - 1)
+                .map(|frame| (frame.pixels.as_slice(), frame.gap)),
+        }
+    }
+
+    fn frame_mut(&mut self, index: usize) -> Option<(&mut Vec<u8>, &mut i32)> {
+        match index {
+            0 => Some((&mut self.pixels, &mut self.gap)),
+            index => self
+                .frames
+                .get_mut(index - 1)
+                .map(|frame| (&mut frame.pixels, &mut frame.gap)),
+        }
+    }
+
+    /// Pixels of the 1-based frame number.
+    fn frame_pixels(&self, number: u32) -> Result<&[u8], KittyError> {
+        (number as usize)
+            .checked_sub(1)
+            .and_then(|index| self.frame(index))
+            .map(|(pixels, _)| pixels)
+            .ok_or_else(|| frame_not_found(number))
+    }
+}
+
+fn frame_not_found(number: u32) -> KittyError {
+    KittyError::new("ENOENT", format!("frame {number} not found"))
+}
+
+impl std::fmt::Debug for StoredImage {
+    fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
+        fmt.debug_struct("StoredImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("last_used", &self.last_used)
+            .finish()
+    }
+}
+
+/// Images transmitted by the programs and the transmission in progress.
+#[derive(Debug, Default)]
+pub struct KittyImageState {
+    /// Command whose data is still being received in chunks.
+    pending: Option<KittyImage>,
+    images: FxHashMap<u32, StoredImage>,
+    /// Newest image id transmitted with each image number.
+    number_to_id: FxHashMap<u32, u32>,
+    last_assigned_id: u32,
+    used_memory: usize,
+    clock: u64,
+    virtual_placements: FxHashMap<KittyPlacementId, VirtualPlacement>,
+    /// Placements shown by each texture, to redo their frames when the
+    /// animation of the image changes.
+    placed: FxHashMap<GraphicId, PlacedImage>,
+}
+
+/// Image placement shown by a texture.
+#[derive(Debug, Clone)]
+pub struct PlacedImage {
+    pub image_id: u32,
+    pub placement: KittyImagePlacement,
+    /// Columns and rows of virtual placements, the image is fit to them.
+    pub cells: Option<(u32, u32)>,
+}
+
+/// Placement shown by the cells with Unicode placeholders, the texture
+/// spans its columns and rows.
+#[derive(Debug)]
+pub struct VirtualPlacement {
+    pub texture: Arc<TextureRef>,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl KittyImageState {
+    /// Joins the chunks of a transmission, the command is returned once all
+    /// of its data was received.
+    pub fn accumulate(&mut self, mut command: KittyImage) -> Option<KittyImage> {
+        if let Some(mut pending) = self.pending.take() {
+            if let (Some(pending_transmit), Some(transmit)) =
+                (pending.transmit_mut(), command.transmit_mut())
+            {
+                if let (KittyImageData::Direct(data), KittyImageData::Direct(chunk)) =
+                    (&mut pending_transmit.data, &transmit.data)
+                {
+                    if data.len() + chunk.len() > MAX_TRANSMISSION_LENGTH {
+                        tracing::warn!("kitty graphics transmission is too large");
+                        return None;
+                    }
+
+                    data.push_str(chunk);
+                    pending_transmit.more_data_follows = transmit.more_data_follows;
+                    if pending_transmit.more_data_follows {
+                        self.pending = Some(pending);
+                        return None;
+                    }
+                    return Some(pending);
+                }
+            }
+
+            tracing::warn!("incomplete kitty graphics transmission discarded");
+        }
+
+        let is_chunked = command.transmit_mut().is_some_and(|transmit| {
+            transmit.more_data_follows
+                && matches!(transmit.data, KittyImageData::Direct(_))
+        });
+        if is_chunked {
+            self.pending = Some(command);
+            return None;
+        }
+
+        Some(command)
+    }
+
+    /// Decodes and stores the transmitted image, returns its id.
+    pub fn transmit(&mut self, transmit: KittyImageTransmit) -> Result<u32, KittyError> {
+        let image_number = transmit.image_number.filter(|number| *number != 0);
+        let image_id = match transmit.image_id.filter(|id| *id != 0) {
+            Some(_) if image_number.is_some() => {
+                return Err(KittyError::new(
+                    "EINVAL",
+                    "image id and number cannot be used together",
+                ));
+            }
+            Some(image_id) => image_id,
+            None => self.assign_id(),
+        };
+
+        let image = decode(transmit)?;
+        self.remove(image_id);
+        self.clock += 1;
+        self.used_memory += image.memory();
+        self.images.insert(
+            image_id,
+            StoredImage {
+                last_used: self.clock,
+                ..image
+            },
+        );
+        if let Some(image_number) = image_number {
+            self.number_to_id.insert(image_number, image_id);
+        }
+
+        Ok(image_id)
+    }
+
+    /// Checks that the image could be loaded, without storing it.
+    pub fn query(&self, transmit: KittyImageTransmit) -> Result<(), KittyError> {
+        decode(transmit).map(|_| ())
+    }
+
+    /// Image id of the command, the newest image is used for numbers.
+    pub fn find(&self, image_id: Option<u32>, image_number: Option<u32>) -> Option<u32> {
+        image_id.or_else(|| {
+            image_number.and_then(|number| self.number_to_id.get(&number).copied())
+        })
+    }
+
+    /// Frees the image data, its placements are kept.
+    pub fn remove(&mut self, image_id: u32) {
+        if let Some(image) = self.images.remove(&image_id) {
+            self.used_memory -= image.memory();
+            self.number_to_id.retain(|_, id| *id != image_id);
+        }
+    }
+
+    /// Graphic of a placement of the image, showing its current frame.
+    pub fn placement_graphic(
+        &mut self,
+        image_id: u32,
+        placement: &KittyImagePlacement,
+    ) -> Result<GraphicData, KittyError> {
+        let image = self.image_mut(image_id)?;
+        placement_frame(image, image.current_frame, placement)
+    }
+
+    /// Animation of a placement of the image, its frames are placed like
+    /// [`Self::placement_graphic`]. `None` if there is a single frame to
+    /// show.
+    pub fn placement_animation(
+        &mut self,
+        image_id: u32,
+        placement: &KittyImagePlacement,
+    ) -> Result<Option<Animation>, KittyError> {
+        let image = self.image_mut(image_id)?;
+
+        let mut frames = Vec::new();
+        let mut current = 0;
+        for index in 0..image.frame_count() {
+            let Some((_, gap)) = image.frame(index) else {
+                continue;
+            };
+            // Gapless frames are only used to compose other frames
+            if gap < 0 {
+                continue;
+            }
+
+            if index <= image.current_frame {
+                current = frames.len();
+            }
+            let gap = if gap == 0 { DEFAULT_FRAME_GAP } else { gap };
+            frames.push(AnimationFrame {
+                graphic: placement_frame(image, index, placement)?,
+                delay: Duration::from_millis(gap as u64),
+            });
+        }
+
+        if frames.len() < 2 {
+            return Ok(None);
+        }
+
+        let mut animation = Animation::new(frames);
+        animation.current = current;
+        animation.running = image.animation != KittyAnimationState::Stopped;
+        animation.loading = image.animation == KittyAnimationState::Loading;
+        // The first loop is the one being played
+        animation.loops = match image.loops {
+            None | Some(1) => None,
+            Some(loops) => Some(loops.saturating_sub(2)),
+        };
+        animation.restart(Instant::now());
+        Ok(Some(animation))
+    }
+
+    /// Adds a frame to the image, or edits one of its frames. The data is
+    /// composed over the base frame or, without one, a background color.
+    pub fn transmit_frame(
+        &mut self,
+        image_id: u32,
+        transmit: KittyImageTransmit,
+        frame: &KittyImageFrame,
+    ) -> Result<(), KittyError> {
+        let data = decode(transmit)?;
+        let image = self.image_mut(image_id)?;
+
+        let x = frame.x.unwrap_or(0) as usize;
+        let y = frame.y.unwrap_or(0) as usize;
+        if x >= image.width || y >= image.height {
+            return Err(KittyError::new("EINVAL", "frame is outside the image"));
+        }
+
+        let mut pixels = match frame.frame_number.or(frame.base_frame) {
+            Some(number) => image.frame_pixels(number)?.to_vec(),
+            None => frame
+                .background_pixel
+                .unwrap_or(0)
+                .to_be_bytes()
+                .repeat(image.width * image.height),
+        };
+        compose_pixels(
+            (&mut pixels, image.width, x, y),
+            (&data.pixels, data.width, 0, 0),
+            (
+                data.width.min(image.width - x),
+                data.height.min(image.height - y),
+            ),
+            frame.composition_mode,
+        );
+
+        image.is_opaque = false;
+        match frame.frame_number {
+            Some(number) => {
+                let (frame_pixels, gap) = image
+                    .frame_mut(number as usize - 1)
+                    .ok_or_else(|| frame_not_found(number))?;
+                *frame_pixels = pixels;
+                if let Some(duration_ms) = frame.duration_ms {
+                    *gap = duration_ms;
+                }
+            }
+            None => {
+                let memory = pixels.len();
+                image.frames.push(StoredFrame {
+                    pixels,
+                    gap: frame.duration_ms.unwrap_or(DEFAULT_FRAME_GAP),
+                });
+                self.used_memory += memory;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Composes a rectangle of a frame over another frame of the image.
+    pub fn compose_frame(
+        &mut self,
+        image_id: u32,
+        compose: &KittyImageFrameCompose,
+    ) -> Result<(), KittyError> {
+        let image = self.image_mut(image_id)?;
+
+        let (Some(source), Some(target)) = (compose.source_frame, compose.target_frame)
+        else {
+            return Err(KittyError::new("ENOENT", "frames to compose are required"));
+        };
+        let source_pixels = image.frame_pixels(source)?.to_vec();
+        image.frame_pixels(target)?;
+
+        let x = compose.x.unwrap_or(0) as usize;
+        let y = compose.y.unwrap_or(0) as usize;
+        let source_x = compose.src_x.unwrap_or(0) as usize;
+        let source_y = compose.src_y.unwrap_or(0) as usize;
+        let width = compose.w.map_or_else(
+            || image.width.saturating_sub(x.max(source_x)),
+            |w| w as usize,
+        );
+        let height = compose.h.map_or_else(
+            || image.height.saturating_sub(y.max(source_y)),
+            |h| h as usize,
+        );
+        if x.max(source_x) + width > image.width
+            || y.max(source_y) + height > image.height
+        {
+            return Err(KittyError::new("EINVAL", "rectangle is outside the image"));
+        }
+
+        let image_width = image.width;
+        image.is_opaque = false;
+        let Some((pixels, _)) = image.frame_mut(target as usize - 1) else {
+            return Err(frame_not_found(target));
+        };
+        compose_pixels(
+            (pixels, image_width, x, y),
+            (&source_pixels, image_width, source_x, source_y),
+            (width, height),
+            compose.composition_mode,
+        );
+
+        Ok(())
+    }
+
+    /// Changes the state, the current frame, the loops or the gap of a
+    /// frame of the animation.
+    pub fn control_animation(
+        &mut self,
+        image_id: u32,
+        animation: &KittyImageAnimation,
+    ) -> Result<(), KittyError> {
+        let image = self.image_mut(image_id)?;
+
+        if let Some(number) = animation.frame_number {
+            let (_, gap) = image
+                .frame_mut(number as usize - 1)
+                .ok_or_else(|| frame_not_found(number))?;
+            if let Some(duration_ms) = animation.duration_ms {
+                *gap = duration_ms;
+            }
+        }
+        if let Some(number) = animation.current_frame {
+            if number as usize > image.frame_count() {
+                return Err(frame_not_found(number));
+            }
+            image.current_frame = number as usize - 1;
+        }
+        if let Some(state) = animation.state {
+            image.animation = state;
+        }
+        if let Some(loops) = animation.loops {
+            image.loops = Some(loops);
+        }
+
+        Ok(())
+    }
+
+    /// Remembers the placement shown by the texture.
+    pub fn track_placement(&mut self, id: GraphicId, placed: PlacedImage) {
+        self.placed.insert(id, placed);
+    }
+
+    /// Textures showing placements of the image.
+    pub fn placed_textures(&self, image_id: u32) -> Vec<(GraphicId, PlacedImage)> {
+        self.placed
+            .iter()
+            .filter(|(_, placed)| placed.image_id == image_id)
+            .map(|(id, placed)| (*id, placed.clone()))
+            .collect()
+    }
+
+    #[inline]
+    pub fn forget_texture(&mut self, id: GraphicId) {
+        self.placed.remove(&id);
+    }
+
+    fn image_mut(&mut self, image_id: u32) -> Result<&mut StoredImage, KittyError> {
+        let Some(image) = self.images.get_mut(&image_id) else {
+            return Err(KittyError::new(
+                "ENOENT",
+                format!("image {image_id} not found"),
+            ));
+        };
+        self.clock += 1;
+        image.last_used = self.clock;
+        Ok(image)
+    }
+    fn assign_id(&mut self) -> u32 {
+        let mut image_id = self.last_assigned_id;
+        loop {
+            image_id = image_id
+                .checked_add(1)
+                .unwrap_or_default()
+                .max(ASSIGNED_IMAGE_ID_START);
+            if !self.images.contains_key(&image_id) {
+                break;
+            }
+        }
+
+        self.last_assigned_id = image_id;
+        image_id
+    }
+
+    /// Replaces the virtual placement with the same image and placement
+    /// ids.
+    pub fn insert_virtual_placement(
+        &mut self,
+        id: KittyPlacementId,
+        placement: VirtualPlacement,
+    ) {
+        self.virtual_placements.insert(id, placement);
+    }
+
+    /// Virtual placement of the image, any of them when the placement id
+    /// is 0.
+    pub fn virtual_placement(
+        &self,
+        image_id: u32,
+        placement_id: u32,
+    ) -> Option<&VirtualPlacement> {
+        let id = KittyPlacementId {
+            image_id,
+            placement_id,
+        };
+        self.virtual_placements.get(&id).or_else(|| {
+            if placement_id != 0 {
+                return None;
+            }
+
+            self.virtual_placements
+                .iter()
+                .filter(|(id, _)| id.image_id == image_id)
+                .min_by_key(|(id, _)| id.placement_id)
+                .map(|(_, placement)| placement)
+        })
+    }
+
+    /// Removes the virtual placements whose texture matches the predicate,
+    /// returning the ids of their images.
+    pub fn remove_virtual_placements(
+        &mut self,
+        predicate: impl Fn(&TextureRef) -> bool,
+    ) -> HashSet<u32> {
+        let mut image_ids = HashSet::new();
+        self.virtual_placements.retain(|id, placement| {
+            let remove = predicate(&placement.texture);
+            if remove {
+                image_ids.insert(id.image_id);
+            }
+            !remove
+        });
+        image_ids
+    }
+
+    /// Memory taken by the decoded images, in bytes.
+    #[inline]
+    pub fn used_memory(&self) -> usize {
+        self.used_memory
+    }
+
+    #[inline]
+    pub fn image_count(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Frees the least recently used images until they take at most
+    /// `max_memory` bytes, returns how many were freed.
+    pub fn evict(&mut self, max_memory: usize) -> usize {
+        let mut evicted = 0;
+        while self.used_memory > max_memory {
+            let Some(image_id) = self
+                .images
+                .iter()
+                .min_by_key(|(_, image)| image.last_used)
+                .map(|(image_id, _)| *image_id)
+            else {
+                break;
+            };
+
+            self.remove(image_id);
+            evicted += 1;
+        }
+
+        evicted
+    }
+}
+
+/// Image, placement and cell of the image shown by a placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placeholder {
+    pub image_id: u32,
+    pub placement_id: u32,
+    pub row: u32,
+    pub column: u32,
+}
+
+impl Placeholder {
+    /// Decodes the placeholder from the colors and diacritics of its cell.
+    /// The row, column and most significant byte of the image id that
+    /// are missing continue the placeholder on the left, if it is for the
+    /// same image.
+    pub fn decode(
+        foreground: AnsiColor,
+        underline_color: Option<AnsiColor>,
+        diacritics: &[char],
+        left: Option<Placeholder>,
+    ) -> Option<Placeholder> {
+        let image_id = color_id(foreground)?;
+        let placement_id = underline_color.and_then(color_id).unwrap_or(0);
+        let mut values = diacritics.iter().filter_map(|diacritic| {
+            ROW_COLUMN_DIACRITICS
+                .binary_search(diacritic)
+                .ok()
+                .map(|index| index as u32)
+        });
+        let row = values.next();
+        let column = values.next();
+        let most_significant_byte = values.next();
+
+        let left = left.filter(|left| {
+            left.image_id & 0xFF_FFFF == image_id
+                && left.placement_id == placement_id
+                && row.is_none_or(|row| row == left.row)
+                && column.is_none_or(|column| column == left.column + 1)
+        });
+
+        let most_significant_byte = most_significant_byte
+            .or(left.map(|left| left.image_id >> 24))
+            .unwrap_or(0);
+        Some(Placeholder {
+            image_id: image_id | most_significant_byte << 24,
+            placement_id,
+            row: row.or(left.map(|left| left.row)).unwrap_or(0),
+            column: column.or(left.map(|left| left.column + 1)).unwrap_or(0),
+        })
+    }
+}
+
+/// Id encoded in the 24-bit or 256 colors of a placeholder.
+fn color_id(color: AnsiColor) -> Option<u32> {
+    match color {
+        AnsiColor::Spec(rgb) => {
+            Some((rgb.r as u32) << 16 | (rgb.g as u32) << 8 | rgb.b as u32)
+        }
+        AnsiColor::Indexed(index) => Some(index as u32),
+        AnsiColor::Named(_) => None,
+    }
+}
+
+/// Graphic of a placement of a frame of the image, cropped to the source
+/// rectangle, offset within its first cell and scaled to the columns and
+/// rows.
+fn placement_frame(
+    image: &StoredImage,
+    index: usize,
+    placement: &KittyImagePlacement,
+) -> Result<GraphicData, KittyError> {
+    let Some((frame, _)) = image.frame(index) else {
+        return Err(frame_not_found(index as u32 + 1));
+    };
+
+    let x = (placement.x.unwrap_or(0) as usize).min(image.width);
+    let y = (placement.y.unwrap_or(0) as usize).min(image.height);
+    let width = placement
+        .w
+        .filter(|w| *w != 0)
+        .map_or(image.width - x, |w| (w as usize).min(image.width - x));
+    let height = placement
+        .h
+        .filter(|h| *h != 0)
+        .map_or(image.height - y, |h| (h as usize).min(image.height - y));
+    if width == 0 || height == 0 {
+        return Err(KittyError::new("EINVAL", "source rectangle is empty"));
+    }
+
+    let offset_x =
+        (placement.x_offset.unwrap_or(0) as usize).min(MAX_GRAPHIC_DIMENSIONS[0] - width);
+    let offset_y = (placement.y_offset.unwrap_or(0) as usize)
+        .min(MAX_GRAPHIC_DIMENSIONS[1] - height);
+    let graphic_width = width + offset_x;
+    let graphic_height = height + offset_y;
+
+    let mut pixels = vec![0; graphic_width * graphic_height * 4];
+    for row in 0..height {
+        let source = ((y + row) * image.width + x) * 4;
+        let destination = ((offset_y + row) * graphic_width + offset_x) * 4;
+        pixels[destination..destination + width * 4]
+            .copy_from_slice(&frame[source..source + width * 4]);
+    }
+
+    let columns = placement.columns.filter(|columns| *columns != 0);
+    let rows = placement.rows.filter(|rows| *rows != 0);
+    let resize = (columns.is_some() || rows.is_some()).then(|| ResizeCommand {
+        width: columns.map_or(ResizeParameter::Auto, ResizeParameter::Cells),
+        height: rows.map_or(ResizeParameter::Auto, ResizeParameter::Cells),
+        preserve_aspect_ratio: false,
+    });
+
+    Ok(GraphicData {
+        id: GraphicId(0),
+        width: graphic_width,
+        height: graphic_height,
+        color_type: ColorType::Rgba,
+        pixels,
+        is_opaque: image.is_opaque && offset_x == 0 && offset_y == 0,
+        resize,
+    })
+}
+
+/// Copies a rectangle of RGBA pixels, given as the pixels, their width and
+/// the origin of the rectangle, blending them unless overwriting.
+fn compose_pixels(
+    (destination, destination_width, x, y): (&mut [u8], usize, usize, usize),
+    (source, source_width, source_x, source_y): (&[u8], usize, usize, usize),
+    (width, height): (usize, usize),
+    mode: KittyFrameCompositionMode,
+) {
+    for row in 0..height {
+        let to = ((y + row) * destination_width + x) * 4;
+        let from = ((source_y + row) * source_width + source_x) * 4;
+        let destination = &mut destination[to..to + width * 4];
+        let source = &source[from..from + width * 4];
+
+        if mode == KittyFrameCompositionMode::Overwrite {
+            destination.copy_from_slice(source);
+            continue;
+        }
+
+        for (under, over) in destination.chunks_exact_mut(4).zip(source.chunks_exact(4)) {
+            let alpha = over[3] as u32;
+            if alpha == 255 {
+                under.copy_from_slice(over);
+                continue;
+            }
+
+            let under_alpha = under[3] as u32 * (255 - alpha) / 255;
+            let out_alpha = alpha + under_alpha;
+            if out_alpha == 0 {
+                under.fill(0);
+                continue;
+            }
+            for channel in 0..3 {
+                under[channel] = ((over[channel] as u32 * alpha
+                    + under[channel] as u32 * under_alpha)
+                    / out_alpha) as u8;
+            }
+            under[3] = out_alpha as u8;
+        }
+    }
+}
+
+fn decode(transmit: KittyImageTransmit) -> Result<StoredImage, KittyError> {
+    let width = transmit.width.unwrap_or(0) as usize;
+    let height = transmit.height.unwrap_or(0) as usize;
+    let format = transmit.format.unwrap_or(KittyImageFormat::Rgba);
+    let compression = transmit.compression;
+
+    let data = transmit
+        .data
+        .load_data()
+        .map_err(|err| KittyError::new("EBADF", err.to_string()))?;
+    if data.is_empty() {
+        return Err(KittyError::new("ENODATA", "no image data"));
+    }
+
+    let data = match compression {
+        KittyImageCompression::None => data,
+        KittyImageCompression::Deflate => {
+            miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(
+                &data,
+                MAX_TRANSMISSION_LENGTH,
+            )
+            .map_err(|err| {
+                KittyError::new(
+                    "EINVAL",
+                    format!("unable to decompress the data: {:?}", err.status),
+                )
+            })?
+        }
+    };
+
+    let image = match format {
+        KittyImageFormat::Png => {
+            let image =
+                image_rs::load_from_memory_with_format(&data, image_rs::ImageFormat::Png)
+                    .map_err(|err| KittyError::new("EBADPNG", err.to_string()))?
+                    .into_rgba8();
+            StoredImage::new(
+                image.width() as usize,
+                image.height() as usize,
+                image.into_raw(),
+                false,
+            )
+        }
+        KittyImageFormat::Rgb | KittyImageFormat::Rgba => {
+            if width == 0 || height == 0 {
+                return Err(KittyError::new("EINVAL", "image size is required"));
+            }
+            if width > MAX_GRAPHIC_DIMENSIONS[0] || height > MAX_GRAPHIC_DIMENSIONS[1] {
+                return Err(KittyError::new("EFBIG", "image is too large"));
+            }
+
+            let is_rgb = format == KittyImageFormat::Rgb;
+            let channels = if is_rgb { 3 } else { 4 };
+            let expected = width * height * channels;
+            if data.len() < expected {
+                return Err(KittyError::new(
+                    "ENODATA",
+                    format!("insufficient image data: {} < {expected}", data.len()),
+                ));
+            }
+
+            let pixels = if is_rgb {
+                data[..expected]
+                    .chunks_exact(3)
+                    .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                    .collect()
+            } else {
+                let mut data = data;
+                data.truncate(expected);
+                data
+            };
+
+            StoredImage::new(width, height, pixels, is_rgb)
+        }
+    };
+
+    if image.width > MAX_GRAPHIC_DIMENSIONS[0] || image.height > MAX_GRAPHIC_DIMENSIONS[1]
+    {
+        return Err(KittyError::new("EFBIG", "image is too large"));
+    }
+
+    Ok(image)
+}
+
+/// Reply to a command, only sent if the program identified the image and
+/// asked for it with the verbosity.
+pub fn response(
+    image_id: Option<u32>,
+    image_number: Option<u32>,
+    placement_id: Option<u32>,
+    verbosity: KittyImageVerbosity,
+    result: &Result<(), KittyError>,
+) -> Option<String> {
+    if image_id.is_none() && image_number.is_none() {
+        return None;
+    }
+
+    let message = match (result, verbosity) {
+        (_, KittyImageVerbosity::Quiet) | (Ok(()), KittyImageVerbosity::OnlyErrors) => {
+            return None;
+        }
+        (Ok(()), _) => String::from("OK"),
+        (Err(err), _) => err.to_string(),
+    };
+
+    let keys = [("i", image_id), ("I", image_number), ("p", placement_id)]
+        .iter()
+        .filter_map(|(key, value)| value.map(|value| format!("{key}={value}")))
+        .collect::<Vec<_>>()
+        .join(",");
+    Some(format!("\x1b_G{keys};{message}\x1b\\"))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1288,46 +2696,330 @@ mod test {
             }
         );
     }
-}
-
-#[derive(Debug, Default)]
-pub struct KittyImageState {
-    accumulator: Vec<KittyImage>,
-    max_image_id: u32,
-    number_to_id: HashMap<u32, u32>,
-    id_to_data: HashMap<u32, Arc<GraphicData>>,
-    placements: HashMap<(u32, Option<u32>), PlacementInfo>,
-    used_memory: usize,
-}
 
-/// Parse the APC _G parameters to add a graphic to the grid.
-pub fn parse(params: &[&[u8]]) -> Option<GraphicData> {
-    let (control_data, payload) = param_values(params)?;
+    fn transmit(command: &str) -> KittyImageTransmit {
+        match KittyImage::parse_apc(command.as_bytes()).unwrap() {
+            KittyImage::TransmitData { transmit, .. } => transmit,
+            command => panic!("unexpected command {command:?}"),
+        }
+    }
 
-    None
-}
+    fn placement(command: &str) -> KittyImagePlacement {
+        match KittyImage::parse_apc(command.as_bytes()).unwrap() {
+            KittyImage::Display { placement, .. } => placement,
+            command => panic!("unexpected command {command:?}"),
+        }
+    }
 
-/// Extract parameter values.
-/// The Format defined by kitty: <ESC>_G<control data>;<payload><ESC>\
-/// Example: <ESC>_Gf=100,t=f;<encoded /path/to/file.png><ESC>\
-fn param_values<'a>(
-    params: &[&'a [u8]],
-) -> Option<(FxHashMap<&'a str, &'a str>, &'a [u8])> {
-    let mut map = FxHashMap::default();
-    let mut contents = None;
-
-    for param in params {
-        if let Some((key, value)) = str::from_utf8(param).ok()?.split_once('=') {
-            if key == "f" {
-                map.insert(key, value);
-            } else if key == "width" || key == "height" {
-                map.insert(key, value);
-            } else {
-                contents = Some(param);
+    #[test]
+    fn kitty_payload_with_empty_keys() {
+        assert_eq!(
+            KittyImage::parse_apc(b"Ga=d,,q=2,").unwrap(),
+            KittyImage::Delete {
+                what: KittyImageDelete::All { delete: false },
+                verbosity: KittyImageVerbosity::Quiet
             }
+        );
+        assert_eq!(KittyImage::parse_apc(b"Xa=d"), None);
+    }
+
+    #[test]
+    fn kitty_temporary_file_removal() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let protocol_file = dir.join(format!("rio-{id}-tty-graphics-protocol.rgb"));
+        let other_file = dir.join(format!("rio-{id}-kitty-image.rgb"));
+        for file in [&protocol_file, &other_file] {
+            std::fs::write(file, b"AAAA").unwrap();
+            let data = KittyImageData::TemporaryFile {
+                path: file.to_string_lossy().to_string(),
+                data_offset: None,
+                data_size: None,
+            };
+            assert_eq!(data.load_data().unwrap(), b"AAAA");
         }
+
+        assert!(!protocol_file.exists());
+        assert!(other_file.exists());
+        assert_eq!(temporary_file_path(&other_file.to_string_lossy()), None);
+        std::fs::remove_file(&other_file).unwrap();
+
+        assert!(is_forbidden_path(&protocol_file.to_string_lossy()));
+        assert!(is_forbidden_path("/dev/null"));
+    }
+
+    #[test]
+    fn kitty_oversized_file_transmission() {
+        let file = std::env::temp_dir()
+            .join(format!("rio-{}-kitty-oversized.rgb", std::process::id()));
+        std::fs::write(&file, b"AAAA").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        let data = KittyImageData::File {
+            path: path.clone(),
+            data_offset: None,
+            data_size: Some(MAX_TRANSMISSION_LENGTH as u32 + 1),
+        };
+        let err = data.load_data().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let data = KittyImageData::File {
+            path: path.clone(),
+            data_offset: Some(MAX_TRANSMISSION_LENGTH as u32 + 1),
+            data_size: None,
+        };
+        assert!(data.load_data().is_err());
+
+        // A size within the limit still can't read past the end
+        let data = KittyImageData::File {
+            path: path.clone(),
+            data_offset: Some(1),
+            data_size: Some(8),
+        };
+        let err = data.load_data().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        let data = KittyImageData::File {
+            path,
+            data_offset: Some(1),
+            data_size: Some(2),
+        };
+        assert_eq!(data.load_data().unwrap(), b"AA");
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn kitty_chunked_transmission() {
+        let mut state = KittyImageState::default();
+
+        let first = KittyImage::parse_apc(b"Ga=T,f=24,s=2,v=1,i=5,m=1;AAAA").unwrap();
+        assert_eq!(state.accumulate(first), None);
+        let last = KittyImage::parse_apc(b"Gm=0;AAAA").unwrap();
+        let command = state.accumulate(last).unwrap();
+
+        assert_eq!(command.image_id(), Some(5));
+        let KittyImage::TransmitDataAndDisplay { transmit, .. } = command else {
+            panic!("unexpected command {command:?}");
+        };
+        assert_eq!(
+            transmit.data,
+            KittyImageData::Direct("AAAAAAAA".to_string())
+        );
+        assert!(!transmit.more_data_follows);
+        assert_eq!(state.transmit(transmit), Ok(5));
+    }
+
+    #[test]
+    fn kitty_transmit_rgb() {
+        let mut state = KittyImageState::default();
+
+        // 2x1 RGB image without padding in the base64 data
+        let pixels = BASE64.encode([255, 0, 0, 0, 255, 0]);
+        let command = format!("Gf=24,s=2,v=1,i=1;{}", pixels.trim_end_matches('='));
+        assert_eq!(state.transmit(transmit(&command)), Ok(1));
+
+        let graphic = state.placement_graphic(1, &placement("Ga=p,i=1")).unwrap();
+        assert_eq!((graphic.width, graphic.height), (2, 1));
+        assert_eq!(graphic.pixels, vec![255, 0, 0, 255, 0, 255, 0, 255]);
+        assert!(graphic.is_opaque);
+        assert_eq!(graphic.resize, None);
+
+        // Not enough data for the size
+        let error = state
+            .transmit(transmit("Gf=24,s=4,v=4,i=2;AAAA"))
+            .unwrap_err();
+        assert_eq!(error.code, "ENODATA");
+
+        let error = state.transmit(transmit("Gi=3,I=4;AAAA")).unwrap_err();
+        assert_eq!(error.code, "EINVAL");
+    }
+
+    #[test]
+    fn kitty_transmit_png_and_compressed() {
+        let mut state = KittyImageState::default();
+
+        let mut png = Vec::new();
+        image_rs::RgbaImage::from_pixel(3, 2, image_rs::Rgba([1, 2, 3, 4]))
+            .write_to(
+                &mut std::io::Cursor::new(&mut png),
+                image_rs::ImageFormat::Png,
+            )
+            .unwrap();
+        let command = format!("Gf=100,I=7;{}", BASE64.encode(&png));
+        let image_id = state.transmit(transmit(&command)).unwrap();
+        assert!(image_id >= ASSIGNED_IMAGE_ID_START);
+        assert_eq!(state.find(None, Some(7)), Some(image_id));
+
+        let graphic = state
+            .placement_graphic(image_id, &placement("Ga=p,I=7"))
+            .unwrap();
+        assert_eq!((graphic.width, graphic.height), (3, 2));
+        assert_eq!(&graphic.pixels[..4], &[1, 2, 3, 4]);
+
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&[9; 16], 6);
+        let command = format!("Gf=32,s=2,v=2,o=z,i=8;{}", BASE64.encode(&compressed));
+        assert_eq!(state.transmit(transmit(&command)), Ok(8));
+
+        state.remove(image_id);
+        assert_eq!(state.find(None, Some(7)), None);
+        let error = state
+            .placement_graphic(image_id, &placement("Ga=p,I=7"))
+            .unwrap_err();
+        assert_eq!(error.code, "ENOENT");
+    }
+
+    #[test]
+    fn kitty_placeholder_decode() {
+        use crate::config::colors::ColorRgb;
+
+        let foreground = AnsiColor::Spec(ColorRgb { r: 1, g: 2, b: 3 });
+        let underline = Some(AnsiColor::Indexed(9));
+        let first = Placeholder::decode(
+            foreground,
+            underline,
+            &['\u{30D}', '\u{30E}', '\u{310}'],
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            first,
+            Placeholder {
+                image_id: 3 << 24 | 0x010203,
+                placement_id: 9,
+                row: 1,
+                column: 2,
+            }
+        );
+
+        // Missing diacritics continue the cell on the left
+        let second = Placeholder::decode(foreground, underline, &[], Some(first));
+        assert_eq!(second, Some(Placeholder { column: 3, ..first }));
+
+        // Unless it shows another image
+        let other = AnsiColor::Indexed(1);
+        let second = Placeholder::decode(other, None, &[], Some(first)).unwrap();
+        assert_eq!((second.image_id, second.row, second.column), (1, 0, 0));
+
+        let named = AnsiColor::Named(crate::config::colors::NamedColor::Foreground);
+        assert_eq!(Placeholder::decode(named, None, &[], None), None);
     }
 
-    let contents = contents?.strip_prefix(b":")?;
-    Some((map, contents))
+    #[test]
+    fn kitty_placement_crop_and_offset() {
+        let mut state = KittyImageState::default();
+
+        // 2x2 RGBA image, each pixel holds its index
+        let pixels: Vec<u8> = (0..4).flat_map(|index| [index; 4]).collect();
+        let command = format!("Gf=32,s=2,v=2,i=1;{}", BASE64.encode(&pixels));
+        state.transmit(transmit(&command)).unwrap();
+
+        let graphic = state
+            .placement_graphic(1, &placement("Ga=p,i=1,x=1,w=1,h=2,X=2,Y=1,c=3"))
+            .unwrap();
+        assert_eq!((graphic.width, graphic.height), (3, 3));
+        assert!(!graphic.is_opaque);
+        assert_eq!(&graphic.pixels[..12], &[0; 12]);
+        assert_eq!(&graphic.pixels[20..24], &[1; 4]);
+        assert_eq!(&graphic.pixels[32..36], &[3; 4]);
+        assert_eq!(
+            graphic.resize,
+            Some(ResizeCommand {
+                width: ResizeParameter::Cells(3),
+                height: ResizeParameter::Auto,
+                preserve_aspect_ratio: false,
+            })
+        );
+
+        let error = state
+            .placement_graphic(1, &placement("Ga=p,i=1,x=2"))
+            .unwrap_err();
+        assert_eq!(error.code, "EINVAL");
+    }
+
+    #[test]
+    fn kitty_animation_frames() {
+        let mut state = KittyImageState::default();
+
+        // 1x1 red image with a green frame shown for 50ms
+        let red = BASE64.encode([255, 0, 0, 255]);
+        let green = BASE64.encode([0, 255, 0, 255]);
+        state
+            .transmit(transmit(&format!("Gf=32,s=1,v=1,i=1;{red}")))
+            .unwrap();
+        let command = format!("Ga=f,i=1,f=32,s=1,v=1,z=50;{green}");
+        let KittyImage::TransmitFrame {
+            transmit, frame, ..
+        } = KittyImage::parse_apc(command.as_bytes()).unwrap()
+        else {
+            panic!("unexpected command");
+        };
+        assert_eq!(frame.duration_ms, Some(50));
+        state.transmit_frame(1, transmit, &frame).unwrap();
+
+        let placement = placement("Ga=p,i=1");
+        let animation = state.placement_animation(1, &placement).unwrap().unwrap();
+        assert_eq!(animation.frames.len(), 2);
+        assert_eq!(
+            animation.frames[0].delay,
+            Duration::from_millis(DEFAULT_FRAME_GAP as u64)
+        );
+        assert_eq!(animation.frames[1].delay, Duration::from_millis(50));
+        assert_eq!(animation.frames[1].graphic.pixels, vec![0, 255, 0, 255]);
+        assert!(!animation.running);
+
+        let control = |command: &str| match KittyImage::parse_apc(command.as_bytes()) {
+            Some(KittyImage::AnimationControl { animation, .. }) => animation,
+            command => panic!("unexpected command {command:?}"),
+        };
+        state
+            .control_animation(1, &control("Ga=a,i=1,s=3,v=1,c=2"))
+            .unwrap();
+        let animation = state.placement_animation(1, &placement).unwrap().unwrap();
+        assert!(animation.running);
+        assert_eq!(animation.current, 1);
+        assert_eq!(animation.loops, None);
+        let error = state
+            .control_animation(1, &control("Ga=a,i=1,c=3"))
+            .unwrap_err();
+        assert_eq!(error.code, "ENOENT");
+
+        // Composing the first frame over the second one
+        let KittyImage::ComposeFrame { frame, .. } =
+            KittyImage::parse_apc(b"Ga=c,i=1,r=1,c=2").unwrap()
+        else {
+            panic!("unexpected command");
+        };
+        state.compose_frame(1, &frame).unwrap();
+        let graphic = state.placement_graphic(1, &placement).unwrap();
+        assert_eq!(graphic.pixels, vec![255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn kitty_response() {
+        let ok = Ok(());
+        let err = Err(KittyError::new("ENOENT", "image not found"));
+        let verbose = KittyImageVerbosity::Verbose;
+
+        assert_eq!(
+            response(Some(1), None, None, verbose, &ok).as_deref(),
+            Some("\x1b_Gi=1;OK\x1b\\")
+        );
+        assert_eq!(
+            response(Some(1), Some(2), Some(3), verbose, &err).as_deref(),
+            Some("\x1b_Gi=1,I=2,p=3;ENOENT:image not found\x1b\\")
+        );
+        assert_eq!(
+            response(Some(1), None, None, KittyImageVerbosity::OnlyErrors, &ok),
+            None
+        );
+        assert!(
+            response(Some(1), None, None, KittyImageVerbosity::OnlyErrors, &err)
+                .is_some()
+        );
+        assert_eq!(
+            response(Some(1), None, None, KittyImageVerbosity::Quiet, &err),
+            None
+        );
+        assert_eq!(response(None, None, None, verbose, &err), None);
+    }
 }
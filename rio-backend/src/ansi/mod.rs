@@ -1,5 +1,7 @@
 use bitflags::bitflags;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sugarloaf::schema_aliases;
 
 pub mod charset;
 pub mod control;
@@ -9,7 +11,10 @@ pub mod kitty_graphics;
 pub mod mode;
 pub mod sixel;
 
-#[derive(Default, Clone, Serialize, Deserialize, Copy, Debug, Eq, PartialEq)]
+#[derive(
+    Default, Clone, Serialize, Deserialize, Copy, Debug, Eq, PartialEq, JsonSchema,
+)]
+#[schemars(transform = schema_aliases("block underline beam hidden"))]
 pub enum CursorShape {
     /// Cursor is a block like `▒`.
     #[default]
@@ -76,6 +81,19 @@ pub enum LineClearMode {
     All,
 }
 
+/// Semantic prompt marks (OSC 133) emitted by the shell integration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptMark {
+    /// `A`: start of the prompt.
+    PromptStart,
+    /// `B`: end of the prompt, the command input starts.
+    CommandStart,
+    /// `C`: the command was executed, its output starts.
+    OutputStart,
+    /// `D`: the command finished.
+    CommandEnd,
+}
+
 bitflags! {
     /// A set of [`kitty keyboard protocol'] modes.
     ///
@@ -100,6 +118,18 @@ bitflags! {
     }
 }
 
+/// Levels of xterm's modifyOtherKeys, set with `CSI > 4 ; Pv m`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifyOtherKeys {
+    /// Modified keys are sent as usual.
+    Reset,
+    /// Encode the modified keys, except the ones with well-known
+    /// behavior like `ctrl` + letter.
+    EnableExceptWellDefined,
+    /// Encode all the modified keys.
+    EnableAll,
+}
+
 /// Describes how the new [`KeyboardModes`] should be applied.
 #[repr(u8)]
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
@@ -1,7 +1,16 @@
 // clipboard.rs was retired originally from https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty/src/clipboard.rs
 // which is licensed under Apache 2.0 license.
 
+use crate::config::clipboard::{Clipboard as ClipboardConfig, ClipboardBackend};
+use crate::config::Shell;
 use raw_window_handle::RawDisplayHandle;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 use tracing::warn;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,9 +28,110 @@ use copypasta::x11_clipboard::{Primary as X11SelectionClipboard, X11ClipboardCon
 use copypasta::ClipboardContext;
 use copypasta::ClipboardProvider;
 
+type ClipboardResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+/// Runs a program to copy and another one to paste, e.g. `wl-copy` and
+/// `wl-paste`, or a script forwarding OSC 52 when only a SSH connection
+/// reaches the clipboard.
+pub struct CommandClipboard {
+    copy: Option<Shell>,
+    paste: Option<Shell>,
+}
+
+/// Longest wait for a copy or paste command, the clipboard is used from the
+/// UI thread so a stuck program is killed instead of freezing the window.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl CommandClipboard {
+    pub fn new(copy: Option<Shell>, paste: Option<Shell>) -> Self {
+        Self { copy, paste }
+    }
+
+    /// Writes `input` to the program when copying, otherwise returns what
+    /// it printed.
+    fn run(shell: &Shell, input: Option<String>) -> ClipboardResult<Vec<u8>> {
+        let (stdin, stdout) = match input {
+            Some(_) => (Stdio::piped(), Stdio::null()),
+            None => (Stdio::null(), Stdio::piped()),
+        };
+        let mut child = Command::new(&shell.program)
+            .args(&shell.args)
+            .stdin(stdin)
+            .stdout(stdout)
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        // The pipes are served from another thread, so the timeout also
+        // covers a program that never reads its input or closes its output
+        let (sender, receiver) = mpsc::channel();
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        thread::spawn(move || {
+            // Closing the input lets the program finish
+            if let (Some(mut stdin), Some(input)) = (stdin, input) {
+                let _ = stdin.write_all(input.as_bytes());
+            }
+
+            let mut output = Vec::new();
+            if let Some(mut stdout) = stdout {
+                let _ = stdout.read_to_end(&mut output);
+            }
+            let _ = sender.send(output);
+        });
+
+        let deadline = Instant::now() + COMMAND_TIMEOUT;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out", shell.program).into());
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        if !status.success() {
+            return Err(format!("{} exited with {}", shell.program, status).into());
+        }
+
+        // A forked process may still hold the output open
+        receiver
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .map_err(|_| format!("{} timed out", shell.program).into())
+    }
+}
+
+impl ClipboardProvider for CommandClipboard {
+    fn get_contents(&mut self) -> ClipboardResult<String> {
+        let Some(paste) = &self.paste else {
+            return Err("paste-command is not set".into());
+        };
+
+        let output = Self::run(paste, None)?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    fn set_contents(&mut self, text: String) -> ClipboardResult<()> {
+        let Some(copy) = &self.copy else {
+            return Err("copy-command is not set".into());
+        };
+
+        Self::run(copy, Some(text))?;
+        Ok(())
+    }
+}
+
 pub struct Clipboard {
     clipboard: Box<dyn ClipboardProvider>,
     selection: Option<Box<dyn ClipboardProvider>>,
+    /// Used instead of the system clipboard and selection when another
+    /// backend is configured.
+    backend: Option<Box<dyn ClipboardProvider>>,
+    /// Texts stored in the clipboard by Rio, the most recent first.
+    history: VecDeque<String>,
+    history_size: usize,
 }
 
 impl Clipboard {
@@ -37,6 +147,9 @@ impl Clipboard {
                 Self {
                     clipboard: Box::new(clipboard),
                     selection: Some(Box::new(selection)),
+                    backend: None,
+                    history: VecDeque::new(),
+                    history_size: 0,
                 }
             }
             _ => Self::default(),
@@ -49,6 +162,9 @@ impl Clipboard {
         Self {
             clipboard: Box::new(NopClipboardContext::new().unwrap()),
             selection: None,
+            backend: None,
+            history: VecDeque::new(),
+            history_size: 0,
         }
     }
 }
@@ -59,6 +175,9 @@ impl Default for Clipboard {
         return Self {
             clipboard: Box::new(ClipboardContext::new().unwrap()),
             selection: None,
+            backend: None,
+            history: VecDeque::new(),
+            history_size: 0,
         };
 
         #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
@@ -67,6 +186,9 @@ impl Default for Clipboard {
             selection: Some(Box::new(
                 X11ClipboardContext::<X11SelectionClipboard>::new().unwrap(),
             )),
+            backend: None,
+            history: VecDeque::new(),
+            history_size: 0,
         };
 
         #[cfg(not(any(feature = "x11", target_os = "macos", windows)))]
@@ -75,21 +197,80 @@ impl Default for Clipboard {
 }
 
 impl Clipboard {
+    /// Switches between the system clipboard and the other backends.
+    pub fn set_backend(&mut self, config: &ClipboardConfig) {
+        self.backend = match config.backend {
+            ClipboardBackend::System => None,
+            ClipboardBackend::Command => Some(Box::new(CommandClipboard::new(
+                config.copy_command.clone(),
+                config.paste_command.clone(),
+            ))),
+            ClipboardBackend::None => Some(Box::new(NopClipboardContext::new().unwrap())),
+        };
+    }
+
     pub fn set(&mut self, ty: ClipboardType, text: impl Into<String>) {
-        let clipboard = match (ty, &mut self.selection) {
-            (ClipboardType::Selection, Some(provider)) => provider,
-            (ClipboardType::Selection, None) => return,
+        let text = text.into();
+        let clipboard = match (ty, &mut self.backend, &mut self.selection) {
+            // The other backends have no primary selection
+            (ClipboardType::Selection, Some(_), _) => return,
+            (ClipboardType::Selection, None, Some(provider)) => provider,
+            (ClipboardType::Selection, None, None) => return,
+            (_, Some(backend), _) => backend,
             _ => &mut self.clipboard,
         };
 
-        clipboard.set_contents(text.into()).unwrap_or_else(|err| {
+        clipboard.set_contents(text).unwrap_or_else(|err| {
             warn!("Unable to store text in clipboard: {}", err);
         });
     }
 
+    /// Whether the system has a primary selection distinct from the
+    /// clipboard, otherwise the selection uses the clipboard.
+    #[inline]
+    pub fn has_selection(&self) -> bool {
+        self.backend.is_none() && self.selection.is_some()
+    }
+
+    /// Number of texts kept in the history, older ones are dropped.
+    pub fn set_history_size(&mut self, size: usize) {
+        self.history_size = size;
+        self.history.truncate(size);
+    }
+
+    /// Texts copied to the clipboard by the user, the most recent first.
+    #[inline]
+    pub fn history(&self) -> &VecDeque<String> {
+        &self.history
+    }
+
+    /// Stores a text copied by the user in the clipboard, it's only kept
+    /// in the history with `keep_in_history`, which is false for secrets.
+    pub fn copy(&mut self, text: impl Into<String>, keep_in_history: bool) {
+        let text = text.into();
+        if keep_in_history {
+            self.push_history(&text);
+        }
+        self.set(ClipboardType::Clipboard, text);
+    }
+
+    fn push_history(&mut self, text: &str) {
+        if self.history_size == 0 || text.is_empty() {
+            return;
+        }
+
+        // Copying the same text again moves it to the top
+        if let Some(index) = self.history.iter().position(|item| item == text) {
+            self.history.remove(index);
+        }
+        self.history.push_front(text.to_owned());
+        self.history.truncate(self.history_size);
+    }
+
     pub fn get(&mut self, ty: ClipboardType) -> String {
-        let clipboard = match (ty, &mut self.selection) {
-            (ClipboardType::Selection, Some(provider)) => provider,
+        let clipboard = match (ty, &mut self.backend, &mut self.selection) {
+            (_, Some(backend), _) => backend,
+            (ClipboardType::Selection, None, Some(provider)) => provider,
             _ => &mut self.clipboard,
         };
 
@@ -102,3 +283,74 @@ impl Clipboard {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_history() {
+        let mut clipboard = Clipboard::new_nop();
+        clipboard.copy("ignored", true);
+        assert!(clipboard.history().is_empty());
+
+        clipboard.set_history_size(2);
+        clipboard.copy("first", true);
+        clipboard.set(ClipboardType::Selection, "selected");
+        clipboard.set(ClipboardType::Clipboard, "stored by a program");
+        clipboard.copy("secret", false);
+        clipboard.copy("second", true);
+        clipboard.copy("first", true);
+        assert_eq!(clipboard.history(), &["first", "second"]);
+
+        clipboard.copy("third", true);
+        assert_eq!(clipboard.history(), &["third", "first"]);
+
+        clipboard.set_history_size(1);
+        assert_eq!(clipboard.history(), &["third"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_backend() {
+        let path = std::env::temp_dir()
+            .join(format!("rio-test-command-clipboard-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let shell = |script: String| Shell {
+            program: String::from("sh"),
+            args: vec![String::from("-c"), script],
+        };
+
+        let mut clipboard = Clipboard::new_nop();
+        clipboard.set_backend(&ClipboardConfig {
+            backend: ClipboardBackend::Command,
+            copy_command: Some(shell(format!("cat > {path}"))),
+            paste_command: Some(shell(format!("cat {path}"))),
+            ..ClipboardConfig::default()
+        });
+        assert!(!clipboard.has_selection());
+
+        clipboard.set(ClipboardType::Clipboard, "copied");
+        assert_eq!(clipboard.get(ClipboardType::Clipboard), "copied");
+        assert_eq!(clipboard.get(ClipboardType::Selection), "copied");
+
+        clipboard.set_backend(&ClipboardConfig {
+            backend: ClipboardBackend::Command,
+            paste_command: Some(shell(String::from("exit 1"))),
+            ..ClipboardConfig::default()
+        });
+        assert_eq!(clipboard.get(ClipboardType::Clipboard), "");
+
+        // A stuck program is killed after the timeout
+        clipboard.set_backend(&ClipboardConfig {
+            backend: ClipboardBackend::Command,
+            paste_command: Some(shell(String::from("sleep 10"))),
+            ..ClipboardConfig::default()
+        });
+        let start = Instant::now();
+        assert_eq!(clipboard.get(ClipboardType::Clipboard), "");
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
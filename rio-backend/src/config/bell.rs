@@ -0,0 +1,24 @@
+use crate::config::defaults::default_bell_min_interval;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Bell {
+    /// Shortest time in milliseconds between two bells of a window, the
+    /// bells in between are dropped. `0` keeps all of them.
+    #[serde(default = "default_bell_min_interval", rename = "min-interval")]
+    pub min_interval: u64,
+    /// Names of the foreground programs whose bells are ignored, like
+    /// `"vim"`.
+    #[serde(default)]
+    pub mute: Vec<String>,
+}
+
+impl Default for Bell {
+    fn default() -> Self {
+        Self {
+            min_interval: default_bell_min_interval(),
+            mute: Vec::new(),
+        }
+    }
+}
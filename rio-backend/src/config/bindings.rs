@@ -1,11 +1,13 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sugarloaf::schema_aliases;
 
 // Examples:
 // { key = "w", mods: "super", action = "quit" }
 // Bytes[27, 91, 53, 126] is equivalent to "\x1b[5~"
 // { key = "Home", mods: "super | shift", bytes = [27, 91, 53, 126] }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct KeyBinding {
     pub key: String,
     #[serde(default = "String::default")]
@@ -15,14 +17,58 @@ pub struct KeyBinding {
     #[serde(default = "String::default")]
     pub text: String,
     #[serde(default = "Vec::default")]
+    #[schemars(default)]
     pub bytes: Vec<u8>,
     #[serde(default = "String::default")]
     pub mode: String,
+    /// Keys pressed before `key` for a multi-step binding, like
+    /// `"control+a"` or `"control+a > g"`.
+    #[serde(default = "String::default")]
+    pub leader: String,
+    /// Program run by the binding, takes precedence over `action`.
+    #[serde(default)]
+    pub command: Option<BindingCommand>,
+}
+
+/// Where the program of a binding runs.
+#[derive(
+    Default, Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, JsonSchema,
+)]
+#[schemars(transform = schema_aliases("background tab"))]
+pub enum CommandTarget {
+    /// Detached from Rio, without a window.
+    #[default]
+    #[serde(alias = "background")]
+    Background,
+    /// In a new tab, which is closed when the program exits.
+    #[serde(alias = "tab")]
+    Tab,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BindingCommand {
+    pub program: String,
+    /// `$selection` is replaced by the selected text, or an empty text
+    /// without a selection.
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default, rename = "in")]
+    pub target: CommandTarget,
+}
+
+impl BindingCommand {
+    /// Arguments with the selected text.
+    pub fn args_with_selection(&self, selection: &str) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| arg.replace("$selection", selection))
+            .collect()
+    }
 }
 
 pub type KeyBindings = Vec<KeyBinding>;
 
-#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Bindings {
     pub keys: KeyBindings,
 }
@@ -30,7 +76,7 @@ pub struct Bindings {
 #[cfg(test)]
 mod tests {
 
-    use crate::config::bindings::Bindings;
+    use crate::config::bindings::{Bindings, CommandTarget};
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -53,6 +99,43 @@ mod tests {
         assert_eq!(decoded.bindings.keys[0].with.to_owned(), "super");
         assert_eq!(decoded.bindings.keys[0].action.to_owned(), "quit");
         assert!(decoded.bindings.keys[0].text.to_owned().is_empty());
+        assert!(decoded.bindings.keys[0].leader.is_empty());
+    }
+
+    #[test]
+    fn test_leader_key() {
+        let content = r#"
+            [bindings]
+            keys = [
+                { leader = 'control+a', key = 'c', action = 'TabCreateNew' }
+            ]
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        assert_eq!(decoded.bindings.keys[0].key, "c");
+        assert_eq!(decoded.bindings.keys[0].leader, "control+a");
+        assert!(decoded.bindings.keys[0].with.is_empty());
+    }
+
+    #[test]
+    fn test_command() {
+        let content = r#"
+            [bindings]
+            keys = [
+                { key = 'o', with = 'super', command = { program = 'code', args = ['.'] } },
+                { key = 'm', with = 'super', command = { program = 'man', args = ['$selection'], in = 'tab' } },
+            ]
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        let command = decoded.bindings.keys[0].command.as_ref().unwrap();
+        assert_eq!(command.program, "code");
+        assert_eq!(command.target, CommandTarget::Background);
+
+        let command = decoded.bindings.keys[1].command.as_ref().unwrap();
+        assert_eq!(command.target, CommandTarget::Tab);
+        assert_eq!(command.args_with_selection("ls"), vec![String::from("ls")]);
+        assert_eq!(command.args_with_selection(""), vec![String::new()]);
     }
 
     #[test]
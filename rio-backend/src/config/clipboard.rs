@@ -0,0 +1,92 @@
+use crate::config::defaults::{default_clipboard_history, default_osc52_max_size};
+use crate::config::Shell;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sugarloaf::schema_aliases;
+
+/// Clipboard access of the programs with the OSC 52 sequence, reading
+/// lets remote programs see everything copied on the machine.
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema,
+)]
+#[schemars(transform = schema_aliases("disabled read write read-write"))]
+pub enum Osc52 {
+    #[serde(alias = "disabled")]
+    Disabled,
+    #[serde(alias = "read")]
+    Read,
+    #[serde(alias = "write")]
+    Write,
+    #[default]
+    #[serde(alias = "read-write")]
+    ReadWrite,
+}
+
+impl Osc52 {
+    #[inline]
+    pub fn can_read(self) -> bool {
+        matches!(self, Osc52::Read | Osc52::ReadWrite)
+    }
+
+    #[inline]
+    pub fn can_write(self) -> bool {
+        matches!(self, Osc52::Write | Osc52::ReadWrite)
+    }
+}
+
+/// Where Rio copies to and pastes from.
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema,
+)]
+#[schemars(transform = schema_aliases("system command none"))]
+pub enum ClipboardBackend {
+    /// Clipboard of the system, with the primary selection on X11 and
+    /// Wayland.
+    #[default]
+    #[serde(alias = "system")]
+    System,
+    /// Runs `copy-command` and `paste-command`.
+    #[serde(alias = "command")]
+    Command,
+    /// Copied texts are only kept in the history.
+    #[serde(alias = "none")]
+    None,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Clipboard {
+    #[serde(default)]
+    pub backend: ClipboardBackend,
+    /// Program receiving the copied text in its standard input.
+    #[serde(default, rename = "copy-command")]
+    pub copy_command: Option<Shell>,
+    /// Program writing the text to paste to its standard output.
+    #[serde(default, rename = "paste-command")]
+    pub paste_command: Option<Shell>,
+    /// Number of texts copied by Rio kept to be picked again, `0` turns
+    /// the history off.
+    #[serde(default = "default_clipboard_history")]
+    pub history: usize,
+    #[serde(default = "Osc52::default")]
+    pub osc52: Osc52,
+    /// Largest text in bytes a program can write or read with OSC 52.
+    #[serde(default = "default_osc52_max_size", rename = "osc52-max-size")]
+    pub osc52_max_size: usize,
+    /// Asks before each OSC 52 read or write.
+    #[serde(default = "bool::default", rename = "osc52-confirm")]
+    pub osc52_confirm: bool,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self {
+            backend: ClipboardBackend::default(),
+            copy_command: None,
+            paste_command: None,
+            history: default_clipboard_history(),
+            osc52: Osc52::default(),
+            osc52_max_size: default_osc52_max_size(),
+            osc52_confirm: false,
+        }
+    }
+}
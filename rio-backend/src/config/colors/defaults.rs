@@ -182,6 +182,19 @@ pub fn default_light_yellow() -> ColorArray {
         .to_arr()
 }
 
+#[inline]
+pub fn window_border() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#ffa133"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn window_border_unfocused() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#292527"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
 #[inline]
 pub fn split() -> ColorArray {
     ColorBuilder::from_hex(String::from("#292527"), Format::SRGB0_1)
@@ -223,3 +236,15 @@ pub fn search_focused_match_background() -> ColorArray {
 pub fn search_focused_match_foreground() -> ColorArray {
     [1., 1., 1., 1.]
 }
+#[inline]
+pub fn hint_background() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#E9FF5E"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+#[inline]
+pub fn hint_foreground() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#1D1F21"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
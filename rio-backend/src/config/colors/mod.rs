@@ -4,10 +4,12 @@ pub mod term;
 
 use defaults::*;
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::Serialize;
 use serde::{de, Deserialize};
 use std::num::ParseIntError;
 use std::ops::Mul;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 pub type ColorWGPU = wgpu::Color;
 pub type ColorArray = [f32; 4];
@@ -81,7 +83,8 @@ pub enum AnsiColor {
     Indexed(u8),
 }
 
-#[derive(Debug, Copy, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Copy, Deserialize, PartialEq, Clone, JsonSchema)]
+#[schemars(transform = hex_defaults)]
 pub struct Colors {
     #[serde(
         deserialize_with = "deserialize_to_composition",
@@ -90,210 +93,281 @@ pub struct Colors {
     /// Background is a special color type called ColorComposition
     /// ColorComposition type is (ColorArray, ColorWGPU)
     /// See more in colors definition
+    #[schemars(with = "String")]
     pub background: ColorComposition,
     #[serde(
         deserialize_with = "deserialize_to_arr",
         default = "defaults::foreground"
     )]
+    #[schemars(with = "String")]
     pub foreground: ColorArray,
     #[serde(deserialize_with = "deserialize_to_arr", default = "defaults::blue")]
+    #[schemars(with = "String")]
     pub blue: ColorArray,
     #[serde(deserialize_with = "deserialize_to_arr", default = "defaults::green")]
+    #[schemars(with = "String")]
     pub green: ColorArray,
     #[serde(deserialize_with = "deserialize_to_arr", default = "defaults::red")]
+    #[schemars(with = "String")]
     pub red: ColorArray,
     #[serde(deserialize_with = "deserialize_to_arr", default = "defaults::yellow")]
+    #[schemars(with = "String")]
     pub yellow: ColorArray,
     #[serde(
         deserialize_with = "deserialize_to_arr",
         default = "defaults::tabs_active",
         rename = "tabs-active"
     )]
+    #[schemars(with = "String")]
     pub tabs_active: ColorArray,
     #[serde(
         deserialize_with = "deserialize_to_arr",
         default = "defaults::tabs_active_foreground",
         rename = "tabs-active-foreground"
     )]
+    #[schemars(with = "String")]
     pub tabs_active_foreground: ColorArray,
     #[serde(
         deserialize_with = "deserialize_to_arr",
         default = "defaults::tabs_foreground",
         rename = "tabs-foreground"
     )]
+    #[schemars(with = "String")]
     pub tabs_foreground: ColorArray,
     #[serde(default = "defaults::cursor", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub cursor: ColorArray,
     #[serde(
         default = "defaults::vi_cursor",
         rename = "vi-cursor",
         deserialize_with = "deserialize_to_arr"
     )]
+    #[schemars(with = "String")]
     pub vi_cursor: ColorArray,
     #[serde(default = "defaults::black", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub black: ColorArray,
     #[serde(default = "defaults::cyan", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub cyan: ColorArray,
     #[serde(default = "defaults::magenta", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub magenta: ColorArray,
     #[serde(default = "defaults::tabs", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub tabs: ColorArray,
     #[serde(default = "defaults::bar", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub bar: ColorArray,
     #[serde(
         default = "defaults::tabs_active_highlight",
         rename = "tabs-active-highlight",
         deserialize_with = "deserialize_to_arr"
     )]
+    #[schemars(with = "String")]
     pub tabs_active_highlight: ColorArray,
     #[serde(default = "defaults::white", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub white: ColorArray,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-black"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_black: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-blue"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_blue: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-cyan"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_cyan: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-foreground"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_foreground: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-green"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_green: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-magenta"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_magenta: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-red"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_red: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-white"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_white: Option<ColorArray>,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "dim-yellow"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub dim_yellow: Option<ColorArray>,
     #[serde(
         default = "default_light_black",
         deserialize_with = "deserialize_to_arr",
         rename = "light-black"
     )]
+    #[schemars(with = "String")]
     pub light_black: ColorArray,
     #[serde(
         default = "default_light_blue",
         deserialize_with = "deserialize_to_arr",
         rename = "light-blue"
     )]
+    #[schemars(with = "String")]
     pub light_blue: ColorArray,
     #[serde(
         default = "default_light_cyan",
         deserialize_with = "deserialize_to_arr",
         rename = "light-cyan"
     )]
+    #[schemars(with = "String")]
     pub light_cyan: ColorArray,
     #[serde(
         default = "Option::default",
         deserialize_with = "deserialize_to_arr_opt",
         rename = "light-foreground"
     )]
+    #[schemars(with = "Option<String>", default)]
     pub light_foreground: Option<ColorArray>,
     #[serde(
         default = "default_light_green",
         deserialize_with = "deserialize_to_arr",
         rename = "light-green"
     )]
+    #[schemars(with = "String")]
     pub light_green: ColorArray,
     #[serde(
         default = "default_light_magenta",
         deserialize_with = "deserialize_to_arr",
         rename = "light-magenta"
     )]
+    #[schemars(with = "String")]
     pub light_magenta: ColorArray,
     #[serde(
         default = "default_light_red",
         deserialize_with = "deserialize_to_arr",
         rename = "light-red"
     )]
+    #[schemars(with = "String")]
     pub light_red: ColorArray,
     #[serde(
         default = "default_light_white",
         deserialize_with = "deserialize_to_arr",
         rename = "light-white"
     )]
+    #[schemars(with = "String")]
     pub light_white: ColorArray,
     #[serde(
         default = "default_light_yellow",
         deserialize_with = "deserialize_to_arr",
         rename = "light-yellow"
     )]
+    #[schemars(with = "String")]
     pub light_yellow: ColorArray,
     #[serde(
         default = "defaults::selection_background",
         deserialize_with = "deserialize_to_arr",
         rename = "selection-background"
     )]
+    #[schemars(with = "String")]
     pub selection_background: ColorArray,
     #[serde(
         default = "defaults::selection_foreground",
         deserialize_with = "deserialize_to_arr",
         rename = "selection-foreground"
     )]
+    #[schemars(with = "String")]
     pub selection_foreground: ColorArray,
     #[serde(default = "defaults::split", deserialize_with = "deserialize_to_arr")]
+    #[schemars(with = "String")]
     pub split: ColorArray,
+    #[serde(
+        default = "defaults::window_border",
+        deserialize_with = "deserialize_to_arr",
+        rename = "window-border"
+    )]
+    #[schemars(with = "String")]
+    pub window_border: ColorArray,
+    #[serde(
+        default = "defaults::window_border_unfocused",
+        deserialize_with = "deserialize_to_arr",
+        rename = "window-border-unfocused"
+    )]
+    #[schemars(with = "String")]
+    pub window_border_unfocused: ColorArray,
     #[serde(
         default = "defaults::search_match_background",
         deserialize_with = "deserialize_to_arr",
         rename = "search-match-background"
     )]
+    #[schemars(with = "String")]
     pub search_match_background: ColorArray,
     #[serde(
         default = "defaults::search_match_foreground",
         deserialize_with = "deserialize_to_arr",
         rename = "search-match-foreground"
     )]
+    #[schemars(with = "String")]
     pub search_match_foreground: ColorArray,
     #[serde(
         default = "defaults::search_focused_match_background",
         deserialize_with = "deserialize_to_arr",
         rename = "search-focused-match-background"
     )]
+    #[schemars(with = "String")]
     pub search_focused_match_background: ColorArray,
     #[serde(
         default = "defaults::search_focused_match_foreground",
         deserialize_with = "deserialize_to_arr",
         rename = "search-focused-match-foreground"
     )]
+    #[schemars(with = "String")]
     pub search_focused_match_foreground: ColorArray,
+    #[serde(
+        default = "defaults::hint_background",
+        deserialize_with = "deserialize_to_arr",
+        rename = "hint-background"
+    )]
+    #[schemars(with = "String")]
+    pub hint_background: ColorArray,
+    #[serde(
+        default = "defaults::hint_foreground",
+        deserialize_with = "deserialize_to_arr",
+        rename = "hint-foreground"
+    )]
+    #[schemars(with = "String")]
+    pub hint_foreground: ColorArray,
 }
 
 impl Default for Colors {
@@ -313,6 +387,8 @@ impl Default for Colors {
             tabs_foreground: defaults::tabs_foreground(),
             cursor: defaults::cursor(),
             split: defaults::split(),
+            window_border: defaults::window_border(),
+            window_border_unfocused: defaults::window_border_unfocused(),
             vi_cursor: defaults::vi_cursor(),
             black: defaults::black(),
             cyan: defaults::cyan(),
@@ -342,6 +418,8 @@ impl Default for Colors {
             search_match_foreground: defaults::search_match_foreground(),
             search_focused_match_background: defaults::search_focused_match_background(),
             search_focused_match_foreground: defaults::search_focused_match_foreground(),
+            hint_background: defaults::hint_background(),
+            hint_foreground: defaults::hint_foreground(),
         }
     }
 }
@@ -472,7 +550,7 @@ impl NamedColor {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy, JsonSchema)]
 pub struct ColorBuilder {
     pub red: f64,
     pub green: f64,
@@ -480,7 +558,7 @@ pub struct ColorBuilder {
     pub alpha: f64,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy, JsonSchema)]
 pub struct ColorBuilder8Bits {
     pub red: u8,
     pub green: u8,
@@ -626,12 +704,82 @@ impl std::fmt::Display for ColorBuilder {
     }
 }
 
+/// Variable of the color config replaced by the system accent color,
+/// e.g. `{accent}` or `{accent}80` with alpha.
+pub const ACCENT_VARIABLE: &str = "{accent}";
+
+// Used when the platform has no accent color
+const DEFAULT_ACCENT_COLOR: u32 = 0x0a84ff;
+
+// Packed as 0x01RRGGBB once the platform reported a color, 0 otherwise
+static ACCENT_COLOR: AtomicU32 = AtomicU32::new(0);
+
+/// Sets the system accent color used by the `{accent}` variable, returns
+/// true if it changed. The config has to be loaded again to use it.
+pub fn set_accent_color(color: Option<ColorRgb>) -> bool {
+    let packed = color.map_or(0, |color| {
+        0x0100_0000 | (color.r as u32) << 16 | (color.g as u32) << 8 | color.b as u32
+    });
+    ACCENT_COLOR.swap(packed, Ordering::Relaxed) != packed
+}
+
+/// Parses a color of the config, the variables are replaced before.
+fn color_from_config(value: String) -> Result<ColorBuilder, String> {
+    if !value.contains(ACCENT_VARIABLE) {
+        return ColorBuilder::from_hex(value, Format::SRGB0_1);
+    }
+
+    let accent = match ACCENT_COLOR.load(Ordering::Relaxed) {
+        0 => DEFAULT_ACCENT_COLOR,
+        packed => packed & 0xffffff,
+    };
+    ColorBuilder::from_hex(
+        value.replace(ACCENT_VARIABLE, &format!("#{accent:06x}")),
+        Format::SRGB0_1,
+    )
+}
+
+/// Formats the color as `#rrggbb`, with the alpha channel if it isn't
+/// opaque.
+pub fn color_arr_to_hex(color: &ColorArray) -> String {
+    let [red, green, blue, alpha] =
+        color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+    if alpha == u8::MAX {
+        format!("#{red:02x}{green:02x}{blue:02x}")
+    } else {
+        format!("#{red:02x}{green:02x}{blue:02x}{alpha:02x}")
+    }
+}
+
+// The defaults of the colors are shown as hex in the config schema
+pub(crate) fn hex_defaults(schema: &mut schemars::Schema) {
+    let Some(properties) = schema
+        .get_mut("properties")
+        .and_then(|properties| properties.as_object_mut())
+    else {
+        return;
+    };
+
+    for (name, property) in properties.iter_mut() {
+        let default = if name == "background" {
+            Some(defaults::background().0)
+        } else {
+            property
+                .get("default")
+                .and_then(|default| serde_json::from_value(default.clone()).ok())
+        };
+        if let (Some(color), Some(property)) = (default, property.as_object_mut()) {
+            property.insert("default".to_string(), color_arr_to_hex(&color).into());
+        }
+    }
+}
+
 pub fn deserialize_to_wgpu<'de, D>(deserializer: D) -> Result<ColorWGPU, D::Error>
 where
     D: de::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    match ColorBuilder::from_hex(s, Format::SRGB0_1) {
+    match color_from_config(s) {
         Ok(color) => Ok(color.to_wgpu()),
         Err(e) => Err(serde::de::Error::custom(e)),
     }
@@ -644,7 +792,7 @@ where
     D: de::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    match ColorBuilder::from_hex(s, Format::SRGB0_1) {
+    match color_from_config(s) {
         Ok(color) => Ok((color.to_arr(), color.to_wgpu())),
         Err(e) => Err(serde::de::Error::custom(e)),
     }
@@ -655,7 +803,7 @@ where
     D: de::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    match ColorBuilder::from_hex(s, Format::SRGB0_1) {
+    match color_from_config(s) {
         Ok(color) => Ok(color.to_arr()),
         Err(e) => Err(serde::de::Error::custom(e)),
     }
@@ -668,7 +816,7 @@ where
     D: de::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    match ColorBuilder::from_hex(s, Format::SRGB0_1) {
+    match color_from_config(s) {
         Ok(color) => Ok(Some(color.to_arr())),
         Err(e) => Err(serde::de::Error::custom(e)),
     }
@@ -678,6 +826,44 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_accent_color() {
+        #[derive(Deserialize)]
+        struct Accent {
+            #[serde(deserialize_with = "deserialize_to_arr")]
+            color: ColorArray,
+            #[serde(deserialize_with = "deserialize_to_arr")]
+            translucent: ColorArray,
+        }
+
+        set_accent_color(Some(ColorRgb {
+            r: 255,
+            g: 0,
+            b: 51,
+        }));
+        assert!(!set_accent_color(Some(ColorRgb {
+            r: 255,
+            g: 0,
+            b: 51,
+        })));
+
+        let accent: Accent =
+            toml::from_str("color = '{accent}'\ntranslucent = '{accent}80'").unwrap();
+        assert_eq!(accent.color, [1.0, 0.0, 0.2, 1.0]);
+        assert_eq!(accent.translucent[..3], accent.color[..3]);
+        assert!((accent.translucent[3] - 0.5).abs() < 0.01);
+
+        assert!(set_accent_color(None));
+        let accent: Accent =
+            toml::from_str("color = '{accent}'\ntranslucent = '#00000000'").unwrap();
+        assert_eq!(
+            accent.color,
+            ColorBuilder::from_hex(String::from("#0a84ff"), Format::SRGB0_1)
+                .unwrap()
+                .to_arr()
+        );
+    }
+
     #[test]
     fn test_conversion_from_hex_invalid_character() {
         let invalid_character_color = match ColorBuilder::from_hex(
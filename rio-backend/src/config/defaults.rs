@@ -10,11 +10,79 @@ pub fn default_line_height() -> f32 {
     1.0
 }
 
+#[inline]
+pub fn default_scroll_multiplier() -> f64 {
+    3.0
+}
+
+#[inline]
+pub fn default_scroll_divider() -> f64 {
+    1.0
+}
+
+#[inline]
+pub fn default_scroll_history() -> usize {
+    10_000
+}
+
+#[inline]
+pub fn default_graphics_max_memory() -> usize {
+    320
+}
+
+#[inline]
+pub fn default_hints_alphabet() -> String {
+    String::from("jfkdls;ahgurieowpq")
+}
+
+#[inline]
+pub fn default_block_selection_modifiers() -> String {
+    String::from("alt")
+}
+
+#[inline]
+pub fn default_clipboard_history() -> usize {
+    20
+}
+
+#[inline]
+pub fn default_osc52_max_size() -> usize {
+    1_000_000
+}
+
+#[inline]
+pub fn default_paste_large_size() -> usize {
+    10_000
+}
+
+#[inline]
+pub fn default_bell_min_interval() -> u64 {
+    100
+}
+
+#[inline]
+pub fn default_hyperlink_hover_modifiers() -> String {
+    #[cfg(target_os = "macos")]
+    {
+        String::from("super")
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        String::from("shift")
+    }
+}
+
 #[inline]
 pub fn default_cursor_interval() -> u64 {
     800
 }
 
+#[inline]
+pub fn default_cursor_thickness() -> f32 {
+    3.0
+}
+
 #[inline]
 pub fn default_title_placeholder() -> Option<String> {
     Some(String::from("▲"))
@@ -72,6 +140,21 @@ pub fn default_opacity() -> f32 {
     1.0
 }
 
+#[inline]
+pub fn default_hdr_brightness() -> f32 {
+    1.0
+}
+
+#[inline]
+pub fn default_opacity_modifiers() -> String {
+    String::from("control|alt")
+}
+
+#[inline]
+pub fn default_chord_timeout() -> u64 {
+    1000
+}
+
 #[inline]
 pub fn default_option_as_alt() -> String {
     String::from("none")
@@ -82,6 +165,11 @@ pub fn default_log_level() -> String {
     String::from("OFF")
 }
 
+#[inline]
+pub fn default_startup_budget() -> u64 {
+    1000
+}
+
 #[inline]
 pub fn default_cursor() -> CursorShape {
     CursorShape::default()
@@ -149,6 +237,16 @@ pub fn default_config_file_content() -> String {
 # Example:
 # ignore-selection-foreground-color = false
 
+# Copy visual layout
+#
+# Copying a selection joins soft-wrapped lines, set it to true to keep
+# a line break at the end of every visual line instead.
+#
+# Default is false
+#
+# Example:
+# copy-visual-layout = false
+
 # Theme
 #
 # It makes Rio look for the specified theme in the themes folder
@@ -182,6 +280,14 @@ pub fn default_config_file_content() -> String {
 # Example:
 # option-as-alt = 'left'
 
+# Language
+#
+# Language of the screens shown by Rio, like the welcome screen. If this
+# is unset it is detected from the system locale.
+#
+# Example:
+# language = "pt-BR"
+
 # Line height
 #
 # This option will apply an modifier to line-height
@@ -200,6 +306,14 @@ pub fn default_config_file_content() -> String {
 # Example:
 # working-dir = "/Users/raphael/Documents/"
 
+# Screenshot directory
+#
+# Directory where the Screenshot and export actions write their files.
+# If this is unset the pictures directory of the user is used.
+#
+# Example:
+# screenshot-dir = "/Users/raphael/Pictures/rio"
+
 # Environment variables
 #
 # Example:
@@ -228,10 +342,21 @@ pub fn default_config_file_content() -> String {
 #
 # blinking-interval - Cursor update on milliseconds interval
 #
+# beam-width - Width of the beam cursor. Values lower than 1 are
+# a fraction of the cell width, otherwise pixels. The default is 3
+#
+# underline-height - Height of the underline cursor. Values lower than 1
+# are a fraction of the cell height, otherwise pixels. The default is 3
+#
+# unfocused-glyph - Glyph drawn as the cursor of unfocused windows,
+# by default a hollow block is drawn
+#
 # [cursor]
 # shape = 'block'
 # blinking = false
 # blinking-interval = 800
+# beam-width = 3
+# underline-height = 3
 
 # Editor
 #
@@ -278,6 +403,8 @@ pub fn default_config_file_content() -> String {
 #   - High: Adapter that has the highest performance. This is often a discrete GPU.
 #   - Low: Adapter that uses the least possible power. This is often an integrated GPU.
 #
+# • adapter: Name (or part of it) of the graphics adapter to use.
+#
 # • Backend: Set WGPU rendering backend
 #   - Automatic: Leave Sugarloaf/WGPU to decide
 #   - GL: Supported on Linux/Android, and Windows and macOS/iOS via ANGLE
@@ -287,21 +414,45 @@ pub fn default_config_file_content() -> String {
 #
 # • disable-unfocused-render: This property disable renderer processes while Rio is unfocused.
 #
+# • max-fps: Caps the frame rate, it never goes above the display refresh rate.
+#
+# • present-mode: How frames are presented to the display.
+#   - Available options: "fifo" (vsync), "mailbox" and "immediate".
+#
 # • level: Configure renderer level
 #   - Available options: 0 and 1.
 #       Higher the level more rendering features and computations
 #       will be done like enable font ligatures or emoji support.
 #       For more information please check the docs.
 #
-# • filters: A list of paths to RetroArch slang shaders. Might not work with OpenGL.
+# • filters: A list of paths to RetroArch slang shaders or WGSL shaders.
+#       Might not work with OpenGL.
+#
+# • battery-saver: Caps the frame rate and disables animations and blur
+#   - Available options: "never", "auto" (only on battery) and "always".
+#
+# • hdr: Requests an extended range surface (macOS and Windows only).
+#
+# • colorspace: Color space of the colors when hdr is enabled.
+#   - Available options: "srgb" and "display-p3".
+#
+# • hdr-brightness: Multiplier of the brightness when hdr is enabled,
+#   values above 1.0 go beyond the SDR white.
 #
 # Example:
 # [renderer]
 # performance = "high"
+# adapter = "intel"
 # backend = "automatic"
 # disable-unfocused-render = false
+# max-fps = 60
+# present-mode = "fifo"
 # level = 1
 # filters = []
+# battery-saver = "never"
+# hdr = false
+# colorspace = "srgb"
+# hdr-brightness = 1.0
 
 # Keyboard
 #
@@ -310,10 +461,18 @@ pub fn default_config_file_content() -> String {
 # disable-ctlseqs-alt - Disable ctlseqs with ALT keys
 #   - For example: Terminal.app does not deal with ctlseqs with ALT keys
 #
+# option-as-alt - Which Option keys are treated as Alt on MacOS
+#   - Possible choices: 'left', 'right', 'both' and 'none'
+#
+# chord-timeout - Milliseconds to wait for the next key of a
+#   binding with a leader (default is 1000)
+#
 # Example:
 # [keyboard]
 # use-kitty-keyboard-protocol = false
 # disable-ctlseqs-alt = false
+# option-as-alt = 'left'
+# chord-timeout = 1000
 
 # Fonts
 #
@@ -382,10 +541,12 @@ pub fn default_config_file_content() -> String {
 #
 # Multiplier default is 3.0.
 # Divider default is 1.0.
+# History is the maximum number of lines in the scrollback, default is 10000.
 # Example:
 # [scroll]
 # multiplier = 3.0
 # divider = 1.0
+# history = 10000
 
 # Navigation
 #
@@ -502,5 +663,15 @@ pub fn default_config_file_content() -> String {
 # [developer]
 # log-level = "OFF"
 # enable-log-file = false
+#
+# Milliseconds the startup may take before a warning with the time spent
+# in each phase is logged.
+#
+# startup-budget = 1000
+#
+# Keeps cache hit rates and rebuild counts per minute in memory, they are
+# written to the log directory by the DumpMetrics action.
+#
+# enable-metrics = false
 "#.to_string()
 }
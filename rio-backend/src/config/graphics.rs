@@ -0,0 +1,26 @@
+use crate::config::defaults::default_graphics_max_memory;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Graphics {
+    /// Memory, in MiB, taken by the images of each terminal before the
+    /// least recently used are evicted.
+    #[serde(default = "default_graphics_max_memory", rename = "max-memory")]
+    pub max_memory: usize,
+}
+
+impl Default for Graphics {
+    fn default() -> Self {
+        Self {
+            max_memory: default_graphics_max_memory(),
+        }
+    }
+}
+
+impl Graphics {
+    #[inline]
+    pub fn max_memory_bytes(&self) -> usize {
+        self.max_memory.saturating_mul(1024 * 1024)
+    }
+}
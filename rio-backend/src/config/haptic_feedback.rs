@@ -0,0 +1,13 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Trackpad feedback played on macOS, every event is off by default.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct HapticFeedback {
+    /// When a double or triple click selection snaps to a word or a line.
+    #[serde(default)]
+    pub selection: bool,
+    /// When scrolling reaches the top or the bottom of the scrollback.
+    #[serde(default)]
+    pub scrollback: bool,
+}
@@ -0,0 +1,57 @@
+use crate::config::defaults::default_hints_alphabet;
+use crate::config::Shell;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sugarloaf::schema_aliases;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Hints {
+    /// Characters the hint labels are made of, the first ones are used
+    /// first.
+    #[serde(default = "default_hints_alphabet")]
+    pub alphabet: String,
+    /// Patterns labeled besides urls and paths.
+    #[serde(default = "Vec::default")]
+    #[schemars(default)]
+    pub patterns: Vec<HintPattern>,
+}
+
+impl Default for Hints {
+    fn default() -> Self {
+        Self {
+            alphabet: default_hints_alphabet(),
+            patterns: Vec::default(),
+        }
+    }
+}
+
+/// What is done with the match picked in hint mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[schemars(transform = schema_aliases("open copy paste"))]
+pub enum HintAction {
+    /// Open the match with the system opener.
+    #[serde(alias = "open")]
+    Open,
+    /// Copy the match to the clipboard.
+    #[serde(alias = "copy")]
+    Copy,
+    /// Write the match to the terminal, like a paste.
+    #[serde(alias = "paste")]
+    Paste,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct HintPattern {
+    /// Regular expression labeled on the screen.
+    pub regex: String,
+    /// Action of the picked matches, the one of the key binding when unset.
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub action: Option<HintAction>,
+    /// Program run with the picked match, `$0` in its arguments is replaced
+    /// by the matched text and `$1`, `$2`... by the capture groups. Takes
+    /// precedence over the action.
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub command: Option<Shell>,
+}
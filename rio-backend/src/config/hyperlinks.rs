@@ -0,0 +1,41 @@
+use crate::config::defaults::default_hyperlink_hover_modifiers;
+use crate::config::Shell;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Hyperlinks {
+    /// Modifiers held to underline the hovered hyperlink, in the format of
+    /// the key bindings `with`, e.g. `"super | shift"`. `"none"` underlines
+    /// hyperlinks whenever they are hovered.
+    #[serde(
+        default = "default_hyperlink_hover_modifiers",
+        rename = "hover-modifiers"
+    )]
+    pub hover_modifiers: String,
+    /// Patterns detected as hyperlinks besides urls.
+    #[serde(default = "Vec::default")]
+    #[schemars(default)]
+    pub patterns: Vec<HyperlinkPattern>,
+}
+
+impl Default for Hyperlinks {
+    fn default() -> Self {
+        Self {
+            hover_modifiers: default_hyperlink_hover_modifiers(),
+            patterns: Vec::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct HyperlinkPattern {
+    /// Regular expression matched against the hovered word.
+    pub regex: String,
+    /// Program opening the match, `$0` in its arguments is replaced by the
+    /// matched text and `$1`, `$2`... by the capture groups. Matches
+    /// without a command are opened like urls.
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub command: Option<Shell>,
+}
@@ -1,8 +1,10 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use super::defaults::default_disable_ctlseqs_alt;
+use super::defaults::{default_chord_timeout, default_disable_ctlseqs_alt};
+use sugarloaf::schema_aliases;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, JsonSchema)]
 pub struct Keyboard {
     // Disable ctlseqs with ALT keys
     // For example: Terminal.app does not deal with ctlseqs with ALT keys
@@ -11,6 +13,51 @@ pub struct Keyboard {
         rename = "disable-ctlseqs-alt"
     )]
     pub disable_ctlseqs_alt: bool,
+    // Which `Option` keys are treated as `Alt` on macOS, the other one
+    // keeps composing special characters
+    #[serde(default, rename = "option-as-alt")]
+    pub option_as_alt: Option<OptionAsAlt>,
+    // Milliseconds to wait for the next key of a multi-step binding
+    #[serde(default = "default_chord_timeout", rename = "chord-timeout")]
+    pub chord_timeout: u64,
+}
+
+/// `Option` keys treated as `Alt`, only used on macOS.
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy, JsonSchema)]
+#[schemars(transform = schema_aliases("left right both none"))]
+pub enum OptionAsAlt {
+    #[serde(alias = "left")]
+    Left,
+    #[serde(alias = "right")]
+    Right,
+    #[serde(alias = "both")]
+    Both,
+    #[default]
+    #[serde(alias = "none")]
+    None,
+}
+
+impl OptionAsAlt {
+    /// Parses the value of the top level `option-as-alt`.
+    pub fn from_name(name: &str) -> OptionAsAlt {
+        match name.to_lowercase().as_str() {
+            "left" => OptionAsAlt::Left,
+            "right" => OptionAsAlt::Right,
+            "both" => OptionAsAlt::Both,
+            _ => OptionAsAlt::None,
+        }
+    }
+
+    /// Whether the `Option` key on that side is treated as `Alt`.
+    #[inline]
+    pub fn is_alt(self, is_left: bool, is_right: bool) -> bool {
+        match self {
+            OptionAsAlt::Both => is_left || is_right,
+            OptionAsAlt::Left => is_left,
+            OptionAsAlt::Right => is_right,
+            OptionAsAlt::None => false,
+        }
+    }
 }
 
 #[allow(clippy::derivable_impls)]
@@ -21,6 +68,8 @@ impl Default for Keyboard {
             disable_ctlseqs_alt: true,
             #[cfg(not(target_os = "macos"))]
             disable_ctlseqs_alt: false,
+            option_as_alt: None,
+            chord_timeout: default_chord_timeout(),
         }
     }
 }
@@ -1,22 +1,43 @@
+pub mod bell;
 pub mod bindings;
+pub mod clipboard;
 pub mod colors;
 pub mod defaults;
+pub mod graphics;
+pub mod haptic_feedback;
+pub mod hints;
+pub mod hyperlinks;
 pub mod keyboard;
 pub mod navigation;
+pub mod paste;
+pub mod profiles;
 pub mod renderer;
+pub mod selection;
 pub mod theme;
 pub mod title;
+pub mod unicode;
 pub mod window;
 
 use crate::ansi::CursorShape;
+use crate::config::bell::Bell;
 use crate::config::bindings::Bindings;
+use crate::config::clipboard::Clipboard;
 use crate::config::defaults::*;
-use crate::config::keyboard::Keyboard;
+use crate::config::graphics::Graphics;
+use crate::config::haptic_feedback::HapticFeedback;
+use crate::config::hints::Hints;
+use crate::config::hyperlinks::Hyperlinks;
+use crate::config::keyboard::{Keyboard, OptionAsAlt};
 use crate::config::navigation::Navigation;
+use crate::config::paste::Paste;
+use crate::config::profiles::Profile;
 use crate::config::renderer::Renderer;
+use crate::config::selection::Selection;
 use crate::config::title::Title;
+use crate::config::unicode::Unicode;
 use crate::config::window::Window;
 use colors::Colors;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::io::Write;
 use std::path::PathBuf;
@@ -32,14 +53,14 @@ pub enum ConfigError {
     PathNotFound,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct Shell {
     pub program: String,
     #[serde(default)]
     pub args: Vec<String>,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct Platform {
     pub linux: Option<PlatformConfig>,
     pub windows: Option<PlatformConfig>,
@@ -47,7 +68,7 @@ pub struct Platform {
 }
 
 /// Other platform specific configuration options can be added here.
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct PlatformConfig {
     shell: Option<Shell>,
     navigation: Option<Navigation>,
@@ -55,22 +76,49 @@ pub struct PlatformConfig {
     renderer: Option<Renderer>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct Scroll {
+    #[serde(default = "default_scroll_multiplier")]
     pub multiplier: f64,
+    #[serde(default = "default_scroll_divider")]
     pub divider: f64,
+    /// Maximum number of lines kept in the scrollback history.
+    #[serde(default = "default_scroll_history")]
+    pub history: usize,
+    /// Whether a hint is shown when scrolling does nothing because an
+    /// application owns the alternate screen.
+    #[serde(default = "default_bool_true", rename = "alternate-screen-hint")]
+    pub alternate_screen_hint: bool,
+    /// Whether typing brings the viewport back to the bottom.
+    #[serde(default = "default_bool_true", rename = "on-input")]
+    pub on_input: bool,
+    /// Whether pasting brings the viewport back to the bottom.
+    #[serde(default = "default_bool_true", rename = "on-paste")]
+    pub on_paste: bool,
+    /// Whether new output brings the viewport back to the bottom.
+    #[serde(default = "bool::default", rename = "on-output")]
+    pub on_output: bool,
+    /// Whether a "new output below" indicator is shown while scrolled up.
+    #[serde(default = "default_bool_true", rename = "output-indicator")]
+    pub output_indicator: bool,
 }
 
 impl Default for Scroll {
     fn default() -> Scroll {
         Scroll {
-            multiplier: 3.0,
-            divider: 1.0,
+            multiplier: default_scroll_multiplier(),
+            divider: default_scroll_divider(),
+            history: default_scroll_history(),
+            alternate_screen_hint: true,
+            on_input: true,
+            on_paste: true,
+            on_output: false,
+            output_indicator: true,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Developer {
     #[serde(default = "bool::default", rename = "enable-fps-counter")]
     pub enable_fps_counter: bool,
@@ -78,6 +126,10 @@ pub struct Developer {
     pub log_level: String,
     #[serde(rename = "enable-log-file", default)]
     pub enable_log_file: bool,
+    #[serde(default = "default_startup_budget", rename = "startup-budget")]
+    pub startup_budget: u64,
+    #[serde(rename = "enable-metrics", default)]
+    pub enable_metrics: bool,
 }
 
 impl Default for Developer {
@@ -86,11 +138,13 @@ impl Default for Developer {
             log_level: default_log_level(),
             enable_log_file: false,
             enable_fps_counter: false,
+            startup_budget: default_startup_budget(),
+            enable_metrics: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct Config {
     #[serde(default)]
     pub cursor: CursorConfig,
@@ -114,7 +168,7 @@ pub struct Config {
     pub line_height: f32,
     #[serde(default = "String::default")]
     pub theme: String,
-    #[serde(default = "Scroll::default")]
+    #[serde(default = "Scroll::default", alias = "scrolling")]
     pub scroll: Scroll,
     #[serde(
         default = "Option::default",
@@ -131,12 +185,14 @@ pub struct Config {
     #[serde(rename = "padding-y", default = "default_padding_y")]
     pub padding_y: [f32; 2],
     #[serde(default = "Vec::default", rename = "env-vars")]
+    #[schemars(default)]
     pub env_vars: Vec<String>,
     #[serde(default = "default_option_as_alt", rename = "option-as-alt")]
     pub option_as_alt: String,
     #[serde(default = "Colors::default", skip_serializing)]
     pub colors: Colors,
     #[serde(default = "Option::default", skip_serializing)]
+    #[schemars(skip)]
     pub adaptive_colors: Option<AdaptiveColors>,
     #[serde(default = "Developer::default")]
     pub developer: Developer,
@@ -147,6 +203,8 @@ pub struct Config {
         rename = "ignore-selection-foreground-color"
     )]
     pub ignore_selection_fg_color: bool,
+    #[serde(default = "bool::default", rename = "copy-visual-layout")]
+    pub copy_visual_layout: bool,
     #[serde(default = "default_bool_true", rename = "confirm-before-quit")]
     pub confirm_before_quit: bool,
     #[serde(
@@ -159,9 +217,38 @@ pub struct Config {
     pub renderer: Renderer,
     #[serde(default = "bool::default", rename = "draw-bold-text-with-light-colors")]
     pub draw_bold_text_with_light_colors: bool,
+    #[serde(default = "Vec::default")]
+    #[schemars(default)]
+    pub profiles: Vec<Profile>,
+    #[serde(default = "bool::default", rename = "password-input-indicator")]
+    pub password_input_indicator: bool,
+    #[serde(default = "Option::default", rename = "screenshot-dir")]
+    #[schemars(default)]
+    pub screenshot_dir: Option<String>,
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub language: Option<String>,
+    #[serde(default = "Unicode::default")]
+    pub unicode: Unicode,
+    #[serde(default = "Graphics::default")]
+    pub graphics: Graphics,
+    #[serde(default = "Hyperlinks::default")]
+    pub hyperlinks: Hyperlinks,
+    #[serde(default = "Hints::default")]
+    pub hints: Hints,
+    #[serde(default = "Selection::default")]
+    pub selection: Selection,
+    #[serde(default = "Clipboard::default")]
+    pub clipboard: Clipboard,
+    #[serde(default = "Paste::default")]
+    pub paste: Paste,
+    #[serde(default = "Bell::default")]
+    pub bell: Bell,
+    #[serde(default = "HapticFeedback::default", rename = "haptic-feedback")]
+    pub haptic_feedback: HapticFeedback,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct CursorConfig {
     #[serde(default = "default_cursor")]
     pub shape: CursorShape,
@@ -169,6 +256,13 @@ pub struct CursorConfig {
     pub blinking: bool,
     #[serde(default = "default_cursor_interval", rename = "blinking-interval")]
     pub blinking_interval: u64,
+    #[serde(default = "default_cursor_thickness", rename = "beam-width")]
+    pub beam_width: f32,
+    #[serde(default = "default_cursor_thickness", rename = "underline-height")]
+    pub underline_height: f32,
+    #[serde(default = "Option::default", rename = "unfocused-glyph")]
+    #[schemars(default)]
+    pub unfocused_glyph: Option<char>,
 }
 
 #[cfg(target_os = "macos")]
@@ -216,6 +310,13 @@ pub fn config_file_content() -> String {
     default_config_file_content()
 }
 
+/// JSON Schema of the config file, generated from the structures it's
+/// deserialized into.
+pub fn config_schema() -> String {
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_string_pretty(&schema).unwrap_or_default()
+}
+
 #[inline]
 pub fn create_config_file(path: Option<PathBuf>) {
     let default_file_path = path.clone().unwrap_or(config_file_path());
@@ -262,6 +363,69 @@ pub fn create_config_file(path: Option<PathBuf>) {
     }
 }
 
+/// Sets the option at the dotted `key`, like `fonts.size`, in the config
+/// file, the comments and formatting of the rest of the file are kept.
+pub fn write_config_value(
+    path: &std::path::Path,
+    key: &str,
+    value: impl Into<toml_edit::Value>,
+) -> std::io::Result<()> {
+    let mut value = value.into();
+    edit_config_file(path, key, |table, name| match table.get_mut(name) {
+        // The comment after the value stays
+        Some(toml_edit::Item::Value(current)) => {
+            *value.decor_mut() = current.decor().clone();
+            *current = value;
+        }
+        _ => {
+            table.insert(name, toml_edit::Item::Value(value));
+        }
+    })
+}
+
+/// Removes the option at the dotted `key` from the config file, so its
+/// default is used.
+pub fn remove_config_value(path: &std::path::Path, key: &str) -> std::io::Result<()> {
+    edit_config_file(path, key, |table, name| {
+        table.remove(name);
+    })
+}
+
+/// Calls `edit` with the table holding the last part of the dotted `key`
+/// and that part, the missing tables are created.
+fn edit_config_file(
+    path: &std::path::Path,
+    key: &str,
+    edit: impl FnOnce(&mut dyn toml_edit::TableLike, &str),
+) -> std::io::Result<()> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let mut document = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(std::io::Error::other)?;
+
+    let keys: Vec<&str> = key.split('.').collect();
+    let Some((name, tables)) = keys.split_last() else {
+        return Ok(());
+    };
+    let mut table: &mut dyn toml_edit::TableLike = document.as_table_mut();
+    for table_name in tables {
+        table = table
+            .entry(table_name)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .ok_or_else(|| {
+                std::io::Error::other(format!("`{table_name}` is not a table"))
+            })?;
+    }
+    edit(table, name);
+
+    std::fs::write(path, document.to_string())
+}
+
 impl Config {
     #[cfg(test)]
     fn load_from_path(path: &PathBuf) -> Self {
@@ -343,6 +507,57 @@ impl Config {
         }
     }
 
+    /// Creates a copy of the configuration with the profile overrides applied.
+    pub fn with_profile(&self, profile: &Profile) -> Config {
+        let mut config = self.clone();
+
+        if let Some(theme) = &profile.theme {
+            let path = config_dir_path()
+                .join("themes")
+                .join(theme)
+                .with_extension("toml");
+            match Config::load_theme(&path) {
+                Ok(loaded_theme) => {
+                    config.colors = loaded_theme.colors;
+                    config.adaptive_colors = None;
+                }
+                Err(err_message) => {
+                    warn!("failed to load profile theme {theme}: {err_message}");
+                }
+            }
+        }
+
+        if let Some(title) = &profile.title {
+            config.title.content = title.to_owned();
+        }
+
+        if let Some(cursor) = &profile.cursor {
+            config.cursor = cursor.to_owned();
+        }
+
+        if let Some(scroll) = &profile.scroll {
+            config.scroll = scroll.to_owned();
+        }
+
+        config
+    }
+
+    /// Line height multiplier, `fonts.line-height` takes precedence over the
+    /// top level `line-height`.
+    #[inline]
+    pub fn line_height(&self) -> f32 {
+        self.fonts.line_height.unwrap_or(self.line_height)
+    }
+
+    /// `Option` keys treated as `Alt`, `keyboard.option-as-alt` takes
+    /// precedence over the top level `option-as-alt`.
+    #[inline]
+    pub fn option_as_alt(&self) -> OptionAsAlt {
+        self.keyboard
+            .option_as_alt
+            .unwrap_or_else(|| OptionAsAlt::from_name(&self.option_as_alt))
+    }
+
     pub fn to_string(&self) -> Result<String, toml::ser::Error> {
         toml::to_string(self)
     }
@@ -525,9 +740,23 @@ impl Default for Config {
             window: Window::default(),
             working_dir: default_working_dir(),
             ignore_selection_fg_color: false,
+            copy_visual_layout: false,
             confirm_before_quit: true,
             hide_cursor_when_typing: false,
             draw_bold_text_with_light_colors: false,
+            profiles: Vec::default(),
+            password_input_indicator: false,
+            screenshot_dir: None,
+            language: None,
+            unicode: Unicode::default(),
+            graphics: Graphics::default(),
+            hyperlinks: Hyperlinks::default(),
+            selection: Selection::default(),
+            clipboard: Clipboard::default(),
+            paste: Paste::default(),
+            bell: Bell::default(),
+            haptic_feedback: HapticFeedback::default(),
+            hints: Hints::default(),
         }
     }
 }
@@ -538,6 +767,9 @@ impl Default for CursorConfig {
             shape: default_cursor(),
             blinking: false,
             blinking_interval: default_cursor_interval(),
+            beam_width: default_cursor_thickness(),
+            underline_height: default_cursor_thickness(),
+            unfocused_glyph: None,
         }
     }
 }
@@ -545,6 +777,7 @@ impl Default for CursorConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clipboard::{ClipboardBackend, Osc52};
     use colors::{hex_to_color_arr, hex_to_color_wgpu};
     use std::io::Write;
     use sugarloaf::font::fonts::parse_unicode;
@@ -739,6 +972,54 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_change_config_cursor_thickness() {
+        let result = create_temporary_config(
+            "change-cursor-thickness",
+            r#"
+            [cursor]
+            shape = 'beam'
+            beam-width = 0.1
+            unfocused-glyph = '_'
+        "#,
+        );
+
+        assert_eq!(result.cursor.shape, CursorShape::Beam);
+        assert_eq!(result.cursor.beam_width, 0.1);
+        assert_eq!(result.cursor.underline_height, default_cursor_thickness());
+        assert_eq!(result.cursor.unfocused_glyph, Some('_'));
+        assert_eq!(CursorConfig::default().unfocused_glyph, None);
+    }
+
+    #[test]
+    fn test_language() {
+        let result = create_temporary_config(
+            "language",
+            r#"
+            language = 'pt-BR'
+        "#,
+        );
+
+        assert_eq!(result.language, Some(String::from("pt-BR")));
+        assert_eq!(Config::default().language, None);
+    }
+
+    #[test]
+    fn test_screenshot_dir() {
+        let result = create_temporary_config(
+            "screenshot-dir",
+            r#"
+            screenshot-dir = '/tmp/rio-screenshots'
+        "#,
+        );
+
+        assert_eq!(
+            result.screenshot_dir,
+            Some(String::from("/tmp/rio-screenshots"))
+        );
+        assert_eq!(Config::default().screenshot_dir, None);
+    }
+
     #[test]
     fn test_change_option_as_alt() {
         let result = create_temporary_config(
@@ -750,6 +1031,7 @@ mod tests {
 
         assert_eq!(result.renderer.performance, renderer::Performance::High);
         assert_eq!(result.option_as_alt, String::from("Both"));
+        assert_eq!(result.option_as_alt(), OptionAsAlt::Both);
         assert_eq!(result.fonts, SugarloafFonts::default());
         assert_eq!(result.theme, String::default());
         // Colors
@@ -759,6 +1041,168 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_keyboard_option_as_alt() {
+        let result = create_temporary_config(
+            "keyboard-option-as-alt",
+            r#"
+            option-as-alt = 'both'
+
+            [keyboard]
+            option-as-alt = 'left'
+        "#,
+        );
+
+        assert_eq!(result.keyboard.option_as_alt, Some(OptionAsAlt::Left));
+        assert_eq!(result.option_as_alt(), OptionAsAlt::Left);
+        assert_eq!(Config::default().option_as_alt(), OptionAsAlt::None);
+        assert!(OptionAsAlt::Right.is_alt(false, true));
+        assert!(!OptionAsAlt::Right.is_alt(true, false));
+    }
+
+    #[test]
+    fn test_config_schema() {
+        let schema: serde_json::Value = serde_json::from_str(&config_schema()).unwrap();
+
+        assert!(schema["properties"]["keyboard"].is_object());
+        assert!(schema["properties"].get("adaptive_colors").is_none());
+        assert_eq!(
+            schema["$defs"]["OptionAsAlt"]["enum"],
+            serde_json::json!([
+                "Left", "Right", "Both", "None", "left", "right", "both", "none"
+            ])
+        );
+        assert_eq!(
+            schema["$defs"]["HintAction"]["oneOf"][3]["enum"],
+            serde_json::json!(["open", "copy", "paste"])
+        );
+        assert_eq!(
+            schema["$defs"]["Colors"]["properties"]["background"]["default"],
+            "#0f0d0e"
+        );
+        assert_eq!(
+            schema["$defs"]["Colors"]["properties"]["foreground"]["default"],
+            "#ffffff"
+        );
+    }
+
+    /// Checks `value` against the parts of JSON Schema the config schema
+    /// uses, pushing the path of each invalid value to `errors`.
+    fn validate_schema(
+        root: &serde_json::Value,
+        schema: &serde_json::Value,
+        value: &serde_json::Value,
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        use serde_json::Value;
+
+        let matches = |schema: &Value| {
+            let mut errors = Vec::new();
+            validate_schema(root, schema, value, path, &mut errors);
+            errors.is_empty()
+        };
+
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            validate_schema(root, &root["$defs"][name], value, path, errors);
+        }
+        if let Some(values) = schema["enum"].as_array() {
+            if !values.contains(value) {
+                errors.push(format!("{path}: {value} is not one of {values:?}"));
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if constant != value {
+                errors.push(format!("{path}: {value} is not {constant}"));
+            }
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(schemas) = schema[keyword].as_array() {
+                if !schemas.iter().any(matches) {
+                    errors.push(format!("{path}: {value} matches no {keyword}"));
+                }
+            }
+        }
+        if let Some(schemas) = schema["allOf"].as_array() {
+            for schema in schemas {
+                validate_schema(root, schema, value, path, errors);
+            }
+        }
+
+        let types: Vec<&str> = match &schema["type"] {
+            Value::String(name) => vec![name],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let has_type = |name: &str| match name {
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            "null" => value.is_null(),
+            _ => true,
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(name)) {
+            errors.push(format!("{path}: {value} is not {types:?}"));
+        }
+
+        match value {
+            Value::Object(fields) => {
+                for (key, field) in fields {
+                    let path = format!("{path}.{key}");
+                    if let Some(schema) = schema["properties"].get(key) {
+                        validate_schema(root, schema, field, &path, errors);
+                    } else if let Some(schema) = schema
+                        .get("additionalProperties")
+                        .filter(|schema| schema.is_object())
+                    {
+                        validate_schema(root, schema, field, &path, errors);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        let path = format!("{path}[{index}]");
+                        validate_schema(root, schema, item, &path, errors);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_config_schema_docs() {
+        let schema: serde_json::Value = serde_json::from_str(&config_schema()).unwrap();
+        let docs = include_str!("../../../docs/docs/config.md");
+
+        let mut errors = Vec::new();
+        let mut examples = 0;
+        for (index, block) in docs.split("```toml").skip(1).enumerate() {
+            let example = block.split("```").next().unwrap();
+            let value: serde_json::Value = match toml::from_str(example) {
+                Ok(value) => value,
+                // Placeholders like `<name>` aren't config files
+                Err(_) => continue,
+            };
+            examples += 1;
+            validate_schema(
+                &schema,
+                &schema,
+                &value,
+                &format!("example {index}"),
+                &mut errors,
+            );
+        }
+
+        assert!(examples > 50);
+        assert!(errors.is_empty(), "{errors:#?}");
+    }
+
     #[test]
     fn test_change_config_width_height() {
         let result = create_temporary_config(
@@ -832,8 +1276,10 @@ mod tests {
         assert_eq!(result.renderer.performance, renderer::Performance::Low);
         assert_eq!(result.fonts.size, 14.0);
         assert_eq!(result.line_height, 2.0);
+        assert_eq!(result.line_height(), 2.0);
         assert_eq!(result.padding_x, 0.0);
         assert_eq!(result.window.opacity, 0.5);
+        assert_eq!(result.window.opacity_modifiers, "control|alt");
         assert_eq!(
             result.window.background_image,
             Some(sugarloaf::ImageProperties {
@@ -848,6 +1294,154 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_background_image_fit() {
+        let result = create_temporary_config(
+            "background-image-fit",
+            r#"
+            [window.background-image]
+            path = "my-image-path.png"
+            fit = "cover"
+            opacity = 0.3
+        "#,
+        );
+
+        assert_eq!(
+            result.window.background_image,
+            Some(sugarloaf::ImageProperties {
+                path: String::from("my-image-path.png"),
+                fit: sugarloaf::ImageFit::Cover,
+                opacity: 0.3,
+                ..sugarloaf::ImageProperties::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_battery_saver() {
+        let result = create_temporary_config(
+            "battery-saver",
+            r#"
+            [renderer]
+            battery-saver = "auto"
+        "#,
+        );
+
+        assert_eq!(
+            result.renderer.battery_saver,
+            crate::config::renderer::BatterySaver::Auto
+        );
+        assert_eq!(
+            Config::default().renderer.battery_saver,
+            crate::config::renderer::BatterySaver::Never
+        );
+    }
+
+    #[test]
+    fn test_renderer_hdr() {
+        let result = create_temporary_config(
+            "renderer-hdr",
+            r#"
+            [renderer]
+            hdr = true
+            colorspace = "display-p3"
+            hdr-brightness = 2.5
+        "#,
+        );
+
+        assert!(result.renderer.hdr);
+        assert_eq!(
+            result.renderer.colorspace,
+            crate::config::renderer::Colorspace::DisplayP3
+        );
+        assert_eq!(result.renderer.hdr_brightness, 2.5);
+        assert_eq!(Config::default().renderer.hdr_brightness, 1.0);
+    }
+
+    #[test]
+    fn test_scroll_history() {
+        let result = create_temporary_config(
+            "scroll-history",
+            r#"
+            [scrolling]
+            history = 2000
+
+            [[profiles]]
+            name = "logs"
+            when = { command = "tail" }
+            scroll = { history = 50000 }
+        "#,
+        );
+
+        assert_eq!(result.scroll.history, 2000);
+        assert_eq!(result.scroll.multiplier, 3.0);
+        assert_eq!(result.scroll.divider, 1.0);
+        assert!(result.scroll.alternate_screen_hint);
+
+        let profile_config = result.with_profile(&result.profiles[0]);
+        assert_eq!(profile_config.scroll.history, 50000);
+        assert_eq!(Config::default().scroll.history, 10_000);
+        assert!(Config::default().scroll.alternate_screen_hint);
+    }
+
+    #[test]
+    fn test_scroll_to_bottom() {
+        let result = create_temporary_config(
+            "scroll-to-bottom",
+            r#"
+            [scroll]
+            on-input = false
+            on-output = true
+            output-indicator = false
+        "#,
+        );
+
+        assert!(!result.scroll.on_input);
+        assert!(result.scroll.on_paste);
+        assert!(result.scroll.on_output);
+        assert!(!result.scroll.output_indicator);
+
+        let default = Config::default().scroll;
+        assert!(default.on_input && default.on_paste && default.output_indicator);
+        assert!(!default.on_output);
+    }
+
+    #[test]
+    fn test_renderer_adapter() {
+        let result = create_temporary_config(
+            "renderer-adapter",
+            r#"
+            [renderer]
+            power-preference = "low-power"
+            adapter = "Intel"
+        "#,
+        );
+
+        assert_eq!(
+            result.renderer.performance,
+            crate::config::renderer::Performance::Low
+        );
+        assert_eq!(result.renderer.adapter, Some(String::from("Intel")));
+    }
+
+    #[test]
+    fn test_renderer_frame_rate() {
+        let result = create_temporary_config(
+            "renderer-frame-rate",
+            r#"
+            [renderer]
+            max-fps = 60
+            present-mode = "mailbox"
+        "#,
+        );
+
+        assert_eq!(result.renderer.max_fps, Some(60));
+        assert_eq!(
+            result.renderer.present_mode,
+            crate::config::renderer::PresentMode::Mailbox
+        );
+    }
+
     #[test]
     fn test_change_theme() {
         let result = create_temporary_config(
@@ -912,6 +1506,28 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_window_border() {
+        let result = create_temporary_config(
+            "window-border",
+            r#"
+            [window]
+            border-width = 2
+
+            [colors]
+            window-border = '#ffffff'
+        "#,
+        );
+
+        assert_eq!(result.window.border_width, 2.0);
+        assert_eq!(result.colors.window_border, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(
+            result.colors.window_border_unfocused,
+            colors::defaults::window_border_unfocused()
+        );
+        assert_eq!(Config::default().window.border_width, 0.0);
+    }
+
     #[test]
     fn test_change_colors() {
         let result = create_temporary_config(
@@ -1048,6 +1664,8 @@ mod tests {
             [developer]
             enable-fps-counter = true
             log-level = "INFO"
+            startup-budget = 250
+            enable-metrics = true
         "#,
         );
 
@@ -1056,6 +1674,8 @@ mod tests {
         // Developer
         assert_eq!(result.developer.log_level, String::from("INFO"));
         assert!(result.developer.enable_fps_counter);
+        assert_eq!(result.developer.startup_budget, 250);
+        assert!(result.developer.enable_metrics);
 
         // Colors
         assert_eq!(result.colors.background, colors::defaults::background());
@@ -1064,6 +1684,409 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_font_feature_toggles() {
+        let result = create_temporary_config(
+            "font-feature-toggles",
+            r#"
+            [fonts]
+            features = ["ss02", "zero"]
+            slashed-zero = true
+            tabular-numbers = true
+        "#,
+        );
+
+        assert!(result.fonts.slashed_zero);
+        assert!(result.fonts.tabular_numbers);
+        assert_eq!(
+            result.fonts.shaping_features(),
+            Some(vec![
+                "ss02".to_string(),
+                "zero".to_string(),
+                "tnum".to_string()
+            ])
+        );
+
+        let result = create_temporary_config("font-feature-toggles-default", "");
+        assert!(!result.fonts.slashed_zero);
+        assert_eq!(result.fonts.shaping_features(), None);
+    }
+
+    #[test]
+    fn test_font_line_height_and_baseline_offset() {
+        let result = create_temporary_config(
+            "font-line-height",
+            r#"
+            line-height = 1.2
+
+            [fonts]
+            line-height = 1.5
+            baseline-offset = -2
+        "#,
+        );
+
+        assert_eq!(result.line_height(), 1.5);
+        assert_eq!(result.fonts.baseline_offset, -2.0);
+
+        let result = create_temporary_config("font-line-height-default", "");
+        assert_eq!(result.line_height(), default_line_height());
+        assert_eq!(result.fonts.baseline_offset, 0.0);
+        assert!(result.fonts.snap_to_pixels);
+
+        let result = create_temporary_config(
+            "font-snap-to-pixels",
+            r#"
+            [fonts]
+            snap-to-pixels = false
+        "#,
+        );
+        assert!(!result.fonts.snap_to_pixels);
+    }
+
+    #[test]
+    fn test_font_letter_spacing() {
+        use sugarloaf::font::fonts::LetterSpacing;
+
+        let result = create_temporary_config(
+            "font-letter-spacing-pixels",
+            r#"
+            [fonts]
+            letter-spacing = 1.5
+        "#,
+        );
+        assert_eq!(result.fonts.letter_spacing, LetterSpacing::Pixels(1.5));
+        assert_eq!(result.fonts.letter_spacing.cell_width(10.0, 2.0), 13.0);
+        assert_eq!(result.fonts.letter_spacing.glyph_offset(13.0, 2.0), 2.0);
+
+        let result = create_temporary_config(
+            "font-letter-spacing-percent",
+            r#"
+            [fonts]
+            letter-spacing = "-10%"
+        "#,
+        );
+        assert_eq!(result.fonts.letter_spacing, LetterSpacing::Percent(-10.0));
+        assert_eq!(result.fonts.letter_spacing.cell_width(20.0, 1.0), 18.0);
+        assert_eq!(result.fonts.letter_spacing.glyph_offset(18.0, 1.0), -1.0);
+
+        let result = create_temporary_config("font-letter-spacing-default", "");
+        assert_eq!(result.fonts.letter_spacing, LetterSpacing::Pixels(0.0));
+        assert_eq!(result.fonts.letter_spacing.cell_width(9.6, 1.0), 10.0);
+    }
+
+    #[test]
+    fn test_font_size_unit() {
+        use sugarloaf::font::fonts::FontSizeUnit;
+
+        let result = create_temporary_config(
+            "font-size-fractional",
+            r#"
+            [fonts]
+            size = 13.5
+        "#,
+        );
+        assert_eq!(result.fonts.size_unit, FontSizeUnit::Px);
+        assert_eq!(result.fonts.size_px(), 13.5);
+
+        let result = create_temporary_config(
+            "font-size-unit-pt",
+            r#"
+            [fonts]
+            size = 12
+            size-unit = "pt"
+        "#,
+        );
+        assert_eq!(result.fonts.size_unit, FontSizeUnit::Pt);
+        assert_eq!(result.fonts.size_px(), 16.0);
+    }
+
+    #[test]
+    fn test_hints() {
+        let result = create_temporary_config(
+            "hints-alphabet",
+            r#"
+            [hints]
+            alphabet = "asdf"
+
+            [[hints.patterns]]
+            regex = "[0-9a-f]{7,40}"
+            action = "paste"
+
+            [[hints.patterns]]
+            regex = "([\\w./-]+):(\\d+)"
+            command = { program = "code", args = ["--goto", "$1:$2"] }
+        "#,
+        );
+        assert_eq!(result.hints.alphabet, "asdf");
+        assert_eq!(result.hints.patterns.len(), 2);
+        assert_eq!(
+            result.hints.patterns[0].action,
+            Some(hints::HintAction::Paste)
+        );
+        assert_eq!(result.hints.patterns[1].regex, "([\\w./-]+):(\\d+)");
+        assert_eq!(result.hints.patterns[1].action, None);
+        assert_eq!(
+            result.hints.patterns[1].command,
+            Some(Shell {
+                program: "code".to_string(),
+                args: vec!["--goto".to_string(), "$1:$2".to_string()],
+            })
+        );
+
+        let result = create_temporary_config("hints-alphabet-default", "");
+        assert_eq!(result.hints.alphabet, "jfkdls;ahgurieowpq");
+        assert!(result.hints.patterns.is_empty());
+    }
+
+    #[test]
+    fn test_hyperlinks() {
+        let result = create_temporary_config(
+            "hyperlinks-hover-modifiers",
+            r#"
+            [hyperlinks]
+            hover-modifiers = "control | shift"
+
+            [[hyperlinks.patterns]]
+            regex = "^[0-9a-f]{7,40}$"
+
+            [[hyperlinks.patterns]]
+            regex = "^[\\w./-]+:\\d+$"
+            command = { program = "code", args = ["--goto", "$0"] }
+        "#,
+        );
+        assert_eq!(result.hyperlinks.hover_modifiers, "control | shift");
+        assert_eq!(result.hyperlinks.patterns.len(), 2);
+        assert_eq!(result.hyperlinks.patterns[0].regex, "^[0-9a-f]{7,40}$");
+        assert_eq!(result.hyperlinks.patterns[0].command, None);
+        assert_eq!(
+            result.hyperlinks.patterns[1].command,
+            Some(Shell {
+                program: String::from("code"),
+                args: vec![String::from("--goto"), String::from("$0")],
+            })
+        );
+
+        let result = create_temporary_config("hyperlinks-hover-modifiers-default", "");
+        assert_eq!(
+            result.hyperlinks.hover_modifiers,
+            default_hyperlink_hover_modifiers()
+        );
+    }
+
+    #[test]
+    fn test_selection_block_modifiers() {
+        let result = create_temporary_config(
+            "selection-block-modifiers",
+            r#"
+            [selection]
+            block-modifiers = "control"
+        "#,
+        );
+        assert_eq!(result.selection.block_modifiers, "control");
+
+        let result = create_temporary_config("selection-block-modifiers-default", "");
+        assert_eq!(result.selection.block_modifiers, "alt");
+        assert!(!result.selection.save_to_clipboard);
+    }
+
+    #[test]
+    fn test_clipboard_history() {
+        let result = create_temporary_config(
+            "clipboard-history",
+            r#"
+            [clipboard]
+            history = 5
+        "#,
+        );
+        assert_eq!(result.clipboard.history, 5);
+
+        let result = create_temporary_config("clipboard-history-default", "");
+        assert_eq!(result.clipboard.history, 20);
+    }
+
+    #[test]
+    fn test_clipboard_osc52() {
+        let result = create_temporary_config(
+            "clipboard-osc52",
+            r#"
+            [clipboard]
+            osc52 = "write"
+            osc52-max-size = 512
+            osc52-confirm = true
+        "#,
+        );
+        assert!(!result.clipboard.osc52.can_read());
+        assert!(result.clipboard.osc52.can_write());
+        assert_eq!(result.clipboard.osc52_max_size, 512);
+        assert!(result.clipboard.osc52_confirm);
+
+        let result = create_temporary_config("clipboard-osc52-default", "");
+        assert_eq!(result.clipboard.osc52, Osc52::ReadWrite);
+        assert!(result.clipboard.osc52.can_read());
+        assert_eq!(result.clipboard.osc52_max_size, 1_000_000);
+        assert!(!result.clipboard.osc52_confirm);
+    }
+
+    #[test]
+    fn test_clipboard_backend() {
+        let result = create_temporary_config(
+            "clipboard-backend",
+            r#"
+            [clipboard]
+            backend = "command"
+            copy-command = { program = "wl-copy" }
+            paste-command = { program = "wl-paste", args = ["--no-newline"] }
+        "#,
+        );
+        assert_eq!(result.clipboard.backend, ClipboardBackend::Command);
+        assert_eq!(
+            result.clipboard.copy_command,
+            Some(Shell {
+                program: String::from("wl-copy"),
+                args: vec![],
+            })
+        );
+        assert_eq!(
+            result.clipboard.paste_command.unwrap().args,
+            vec![String::from("--no-newline")]
+        );
+
+        let result = create_temporary_config("clipboard-backend-default", "");
+        assert_eq!(result.clipboard.backend, ClipboardBackend::System);
+        assert_eq!(result.clipboard.copy_command, None);
+    }
+
+    #[test]
+    fn test_bell() {
+        let result = create_temporary_config(
+            "bell",
+            r#"
+            [bell]
+            min-interval = 500
+            mute = ["vim", "less"]
+        "#,
+        );
+        assert_eq!(result.bell.min_interval, 500);
+        assert_eq!(result.bell.mute, vec!["vim", "less"]);
+
+        let result = create_temporary_config("bell-default", "");
+        assert_eq!(result.bell.min_interval, 100);
+        assert!(result.bell.mute.is_empty());
+    }
+
+    #[test]
+    fn test_haptic_feedback() {
+        let result = create_temporary_config(
+            "haptic-feedback",
+            r#"
+            [haptic-feedback]
+            selection = true
+        "#,
+        );
+        assert!(result.haptic_feedback.selection);
+        assert!(!result.haptic_feedback.scrollback);
+
+        let result = create_temporary_config("haptic-feedback-default", "");
+        assert_eq!(result.haptic_feedback, HapticFeedback::default());
+    }
+
+    #[test]
+    fn test_macos_quit_on_last_window_closed() {
+        let result = create_temporary_config(
+            "macos-quit-on-last-window-closed",
+            r#"
+            [window]
+            macos-quit-on-last-window-closed = true
+        "#,
+        );
+        assert_eq!(result.window.macos_quit_on_last_window_closed, Some(true));
+
+        let result = create_temporary_config("macos-quit-default", "");
+        assert_eq!(result.window.macos_quit_on_last_window_closed, None);
+    }
+
+    #[test]
+    fn test_paste() {
+        let result = create_temporary_config(
+            "paste",
+            r#"
+            [paste]
+            confirm = false
+            large-size = 500
+            highlight-shell = false
+        "#,
+        );
+        assert!(!result.paste.confirm);
+        assert_eq!(result.paste.large_size, 500);
+        assert!(!result.paste.highlight_shell);
+
+        let result = create_temporary_config("paste-default", "");
+        assert!(result.paste.confirm);
+        assert_eq!(result.paste.large_size, 10_000);
+        assert!(result.paste.highlight_shell);
+    }
+
+    #[test]
+    fn test_unicode_emoji_width() {
+        let result = create_temporary_config(
+            "unicode-emoji-width",
+            r#"
+            [unicode]
+            emoji-width = 1
+        "#,
+        );
+        assert_eq!(result.unicode.emoji_width, unicode::EmojiWidth::Narrow);
+
+        let result = create_temporary_config("unicode-emoji-width-default", "");
+        assert_eq!(result.unicode.emoji_width, unicode::EmojiWidth::Wide);
+    }
+
+    #[test]
+    fn test_unicode_zero_width() {
+        let result = create_temporary_config(
+            "unicode-zero-width",
+            r#"
+            [unicode]
+            zero-width = "show"
+        "#,
+        );
+        assert_eq!(result.unicode.zero_width, unicode::ZeroWidth::Show);
+
+        let result = create_temporary_config("unicode-zero-width-default", "");
+        assert_eq!(result.unicode.zero_width, unicode::ZeroWidth::Hide);
+    }
+
+    #[test]
+    fn test_unicode_invalid_utf8() {
+        let result = create_temporary_config(
+            "unicode-invalid-utf8",
+            r#"
+            [unicode]
+            invalid-utf8 = "latin1"
+        "#,
+        );
+        assert_eq!(result.unicode.invalid_utf8, unicode::InvalidUtf8::Latin1);
+
+        let result = create_temporary_config("unicode-invalid-utf8-default", "");
+        assert_eq!(result.unicode.invalid_utf8, unicode::InvalidUtf8::Replace);
+    }
+
+    #[test]
+    fn test_graphics_max_memory() {
+        let result = create_temporary_config(
+            "graphics-max-memory",
+            r#"
+            [graphics]
+            max-memory = 64
+        "#,
+        );
+        assert_eq!(result.graphics.max_memory, 64);
+        assert_eq!(result.graphics.max_memory_bytes(), 64 * 1024 * 1024);
+
+        let result = create_temporary_config("graphics-max-memory-default", "");
+        assert_eq!(result.graphics.max_memory, 320);
+    }
+
     #[test]
     fn test_symbol_map() {
         let result = create_temporary_config(
@@ -1094,4 +2117,167 @@ mod tests {
         assert_eq!(parse_unicode(&symbol_map[1].start), Some('\u{E0C0}'));
         assert_eq!(parse_unicode(&symbol_map[1].end), Some('\u{E0C7}'));
     }
+
+    #[test]
+    fn test_profiles() {
+        let result = create_temporary_config(
+            "profiles",
+            r#"
+            [[profiles]]
+            name = "production"
+            when = { hostname = "^prod-", command = "ssh" }
+            title = "PRODUCTION {{ title }}"
+            cursor = { shape = "underline", blinking = true }
+
+            [[profiles]]
+            name = "rio"
+            when = { cwd = "/rio$" }
+        "#,
+        );
+
+        assert_eq!(result.profiles.len(), 2);
+        assert_eq!(result.profiles[0].name, "production");
+        assert_eq!(
+            result.profiles[0]
+                .when
+                .hostname
+                .as_ref()
+                .map(profiles::ProfilePattern::as_str),
+            Some("^prod-")
+        );
+        assert_eq!(result.profiles[0].when.cwd, None);
+        let cursor = result.profiles[0].cursor.as_ref().unwrap();
+        assert_eq!(cursor.shape, CursorShape::Underline);
+        assert!(cursor.blinking);
+
+        let state = profiles::ProfileState {
+            cwd: Some("/home/user/rio"),
+            hostname: Some("prod-01"),
+            command: Some("ssh"),
+            user_vars: None,
+        };
+        let profile = profiles::find_profile(&result.profiles, &state).unwrap();
+        assert_eq!(profile.name, "production");
+
+        let state = profiles::ProfileState {
+            cwd: Some("/home/user/rio"),
+            hostname: Some("local"),
+            command: Some("zsh"),
+            user_vars: None,
+        };
+        let profile = profiles::find_profile(&result.profiles, &state).unwrap();
+        assert_eq!(profile.name, "rio");
+
+        let state = profiles::ProfileState {
+            cwd: Some("/home/user"),
+            hostname: None,
+            command: Some("zsh"),
+            user_vars: None,
+        };
+        assert!(profiles::find_profile(&result.profiles, &state).is_none());
+
+        let config = result.with_profile(&result.profiles[0]);
+        assert_eq!(config.title.content, "PRODUCTION {{ title }}");
+        assert_eq!(config.cursor.shape, CursorShape::Underline);
+    }
+
+    #[test]
+    fn test_profile_user_vars() {
+        let result = create_temporary_config(
+            "profile-user-vars",
+            r#"
+            [[profiles]]
+            name = "kubernetes"
+            when = { user-vars = { k8s_context = "^prod" } }
+            theme = "dracula"
+        "#,
+        );
+
+        let mut user_vars = rustc_hash::FxHashMap::default();
+        let state = profiles::ProfileState {
+            user_vars: Some(&user_vars),
+            ..profiles::ProfileState::default()
+        };
+        assert!(profiles::find_profile(&result.profiles, &state).is_none());
+
+        user_vars.insert(String::from("k8s_context"), String::from("prod-eu"));
+        let state = profiles::ProfileState {
+            user_vars: Some(&user_vars),
+            ..profiles::ProfileState::default()
+        };
+        let profile = profiles::find_profile(&result.profiles, &state).unwrap();
+        assert_eq!(profile.name, "kubernetes");
+    }
+
+    #[test]
+    fn test_profile_invalid_rule() {
+        let result = create_temporary_config(
+            "profile-invalid-rule",
+            r#"
+            [[profiles]]
+            name = "broken"
+            when = { cwd = "(" }
+        "#,
+        );
+
+        assert_eq!(result.profiles.len(), 1);
+        let state = profiles::ProfileState {
+            cwd: Some("("),
+            ..profiles::ProfileState::default()
+        };
+        assert!(profiles::find_profile(&result.profiles, &state).is_none());
+    }
+
+    #[test]
+    fn test_profile_env_vars() {
+        let result = create_temporary_config(
+            "profile-env-vars",
+            r#"
+            [[profiles]]
+            name = "legacy"
+            when = { cwd = "/legacy" }
+            env-vars = ["LANG=C", "TERM=xterm", "LESS=-R -X", "INVALID"]
+        "#,
+        );
+
+        assert_eq!(
+            result.profiles[0].env(),
+            [
+                (String::from("LANG"), String::from("C")),
+                (String::from("TERM"), String::from("xterm")),
+                (String::from("LESS"), String::from("-R -X")),
+            ]
+        );
+        assert!(Profile::default().env().is_empty());
+    }
+
+    #[test]
+    fn test_write_config_value() {
+        let file_name = tmp_dir().join("test-rio-write-value-config.toml");
+        std::fs::write(
+            &file_name,
+            "# my config\ntheme = \"dracula\"\n\n[fonts]\nsize = 14 # small\n",
+        )
+        .unwrap();
+
+        write_config_value(&file_name, "fonts.size", 16.0).unwrap();
+        write_config_value(&file_name, "cursor.blinking", true).unwrap();
+        write_config_value(&file_name, "theme", "nord").unwrap();
+
+        let content = std::fs::read_to_string(&file_name).unwrap();
+        assert!(content.starts_with("# my config\ntheme = \"nord\"\n"));
+        assert!(content.contains("size = 16.0 # small\n"));
+
+        let config = Config::load_from_path_without_fallback(&file_name).unwrap();
+        assert_eq!(config.fonts.size, 16.0);
+        assert!(config.cursor.blinking);
+        assert_eq!(config.theme, "nord");
+
+        remove_config_value(&file_name, "theme").unwrap();
+        let config = Config::load_from_path_without_fallback(&file_name).unwrap();
+        assert_eq!(config.theme, "");
+
+        // Values of a table can't be set through a value
+        assert!(write_config_value(&file_name, "fonts.size.value", 1).is_err());
+    }
 }
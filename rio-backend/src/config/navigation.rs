@@ -1,8 +1,16 @@
 use crate::config::colors::{deserialize_to_arr, ColorArray};
 use crate::config::default_bool_true;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+use sugarloaf::schema_aliases;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, JsonSchema)]
+#[schemars(transform = schema_aliases("plain toptab nativetab bottomtab bookmark"))]
+// The schema is the same on every platform
+#[cfg_attr(
+    not(target_os = "macos"),
+    schemars(transform = schema_aliases("NativeTab"))
+)]
 pub enum NavigationMode {
     #[serde(alias = "plain")]
     Plain,
@@ -90,7 +98,8 @@ impl std::str::FromStr for NavigationMode {
     }
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+#[schemars(transform = crate::config::colors::hex_defaults)]
 pub struct ColorAutomation {
     #[serde(default = "String::new")]
     pub program: String,
@@ -100,6 +109,7 @@ pub struct ColorAutomation {
         deserialize_with = "deserialize_to_arr",
         default = "crate::config::colors::defaults::tabs"
     )]
+    #[schemars(with = "String")]
     pub color: ColorArray,
 }
 
@@ -108,7 +118,7 @@ pub fn default_unfocused_split_opacity() -> f32 {
     0.4
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Navigation {
     #[serde(default = "NavigationMode::default")]
     pub mode: NavigationMode,
@@ -0,0 +1,29 @@
+use crate::config::defaults::{default_bool_true, default_paste_large_size};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Paste {
+    /// Whether suspicious pastes, with new lines or control characters,
+    /// are confirmed before being written.
+    #[serde(default = "default_bool_true")]
+    pub confirm: bool,
+    /// Number of characters above which a paste is confirmed, `0` to
+    /// never confirm large pastes.
+    #[serde(default = "default_paste_large_size", rename = "large-size")]
+    pub large_size: usize,
+    /// Whether the characters interpreted by the shell, like quotes and
+    /// command substitutions, are highlighted when confirming a paste.
+    #[serde(default = "default_bool_true", rename = "highlight-shell")]
+    pub highlight_shell: bool,
+}
+
+impl Default for Paste {
+    fn default() -> Self {
+        Self {
+            confirm: true,
+            large_size: default_paste_large_size(),
+            highlight_shell: true,
+        }
+    }
+}
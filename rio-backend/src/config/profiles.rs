@@ -0,0 +1,164 @@
+use crate::config::{CursorConfig, Scroll};
+use regex::Regex;
+use rustc_hash::FxHashMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Regular expression of a profile rule, compiled when the config is
+/// loaded. Invalid patterns are reported then and never match.
+#[derive(Debug, Clone)]
+pub struct ProfilePattern {
+    pattern: String,
+    regex: Option<Regex>,
+}
+
+impl ProfilePattern {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        let regex = match Regex::new(&pattern) {
+            Ok(regex) => Some(regex),
+            Err(err) => {
+                tracing::warn!("invalid profile rule {pattern:?}: {err}");
+                None
+            }
+        };
+
+        Self { pattern, regex }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    #[inline]
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(value))
+    }
+}
+
+impl PartialEq for ProfilePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Serialize for ProfilePattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.pattern)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProfilePattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(ProfilePattern::new)
+    }
+}
+
+/// Conditions under which a profile is applied, each one is a regular
+/// expression and all the defined ones need to match.
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct ProfileRule {
+    #[serde(default = "Option::default")]
+    #[schemars(with = "Option<String>", default)]
+    pub cwd: Option<ProfilePattern>,
+    #[serde(default = "Option::default")]
+    #[schemars(with = "Option<String>", default)]
+    pub hostname: Option<ProfilePattern>,
+    #[serde(default = "Option::default")]
+    #[schemars(with = "Option<String>", default)]
+    pub command: Option<ProfilePattern>,
+    /// Patterns of the user variables set via OSC 1337 `SetUserVar`, by
+    /// name.
+    #[serde(default = "BTreeMap::default", rename = "user-vars")]
+    #[schemars(with = "BTreeMap<String, String>", default)]
+    pub user_vars: BTreeMap<String, ProfilePattern>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default = "ProfileRule::default")]
+    pub when: ProfileRule,
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub theme: Option<String>,
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub title: Option<String>,
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub cursor: Option<CursorConfig>,
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub scroll: Option<Scroll>,
+    /// Variables set on the terminals spawned while the profile matches,
+    /// written as `KEY=VALUE`.
+    #[serde(default, rename = "env-vars")]
+    pub env_vars: Vec<String>,
+}
+
+/// Terminal state used to evaluate profile rules.
+#[derive(Default, Debug)]
+pub struct ProfileState<'a> {
+    pub cwd: Option<&'a str>,
+    pub hostname: Option<&'a str>,
+    pub command: Option<&'a str>,
+    pub user_vars: Option<&'a FxHashMap<String, String>>,
+}
+
+#[inline]
+fn is_match(pattern: &Option<ProfilePattern>, value: Option<&str>) -> bool {
+    let Some(pattern) = pattern else {
+        return true;
+    };
+
+    value.is_some_and(|value| pattern.is_match(value))
+}
+
+impl Profile {
+    /// A profile without any condition never matches.
+    pub fn matches(&self, state: &ProfileState) -> bool {
+        let rule = &self.when;
+        if rule.cwd.is_none()
+            && rule.hostname.is_none()
+            && rule.command.is_none()
+            && rule.user_vars.is_empty()
+        {
+            return false;
+        }
+
+        is_match(&rule.cwd, state.cwd)
+            && is_match(&rule.hostname, state.hostname)
+            && is_match(&rule.command, state.command)
+            && rule.user_vars.iter().all(|(name, pattern)| {
+                state
+                    .user_vars
+                    .and_then(|user_vars| user_vars.get(name))
+                    .is_some_and(|value| pattern.is_match(value))
+            })
+    }
+
+    /// Parsed `env-vars`, the entries without `=` are skipped.
+    pub fn env(&self) -> Vec<(String, String)> {
+        self.env_vars
+            .iter()
+            .filter_map(|var| {
+                let (key, value) = var.split_once('=')?;
+                Some((key.to_owned(), value.to_owned()))
+            })
+            .collect()
+    }
+}
+
+/// Returns the first profile that matches the state.
+#[inline]
+pub fn find_profile<'a>(
+    profiles: &'a [Profile],
+    state: &ProfileState,
+) -> Option<&'a Profile> {
+    profiles.iter().find(|profile| profile.matches(state))
+}
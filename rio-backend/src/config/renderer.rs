@@ -1,24 +1,96 @@
+use crate::config::defaults::default_hdr_brightness;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use sugarloaf::schema_aliases;
 use sugarloaf::Filter;
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
 pub struct Renderer {
-    #[serde(default = "Performance::default")]
+    #[serde(default = "Performance::default", alias = "power-preference")]
     pub performance: Performance,
+    /// Name (or part of it) of the graphics adapter to use.
+    #[serde(default = "Option::default")]
+    #[schemars(default)]
+    pub adapter: Option<String>,
     #[serde(default = "Backend::default", skip_serializing)]
     pub backend: Backend,
     #[serde(default = "bool::default", rename = "disable-unfocused-render")]
     pub disable_unfocused_render: bool,
     #[serde(default = "Option::default", rename = "target-fps")]
+    #[schemars(default)]
     pub target_fps: Option<u64>,
+    /// Caps the frame rate, unlike `target_fps` it never goes above the
+    /// display refresh rate.
+    #[serde(default = "Option::default", rename = "max-fps")]
+    #[schemars(default)]
+    pub max_fps: Option<u64>,
+    #[serde(default = "PresentMode::default", rename = "present-mode")]
+    pub present_mode: PresentMode,
     #[serde(default = "Vec::default")]
+    #[schemars(default)]
     pub filters: Vec<Filter>,
     #[serde(default = "RendererStategy::default")]
     pub strategy: RendererStategy,
+    #[serde(default = "BatterySaver::default", rename = "battery-saver")]
+    pub battery_saver: BatterySaver,
+    #[serde(default = "bool::default")]
+    pub hdr: bool,
+    #[serde(default = "Colorspace::default")]
+    pub colorspace: Colorspace,
+    /// Multiplier of the content brightness when hdr is enabled, values
+    /// above 1.0 make it brighter than the SDR white.
+    #[serde(default = "default_hdr_brightness", rename = "hdr-brightness")]
+    pub hdr_brightness: f32,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Deserialize, Serialize)]
+/// How frames are presented to the display.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[schemars(transform = schema_aliases("fifo mailbox immediate"))]
+pub enum PresentMode {
+    /// Waits for the vertical blank (vsync), no tearing.
+    #[default]
+    #[serde(alias = "fifo")]
+    Fifo,
+    /// Waits for the vertical blank but replaces the queued frame with
+    /// the newest one, lower latency without tearing.
+    #[serde(alias = "mailbox")]
+    Mailbox,
+    /// Presents right away, lowest latency but may tear.
+    #[serde(alias = "immediate")]
+    Immediate,
+}
+
+/// Color space of the configured colors, used when rendering to an
+/// extended range (HDR) surface.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[schemars(transform = schema_aliases("srgb display-p3"))]
+pub enum Colorspace {
+    #[default]
+    #[serde(alias = "srgb")]
+    Srgb,
+    #[serde(alias = "display-p3")]
+    DisplayP3,
+}
+
+/// Lowers the rendering cost to save energy: caps the frame rate,
+/// disables cursor blinking and window blur and pauses the rendering
+/// of unfocused panes.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[schemars(transform = schema_aliases("never auto always"))]
+pub enum BatterySaver {
+    #[default]
+    #[serde(alias = "never")]
+    Never,
+    /// Enabled while the system is running on battery.
+    #[serde(alias = "auto")]
+    Auto,
+    #[serde(alias = "always")]
+    Always,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[schemars(transform = schema_aliases("events game"))]
 pub enum RendererStategy {
     #[default]
     #[serde(alias = "events")]
@@ -44,21 +116,29 @@ impl Default for Renderer {
     fn default() -> Renderer {
         Renderer {
             performance: Performance::default(),
+            adapter: None,
             backend: Backend::default(),
             disable_unfocused_render: false,
             target_fps: None,
+            max_fps: None,
+            present_mode: PresentMode::default(),
             filters: Vec::default(),
             strategy: RendererStategy::Events,
+            battery_saver: BatterySaver::default(),
+            hdr: false,
+            colorspace: Colorspace::default(),
+            hdr_brightness: default_hdr_brightness(),
         }
     }
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy, JsonSchema)]
+#[schemars(transform = schema_aliases("high high-performance low low-power"))]
 pub enum Performance {
     #[default]
-    #[serde(alias = "high")]
+    #[serde(alias = "high", alias = "high-performance")]
     High,
-    #[serde(alias = "low")]
+    #[serde(alias = "low", alias = "low-power")]
     Low,
 }
 
@@ -75,7 +155,8 @@ impl Display for Performance {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Default, Deserialize, Clone, PartialEq, JsonSchema)]
+#[schemars(transform = schema_aliases("automatic gl vulkan dx12 metal"))]
 pub enum Backend {
     // Leave Sugarloaf/WGPU to decide
     #[default]
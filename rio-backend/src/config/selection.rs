@@ -0,0 +1,27 @@
+use crate::config::defaults::default_block_selection_modifiers;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Selection {
+    /// Modifiers held when clicking to start a block selection, in the
+    /// format of the key bindings `with`, e.g. `"alt"`.
+    #[serde(
+        default = "default_block_selection_modifiers",
+        rename = "block-modifiers"
+    )]
+    pub block_modifiers: String,
+    /// Whether the selected text is also copied to the clipboard when the
+    /// system has a primary selection, like X11 and Wayland.
+    #[serde(default = "bool::default", rename = "save-to-clipboard")]
+    pub save_to_clipboard: bool,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self {
+            block_modifiers: default_block_selection_modifiers(),
+            save_to_clipboard: false,
+        }
+    }
+}
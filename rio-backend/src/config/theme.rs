@@ -1,7 +1,17 @@
 use crate::config::colors::Colors;
+use rio_window::window::Theme as Appearance;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::time::Duration;
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone)]
+const MINUTES_PER_DAY: f64 = 1440.0;
+
+// The sun times move every day and clock changes aren't notified,
+// so the schedule is checked again at least once per hour
+const MAX_SCHEDULE_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct AdaptiveColors {
     #[serde(default = "Option::default", skip_serializing)]
     pub dark: Option<Colors>,
@@ -9,14 +19,239 @@ pub struct AdaptiveColors {
     pub light: Option<Colors>,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct AdaptiveTheme {
     pub dark: String,
     pub light: String,
+    /// Local time the light theme starts, takes precedence over the
+    /// sunrise when set with `dark-at`.
+    #[serde(default, rename = "light-at")]
+    pub light_at: Option<TimeOfDay>,
+    #[serde(default, rename = "dark-at")]
+    pub dark_at: Option<TimeOfDay>,
+    /// Coordinates used to follow the sunrise and sunset.
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+}
+
+impl AdaptiveTheme {
+    /// Appearance for the time, and how long until it should be checked
+    /// again. `None` when the theme follows the system appearance.
+    ///
+    /// `utc_offset` is the local offset from UTC in seconds.
+    pub fn scheduled_appearance(
+        &self,
+        unix_time: i64,
+        utc_offset: i64,
+    ) -> Option<(Appearance, Duration)> {
+        let local_time = unix_time + utc_offset;
+        let days = local_time.div_euclid(86400);
+        let now = local_time.rem_euclid(86400) as f64 / 60.0;
+
+        let (light, dark) = match (self.light_at, self.dark_at) {
+            (Some(light), Some(dark)) => (light.minutes(), dark.minutes()),
+            _ => {
+                let (latitude, longitude) = self.latitude.zip(self.longitude)?;
+                match sun_times(days, latitude, longitude, utc_offset as f64 / 60.0) {
+                    Ok(times) => times,
+                    Err(appearance) => return Some((appearance, MAX_SCHEDULE_INTERVAL)),
+                }
+            }
+        };
+
+        let is_light = if light <= dark {
+            now >= light && now < dark
+        } else {
+            now >= light || now < dark
+        };
+
+        let next = if is_light { dark } else { light };
+        let until_next = (next - now).rem_euclid(MINUTES_PER_DAY) * 60.0;
+        let interval = Duration::from_secs_f64(until_next)
+            .clamp(Duration::from_secs(1), MAX_SCHEDULE_INTERVAL);
+
+        let appearance = if is_light {
+            Appearance::Light
+        } else {
+            Appearance::Dark
+        };
+        Some((appearance, interval))
+    }
+}
+
+/// Local time written as "HH:MM", stored in minutes since midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay(u16);
+
+impl TimeOfDay {
+    #[inline]
+    fn minutes(self) -> f64 {
+        self.0 as f64
+    }
 }
 
-#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let parsed = value.split_once(':').and_then(|(hours, minutes)| {
+            let hours: u16 = hours.trim().parse().ok()?;
+            let minutes: u16 = minutes.trim().parse().ok()?;
+            (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+        });
+
+        parsed
+            .map(TimeOfDay)
+            .ok_or_else(|| format!("time must be written as \"HH:MM\", got {value:?}"))
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(value: TimeOfDay) -> String {
+        format!("{:02}:{:02}", value.0 / 60, value.0 % 60)
+    }
+}
+
+/// Sunrise and sunset in minutes of the local day, using the NOAA
+/// approximation. On polar days and nights the appearance for the whole
+/// day is returned instead.
+fn sun_times(
+    days: i64,
+    latitude: f64,
+    longitude: f64,
+    utc_offset_minutes: f64,
+) -> Result<(f64, f64), Appearance> {
+    // The fraction of the year only needs to be close enough, counting
+    // from 1970 with the average year length drifts less than a day
+    let gamma = 2.0 * PI * (days as f64).rem_euclid(365.2425) / 365.2425;
+
+    let equation_of_time = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = 90.833_f64.to_radians().cos()
+        / (latitude.cos() * declination.cos())
+        - latitude.tan() * declination.tan();
+    if cos_hour_angle > 1.0 {
+        return Err(Appearance::Dark);
+    }
+    if cos_hour_angle < -1.0 {
+        return Err(Appearance::Light);
+    }
+
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+    let noon = 720.0 - 4.0 * longitude - equation_of_time + utc_offset_minutes;
+    Ok((
+        (noon - 4.0 * hour_angle).rem_euclid(MINUTES_PER_DAY),
+        (noon + 4.0 * hour_angle).rem_euclid(MINUTES_PER_DAY),
+    ))
+}
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq, JsonSchema)]
 pub struct Theme {
     #[serde(default = "Colors::default")]
     pub colors: Colors,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-06-21 00:00 UTC
+    const SOLSTICE: i64 = 1718928000;
+
+    fn adaptive_theme() -> AdaptiveTheme {
+        AdaptiveTheme {
+            dark: String::from("dark"),
+            light: String::from("light"),
+            ..AdaptiveTheme::default()
+        }
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        assert_eq!(
+            TimeOfDay::try_from(String::from("07:30")),
+            Ok(TimeOfDay(450))
+        );
+        assert_eq!(String::from(TimeOfDay(450)), "07:30");
+        assert!(TimeOfDay::try_from(String::from("24:00")).is_err());
+        assert!(TimeOfDay::try_from(String::from("7")).is_err());
+    }
+
+    #[test]
+    fn test_scheduled_appearance_by_time() {
+        let mut theme = adaptive_theme();
+        assert_eq!(theme.scheduled_appearance(SOLSTICE, 0), None);
+
+        theme.light_at = Some(TimeOfDay(7 * 60));
+        theme.dark_at = Some(TimeOfDay(19 * 60));
+        assert_eq!(
+            theme.scheduled_appearance(SOLSTICE + 6 * 3600, 0),
+            Some((Appearance::Dark, Duration::from_secs(3600)))
+        );
+        assert_eq!(
+            theme.scheduled_appearance(SOLSTICE + 18 * 3600 + 1800, 0),
+            Some((Appearance::Light, Duration::from_secs(1800)))
+        );
+        // 06:00 UTC is 08:00 two hours ahead
+        assert_eq!(
+            theme
+                .scheduled_appearance(SOLSTICE + 6 * 3600, 2 * 3600)
+                .map(|(appearance, _)| appearance),
+            Some(Appearance::Light)
+        );
+
+        // Dark during the day
+        theme.light_at = Some(TimeOfDay(22 * 60));
+        theme.dark_at = Some(TimeOfDay(6 * 60));
+        assert_eq!(
+            theme
+                .scheduled_appearance(SOLSTICE + 12 * 3600, 0)
+                .map(|(appearance, _)| appearance),
+            Some(Appearance::Dark)
+        );
+    }
+
+    #[test]
+    fn test_scheduled_appearance_by_sun() {
+        // London, the sun rises around 03:43 and sets around 20:21 UTC
+        let mut theme = adaptive_theme();
+        theme.latitude = Some(51.5);
+        theme.longitude = Some(-0.13);
+
+        let (sunrise, sunset) = sun_times(SOLSTICE / 86400, 51.5, -0.13, 0.0).unwrap();
+        assert!((sunrise - 223.0).abs() < 5.0, "sunrise at {sunrise}");
+        assert!((sunset - 1221.0).abs() < 5.0, "sunset at {sunset}");
+
+        let appearance_at = |theme: &AdaptiveTheme, hour: i64| {
+            theme
+                .scheduled_appearance(SOLSTICE + hour * 3600, 0)
+                .map(|(appearance, _)| appearance)
+        };
+        assert_eq!(appearance_at(&theme, 2), Some(Appearance::Dark));
+        assert_eq!(appearance_at(&theme, 12), Some(Appearance::Light));
+        assert_eq!(appearance_at(&theme, 22), Some(Appearance::Dark));
+
+        // Midnight sun in Tromsø
+        theme.latitude = Some(69.65);
+        theme.longitude = Some(18.96);
+        assert_eq!(appearance_at(&theme, 0), Some(Appearance::Light));
+
+        // Fixed times take precedence
+        theme.light_at = Some(TimeOfDay(8 * 60));
+        theme.dark_at = Some(TimeOfDay(20 * 60));
+        assert_eq!(appearance_at(&theme, 0), Some(Appearance::Dark));
+    }
+}
@@ -0,0 +1,394 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sugarloaf::schema_aliases;
+use unicode_width::UnicodeWidthChar;
+
+/// Number of cells taken by emoji, configured as `1` or `2`.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum EmojiWidth {
+    Narrow,
+    #[default]
+    Wide,
+}
+
+impl EmojiWidth {
+    #[inline]
+    pub fn cells(self) -> usize {
+        match self {
+            EmojiWidth::Narrow => 1,
+            EmojiWidth::Wide => 2,
+        }
+    }
+
+    /// Width of the character in the grid, emoji take the configured
+    /// number of cells.
+    #[inline]
+    pub fn char_width(self, c: char) -> Option<usize> {
+        match c.width()? {
+            2 if self == EmojiWidth::Narrow && is_wide_emoji(c) => Some(1),
+            width => Some(width),
+        }
+    }
+}
+
+impl TryFrom<u8> for EmojiWidth {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(EmojiWidth::Narrow),
+            2 => Ok(EmojiWidth::Wide),
+            _ => Err(format!("emoji-width must be 1 or 2, got {value}")),
+        }
+    }
+}
+
+impl From<EmojiWidth> for u8 {
+    fn from(value: EmojiWidth) -> u8 {
+        value.cells() as u8
+    }
+}
+
+/// Version of Unicode the width tables follow.
+pub const UNICODE_TABLES_VERSION: u8 = 16;
+
+/// Unicode version the character widths follow, from 8 to the version of
+/// the tables. Unicode 9 made the emoji wide, pinning an older version
+/// keeps them in a single cell like programs built with older tables do,
+/// and emoji added after the pinned version take a single cell like other
+/// unassigned characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct UnicodeVersion(u8);
+
+impl UnicodeVersion {
+    /// Whether emoji take two cells.
+    #[inline]
+    pub fn has_wide_emoji(self) -> bool {
+        self.0 >= 9
+    }
+
+    /// Whether the character was assigned in this version, only the emoji
+    /// added after Unicode 9 are tracked.
+    #[inline]
+    pub fn has_char(self, c: char) -> bool {
+        let c = c as u32;
+        let index = EMOJI_VERSIONS.partition_point(|&(_, end, _)| end < c);
+        match EMOJI_VERSIONS.get(index) {
+            Some(&(start, _, added)) if start <= c => added <= self.0,
+            _ => true,
+        }
+    }
+}
+
+/// Emoji added after Unicode 9 and the version adding them, sorted by code
+/// point.
+const EMOJI_VERSIONS: &[(u32, u32, u8)] = &[
+    (0x1F6D5, 0x1F6D5, 12),
+    (0x1F6D6, 0x1F6D7, 13),
+    (0x1F6DC, 0x1F6DC, 15),
+    (0x1F6DD, 0x1F6DF, 14),
+    (0x1F6F7, 0x1F6F8, 10),
+    (0x1F6F9, 0x1F6F9, 11),
+    (0x1F6FA, 0x1F6FA, 12),
+    (0x1F6FB, 0x1F6FC, 13),
+    (0x1F7E0, 0x1F7EB, 12),
+    (0x1F7F0, 0x1F7F0, 14),
+    (0x1F90C, 0x1F90C, 13),
+    (0x1F90D, 0x1F90F, 12),
+    (0x1F91F, 0x1F91F, 10),
+    (0x1F928, 0x1F92F, 10),
+    (0x1F931, 0x1F932, 10),
+    (0x1F93F, 0x1F93F, 12),
+    (0x1F94C, 0x1F94C, 10),
+    (0x1F94D, 0x1F94F, 11),
+    (0x1F95F, 0x1F96B, 10),
+    (0x1F96C, 0x1F970, 11),
+    (0x1F971, 0x1F971, 12),
+    (0x1F972, 0x1F972, 13),
+    (0x1F973, 0x1F976, 11),
+    (0x1F977, 0x1F978, 13),
+    (0x1F979, 0x1F979, 14),
+    (0x1F97A, 0x1F97A, 11),
+    (0x1F97B, 0x1F97B, 12),
+    (0x1F97C, 0x1F97F, 11),
+    (0x1F992, 0x1F997, 10),
+    (0x1F998, 0x1F9A2, 11),
+    (0x1F9A3, 0x1F9A4, 13),
+    (0x1F9A5, 0x1F9AA, 12),
+    (0x1F9AB, 0x1F9AD, 13),
+    (0x1F9AE, 0x1F9AF, 12),
+    (0x1F9B0, 0x1F9B9, 11),
+    (0x1F9BA, 0x1F9BF, 12),
+    (0x1F9C1, 0x1F9C2, 11),
+    (0x1F9C3, 0x1F9CA, 12),
+    (0x1F9CB, 0x1F9CB, 13),
+    (0x1F9CC, 0x1F9CC, 14),
+    (0x1F9CD, 0x1F9CF, 12),
+    (0x1F9D0, 0x1F9E6, 10),
+    (0x1F9E7, 0x1F9FF, 11),
+    (0x1FA70, 0x1FA73, 12),
+    (0x1FA74, 0x1FA74, 13),
+    (0x1FA75, 0x1FA77, 15),
+    (0x1FA78, 0x1FA7A, 12),
+    (0x1FA7B, 0x1FA7C, 14),
+    (0x1FA80, 0x1FA82, 12),
+    (0x1FA83, 0x1FA86, 13),
+    (0x1FA87, 0x1FA88, 15),
+    (0x1FA89, 0x1FA89, 16),
+    (0x1FA8F, 0x1FA8F, 16),
+    (0x1FA90, 0x1FA95, 12),
+    (0x1FA96, 0x1FAA8, 13),
+    (0x1FAA9, 0x1FAAC, 14),
+    (0x1FAAD, 0x1FAAF, 15),
+    (0x1FAB0, 0x1FAB6, 13),
+    (0x1FAB7, 0x1FABA, 14),
+    (0x1FABB, 0x1FABD, 15),
+    (0x1FABE, 0x1FABE, 16),
+    (0x1FABF, 0x1FABF, 15),
+    (0x1FAC0, 0x1FAC2, 13),
+    (0x1FAC3, 0x1FAC5, 14),
+    (0x1FAC6, 0x1FAC6, 16),
+    (0x1FACE, 0x1FACF, 15),
+    (0x1FAD0, 0x1FAD6, 13),
+    (0x1FAD7, 0x1FAD9, 14),
+    (0x1FADA, 0x1FADB, 15),
+    (0x1FADC, 0x1FADC, 16),
+    (0x1FADF, 0x1FADF, 16),
+    (0x1FAE0, 0x1FAE7, 14),
+    (0x1FAE8, 0x1FAE8, 15),
+    (0x1FAE9, 0x1FAE9, 16),
+    (0x1FAF0, 0x1FAF6, 14),
+    (0x1FAF7, 0x1FAF8, 15),
+];
+
+impl Default for UnicodeVersion {
+    fn default() -> Self {
+        UnicodeVersion(UNICODE_TABLES_VERSION)
+    }
+}
+
+impl TryFrom<u8> for UnicodeVersion {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if (8..=UNICODE_TABLES_VERSION).contains(&value) {
+            Ok(UnicodeVersion(value))
+        } else {
+            Err(format!(
+                "unicode version must be between 8 and {UNICODE_TABLES_VERSION}, got {value}"
+            ))
+        }
+    }
+}
+
+impl From<UnicodeVersion> for u8 {
+    fn from(value: UnicodeVersion) -> u8 {
+        value.0
+    }
+}
+
+/// How invisible characters, like soft hyphens and zero-width spaces, are
+/// shown.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[schemars(transform = schema_aliases("hide show"))]
+pub enum ZeroWidth {
+    /// Zero width, kept with the previous character and not drawn.
+    #[default]
+    #[serde(alias = "hide")]
+    Hide,
+    /// A cell each, drawn as a box with the code point.
+    #[serde(alias = "show")]
+    Show,
+}
+
+impl ZeroWidth {
+    /// Width of the invisible characters in the grid, `None` for the
+    /// others.
+    #[inline]
+    pub fn invisible_width(self, c: char) -> Option<usize> {
+        if !is_invisible(c) {
+            return None;
+        }
+
+        match self {
+            ZeroWidth::Hide => Some(0),
+            ZeroWidth::Show => Some(1),
+        }
+    }
+}
+
+/// Format characters without a glyph: the soft hyphen, zero-width spaces
+/// and joiners, bidirectional marks and the byte order mark.
+#[inline]
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{AD}'
+            | '\u{34F}'
+            | '\u{61C}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{206F}'
+            | '\u{FEFF}'
+    )
+}
+
+/// Whether a wide character is an emoji rather than a CJK character. The
+/// wide characters of the symbol and pictograph blocks are all emoji.
+#[inline]
+fn is_wide_emoji(c: char) -> bool {
+    matches!(c as u32, 0x2300..=0x2BFF | 0x1F000..=0x1FAFF)
+}
+
+/// Regional indicator symbols, a pair of them forms a flag.
+#[inline]
+pub fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+/// How bytes which aren't valid UTF-8 in the output of programs are shown.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[schemars(transform = schema_aliases("replace latin1"))]
+pub enum InvalidUtf8 {
+    /// A replacement character for each invalid sequence.
+    #[default]
+    #[serde(alias = "replace")]
+    Replace,
+    /// Each invalid byte is decoded as Latin-1.
+    #[serde(alias = "latin1")]
+    Latin1,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+pub struct Unicode {
+    #[serde(default = "UnicodeVersion::default")]
+    pub version: UnicodeVersion,
+    #[serde(default = "EmojiWidth::default", rename = "emoji-width")]
+    pub emoji_width: EmojiWidth,
+    #[serde(default = "ZeroWidth::default", rename = "zero-width")]
+    pub zero_width: ZeroWidth,
+    #[serde(default = "InvalidUtf8::default", rename = "invalid-utf8")]
+    pub invalid_utf8: InvalidUtf8,
+}
+
+/// Widths of the characters following the Unicode configuration, used by
+/// the grid and the renderer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CharWidth {
+    pub version: UnicodeVersion,
+    pub emoji_width: EmojiWidth,
+    pub zero_width: ZeroWidth,
+}
+
+impl CharWidth {
+    pub fn new(unicode: &Unicode) -> Self {
+        Self {
+            version: unicode.version,
+            emoji_width: unicode.emoji_width,
+            zero_width: unicode.zero_width,
+        }
+    }
+
+    /// Number of cells taken by emoji.
+    #[inline]
+    pub fn emoji_width(self) -> EmojiWidth {
+        if self.version.has_wide_emoji() {
+            self.emoji_width
+        } else {
+            EmojiWidth::Narrow
+        }
+    }
+
+    /// Width of the character in the grid, `None` for control characters.
+    #[inline]
+    pub fn width(self, c: char) -> Option<usize> {
+        if let Some(width) = self.zero_width.invisible_width(c) {
+            return Some(width);
+        }
+
+        if !self.version.has_char(c) {
+            return Some(1);
+        }
+
+        self.emoji_width().char_width(c)
+    }
+
+    /// Cells of a character drawn with an emoji font.
+    #[inline]
+    pub fn emoji_cells(self, c: char) -> usize {
+        if self.version.has_char(c) {
+            self.emoji_width().cells()
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emoji_char_width() {
+        assert_eq!(EmojiWidth::Wide.char_width('😀'), Some(2));
+        assert_eq!(EmojiWidth::Narrow.char_width('😀'), Some(1));
+        assert_eq!(EmojiWidth::Narrow.char_width('⌚'), Some(1));
+        // CJK characters stay wide
+        assert_eq!(EmojiWidth::Narrow.char_width('漢'), Some(2));
+        assert_eq!(EmojiWidth::Narrow.char_width('a'), Some(1));
+        assert_eq!(EmojiWidth::Narrow.char_width('\u{301}'), Some(0));
+    }
+
+    #[test]
+    fn test_char_width_version() {
+        let latest = CharWidth::default();
+        assert_eq!(latest.width('😀'), Some(2));
+        assert_eq!(latest.width('\u{200B}'), Some(0));
+
+        let unicode_8 = CharWidth {
+            version: UnicodeVersion::try_from(8).unwrap(),
+            ..CharWidth::default()
+        };
+        assert_eq!(unicode_8.emoji_width(), EmojiWidth::Narrow);
+        assert_eq!(unicode_8.width('😀'), Some(1));
+        assert_eq!(unicode_8.width('漢'), Some(2));
+
+        // Emoji added after the pinned version take a single cell
+        let unicode_11 = CharWidth {
+            version: UnicodeVersion::try_from(11).unwrap(),
+            ..CharWidth::default()
+        };
+        assert_eq!(unicode_11.width('\u{1F970}'), Some(2));
+        assert_eq!(unicode_11.width('\u{1F971}'), Some(1));
+        assert_eq!(unicode_11.width('\u{1FAE0}'), Some(1));
+        assert_eq!(unicode_11.emoji_cells('\u{1FAE0}'), 1);
+        assert_eq!(latest.width('\u{1FAE0}'), Some(2));
+        assert_eq!(latest.emoji_cells('\u{1FAE0}'), 2);
+
+        assert!(UnicodeVersion::try_from(7).is_err());
+        assert!(UnicodeVersion::try_from(UNICODE_TABLES_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_invisible_width() {
+        assert_eq!(ZeroWidth::Hide.invisible_width('\u{AD}'), Some(0));
+        assert_eq!(ZeroWidth::Hide.invisible_width('\u{200B}'), Some(0));
+        assert_eq!(ZeroWidth::Show.invisible_width('\u{200C}'), Some(1));
+        assert_eq!(ZeroWidth::Show.invisible_width('\u{FEFF}'), Some(1));
+        // Combining marks and variation selectors change the previous
+        // character, they are not invisible
+        assert_eq!(ZeroWidth::Show.invisible_width('\u{301}'), None);
+        assert_eq!(ZeroWidth::Show.invisible_width('\u{FE0F}'), None);
+        assert_eq!(ZeroWidth::Show.invisible_width('a'), None);
+    }
+}
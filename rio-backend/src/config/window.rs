@@ -1,8 +1,11 @@
 use crate::config::defaults::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sugarloaf::schema_aliases;
 use sugarloaf::ImageProperties;
 
-#[derive(Default, Clone, Serialize, Deserialize, Copy, Debug, PartialEq)]
+#[derive(Default, Clone, Serialize, Deserialize, Copy, Debug, PartialEq, JsonSchema)]
+#[schemars(transform = schema_aliases("maximized fullscreen windowed"))]
 pub enum WindowMode {
     #[serde(alias = "maximized")]
     Maximized,
@@ -14,7 +17,8 @@ pub enum WindowMode {
     Windowed,
 }
 
-#[derive(Clone, Serialize, Deserialize, Copy, Debug, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, Copy, Debug, PartialEq, JsonSchema)]
+#[schemars(transform = schema_aliases("enabled disabled transparent buttonless"))]
 pub enum Decorations {
     #[serde(alias = "enabled")]
     Enabled,
@@ -39,7 +43,8 @@ impl Default for Decorations {
     }
 }
 
-#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[schemars(transform = schema_aliases("default donotround round roundsmall"))]
 pub enum WindowsCornerPreference {
     #[serde(alias = "default")]
     Default = 0,
@@ -51,7 +56,7 @@ pub enum WindowsCornerPreference {
     RoundSmall = 3,
 }
 
-#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct Window {
     #[serde(default = "default_window_width")]
     pub width: i32,
@@ -61,6 +66,14 @@ pub struct Window {
     pub mode: WindowMode,
     #[serde(default = "default_opacity")]
     pub opacity: f32,
+    /// Modifiers held while scrolling to change the opacity of the window
+    /// until it's closed, in the format of the key bindings `with`.
+    #[serde(default = "default_opacity_modifiers", rename = "opacity-modifiers")]
+    pub opacity_modifiers: String,
+    /// Width of the border drawn around the window content, using the
+    /// `window-border` colors, `0` draws none.
+    #[serde(default = "f32::default", rename = "border-width")]
+    pub border_width: f32,
     #[serde(default = "bool::default")]
     pub blur: bool,
     #[serde(rename = "background-image", skip_serializing)]
@@ -71,16 +84,28 @@ pub struct Window {
     pub macos_use_unified_titlebar: bool,
     #[serde(rename = "macos-use-shadow", default = "default_bool_true")]
     pub macos_use_shadow: bool,
+    #[serde(rename = "macos-secure-keyboard-entry", default = "bool::default")]
+    pub macos_secure_keyboard_entry: bool,
+    /// Unset quits only when `confirm-before-quit` is disabled.
+    #[serde(
+        rename = "macos-quit-on-last-window-closed",
+        default = "Option::default"
+    )]
+    #[schemars(default)]
+    pub macos_quit_on_last_window_closed: Option<bool>,
     #[serde(rename = "initial-title", skip_serializing)]
     pub initial_title: Option<String>,
     #[serde(rename = "windows-use-undecorated-shadow", default = "Option::default")]
+    #[schemars(default)]
     pub windows_use_undecorated_shadow: Option<bool>,
     #[serde(
         rename = "windows-use-no-redirection-bitmap",
         default = "Option::default"
     )]
+    #[schemars(default)]
     pub windows_use_no_redirection_bitmap: Option<bool>,
     #[serde(rename = "windows-corner-preference", default = "Option::default")]
+    #[schemars(default)]
     pub windows_corner_preference: Option<WindowsCornerPreference>,
 }
 
@@ -91,11 +116,15 @@ impl Default for Window {
             height: default_window_height(),
             mode: WindowMode::default(),
             opacity: default_opacity(),
+            opacity_modifiers: default_opacity_modifiers(),
+            border_width: 0.0,
             background_image: None,
             decorations: Decorations::default(),
             blur: false,
             macos_use_unified_titlebar: false,
             macos_use_shadow: true,
+            macos_secure_keyboard_entry: false,
+            macos_quit_on_last_window_closed: None,
             initial_title: None,
             windows_use_undecorated_shadow: None,
             windows_use_no_redirection_bitmap: None,
@@ -0,0 +1,559 @@
+//! Serializes the grid content with its styling, either as ANSI escape
+//! sequences, as a standalone HTML document or as an RTF document.
+
+use crate::config::colors::term::{List, TermColors, DIM_FACTOR};
+use crate::config::colors::{AnsiColor, ColorArray, NamedColor};
+use crate::crosswords::pos::Line;
+use crate::crosswords::snapshot::{SnapshotLine, SnapshotRun, SnapshotStyle};
+use crate::crosswords::square::Flags;
+use crate::crosswords::Crosswords;
+use crate::event::EventListener;
+use std::fmt::Write;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Ansi,
+    Html,
+    Rtf,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ansi => "ansi",
+            ExportFormat::Html => "html",
+            ExportFormat::Rtf => "rtf",
+        }
+    }
+}
+
+impl<U: EventListener> Crosswords<U> {
+    /// Serializes the lines, the palette is used to resolve the colors
+    /// of the HTML document.
+    pub fn export(
+        &self,
+        lines: Range<Line>,
+        format: ExportFormat,
+        palette: &List,
+    ) -> String {
+        serialize(&self.snapshot(lines), format, &self.colors, palette)
+    }
+
+    /// Serializes the selected cells, `None` without a selection.
+    pub fn export_selection(
+        &self,
+        format: ExportFormat,
+        palette: &List,
+    ) -> Option<String> {
+        let lines = self.selection_snapshot()?;
+        Some(serialize(&lines, format, &self.colors, palette))
+    }
+}
+
+fn serialize(
+    lines: &[SnapshotLine],
+    format: ExportFormat,
+    term_colors: &TermColors,
+    palette: &List,
+) -> String {
+    match format {
+        ExportFormat::Ansi => ansi(lines),
+        ExportFormat::Html => html(lines, term_colors, palette),
+        ExportFormat::Rtf => rtf(lines, term_colors, palette),
+    }
+}
+
+fn ansi(lines: &[SnapshotLine]) -> String {
+    let mut output = String::new();
+    for line in lines {
+        for SnapshotRun { style, text } in &line.runs {
+            if let Some(hyperlink) = &style.hyperlink {
+                let _ = write!(
+                    output,
+                    "\x1b]8;id={};{}\x1b\\",
+                    hyperlink.id(),
+                    hyperlink.uri()
+                );
+            }
+
+            output.push_str("\x1b[0");
+            push_sgr(&mut output, style);
+            output.push('m');
+            output.push_str(text);
+
+            if style.hyperlink.is_some() {
+                output.push_str("\x1b]8;;\x1b\\");
+            }
+        }
+
+        if !line.runs.is_empty() {
+            output.push_str("\x1b[0m");
+        }
+        if !line.is_wrapped {
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+fn push_sgr(output: &mut String, style: &SnapshotStyle) {
+    let flags = style.flags;
+    for (flag, sgr) in [
+        (Flags::BOLD, ";1"),
+        (Flags::DIM, ";2"),
+        (Flags::ITALIC, ";3"),
+        (Flags::UNDERLINE, ";4"),
+        (Flags::DOUBLE_UNDERLINE, ";4:2"),
+        (Flags::UNDERCURL, ";4:3"),
+        (Flags::DOTTED_UNDERLINE, ";4:4"),
+        (Flags::DASHED_UNDERLINE, ";4:5"),
+        (Flags::INVERSE, ";7"),
+        (Flags::HIDDEN, ";8"),
+        (Flags::STRIKEOUT, ";9"),
+    ] {
+        if flags.contains(flag) {
+            output.push_str(sgr);
+        }
+    }
+
+    push_sgr_color(output, style.fg, 30, 90, 38);
+    push_sgr_color(output, style.bg, 40, 100, 48);
+    if let Some(color) = style.underline_color {
+        push_sgr_color(output, color, 0, 0, 58);
+    }
+}
+
+fn push_sgr_color(
+    output: &mut String,
+    color: AnsiColor,
+    base: u8,
+    light: u8,
+    extended: u8,
+) {
+    let index = match color {
+        AnsiColor::Spec(rgb) => {
+            let _ = write!(output, ";{extended};2;{};{};{}", rgb.r, rgb.g, rgb.b);
+            return;
+        }
+        AnsiColor::Indexed(index) => index as usize,
+        AnsiColor::Named(named) if named >= NamedColor::DimBlack => {
+            // Dim colors are drawn from the DIM flag
+            let index = named as usize - NamedColor::DimBlack as usize;
+            if index >= 8 {
+                return;
+            }
+            index
+        }
+        // The default colors are not written
+        AnsiColor::Named(named) if named >= NamedColor::Foreground => return,
+        AnsiColor::Named(named) => named as usize,
+    };
+
+    match index {
+        0..=7 if base != 0 => {
+            let _ = write!(output, ";{}", base as usize + index);
+        }
+        8..=15 if light != 0 => {
+            let _ = write!(output, ";{}", light as usize + index - 8);
+        }
+        _ => {
+            let _ = write!(output, ";{extended};5;{index}");
+        }
+    }
+}
+
+fn resolve_color(
+    color: AnsiColor,
+    term_colors: &TermColors,
+    palette: &List,
+) -> ColorArray {
+    match color {
+        AnsiColor::Spec(rgb) => rgb.to_arr(),
+        AnsiColor::Indexed(index) => {
+            term_colors[index as usize].unwrap_or(palette[index as usize])
+        }
+        AnsiColor::Named(named) => {
+            term_colors[named as usize].unwrap_or(palette[named as usize])
+        }
+    }
+}
+
+/// Colors the run is drawn with, once the inverse and dim flags are applied.
+fn run_colors(
+    style: &SnapshotStyle,
+    resolve: &impl Fn(AnsiColor) -> ColorArray,
+) -> (ColorArray, ColorArray) {
+    let (fg, bg) = if style.flags.contains(Flags::INVERSE) {
+        (style.bg, style.fg)
+    } else {
+        (style.fg, style.bg)
+    };
+
+    let mut fg = resolve(fg);
+    if style.flags.contains(Flags::DIM) {
+        for channel in &mut fg[..3] {
+            *channel *= DIM_FACTOR;
+        }
+    }
+    (fg, resolve(bg))
+}
+
+/// Schemes of the hyperlinks kept in the HTML. The URIs come from the
+/// programs running in the terminal, others like `javascript:` would run
+/// in the browser or document the HTML is opened or pasted in.
+const HTML_LINK_SCHEMES: [&str; 4] = ["http", "https", "file", "mailto"];
+
+fn is_html_link_allowed(uri: &str) -> bool {
+    uri.split_once(':').is_some_and(|(scheme, _)| {
+        HTML_LINK_SCHEMES
+            .iter()
+            .any(|allowed| scheme.eq_ignore_ascii_case(allowed))
+    })
+}
+
+fn html(lines: &[SnapshotLine], term_colors: &TermColors, palette: &List) -> String {
+    let resolve = |color: AnsiColor| resolve_color(color, term_colors, palette);
+
+    let foreground = css_color(resolve(AnsiColor::Named(NamedColor::Foreground)));
+    let background = css_color(resolve(AnsiColor::Named(NamedColor::Background)));
+
+    let mut output = String::new();
+    let _ = write!(
+        output,
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>Rio</title>\n\
+         <style>\n\
+         body {{ margin: 0; background: {background}; }}\n\
+         pre {{ margin: 0; padding: 8px; color: {foreground}; \
+         font-family: ui-monospace, monospace; white-space: pre-wrap; \
+         word-break: break-all; }}\n\
+         a {{ color: inherit; }}\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <pre>"
+    );
+
+    for line in lines {
+        for SnapshotRun { style, text } in &line.runs {
+            // Links with other schemes are written as plain text
+            let link = style
+                .hyperlink
+                .as_ref()
+                .map(|hyperlink| hyperlink.uri())
+                .filter(|uri| is_html_link_allowed(uri));
+            if let Some(uri) = link {
+                output.push_str("<a href=\"");
+                push_escaped(&mut output, uri);
+                output.push_str("\">");
+            }
+
+            let css = css_style(style, &resolve);
+            if !css.is_empty() {
+                let _ = write!(output, "<span style=\"{css}\">");
+            }
+            push_escaped(&mut output, text);
+            if !css.is_empty() {
+                output.push_str("</span>");
+            }
+
+            if link.is_some() {
+                output.push_str("</a>");
+            }
+        }
+
+        if !line.is_wrapped {
+            output.push('\n');
+        }
+    }
+
+    output.push_str("</pre>\n</body>\n</html>\n");
+    output
+}
+
+fn css_style(
+    style: &SnapshotStyle,
+    resolve: &impl Fn(AnsiColor) -> ColorArray,
+) -> String {
+    let default = SnapshotStyle::default();
+    let mut css = String::new();
+
+    let (fg, bg) = run_colors(style, resolve);
+    let is_inverse = style.flags.contains(Flags::INVERSE);
+
+    if style.fg != default.fg || style.flags.intersects(Flags::DIM | Flags::INVERSE) {
+        let _ = write!(css, "color: {};", css_color(fg));
+    }
+    if style.bg != default.bg || is_inverse {
+        let _ = write!(css, "background: {};", css_color(bg));
+    }
+    if style.flags.contains(Flags::BOLD) {
+        css.push_str("font-weight: bold;");
+    }
+    if style.flags.contains(Flags::ITALIC) {
+        css.push_str("font-style: italic;");
+    }
+    if style.flags.contains(Flags::HIDDEN) {
+        css.push_str("visibility: hidden;");
+    }
+
+    let underline = [
+        (Flags::UNDERLINE, "solid"),
+        (Flags::DOUBLE_UNDERLINE, "double"),
+        (Flags::UNDERCURL, "wavy"),
+        (Flags::DOTTED_UNDERLINE, "dotted"),
+        (Flags::DASHED_UNDERLINE, "dashed"),
+    ]
+    .into_iter()
+    .find(|(flag, _)| style.flags.contains(*flag));
+    let is_strikeout = style.flags.contains(Flags::STRIKEOUT);
+
+    match (underline, is_strikeout) {
+        (Some(_), true) => css.push_str("text-decoration-line: underline line-through;"),
+        (Some(_), false) => css.push_str("text-decoration-line: underline;"),
+        (None, true) => css.push_str("text-decoration-line: line-through;"),
+        (None, false) => {}
+    }
+    if let Some((_, decoration_style)) = underline {
+        let _ = write!(css, "text-decoration-style: {decoration_style};");
+        if let Some(color) = style.underline_color {
+            let _ = write!(css, "text-decoration-color: {};", css_color(resolve(color)));
+        }
+    }
+
+    css
+}
+
+fn css_color(color: ColorArray) -> String {
+    let [r, g, b] = rgb8(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn rgb8(color: ColorArray) -> [u8; 3] {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [channel(color[0]), channel(color[1]), channel(color[2])]
+}
+
+fn push_escaped(output: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+/// Every run has its colors written, so the text keeps the terminal
+/// colors when pasted in a document with another background.
+fn rtf(lines: &[SnapshotLine], term_colors: &TermColors, palette: &List) -> String {
+    let resolve = |color: AnsiColor| resolve_color(color, term_colors, palette);
+
+    // The index 0 of the color table is the document default
+    let mut colors: Vec<[u8; 3]> = Vec::new();
+    let mut color_index = |color: ColorArray| {
+        let rgb = rgb8(color);
+        let index = colors.iter().position(|c| *c == rgb).unwrap_or_else(|| {
+            colors.push(rgb);
+            colors.len() - 1
+        });
+        index + 1
+    };
+
+    let mut body = String::new();
+    for line in lines {
+        for SnapshotRun { style, text } in &line.runs {
+            let (fg, bg) = run_colors(style, &resolve);
+            let (fg, bg) = (color_index(fg), color_index(bg));
+            let _ = write!(body, "{{\\cf{fg}\\cb{bg}\\chcbpat{bg}");
+            for (flag, control) in [
+                (Flags::BOLD, "\\b"),
+                (Flags::ITALIC, "\\i"),
+                (Flags::ALL_UNDERLINES, "\\ul"),
+                (Flags::STRIKEOUT, "\\strike"),
+                (Flags::HIDDEN, "\\v"),
+            ] {
+                if style.flags.intersects(flag) {
+                    body.push_str(control);
+                }
+            }
+            body.push(' ');
+            push_rtf_escaped(&mut body, text);
+            body.push('}');
+        }
+
+        if !line.is_wrapped {
+            body.push_str("\\line\n");
+        }
+    }
+
+    let mut output = String::from(
+        "{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Courier New;}}{\\colortbl;",
+    );
+    for [r, g, b] in colors {
+        let _ = write!(output, "\\red{r}\\green{g}\\blue{b};");
+    }
+    output.push_str("}\n\\f0 ");
+    output.push_str(&body);
+    output.push_str("}\n");
+    output
+}
+
+fn push_rtf_escaped(output: &mut String, text: &str) {
+    let mut units = [0; 2];
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                output.push('\\');
+                output.push(c);
+            }
+            ' '..='~' => output.push(c),
+            // Written as signed UTF-16 code units, `?` is shown by
+            // readers without unicode support
+            _ => {
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(output, "\\u{}?", *unit as i16);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ansi::CursorShape;
+    use crate::config::colors::Colors;
+    use crate::crosswords::attr::Attr;
+    use crate::crosswords::pos::{Column, Pos, Side};
+    use crate::crosswords::square::Hyperlink;
+    use crate::crosswords::CrosswordsSize;
+    use crate::event::{VoidListener, WindowId};
+    use crate::performer::handler::Handler;
+    use crate::selection::{Selection, SelectionType};
+
+    fn styled_term() -> Crosswords<VoidListener> {
+        let size = CrosswordsSize::new(10, 3);
+        let mut cw = Crosswords::new(
+            size,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+            0,
+        );
+
+        cw.terminal_attribute(Attr::Bold);
+        cw.terminal_attribute(Attr::Foreground(AnsiColor::Named(NamedColor::Red)));
+        for c in "a<b".chars() {
+            cw.input(c);
+        }
+        cw.terminal_attribute(Attr::Reset);
+        cw.set_hyperlink(Some(Hyperlink::new(Some("1"), "https://rioterm.com")));
+        cw.input('c');
+        cw.set_hyperlink(None);
+        cw.carriage_return();
+        cw.linefeed();
+        cw.input('d');
+        cw
+    }
+
+    #[test]
+    fn test_export_ansi() {
+        let cw = styled_term();
+        let palette = List::from(&Colors::default());
+
+        assert_eq!(
+            cw.export(cw.viewport_lines(), ExportFormat::Ansi, &palette),
+            "\x1b[0;1;31ma<b\x1b]8;id=1;https://rioterm.com\x1b\\\x1b[0mc\
+             \x1b]8;;\x1b\\\x1b[0m\n\x1b[0md\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_export_html() {
+        let cw = styled_term();
+        let colors = Colors::default();
+        let palette = List::from(&colors);
+        let html = cw.export(cw.history_lines(), ExportFormat::Html, &palette);
+
+        let red = css_color(colors.red);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(&format!(
+            "<span style=\"color: {red};font-weight: bold;\">a&lt;b</span>\
+             <a href=\"https://rioterm.com\">c</a>\nd\n</pre>"
+        )));
+    }
+
+    #[test]
+    fn test_export_html_drops_unsafe_links() {
+        let mut cw = styled_term();
+        cw.set_hyperlink(Some(Hyperlink::new(None, "javascript:alert(1)")));
+        cw.input('e');
+        cw.set_hyperlink(Some(Hyperlink::new(None, "MAILTO:rio@example.com")));
+        cw.input('f');
+        cw.set_hyperlink(None);
+        let palette = List::from(&Colors::default());
+        let html = cw.export(cw.history_lines(), ExportFormat::Html, &palette);
+
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("de<a href=\"MAILTO:rio@example.com\">f</a>\n"));
+
+        assert!(is_html_link_allowed("file:///tmp/rio"));
+        assert!(!is_html_link_allowed("data:text/html,rio"));
+        assert!(!is_html_link_allowed("rioterm.com"));
+    }
+
+    #[test]
+    fn test_export_rtf() {
+        let cw = styled_term();
+        let colors = Colors::default();
+        let palette = List::from(&colors);
+        let rtf = cw.export(cw.viewport_lines(), ExportFormat::Rtf, &palette);
+
+        let [r, g, b] = rgb8(colors.red);
+        assert!(rtf.starts_with(&format!(
+            "{{\\rtf1\\ansi\\deff0{{\\fonttbl{{\\f0\\fmodern Courier New;}}}}\
+             {{\\colortbl;\\red{r}\\green{g}\\blue{b};"
+        )));
+        assert!(
+            rtf.contains("{\\cf1\\cb2\\chcbpat2\\b a<b}{\\cf3\\cb2\\chcbpat2 c}\\line\n")
+        );
+        assert!(rtf.ends_with("{\\cf3\\cb2\\chcbpat2 d}\\line\n}\n"));
+
+        let mut escaped = String::new();
+        push_rtf_escaped(&mut escaped, "{é😀}");
+        assert_eq!(escaped, "\\{\\u233?\\u-10179?\\u-8704?\\}");
+    }
+
+    #[test]
+    fn test_export_selection() {
+        let mut cw = styled_term();
+        let palette = List::from(&Colors::default());
+        assert_eq!(cw.export_selection(ExportFormat::Ansi, &palette), None);
+
+        cw.selection = Some(Selection::new(
+            SelectionType::Simple,
+            Pos::new(Line(0), Column(1)),
+            Side::Left,
+        ));
+        if let Some(selection) = cw.selection.as_mut() {
+            selection.update(Pos::new(Line(1), Column(0)), Side::Right);
+        }
+
+        assert_eq!(
+            cw.export_selection(ExportFormat::Ansi, &palette).as_deref(),
+            Some(
+                "\x1b[0;1;31m<b\x1b]8;id=1;https://rioterm.com\x1b\\\x1b[0mc\
+                 \x1b]8;;\x1b\\\x1b[0m\n\x1b[0md\x1b[0m\n"
+            )
+        );
+    }
+}
@@ -240,7 +240,9 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
         }
     }
 
-    pub fn clear_viewport<D>(&mut self)
+    /// Clears the viewport by scrolling its content into the history,
+    /// returns the number of lines scrolled.
+    pub fn clear_viewport<D>(&mut self) -> usize
     where
         T: ResetDiscriminant<D>,
         D: PartialEq,
@@ -264,6 +266,8 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
         for line in (0..(self.lines - positions)).map(Line::from) {
             self.raw[line].reset(&self.cursor.template);
         }
+
+        positions
     }
 
     /// Completely reset the grid state.
@@ -0,0 +1,561 @@
+//! Placement and deletion of kitty graphics protocol images in the grid.
+//!
+//! Placements are attached to the cells like Sixel graphics, so they scroll
+//! and are erased with the text. Each texture remembers the image and
+//! placement ids it was created for, which is what deletions match against.
+//!
+//! Virtual placements are not drawn by themselves, each cell holding a
+//! Unicode placeholder gets the part of the placement's texture it shows.
+//!
+//! Textures of animated images get all the frames of their placement,
+//! which are made again whenever the frames or the animation change.
+
+use crate::ansi::graphics::{AnimationFrame, GraphicCell, TextureRef};
+use crate::ansi::kitty_graphics::{
+    self, KittyError, KittyImage, KittyImageDelete, KittyImagePlacement,
+    KittyPlacementId, PlacedImage, Placeholder, VirtualPlacement, PLACEHOLDER,
+};
+use crate::crosswords::grid::Dimensions;
+use crate::crosswords::pos::{Column, Line};
+use crate::crosswords::square::Flags;
+use crate::crosswords::Crosswords;
+use crate::event::{EventListener, RioEvent};
+use crate::performer::handler::Handler;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
+use sugarloaf::{
+    GraphicData, GraphicId, ResizeCommand, ResizeParameter, MAX_GRAPHIC_DIMENSIONS,
+};
+use tracing::debug;
+
+impl<U: EventListener> Crosswords<U> {
+    pub(super) fn kitty_graphics_command(&mut self, command: KittyImage) {
+        let Some(command) = self.graphics.kitty.accumulate(command) else {
+            return;
+        };
+
+        let verbosity = command.verbosity();
+        let mut image_id = command.image_id();
+        let image_number = command.image_number();
+        let placement_id = command.placement_id();
+
+        let result = match command {
+            KittyImage::TransmitData { transmit, .. } => {
+                self.graphics.kitty.transmit(transmit).map(|id| {
+                    if image_number.is_some() {
+                        image_id = Some(id);
+                    }
+                })
+            }
+            KittyImage::TransmitDataAndDisplay {
+                transmit,
+                placement,
+                ..
+            } => self.graphics.kitty.transmit(transmit).and_then(|id| {
+                if image_number.is_some() {
+                    image_id = Some(id);
+                }
+                self.kitty_place(id, &placement)
+            }),
+            KittyImage::Display { placement, .. } => {
+                match self.graphics.kitty.find(image_id, image_number) {
+                    Some(id) => self.kitty_place(id, &placement),
+                    None => Err(KittyError::new("ENOENT", "image not found")),
+                }
+            }
+            KittyImage::Query { transmit } => self.graphics.kitty.query(transmit),
+            KittyImage::Delete { what, .. } => {
+                self.kitty_delete(what);
+                return;
+            }
+            KittyImage::TransmitFrame {
+                transmit, frame, ..
+            } => match self.graphics.kitty.find(image_id, image_number) {
+                Some(id) => self
+                    .graphics
+                    .kitty
+                    .transmit_frame(id, transmit, &frame)
+                    .map(|()| self.kitty_animate(id)),
+                None => Err(KittyError::new("ENOENT", "image not found")),
+            },
+            KittyImage::ComposeFrame { frame, .. } => {
+                match self.graphics.kitty.find(image_id, image_number) {
+                    Some(id) => self
+                        .graphics
+                        .kitty
+                        .compose_frame(id, &frame)
+                        .map(|()| self.kitty_animate(id)),
+                    None => Err(KittyError::new("ENOENT", "image not found")),
+                }
+            }
+            KittyImage::AnimationControl { animation, .. } => {
+                match self.graphics.kitty.find(image_id, image_number) {
+                    Some(id) => self
+                        .graphics
+                        .kitty
+                        .control_animation(id, &animation)
+                        .map(|()| self.kitty_animate(id)),
+                    None => Err(KittyError::new("ENOENT", "image not found")),
+                }
+            }
+        };
+
+        if let Err(err) = &result {
+            debug!("kitty graphics command failed: {err}");
+        }
+        self.reclaim_graphics_memory();
+
+        if let Some(response) = kitty_graphics::response(
+            image_id,
+            image_number,
+            placement_id,
+            verbosity,
+            &result,
+        ) {
+            self.event_proxy
+                .send_event(RioEvent::PtyWrite(self.route_id, response), self.window_id);
+        }
+    }
+
+    /// Place the image at the cursor. Placing again with the same image and
+    /// placement ids moves the placement.
+    fn kitty_place(
+        &mut self,
+        image_id: u32,
+        placement: &KittyImagePlacement,
+    ) -> Result<(), KittyError> {
+        if placement.unicode_placeholder {
+            return self.kitty_virtual_place(image_id, placement);
+        }
+
+        let graphic = self.graphics.kitty.placement_graphic(image_id, placement)?;
+
+        let kitty = KittyPlacementId {
+            image_id,
+            placement_id: placement.placement_id.unwrap_or(0),
+        };
+        if kitty.placement_id != 0 {
+            self.remove_kitty_placements(self.all_lines(), |texture| {
+                texture.kitty == Some(kitty)
+            });
+        }
+
+        let scrolling = !placement.do_not_move_cursor;
+        let (id, columns) = self
+            .place_graphic(
+                graphic,
+                self.grid.cursor.pos,
+                scrolling,
+                placement.z_index.unwrap_or(0),
+                Some(kitty),
+            )
+            .ok_or_else(|| KittyError::new("EINVAL", "unable to place the image"))?;
+        self.kitty_animate_texture(
+            id,
+            PlacedImage {
+                image_id,
+                placement: placement.clone(),
+                cells: None,
+            },
+        );
+
+        // The cursor ends after the last column, in the last row of the image
+        if scrolling {
+            self.move_forward(Column(columns));
+        }
+
+        Ok(())
+    }
+
+    /// Create a virtual placement, the image is scaled to fit its columns
+    /// and rows and centered. The cursor does not move.
+    fn kitty_virtual_place(
+        &mut self,
+        image_id: u32,
+        placement: &KittyImagePlacement,
+    ) -> Result<(), KittyError> {
+        let cell_width = self.graphics.cell_width as usize;
+        let cell_height = self.graphics.cell_height as usize;
+        if cell_width == 0 || cell_height == 0 {
+            return Err(KittyError::new("EINVAL", "unable to place the image"));
+        }
+
+        let graphic = self.graphics.kitty.placement_graphic(image_id, placement)?;
+        let columns = placement
+            .columns
+            .filter(|columns| *columns != 0)
+            .unwrap_or(graphic.width.div_ceil(cell_width) as u32);
+        let rows = placement
+            .rows
+            .filter(|rows| *rows != 0)
+            .unwrap_or(graphic.height.div_ceil(cell_height) as u32);
+        let width = columns as usize * cell_width;
+        let height = rows as usize * cell_height;
+        if width > MAX_GRAPHIC_DIMENSIONS[0] || height > MAX_GRAPHIC_DIMENSIONS[1] {
+            return Err(KittyError::new("EINVAL", "the placement is too large"));
+        }
+
+        let graphic = self
+            .fit_to_cells(graphic, columns, rows)
+            .ok_or_else(|| KittyError::new("EINVAL", "unable to scale the image"))?;
+
+        let kitty = KittyPlacementId {
+            image_id,
+            placement_id: placement.placement_id.unwrap_or(0),
+        };
+        self.remove_kitty_placements(self.all_lines(), |texture| {
+            texture.kitty == Some(kitty)
+        });
+
+        let id = self.graphics.next_id();
+        let texture = Arc::new(TextureRef {
+            id,
+            width: width as u16,
+            height: height as u16,
+            cell_height,
+            z_index: placement.z_index.unwrap_or(0),
+            kitty: Some(kitty),
+            texture_operations: Arc::downgrade(&self.graphics.texture_operations),
+        });
+        self.graphics.track_texture(id, graphic.pixels.len());
+        self.graphics.pending.push(GraphicData { id, ..graphic });
+        self.graphics.kitty.insert_virtual_placement(
+            kitty,
+            VirtualPlacement {
+                texture,
+                columns,
+                rows,
+            },
+        );
+        self.kitty_animate_texture(
+            id,
+            PlacedImage {
+                image_id,
+                placement: placement.clone(),
+                cells: Some((columns, rows)),
+            },
+        );
+
+        // Placeholders already on the screen show it now
+        let display_offset = self.grid.display_offset() as i32;
+        for row in 0..self.grid.screen_lines() as i32 {
+            self.attach_placeholders(Line(row - display_offset), 0..self.grid.columns());
+        }
+
+        Ok(())
+    }
+
+    /// Scale the graphic to fit the columns and rows, centered. `None` if
+    /// the cell size is still unknown.
+    fn fit_to_cells(
+        &self,
+        graphic: GraphicData,
+        columns: u32,
+        rows: u32,
+    ) -> Option<GraphicData> {
+        let cell_width = self.graphics.cell_width as usize;
+        let cell_height = self.graphics.cell_height as usize;
+        let width = columns as usize * cell_width;
+        let height = rows as usize * cell_height;
+
+        let graphic = GraphicData {
+            resize: Some(ResizeCommand {
+                width: ResizeParameter::Cells(columns),
+                height: ResizeParameter::Cells(rows),
+                preserve_aspect_ratio: true,
+            }),
+            ..graphic
+        }
+        .resized(cell_width, cell_height, width, height)?;
+        Some(centered(graphic, width, height))
+    }
+
+    /// Make again the frames of the textures showing the image, after its
+    /// frames or its animation changed.
+    fn kitty_animate(&mut self, image_id: u32) {
+        // Forget the textures dropped from the grid
+        self.graphics.used_memory();
+
+        for (id, placed) in self.graphics.kitty.placed_textures(image_id) {
+            self.kitty_animate_texture(id, placed);
+        }
+    }
+
+    /// Animate the texture if its image has frames, which are placed and
+    /// scaled like the texture.
+    fn kitty_animate_texture(&mut self, id: GraphicId, placed: PlacedImage) {
+        let animation = self
+            .graphics
+            .kitty
+            .placement_animation(placed.image_id, &placed.placement);
+        self.graphics.kitty.track_placement(id, placed.clone());
+
+        let mut animation = match animation {
+            Ok(Some(animation)) => animation,
+            // Still images keep the graphic they were placed with
+            Ok(None) | Err(_) => return,
+        };
+
+        let frames = std::mem::take(&mut animation.frames)
+            .into_iter()
+            .map(|frame| {
+                let graphic = match placed.cells {
+                    Some((columns, rows)) => {
+                        self.fit_to_cells(frame.graphic, columns, rows)
+                    }
+                    None => self.resize_graphic(frame.graphic),
+                }?;
+                Some(AnimationFrame { graphic, ..frame })
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(frames) = frames {
+            animation.frames = frames;
+            self.graphics.animate(id, animation);
+        }
+    }
+
+    /// Attach to the placeholder cell the part of the virtual placement it
+    /// shows, or nothing if there is no such placement. The cells after it
+    /// continue its row and column, so the whole run of placeholders is
+    /// decoded again.
+    pub(super) fn attach_placeholder(&mut self, line: Line, column: Column) {
+        let row = &self.grid[line];
+        let start = (0..column.0)
+            .rev()
+            .take_while(|column| row[Column(*column)].c == PLACEHOLDER)
+            .last()
+            .unwrap_or(column.0);
+        let end = (column.0..self.grid.columns())
+            .take_while(|column| row[Column(*column)].c == PLACEHOLDER)
+            .last()
+            .map_or(column.0, |column| column + 1);
+        self.attach_placeholders(line, start..end);
+    }
+
+    /// Attach to the placeholder cells of the columns the parts of the
+    /// virtual placements they show. Each cell is decoded once, left to
+    /// right, carrying the row and column of the cell before it.
+    fn attach_placeholders(&mut self, line: Line, columns: Range<usize>) {
+        let mut left = None;
+        let mut attached = false;
+        for column in columns {
+            let square = &self.grid[line][Column(column)];
+            if square.c != PLACEHOLDER {
+                left = None;
+                continue;
+            }
+
+            let placeholder = Placeholder::decode(
+                square.fg,
+                square.underline_color(),
+                square.zerowidth().unwrap_or_default(),
+                left,
+            );
+            left = placeholder;
+            let graphic =
+                placeholder.and_then(|placeholder| self.placeholder_graphic(placeholder));
+
+            let square = &mut self.grid[line][Column(column)];
+            drop(square.take_graphics());
+            match graphic {
+                Some(graphic) => square.set_graphics(smallvec::smallvec![graphic]),
+                None => square.flags.remove(Flags::GRAPHICS),
+            }
+            attached = true;
+        }
+
+        if attached && line.0 >= 0 {
+            self.mark_line_damaged(line);
+        }
+    }
+
+    /// Part of its virtual placement the placeholder shows.
+    fn placeholder_graphic(&self, placeholder: Placeholder) -> Option<GraphicCell> {
+        let placement = self
+            .graphics
+            .kitty
+            .virtual_placement(placeholder.image_id, placeholder.placement_id)?;
+        if placeholder.row >= placement.rows || placeholder.column >= placement.columns {
+            return None;
+        }
+
+        let texture = placement.texture.clone();
+        let offset_x = placeholder.column * texture.width as u32 / placement.columns;
+        let offset_y = placeholder.row * texture.height as u32 / placement.rows;
+        Some(GraphicCell {
+            texture,
+            offset_x: offset_x as u16,
+            offset_y: offset_y as u16,
+            texture_operations: Arc::downgrade(&self.graphics.texture_operations),
+        })
+    }
+
+    /// Delete placements, the uppercase variants also free the data of the
+    /// images whose placements were deleted.
+    fn kitty_delete(&mut self, what: KittyImageDelete) {
+        let screen = Line(0)..Line(self.grid.screen_lines() as i32);
+
+        let (image_ids, delete) = match what {
+            KittyImageDelete::All { delete } => {
+                (self.remove_kitty_placements(screen, |_| true), delete)
+            }
+            KittyImageDelete::ByImageId {
+                image_id,
+                placement_id,
+                delete,
+            } => (self.remove_image_placements(image_id, placement_id), delete),
+            KittyImageDelete::ByImageNumber {
+                image_number,
+                placement_id,
+                delete,
+            } => match self.graphics.kitty.find(None, Some(image_number)) {
+                Some(image_id) => {
+                    (self.remove_image_placements(image_id, placement_id), delete)
+                }
+                None => return,
+            },
+            KittyImageDelete::AtCursorPosition { delete } => {
+                let cursor = self.grid.cursor.pos;
+                let textures = self.kitty_textures_at(cursor.row, cursor.col);
+                (self.remove_textures(&textures, None), delete)
+            }
+            KittyImageDelete::DeleteAt { x, y, delete } => {
+                let textures = self.kitty_textures_at(
+                    Line(y.saturating_sub(1) as i32),
+                    Column(x.saturating_sub(1) as usize),
+                );
+                (self.remove_textures(&textures, None), delete)
+            }
+            KittyImageDelete::DeleteAtZ { x, y, z, delete } => {
+                let textures = self.kitty_textures_at(
+                    Line(y.saturating_sub(1) as i32),
+                    Column(x.saturating_sub(1) as usize),
+                );
+                (self.remove_textures(&textures, Some(z)), delete)
+            }
+            KittyImageDelete::DeleteColumn { x, delete } => {
+                let column = Column(x.saturating_sub(1) as usize);
+                let mut textures = HashSet::new();
+                for row in 0..self.grid.screen_lines() as i32 {
+                    textures.extend(self.kitty_textures_at(Line(row), column));
+                }
+                (self.remove_textures(&textures, None), delete)
+            }
+            KittyImageDelete::DeleteRow { y, delete } => {
+                let line = Line(y.saturating_sub(1) as i32);
+                let mut textures = HashSet::new();
+                for column in 0..self.grid.columns() {
+                    textures.extend(self.kitty_textures_at(line, Column(column)));
+                }
+                (self.remove_textures(&textures, None), delete)
+            }
+            KittyImageDelete::DeleteZ { z, delete } => (
+                self.remove_kitty_placements(screen, |texture| texture.z_index == z),
+                delete,
+            ),
+            // Frames are replaced by transmitting them again, deleting them
+            // is not supported
+            KittyImageDelete::AnimationFrames { .. } => return,
+        };
+
+        if delete {
+            for image_id in image_ids {
+                self.graphics.kitty.remove(image_id);
+            }
+        }
+    }
+
+    fn remove_image_placements(
+        &mut self,
+        image_id: u32,
+        placement_id: Option<u32>,
+    ) -> HashSet<u32> {
+        let placement_id = placement_id.filter(|id| *id != 0);
+        let mut image_ids = self.remove_kitty_placements(self.all_lines(), |texture| {
+            texture.kitty.is_some_and(|kitty| {
+                kitty.image_id == image_id
+                    && placement_id.is_none_or(|id| kitty.placement_id == id)
+            })
+        });
+
+        // The image data is freed even if it was never placed
+        if placement_id.is_none() {
+            image_ids.insert(image_id);
+        }
+
+        image_ids
+    }
+
+    /// Remove every placement of the textures, in any line, optionally only
+    /// the ones with the z-index.
+    fn remove_textures(
+        &mut self,
+        textures: &HashSet<GraphicId>,
+        z_index: Option<i32>,
+    ) -> HashSet<u32> {
+        if textures.is_empty() {
+            return HashSet::new();
+        }
+
+        self.remove_kitty_placements(self.all_lines(), |texture| {
+            textures.contains(&texture.id) && z_index.is_none_or(|z| texture.z_index == z)
+        })
+    }
+
+    /// Kitty textures drawn in the cell, nothing if it is outside the screen.
+    fn kitty_textures_at(&self, line: Line, column: Column) -> HashSet<GraphicId> {
+        if line.0 >= self.grid.screen_lines() as i32 || column.0 >= self.grid.columns() {
+            return HashSet::new();
+        }
+
+        self.grid[line][column]
+            .graphics()
+            .into_iter()
+            .flatten()
+            .filter(|graphic| graphic.texture.kitty.is_some())
+            .map(|graphic| graphic.texture.id)
+            .collect()
+    }
+
+    /// Remove the kitty placements matching the predicate from the lines,
+    /// returning the ids of their images.
+    fn remove_kitty_placements(
+        &mut self,
+        lines: Range<Line>,
+        predicate: impl Fn(&TextureRef) -> bool,
+    ) -> HashSet<u32> {
+        let predicate =
+            |texture: &TextureRef| texture.kitty.is_some() && predicate(texture);
+        let mut image_ids = self.graphics.kitty.remove_virtual_placements(predicate);
+        image_ids.extend(self.remove_graphics(lines, predicate));
+        image_ids
+    }
+}
+
+/// Centers the RGBA graphic in a transparent canvas of the size.
+fn centered(graphic: GraphicData, width: usize, height: usize) -> GraphicData {
+    if graphic.width >= width && graphic.height >= height {
+        return graphic;
+    }
+
+    let left = width.saturating_sub(graphic.width) / 2;
+    let top = height.saturating_sub(graphic.height) / 2;
+    let columns = graphic.width.min(width);
+    let mut pixels = vec![0; width * height * 4];
+    for row in 0..graphic.height.min(height) {
+        let source = row * graphic.width * 4;
+        let destination = ((top + row) * width + left) * 4;
+        pixels[destination..destination + columns * 4]
+            .copy_from_slice(&graphic.pixels[source..source + columns * 4]);
+    }
+
+    GraphicData {
+        width,
+        height,
+        pixels,
+        is_opaque: false,
+        resize: None,
+        ..graphic
+    }
+}
@@ -15,26 +15,38 @@
 */
 
 pub mod attr;
+pub mod export;
 pub mod grid;
+mod kitty_graphics;
 pub mod pos;
 pub mod search;
+pub mod snapshot;
 pub mod square;
 pub mod vi_mode;
 
+use crate::ansi::control::{control_picture, C0};
+use crate::ansi::graphics::Animation;
+use crate::ansi::graphics::AnimationFrame;
 use crate::ansi::graphics::GraphicCell;
 use crate::ansi::graphics::Graphics;
 use crate::ansi::graphics::TextureRef;
 use crate::ansi::graphics::UpdateQueues;
+use crate::ansi::graphics::BELOW_TEXT_Z_INDEX;
+use crate::ansi::kitty_graphics::{KittyImage, KittyPlacementId, PLACEHOLDER};
 use crate::ansi::mode::NamedMode;
 use crate::ansi::mode::NamedPrivateMode;
 use crate::ansi::mode::PrivateMode;
 use crate::ansi::sixel;
 use crate::ansi::{
     mode::Mode as AnsiMode, ClearMode, CursorShape, KeyboardModes,
-    KeyboardModesApplyBehavior, LineClearMode, TabulationClearMode,
+    KeyboardModesApplyBehavior, LineClearMode, ModifyOtherKeys, PromptMark,
+    TabulationClearMode,
 };
 use crate::clipboard::ClipboardType;
 use crate::config::colors::{self, AnsiColor, ColorRgb};
+use crate::config::hyperlinks::HyperlinkPattern;
+use crate::config::unicode::{is_regional_indicator, CharWidth, EmojiWidth};
+use crate::config::Shell;
 use crate::crosswords::colors::term::TermColors;
 use crate::crosswords::grid::{BidirectionalIterator, Dimensions, Grid, Scroll};
 use crate::event::WindowId;
@@ -49,6 +61,7 @@ use grid::row::Row;
 use pos::{
     Boundary, CharsetIndex, Column, Cursor, CursorState, Direction, Line, Pos, Side,
 };
+use rustc_hash::FxHashMap;
 use square::{Hyperlink, LineLength, Square};
 use std::collections::HashSet;
 use std::mem;
@@ -56,9 +69,9 @@ use std::ops::{Index, IndexMut, Range};
 use std::option::Option;
 use std::ptr;
 use std::sync::Arc;
-use sugarloaf::{GraphicData, MAX_GRAPHIC_DIMENSIONS};
+use std::time::Instant;
+use sugarloaf::{GraphicData, GraphicId, MAX_GRAPHIC_DIMENSIONS};
 use tracing::{debug, info, trace, warn};
-use unicode_width::UnicodeWidthChar;
 use vi_mode::{ViModeCursor, ViMotion};
 
 pub type NamedColor = colors::NamedColor;
@@ -66,6 +79,10 @@ pub type NamedColor = colors::NamedColor;
 pub const MIN_COLUMNS: usize = 2;
 pub const MIN_LINES: usize = 1;
 
+/// Regex used for the default URL hint.
+pub const URL_REGEX: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file:|git://|ssh:|ftp://)\
+                         [^\u{0000}-\u{001F}\u{007F}-\u{009F}<>\"\\s{-}\\^⟨⟩`\\\\]+";
+
 /// Max. number of graphics stored in a single cell.
 const MAX_GRAPHICS_PER_CELL: usize = 20;
 
@@ -96,6 +113,10 @@ bitflags! {
         const REPORT_ALTERNATE_KEYS   = 1 << 20;
         const REPORT_ALL_KEYS_AS_ESC  = 1 << 21;
         const REPORT_ASSOCIATED_TEXT  = 1 << 22;
+        const CONTROL_PICTURES        = 1 << 23;
+        const MODIFY_OTHER_KEYS       = 1 << 24;
+        const MODIFY_OTHER_KEYS_ALL   = 1 << 25;
+        const WIN32_INPUT             = 1 << 26;
         const MOUSE_MODE = Self::MOUSE_REPORT_CLICK.bits() | Self::MOUSE_MOTION.bits() | Self::MOUSE_DRAG.bits();
         const KITTY_KEYBOARD_PROTOCOL = Self::DISAMBIGUATE_ESC_CODES.bits()
                                       | Self::REPORT_EVENT_TYPES.bits()
@@ -261,6 +282,10 @@ struct TermDamageState {
     /// Hint whether terminal should be damaged entirely regardless of the actual damage changes.
     full: bool,
 
+    /// Lines the viewport content moved down (up when negative) by
+    /// scrolling the display.
+    scrolled: i32,
+
     /// Information about damage on terminal lines.
     lines: Vec<LineDamageBounds>,
 
@@ -281,6 +306,7 @@ impl TermDamageState {
 
         Self {
             full: true,
+            scrolled: 0,
             lines,
             last_cursor: Default::default(),
             last_vi_cursor_point: Default::default(),
@@ -303,6 +329,16 @@ impl TermDamageState {
         }
     }
 
+    /// Record the display scrolled by `delta` lines, scrolling further
+    /// than the viewport leaves nothing to reuse.
+    #[inline]
+    fn scroll(&mut self, delta: i32) {
+        self.scrolled += delta;
+        if self.scrolled.unsigned_abs() as usize >= self.lines.len() {
+            self.full = true;
+        }
+    }
+
     /// Damage point inside of the viewport.
     #[inline]
     fn damage_point(&mut self, point: Pos<usize>) {
@@ -342,6 +378,7 @@ impl TermDamageState {
     /// Reset information about terminal damage.
     fn reset(&mut self, num_cols: usize) {
         self.full = false;
+        self.scrolled = 0;
         self.lines.iter_mut().for_each(|line| line.reset(num_cols));
     }
 }
@@ -354,6 +391,12 @@ struct TabStops {
 /// Default tab interval, corresponding to terminfo `it` value.
 const INITIAL_TABSTOPS: usize = 8;
 
+/// Scrollback history used until the configured one is applied.
+const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
+/// Upper bound for the configurable scrollback history.
+const MAX_SCROLLBACK_LINES: usize = 100_000;
+
 impl TabStops {
     #[inline]
     fn new(columns: usize) -> TabStops {
@@ -423,6 +466,49 @@ const TITLE_STACK_MAX_DEPTH: usize = 4096;
 // Max size of the keyboard modes.
 const KEYBOARD_MODE_STACK_MAX_DEPTH: usize = 16384;
 
+// Max number of region scrolls kept until the frontend reads them.
+const REGION_SCROLLS_MAX_LEN: usize = 32;
+
+// Max number of user variables set by the programs.
+const USER_VARS_MAX_LEN: usize = 128;
+
+// Max size in bytes of the name and value of a user variable.
+const USER_VAR_MAX_SIZE: usize = 4096;
+
+/// Scroll applied to a region of the screen, used by the frontend to move
+/// overlays kept outside of the grid along with the content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionScroll {
+    pub region: Range<Line>,
+    /// Number of lines the content moved up, negative when it moved down.
+    pub delta: i32,
+    /// Whether the lines leaving the top of the region went into the history.
+    pub into_history: bool,
+}
+
+impl RegionScroll {
+    #[inline]
+    fn is_scrolled(&self, row: Line) -> bool {
+        row < self.region.end
+            && (row >= self.region.start || (self.into_history && row < 0))
+    }
+
+    /// Moves the range between `start` and `end` with the scrolled content,
+    /// returns `None` when the range was split or left the region.
+    pub fn rotate(&self, start: Pos, end: Pos) -> Option<(Pos, Pos)> {
+        match (self.is_scrolled(start.row), self.is_scrolled(end.row)) {
+            (false, false) => Some((start, end)),
+            (true, true) => {
+                let start = Pos::new(start.row - self.delta, start.col);
+                let end = Pos::new(end.row - self.delta, end.col);
+                (self.is_scrolled(start.row) && self.is_scrolled(end.row))
+                    .then_some((start, end))
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Crosswords<U>
 where
@@ -445,11 +531,38 @@ where
     pub cursor_shape: CursorShape,
     pub default_cursor_shape: CursorShape,
     pub blinking_cursor: bool,
+    /// Blinking restored by DECSCUSR 0 and reset.
+    pub default_blinking_cursor: bool,
+    /// Keep the line breaks of soft-wrapped lines when copying.
+    pub copy_visual_layout: bool,
+    /// Bring the viewport back to the bottom when new output arrives.
+    pub scroll_on_output: bool,
+    /// Lines of output that arrived while the viewport was scrolled up.
+    new_lines_below: usize,
+    /// Widths of the characters.
+    pub char_width: CharWidth,
+    /// Total of bytes read from the PTY.
+    pub pty_bytes_read: u64,
+    /// Whether the bell was rung since the PTY reader last sent a
+    /// `RioEvent::Bell`, so a burst of bells is sent once.
+    pub bell_rung: bool,
     pub window_id: WindowId,
     pub route_id: usize,
     title_stack: Vec<String>,
     pub current_directory: Option<std::path::PathBuf>,
+    pub current_hostname: Option<String>,
+    pub user_vars: FxHashMap<String, String>,
     hyperlink_re: regex::Regex,
+    /// Patterns detected as hyperlinks before urls, with the program
+    /// opening them.
+    hyperlink_patterns: Vec<(regex::Regex, Option<Shell>)>,
+    /// Semantic prompt marks of the primary screen in chronological order,
+    /// moved along with the content when it scrolls.
+    prompt_marks: Vec<(PromptMark, Pos)>,
+    /// Region scrolls since the frontend last read them.
+    region_scrolls: Vec<RegionScroll>,
+    /// Whether the content moved in a way the region scrolls can't describe.
+    region_scrolls_lost: bool,
 
     // The stack for the keyboard modes.
     keyboard_mode_stack: Vec<KeyboardModes>,
@@ -468,15 +581,12 @@ impl<U: EventListener> Crosswords<U> {
     ) -> Crosswords<U> {
         let cols = dimensions.columns();
         let rows = dimensions.screen_lines();
-        let grid = Grid::new(rows, cols, 10_000);
+        let grid = Grid::new(rows, cols, DEFAULT_SCROLLBACK_LINES);
         let alt = Grid::new(rows, cols, 0);
 
         let scroll_region = Line(0)..Line(rows as i32);
         let semantic_escape_chars = String::from(",│`|:\"' ()[]{}<>\t");
         let term_colors = TermColors::default();
-        // Regex used for the default URL hint.
-        let url_regex: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file:|git://|ssh:|ftp://)\
-                         [^\u{0000}-\u{001F}\u{007F}-\u{009F}<>\"\\s{-}\\^⟨⟩`\\\\]+";
 
         Crosswords {
             vi_mode_cursor: ViModeCursor::new(grid.cursor.pos),
@@ -488,7 +598,8 @@ impl<U: EventListener> Crosswords<U> {
             scroll_region,
             event_proxy,
             colors: term_colors,
-            hyperlink_re: regex::Regex::new(url_regex).unwrap(),
+            hyperlink_re: regex::Regex::new(URL_REGEX).unwrap(),
+            hyperlink_patterns: Vec::new(),
             title: String::from(""),
             tabs: TabStops::new(cols),
             mode: Mode::SHOW_CURSOR
@@ -500,10 +611,22 @@ impl<U: EventListener> Crosswords<U> {
             default_cursor_shape: cursor_shape,
             cursor_shape,
             blinking_cursor: false,
+            default_blinking_cursor: false,
+            copy_visual_layout: false,
+            scroll_on_output: false,
+            new_lines_below: 0,
+            char_width: CharWidth::default(),
+            pty_bytes_read: 0,
+            bell_rung: false,
             window_id,
             route_id,
             title_stack: Default::default(),
             current_directory: None,
+            current_hostname: None,
+            user_vars: FxHashMap::default(),
+            prompt_marks: Vec::new(),
+            region_scrolls: Vec::new(),
+            region_scrolls_lost: false,
             keyboard_mode_stack: Default::default(),
             inactive_keyboard_mode_stack: Default::default(),
         }
@@ -554,12 +677,22 @@ impl<U: EventListener> Crosswords<U> {
         // Always damage current cursor.
         self.damage_cursor();
 
-        // NOTE: damage which changes all the content when the display offset is non-zero (e.g.
-        // scrolling) is handled via full damage.
+        // NOTE: damage which changes all the content when the display offset is non-zero is
+        // handled via full damage, scrolling the display via `scrolled_lines`.
         let display_offset = self.grid.display_offset();
         TermDamage::Partial(TermDamageIterator::new(&self.damage.lines, display_offset))
     }
 
+    /// Lines the viewport content moved down (up when negative) by
+    /// scrolling the display since the last [`reset_damage`], the lines
+    /// scrolled into view aren't part of the partial damage.
+    ///
+    /// [`reset_damage`]: Self::reset_damage
+    #[inline]
+    pub fn scrolled_lines(&self) -> i32 {
+        self.damage.scrolled
+    }
+
     #[inline]
     pub fn reset_damage(&mut self) {
         self.damage.reset(self.grid.columns());
@@ -570,6 +703,102 @@ impl<U: EventListener> Crosswords<U> {
         self.grid.display_offset()
     }
 
+    /// Lines of output below the viewport that arrived since it was
+    /// scrolled up, output written on the last line counts as one.
+    #[inline]
+    pub fn new_lines_below(&self) -> usize {
+        self.new_lines_below.min(self.grid.display_offset())
+    }
+
+    /// Called before the output changes the screen with the number of
+    /// lines it scrolls, follows it when `scroll_on_output` is set or
+    /// counts it for the indicator.
+    #[inline]
+    fn on_output(&mut self, lines: usize) {
+        if self.grid.display_offset() == 0 {
+            self.new_lines_below = 0;
+            return;
+        }
+
+        if self.scroll_on_output {
+            self.scroll_display(Scroll::Bottom);
+        } else {
+            self.new_lines_below = (self.new_lines_below + lines).max(1);
+        }
+    }
+
+    /// Lines affected by scrolling, as set by DECSTBM.
+    #[inline]
+    pub fn scroll_region(&self) -> &Range<Line> {
+        &self.scroll_region
+    }
+
+    /// Takes the region scrolls since the last call, `None` means positions
+    /// taken before can't be followed anymore and should be dropped.
+    pub fn take_region_scrolls(&mut self) -> Option<Vec<RegionScroll>> {
+        if std::mem::take(&mut self.region_scrolls_lost) {
+            self.region_scrolls.clear();
+            return None;
+        }
+
+        Some(std::mem::take(&mut self.region_scrolls))
+    }
+
+    fn record_region_scroll(&mut self, region: &Range<Line>, delta: i32) {
+        if delta == 0 || self.region_scrolls_lost {
+            return;
+        }
+
+        let into_history =
+            delta > 0 && region.start == 0 && !self.mode.contains(Mode::ALT_SCREEN);
+        if let Some(last) = self.region_scrolls.last_mut() {
+            if last.region == *region && last.into_history == into_history {
+                last.delta += delta;
+                return;
+            }
+        }
+
+        if self.region_scrolls.len() >= REGION_SCROLLS_MAX_LEN {
+            self.invalidate_region_scrolls();
+            return;
+        }
+
+        self.region_scrolls.push(RegionScroll {
+            region: region.clone(),
+            delta,
+            into_history,
+        });
+    }
+
+    #[inline]
+    fn invalidate_region_scrolls(&mut self) {
+        self.region_scrolls.clear();
+        self.region_scrolls_lost = true;
+    }
+
+    /// Updates the maximum number of lines in the scrollback history,
+    /// lines beyond the new limit are dropped.
+    pub fn update_history(&mut self, history_size: usize) {
+        let history_size = history_size.min(MAX_SCROLLBACK_LINES);
+        let old_display_offset = self.grid.display_offset();
+
+        // A selection could point to the lines being dropped
+        if history_size < self.grid.history_size() {
+            self.selection = None;
+        }
+        self.grid.update_history(history_size);
+        self.drop_prompt_marks_outside_grid();
+
+        if old_display_offset != self.grid.display_offset() {
+            self.vi_mode_cursor.pos.row = self
+                .vi_mode_cursor
+                .pos
+                .row
+                .grid_clamp(&self.grid, Boundary::Grid);
+            self.mark_fully_damaged();
+        }
+    }
+
     #[inline]
     pub fn clear_saved_history(&mut self) {
         self.clear_screen(ClearMode::Saved);
@@ -581,6 +810,9 @@ impl<U: EventListener> Crosswords<U> {
         self.event_proxy
             .send_event(RioEvent::MouseCursorDirty, self.window_id);
         self.grid.scroll_display(scroll);
+        if self.grid.display_offset() == 0 {
+            self.new_lines_below = 0;
+        }
 
         // Clamp vi mode cursor to the viewport.
         let viewport_start = -(self.grid.display_offset() as i32);
@@ -590,9 +822,17 @@ impl<U: EventListener> Crosswords<U> {
             std::cmp::min(viewport_end, std::cmp::max(viewport_start, *vi_cursor_line));
         self.vi_mode_recompute_selection();
 
-        // Damage everything if display offset changed.
+        // The content moved along with the display offset, so only the
+        // lines scrolled into view are new. Vi mode cursor and selection
+        // move independently, damage everything for those.
         if old_display_offset != self.grid.display_offset() {
-            self.mark_fully_damaged();
+            if self.mode.contains(Mode::VI) {
+                self.mark_fully_damaged();
+            } else {
+                self.damage.scroll(
+                    self.grid.display_offset() as i32 - old_display_offset as i32,
+                );
+            }
 
             // TODO: This should leave here
             self.event_proxy
@@ -614,6 +854,7 @@ impl<U: EventListener> Crosswords<U> {
     /// `None`.
     #[inline]
     pub fn graphics_take_queues(&mut self) -> Option<UpdateQueues> {
+        self.graphics.advance_animations(Instant::now());
         self.graphics.take_queues()
     }
 
@@ -649,6 +890,16 @@ impl<U: EventListener> Crosswords<U> {
         self.grid.resize(!is_alt, num_lines, num_cols);
         self.inactive_grid.resize(is_alt, num_lines, num_cols);
 
+        // Prompt marks can't follow reflowed lines.
+        if old_cols != num_cols || is_alt {
+            self.prompt_marks.clear();
+        } else {
+            for (_, pos) in &mut self.prompt_marks {
+                pos.row += delta;
+            }
+            self.drop_prompt_marks_outside_grid();
+        }
+
         // Invalidate selection and tabs only when necessary.
         if old_cols != num_cols {
             self.selection = None;
@@ -672,6 +923,7 @@ impl<U: EventListener> Crosswords<U> {
 
         // Reset scrolling region.
         self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.invalidate_region_scrolls();
 
         // Resize damage information.
         self.damage.resize(num_cols, num_lines);
@@ -705,6 +957,13 @@ impl<U: EventListener> Crosswords<U> {
             .send_event(RioEvent::CursorBlinkingChange, self.window_id);
     }
 
+    /// Show C0 control characters as their control picture glyphs, for
+    /// inspecting the output of programs.
+    #[inline]
+    pub fn toggle_control_pictures(&mut self) {
+        self.mode ^= Mode::CONTROL_PICTURES;
+    }
+
     /// Update the active selection to match the vi mode cursor position.
     #[inline]
     fn vi_mode_recompute_selection(&mut self) {
@@ -750,6 +1009,55 @@ impl<U: EventListener> Crosswords<U> {
         self.vi_mode_recompute_selection();
     }
 
+    /// Move the vi cursor to the next `needle` of the line in `direction`,
+    /// or to the cell before it with `stop_short`. When `repeat` is set a
+    /// needle right next to the cursor is skipped, so repeating a search
+    /// stopping short doesn't get stuck. Returns whether the cursor moved.
+    pub fn vi_inline_search(
+        &mut self,
+        needle: char,
+        direction: Direction,
+        stop_short: bool,
+        repeat: bool,
+    ) -> bool
+    where
+        U: EventListener,
+    {
+        let mut buf = [0; 4];
+        let needle = needle.encode_utf8(&mut buf);
+        let mut start = self.vi_mode_cursor.pos;
+        if stop_short && repeat {
+            let mut iter = self.grid.iter_from(start);
+            let next = match direction {
+                Direction::Right => iter.next(),
+                Direction::Left => iter.prev(),
+            };
+            if let Some(cell) = next.filter(|cell| cell.pos.row == start.row) {
+                start = cell.pos;
+            }
+        }
+
+        let found = match direction {
+            Direction::Right => self.inline_search_right(start, needle),
+            Direction::Left => self.inline_search_left(start, needle),
+        };
+        let Ok(mut pos) = found else {
+            return false;
+        };
+
+        if stop_short {
+            let mut iter = self.grid.iter_from(pos);
+            let before = match direction {
+                Direction::Right => iter.prev(),
+                Direction::Left => iter.next(),
+            };
+            pos = before.map_or(pos, |cell| cell.pos);
+        }
+
+        self.vi_goto_pos(pos);
+        true
+    }
+
     /// Scroll display to point if it is outside of viewport.
     #[inline]
     pub fn scroll_to_pos(&mut self, pos: Pos)
@@ -878,6 +1186,8 @@ impl<U: EventListener> Crosswords<U> {
 
         // Scroll between origin and bottom
         self.grid.scroll_down(&region, lines);
+        self.rotate_prompt_marks(&region, -(lines as i32));
+        self.record_region_scroll(&region, -(lines as i32));
         self.mark_fully_damaged();
     }
 
@@ -891,6 +1201,8 @@ impl<U: EventListener> Crosswords<U> {
         );
 
         let region = origin..self.scroll_region.end;
+        // Only the lines moved into the history are new below the viewport
+        self.on_output(if region.start == 0 { lines } else { 0 });
 
         // Scroll selection.
         self.selection = self
@@ -899,6 +1211,8 @@ impl<U: EventListener> Crosswords<U> {
             .and_then(|s| s.rotate(&self.grid, &region, lines as i32));
 
         self.grid.scroll_up(&region, lines);
+        self.rotate_prompt_marks(&region, lines as i32);
+        self.record_region_scroll(&region, lines as i32);
 
         // Scroll vi mode cursor.
         let viewport_top = Line(-(self.grid.display_offset() as i32));
@@ -914,6 +1228,41 @@ impl<U: EventListener> Crosswords<U> {
         self.mark_fully_damaged();
     }
 
+    /// Moves the prompt marks with the scrolled region content, marks
+    /// leaving the region or the history are dropped.
+    fn rotate_prompt_marks(&mut self, region: &Range<Line>, delta: i32) {
+        if self.prompt_marks.is_empty() || self.mode.contains(Mode::ALT_SCREEN) {
+            return;
+        }
+
+        // Lines are only moved into the history if the region starts at the top
+        let into_history = delta > 0 && region.start == 0;
+        let lower = if into_history {
+            self.grid.topmost_line()
+        } else {
+            region.start
+        };
+
+        self.prompt_marks.retain_mut(|(_, pos)| {
+            let is_scrolled = pos.row < region.end
+                && (pos.row >= region.start || (into_history && pos.row < 0));
+            if !is_scrolled {
+                return true;
+            }
+
+            pos.row = Line(pos.row.0 - delta);
+            pos.row >= lower && pos.row < region.end
+        });
+    }
+
+    #[inline]
+    fn drop_prompt_marks_outside_grid(&mut self) {
+        let topmost = self.grid.topmost_line();
+        let bottommost = self.grid.bottommost_line();
+        self.prompt_marks
+            .retain(|(_, pos)| pos.row >= topmost && pos.row <= bottommost);
+    }
+
     #[inline]
     pub fn search_nearest_hyperlink_from_pos(
         &mut self,
@@ -1000,15 +1349,32 @@ impl<U: EventListener> Crosswords<U> {
             return Some(range);
         }
 
-        if content.len() <= 4 {
-            return None;
-        }
-
         let value = content.iter().collect::<String>();
-        if let Some(uri) = self.hyperlink_re.find(&value) {
-            let uri = uri.as_str().to_string();
-            let hyperlink = Some(Hyperlink::new(None, uri));
+        let pattern_link = self.hyperlink_patterns.iter().find_map(|(regex, command)| {
+            let captures = regex.captures(&value)?;
+            let uri = captures[0].to_string();
+            Some(match command {
+                Some(command) => {
+                    let mut args = vec![command.program.clone()];
+                    for arg in &command.args {
+                        let mut expanded = String::new();
+                        captures.expand(arg, &mut expanded);
+                        args.push(expanded);
+                    }
+                    Hyperlink::with_command(uri, args)
+                }
+                None => Hyperlink::new(None, uri),
+            })
+        });
+        let hyperlink = pattern_link.or_else(|| {
+            if content.len() <= 4 {
+                return None;
+            }
+            let uri = self.hyperlink_re.find(&value)?.as_str().to_string();
+            Some(Hyperlink::new(None, uri))
+        });
 
+        if hyperlink.is_some() {
             for link_pos in positions_to_update.iter() {
                 self.grid[link_pos.row][link_pos.col].set_hyperlink(hyperlink.to_owned());
             }
@@ -1024,6 +1390,69 @@ impl<U: EventListener> Crosswords<U> {
         None
     }
 
+    /// Join the regional indicator with a lone one right before the cursor,
+    /// the pair is a flag taking the cells of an emoji. Returns false if
+    /// there is nothing to join with or a wide flag would not fit.
+    fn join_regional_indicator(&mut self, c: char) -> bool {
+        let wide = self.char_width.emoji_width() == EmojiWidth::Wide;
+        let pos = self.grid.cursor.pos;
+        let column = if self.grid.cursor.should_wrap {
+            if wide {
+                return false;
+            }
+            pos.col
+        } else if pos.col > 0 {
+            pos.col - 1
+        } else {
+            return false;
+        };
+
+        let square = &mut self.grid[pos.row][column];
+        if !is_regional_indicator(square.c)
+            || square.zerowidth().is_some_and(|chars| !chars.is_empty())
+            || square.flags.intersects(
+                square::Flags::WIDE_CHAR | square::Flags::LEADING_WIDE_CHAR_SPACER,
+            )
+        {
+            return false;
+        }
+
+        square.push_zerowidth(c);
+        if !wide {
+            return true;
+        }
+        square.flags.insert(square::Flags::WIDE_CHAR);
+
+        let columns = self.grid.columns();
+        if self.mode.contains(Mode::INSERT) && pos.col + 1 < columns {
+            let row = &mut self.grid[pos.row][..];
+            for col in (pos.col.0..(columns - 1)).rev() {
+                row.swap(col + 1, col);
+            }
+        }
+
+        // The cell under the cursor becomes the spacer of the flag
+        self.grid
+            .cursor
+            .template
+            .flags
+            .insert(square::Flags::WIDE_CHAR_SPACER);
+        self.write_at_cursor(' ');
+        self.grid
+            .cursor
+            .template
+            .flags
+            .remove(square::Flags::WIDE_CHAR_SPACER);
+
+        if pos.col + 1 < columns {
+            self.grid.cursor.pos.col += 1;
+        } else {
+            self.grid.cursor.should_wrap = true;
+        }
+
+        true
+    }
+
     #[inline(always)]
     pub fn write_at_cursor(&mut self, c: char) {
         let c = self.grid.cursor.charsets[self.active_charset].map(c);
@@ -1131,11 +1560,6 @@ impl<U: EventListener> Crosswords<U> {
             content = CursorShape::Hidden;
         }
 
-        // If is not using app cursor then use default
-        if content != CursorShape::Hidden && !self.mode.contains(Mode::ALT_SCREEN) {
-            content = self.default_cursor_shape;
-        }
-
         CursorState { pos, content }
     }
 
@@ -1151,6 +1575,7 @@ impl<U: EventListener> Crosswords<U> {
             self.inactive_grid.reset_region(..);
         }
 
+        self.invalidate_region_scrolls();
         mem::swap(
             &mut self.keyboard_mode_stack,
             &mut self.inactive_keyboard_mode_stack,
@@ -1176,6 +1601,96 @@ impl<U: EventListener> Crosswords<U> {
             .damage_line(line.0 as usize, 0, self.columns() - 1);
     }
 
+    /// Selects the whole scrollback history and screen.
+    pub fn select_all(&mut self) {
+        let start = Pos::new(self.grid.topmost_line(), Column(0));
+        let end = Pos::new(self.grid.bottommost_line(), self.grid.last_column());
+
+        let mut selection = Selection::new(SelectionType::Simple, start, Side::Left);
+        selection.update(end, Side::Right);
+        self.selection = Some(selection);
+        self.mark_fully_damaged();
+    }
+
+    /// Selects the output of the last command, delimited by the semantic
+    /// prompt marks. Returns false if there is no output to select.
+    pub fn select_last_command_output(&mut self) -> bool {
+        let Some(index) = self
+            .prompt_marks
+            .iter()
+            .rposition(|(mark, _)| *mark == PromptMark::OutputStart)
+        else {
+            return false;
+        };
+
+        let start = self.prompt_marks[index].1;
+        // The output of a running command ends at the cursor
+        let end = self.prompt_marks[index + 1..]
+            .iter()
+            .find(|(mark, _)| *mark != PromptMark::OutputStart)
+            .map_or(self.grid.cursor.pos, |(_, pos)| *pos);
+
+        // The end mark is exclusive, select up to the previous cell
+        let end = if end.col > 0 {
+            Pos::new(end.row, end.col - 1)
+        } else {
+            Pos::new(end.row - 1, self.grid.last_column())
+        };
+
+        if end < start {
+            return false;
+        }
+
+        let mut selection = Selection::new(SelectionType::Simple, start, Side::Left);
+        selection.update(end, Side::Right);
+        self.selection = Some(selection);
+        self.mark_fully_damaged();
+        true
+    }
+
+    /// Text of the last `lines` lines with content up to the cursor, the
+    /// prompt being edited is left out when the shell emits prompt marks.
+    pub fn recent_output(&self, lines: usize) -> String {
+        let topmost_line = self.grid.topmost_line();
+        let mut end = self.grid.cursor.pos.row;
+        if let Some((PromptMark::PromptStart | PromptMark::CommandStart, _)) =
+            self.prompt_marks.last()
+        {
+            if let Some((_, pos)) = self
+                .prompt_marks
+                .iter()
+                .rfind(|(mark, _)| *mark == PromptMark::PromptStart)
+            {
+                end = pos.row - 1;
+            }
+        }
+
+        let full_line = Column(0)..self.grid.last_column();
+        while end >= topmost_line
+            && self
+                .line_to_string(end, full_line.clone(), true)
+                .trim()
+                .is_empty()
+        {
+            end -= 1;
+        }
+
+        if lines == 0 || end < topmost_line {
+            return String::new();
+        }
+
+        let lines = lines.min(self.grid.total_lines());
+        let start = std::cmp::max(end - (lines - 1), topmost_line);
+        self.bounds_to_string(
+            Pos::new(start, Column(0)),
+            Pos::new(end, self.grid.last_column()),
+        )
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
     pub fn selection_to_string(&self) -> Option<String> {
         let selection_range = self.selection.as_ref().and_then(|s| s.to_range(self))?;
         let SelectionRange { start, end, .. } = selection_range;
@@ -1212,6 +1727,33 @@ impl<U: EventListener> Crosswords<U> {
         Some(res)
     }
 
+    /// Targets of the OSC 8 hyperlinks covered by the selection, once each
+    /// in the order they appear.
+    pub fn selection_hyperlinks(&self) -> Vec<String> {
+        let Some(range) = self.selection.as_ref().and_then(|s| s.to_range(self)) else {
+            return Vec::new();
+        };
+
+        let mut uris: Vec<String> = Vec::new();
+        for line in (range.start.row.0..=range.end.row.0).map(Line::from) {
+            let row = &self.grid[line];
+            for column in 0..row.len() {
+                let pos = Pos::new(line, Column(column));
+                if !range.contains(pos) {
+                    continue;
+                }
+
+                if let Some(hyperlink) = row[pos.col].hyperlink() {
+                    if !uris.iter().any(|uri| uri == hyperlink.uri()) {
+                        uris.push(hyperlink.uri().to_owned());
+                    }
+                }
+            }
+        }
+
+        uris
+    }
+
     pub fn bounds_to_string(&self, start: Pos, end: Pos) -> String {
         let mut res = String::new();
 
@@ -1284,7 +1826,8 @@ impl<U: EventListener> Crosswords<U> {
         }
 
         if cols.end >= self.grid.columns() - 1
-            && (line_length.0 == 0
+            && (self.copy_visual_layout
+                || line_length.0 == 0
                 || !self.grid[line][line_length - 1]
                     .flags
                     .contains(square::Flags::WRAPLINE))
@@ -1349,61 +1892,439 @@ impl<U: EventListener> Crosswords<U> {
 
         point
     }
-}
 
-impl<U: EventListener> Handler for Crosswords<U> {
-    #[inline]
-    fn set_mode(&mut self, mode: AnsiMode) {
-        let mode = match mode {
-            AnsiMode::Named(mode) => mode,
-            AnsiMode::Unknown(mode) => {
-                debug!("Ignoring unknown mode {} in set_mode", mode);
-                return;
-            }
-        };
+    /// Resize the graphic as requested, relative to the cells and the
+    /// screen. `None` if the cell size is still unknown.
+    fn resize_graphic(&self, graphic: GraphicData) -> Option<GraphicData> {
+        let cell_width = self.graphics.cell_width as usize;
+        let cell_height = self.graphics.cell_height as usize;
 
-        trace!("Setting public mode: {:?}", mode);
-        match mode {
-            NamedMode::Insert => self.mode.insert(Mode::INSERT),
-            NamedMode::LineFeedNewLine => self.mode.insert(Mode::LINE_FEED_NEW_LINE),
+        // The cell size is unknown until the first resize
+        if cell_width == 0 || cell_height == 0 {
+            return None;
         }
+
+        graphic.resized(
+            cell_width,
+            cell_height,
+            cell_width * self.grid.columns(),
+            cell_height * self.grid.screen_lines(),
+        )
     }
 
-    #[inline]
-    fn unset_mode(&mut self, mode: AnsiMode) {
-        let mode = match mode {
-            AnsiMode::Named(mode) => mode,
-            AnsiMode::Unknown(mode) => {
-                debug!("Ignoring unknown mode {} in unset_mode", mode);
-                return;
-            }
+    /// Place a Sixel or iTerm2 graphic, returning its id.
+    fn display_graphic(&mut self, graphic: GraphicData) -> Option<GraphicId> {
+        // If SIXEL_DISPLAY is disabled, the start of the graphic is the
+        // cursor position, and the grid can be scrolled if the graphic is
+        // larger than the screen. The cursor is moved to the next line
+        // after the graphic.
+        //
+        // If it is disabled, the graphic starts at (0, 0), the grid is never
+        // scrolled, and the cursor position is unmodified.
+
+        let scrolling = !self.mode.contains(Mode::SIXEL_DISPLAY);
+        let origin = if scrolling {
+            self.grid.cursor.pos
+        } else {
+            Pos::new(Line(0), Column(0))
         };
 
-        trace!("Setting public mode: {:?}", mode);
-        match mode {
-            NamedMode::Insert => {
-                self.mode.remove(Mode::INSERT);
-                self.mark_fully_damaged();
-            }
-            NamedMode::LineFeedNewLine => self.mode.remove(Mode::LINE_FEED_NEW_LINE),
+        let (id, graphic_columns) =
+            self.place_graphic(graphic, origin, scrolling, BELOW_TEXT_Z_INDEX, None)?;
+
+        if self.mode.contains(Mode::SIXEL_CURSOR_TO_THE_RIGHT) {
+            self.move_forward(Column(graphic_columns));
+        } else if scrolling {
+            self.linefeed();
+            self.carriage_return();
         }
+
+        Some(id)
     }
 
-    #[inline]
-    fn report_mode(&mut self, mode: AnsiMode) {
-        trace!("Reporting mode {mode:?}");
-        let state = match mode {
-            AnsiMode::Named(mode) => match mode {
-                NamedMode::Insert => self.mode.contains(Mode::INSERT).into(),
-                NamedMode::LineFeedNewLine => {
-                    self.mode.contains(Mode::LINE_FEED_NEW_LINE).into()
-                }
-            },
+    /// Attach the graphic to the cells starting at `origin`, returning its
+    /// id and the number of columns it covers.
+    ///
+    /// When `scrolling`, the rows follow the cursor, which must be at
+    /// `origin`, and the grid is scrolled if the graphic does not fit. The
+    /// cursor is left in the last row of the graphic. Otherwise the graphic
+    /// is clipped to the screen.
+    fn place_graphic(
+        &mut self,
+        graphic: GraphicData,
+        origin: Pos,
+        scrolling: bool,
+        z_index: i32,
+        kitty: Option<KittyPlacementId>,
+    ) -> Option<(GraphicId, usize)> {
+        let cell_width = self.graphics.cell_width as usize;
+        let cell_height = self.graphics.cell_height as usize;
+        let graphic = self.resize_graphic(graphic)?;
+
+        if graphic.width > MAX_GRAPHIC_DIMENSIONS[0]
+            || graphic.height > MAX_GRAPHIC_DIMENSIONS[1]
+        {
+            return None;
+        }
+
+        let width = graphic.width as u16;
+        let height = graphic.height as u16;
+
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let graphic_id = self.graphics.next_id();
+        let leftmost = origin.col.0;
+
+        // A very simple optimization is to detect is a new graphic is replacing
+        // completely a previous one. This happens if the following conditions
+        // are met:
+        //
+        // - Both graphics are attached to the same top-left cell.
+        // - Both graphics have the same size.
+        // - The new graphic does not contain transparent pixels.
+        //
+        // In this case, we will ignore cells with a reference to the replaced
+        // graphic.
+
+        let skip_textures = {
+            if graphic.maybe_transparent() {
+                HashSet::new()
+            } else {
+                let mut set = HashSet::new();
+
+                if let Some(old_graphics) =
+                    self.grid[origin.row][Column(leftmost)].graphics()
+                {
+                    for graphic in old_graphics {
+                        let tex = &*graphic.texture;
+                        if tex.width == width
+                            && tex.height == height
+                            && tex.cell_height == cell_height
+                        {
+                            set.insert(tex.id);
+                        }
+                    }
+                }
+
+                set
+            }
+        };
+
+        // Fill the cells under the graphic.
+        //
+        // The cell in the first column contains a reference to the
+        // graphic, with the offset from the start. The rest of the
+        // cells are not overwritten, allowing any text behind
+        // transparent portions of the image to be visible.
+
+        let texture = Arc::new(TextureRef {
+            id: graphic_id,
+            width,
+            height,
+            cell_height,
+            z_index,
+            kitty,
+            texture_operations: Arc::downgrade(&self.graphics.texture_operations),
+        });
+
+        for (top, offset_y) in (0..).zip((0..height).step_by(cell_height)) {
+            let line = if scrolling {
+                self.grid.cursor.pos.row
+            } else {
+                let line = origin.row + top;
+
+                // Check if the image is beyond the screen limit.
+                if line >= self.grid.screen_lines() as i32 {
+                    break;
+                }
+
+                line
+            };
+
+            // Store a reference to the graphic in the first column.
+            let row_len = self.grid[line].len();
+            for (left, offset_x) in (leftmost..).zip((0..width).step_by(cell_width)) {
+                if left >= row_len {
+                    break;
+                }
+
+                let texture_operations =
+                    Arc::downgrade(&self.graphics.texture_operations);
+                let graphic_cell = GraphicCell {
+                    texture: texture.clone(),
+                    offset_x,
+                    offset_y,
+                    texture_operations,
+                };
+
+                let mut cell = self.grid.cursor.template.clone();
+                let cell_ref = &mut self.grid[line][Column(left)];
+
+                // If the cell contains any graphics, and the region of the cell
+                // is not fully filled by the new graphic, the old graphics are
+                // kept in the cell.
+                let graphics = match cell_ref.take_graphics() {
+                    Some(mut old_graphics)
+                        if old_graphics.iter().any(|graphic| {
+                            !skip_textures.contains(&graphic.texture.id)
+                        }) && !graphic.is_filled(
+                            offset_x as usize,
+                            offset_y as usize,
+                            cell_width,
+                            cell_height,
+                        ) =>
+                    {
+                        // Ensure that we don't exceed the graphics limit per cell.
+                        while old_graphics.len() >= MAX_GRAPHICS_PER_CELL {
+                            drop(old_graphics.remove(0));
+                        }
+
+                        old_graphics.push(graphic_cell);
+                        old_graphics
+                    }
+
+                    _ => smallvec::smallvec![graphic_cell],
+                };
+
+                cell.set_graphics(graphics);
+                *cell_ref = cell;
+            }
+
+            self.mark_line_damaged(line);
+
+            if scrolling && offset_y < height.saturating_sub(cell_height as u16) {
+                self.linefeed();
+            }
+        }
+
+        let graphic_columns = graphic.width.div_ceil(cell_width);
+
+        // Add the graphic data to the pending queue.
+        self.graphics
+            .track_texture(graphic_id, graphic.pixels.len());
+        self.graphics.pending.push(GraphicData {
+            id: graphic_id,
+            ..graphic
+        });
+        self.reclaim_graphics_memory();
+
+        Some((graphic_id, graphic_columns))
+    }
+
+    /// Sets the patterns detected as hyperlinks, invalid ones are skipped.
+    pub fn set_hyperlink_patterns(&mut self, patterns: &[HyperlinkPattern]) {
+        self.hyperlink_patterns = patterns
+            .iter()
+            .filter_map(|pattern| match regex::Regex::new(&pattern.regex) {
+                Ok(regex) => Some((regex, pattern.command.clone())),
+                Err(err) => {
+                    warn!("invalid hyperlink pattern {:?}: {err}", pattern.regex);
+                    None
+                }
+            })
+            .collect();
+    }
+
+    /// Sets the memory limit of the graphics, in bytes, evicting them if
+    /// they take more.
+    pub fn set_graphics_max_memory(&mut self, max_memory: usize) {
+        self.graphics.max_memory = max_memory;
+        self.reclaim_graphics_memory();
+    }
+
+    /// Evicts graphics until they take at most the configured memory.
+    /// Graphics scrolled off the screen go first, then the images stored
+    /// by the kitty protocol and at last the oldest graphics on the screen.
+    fn reclaim_graphics_memory(&mut self) {
+        let limit = self.graphics.max_memory;
+        let mut used = self.graphics.used_memory();
+        if used <= limit {
+            return;
+        }
+
+        let mut on_screen = HashSet::new();
+        for row in 0..self.grid.screen_lines() as i32 {
+            for square in self.grid[Line(row)].inner.iter() {
+                if let Some(graphics) = square.graphics() {
+                    on_screen.extend(graphics.iter().map(|graphic| graphic.texture.id));
+                }
+            }
+        }
+
+        let textures: Vec<(GraphicId, usize)> = self
+            .graphics
+            .textures
+            .iter()
+            .map(|(id, memory)| (*id, *memory))
+            .collect();
+        let mut evict = HashSet::new();
+        for (id, memory) in textures.iter().filter(|(id, _)| !on_screen.contains(id)) {
+            if used <= limit {
+                break;
+            }
+            evict.insert(*id);
+            used -= memory;
+        }
+
+        if used > limit {
+            let image_memory = self.graphics.kitty.used_memory();
+            let image_limit = limit.saturating_sub(used - image_memory);
+            let evicted = self.graphics.kitty.evict(image_limit);
+            self.graphics.evicted += evicted as u64;
+            used = used - image_memory + self.graphics.kitty.used_memory();
+        }
+
+        // The graphic just placed is kept, even if larger than the limit
+        let newest = GraphicId(self.graphics.last_id);
+        for (id, memory) in textures
+            .iter()
+            .filter(|(id, _)| on_screen.contains(id) && *id != newest)
+        {
+            if used <= limit {
+                break;
+            }
+            evict.insert(*id);
+            used -= memory;
+        }
+
+        if evict.is_empty() {
+            return;
+        }
+
+        debug!("evicting {} graphics over the memory limit", evict.len());
+        self.graphics
+            .pending
+            .retain(|graphic| !evict.contains(&graphic.id));
+        self.graphics
+            .frames
+            .retain(|graphic| !evict.contains(&graphic.id));
+        self.graphics
+            .kitty
+            .remove_virtual_placements(|texture| evict.contains(&texture.id));
+        self.remove_graphics(self.all_lines(), |texture| evict.contains(&texture.id));
+
+        // Graphics also referenced by the inactive grid are still alive
+        self.graphics.used_memory();
+        let evicted = evict
+            .iter()
+            .filter(|id| !self.graphics.textures.contains_key(id))
+            .count();
+        self.graphics.evicted += evicted as u64;
+    }
+
+    /// Remove the graphics matching the predicate from the lines, returning
+    /// the ids of the kitty images they were placed for.
+    fn remove_graphics(
+        &mut self,
+        lines: Range<Line>,
+        predicate: impl Fn(&TextureRef) -> bool,
+    ) -> HashSet<u32> {
+        let mut image_ids = HashSet::new();
+
+        for row in lines.start.0..lines.end.0 {
+            let line = Line(row);
+            let mut damaged = false;
+
+            for column in 0..self.grid.columns() {
+                let cell = &mut self.grid[line][Column(column)];
+                let has_matches = cell.graphics().is_some_and(|graphics| {
+                    graphics.iter().any(|graphic| predicate(&graphic.texture))
+                });
+                if !has_matches {
+                    continue;
+                }
+
+                let Some(mut graphics) = cell.take_graphics() else {
+                    continue;
+                };
+                graphics.retain(|graphic| {
+                    let remove = predicate(&graphic.texture);
+                    if let (true, Some(kitty)) = (remove, graphic.texture.kitty) {
+                        image_ids.insert(kitty.image_id);
+                    }
+                    !remove
+                });
+
+                if graphics.is_empty() {
+                    cell.flags.remove(square::Flags::GRAPHICS);
+                } else {
+                    cell.set_graphics(graphics);
+                }
+                damaged = true;
+            }
+
+            // Lines in the scrollback are not tracked for damage
+            if damaged && row >= 0 {
+                self.mark_line_damaged(line);
+            }
+        }
+
+        image_ids
+    }
+
+    fn all_lines(&self) -> Range<Line> {
+        self.grid.topmost_line()..Line(self.grid.screen_lines() as i32)
+    }
+}
+
+impl<U: EventListener> Handler for Crosswords<U> {
+    #[inline]
+    fn set_mode(&mut self, mode: AnsiMode) {
+        let mode = match mode {
+            AnsiMode::Named(mode) => mode,
+            AnsiMode::Unknown(mode) => {
+                debug!("Ignoring unknown mode {} in set_mode", mode);
+                return;
+            }
+        };
+
+        trace!("Setting public mode: {:?}", mode);
+        match mode {
+            NamedMode::Insert => self.mode.insert(Mode::INSERT),
+            NamedMode::LineFeedNewLine => self.mode.insert(Mode::LINE_FEED_NEW_LINE),
+        }
+    }
+
+    #[inline]
+    fn unset_mode(&mut self, mode: AnsiMode) {
+        let mode = match mode {
+            AnsiMode::Named(mode) => mode,
+            AnsiMode::Unknown(mode) => {
+                debug!("Ignoring unknown mode {} in unset_mode", mode);
+                return;
+            }
+        };
+
+        trace!("Setting public mode: {:?}", mode);
+        match mode {
+            NamedMode::Insert => {
+                self.mode.remove(Mode::INSERT);
+                self.mark_fully_damaged();
+            }
+            NamedMode::LineFeedNewLine => self.mode.remove(Mode::LINE_FEED_NEW_LINE),
+        }
+    }
+
+    #[inline]
+    fn report_mode(&mut self, mode: AnsiMode) {
+        trace!("Reporting mode {mode:?}");
+        let state = match mode {
+            AnsiMode::Named(mode) => match mode {
+                NamedMode::Insert => self.mode.contains(Mode::INSERT).into(),
+                NamedMode::LineFeedNewLine => {
+                    self.mode.contains(Mode::LINE_FEED_NEW_LINE).into()
+                }
+            },
             AnsiMode::Unknown(_) => ModeState::NotSupported,
         };
 
         self.event_proxy.send_event(
-            RioEvent::PtyWrite(format!("\x1b[{};{}$y", mode.raw(), state as u8,)),
+            RioEvent::PtyWrite(
+                self.route_id,
+                format!("\x1b[{};{}$y", mode.raw(), state as u8,),
+            ),
             self.window_id,
         );
     }
@@ -1487,6 +2408,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
                     .send_event(RioEvent::CursorBlinkingChange, self.window_id);
             }
             NamedPrivateMode::SyncUpdate => (),
+            NamedPrivateMode::Win32Input => self.mode.insert(Mode::WIN32_INPUT),
         }
     }
 
@@ -1559,6 +2481,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
                     .send_event(RioEvent::CursorBlinkingChange, self.window_id);
             }
             NamedPrivateMode::SyncUpdate => (),
+            NamedPrivateMode::Win32Input => self.mode.remove(Mode::WIN32_INPUT),
         }
     }
 
@@ -1605,13 +2528,19 @@ impl<U: EventListener> Handler for Crosswords<U> {
                     self.mode.contains(Mode::BRACKETED_PASTE).into()
                 }
                 NamedPrivateMode::SyncUpdate => ModeState::Reset,
+                NamedPrivateMode::Win32Input => {
+                    self.mode.contains(Mode::WIN32_INPUT).into()
+                }
                 NamedPrivateMode::ColumnMode => ModeState::NotSupported,
             },
             PrivateMode::Unknown(_) => ModeState::NotSupported,
         };
 
         self.event_proxy.send_event(
-            RioEvent::PtyWrite(format!("\x1b[?{};{}$y", mode.raw(), state as u8,)),
+            RioEvent::PtyWrite(
+                self.route_id,
+                format!("\x1b[?{};{}$y", mode.raw(), state as u8,),
+            ),
             self.window_id,
         );
     }
@@ -1626,6 +2555,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
         let terminator = terminator.to_owned();
         self.event_proxy.send_event(
             RioEvent::ColorRequest(
+                self.route_id,
                 index,
                 Arc::new(move |color| {
                     format!(
@@ -1905,20 +2835,24 @@ impl<U: EventListener> Handler for Crosswords<U> {
         }
         self.active_charset = Default::default();
         self.cursor_shape = self.default_cursor_shape;
+        self.blinking_cursor = self.default_blinking_cursor;
         self.grid.reset();
         self.inactive_grid.reset();
         self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.invalidate_region_scrolls();
         self.tabs = TabStops::new(self.grid.columns());
         self.title_stack = Vec::new();
         self.keyboard_mode_stack = Vec::new();
         self.title = String::from("");
         self.selection = None;
+        self.prompt_marks.clear();
+        self.graphics.kitty = Default::default();
         self.vi_mode_cursor = Default::default();
         self.keyboard_mode_stack = Default::default();
         self.inactive_keyboard_mode_stack = Default::default();
 
-        // Preserve vi mode across resets.
-        self.mode &= Mode::VI;
+        // Preserve vi mode and control pictures across resets.
+        self.mode &= Mode::VI | Mode::CONTROL_PICTURES;
         self.mode.insert(Mode::default());
 
         self.event_proxy
@@ -2005,15 +2939,60 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.current_directory = Some(path);
     }
 
-    #[inline]
-    fn set_cursor_style(&mut self, style: Option<CursorShape>, blinking: bool) {
-        if let Some(cursor_shape) = style {
-            self.cursor_shape = cursor_shape;
-        } else {
-            self.cursor_shape = self.default_cursor_shape;
-        }
+    fn set_current_hostname(&mut self, hostname: Option<String>) {
+        trace!("Setting hostname {:?}", hostname);
+        self.current_hostname = hostname;
+    }
+
+    #[inline]
+    fn set_prompt_mark(&mut self, mark: PromptMark) {
+        trace!("Setting prompt mark {:?}", mark);
+        if self.mode.contains(Mode::ALT_SCREEN) {
+            return;
+        }
+
+        self.prompt_marks.push((mark, self.grid.cursor.pos));
+    }
+
+    fn set_user_var(&mut self, name: String, value: String) {
+        trace!("Setting user var {:?} to {:?}", name, value);
+        if name.len() > USER_VAR_MAX_SIZE || value.len() > USER_VAR_MAX_SIZE {
+            debug!("Ignoring user var larger than {USER_VAR_MAX_SIZE} bytes");
+            return;
+        }
+
+        // An empty value unsets the variable
+        if value.is_empty() {
+            if self.user_vars.remove(&name).is_none() {
+                return;
+            }
+        } else if self.user_vars.get(&name) == Some(&value) {
+            return;
+        } else if self.user_vars.len() >= USER_VARS_MAX_LEN
+            && !self.user_vars.contains_key(&name)
+        {
+            debug!("Ignoring user var {name:?}, {USER_VARS_MAX_LEN} are already set");
+            return;
+        } else {
+            self.user_vars.insert(name.clone(), value.clone());
+        }
+
+        self.event_proxy.send_event(
+            RioEvent::UserVarChanged(self.route_id, name, value),
+            self.window_id,
+        );
+    }
+
+    #[inline]
+    fn set_cursor_style(&mut self, style: Option<CursorShape>, blinking: bool) {
+        if let Some(cursor_shape) = style {
+            self.cursor_shape = cursor_shape;
+            self.blinking_cursor = blinking;
+        } else {
+            self.cursor_shape = self.default_cursor_shape;
+            self.blinking_cursor = self.default_blinking_cursor;
+        }
 
-        self.blinking_cursor = blinking;
         self.event_proxy
             .send_event(RioEvent::CursorBlinkingChange, self.window_id);
     }
@@ -2066,11 +3045,17 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline(never)]
     fn input(&mut self, c: char) {
-        let width = match c.width() {
+        let width = match self.char_width.width(c) {
             Some(width) => width,
             None => return,
         };
 
+        self.on_output(0);
+
+        if is_regional_indicator(c) && self.join_regional_indicator(c) {
+            return;
+        }
+
         // Handle zero-width characters.
         if width == 0 {
             // // Get previous column.
@@ -2089,6 +3074,10 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
 
             self.grid[row][column].push_zerowidth(c);
+            // The diacritics of a placeholder change the cell it shows
+            if self.grid[row][column].c == PLACEHOLDER {
+                self.attach_placeholder(row, column);
+            }
             return;
         }
 
@@ -2110,6 +3099,10 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
         if width == 1 {
             self.write_at_cursor(c);
+            if c == PLACEHOLDER {
+                let pos = self.grid.cursor.pos;
+                self.attach_placeholder(pos.row, pos.col);
+            }
         } else {
             if self.grid.cursor.pos.col + 1 >= columns {
                 if self.mode.contains(Mode::LINE_WRAP) {
@@ -2174,14 +3167,14 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 trace!("Reporting primary device attributes");
                 let text = String::from("\x1b[?62;4;6;22c");
                 self.event_proxy
-                    .send_event(RioEvent::PtyWrite(text), self.window_id);
+                    .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
             }
             Some('>') => {
                 trace!("Reporting secondary device attributes");
                 let version = version_number(env!("CARGO_PKG_VERSION"));
                 let text = format!("\x1b[>0;{version};1c");
                 self.event_proxy
-                    .send_event(RioEvent::PtyWrite(text), self.window_id);
+                    .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
             }
             _ => debug!("Unsupported device attributes intermediate"),
         }
@@ -2196,7 +3189,35 @@ impl<U: EventListener> Handler for Crosswords<U> {
             .bits();
         let text = format!("\x1b[?{current_mode}u");
         self.event_proxy
-            .send_event(RioEvent::PtyWrite(text), self.window_id);
+            .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
+    }
+
+    #[inline]
+    fn set_modify_other_keys(&mut self, mode: ModifyOtherKeys) {
+        info!("Setting modifyOtherKeys to {mode:?}");
+        self.mode
+            .remove(Mode::MODIFY_OTHER_KEYS | Mode::MODIFY_OTHER_KEYS_ALL);
+        match mode {
+            ModifyOtherKeys::Reset => (),
+            ModifyOtherKeys::EnableExceptWellDefined => {
+                self.mode.insert(Mode::MODIFY_OTHER_KEYS)
+            }
+            ModifyOtherKeys::EnableAll => self.mode.insert(Mode::MODIFY_OTHER_KEYS_ALL),
+        }
+    }
+
+    #[inline]
+    fn report_modify_other_keys(&mut self) {
+        let level = if self.mode.contains(Mode::MODIFY_OTHER_KEYS_ALL) {
+            2
+        } else if self.mode.contains(Mode::MODIFY_OTHER_KEYS) {
+            1
+        } else {
+            0
+        };
+        let text = format!("\x1b[>4;{level}m");
+        self.event_proxy
+            .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
     }
 
     #[inline]
@@ -2242,13 +3263,13 @@ impl<U: EventListener> Handler for Crosswords<U> {
             5 => {
                 let text = String::from("\x1b[0n");
                 self.event_proxy
-                    .send_event(RioEvent::PtyWrite(text), self.window_id);
+                    .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
             }
             6 => {
                 let pos = self.grid.cursor.pos;
                 let text = format!("\x1b[{};{}R", pos.row + 1, pos.col + 1);
                 self.event_proxy
-                    .send_event(RioEvent::PtyWrite(text), self.window_id);
+                    .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
             }
             _ => debug!("unknown device status query: {}", arg),
         };
@@ -2320,7 +3341,10 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 } else {
                     let old_offset = self.grid.display_offset();
 
-                    self.grid.clear_viewport();
+                    let positions = self.grid.clear_viewport();
+                    let region = Line(0)..Line(screen_lines as i32);
+                    self.rotate_prompt_marks(&region, positions as i32);
+                    self.record_region_scroll(&region, positions as i32);
 
                     // Compute number of lines scrolled by clearing the viewport.
                     let lines = self.grid.display_offset().saturating_sub(old_offset);
@@ -2333,6 +3357,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             ClearMode::Saved if self.history_size() > 0 => {
                 self.grid.clear_history();
+                self.drop_prompt_marks_outside_grid();
 
                 self.vi_mode_cursor.pos.row = self
                     .vi_mode_cursor
@@ -2411,7 +3436,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn bell(&mut self) {
-        warn!("[unimplemented] Bell");
+        self.bell_rung = true;
     }
 
     #[inline]
@@ -2419,6 +3444,24 @@ impl<U: EventListener> Handler for Crosswords<U> {
         warn!("[unimplemented] Substitute");
     }
 
+    #[inline]
+    fn display_control(&mut self, byte: u8) -> bool {
+        if !self.mode.contains(Mode::CONTROL_PICTURES) {
+            return false;
+        }
+        let Some(picture) = control_picture(byte) else {
+            return false;
+        };
+
+        self.input(picture);
+        // Line breaks are still performed so the output stays readable
+        if matches!(byte, C0::LF | C0::VT | C0::FF) {
+            self.carriage_return();
+            self.linefeed();
+        }
+        true
+    }
+
     #[inline]
     fn clipboard_load(&mut self, clipboard: u8, terminator: &str) {
         let clipboard_type = match clipboard {
@@ -2431,6 +3474,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
         self.event_proxy.send_event(
             RioEvent::ClipboardLoad(
+                self.route_id,
                 clipboard_type,
                 Arc::new(move |text| {
                     let base64 = general_purpose::STANDARD.encode(text);
@@ -2578,11 +3622,14 @@ impl<U: EventListener> Handler for Crosswords<U> {
     fn text_area_size_pixels(&mut self) {
         debug!("text_area_size_pixels");
         self.event_proxy.send_event(
-            RioEvent::TextAreaSizeRequest(Arc::new(move |window_size| {
-                let height = window_size.height;
-                let width = window_size.width;
-                format!("\x1b[4;{height};{width}t")
-            })),
+            RioEvent::TextAreaSizeRequest(
+                self.route_id,
+                Arc::new(move |window_size| {
+                    let height = window_size.height;
+                    let width = window_size.width;
+                    format!("\x1b[4;{height};{width}t")
+                }),
+            ),
             self.window_id,
         );
     }
@@ -2596,7 +3643,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
         );
         debug!("cells_size_pixels {:?}", text);
         self.event_proxy
-            .send_event(RioEvent::PtyWrite(text), self.window_id);
+            .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
     }
 
     #[inline]
@@ -2608,7 +3655,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
         );
         debug!("text_area_size_chars {:?}", text);
         self.event_proxy
-            .send_event(RioEvent::PtyWrite(text), self.window_id);
+            .send_event(RioEvent::PtyWrite(self.route_id, text), self.window_id);
     }
 
     #[inline]
@@ -2668,23 +3715,26 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 match pa {
                     1 => {
                         self.event_proxy.send_event(
-                            RioEvent::TextAreaSizeRequest(Arc::new(move |window_size| {
-                                let width = window_size.width;
-                                let height = window_size.height;
-                                let graphic_dimensions = [
-                                    std::cmp::min(
-                                        width as usize,
-                                        MAX_GRAPHIC_DIMENSIONS[0],
-                                    ),
-                                    std::cmp::min(
-                                        height as usize,
-                                        MAX_GRAPHIC_DIMENSIONS[1],
-                                    ),
-                                ];
-
-                                let (ps, pv) = (0, &graphic_dimensions[..]);
-                                generate_response(pi, ps, pv)
-                            })),
+                            RioEvent::TextAreaSizeRequest(
+                                self.route_id,
+                                Arc::new(move |window_size| {
+                                    let width = window_size.width;
+                                    let height = window_size.height;
+                                    let graphic_dimensions = [
+                                        std::cmp::min(
+                                            width as usize,
+                                            MAX_GRAPHIC_DIMENSIONS[0],
+                                        ),
+                                        std::cmp::min(
+                                            height as usize,
+                                            MAX_GRAPHIC_DIMENSIONS[1],
+                                        ),
+                                    ];
+
+                                    let (ps, pv) = (0, &graphic_dimensions[..]);
+                                    generate_response(pi, ps, pv)
+                                }),
+                            ),
                             self.window_id,
                         );
                         return;
@@ -2706,7 +3756,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
         };
 
         self.event_proxy.send_event(
-            RioEvent::PtyWrite(generate_response(pi, ps, pv)),
+            RioEvent::PtyWrite(self.route_id, generate_response(pi, ps, pv)),
             self.window_id,
         );
     }
@@ -2752,9 +3802,6 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn insert_graphic(&mut self, graphic: GraphicData, palette: Option<Vec<ColorRgb>>) {
-        let cell_width = self.graphics.cell_width as usize;
-        let cell_height = self.graphics.cell_height as usize;
-
         // Store last palette if we receive a new one, and it is shared.
         if let Some(palette) = palette {
             if !self.mode.contains(Mode::SIXEL_PRIV_PALETTE) {
@@ -2762,182 +3809,36 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
         }
 
-        let graphic = match graphic.resized(
-            cell_width,
-            cell_height,
-            cell_width * self.grid.columns(),
-            cell_height * self.grid.screen_lines(),
-        ) {
-            Some(graphic) => graphic,
-            None => return,
-        };
-
-        if graphic.width > MAX_GRAPHIC_DIMENSIONS[0]
-            || graphic.height > MAX_GRAPHIC_DIMENSIONS[1]
-        {
-            return;
-        }
-
-        let width = graphic.width as u16;
-        let height = graphic.height as u16;
+        self.display_graphic(graphic);
+    }
 
-        if width == 0 || height == 0 {
+    fn insert_animated_graphic(&mut self, frames: Vec<AnimationFrame>) {
+        let Some(first) = frames.first() else {
             return;
-        }
-
-        let graphic_id = self.graphics.next_id();
-
-        // If SIXEL_DISPLAY is disabled, the start of the graphic is the
-        // cursor position, and the grid can be scrolled if the graphic is
-        // larger than the screen. The cursor is moved to the next line
-        // after the graphic.
-        //
-        // If it is disabled, the graphic starts at (0, 0), the grid is never
-        // scrolled, and the cursor position is unmodified.
-
-        let scrolling = !self.mode.contains(Mode::SIXEL_DISPLAY);
-
-        let leftmost = if scrolling {
-            self.grid.cursor.pos.col.0
-        } else {
-            0
         };
-
-        // A very simple optimization is to detect is a new graphic is replacing
-        // completely a previous one. This happens if the following conditions
-        // are met:
-        //
-        // - Both graphics are attached to the same top-left cell.
-        // - Both graphics have the same size.
-        // - The new graphic does not contain transparent pixels.
-        //
-        // In this case, we will ignore cells with a reference to the replaced
-        // graphic.
-
-        let skip_textures = {
-            if graphic.maybe_transparent() {
-                HashSet::new()
-            } else {
-                let mut set = HashSet::new();
-
-                let line = if scrolling {
-                    self.grid.cursor.pos.row
-                } else {
-                    Line(0)
-                };
-
-                if let Some(old_graphics) = self.grid[line][Column(leftmost)].graphics() {
-                    for graphic in old_graphics {
-                        let tex = &*graphic.texture;
-                        if tex.width == width
-                            && tex.height == height
-                            && tex.cell_height == cell_height
-                        {
-                            set.insert(tex.id);
-                        }
-                    }
-                }
-
-                set
-            }
+        let Some(id) = self.display_graphic(first.graphic.clone()) else {
+            return;
         };
 
-        // Fill the cells under the graphic.
-        //
-        // The cell in the first column contains a reference to the
-        // graphic, with the offset from the start. The rest of the
-        // cells are not overwritten, allowing any text behind
-        // transparent portions of the image to be visible.
-
-        let texture = Arc::new(TextureRef {
-            id: graphic_id,
-            width,
-            height,
-            cell_height,
-            texture_operations: Arc::downgrade(&self.graphics.texture_operations),
-        });
-
-        for (top, offset_y) in (0..).zip((0..height).step_by(cell_height)) {
-            let line = if scrolling {
-                self.grid.cursor.pos.row
-            } else {
-                // Check if the image is beyond the screen limit.
-                if top >= self.grid.screen_lines() as i32 {
-                    break;
-                }
-
-                Line(top)
-            };
-
-            // Store a reference to the graphic in the first column.
-            let row_len = self.grid[line].len();
-            for (left, offset_x) in (leftmost..).zip((0..width).step_by(cell_width)) {
-                if left >= row_len {
-                    break;
-                }
-
-                let texture_operations =
-                    Arc::downgrade(&self.graphics.texture_operations);
-                let graphic_cell = GraphicCell {
-                    texture: texture.clone(),
-                    offset_x,
-                    offset_y,
-                    texture_operations,
-                };
-
-                let mut cell = self.grid.cursor.template.clone();
-                let cell_ref = &mut self.grid[line][Column(left)];
-
-                // If the cell contains any graphics, and the region of the cell
-                // is not fully filled by the new graphic, the old graphics are
-                // kept in the cell.
-                let graphics = match cell_ref.take_graphics() {
-                    Some(mut old_graphics)
-                        if old_graphics.iter().any(|graphic| {
-                            !skip_textures.contains(&graphic.texture.id)
-                        }) && !graphic.is_filled(
-                            offset_x as usize,
-                            offset_y as usize,
-                            cell_width,
-                            cell_height,
-                        ) =>
-                    {
-                        // Ensure that we don't exceed the graphics limit per cell.
-                        while old_graphics.len() >= MAX_GRAPHICS_PER_CELL {
-                            drop(old_graphics.remove(0));
-                        }
-
-                        old_graphics.push(graphic_cell);
-                        old_graphics
-                    }
-
-                    _ => smallvec::smallvec![graphic_cell],
-                };
-
-                cell.set_graphics(graphics);
-                *cell_ref = cell;
-            }
-
-            self.mark_line_damaged(line);
-
-            if scrolling && offset_y < height.saturating_sub(cell_height as u16) {
-                self.linefeed();
-            }
-        }
-
-        if self.mode.contains(Mode::SIXEL_CURSOR_TO_THE_RIGHT) {
-            let graphic_columns = graphic.width.div_ceil(cell_width);
-            self.move_forward(Column(graphic_columns));
-        } else if scrolling {
-            self.linefeed();
-            self.carriage_return();
+        // Every frame is resized like the first one
+        let frames = frames
+            .into_iter()
+            .map(|frame| {
+                Some(AnimationFrame {
+                    graphic: self.resize_graphic(frame.graphic)?,
+                    ..frame
+                })
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(frames) = frames {
+            self.graphics.animate(id, Animation::new(frames));
+            self.reclaim_graphics_memory();
         }
+    }
 
-        // Add the graphic data to the pending queue.
-        self.graphics.pending.push(GraphicData {
-            id: graphic_id,
-            ..graphic
-        });
+    #[inline]
+    fn kitty_graphics(&mut self, command: KittyImage) {
+        self.kitty_graphics_command(command);
     }
 }
 
@@ -3035,6 +3936,86 @@ mod tests {
     use crate::crosswords::CrosswordsSize;
     use crate::event::VoidListener;
 
+    /// Writes `text` and moves to the start of the next line.
+    fn write_line(cw: &mut Crosswords<VoidListener>, text: &str) {
+        for c in text.chars() {
+            cw.input(c);
+        }
+        cw.carriage_return();
+        cw.linefeed();
+    }
+
+    #[test]
+    fn set_user_var_empty_value_unsets() {
+        let size = CrosswordsSize::new(10, 2);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        cw.set_user_var(String::from("git_branch"), String::from("main"));
+        assert_eq!(
+            cw.user_vars.get("git_branch").map(String::as_str),
+            Some("main")
+        );
+
+        cw.set_user_var(String::from("git_branch"), String::new());
+        assert!(!cw.user_vars.contains_key("git_branch"));
+    }
+
+    #[test]
+    fn set_user_var_is_capped() {
+        let size = CrosswordsSize::new(10, 2);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        cw.set_user_var(String::from("big"), "a".repeat(USER_VAR_MAX_SIZE + 1));
+        assert!(cw.user_vars.is_empty());
+
+        for index in 0..USER_VARS_MAX_LEN + 1 {
+            cw.set_user_var(format!("var{index}"), String::from("1"));
+        }
+        assert_eq!(cw.user_vars.len(), USER_VARS_MAX_LEN);
+        assert!(!cw
+            .user_vars
+            .contains_key(&format!("var{USER_VARS_MAX_LEN}")));
+
+        // Set variables can still change
+        cw.set_user_var(String::from("var0"), String::from("2"));
+        assert_eq!(cw.user_vars.get("var0").map(String::as_str), Some("2"));
+    }
+
+    #[test]
+    fn vi_inline_search() {
+        let size = CrosswordsSize::new(10, 2);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        for (i, c) in "a.b.c.d".chars().enumerate() {
+            cw.grid[Line(0)][Column(i)].c = c;
+        }
+        cw.grid[Line(1)][Column(0)].c = '.';
+
+        assert!(cw.vi_inline_search('.', Direction::Right, false, false));
+        assert_eq!(cw.vi_mode_cursor.pos, Pos::new(Line(0), Column(1)));
+        assert!(cw.vi_inline_search('.', Direction::Right, false, true));
+        assert_eq!(cw.vi_mode_cursor.pos, Pos::new(Line(0), Column(3)));
+
+        // Stopping short skips the needle next to the cursor when repeated.
+        assert!(cw.vi_inline_search('.', Direction::Right, true, false));
+        assert_eq!(cw.vi_mode_cursor.pos, Pos::new(Line(0), Column(4)));
+        assert!(!cw.vi_inline_search('.', Direction::Right, true, true));
+        assert_eq!(cw.vi_mode_cursor.pos, Pos::new(Line(0), Column(4)));
+        assert!(cw.vi_inline_search('.', Direction::Left, true, true));
+        assert_eq!(cw.vi_mode_cursor.pos, Pos::new(Line(0), Column(2)));
+
+        // The search doesn't leave the line.
+        assert!(!cw.vi_inline_search('x', Direction::Right, false, false));
+        cw.vi_mode_cursor.pos = Pos::new(Line(0), Column(6));
+        assert!(!cw.vi_inline_search('.', Direction::Right, false, false));
+        assert_eq!(cw.vi_mode_cursor.pos, Pos::new(Line(0), Column(6)));
+    }
+
     #[test]
     fn scroll_up() {
         let size = CrosswordsSize::new(1, 10);
@@ -3082,6 +4063,296 @@ mod tests {
         assert_eq!(cw.grid.total_lines(), 2);
     }
 
+    #[test]
+    fn test_update_history() {
+        let size = CrosswordsSize::new(5, 3);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        for _ in 0..10 {
+            cw.linefeed();
+        }
+        assert_eq!(cw.history_size(), 8);
+
+        cw.scroll_display(Scroll::Top);
+        assert_eq!(cw.display_offset(), 8);
+
+        cw.update_history(4);
+        assert_eq!(cw.history_size(), 4);
+        assert_eq!(cw.display_offset(), 4);
+
+        cw.update_history(6);
+        for _ in 0..10 {
+            cw.linefeed();
+        }
+        assert_eq!(cw.history_size(), 6);
+
+        cw.update_history(0);
+        assert_eq!(cw.history_size(), 0);
+        assert_eq!(cw.display_offset(), 0);
+    }
+
+    #[test]
+    fn test_set_cursor_style() {
+        let size = CrosswordsSize::new(5, 3);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.blinking_cursor = true;
+        cw.default_blinking_cursor = true;
+
+        // CSI 6 SP q
+        cw.set_cursor_style(Some(CursorShape::Beam), false);
+        assert_eq!(cw.cursor().content, CursorShape::Beam);
+        assert!(!cw.blinking_cursor);
+
+        // CSI 3 SP q
+        cw.set_cursor_style(Some(CursorShape::Underline), true);
+        assert_eq!(cw.cursor().content, CursorShape::Underline);
+        assert!(cw.blinking_cursor);
+
+        // CSI 0 SP q restores the configured cursor
+        cw.set_cursor_style(Some(CursorShape::Beam), false);
+        cw.set_cursor_style(None, false);
+        assert_eq!(cw.cursor().content, CursorShape::Block);
+        assert!(cw.blinking_cursor);
+    }
+
+    #[test]
+    fn test_output_while_scrolled_up() {
+        let size = CrosswordsSize::new(5, 3);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        for _ in 0..10 {
+            cw.linefeed();
+        }
+        cw.scroll_display(Scroll::Delta(2));
+        assert_eq!(cw.new_lines_below(), 0);
+
+        // The viewport stays on the same content
+        cw.input('a');
+        assert_eq!(cw.new_lines_below(), 1);
+        cw.linefeed();
+        cw.linefeed();
+        assert_eq!(cw.display_offset(), 4);
+        assert_eq!(cw.new_lines_below(), 3);
+
+        // Limited to the lines below the viewport
+        cw.scroll_display(Scroll::Delta(-3));
+        assert_eq!(cw.new_lines_below(), 1);
+
+        cw.scroll_display(Scroll::Bottom);
+        assert_eq!(cw.new_lines_below(), 0);
+
+        cw.scroll_on_output = true;
+        cw.scroll_display(Scroll::Delta(2));
+        cw.input('a');
+        assert_eq!(cw.display_offset(), 0);
+        assert_eq!(cw.new_lines_below(), 0);
+    }
+
+    #[test]
+    fn test_scrolling_display_is_partial_damage() {
+        let size = CrosswordsSize::new(5, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        for _ in 0..20 {
+            cw.linefeed();
+        }
+        cw.reset_damage();
+
+        cw.scroll_display(Scroll::Delta(3));
+        cw.scroll_display(Scroll::Delta(-1));
+        assert!(!cw.is_fully_damaged());
+        assert_eq!(cw.scrolled_lines(), 2);
+        cw.reset_damage();
+        assert_eq!(cw.scrolled_lines(), 0);
+
+        // Nothing is left to reuse after scrolling a whole screen
+        cw.scroll_display(Scroll::Delta(5));
+        assert!(cw.is_fully_damaged());
+        cw.reset_damage();
+
+        // Vi mode cursor doesn't follow the content
+        cw.toggle_vi_mode();
+        cw.reset_damage();
+        cw.scroll_display(Scroll::Delta(1));
+        assert!(cw.is_fully_damaged());
+    }
+
+    #[test]
+    fn test_select_last_command_output() {
+        let size = CrosswordsSize::new(10, 3);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        assert!(!cw.select_last_command_output());
+
+        for command in ["ls", "pwd"] {
+            cw.set_prompt_mark(PromptMark::PromptStart);
+            cw.input('$');
+            cw.set_prompt_mark(PromptMark::CommandStart);
+            write_line(&mut cw, command);
+            cw.set_prompt_mark(PromptMark::OutputStart);
+            write_line(&mut cw, &format!("{command}-1"));
+            write_line(&mut cw, &format!("{command}-2"));
+            cw.set_prompt_mark(PromptMark::CommandEnd);
+        }
+        cw.set_prompt_mark(PromptMark::PromptStart);
+        cw.input('$');
+
+        // The output was scrolled into the history
+        assert!(cw.select_last_command_output());
+        assert_eq!(cw.selection_to_string(), Some(String::from("pwd-1\npwd-2")));
+
+        cw.select_all();
+        assert_eq!(
+            cw.selection_to_string(),
+            Some(String::from("$ls\nls-1\nls-2\n$pwd\npwd-1\npwd-2\n$"))
+        );
+
+        // Marks are dropped with the history
+        cw.clear_saved_history();
+        assert_eq!(cw.prompt_marks.len(), 3);
+        assert!(cw.select_last_command_output());
+        assert_eq!(cw.selection_to_string(), Some(String::from("pwd-1\npwd-2")));
+    }
+
+    #[test]
+    fn test_selection_hyperlinks() {
+        let size = CrosswordsSize::new(10, 2);
+        let mut cw = Crosswords::new(
+            size,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+            0,
+        );
+
+        cw.input('a');
+        cw.set_hyperlink(Some(Hyperlink::new(None, "https://first.org")));
+        cw.input('b');
+        cw.input('c');
+        cw.set_hyperlink(None);
+        cw.input('d');
+        cw.set_hyperlink(Some(Hyperlink::new(None, "https://second.org")));
+        cw.input('e');
+        cw.set_hyperlink(Some(Hyperlink::new(None, "https://first.org")));
+        cw.input('f');
+        cw.set_hyperlink(None);
+
+        assert!(cw.selection_hyperlinks().is_empty());
+
+        cw.selection = Some(Selection::new(
+            SelectionType::Simple,
+            Pos::new(Line(0), Column(0)),
+            Side::Left,
+        ));
+        cw.selection
+            .as_mut()
+            .unwrap()
+            .update(Pos::new(Line(0), Column(3)), Side::Right);
+        assert_eq!(cw.selection_to_string(), Some(String::from("abcd")));
+        assert_eq!(cw.selection_hyperlinks(), vec!["https://first.org"]);
+
+        cw.select_all();
+        assert_eq!(
+            cw.selection_hyperlinks(),
+            vec!["https://first.org", "https://second.org"]
+        );
+    }
+
+    #[test]
+    fn test_recent_output() {
+        let size = CrosswordsSize::new(10, 4);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        assert_eq!(cw.recent_output(3), "");
+
+        for line in ["one", "two", "three", "four"] {
+            write_line(&mut cw, line);
+        }
+        // Lines scrolled into the history are included
+        assert_eq!(cw.recent_output(3), "two\nthree\nfour");
+        assert_eq!(cw.recent_output(10), "one\ntwo\nthree\nfour");
+        assert_eq!(cw.recent_output(0), "");
+
+        // The prompt being edited isn't output
+        cw.set_prompt_mark(PromptMark::PromptStart);
+        cw.input('$');
+        cw.set_prompt_mark(PromptMark::CommandStart);
+        cw.input('l');
+        assert_eq!(cw.recent_output(2), "three\nfour");
+
+        cw.set_prompt_mark(PromptMark::OutputStart);
+        cw.carriage_return();
+        cw.linefeed();
+        write_line(&mut cw, "five");
+        assert_eq!(cw.recent_output(2), "$l\nfive");
+    }
+
+    #[test]
+    fn test_region_scrolls() {
+        let size = CrosswordsSize::new(10, 6);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        cw.set_scrolling_region(2, Some(5));
+        assert_eq!(cw.scroll_region(), &(Line(1)..Line(5)));
+
+        cw.scroll_up_relative(Line(1), 1);
+        cw.scroll_up_relative(Line(1), 1);
+        let scrolls = cw.take_region_scrolls().unwrap();
+        assert_eq!(
+            scrolls,
+            vec![RegionScroll {
+                region: Line(1)..Line(5),
+                delta: 2,
+                into_history: false,
+            }]
+        );
+        assert_eq!(cw.take_region_scrolls(), Some(vec![]));
+
+        let scroll = &scrolls[0];
+        let range = |row: i32, end_row: i32| {
+            (
+                Pos::new(Line(row), Column(0)),
+                Pos::new(Line(end_row), Column(3)),
+            )
+        };
+        // Ranges in the region move with it, outside they stay in place
+        assert_eq!(
+            scroll.rotate(range(3, 4).0, range(3, 4).1),
+            Some(range(1, 2))
+        );
+        assert_eq!(
+            scroll.rotate(range(0, 0).0, range(0, 0).1),
+            Some(range(0, 0))
+        );
+        assert_eq!(
+            scroll.rotate(range(5, 5).0, range(5, 5).1),
+            Some(range(5, 5))
+        );
+        // Leaving the region or crossing its boundary drops the range
+        assert_eq!(scroll.rotate(range(1, 2).0, range(1, 2).1), None);
+        assert_eq!(scroll.rotate(range(4, 5).0, range(4, 5).1), None);
+
+        // Positions can't be followed across a resize
+        cw.scroll_down_relative(Line(1), 1);
+        cw.resize(CrosswordsSize::new(10, 4));
+        assert_eq!(cw.take_region_scrolls(), None);
+        assert_eq!(cw.take_region_scrolls(), Some(vec![]));
+    }
+
     #[test]
     fn test_linefeed_moving_cursor() {
         let size = CrosswordsSize::new(1, 3);
@@ -3204,6 +4475,13 @@ mod tests {
             term.selection_to_string(),
             Some(String::from(" aaa  aaa\""))
         );
+
+        // A wrapline keeping the visual layout.
+        term.copy_visual_layout = true;
+        assert_eq!(
+            term.selection_to_string(),
+            Some(String::from(" aaa \n aaa\""))
+        );
     }
 
     #[test]
@@ -3607,6 +4885,445 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_search_nearest_hyperlink_from_pos_with_patterns() {
+        let size = CrosswordsSize::new(20, 1);
+        let window_id = crate::event::WindowId::from(0);
+        let mut term =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        term.set_hyperlink_patterns(&[
+            HyperlinkPattern {
+                regex: String::from("("),
+                command: None,
+            },
+            HyperlinkPattern {
+                regex: String::from(r"^RIO-(\d+)$"),
+                command: Some(Shell {
+                    program: String::from("open"),
+                    args: vec![String::from("https://tickets.rio/$1")],
+                }),
+            },
+            HyperlinkPattern {
+                regex: String::from(r"^[0-9a-f]{7}$"),
+                command: None,
+            },
+        ]);
+
+        for (i, c) in "RIO-12 a1b2c3d".chars().enumerate() {
+            term.grid[Line(0)][Column(i)].c = c;
+        }
+
+        let result = term
+            .search_nearest_hyperlink_from_pos(Pos::new(pos::Line(0), pos::Column(1)));
+        assert_eq!(result.map(|range| range.end.col), Some(Column(5)));
+        let hyperlink = term.grid[Line(0)][Column(0)].hyperlink().unwrap();
+        assert_eq!(hyperlink.uri(), "RIO-12");
+        assert_eq!(
+            hyperlink.command(),
+            Some(&[String::from("open"), String::from("https://tickets.rio/12")][..])
+        );
+
+        // Short matches of a pattern are hyperlinks, opened like urls
+        term.search_nearest_hyperlink_from_pos(Pos::new(pos::Line(0), pos::Column(8)))
+            .unwrap();
+        let hyperlink = term.grid[Line(0)][Column(13)].hyperlink().unwrap();
+        assert_eq!(hyperlink.uri(), "a1b2c3d");
+        assert_eq!(hyperlink.command(), None);
+    }
+
+    #[test]
+    fn regional_indicators_form_flags() {
+        let size = CrosswordsSize::new(5, 2);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        // Two flags and a lone regional indicator
+        for c in "🇧🇷🇵🇹🇺".chars() {
+            cw.input(c);
+        }
+
+        let row = &cw.grid[Line(0)];
+        assert_eq!(row[Column(0)].c, '🇧');
+        assert_eq!(row[Column(0)].zerowidth(), Some(&['🇷'][..]));
+        assert!(row[Column(0)].flags.contains(square::Flags::WIDE_CHAR));
+        assert!(row[Column(1)]
+            .flags
+            .contains(square::Flags::WIDE_CHAR_SPACER));
+        assert_eq!(row[Column(2)].c, '🇵');
+        assert_eq!(row[Column(2)].zerowidth(), Some(&['🇹'][..]));
+        assert_eq!(row[Column(4)].c, '🇺');
+        assert_eq!(row[Column(4)].zerowidth(), None);
+        assert!(cw.grid.cursor.should_wrap);
+
+        // A wide flag does not fit after the last column
+        cw.input('🇸');
+        assert_eq!(cw.grid[Line(0)][Column(4)].zerowidth(), None);
+        assert_eq!(cw.grid[Line(1)][Column(0)].c, '🇸');
+
+        // Narrow flags take a single cell
+        let mut cw = Crosswords::new(
+            CrosswordsSize::new(5, 2),
+            CursorShape::Block,
+            VoidListener {},
+            window_id,
+            0,
+        );
+        cw.char_width.emoji_width = EmojiWidth::Narrow;
+        for c in "🇧🇷a".chars() {
+            cw.input(c);
+        }
+
+        let row = &cw.grid[Line(0)];
+        assert_eq!(row[Column(0)].zerowidth(), Some(&['🇷'][..]));
+        assert!(!row[Column(0)].flags.contains(square::Flags::WIDE_CHAR));
+        assert_eq!(row[Column(1)].c, 'a');
+    }
+
+    #[test]
+    fn zero_width_characters() {
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw = Crosswords::new(
+            CrosswordsSize::new(5, 1),
+            CursorShape::Block,
+            VoidListener {},
+            window_id,
+            0,
+        );
+        for c in "a\u{AD}\u{200B}b".chars() {
+            cw.input(c);
+        }
+
+        // Hidden, they stay with the previous character
+        let row = &cw.grid[Line(0)];
+        assert_eq!(
+            row[Column(0)].zerowidth(),
+            Some(&['\u{AD}', '\u{200B}'][..])
+        );
+        assert_eq!(row[Column(1)].c, 'b');
+        assert_eq!(cw.grid.cursor.pos.col, Column(2));
+
+        let mut cw = Crosswords::new(
+            CrosswordsSize::new(5, 1),
+            CursorShape::Block,
+            VoidListener {},
+            window_id,
+            0,
+        );
+        cw.char_width.zero_width = crate::config::unicode::ZeroWidth::Show;
+        for c in "a\u{AD}\u{200B}b".chars() {
+            cw.input(c);
+        }
+
+        // Shown, each takes a cell
+        let row = &cw.grid[Line(0)];
+        assert_eq!(row[Column(0)].zerowidth(), None);
+        assert_eq!(row[Column(1)].c, '\u{AD}');
+        assert_eq!(row[Column(2)].c, '\u{200B}');
+        assert_eq!(row[Column(3)].c, 'b');
+        assert_eq!(cw.grid.cursor.pos.col, Column(4));
+    }
+
+    #[test]
+    fn control_pictures() {
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw = Crosswords::new(
+            CrosswordsSize::new(5, 3),
+            CursorShape::Block,
+            VoidListener {},
+            window_id,
+            0,
+        );
+        let mut processor: crate::performer::handler::Processor =
+            crate::performer::handler::Processor::new();
+
+        cw.toggle_control_pictures();
+        processor.advance(&mut cw, b"a\x07\r\nb");
+        let row = &cw.grid[Line(0)];
+        assert_eq!(row[Column(0)].c, 'a');
+        assert_eq!(row[Column(1)].c, '\u{2407}');
+        assert_eq!(row[Column(2)].c, '\u{240D}');
+        assert_eq!(row[Column(3)].c, '\u{240A}');
+        assert_eq!(cw.grid[Line(1)][Column(0)].c, 'b');
+
+        // Kept across resets, performed again once toggled off
+        cw.reset_state();
+        assert!(cw.mode().contains(Mode::CONTROL_PICTURES));
+        cw.toggle_control_pictures();
+        processor.advance(&mut cw, b"a\r\nb");
+        assert_eq!(cw.grid[Line(0)][Column(1)].c, ' ');
+        assert_eq!(cw.grid[Line(1)][Column(0)].c, 'b');
+    }
+
+    #[test]
+    fn modify_other_keys() {
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw = Crosswords::new(
+            CrosswordsSize::new(5, 3),
+            CursorShape::Block,
+            VoidListener {},
+            window_id,
+            0,
+        );
+        let mut processor: crate::performer::handler::Processor =
+            crate::performer::handler::Processor::new();
+        let levels = |cw: &Crosswords<VoidListener>| {
+            (
+                cw.mode().contains(Mode::MODIFY_OTHER_KEYS),
+                cw.mode().contains(Mode::MODIFY_OTHER_KEYS_ALL),
+            )
+        };
+
+        processor.advance(&mut cw, b"\x1b[>4;1m");
+        assert_eq!(levels(&cw), (true, false));
+        processor.advance(&mut cw, b"\x1b[>4;2m");
+        assert_eq!(levels(&cw), (false, true));
+
+        // Other resources are left alone
+        processor.advance(&mut cw, b"\x1b[>1;2m");
+        assert_eq!(levels(&cw), (false, true));
+
+        processor.advance(&mut cw, b"\x1b[>4m");
+        assert_eq!(levels(&cw), (false, false));
+
+        processor.advance(&mut cw, b"\x1b[>4;1m\x1b[>4n");
+        assert_eq!(levels(&cw), (false, false));
+
+        // Doesn't change the kitty keyboard modes
+        processor.advance(&mut cw, b"\x1b[>1u\x1b[>4;2m\x1b[>m");
+        assert_eq!(levels(&cw), (false, false));
+        assert!(cw.mode().contains(Mode::DISAMBIGUATE_ESC_CODES));
+    }
+
+    #[test]
+    fn win32_input_mode() {
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw = Crosswords::new(
+            CrosswordsSize::new(5, 3),
+            CursorShape::Block,
+            VoidListener {},
+            window_id,
+            0,
+        );
+        let mut processor: crate::performer::handler::Processor =
+            crate::performer::handler::Processor::new();
+
+        processor.advance(&mut cw, b"\x1b[?9001h");
+        assert!(cw.mode().contains(Mode::WIN32_INPUT));
+        processor.advance(&mut cw, b"\x1b[?9001l");
+        assert!(!cw.mode().contains(Mode::WIN32_INPUT));
+    }
+
+    #[test]
+    fn kitty_graphics_place_and_delete() {
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.graphics.cell_width = 10.;
+        cw.graphics.cell_height = 10.;
+
+        // 20x20 RGBA image, covering two columns and two rows
+        let pixels = general_purpose::STANDARD.encode([255; 20 * 20 * 4]);
+        let command = format!("Ga=T,f=32,s=20,v=20,i=1,p=1,z=-1;{pixels}");
+        cw.kitty_graphics(KittyImage::parse_apc(command.as_bytes()).unwrap());
+
+        let texture = &cw.grid[Line(1)][Column(1)].graphics().unwrap()[0].texture;
+        assert_eq!(texture.z_index, -1);
+        assert_eq!(
+            texture.kitty,
+            Some(KittyPlacementId {
+                image_id: 1,
+                placement_id: 1
+            })
+        );
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(1), Column(2)));
+        assert_eq!(cw.graphics.pending.len(), 1);
+
+        // Placing with the same ids moves the placement
+        cw.goto(Line(3), Column(5));
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=p,i=1,p=1,C=1").unwrap());
+        assert!(cw.grid[Line(0)][Column(0)].graphics().is_none());
+        assert!(cw.grid[Line(3)][Column(5)].graphics().is_some());
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(3), Column(5)));
+
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=d,d=p,x=7,y=5").unwrap());
+        assert!(cw.grid[Line(3)][Column(5)].graphics().is_none());
+        assert!(!cw.grid[Line(4)][Column(6)]
+            .flags
+            .contains(square::Flags::GRAPHICS));
+
+        // The image data is kept until deleted with an uppercase variant
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=p,i=1").unwrap());
+        assert!(cw.grid[Line(3)][Column(5)].graphics().is_some());
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=d,d=I,i=1").unwrap());
+        assert!(cw.grid[Line(3)][Column(5)].graphics().is_none());
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=p,i=1").unwrap());
+        assert!(cw.grid[Line(3)][Column(5)].graphics().is_none());
+    }
+
+    #[test]
+    fn kitty_graphics_animation() {
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.graphics.cell_width = 10.;
+        cw.graphics.cell_height = 10.;
+
+        let red = general_purpose::STANDARD.encode([255, 0, 0, 255]);
+        let green = general_purpose::STANDARD.encode([0, 255, 0, 255]);
+        let command = format!("Ga=T,f=32,s=1,v=1,i=1,q=2;{red}");
+        cw.kitty_graphics(KittyImage::parse_apc(command.as_bytes()).unwrap());
+        assert!(cw.graphics.animations.is_empty());
+
+        // A second frame turns the placement into an animation
+        let command = format!("Ga=f,f=32,s=1,v=1,i=1,z=20,q=2;{green}");
+        cw.kitty_graphics(KittyImage::parse_apc(command.as_bytes()).unwrap());
+        assert_eq!(cw.graphics.animations.len(), 1);
+        assert_eq!(cw.graphics.next_frame(), None);
+
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=a,i=1,s=3,q=2").unwrap());
+        let deadline = cw.graphics.next_frame().unwrap();
+        cw.graphics.frames.clear();
+        cw.graphics.advance_animations(deadline);
+        assert_eq!(cw.graphics.frames.len(), 1);
+        assert_eq!(cw.graphics.frames[0].pixels, vec![0, 255, 0, 255]);
+
+        // Deleting the image stops the animation
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=d,d=I,i=1").unwrap());
+        cw.graphics_take_queues();
+        assert!(cw.graphics.animations.is_empty());
+    }
+
+    #[test]
+    fn kitty_graphics_unicode_placeholders() {
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.graphics.cell_width = 10.;
+        cw.graphics.cell_height = 10.;
+
+        let pixels = general_purpose::STANDARD.encode([255; 20 * 20 * 4]);
+        let command = format!("Ga=t,f=32,s=20,v=20,i=7;{pixels}");
+        cw.kitty_graphics(KittyImage::parse_apc(command.as_bytes()).unwrap());
+
+        // Placeholders written before the virtual placement
+        cw.terminal_attribute(Attr::Foreground(AnsiColor::Indexed(7)));
+        for c in [PLACEHOLDER, '\u{305}', '\u{305}', PLACEHOLDER] {
+            cw.input(c);
+        }
+        assert!(cw.grid[Line(0)][Column(0)].graphics().is_none());
+
+        cw.goto(Line(3), Column(3));
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=p,U=1,i=7,c=2,r=2").unwrap());
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(3), Column(3)));
+        assert!(cw.grid[Line(3)][Column(3)].graphics().is_none());
+
+        // The second cell continues the row of the first one
+        let graphic = &cw.grid[Line(0)][Column(1)].graphics().unwrap()[0];
+        assert_eq!((graphic.offset_x, graphic.offset_y), (10, 0));
+
+        // Second row, with its row and column diacritics
+        cw.goto(Line(1), Column(0));
+        for c in [PLACEHOLDER, '\u{30D}', '\u{305}', PLACEHOLDER] {
+            cw.input(c);
+        }
+        let graphic = &cw.grid[Line(1)][Column(1)].graphics().unwrap()[0];
+        assert_eq!((graphic.offset_x, graphic.offset_y), (10, 10));
+        assert_eq!(graphic.texture.width, 20);
+
+        // Cells outside the placement show nothing
+        cw.input(PLACEHOLDER);
+        assert!(cw.grid[Line(1)][Column(2)].graphics().is_none());
+
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=d,d=i,i=7").unwrap());
+        assert!(cw.grid[Line(0)][Column(0)].graphics().is_none());
+        cw.goto(Line(2), Column(0));
+        cw.input(PLACEHOLDER);
+        assert!(cw.grid[Line(2)][Column(0)].graphics().is_none());
+    }
+
+    #[test]
+    fn kitty_graphics_placeholders_on_the_screen() {
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.update_history(100);
+        cw.graphics.cell_width = 10.;
+        cw.graphics.cell_height = 10.;
+
+        let pixels = general_purpose::STANDARD.encode([255; 20 * 20 * 4]);
+        let command = format!("Ga=t,f=32,s=20,v=20,i=7;{pixels}");
+        cw.kitty_graphics(KittyImage::parse_apc(command.as_bytes()).unwrap());
+
+        // A row scrolled into the history and a row on the screen
+        cw.terminal_attribute(Attr::Foreground(AnsiColor::Indexed(7)));
+        for c in [PLACEHOLDER, '\u{305}', '\u{305}', PLACEHOLDER] {
+            cw.input(c);
+        }
+        for _ in 0..5 {
+            cw.linefeed();
+        }
+        cw.carriage_return();
+        cw.input(PLACEHOLDER);
+        cw.input(PLACEHOLDER);
+
+        cw.kitty_graphics(KittyImage::parse_apc(b"Ga=p,U=1,i=7,c=2,r=2").unwrap());
+        assert!(cw.grid[Line(-1)][Column(1)].graphics().is_none());
+        let graphic = &cw.grid[Line(4)][Column(1)].graphics().unwrap()[0];
+        assert_eq!((graphic.offset_x, graphic.offset_y), (10, 0));
+
+        // Diacritics written into the first cell change the cells after it
+        cw.goto(Line(4), Column(1));
+        cw.input('\u{30D}');
+        let graphic = &cw.grid[Line(4)][Column(1)].graphics().unwrap()[0];
+        assert_eq!((graphic.offset_x, graphic.offset_y), (10, 10));
+    }
+
+    #[test]
+    fn graphics_memory_limit() {
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.update_history(100);
+        cw.graphics.cell_width = 10.;
+        cw.graphics.cell_height = 10.;
+        // Two stored images and one graphic, each 20x20 RGBA is 1600 bytes
+        cw.set_graphics_max_memory(3500);
+
+        let pixels = general_purpose::STANDARD.encode([255; 20 * 20 * 4]);
+        let command = format!("Ga=T,f=32,s=20,v=20,i=1;{pixels}");
+        cw.kitty_graphics(KittyImage::parse_apc(command.as_bytes()).unwrap());
+        assert_eq!(cw.graphics.used_memory(), 3200);
+        assert_eq!(cw.graphics.evicted, 0);
+
+        // Scroll the first graphic off the screen
+        cw.goto(Line(4), Column(0));
+        for _ in 0..5 {
+            cw.linefeed();
+        }
+
+        // The graphic in the scrollback goes first, then the oldest image
+        let command = format!("Ga=T,f=32,s=20,v=20,i=2;{pixels}");
+        cw.kitty_graphics(KittyImage::parse_apc(command.as_bytes()).unwrap());
+        let stats = cw.graphics.memory_stats();
+        assert_eq!(stats.used, 3200);
+        assert_eq!(stats.graphics, 1);
+        assert_eq!(stats.images, 1);
+        assert_eq!(stats.evicted, 2);
+        assert!(cw.grid[Line(4)][Column(0)].graphics().is_some());
+        assert!(cw.grid[Line(-5)][Column(0)].graphics().is_none());
+
+        // Lowering the limit evicts the graphics on the screen too, except
+        // the newest one
+        cw.set_graphics_max_memory(0);
+        assert_eq!(cw.graphics.used_memory(), 1600);
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert_eq!(version_number("0.0.1-nightly"), 1);
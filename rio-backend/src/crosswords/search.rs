@@ -10,6 +10,7 @@ use std::ops::RangeInclusive;
 use regex_automata::hybrid::dfa::{Builder, Cache, Config, DFA};
 pub use regex_automata::hybrid::BuildError;
 use regex_automata::nfa::thompson::Config as ThompsonConfig;
+use regex_automata::util::start::Config as StartConfig;
 use regex_automata::util::syntax::Config as SyntaxConfig;
 use regex_automata::{Anchored, Input, MatchKind};
 use tracing::{debug, warn};
@@ -17,13 +18,32 @@ use tracing::{debug, warn};
 use crate::crosswords::grid::{BidirectionalIterator, Dimensions, GridIterator, Indexed};
 use crate::crosswords::square::{Flags, Square};
 use crate::crosswords::Crosswords;
-use crate::crosswords::{Boundary, Column, Direction, Pos, Side};
+use crate::crosswords::{Boundary, Column, Direction, Line, Pos, Side};
 
 /// Used to match equal brackets, when performing a bracket-pair selection.
 const BRACKET_PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
 
 pub type Match = RangeInclusive<Pos>;
 
+/// How the search text is matched against the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Ignore the case unless the search has an uppercase letter, when
+    /// disabled the case always has to match.
+    pub smart_case: bool,
+    /// Only match whole words.
+    pub whole_word: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            smart_case: true,
+            whole_word: false,
+        }
+    }
+}
+
 /// Crosswordsinal regex search state.
 #[derive(Clone, Debug)]
 pub struct RegexSearch {
@@ -36,12 +56,30 @@ pub struct RegexSearch {
 impl RegexSearch {
     /// Build the forward and backward search DFAs.
     pub fn new(search: &str) -> Result<RegexSearch, Box<BuildError>> {
+        Self::with_options(search, SearchOptions::default())
+    }
+
+    /// Build the search DFAs matching the search as described by `options`.
+    pub fn with_options(
+        search: &str,
+        options: SearchOptions,
+    ) -> Result<RegexSearch, Box<BuildError>> {
+        // Word boundaries are ASCII only, the lazy DFAs can't handle Unicode ones
+        let whole_word_search;
+        let search = if options.whole_word {
+            whole_word_search = format!(r"(?-u:\b)(?:{search})(?-u:\b)");
+            &whole_word_search
+        } else {
+            search
+        };
+
         // Setup configs for both DFA directions.
         //
         // Bounds are based on Regex's meta engine:
         // https://github.com/rust-lang/regex/blob/061ee815ef2c44101dba7b0b124600fcb03c1912/regex-automata/src/meta/wrappers.rs#L581-L599
         let has_uppercase = search.chars().any(|c| c.is_uppercase());
-        let syntax_config = SyntaxConfig::new().case_insensitive(!has_uppercase);
+        let syntax_config =
+            SyntaxConfig::new().case_insensitive(options.smart_case && !has_uppercase);
         let config = Config::new()
             .minimum_cache_clear_count(Some(3))
             .minimum_bytes_per_state(Some(10));
@@ -160,6 +198,30 @@ impl<T: event::EventListener> Crosswords<T> {
         }
     }
 
+    /// Count all regex matches in the grid, including the scrollback.
+    ///
+    /// Returns the total number of matches and the one-based index of `focused`
+    /// among them, if it is one of the matches.
+    pub fn search_match_count(
+        &self,
+        regex: &mut RegexSearch,
+        focused: Option<&Match>,
+    ) -> (usize, Option<usize>) {
+        let start = Pos::new(self.grid.topmost_line(), Column(0));
+        let end = Pos::new(self.grid.bottommost_line(), self.grid.last_column());
+
+        let mut index = None;
+        let mut total = 0;
+        for regex_match in RegexIter::new(start, end, Direction::Right, self, regex) {
+            total += 1;
+            if focused == Some(&regex_match) {
+                index = Some(total);
+            }
+        }
+
+        (total, index)
+    }
+
     /// Find the next match to the right of the origin.
     fn next_match_right(
         &self,
@@ -320,10 +382,11 @@ impl<T: event::EventListener> Crosswords<T> {
             Anchored::No
         };
         let input = Input::new(&[]).anchored(regex_anchored);
-        let mut state = regex
-            .dfa
-            .start_state_forward(&mut regex.cache, &input)
-            .unwrap();
+        // Assertions like word boundaries need to see the text before the start
+        let start_config = StartConfig::new()
+            .anchored(regex_anchored)
+            .look_behind(self.search_look_behind(start, regex.direction));
+        let mut state = regex.dfa.start_state(&mut regex.cache, &start_config)?;
 
         let mut iter = self.grid.iter_from(start);
         let mut regex_match = None;
@@ -468,6 +531,42 @@ impl<T: event::EventListener> Crosswords<T> {
         Ok(regex_match)
     }
 
+    /// Last byte before `point` in the search direction, `None` at the
+    /// beginning of a line.
+    fn search_look_behind(&self, point: Pos, direction: Direction) -> Option<u8> {
+        let last_column = self.grid.last_column();
+        let is_wrapped =
+            |line: Line| self.grid[line][last_column].flags.contains(Flags::WRAPLINE);
+        let point = match direction {
+            Direction::Right if point.col > Column(0) => {
+                Pos::new(point.row, point.col - 1)
+            }
+            Direction::Right => {
+                let line = point.row - 1;
+                if line < self.grid.topmost_line() || !is_wrapped(line) {
+                    return None;
+                }
+                Pos::new(line, last_column)
+            }
+            Direction::Left if point.col < last_column => {
+                Pos::new(point.row, point.col + 1)
+            }
+            Direction::Left => {
+                if point.row >= self.grid.bottommost_line() || !is_wrapped(point.row) {
+                    return None;
+                }
+                Pos::new(point.row + 1, Column(0))
+            }
+        };
+
+        let mut buf = [0; 4];
+        let bytes = self.grid[point].c.encode_utf8(&mut buf).as_bytes();
+        match direction {
+            Direction::Right => bytes.last().copied(),
+            Direction::Left => bytes.first().copied(),
+        }
+    }
+
     /// Advance a grid iterator over fullwidth characters.
     fn skip_fullwidth<'a>(
         &self,
@@ -834,6 +933,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_match_count() {
+        #[rustfmt::skip]
+        let term = mock_term("\
+            Rio Terminal\r\n\
+            terminal rio\r\n\
+            Rio\
+        ");
+
+        let mut regex = RegexSearch::new("Rio").unwrap();
+        let second = Pos::new(Line(2), Column(0))..=Pos::new(Line(2), Column(2));
+        assert_eq!(term.search_match_count(&mut regex, None), (2, None));
+        assert_eq!(
+            term.search_match_count(&mut regex, Some(&second)),
+            (2, Some(2))
+        );
+
+        let mut regex = RegexSearch::new("(?i)rio").unwrap();
+        assert_eq!(term.search_match_count(&mut regex, None), (3, None));
+
+        let mut regex = RegexSearch::new("alacritty").unwrap();
+        assert_eq!(term.search_match_count(&mut regex, None), (0, None));
+    }
+
+    #[test]
+    fn search_options() {
+        #[rustfmt::skip]
+        let term = mock_term("\
+            rio Rio riot\
+        ");
+
+        let options = SearchOptions {
+            smart_case: false,
+            whole_word: false,
+        };
+        let mut regex = RegexSearch::with_options("rio", options).unwrap();
+        assert_eq!(term.search_match_count(&mut regex, None), (2, None));
+
+        let options = SearchOptions {
+            smart_case: true,
+            whole_word: true,
+        };
+        let mut regex = RegexSearch::with_options("rio", options).unwrap();
+        assert_eq!(term.search_match_count(&mut regex, None), (2, None));
+
+        // Starting inside a word doesn't turn its rest into a whole word.
+        let mut regex = RegexSearch::with_options("iot", options).unwrap();
+        let start = Pos::new(Line(0), Column(9));
+        let end = Pos::new(Line(0), Column(11));
+        assert_eq!(term.regex_search_right(&mut regex, start, end), None);
+        let mut regex = RegexSearch::with_options("rio", options).unwrap();
+        let start = Pos::new(Line(0), Column(10));
+        let end = Pos::new(Line(0), Column(0));
+        let match_start = Pos::new(Line(0), Column(4));
+        let match_end = Pos::new(Line(0), Column(6));
+        assert_eq!(
+            term.regex_search_left(&mut regex, start, end),
+            Some(match_start..=match_end)
+        );
+    }
+
     #[test]
     fn regex_left() {
         #[rustfmt::skip]
@@ -0,0 +1,274 @@
+//! Structured copy of the grid content, the lines are split in runs of
+//! cells sharing the same style. The result only depends on the grid, so
+//! it's the base of the exports and can be compared in tests.
+
+use crate::config::colors::{AnsiColor, NamedColor};
+use crate::crosswords::grid::{Dimensions, GridSquare};
+use crate::crosswords::pos::{Column, Line};
+use crate::crosswords::square::{Flags, Hyperlink, Square};
+use crate::crosswords::Crosswords;
+use crate::event::EventListener;
+use crate::selection::SelectionRange;
+use std::ops::Range;
+
+/// Flags that change how a cell is drawn.
+const STYLE_FLAGS: Flags = Flags::INVERSE
+    .union(Flags::BOLD_ITALIC)
+    .union(Flags::ALL_UNDERLINES)
+    .union(Flags::DIM)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+/// Attributes shared by the cells of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotStyle {
+    pub fg: AnsiColor,
+    pub bg: AnsiColor,
+    /// Only the flags that change how a cell is drawn.
+    pub flags: Flags,
+    pub underline_color: Option<AnsiColor>,
+    pub hyperlink: Option<Hyperlink>,
+}
+
+impl Default for SnapshotStyle {
+    fn default() -> Self {
+        SnapshotStyle {
+            fg: AnsiColor::Named(NamedColor::Foreground),
+            bg: AnsiColor::Named(NamedColor::Background),
+            flags: Flags::empty(),
+            underline_color: None,
+            hyperlink: None,
+        }
+    }
+}
+
+impl From<&Square> for SnapshotStyle {
+    fn from(square: &Square) -> Self {
+        SnapshotStyle {
+            fg: square.fg,
+            bg: square.bg,
+            flags: square.flags & STYLE_FLAGS,
+            underline_color: square.underline_color(),
+            hyperlink: square.hyperlink(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotRun {
+    pub style: SnapshotStyle,
+    pub text: String,
+}
+
+/// Cells of a line up to the last one with content, wide char spacers
+/// are left out and tabs are written as spaces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotLine {
+    pub runs: Vec<SnapshotRun>,
+    /// Whether the line continues on the next one.
+    pub is_wrapped: bool,
+}
+
+impl SnapshotLine {
+    /// Text of the line without the styling.
+    pub fn text(&self) -> String {
+        self.runs.iter().map(|run| run.text.as_str()).collect()
+    }
+}
+
+/// Text of the lines without the styling, the wrapped lines are joined.
+pub fn snapshot_text(lines: &[SnapshotLine]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        for run in &line.runs {
+            text.push_str(&run.text);
+        }
+        if !line.is_wrapped {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+impl<U: EventListener> Crosswords<U> {
+    /// Lines shown in the viewport.
+    pub fn viewport_lines(&self) -> Range<Line> {
+        let top = Line(-(self.grid.display_offset() as i32));
+        top..top + self.grid.screen_lines()
+    }
+
+    /// Lines of the scrollback history and the screen.
+    pub fn history_lines(&self) -> Range<Line> {
+        self.grid.topmost_line()..self.grid.bottommost_line() + 1
+    }
+
+    /// Content of the lines, the blank lines at the end, e.g. below the
+    /// prompt, are left out.
+    pub fn snapshot(&self, lines: Range<Line>) -> Vec<SnapshotLine> {
+        let columns = self.grid.columns();
+        let mut result: Vec<SnapshotLine> = (lines.start.0..lines.end.0)
+            .map(|line| self.snapshot_line(Line(line), 0..columns))
+            .collect();
+
+        while result
+            .last()
+            .is_some_and(|line| line.runs.is_empty() && !line.is_wrapped)
+        {
+            result.pop();
+        }
+
+        result
+    }
+
+    /// Content of the selection, the lines are cut to the selected columns.
+    pub fn selection_snapshot(&self) -> Option<Vec<SnapshotLine>> {
+        let SelectionRange {
+            start,
+            end,
+            is_block,
+        } = self.selection.as_ref().and_then(|s| s.to_range(self))?;
+
+        let columns = self.grid.columns();
+        let lines = (start.row.0..=end.row.0)
+            .map(Line)
+            .map(|line| {
+                let mut first = if is_block || line == start.row {
+                    start.col.0
+                } else {
+                    0
+                };
+                let last = if is_block || line == end.row {
+                    end.col.0
+                } else {
+                    columns - 1
+                };
+
+                // Include the wide char when its spacer is selected
+                if first > 0
+                    && self.grid[line][Column(first)]
+                        .flags
+                        .contains(Flags::WIDE_CHAR_SPACER)
+                {
+                    first -= 1;
+                }
+
+                let mut snapshot = self.snapshot_line(line, first..last + 1);
+                snapshot.is_wrapped &= !is_block && last == columns - 1;
+                snapshot
+            })
+            .collect();
+
+        Some(lines)
+    }
+
+    /// Runs of the cells in the columns, up to the last one with content.
+    fn snapshot_line(&self, line: Line, columns: Range<usize>) -> SnapshotLine {
+        let row = &self.grid[line];
+        let length = columns
+            .clone()
+            .rev()
+            .find(|column| !row[Column(*column)].is_empty())
+            .map_or(columns.start, |column| column + 1);
+
+        let mut runs: Vec<SnapshotRun> = Vec::new();
+        for square in row[..][columns.start..length].iter() {
+            if square
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+
+            let style = SnapshotStyle::from(square);
+            let text = match runs.last_mut() {
+                Some(run) if run.style == style => &mut run.text,
+                _ => {
+                    runs.push(SnapshotRun {
+                        style,
+                        text: String::new(),
+                    });
+                    &mut runs.last_mut().unwrap().text
+                }
+            };
+
+            text.push(if square.c == '\t' { ' ' } else { square.c });
+            if let Some(zerowidth) = square.zerowidth() {
+                text.extend(zerowidth);
+            }
+        }
+
+        SnapshotLine {
+            runs,
+            is_wrapped: row[Column(row.len() - 1)].flags.contains(Flags::WRAPLINE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ansi::CursorShape;
+    use crate::crosswords::attr::Attr;
+    use crate::crosswords::CrosswordsSize;
+    use crate::event::{VoidListener, WindowId};
+    use crate::performer::handler::Handler;
+
+    #[test]
+    fn test_snapshot() {
+        let size = CrosswordsSize::new(4, 4);
+        let mut cw = Crosswords::new(
+            size,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+            0,
+        );
+
+        cw.input('a');
+        cw.terminal_attribute(Attr::Italic);
+        cw.input('界');
+        cw.terminal_attribute(Attr::Reset);
+        cw.input('b');
+        cw.input('c');
+        cw.carriage_return();
+        cw.linefeed();
+
+        let italic = SnapshotStyle {
+            flags: Flags::ITALIC,
+            ..SnapshotStyle::default()
+        };
+        let lines = cw.snapshot(cw.viewport_lines());
+        assert_eq!(
+            lines,
+            vec![
+                SnapshotLine {
+                    runs: vec![
+                        SnapshotRun {
+                            style: SnapshotStyle::default(),
+                            text: String::from("a"),
+                        },
+                        SnapshotRun {
+                            style: italic,
+                            text: String::from("界"),
+                        },
+                        SnapshotRun {
+                            style: SnapshotStyle::default(),
+                            text: String::from("b"),
+                        },
+                    ],
+                    is_wrapped: true,
+                },
+                SnapshotLine {
+                    runs: vec![SnapshotRun {
+                        style: SnapshotStyle::default(),
+                        text: String::from("c"),
+                    }],
+                    is_wrapped: false,
+                },
+            ]
+        );
+        assert_eq!(lines[0].text(), "a界b");
+        assert_eq!(snapshot_text(&lines), "a界bc\n");
+    }
+}
@@ -51,6 +51,16 @@ impl Hyperlink {
         Self { inner }
     }
 
+    /// Hyperlink opened by running a program instead of the default opener,
+    /// the command is the program followed by its arguments.
+    pub fn with_command<T: ToString>(uri: T, command: Vec<String>) -> Self {
+        let mut inner = HyperlinkInner::new(None, uri);
+        inner.command = Some(command);
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
     pub fn id(&self) -> &str {
         &self.inner.id
     }
@@ -58,6 +68,10 @@ impl Hyperlink {
     pub fn uri(&self) -> &str {
         &self.inner.uri
     }
+
+    pub fn command(&self) -> Option<&[String]> {
+        self.inner.command.as_deref()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -67,6 +81,9 @@ struct HyperlinkInner {
 
     /// Resource identifier of the hyperlink.
     uri: String,
+
+    /// Program and arguments opening the hyperlink.
+    command: Option<Vec<String>>,
 }
 
 impl HyperlinkInner {
@@ -85,6 +102,7 @@ impl HyperlinkInner {
         Self {
             id,
             uri: uri.to_string(),
+            command: None,
         }
     }
 }
@@ -57,6 +57,11 @@ pub enum RioErrorType {
     // configuration invalid theme
     InvalidConfigurationTheme(String),
 
+    // started with --safe-mode, the configuration is not loaded
+    SafeMode,
+    // the last session panicked with the message
+    Crashed(String),
+
     // reports that are ignored by RioErrorType
     IgnoredReport,
 }
@@ -98,6 +103,12 @@ impl std::fmt::Display for RioErrorType {
             RioErrorType::InvalidConfigurationTheme(message) => {
                 write!(f, "Found an issue in the configured theme:\n\n{message}")
             }
+            RioErrorType::SafeMode => {
+                write!(f, "Rio is running in safe mode\n\nThe configuration file, themes and shaders are not loaded, the default configuration is used instead.\n\nStart Rio without --safe-mode once the configuration is fixed")
+            }
+            RioErrorType::Crashed(message) => {
+                write!(f, "Rio crashed in the last session:\n\n{message}\n\nIf the configuration or a shader causes it, start Rio with --safe-mode to use the default configuration")
+            }
         }
     }
 }
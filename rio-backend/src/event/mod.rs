@@ -4,7 +4,7 @@ use crate::clipboard::ClipboardType;
 use crate::config::colors::ColorRgb;
 use crate::crosswords::grid::Scroll;
 use crate::crosswords::pos::{Direction, Pos};
-use crate::crosswords::search::{Match, RegexSearch};
+use crate::crosswords::search::{Match, RegexSearch, SearchOptions};
 use crate::error::RioError;
 use rio_window::event::Event as RioWindowEvent;
 use std::borrow::Cow;
@@ -30,10 +30,16 @@ pub enum Msg {
     /// Data that should be written to the PTY.
     Input(Cow<'static, [u8]>),
 
-    #[allow(dead_code)]
+    /// Stops the PTY reader and drops the PTY.
     Shutdown,
 
     Resize(WinsizeBuilder),
+
+    /// Decode invalid UTF-8 as Latin-1.
+    Latin1Fallback(bool),
+
+    /// The terminal was moved to another window, with its new route.
+    Migrate(WindowId, usize),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -58,12 +64,22 @@ pub enum RioEvent {
     UpdateFontSize(u8),
     Scroll(Scroll),
     ToggleFullScreen,
+    /// Opacity of the window background changed by a key binding.
+    UpdateOpacity(f32),
     Minimize(bool),
     Hide,
     HideOtherApplications,
+    /// Play the trackpad haptic feedback, only on macOS.
+    HapticFeedback,
     UpdateConfig,
     CreateWindow,
     CloseWindow,
+    /// Focus the next Rio window, `false` focuses the previous one.
+    SelectWindow(bool),
+    /// Move the current tab, with its running terminals, to a new window.
+    MoveTabToNewWindow,
+    /// Move the tabs of every other window to this one.
+    MergeWindows,
     CreateNativeTab(Option<String>),
     CreateConfigEditor,
     SelectNativeTabByIndex(usize),
@@ -85,40 +101,53 @@ pub enum RioEvent {
     /// Reset to the default window title.
     ResetTitle,
 
+    /// User variable set through OSC 1337 `SetUserVar` (route id, name, value).
+    UserVarChanged(usize, String, String),
+
+    /// Profile matched by the profile rules for a route, `None` reverts to
+    /// the configuration.
+    UpdateProfile(usize, Option<String>),
+
     /// Request to store a text string in the clipboard.
     ClipboardStore(ClipboardType, String),
 
-    /// Request to write the contents of the clipboard to the PTY.
+    /// Request to write the contents of the clipboard to the PTY of the route.
     ///
     /// The attached function is a formatter which will correctly transform the clipboard content
     /// into the expected escape sequence format.
     ClipboardLoad(
+        usize,
         ClipboardType,
         Arc<dyn Fn(&str) -> String + Sync + Send + 'static>,
     ),
 
-    /// Request to write the RGB value of a color to the PTY.
+    /// Request to write the RGB value of a color to the PTY of the route,
+    /// with the index of the color.
     ///
     /// The attached function is a formatter which will correctly transform the RGB color into the
     /// expected escape sequence format.
     ColorRequest(
+        usize,
         usize,
         Arc<dyn Fn(ColorRgb) -> String + Sync + Send + 'static>,
     ),
 
-    /// Write some text to the PTY.
-    PtyWrite(String),
+    /// Write some text to the PTY of the route.
+    PtyWrite(usize, String),
 
-    /// Request to write the text area size.
-    TextAreaSizeRequest(Arc<dyn Fn(WinsizeBuilder) -> String + Sync + Send + 'static>),
+    /// Request to write the text area size to the PTY of the route.
+    TextAreaSizeRequest(
+        usize,
+        Arc<dyn Fn(WinsizeBuilder) -> String + Sync + Send + 'static>,
+    ),
 
     /// Cursor blinking state has changed.
     CursorBlinkingChange,
 
     CursorBlinkingChangeOnRoute(usize),
 
-    /// Terminal bell ring.
-    Bell,
+    /// Terminal bell ring, with the route of the terminal.
+    Bell(usize),
 
     /// Shutdown request.
     Exit,
@@ -131,6 +160,31 @@ pub enum RioEvent {
 
     BlinkCursor(u64, usize),
 
+    /// Search again without the limit after the given milliseconds, the
+    /// search while typing ran into it.
+    PrepareDelayedSearch(u64),
+
+    /// Run the delayed search.
+    DelayedSearch,
+
+    /// Check the power source again to update the battery saver.
+    UpdateBatterySaver,
+
+    /// Check the time based adaptive theme schedule.
+    UpdateThemeSchedule,
+
+    /// Open the clipboard history to pick a text to paste.
+    ClipboardHistory,
+
+    /// Open the command palette to search an action to run.
+    CommandPalette,
+
+    /// Open the settings screen.
+    Settings,
+
+    /// Ask to confirm the paste waiting in the screen.
+    ConfirmPaste,
+
     // No operation
     Noop,
 }
@@ -141,10 +195,18 @@ impl Debug for RioEvent {
             RioEvent::ClipboardStore(ty, text) => {
                 write!(f, "ClipboardStore({ty:?}, {text})")
             }
-            RioEvent::ClipboardLoad(ty, _) => write!(f, "ClipboardLoad({ty:?})"),
-            RioEvent::TextAreaSizeRequest(_) => write!(f, "TextAreaSizeRequest"),
-            RioEvent::ColorRequest(index, _) => write!(f, "ColorRequest({index})"),
-            RioEvent::PtyWrite(text) => write!(f, "PtyWrite({text})"),
+            RioEvent::ClipboardLoad(route_id, ty, _) => {
+                write!(f, "ClipboardLoad({route_id}, {ty:?})")
+            }
+            RioEvent::TextAreaSizeRequest(route_id, _) => {
+                write!(f, "TextAreaSizeRequest({route_id})")
+            }
+            RioEvent::ColorRequest(route_id, index, _) => {
+                write!(f, "ColorRequest({route_id}, {index})")
+            }
+            RioEvent::PtyWrite(route_id, text) => {
+                write!(f, "PtyWrite({route_id}, {text})")
+            }
             RioEvent::Title(title) => write!(f, "Title({title})"),
             RioEvent::TitleWithSubtitle(title, subtitle) => {
                 write!(f, "TitleWithSubtitle({title}, {subtitle})")
@@ -152,12 +214,19 @@ impl Debug for RioEvent {
             RioEvent::Minimize(cond) => write!(f, "Minimize({cond})"),
             RioEvent::Hide => write!(f, "Hide)"),
             RioEvent::HideOtherApplications => write!(f, "HideOtherApplications)"),
+            RioEvent::HapticFeedback => write!(f, "HapticFeedback"),
             RioEvent::CursorBlinkingChange => write!(f, "CursorBlinkingChange"),
             RioEvent::CursorBlinkingChangeOnRoute(route_id) => {
                 write!(f, "CursorBlinkingChangeOnRoute {route_id}")
             }
             RioEvent::MouseCursorDirty => write!(f, "MouseCursorDirty"),
             RioEvent::ResetTitle => write!(f, "ResetTitle"),
+            RioEvent::UserVarChanged(route_id, name, value) => {
+                write!(f, "UserVarChanged({route_id}, {name}, {value})")
+            }
+            RioEvent::UpdateProfile(route_id, profile) => {
+                write!(f, "UpdateProfile({route_id}, {profile:?})")
+            }
             RioEvent::PrepareUpdateConfig => write!(f, "PrepareUpdateConfig"),
             RioEvent::PrepareRender(millis) => write!(f, "PrepareRender({millis})"),
             RioEvent::PrepareRenderOnRoute(millis, route) => {
@@ -166,12 +235,15 @@ impl Debug for RioEvent {
             RioEvent::Render => write!(f, "Render"),
             RioEvent::RenderRoute(route) => write!(f, "Render route {route}"),
             RioEvent::Scroll(scroll) => write!(f, "Scroll {scroll:?}"),
-            RioEvent::Bell => write!(f, "Bell"),
+            RioEvent::Bell(route_id) => write!(f, "Bell({route_id})"),
             RioEvent::Exit => write!(f, "Exit"),
             RioEvent::Quit => write!(f, "Quit"),
             RioEvent::CloseTerminal(route) => write!(f, "CloseTerminal {route}"),
             RioEvent::CreateWindow => write!(f, "CreateWindow"),
             RioEvent::CloseWindow => write!(f, "CloseWindow"),
+            RioEvent::SelectWindow(next) => write!(f, "SelectWindow({next})"),
+            RioEvent::MoveTabToNewWindow => write!(f, "MoveTabToNewWindow"),
+            RioEvent::MergeWindows => write!(f, "MergeWindows"),
             RioEvent::CreateNativeTab(_) => write!(f, "CreateNativeTab"),
             RioEvent::SelectNativeTabByIndex(tab_index) => {
                 write!(f, "SelectNativeTabByIndex({tab_index})")
@@ -185,9 +257,20 @@ impl Debug for RioEvent {
                 write!(f, "ReportToAssistant({})", error_report.report)
             }
             RioEvent::ToggleFullScreen => write!(f, "FullScreen"),
+            RioEvent::UpdateOpacity(opacity) => write!(f, "UpdateOpacity({opacity})"),
             RioEvent::BlinkCursor(timeout, route_id) => {
                 write!(f, "BlinkCursor {timeout} {route_id}")
             }
+            RioEvent::PrepareDelayedSearch(millis) => {
+                write!(f, "PrepareDelayedSearch({millis})")
+            }
+            RioEvent::DelayedSearch => write!(f, "DelayedSearch"),
+            RioEvent::UpdateBatterySaver => write!(f, "UpdateBatterySaver"),
+            RioEvent::UpdateThemeSchedule => write!(f, "UpdateThemeSchedule"),
+            RioEvent::ClipboardHistory => write!(f, "ClipboardHistory"),
+            RioEvent::CommandPalette => write!(f, "CommandPalette"),
+            RioEvent::Settings => write!(f, "Settings"),
+            RioEvent::ConfirmPaste => write!(f, "ConfirmPaste"),
             RioEvent::Noop => write!(f, "Noop"),
             RioEvent::Copy(_) => write!(f, "Copy"),
             RioEvent::Paste => write!(f, "Paste"),
@@ -299,6 +382,13 @@ pub struct SearchState {
 
     /// Compiled search automatons.
     pub dfas: Option<RegexSearch>,
+
+    /// How the search is matched, kept between searches.
+    pub options: SearchOptions,
+
+    /// Whether the search while typing ran into its limit and a search
+    /// without it is scheduled.
+    pub delayed_search: bool,
 }
 
 impl SearchState {
@@ -339,6 +429,33 @@ impl SearchState {
     }
 }
 
+/// Vi mode search for a character of the line.
+#[derive(Debug, Clone, Copy)]
+pub struct InlineSearchState {
+    /// Whether the next typed character is the one to search for.
+    pub char_pending: bool,
+
+    /// Character searched for.
+    pub character: Option<char>,
+
+    /// Direction of the search.
+    pub direction: Direction,
+
+    /// Stop one cell before the character.
+    pub stop_short: bool,
+}
+
+impl Default for InlineSearchState {
+    fn default() -> Self {
+        Self {
+            char_pending: false,
+            character: None,
+            direction: Direction::Right,
+            stop_short: false,
+        }
+    }
+}
+
 impl Default for SearchState {
     fn default() -> Self {
         Self {
@@ -349,6 +466,8 @@ impl Default for SearchState {
             history: Default::default(),
             origin: Default::default(),
             dfas: Default::default(),
+            options: Default::default(),
+            delayed_search: false,
         }
     }
 }
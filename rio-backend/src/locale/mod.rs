@@ -0,0 +1,193 @@
+//! Translations of the strings shown by Rio itself, like the welcome and
+//! confirmation screens. Each language is a flat TOML table of keys in
+//! `translations`, English is built in and used for missing keys.
+
+use std::collections::HashMap;
+
+const FALLBACK_LANGUAGE: &str = "en";
+
+/// Available translations by language tag, lowercase with `-` separators.
+const TRANSLATIONS: &[(&str, &str)] = &[
+    ("en", include_str!("translations/en.toml")),
+    ("pt-br", include_str!("translations/pt-br.toml")),
+];
+
+/// Primary languages written from right to left.
+const RTL_LANGUAGES: &[&str] = &[
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ps", "sd", "ug", "ur", "yi",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Locale {
+    language: String,
+    is_rtl: bool,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Locale {
+    /// Loads the translation for the language, detected from the system
+    /// locale environment variables when `None`.
+    pub fn new(language: Option<&str>) -> Locale {
+        let requested = language.map(str::to_owned).or_else(system_language);
+        let found = requested.as_deref().and_then(find_language);
+        if let (Some(requested), None) = (&requested, found) {
+            tracing::info!("no translation for {requested}, using {FALLBACK_LANGUAGE}");
+        }
+        let language = found.unwrap_or(FALLBACK_LANGUAGE);
+        // The direction follows the requested language, even when the
+        // strings fall back to English
+        let is_rtl = requested.as_deref().is_some_and(is_rtl_language);
+
+        Locale {
+            language: language.to_owned(),
+            is_rtl,
+            strings: load(language),
+            fallback: load(FALLBACK_LANGUAGE),
+        }
+    }
+
+    #[inline]
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Whether the interface should be laid out from right to left.
+    #[inline]
+    pub fn is_rtl(&self) -> bool {
+        self.is_rtl
+    }
+
+    /// Translated string for the key, or the key itself if no translation
+    /// has it.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// Like [`Locale::get`], replacing `{name}` with the value of each
+    /// argument.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut result = self.get(key).to_owned();
+        for (name, value) in args {
+            result = result.replace(&format!("{{{name}}}"), value);
+        }
+        result
+    }
+}
+
+/// Matches a language like `pt_BR.UTF-8` to a translation, falling back to
+/// the primary language, e.g. `pt`, then to another region of it, so `pt`
+/// and `pt_PT` use `pt-br`.
+fn find_language(language: &str) -> Option<&'static str> {
+    let tag = normalize(language);
+    let primary = tag.split('-').next().unwrap_or_default();
+
+    TRANSLATIONS
+        .iter()
+        .find(|(name, _)| *name == tag)
+        .or_else(|| TRANSLATIONS.iter().find(|(name, _)| *name == primary))
+        .or_else(|| {
+            TRANSLATIONS
+                .iter()
+                .find(|(name, _)| name.split('-').next() == Some(primary))
+        })
+        .map(|(name, _)| *name)
+}
+
+fn is_rtl_language(language: &str) -> bool {
+    let tag = normalize(language);
+    let primary = tag.split('-').next().unwrap_or_default();
+    RTL_LANGUAGES.contains(&primary)
+}
+
+/// Lowercase language tag with `-` separators, without the encoding and
+/// modifier of system locales.
+fn normalize(language: &str) -> String {
+    language
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-")
+        .to_lowercase()
+}
+
+fn system_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+}
+
+fn load(language: &str) -> HashMap<String, String> {
+    let Some((_, content)) = TRANSLATIONS.iter().find(|(name, _)| *name == language)
+    else {
+        return HashMap::new();
+    };
+
+    toml::from_str(content).unwrap_or_else(|err| {
+        tracing::warn!("invalid translation for {language}: {err}");
+        HashMap::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_language() {
+        assert_eq!(find_language("en"), Some("en"));
+        assert_eq!(find_language("pt_BR.UTF-8"), Some("pt-br"));
+        assert_eq!(find_language("pt-BR"), Some("pt-br"));
+        assert_eq!(find_language("pt"), Some("pt-br"));
+        assert_eq!(find_language("pt_PT"), Some("pt-br"));
+        assert_eq!(find_language("pt-PT"), Some("pt-br"));
+        assert_eq!(find_language("en_GB"), Some("en"));
+        assert_eq!(find_language("xx"), None);
+    }
+
+    #[test]
+    fn test_locale_fallback() {
+        let locale = Locale::new(Some("pt-BR"));
+        assert_eq!(locale.language(), "pt-br");
+        assert_eq!(locale.get("quit-heading"), "Sair do Rio?");
+        assert_eq!(locale.get("missing-key"), "missing-key");
+
+        let locale = Locale::new(Some("xx"));
+        assert_eq!(locale.language(), "en");
+        assert_eq!(
+            locale.format("welcome-settings-shortcut", &[("shortcut", "Ctrl")]),
+            "Ctrl (comma)"
+        );
+    }
+
+    #[test]
+    fn test_is_rtl() {
+        assert!(Locale::new(Some("ar")).is_rtl());
+        assert!(Locale::new(Some("he_IL.UTF-8")).is_rtl());
+        assert!(Locale::new(Some("fa-IR")).is_rtl());
+        assert!(!Locale::new(Some("en")).is_rtl());
+        assert!(!Locale::new(Some("pt-BR")).is_rtl());
+        assert!(!Locale::new(Some("arn")).is_rtl());
+    }
+
+    #[test]
+    fn test_translations_are_valid() {
+        let english = load(FALLBACK_LANGUAGE);
+        assert!(!english.is_empty());
+
+        for (language, content) in TRANSLATIONS {
+            let strings: HashMap<String, String> = toml::from_str(content)
+                .unwrap_or_else(|err| panic!("invalid translation {language}: {err}"));
+            for key in strings.keys() {
+                assert!(
+                    english.contains_key(key),
+                    "{language} has {key}, which is not in the English strings"
+                );
+            }
+        }
+    }
+}
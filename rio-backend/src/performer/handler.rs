@@ -1,6 +1,8 @@
+use crate::ansi::graphics::AnimationFrame;
 use crate::ansi::iterm2_image_protocol;
-use crate::ansi::CursorShape;
-use crate::ansi::{sixel, KeyboardModes, KeyboardModesApplyBehavior};
+use crate::ansi::kitty_graphics::{KittyImage, MAX_TRANSMISSION_LENGTH};
+use crate::ansi::{sixel, KeyboardModes, KeyboardModesApplyBehavior, ModifyOtherKeys};
+use crate::ansi::{CursorShape, PromptMark};
 use crate::config::colors::{AnsiColor, ColorRgb, NamedColor};
 use crate::crosswords::pos::{CharsetIndex, Column, Line, StandardCharset};
 use crate::crosswords::square::Hyperlink;
@@ -141,6 +143,15 @@ pub trait Handler {
     /// OSC to set current directory.
     fn set_current_directory(&mut self, _: std::path::PathBuf) {}
 
+    /// OSC to set current hostname, reported along with the directory.
+    fn set_current_hostname(&mut self, _: Option<String>) {}
+
+    /// OSC 133 semantic prompt mark at the cursor position.
+    fn set_prompt_mark(&mut self, _mark: PromptMark) {}
+
+    /// OSC 1337 to set an user variable (iTerm2 `SetUserVar`).
+    fn set_user_var(&mut self, _name: String, _value: String) {}
+
     /// Set the cursor style.
     fn set_cursor_style(&mut self, _style: Option<CursorShape>, _blinking: bool) {}
 
@@ -206,6 +217,12 @@ pub trait Handler {
     /// Substitute char under cursor.
     fn substitute(&mut self) {}
 
+    /// Show a C0 control character instead of performing it, returns
+    /// whether it was shown.
+    fn display_control(&mut self, _byte: u8) -> bool {
+        false
+    }
+
     /// Newline.
     fn newline(&mut self) {}
 
@@ -362,6 +379,12 @@ pub trait Handler {
     /// Insert a new graphic item.
     fn insert_graphic(&mut self, _data: GraphicData, _palette: Option<Vec<ColorRgb>>) {}
 
+    /// Insert a new animated graphic.
+    fn insert_animated_graphic(&mut self, _frames: Vec<AnimationFrame>) {}
+
+    /// Handle a kitty graphics protocol command.
+    fn kitty_graphics(&mut self, _command: KittyImage) {}
+
     /// Set hyperlink.
     fn set_hyperlink(&mut self, _: Option<Hyperlink>) {}
 
@@ -388,6 +411,12 @@ pub trait Handler {
         _behavior: KeyboardModesApplyBehavior,
     ) {
     }
+
+    /// Set xterm's modifyOtherKeys level.
+    fn set_modify_other_keys(&mut self, _mode: ModifyOtherKeys) {}
+
+    /// Report the modifyOtherKeys level.
+    fn report_modify_other_keys(&mut self) {}
 }
 
 pub trait Timeout: Default {
@@ -410,6 +439,9 @@ struct ProcessorState<T: Timeout> {
 
     /// State for synchronized terminal updates.
     sync_state: SyncState<T>,
+
+    /// Bytes of the APC sequence being read.
+    apc_buffer: Vec<u8>,
 }
 
 #[derive(Debug)]
@@ -472,6 +504,12 @@ impl<T: Timeout> Processor<T> {
         Self::default()
     }
 
+    /// Decode the bytes which aren't valid UTF-8 as Latin-1.
+    #[inline]
+    pub fn set_latin1_fallback(&mut self, enabled: bool) {
+        self.parser.set_latin1_fallback(enabled);
+    }
+
     /// Synchronized update timeout.
     pub fn sync_timeout(&self) -> &T {
         &self.state.sync_state.timeout
@@ -631,6 +669,10 @@ impl<U: Handler, T: Timeout> copa::Perform for Performer<'_, U, T> {
     fn execute(&mut self, byte: u8) {
         tracing::trace!("[execute] {byte:04x}");
 
+        if self.handler.display_control(byte) {
+            return;
+        }
+
         match byte {
             C0::HT => self.handler.put_tab(1),
             C0::BS => self.handler.backspace(),
@@ -682,6 +724,34 @@ impl<U: Handler, T: Timeout> copa::Perform for Performer<'_, U, T> {
         }
     }
 
+    fn apc_start(&mut self) {
+        self.state.apc_buffer.clear();
+    }
+
+    #[inline]
+    fn apc_put(&mut self, byte: u8) {
+        // One byte past the limit marks the sequence as too long
+        if self.state.apc_buffer.len() <= MAX_TRANSMISSION_LENGTH {
+            self.state.apc_buffer.push(byte);
+        }
+    }
+
+    fn apc_end(&mut self) {
+        let buffer = mem::take(&mut self.state.apc_buffer);
+        if buffer.len() > MAX_TRANSMISSION_LENGTH {
+            warn!("APC sequence is too long, ignoring it");
+            return;
+        }
+
+        match buffer.first() {
+            Some(b'G') => match KittyImage::parse_apc(&buffer) {
+                Some(command) => self.handler.kitty_graphics(command),
+                None => warn!("invalid kitty graphics command"),
+            },
+            _ => debug!("[unhandled apc] {:?}", String::from_utf8_lossy(&buffer)),
+        }
+    }
+
     fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
         debug!("[osc_dispatch] params={params:?} bell_terminated={bell_terminated}");
 
@@ -763,6 +833,11 @@ impl<U: Handler, T: Timeout> copa::Perform for Performer<'_, U, T> {
                         let path = &path[1..];
 
                         self.handler.set_current_directory(path.into());
+                        self.handler.set_current_hostname(
+                            url.host_str()
+                                .filter(|host| !host.is_empty())
+                                .map(String::from),
+                        );
                     }
                 }
             }
@@ -890,11 +965,35 @@ impl<U: Handler, T: Timeout> copa::Perform for Performer<'_, U, T> {
             // Reset text cursor color.
             b"112" => self.handler.reset_color(NamedColor::Cursor as usize),
 
+            // Semantic prompt marks (FinalTerm), extra options are ignored.
+            b"133" if params.len() > 1 => {
+                let mark = match params[1].first() {
+                    Some(b'A') => PromptMark::PromptStart,
+                    Some(b'B') => PromptMark::CommandStart,
+                    Some(b'C') => PromptMark::OutputStart,
+                    Some(b'D') => PromptMark::CommandEnd,
+                    _ => {
+                        unhandled(params);
+                        return;
+                    }
+                };
+
+                self.handler.set_prompt_mark(mark);
+            }
+
             // OSC 1337 is not necessarily only used by iTerm2 protocol
             // OSC 1337 is equal to xterm OSC 50
             b"1337" => {
-                if let Some(graphic) = iterm2_image_protocol::parse(params) {
-                    self.handler.insert_graphic(graphic, None);
+                if let Some((name, value)) =
+                    iterm2_image_protocol::parse_set_user_var(params)
+                {
+                    self.handler.set_user_var(name, value);
+                } else if let Some(mut frames) = iterm2_image_protocol::parse(params) {
+                    if frames.len() == 1 {
+                        self.handler.insert_graphic(frames.remove(0).graphic, None);
+                    } else {
+                        self.handler.insert_animated_graphic(frames);
+                    }
                 }
             }
 
@@ -1048,7 +1147,41 @@ impl<U: Handler, T: Timeout> copa::Perform for Performer<'_, U, T> {
                     }
                 }
             }
+            ('m', [b'>']) => {
+                // XTMODKEYS, only the modifyOtherKeys resource is supported.
+                // Without parameters all the resources are reset, the
+                // parser reports it as a single zero.
+                let mode = match (
+                    params_iter.next().map(|param| param[0]),
+                    params_iter.next(),
+                ) {
+                    (None | Some(0), None) => ModifyOtherKeys::Reset,
+                    (Some(4), value) => match value.map_or(0, |param| param[0]) {
+                        0 => ModifyOtherKeys::Reset,
+                        1 => ModifyOtherKeys::EnableExceptWellDefined,
+                        2 => ModifyOtherKeys::EnableAll,
+                        _ => {
+                            csi_unhandled!();
+                            return;
+                        }
+                    },
+                    _ => {
+                        csi_unhandled!();
+                        return;
+                    }
+                };
+                handler.set_modify_other_keys(mode);
+            }
+            ('m', [b'?']) => match next_param_or(0) {
+                4 => handler.report_modify_other_keys(),
+                _ => csi_unhandled!(),
+            },
             ('n', []) => handler.device_status(next_param_or(0) as usize),
+            ('n', [b'>']) => match next_param_or(0) {
+                // Disables the resource, which is the same as resetting it.
+                4 => handler.set_modify_other_keys(ModifyOtherKeys::Reset),
+                _ => csi_unhandled!(),
+            },
             ('P', []) => handler.delete_chars(next_param_or(1) as usize),
             ('p', [b'$']) => {
                 let mode = next_param_or(0);
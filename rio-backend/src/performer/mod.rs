@@ -42,6 +42,7 @@ pub struct Machine<T: teletypewriter::EventedPty, U: EventListener> {
     event_proxy: U,
     window_id: WindowId,
     route_id: usize,
+    latin1_fallback: bool,
 }
 
 #[derive(Default)]
@@ -134,9 +135,17 @@ where
             event_proxy,
             window_id,
             route_id,
+            latin1_fallback: false,
         })
     }
 
+    /// Decode the bytes which aren't valid UTF-8 as Latin-1 from the start,
+    /// it's changed later with `Msg::Latin1Fallback`.
+    #[inline]
+    pub fn set_latin1_fallback(&mut self, enabled: bool) {
+        self.latin1_fallback = enabled;
+    }
+
     #[inline]
     fn pty_read(&mut self, state: &mut State, buf: &mut [u8]) -> io::Result<()> {
         let mut unprocessed = 0;
@@ -178,6 +187,8 @@ where
 
             // Parse the incoming bytes.
             state.parser.advance(&mut **terminal, &buf[..unprocessed]);
+            terminal.pty_bytes_read =
+                terminal.pty_bytes_read.wrapping_add(unprocessed as u64);
 
             processed += unprocessed;
             unprocessed = 0;
@@ -188,6 +199,10 @@ where
             }
         }
 
+        if let Some(terminal) = terminal.as_mut() {
+            self.send_bell(terminal);
+        }
+
         // Queue terminal redraw unless all processed bytes were synchronized.
         if state.parser.sync_bytes_count() < processed && processed > 0 {
             self.event_proxy
@@ -197,6 +212,15 @@ where
         Ok(())
     }
 
+    /// Send one `RioEvent::Bell` for all the bells rung since the last one.
+    #[inline]
+    fn send_bell(&self, terminal: &mut Crosswords<U>) {
+        if std::mem::take(&mut terminal.bell_rung) {
+            self.event_proxy
+                .send_event(RioEvent::Bell(self.route_id), self.window_id);
+        }
+    }
+
     fn should_keep_alive(&mut self, state: &mut State) -> bool {
         while let Ok(msg) = self.receiver.try_recv() {
             match msg {
@@ -206,6 +230,13 @@ where
                 Msg::Resize(window_size) => {
                     let _ = self.pty.set_winsize(window_size);
                 }
+                Msg::Latin1Fallback(enabled) => {
+                    state.parser.set_latin1_fallback(enabled);
+                }
+                Msg::Migrate(window_id, route_id) => {
+                    self.window_id = window_id;
+                    self.route_id = route_id;
+                }
                 Msg::Shutdown => return false,
             }
         }
@@ -272,6 +303,7 @@ where
     pub fn spawn(mut self) {
         spawn_named("PTY reader", move || {
             let mut state = State::default();
+            state.parser.set_latin1_fallback(self.latin1_fallback);
             let mut buf = [0u8; READ_BUFFER_SIZE];
 
             let mut tokens = (0..).map(Into::into);
@@ -307,7 +339,10 @@ where
 
                 // Handle synchronized update timeout.
                 if events.is_empty() {
-                    state.parser.stop_sync(&mut *self.terminal.lock());
+                    let mut terminal = self.terminal.lock();
+                    state.parser.stop_sync(&mut *terminal);
+                    self.send_bell(&mut terminal);
+                    drop(terminal);
                     self.event_proxy
                         .send_event(RioEvent::RenderRoute(self.route_id), self.window_id);
 
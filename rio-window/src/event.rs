@@ -142,9 +142,16 @@ pub enum Hook {
     CreateTab,
     Close,
     Copy,
+    CopyLink,
+    CopyFormatted,
     Paste,
+    SelectAll,
+    SelectLastCommandOutput,
     SplitDown,
     SplitRight,
+    NextWindow,
+    PreviousWindow,
+    ToggleSecureKeyboardEntry,
 }
 
 /// Describes the reason the event loop is resuming.
@@ -16,6 +16,7 @@
 
 use std::os::raw::c_void;
 
+use crate::dpi::{Position, Size};
 use crate::event_loop::{ActiveEventLoop, EventLoopBuilder};
 use crate::monitor::MonitorHandle;
 use crate::window::{Window, WindowAttributes};
@@ -100,6 +101,12 @@ pub trait WindowExtMacOS {
     fn set_unified_titlebar(&self, unified_titlebar: bool);
     /// Getter for the [`WindowExtMacOS::set_unified_titlebar`].
     fn unified_titlebar(&self) -> bool;
+
+    /// Sets the area of the text caret reported to the accessibility
+    /// clients, like the screen magnifier following the typing focus.
+    ///
+    /// The position is relative to the top-left corner of the window.
+    fn set_accessibility_caret_area(&self, position: Position, size: Size);
 }
 
 impl WindowExtMacOS for Window {
@@ -195,6 +202,12 @@ impl WindowExtMacOS for Window {
     fn unified_titlebar(&self) -> bool {
         self.window.maybe_wait_on_main(|w| w.unified_titlebar())
     }
+
+    #[inline]
+    fn set_accessibility_caret_area(&self, position: Position, size: Size) {
+        self.window
+            .maybe_queue_on_main(move |w| w.set_accessibility_caret_area(position, size))
+    }
 }
 
 /// Corresponds to `NSApplicationActivationPolicy`.
@@ -447,6 +460,22 @@ pub trait ActiveEventLoopExtMacOS {
     fn set_allows_automatic_window_tabbing(&self, enabled: bool);
     /// Returns whether the system can automatically organize windows into tabs.
     fn allows_automatic_window_tabbing(&self) -> bool;
+    /// Enable or disable secure keyboard entry, which prevents other processes
+    /// from observing keystrokes.
+    ///
+    /// <https://developer.apple.com/library/archive/technotes/tn2150/_index.html>
+    fn set_secure_keyboard_entry(&self, enabled: bool);
+    /// Check or uncheck the secure keyboard entry item of the application
+    /// menu, which can differ from the system state while unfocused.
+    fn set_secure_keyboard_entry_menu_state(&self, checked: bool);
+    /// Returns whether secure keyboard entry was enabled by the application.
+    fn is_secure_keyboard_entry_enabled(&self) -> bool;
+    /// Play the alignment haptic feedback on the trackpad, like when an
+    /// object snaps into place. Nothing happens without a Force Touch
+    /// trackpad or when it's disabled in the system settings.
+    ///
+    /// <https://developer.apple.com/documentation/appkit/nshapticfeedbackmanager>
+    fn perform_haptic_feedback(&self);
 }
 
 impl ActiveEventLoopExtMacOS for ActiveEventLoop {
@@ -465,6 +494,22 @@ impl ActiveEventLoopExtMacOS for ActiveEventLoop {
     fn allows_automatic_window_tabbing(&self) -> bool {
         self.p.allows_automatic_window_tabbing()
     }
+
+    fn set_secure_keyboard_entry(&self, enabled: bool) {
+        self.p.set_secure_keyboard_entry(enabled);
+    }
+
+    fn set_secure_keyboard_entry_menu_state(&self, checked: bool) {
+        self.p.set_secure_keyboard_entry_menu_state(checked);
+    }
+
+    fn is_secure_keyboard_entry_enabled(&self) -> bool {
+        self.p.is_secure_keyboard_entry_enabled()
+    }
+
+    fn perform_haptic_feedback(&self) {
+        self.p.perform_haptic_feedback()
+    }
 }
 
 /// Option as alt behavior.
@@ -6,7 +6,7 @@ use std::borrow::Borrow;
 use std::ffi::c_void;
 use std::path::Path;
 
-use crate::dpi::PhysicalSize;
+use crate::dpi::{PhysicalSize, Position, Size};
 use crate::event::DeviceId;
 use crate::event_loop::EventLoopBuilder;
 use crate::monitor::MonitorHandle;
@@ -311,6 +311,13 @@ pub trait WindowExtWindows {
     /// Not supported on Windows 7 and earlier.
     fn set_cloaked(&self, cloaked: bool);
 
+    /// Sets the area of the text caret reported to the accessibility
+    /// clients, like the magnifier following the typing focus.
+    ///
+    /// The position is relative to the top-left corner of the client area,
+    /// the area is only reported while the window has the keyboard focus.
+    fn set_accessibility_caret_area(&self, position: Position, size: Size);
+
     /// Get the raw window handle for this [`Window`] without checking for thread affinity.
     ///
     /// Window handles in Win32 have a property called "thread affinity" that ties them to their
@@ -420,6 +427,11 @@ impl WindowExtWindows for Window {
         self.window.set_cloaked(cloaked)
     }
 
+    #[inline]
+    fn set_accessibility_caret_area(&self, position: Position, size: Size) {
+        self.window.set_accessibility_caret_area(position, size)
+    }
+
     unsafe fn window_handle_any_thread(
         &self,
     ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
@@ -181,6 +181,34 @@ declare_class!(
             }
         }
 
+        #[method(rioCopyLink:)]
+        fn copy_link(&self, _sender: Option<&AnyObject>) {
+            if self.is_launched() {
+                self.dispatch_hook(Hook::CopyLink);
+            }
+        }
+
+        #[method(rioNextWindow:)]
+        fn next_window(&self, _sender: Option<&AnyObject>) {
+            if self.is_launched() {
+                self.dispatch_hook(Hook::NextWindow);
+            }
+        }
+
+        #[method(rioPreviousWindow:)]
+        fn previous_window(&self, _sender: Option<&AnyObject>) {
+            if self.is_launched() {
+                self.dispatch_hook(Hook::PreviousWindow);
+            }
+        }
+
+        #[method(rioCopyFormatted:)]
+        fn copy_formatted(&self, _sender: Option<&AnyObject>) {
+            if self.is_launched() {
+                self.dispatch_hook(Hook::CopyFormatted);
+            }
+        }
+
         #[method(paste:)]
         fn paste(&self, _sender: Option<&AnyObject>) {
             if self.is_launched() {
@@ -188,6 +216,20 @@ declare_class!(
             }
         }
 
+        #[method(selectAll:)]
+        fn select_all(&self, _sender: Option<&AnyObject>) {
+            if self.is_launched() {
+                self.dispatch_hook(Hook::SelectAll);
+            }
+        }
+
+        #[method(rioSelectLastCommandOutput:)]
+        fn select_last_command_output(&self, _sender: Option<&AnyObject>) {
+            if self.is_launched() {
+                self.dispatch_hook(Hook::SelectLastCommandOutput);
+            }
+        }
+
         #[method(rioCreateTab:)]
         fn create_tab(&self, _sender: Option<&AnyObject>) {
             if self.is_launched() {
@@ -216,6 +258,13 @@ declare_class!(
             }
         }
 
+        #[method(rioToggleSecureKeyboardEntry:)]
+        fn toggle_secure_keyboard_entry(&self, _sender: Option<&AnyObject>) {
+            if self.is_launched() {
+                self.dispatch_hook(Hook::ToggleSecureKeyboardEntry);
+            }
+        }
+
         #[method(openConfig:)]
         fn open_configuration(
             &self,
@@ -16,8 +16,8 @@ use core_foundation::runloop::{
     CFRunLoopSourceCreate, CFRunLoopSourceRef, CFRunLoopSourceSignal, CFRunLoopWakeUp,
 };
 use objc2::rc::{autoreleasepool, Retained};
-use objc2::runtime::ProtocolObject;
-use objc2::{msg_send_id, ClassType};
+use objc2::runtime::{AnyObject, ProtocolObject};
+use objc2::{class, msg_send, msg_send_id, ClassType};
 use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy, NSWindow};
 use objc2_foundation::{MainThreadMarker, NSObjectProtocol};
 
@@ -26,6 +26,7 @@ use super::app_delegate::{ApplicationDelegate, HandlePendingUserEvents};
 use super::event::dummy_event;
 use super::monitor::{self, MonitorHandle};
 use super::observer::setup_control_flow_observers;
+use super::{ffi, menu};
 use crate::error::EventLoopError;
 use crate::event::Event;
 use crate::event_loop::{
@@ -167,6 +168,59 @@ impl ActiveEventLoop {
     pub(crate) fn allows_automatic_window_tabbing(&self) -> bool {
         NSWindow::allowsAutomaticWindowTabbing(self.mtm)
     }
+
+    pub(crate) fn set_secure_keyboard_entry(&self, enabled: bool) {
+        // Secure event input is reference counted by the system, so
+        // calls must be balanced.
+        if SECURE_KEYBOARD_ENTRY.get() != enabled {
+            let status = unsafe {
+                if enabled {
+                    ffi::EnableSecureEventInput()
+                } else {
+                    ffi::DisableSecureEventInput()
+                }
+            };
+
+            if status != 0 {
+                tracing::warn!("failed to change secure keyboard entry: {status}");
+                return;
+            }
+
+            SECURE_KEYBOARD_ENTRY.set(enabled);
+        }
+    }
+
+    pub(crate) fn set_secure_keyboard_entry_menu_state(&self, checked: bool) {
+        menu::set_secure_keyboard_entry_state(
+            &NSApplication::sharedApplication(self.mtm),
+            checked,
+        );
+    }
+
+    pub(crate) fn is_secure_keyboard_entry_enabled(&self) -> bool {
+        SECURE_KEYBOARD_ENTRY.get()
+    }
+
+    pub(crate) fn perform_haptic_feedback(&self) {
+        unsafe {
+            let performer: Option<Retained<AnyObject>> =
+                msg_send_id![class!(NSHapticFeedbackManager), defaultPerformer];
+            if let Some(performer) = performer {
+                // NSHapticFeedbackPatternAlignment and
+                // NSHapticFeedbackPerformanceTimeNow
+                let _: () = msg_send![
+                    &*performer,
+                    performFeedbackPattern: 1isize,
+                    performanceTime: 1usize
+                ];
+            }
+        }
+    }
+}
+
+thread_local! {
+    // Only accessed from the main thread.
+    static SECURE_KEYBOARD_ENTRY: Cell<bool> = const { Cell::new(false) };
 }
 
 fn map_user_event<T: 'static>(
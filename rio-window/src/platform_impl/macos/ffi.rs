@@ -211,6 +211,12 @@ extern "C" {
         actualStringLength: *mut UniCharCount,
         unicodeString: *mut UniChar,
     ) -> OSStatus;
+
+    pub fn EnableSecureEventInput() -> OSStatus;
+
+    pub fn DisableSecureEventInput() -> OSStatus;
+
+    pub fn IsSecureEventInputEnabled() -> Boolean;
 }
 
 // CGWindowLevel.h
@@ -255,3 +261,13 @@ mod window_level {
 }
 
 pub use window_level::*;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    pub static NSAccessibilitySelectedTextChangedNotification: CFStringRef;
+
+    pub fn NSAccessibilityPostNotification(
+        element: *mut AnyObject,
+        notification: CFStringRef,
+    );
+}
@@ -1,9 +1,15 @@
 use objc2::rc::Retained;
 use objc2::runtime::Sel;
 use objc2::sel;
-use objc2_app_kit::{NSApplication, NSEventModifierFlags, NSMenu, NSMenuItem};
+use objc2_app_kit::{
+    NSApplication, NSControlStateValueOff, NSControlStateValueOn, NSEventModifierFlags,
+    NSMenu, NSMenuItem,
+};
 use objc2_foundation::{ns_string, MainThreadMarker, NSProcessInfo, NSString};
 
+/// Tag used to find the secure keyboard entry item in the application menu.
+const SECURE_KEYBOARD_ENTRY_TAG: isize = 1;
+
 pub struct KeyEquivalent<'a> {
     key: &'a NSString,
     masks: Option<NSEventModifierFlags>,
@@ -58,6 +64,15 @@ pub fn initialize(app: &NSApplication) {
         }),
     );
 
+    let secure_keyboard_entry_title = ns_string!("Secure Keyboard Entry");
+    let secure_keyboard_entry = menu_item(
+        mtm,
+        secure_keyboard_entry_title,
+        Some(sel!(rioToggleSecureKeyboardEntry:)),
+        None,
+    );
+    unsafe { secure_keyboard_entry.setTag(SECURE_KEYBOARD_ENTRY_TAG) };
+
     // Hide application menu item
     let hide_item_title = ns_string!("Hide ").stringByAppendingString(&process_name);
     let hide_item = menu_item(
@@ -168,7 +183,8 @@ pub fn initialize(app: &NSApplication) {
         }),
     );
 
-    let copy_title = ns_string!("Copy");
+    // Both copy the selection, as shown or the targets of its hyperlinks
+    let copy_title = ns_string!("Copy Text");
     let copy_item = menu_item(
         mtm,
         copy_title,
@@ -178,6 +194,15 @@ pub fn initialize(app: &NSApplication) {
             masks: Some(NSEventModifierFlags::NSEventModifierFlagCommand),
         }),
     );
+    let copy_link_title = ns_string!("Copy Link");
+    let copy_link_item = menu_item(mtm, copy_link_title, Some(sel!(rioCopyLink:)), None);
+    let copy_formatted_title = ns_string!("Copy with Formatting");
+    let copy_formatted_item = menu_item(
+        mtm,
+        copy_formatted_title,
+        Some(sel!(rioCopyFormatted:)),
+        None,
+    );
     let paste_title = ns_string!("Paste");
     let paste_item = menu_item(
         mtm,
@@ -189,6 +214,41 @@ pub fn initialize(app: &NSApplication) {
         }),
     );
 
+    let select_all_title = ns_string!("Select All");
+    let select_all_item = menu_item(
+        mtm,
+        select_all_title,
+        Some(sel!(selectAll:)),
+        Some(KeyEquivalent {
+            key: ns_string!("a"),
+            masks: Some(NSEventModifierFlags::NSEventModifierFlagCommand),
+        }),
+    );
+    let select_output_title = ns_string!("Select Output of Last Command");
+    let select_output_item = menu_item(
+        mtm,
+        select_output_title,
+        Some(sel!(rioSelectLastCommandOutput:)),
+        Some(KeyEquivalent {
+            key: ns_string!("a"),
+            masks: Some(
+                NSEventModifierFlags::NSEventModifierFlagCommand
+                    | NSEventModifierFlags::NSEventModifierFlagShift,
+            ),
+        }),
+    );
+
+    let next_window_title = ns_string!("Select Next Window");
+    let next_window_item =
+        menu_item(mtm, next_window_title, Some(sel!(rioNextWindow:)), None);
+    let previous_window_title = ns_string!("Select Previous Window");
+    let previous_window_item = menu_item(
+        mtm,
+        previous_window_title,
+        Some(sel!(rioPreviousWindow:)),
+        None,
+    );
+
     let shell_menu = unsafe { NSMenu::initWithTitle(mtm.alloc(), ns_string!("Shell")) };
     let edit_menu = unsafe { NSMenu::initWithTitle(mtm.alloc(), ns_string!("Edit")) };
     let view_menu = unsafe { NSMenu::initWithTitle(mtm.alloc(), ns_string!("View")) };
@@ -199,6 +259,7 @@ pub fn initialize(app: &NSApplication) {
     app_menu.addItem(&sep_first);
     app_menu.addItem(&services_item);
     app_menu.addItem(&open_config);
+    app_menu.addItem(&secure_keyboard_entry);
     app_menu.addItem(&hide_item);
     app_menu.addItem(&hide_others_item);
     app_menu.addItem(&show_all_item);
@@ -212,9 +273,15 @@ pub fn initialize(app: &NSApplication) {
     shell_menu.addItem(&create_split_vertical_item);
     shell_menu_item.setSubmenu(Some(&shell_menu));
     edit_menu.addItem(&copy_item);
+    edit_menu.addItem(&copy_link_item);
+    edit_menu.addItem(&copy_formatted_item);
     edit_menu.addItem(&paste_item);
+    edit_menu.addItem(&select_all_item);
+    edit_menu.addItem(&select_output_item);
     edit_menu_item.setSubmenu(Some(&edit_menu));
     view_menu_item.setSubmenu(Some(&view_menu));
+    window_menu.addItem(&next_window_item);
+    window_menu.addItem(&previous_window_item);
     window_menu_item.setSubmenu(Some(&window_menu));
     help_menu_item.setSubmenu(Some(&help_menu));
 
@@ -226,6 +293,28 @@ pub fn initialize(app: &NSApplication) {
     app.setMainMenu(Some(&menubar));
 }
 
+/// Updates the check mark of the secure keyboard entry menu item.
+pub fn set_secure_keyboard_entry_state(app: &NSApplication, enabled: bool) {
+    let Some(main_menu) = app.mainMenu() else {
+        return;
+    };
+
+    unsafe {
+        let item = main_menu
+            .itemAtIndex(0)
+            .and_then(|app_menu_item| app_menu_item.submenu())
+            .and_then(|app_menu| app_menu.itemWithTag(SECURE_KEYBOARD_ENTRY_TAG));
+
+        if let Some(item) = item {
+            item.setState(if enabled {
+                NSControlStateValueOn
+            } else {
+                NSControlStateValueOff
+            });
+        }
+    }
+}
+
 pub fn menu_item(
     mtm: MainThreadMarker,
     title: &NSString,
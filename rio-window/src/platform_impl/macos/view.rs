@@ -22,6 +22,7 @@ use super::event::{
     code_to_key, code_to_location, create_key_event, event_mods, lalt_pressed,
     ralt_pressed, scancode_to_physicalkey,
 };
+use super::ffi;
 use super::window::WinitWindow;
 use super::DEVICE_ID;
 use crate::dpi::{LogicalPosition, LogicalSize};
@@ -119,6 +120,8 @@ pub struct ViewState {
     cursor_state: RefCell<CursorState>,
     ime_position: Cell<NSPoint>,
     ime_size: Cell<NSSize>,
+    /// Text caret reported to the accessibility clients.
+    accessibility_caret: Cell<NSRect>,
     modifiers: Cell<Modifiers>,
     phys_modifiers: RefCell<HashMap<Key, ModLocationMask>>,
     tracking_rect: Cell<Option<NSTrackingRectTag>>,
@@ -229,6 +232,32 @@ declare_class!(
             None
         }
 
+        // The view is exposed as a text area so the screen magnifiers, like the zoom of
+        // macOS, can follow the caret while typing.
+        #[method(isAccessibilityElement)]
+        fn is_accessibility_element(&self) -> bool {
+            true
+        }
+
+        #[method_id(accessibilityRole)]
+        fn accessibility_role(&self) -> Retained<NSString> {
+            NSString::from_str("AXTextArea")
+        }
+
+        #[method(accessibilitySelectedTextRange)]
+        fn accessibility_selected_text_range(&self) -> NSRange {
+            NSRange::new(0, 0)
+        }
+
+        #[method(accessibilityFrameForRange:)]
+        fn accessibility_frame_for_range(&self, _range: NSRange) -> NSRect {
+            trace_scope!("accessibilityFrameForRange:");
+            let rect = self.ivars().accessibility_caret.get();
+            // Return value is expected to be in screen coordinates, so we need a conversion here
+            self.window()
+                .convertRectToScreen(self.convertRect_toView(rect, None))
+        }
+
         #[method(resetCursorRects)]
         fn reset_cursor_rects(&self) {
             trace_scope!("resetCursorRects");
@@ -791,6 +820,7 @@ impl WinitView {
             cursor_state: Default::default(),
             ime_position: Default::default(),
             ime_size: Default::default(),
+            accessibility_caret: Default::default(),
             modifiers: Default::default(),
             phys_modifiers: Default::default(),
             tracking_rect: Default::default(),
@@ -906,6 +936,21 @@ impl WinitView {
         input_context.invalidateCharacterCoordinates();
     }
 
+    pub(super) fn set_accessibility_caret_area(&self, position: NSPoint, size: NSSize) {
+        let rect = NSRect::new(position, size);
+        if self.ivars().accessibility_caret.get() == rect {
+            return;
+        }
+
+        self.ivars().accessibility_caret.set(rect);
+        unsafe {
+            ffi::NSAccessibilityPostNotification(
+                self as *const Self as *mut AnyObject,
+                ffi::NSAccessibilitySelectedTextChangedNotification,
+            );
+        }
+    }
+
     /// Reset modifiers and emit a synthetic ModifiersChanged event if deemed necessary.
     pub(super) fn reset_modifiers(&self) {
         if !self.ivars().modifiers.get().state().is_empty() {
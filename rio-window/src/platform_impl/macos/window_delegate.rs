@@ -1871,6 +1871,17 @@ impl WindowExtMacOS for WindowDelegate {
                 && window.toolbarStyle() == NSWindowToolbarStyle::Unified
         }
     }
+
+    fn set_accessibility_caret_area(&self, position: Position, size: Size) {
+        let scale_factor = self.scale_factor();
+        let position = position.to_logical(scale_factor);
+        let position = NSPoint::new(position.x, position.y);
+
+        let size = size.to_logical(scale_factor);
+        let size = NSSize::new(size.width, size.height);
+
+        self.view().set_accessibility_caret_area(position, size);
+    }
 }
 
 const DEFAULT_STANDARD_FRAME: NSRect =
@@ -7,7 +7,9 @@ use rio_window::{
     dpi::LogicalSize, event::WindowEvent, event_loop::EventLoop, window::WindowAttributes,
 };
 use std::error::Error;
-use sugarloaf::{layout::RootStyle, Sugarloaf, SugarloafWindow, SugarloafWindowSize};
+use sugarloaf::{
+    layout::RootStyle, Image, Object, Sugarloaf, SugarloafWindow, SugarloafWindowSize,
+};
 
 fn main() {
     let width = 400.0;
@@ -80,6 +82,7 @@ impl ApplicationHandler for Application {
             height: Some(400.),
             x: 0.,
             y: 0.,
+            ..sugarloaf::ImageProperties::default()
         });
 
         window.request_redraw();
@@ -119,6 +122,11 @@ impl ApplicationHandler for Application {
                 window.request_redraw();
             }
             WindowEvent::RedrawRequested => {
+                sugarloaf.set_objects(vec![Object::Image(Image::from_path(
+                    "resources/rio-colors.png",
+                    [250., 250.],
+                    [100., 100.],
+                ))]);
                 sugarloaf.render();
                 event_loop.set_control_flow(ControlFlow::Wait);
             }
@@ -1,5 +1,6 @@
 mod builtin;
 mod runtime;
+mod wgsl;
 
 use crate::context::Context;
 use librashader_common::{Size, Viewport};
@@ -8,10 +9,16 @@ use std::sync::Arc;
 
 pub type Filter = String;
 
-/// A brush for applying RetroArch filters.
+enum FilterPass {
+    RetroArch(Box<crate::components::filters::runtime::FilterChain>),
+    Wgsl(wgsl::WgslFilter),
+}
+
+/// A brush for applying RetroArch filters and WGSL shaders.
 #[derive(Default)]
 pub struct FiltersBrush {
-    filter_chains: Vec<crate::components::filters::runtime::FilterChain>,
+    filters: Vec<Filter>,
+    filter_chains: Vec<FilterPass>,
     filter_intermediates: Vec<Arc<wgpu::Texture>>,
     framecount: usize,
 }
@@ -19,6 +26,7 @@ pub struct FiltersBrush {
 impl FiltersBrush {
     #[inline]
     pub fn update_filters(&mut self, ctx: &Context, filters: &[Filter]) {
+        self.filters = filters.to_vec();
         self.filter_chains.clear();
         self.filter_intermediates.clear();
 
@@ -58,7 +66,7 @@ impl FiltersBrush {
                                 &ctx.queue,
                                 None,
                             ) {
-                                Ok(f) => self.filter_chains.push(f),
+                                Ok(f) => self.filter_chains.push(FilterPass::RetroArch(Box::new(f))),
                                 Err(e) => tracing::error!("Failed to load builtin filter {}: {}", configured_filter, e),
                             }
                         },
@@ -68,6 +76,20 @@ impl FiltersBrush {
                         },
                     }
                 }
+                _ if wgsl::WgslFilter::is_wgsl(filter) => {
+                    tracing::debug!("Loading WGSL filter {}", filter);
+
+                    match wgsl::WgslFilter::load_from_path(ctx, filter) {
+                        Ok(f) => self.filter_chains.push(FilterPass::Wgsl(f)),
+                        Err(e) => {
+                            tracing::error!(
+                                "Failed to load WGSL filter {}: {}",
+                                filter,
+                                e
+                            )
+                        }
+                    }
+                }
                 _ => {
                     tracing::debug!("Loading filter {}", filter);
 
@@ -78,7 +100,9 @@ impl FiltersBrush {
                         &ctx.queue,
                         None,
                     ) {
-                        Ok(f) => self.filter_chains.push(f),
+                        Ok(f) => {
+                            self.filter_chains.push(FilterPass::RetroArch(Box::new(f)))
+                        }
                         Err(e) => {
                             tracing::error!("Failed to load filter {}: {}", filter, e)
                         }
@@ -123,6 +147,13 @@ impl FiltersBrush {
         }
     }
 
+    /// Loads the current filters again, used after the device is recreated.
+    #[inline]
+    pub fn reload(&mut self, ctx: &Context) {
+        let filters = std::mem::take(&mut self.filters);
+        self.update_filters(ctx, &filters);
+    }
+
     /// Render the filters on top of the src_texture to dst_texture.
     /// If the filters are not set, the src_texture is copied to dst_texture.
     #[inline]
@@ -195,6 +226,14 @@ impl FiltersBrush {
                 filter_dst_texture = &self.filter_intermediates[idx];
             }
 
+            let filter = match filter {
+                FilterPass::RetroArch(filter) => filter,
+                FilterPass::Wgsl(filter) => {
+                    filter.render(ctx, encoder, &filter_src_texture, filter_dst_texture);
+                    continue;
+                }
+            };
+
             let dst_texture_view =
                 filter_dst_texture.create_view(&wgpu::TextureViewDescriptor::default());
             let dst_output_view =
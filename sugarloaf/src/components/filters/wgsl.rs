@@ -0,0 +1,233 @@
+// Copyright (c) 2023-present, Raphael Amorim.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! User provided WGSL shaders applied as a fullscreen post-processing pass.
+//!
+//! The shader file only needs to declare the fragment entry point
+//! `fs_main`, the bindings and the fullscreen vertex stage are prepended
+//! by Sugarloaf:
+//!
+//! ```wgsl
+//! @fragment
+//! fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+//!     let color = textureSample(input_texture, input_sampler, in.uv);
+//!     let scanline = 0.9 + 0.1 * sin(in.uv.y * uniforms.resolution.y);
+//!     return vec4<f32>(color.rgb * scanline, color.a);
+//! }
+//! ```
+
+use crate::context::Context;
+use bytemuck::{Pod, Zeroable};
+
+const PRELUDE: &str = include_str!("./wgsl_prelude.wgsl");
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+struct Uniforms {
+    resolution: [f32; 2],
+    time: f32,
+    _padding: f32,
+}
+
+const UNIFORMS_SIZE: u64 = std::mem::size_of::<Uniforms>() as u64;
+
+pub struct WgslFilter {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniforms: wgpu::Buffer,
+    #[cfg(not(target_arch = "wasm32"))]
+    started_at: std::time::Instant,
+}
+
+impl WgslFilter {
+    /// Returns true if the filter should be loaded as a WGSL shader.
+    #[inline]
+    pub fn is_wgsl(filter: &str) -> bool {
+        std::path::Path::new(filter)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("wgsl"))
+    }
+
+    pub fn load_from_path(ctx: &Context, path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Self::new(ctx, &source)
+    }
+
+    pub fn new(ctx: &Context, source: &str) -> Result<Self, String> {
+        let device = &ctx.device;
+
+        // User shaders can be invalid, so validation errors are captured
+        // instead of reaching the uncaptured error handler.
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sugarloaf::filters::wgsl shader"),
+            source: wgpu::ShaderSource::Wgsl(format!("{PRELUDE}\n{source}").into()),
+        });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("sugarloaf::filters::wgsl bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(UNIFORMS_SIZE),
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sugarloaf::filters::wgsl pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            cache: None,
+            label: Some("sugarloaf::filters::wgsl render pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ctx.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        if let Some(err) = futures::executor::block_on(device.pop_error_scope()) {
+            return Err(err.to_string());
+        }
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("sugarloaf::filters::wgsl sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sugarloaf::filters::wgsl uniforms buffer"),
+            size: UNIFORMS_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniforms,
+            #[cfg(not(target_arch = "wasm32"))]
+            started_at: std::time::Instant::now(),
+        })
+    }
+
+    #[inline]
+    fn elapsed(&self) -> f32 {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.started_at.elapsed().as_secs_f32();
+
+        #[cfg(target_arch = "wasm32")]
+        0.0
+    }
+
+    pub fn render(
+        &self,
+        ctx: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        src_texture: &wgpu::Texture,
+        dst_texture: &wgpu::Texture,
+    ) {
+        let uniforms = Uniforms {
+            resolution: [ctx.size.width, ctx.size.height],
+            time: self.elapsed(),
+            _padding: 0.0,
+        };
+        ctx.queue
+            .write_buffer(&self.uniforms, 0, bytemuck::bytes_of(&uniforms));
+
+        let src_view = src_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let dst_view = dst_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sugarloaf::filters::wgsl bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&src_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("sugarloaf::filters::wgsl render pass"),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &dst_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,39 @@
+// Converts the sRGB encoded content into extended linear sRGB,
+// colors outside of the sRGB gamut end up out of the [0, 1] range and
+// brightness above 1.0 goes beyond the SDR white, nothing is clamped
+override display_p3: bool = false;
+override brightness: f32 = 1.0;
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+
+// Display P3 (D65) to linear sRGB, declared by columns
+const P3_TO_SRGB = mat3x3<f32>(
+    vec3<f32>(1.2249, -0.0420, -0.0197),
+    vec3<f32>(-0.2247, 1.0419, -0.0786),
+    vec3<f32>(0.0, 0.0, 1.0979),
+);
+
+// Single triangle covering the whole output
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let lower = color / 12.92;
+    let higher = pow((color + vec3<f32>(0.055)) / 1.055, vec3<f32>(2.4));
+    return select(higher, lower, color <= vec3<f32>(0.04045));
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = textureLoad(input_texture, vec2<i32>(position.xy), 0);
+
+    var rgb = srgb_to_linear(color.rgb);
+    if display_p3 {
+        rgb = P3_TO_SRGB * rgb;
+    }
+
+    return vec4<f32>(rgb * brightness, color.a);
+}
@@ -0,0 +1,300 @@
+use crate::context::Context;
+use crate::Colorspace;
+
+/// Presents the content on extended range (HDR) surfaces.
+///
+/// Brushes keep rendering sRGB encoded colors into an intermediate texture,
+/// which is converted to the extended linear sRGB expected by the surface.
+/// The output isn't clamped, so it can go beyond 1.0 for wide-gamut colors
+/// or a `hdr_brightness` above 1.0.
+pub struct HdrBrush {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    texture: Option<wgpu::Texture>,
+}
+
+impl HdrBrush {
+    pub fn new(ctx: &Context) -> Self {
+        let device = &ctx.device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sugarloaf::hdr shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "./hdr.wgsl"
+            ))),
+        });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("sugarloaf::hdr bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                }],
+            });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sugarloaf::hdr pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let display_p3 = f64::from(u8::from(ctx.colorspace == Colorspace::DisplayP3));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            cache: None,
+            label: Some("sugarloaf::hdr render pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &[
+                        ("display_p3", display_p3),
+                        ("brightness", f64::from(ctx.hdr_brightness)),
+                    ],
+                    ..Default::default()
+                },
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ctx.surface_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            texture: None,
+        }
+    }
+
+    /// Texture the content is rendered into, recreated whenever the
+    /// surface size changes.
+    pub fn texture(&mut self, ctx: &Context) -> wgpu::Texture {
+        let width = (ctx.size.width as u32).max(1);
+        let height = (ctx.size.height as u32).max(1);
+
+        if let Some(texture) = &self.texture {
+            if texture.width() == width && texture.height() == height {
+                return texture.clone();
+            }
+        }
+
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("sugarloaf::hdr intermediate texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: ctx.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        self.texture = Some(texture.clone());
+        texture
+    }
+
+    /// Converts the intermediate texture into dst_texture.
+    pub fn render(
+        &self,
+        ctx: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        dst_texture: &wgpu::Texture,
+    ) {
+        let Some(texture) = &self.texture else {
+            return;
+        };
+
+        let src_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let dst_view = dst_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sugarloaf::hdr bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&src_view),
+            }],
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("sugarloaf::hdr render pass"),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &dst_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sugarloaf::f16_to_f32;
+    use crate::{SugarloafRenderer, SugarloafWindowSize};
+
+    const WIDTH: u32 = 4;
+    const HEIGHT: u32 = 1;
+
+    /// Converts a single sRGB encoded pixel, returns the linear RGB written
+    /// to the extended range surface or `None` without a graphics adapter.
+    fn convert(pixel: [u8; 4], renderer: SugarloafRenderer) -> Option<[f32; 3]> {
+        let size = SugarloafWindowSize {
+            width: WIDTH as f32,
+            height: HEIGHT as f32,
+        };
+        let mut ctx = match Context::new_headless(size, 1.0, renderer) {
+            Ok(ctx) => ctx,
+            Err(err) => {
+                eprintln!("skipping hdr test, no graphics adapter: {err}");
+                return None;
+            }
+        };
+        ctx.surface_format = wgpu::TextureFormat::Rgba16Float;
+
+        let mut hdr_brush = HdrBrush::new(&ctx);
+        let texture = hdr_brush.texture(&ctx);
+        ctx.queue.write_texture(
+            texture.as_image_copy(),
+            &pixel.repeat((WIDTH * HEIGHT) as usize),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * WIDTH),
+                rows_per_image: Some(HEIGHT),
+            },
+            texture.size(),
+        );
+
+        let dst_texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: texture.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: ctx.surface_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: u64::from(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT * HEIGHT),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        hdr_brush.render(&ctx, &mut encoder, &dst_texture);
+        encoder.copy_texture_to_buffer(
+            dst_texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: Some(HEIGHT),
+                },
+            },
+            dst_texture.size(),
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        ctx.device.poll(wgpu::PollType::Wait).unwrap();
+        let data = slice.get_mapped_range();
+        let channel = |index: usize| {
+            f16_to_f32(u16::from_le_bytes([data[index * 2], data[index * 2 + 1]]))
+        };
+        Some([channel(0), channel(1), channel(2)])
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 0.01,
+                "expected {expected}, got {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn srgb_white_is_the_sdr_white() {
+        let Some(rgb) = convert([255, 255, 255, 255], SugarloafRenderer::default())
+        else {
+            return;
+        };
+        assert_close(rgb, [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn brightness_goes_beyond_the_sdr_white() {
+        let renderer = SugarloafRenderer {
+            hdr_brightness: 2.5,
+            ..SugarloafRenderer::default()
+        };
+        let Some(rgb) = convert([255, 255, 255, 255], renderer) else {
+            return;
+        };
+        assert_close(rgb, [2.5, 2.5, 2.5]);
+
+        // Mid gray is scaled in linear light
+        let renderer = SugarloafRenderer {
+            hdr_brightness: 2.5,
+            ..SugarloafRenderer::default()
+        };
+        let Some(rgb) = convert([188, 188, 188, 255], renderer) else {
+            return;
+        };
+        assert_close(rgb, [1.2572, 1.2572, 1.2572]);
+    }
+
+    #[test]
+    fn display_p3_is_not_clipped_to_srgb() {
+        let renderer = SugarloafRenderer {
+            colorspace: Colorspace::DisplayP3,
+            ..SugarloafRenderer::default()
+        };
+        let Some(rgb) = convert([255, 0, 0, 255], renderer) else {
+            return;
+        };
+        assert_close(rgb, [1.2249, -0.0420, -0.0197]);
+    }
+}
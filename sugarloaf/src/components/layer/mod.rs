@@ -218,6 +218,7 @@ impl LayerBrush {
                             3 => Float32x2,
                             4 => Float32x2,
                             5 => Sint32,
+                            6 => Float32,
                         ),
                     },
                 ],
@@ -328,6 +329,7 @@ impl LayerBrush {
                 add_instances(
                     [bounds.x, bounds.y],
                     [bounds.width, bounds.height],
+                    image.opacity,
                     atlas_entry,
                     instances,
                 );
@@ -374,7 +376,7 @@ impl LayerBrush {
         ctx: &mut Context,
         handle: &image::Handle,
         bounds: &Rectangle,
-    ) {
+    ) -> bool {
         let transformation: [f32; 16] =
             orthographic_projection(ctx.size.width, ctx.size.height);
         let device = &ctx.device;
@@ -389,13 +391,14 @@ impl LayerBrush {
             add_instances(
                 [bounds.x, bounds.y],
                 [bounds.width, bounds.height],
+                1.0,
                 atlas_entry,
                 instances,
             );
         }
 
         if instances.is_empty() {
-            return;
+            return false;
         }
 
         let texture_version = self.texture_atlas.layer_count();
@@ -426,6 +429,7 @@ impl LayerBrush {
         layer.prepare(device, queue, instances, transformation);
 
         self.prepare_layer += 1;
+        true
     }
 
     #[inline]
@@ -502,6 +506,13 @@ impl LayerBrush {
         }
     }
 
+    /// Prepares the layers from the first one again, for content that is
+    /// drawn more than once per frame.
+    #[inline]
+    pub fn begin_draw(&mut self) {
+        self.prepare_layer = 0;
+    }
+
     pub fn end_frame(&mut self) {
         self.raster_cache.borrow_mut().trim(&mut self.texture_atlas);
 
@@ -540,6 +551,7 @@ struct Instance {
     _position_in_atlas: [f32; 2],
     _size_in_atlas: [f32; 2],
     _layer: u32,
+    _opacity: f32,
 }
 
 impl Instance {
@@ -555,12 +567,13 @@ struct Uniforms {
 fn add_instances(
     image_position: [f32; 2],
     image_size: [f32; 2],
+    opacity: f32,
     entry: &atlas::Entry,
     instances: &mut Vec<Instance>,
 ) {
     match entry {
         atlas::Entry::Contiguous(allocation) => {
-            add_instance(image_position, image_size, allocation, instances);
+            add_instance(image_position, image_size, opacity, allocation, instances);
         }
         atlas::Entry::Fragmented { fragments, size } => {
             let scaling_x = image_size[0] / size.width as f32;
@@ -586,7 +599,7 @@ fn add_instances(
                     fragment_height as f32 * scaling_y,
                 ];
 
-                add_instance(position, size, allocation, instances);
+                add_instance(position, size, opacity, allocation, instances);
             }
         }
     }
@@ -596,6 +609,7 @@ fn add_instances(
 fn add_instance(
     position: [f32; 2],
     size: [f32; 2],
+    opacity: f32,
     allocation: &atlas::Allocation,
     instances: &mut Vec<Instance>,
 ) {
@@ -615,7 +629,57 @@ fn add_instance(
             (height as f32 - 1.0) / atlas::SIZE as f32,
         ],
         _layer: layer as u32,
+        _opacity: opacity,
     };
 
     instances.push(instance);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SugarloafRenderer, SugarloafWindowSize};
+
+    #[test]
+    fn drawing_again_reuses_the_prepared_layers() {
+        let size = SugarloafWindowSize {
+            width: 8.0,
+            height: 8.0,
+        };
+        let mut ctx = match Context::new_headless(size, 1.0, SugarloafRenderer::default())
+        {
+            Ok(ctx) => ctx,
+            Err(err) => {
+                eprintln!("skipping layer test, no graphics adapter: {err}");
+                return;
+            }
+        };
+
+        let mut layer_brush = LayerBrush::new(&ctx);
+        let handle = image::Handle::from_pixels(1, 1, vec![255; 4]);
+        let bounds = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        };
+
+        for _ in 0..2 {
+            layer_brush.begin_draw();
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            assert!(layer_brush.prepare_with_handle(
+                &mut encoder,
+                &mut ctx,
+                &handle,
+                &bounds
+            ));
+            ctx.queue.submit(Some(encoder.finish()));
+        }
+
+        assert_eq!(layer_brush.prepare_layer, 1);
+        assert_eq!(layer_brush.layers.len(), 1);
+        layer_brush.end_frame();
+    }
+}
@@ -10,4 +10,7 @@ pub struct Raster {
 
     /// The bounds of the image.
     pub bounds: Rectangle,
+
+    /// The opacity of the image, from 0.0 to 1.0.
+    pub opacity: f32,
 }
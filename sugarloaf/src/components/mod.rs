@@ -1,5 +1,6 @@
 pub mod core;
 pub mod filters;
+pub mod hdr;
 pub mod layer;
 pub mod quad;
 pub mod rich_text;
@@ -46,6 +46,41 @@ pub struct Quad {
     pub shadow_blur_radius: f32,
 }
 
+impl Quad {
+    /// Creates a flat [`Quad`] without border or shadow.
+    #[inline]
+    pub fn solid(position: [f32; 2], size: [f32; 2], color: [f32; 4]) -> Self {
+        Self {
+            position,
+            size,
+            color,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the same radius for all corners.
+    #[inline]
+    pub fn with_border_radius(self, radius: f32) -> Self {
+        self.with_border_radii([radius; 4])
+    }
+
+    /// Sets the corners radii, ordered as top-left, top-right,
+    /// bottom-right and bottom-left.
+    #[inline]
+    pub fn with_border_radii(mut self, radii: [f32; 4]) -> Self {
+        self.border_radius = radii;
+        self
+    }
+
+    /// Sets a border drawn inside the bounds of the [`Quad`].
+    #[inline]
+    pub fn with_border(mut self, width: f32, color: [f32; 4]) -> Self {
+        self.border_width = width;
+        self.border_color = color;
+        self
+    }
+}
+
 #[derive(Debug)]
 pub struct QuadBrush {
     pipeline: wgpu::RenderPipeline,
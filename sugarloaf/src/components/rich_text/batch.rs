@@ -14,6 +14,27 @@ use crate::DrawableChar;
 use crate::UnderlineShape;
 use bytemuck::{Pod, Zeroable};
 
+/// Hexadecimal digits on a 3×5 grid, one bit per dot, read row by row
+/// from the top left.
+const HEX_DIGITS: [u16; 16] = [
+    0b111_101_101_101_111, // 0
+    0b010_110_010_010_111, // 1
+    0b111_001_111_100_111, // 2
+    0b111_001_111_001_111, // 3
+    0b101_101_111_001_001, // 4
+    0b111_100_111_001_111, // 5
+    0b111_100_111_101_111, // 6
+    0b111_001_001_001_001, // 7
+    0b111_101_111_101_111, // 8
+    0b111_101_111_001_111, // 9
+    0b111_101_111_101_101, // A
+    0b110_101_110_101_110, // B
+    0b111_100_100_100_111, // C
+    0b110_101_101_101_110, // D
+    0b111_100_111_100_111, // E
+    0b111_100_111_100_100, // F
+];
+
 #[derive(Default, Clone, Copy)]
 pub struct RunUnderline {
     pub enabled: bool,
@@ -3788,6 +3809,57 @@ impl BatchManager {
                     }
                 }
             }
+            DrawableChar::HexBox(code_point) => {
+                // The last four hexadecimal digits in two rows, inside
+                // a box:
+                // ╭─────╮
+                // │ 2 0 │
+                // │ 0 B │
+                // ╰─────╯
+
+                let border = (stroke / 2.0).max(1.0);
+                let outline = [
+                    Rect::new(x, y, line_width, border),
+                    Rect::new(x, y + line_height - border, line_width, border),
+                    Rect::new(x, y, border, line_height),
+                    Rect::new(x + line_width - border, y, border, line_height),
+                ];
+                for rect in &outline {
+                    self.add_rect(rect, depth, &color);
+                }
+
+                // Two digits of 3 dots with a dot between them across, two
+                // rows of 5 dots with a dot between them down, and a dot
+                // of margin on every side
+                let inner_width = line_width - border * 2.0;
+                let inner_height = line_height - border * 2.0;
+                let dot = f32::min(inner_width / 9.0, inner_height / 13.0);
+                let left = x + (line_width - dot * 7.0) / 2.0;
+                let top = y + (line_height - dot * 11.0) / 2.0;
+
+                for index in 0..4 {
+                    let digit = (code_point >> ((3 - index) * 4)) & 0xF;
+                    let glyph = HEX_DIGITS[digit as usize];
+                    let digit_x = left + (index % 2) as f32 * dot * 4.0;
+                    let digit_y = top + (index / 2) as f32 * dot * 6.0;
+
+                    for i in 0..15 {
+                        if glyph & (1 << (14 - i)) != 0 {
+                            let row = i / 3;
+                            let col = i % 3;
+
+                            let dot_rect = Rect {
+                                x: digit_x + (col as f32 * dot),
+                                y: digit_y + (row as f32 * dot),
+                                width: dot,
+                                height: dot,
+                            };
+
+                            self.add_rect(&dot_rect, depth, &color);
+                        }
+                    }
+                }
+            }
         }
     }
 
@@ -21,10 +21,49 @@ use crate::Graphics;
 use crate::{DrawableChar, SugarCursor};
 use halfbrown::HashMap;
 
+/// Border width of the hollow block cursor.
+const HOLLOW_CURSOR_BORDER: f32 = 2.0;
+
+/// Top, bottom, left and right borders of the rect.
+#[inline]
+fn outline(rect: &Rect, width: f32) -> [Rect; 4] {
+    let width = width.min(rect.width / 2.0).min(rect.height / 2.0);
+    let inner_height = rect.height - width * 2.0;
+    [
+        Rect::new(rect.x, rect.y, rect.width, width),
+        Rect::new(rect.x, rect.y + rect.height - width, rect.width, width),
+        Rect::new(rect.x, rect.y + width, width, inner_height),
+        Rect::new(
+            rect.x + rect.width - width,
+            rect.y + width,
+            width,
+            inner_height,
+        ),
+    ]
+}
+
+/// Cursor thickness lower than 1 is a fraction of the cell size,
+/// otherwise it's already in pixels.
+#[inline]
+fn cursor_thickness(value: f32, cell_size: f32) -> f32 {
+    if value < 1.0 {
+        (value * cell_size).max(1.0)
+    } else {
+        value
+    }
+}
+
 // First, let's define a structure to store the cached draw operations
 pub struct LineCache {
     // Maps rich_text_id -> line_index -> cached batches
-    caches: HashMap<usize, HashMap<usize, Vec<BatchOperation>>>,
+    caches: HashMap<usize, HashMap<usize, CachedLine>>,
+}
+
+// Operations of a line along with the top it was drawn at, so the line
+// can be drawn again somewhere else after scrolling
+struct CachedLine {
+    top: f32,
+    operations: Vec<BatchOperation>,
 }
 
 // This will represent operations we need to cache
@@ -95,6 +134,26 @@ impl LineCache {
         self.caches.clear();
     }
 
+    // Move the cached lines `delta` positions down (up when negative)
+    // along with the scrolled content, lines moved out of `lines` are dropped
+    #[inline]
+    pub fn scroll(&mut self, rich_text_id: usize, delta: i32, lines: usize) {
+        if delta == 0 {
+            return;
+        }
+
+        if let Some(text_cache) = self.caches.get_mut(&rich_text_id) {
+            for (line_number, cached) in std::mem::take(text_cache) {
+                if let Some(line_number) = line_number
+                    .checked_add_signed(delta as isize)
+                    .filter(|line_number| *line_number < lines)
+                {
+                    text_cache.insert(line_number, cached);
+                }
+            }
+        }
+    }
+
     // Check if a cache entry exists
     #[inline]
     pub fn has_cache(&self, rich_text_id: usize, line_number: usize) -> bool {
@@ -109,29 +168,36 @@ impl LineCache {
         &mut self,
         rich_text_id: usize,
         line_number: usize,
+        top: f32,
         operations: Vec<BatchOperation>,
     ) {
         self.caches
             .entry(rich_text_id)
             .or_insert_with(HashMap::new)
-            .insert(line_number, operations);
+            .insert(line_number, CachedLine { top, operations });
     }
 
-    // Apply cached operations to batches
+    // Apply cached operations to batches, moved to the line's current top
     #[inline]
     pub fn apply_cache(
         &self,
         rich_text_id: usize,
         line_number: usize,
+        top: f32,
         comp: &mut Compositor,
         graphics: &mut Graphics,
     ) -> bool {
         if let Some(text_cache) = self.caches.get(&rich_text_id) {
-            if let Some(operations) = text_cache.get(&line_number) {
-                for op in operations {
+            if let Some(cached) = text_cache.get(&line_number) {
+                let dy = top - cached.top;
+                let moved = |rect: &Rect| Rect {
+                    y: rect.y + dy,
+                    ..*rect
+                };
+                for op in &cached.operations {
                     match op {
                         BatchOperation::Rect { rect, depth, color } => {
-                            comp.batches.add_rect(rect, *depth, color);
+                            comp.batches.add_rect(&moved(rect), *depth, color);
                         }
                         BatchOperation::MaskRect {
                             rect,
@@ -140,8 +206,13 @@ impl LineCache {
                             coords,
                             has_alpha,
                         } => {
-                            comp.batches
-                                .add_mask_rect(rect, *depth, color, coords, *has_alpha);
+                            comp.batches.add_mask_rect(
+                                &moved(rect),
+                                *depth,
+                                color,
+                                coords,
+                                *has_alpha,
+                            );
                         }
                         BatchOperation::ImageRect {
                             rect,
@@ -150,8 +221,13 @@ impl LineCache {
                             coords,
                             has_alpha,
                         } => {
-                            comp.batches
-                                .add_image_rect(rect, *depth, color, coords, *has_alpha);
+                            comp.batches.add_image_rect(
+                                &moved(rect),
+                                *depth,
+                                color,
+                                coords,
+                                *has_alpha,
+                            );
                         }
                         BatchOperation::DrawableChar {
                             x,
@@ -164,7 +240,7 @@ impl LineCache {
                         } => {
                             comp.batches.draw_drawable_character(
                                 *x,
-                                *y,
+                                *y + dy,
                                 *width,
                                 *char_type,
                                 *color,
@@ -184,14 +260,18 @@ impl LineCache {
                                 info,
                                 *x,
                                 *width,
-                                *baseline,
+                                *baseline + dy,
                                 *depth,
                                 *line_height,
                             );
                         }
                         BatchOperation::GraphicRequest(graphic_request) => {
-                            if !graphics.top_layer.contains(graphic_request) {
-                                graphics.top_layer.push(*graphic_request);
+                            let graphic_request = GraphicRenderRequest {
+                                pos_y: graphic_request.pos_y + dy,
+                                ..*graphic_request
+                            };
+                            if !graphics.top_layer.contains(&graphic_request) {
+                                graphics.top_layer.push(graphic_request);
                             }
                         }
                     }
@@ -377,38 +457,24 @@ impl Compositor {
                         style.line_height_without_mod,
                     );
 
-                    self.batches.add_rect(&outer_rect, depth, &cursor_color);
-                    if let Some(cache) = &mut cache_operations {
-                        cache.push(BatchOperation::Rect {
-                            rect: outer_rect,
-                            depth,
-                            color: cursor_color,
-                        });
-                    }
-
-                    if let Some(bg_color) = style.background_color {
-                        let inner_rect = Rect::new(
-                            rect.x + 2.0,
-                            style.topline + style.padding_y + 2.0,
-                            rect.width - 4.0,
-                            style.line_height_without_mod - 4.0,
-                        );
-
-                        self.batches.add_rect(&inner_rect, depth, &bg_color);
+                    // Only the border is drawn so the glyph and the cell
+                    // background remain visible
+                    for border in outline(&outer_rect, HOLLOW_CURSOR_BORDER) {
+                        self.batches.add_rect(&border, depth, &cursor_color);
                         if let Some(cache) = &mut cache_operations {
                             cache.push(BatchOperation::Rect {
-                                rect: inner_rect,
+                                rect: border,
                                 depth,
-                                color: bg_color,
+                                color: cursor_color,
                             });
                         }
                     }
                 }
-                Some(SugarCursor::Caret(cursor_color)) => {
+                Some(SugarCursor::Caret(cursor_color, width)) => {
                     let caret_rect = Rect::new(
                         rect.x,
                         style.topline + style.padding_y,
-                        3.0,
+                        cursor_thickness(width, rect.width),
                         style.line_height_without_mod,
                     );
 
@@ -421,7 +487,24 @@ impl Compositor {
                         });
                     }
                 }
-                _ => {}
+                Some(SugarCursor::Underline(cursor_color, height)) => {
+                    let underline_rect = Rect::new(
+                        rect.x,
+                        style.baseline,
+                        rect.width,
+                        cursor_thickness(height, style.line_height_without_mod),
+                    );
+
+                    self.batches.add_rect(&underline_rect, depth, &cursor_color);
+                    if let Some(cache) = &mut cache_operations {
+                        cache.push(BatchOperation::Rect {
+                            rect: underline_rect,
+                            depth,
+                            color: cursor_color,
+                        });
+                    }
+                }
+                None => {}
             }
 
             // Handle underline
@@ -561,38 +644,24 @@ impl Compositor {
                         style.line_height_without_mod,
                     );
 
-                    self.batches.add_rect(&outer_rect, depth, &cursor_color);
-                    if let Some(cache) = &mut cache_operations {
-                        cache.push(BatchOperation::Rect {
-                            rect: outer_rect,
-                            depth,
-                            color: cursor_color,
-                        });
-                    }
-
-                    if let Some(bg_color) = style.background_color {
-                        let inner_rect = Rect::new(
-                            rect.x + 2.0,
-                            style.topline + style.padding_y + 2.0,
-                            rect.width - 4.0,
-                            style.line_height_without_mod - 4.0,
-                        );
-
-                        self.batches.add_rect(&inner_rect, depth, &bg_color);
+                    // Only the border is drawn so the glyph and the cell
+                    // background remain visible
+                    for border in outline(&outer_rect, HOLLOW_CURSOR_BORDER) {
+                        self.batches.add_rect(&border, depth, &cursor_color);
                         if let Some(cache) = &mut cache_operations {
                             cache.push(BatchOperation::Rect {
-                                rect: inner_rect,
+                                rect: border,
                                 depth,
-                                color: bg_color,
+                                color: cursor_color,
                             });
                         }
                     }
                 }
-                Some(SugarCursor::Caret(cursor_color)) => {
+                Some(SugarCursor::Caret(cursor_color, width)) => {
                     let caret_rect = Rect::new(
                         rect.x,
                         style.topline + style.padding_y,
-                        3.0,
+                        cursor_thickness(width, rect.width),
                         style.line_height_without_mod,
                     );
 
@@ -605,7 +674,24 @@ impl Compositor {
                         });
                     }
                 }
-                _ => {}
+                Some(SugarCursor::Underline(cursor_color, height)) => {
+                    let underline_rect = Rect::new(
+                        rect.x,
+                        style.baseline,
+                        rect.width,
+                        cursor_thickness(height, style.line_height_without_mod),
+                    );
+
+                    self.batches.add_rect(&underline_rect, depth, &cursor_color);
+                    if let Some(cache) = &mut cache_operations {
+                        cache.push(BatchOperation::Rect {
+                            rect: underline_rect,
+                            depth,
+                            color: cursor_color,
+                        });
+                    }
+                }
+                None => {}
             }
 
             // Handle underline
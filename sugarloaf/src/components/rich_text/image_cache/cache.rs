@@ -29,6 +29,8 @@ pub struct ImageCache {
     max_texture_size: u16,
     texture: wgpu::Texture,
     pub texture_view: wgpu::TextureView,
+    /// Allocations refused because the atlas was full.
+    pub overflows: u64,
 }
 
 #[inline]
@@ -79,6 +81,7 @@ impl ImageCache {
             max_texture_size,
             texture_view,
             texture,
+            overflows: 0,
         }
     }
 
@@ -116,7 +119,10 @@ impl ImageCache {
         //     println!("should try to grow or reset atlas");
         // }
         // }
-        let (x, y) = atlas_data?;
+        let Some((x, y)) = atlas_data else {
+            self.overflows += 1;
+            return None;
+        };
         let entry_index = self.entries.len();
         self.entries.push(Entry {
             allocated: true,
@@ -287,6 +293,11 @@ fn fill(
     target_width: u16,
     target: &mut [u8],
 ) -> Option<()> {
+    // Empty glyphs (e.g. whitespace) have nothing to copy
+    if width == 0 {
+        return Some(());
+    }
+
     let channels = 4;
     let image_pitch = width as usize * channels;
     let buffer_pitch = target_width as usize * channels;
@@ -6,6 +6,7 @@ use crate::font_introspector::scale::{
     *,
 };
 use crate::font_introspector::zeno::Format;
+use crate::sugarloaf::stats::CacheStats;
 use core::borrow::Borrow;
 use core::hash::{Hash, Hasher};
 use rustc_hash::FxHashMap;
@@ -20,11 +21,19 @@ const SOURCES: &[Source] = &[
     Source::Outline,
 ];
 
+/// Steps per pixel of the sizes glyphs are rasterized at, so fractional
+/// font sizes at any scale factor match the metrics of the cells.
+const SIZE_QUANTIZATION: f32 = 4.0;
+
+/// Number of glyph lookups served by the cache and rasterized.
+pub type GlyphCacheStats = CacheStats;
+
 pub struct GlyphCache {
     scx: ScaleContext,
     fonts: FxHashMap<FontKey, FontEntry>,
     img: GlyphImage,
     max_height: u16,
+    stats: GlyphCacheStats,
 }
 
 impl GlyphCache {
@@ -34,9 +43,15 @@ impl GlyphCache {
             fonts: FxHashMap::default(),
             img: GlyphImage::new(),
             max_height: 0,
+            stats: GlyphCacheStats::default(),
         }
     }
 
+    #[inline]
+    pub fn stats(&self) -> GlyphCacheStats {
+        self.stats
+    }
+
     #[inline]
     pub fn session<'a>(
         &'a mut self,
@@ -46,8 +61,7 @@ impl GlyphCache {
         coords: &[i16],
         size: f32,
     ) -> GlyphCacheSession<'a> {
-        // let quant_size = (size * 32.) as u16;
-        let quant_size = size as u16;
+        let quant_size = (size * SIZE_QUANTIZATION).round() as u16;
         let entry = get_entry(&mut self.fonts, font, coords);
         GlyphCacheSession {
             font,
@@ -58,6 +72,7 @@ impl GlyphCache {
             scaled_image: &mut self.img,
             quant_size,
             scale_context: &mut self.scx,
+            stats: &mut self.stats,
         }
     }
 
@@ -100,6 +115,7 @@ pub struct GlyphCacheSession<'a> {
     quant_size: u16,
     #[allow(unused)]
     max_height: &'a u16,
+    stats: &'a mut GlyphCacheStats,
 }
 
 impl GlyphCacheSession<'_> {
@@ -115,10 +131,13 @@ impl GlyphCacheSession<'_> {
         };
         if let Some(entry) = self.entry.glyphs.get(&key) {
             if self.images.is_valid(entry.image) {
+                self.stats.hits += 1;
                 return Some(*entry);
             }
         }
 
+        self.stats.misses += 1;
+
         self.scaled_image.data.clear();
         let mut font_library_data = self.font_library.inner.lock();
         let enable_hint = font_library_data.hinting;
@@ -137,7 +156,7 @@ impl GlyphCacheSession<'_> {
                 // now ignores font hint information completely.
                 // .hint(!IS_MACOS)
                 .hint(enable_hint)
-                .size(self.quant_size.into())
+                .size(self.quant_size as f32 / SIZE_QUANTIZATION)
                 // .normalized_coords(coords)
                 .build();
 
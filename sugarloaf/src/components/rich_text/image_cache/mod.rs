@@ -5,7 +5,7 @@ pub mod glyph;
 use std::sync::Arc;
 
 pub use cache::ImageCache;
-pub use glyph::GlyphCache;
+pub use glyph::{GlyphCache, GlyphCacheStats};
 
 /// Identifier for an image in a cache.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
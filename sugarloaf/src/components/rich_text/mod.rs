@@ -3,6 +3,8 @@ mod compositor;
 mod image_cache;
 pub mod text;
 
+pub use image_cache::GlyphCacheStats;
+
 use crate::components::core::orthographic_projection;
 use crate::components::rich_text::compositor::{BatchOperation, LineCache};
 use crate::components::rich_text::image_cache::{GlyphCache, ImageCache};
@@ -10,8 +12,8 @@ use crate::context::Context;
 use crate::font::FontLibrary;
 use crate::layout::{BuilderStateUpdate, RichTextLayout, SugarDimensions};
 use crate::sugarloaf::graphics::GraphicRenderRequest;
-use crate::Graphics;
 use crate::RichTextLinesRange;
+use crate::{CacheStats, Graphics};
 use compositor::{Compositor, Rect, Vertex};
 use std::collections::HashSet;
 use std::{borrow::Cow, mem};
@@ -46,6 +48,17 @@ pub struct RichTextBrush {
     images: ImageCache,
     glyphs: GlyphCache,
     line_cache: LineCache,
+    stats: RichTextStats,
+}
+
+/// Drawing work done since the last [`RichTextBrush::take_stats`].
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct RichTextStats {
+    /// Hits reused the vertices of the line from a previous frame.
+    pub drawn_lines: CacheStats,
+    pub full_rebuilds: usize,
+    pub partial_rebuilds: usize,
+    pub atlas_overflows: u64,
 }
 
 impl RichTextBrush {
@@ -219,6 +232,7 @@ impl RichTextBrush {
 
         RichTextBrush {
             line_cache: LineCache::new(),
+            stats: RichTextStats::default(),
             layout_bind_group,
             layout_bind_group_layout,
             constant_bind_group,
@@ -252,12 +266,18 @@ impl RichTextBrush {
 
         for rich_text in &state.rich_texts {
             if let Some(rt) = state.content.get_state(&rich_text.id) {
+                // Lines moved by scrolling keep what was drawn for them
+                self.line_cache
+                    .scroll(rich_text.id, rt.scrolled, rt.lines.len());
+
                 // Check if this specific rich text needs cache invalidation
                 match &rt.last_update {
                     BuilderStateUpdate::Full => {
                         self.line_cache.clear_text_cache(rich_text.id);
+                        self.stats.full_rebuilds += 1;
                     }
                     BuilderStateUpdate::Partial(lines) => {
+                        self.stats.partial_rebuilds += lines.len();
                         for line in lines {
                             self.line_cache.clear_cache(rich_text.id, line);
                         }
@@ -389,8 +409,26 @@ impl RichTextBrush {
 
             // Calculate line height with modifier if available
             let line_height_without_mod = ascent + descent + leading;
-            let line_height_mod = rte_layout.map_or(1.0, |layout| layout.line_height);
-            let line_height = line_height_without_mod * line_height_mod;
+            let line_height_mod =
+                rte_layout.map_or(1.0, |layout| layout.effective_line_height());
+            let snap_to_pixels = rte_layout.is_some_and(|layout| layout.snap_to_pixels);
+            let line_height = if snap_to_pixels {
+                (line_height_without_mod * line_height_mod).round()
+            } else {
+                line_height_without_mod * line_height_mod
+            };
+            let baseline_offset = rte_layout.map_or(0.0, |layout| {
+                layout.baseline_offset * layout.dimensions.scale
+            });
+            // Glyphs are centered in cells widened by the letter spacing
+            let glyph_offset = rte_layout.map_or(0.0, |layout| {
+                layout
+                    .letter_spacing
+                    .glyph_offset(layout.dimensions.width, layout.dimensions.scale)
+            });
+
+            // Font of the last run and whether it is an emoji font
+            let mut emoji_font = (usize::MAX, false);
 
             let skip_count = selected_lines.map_or(0, |range| range.start);
             let take_count = selected_lines
@@ -407,17 +445,27 @@ impl RichTextBrush {
                 }
 
                 // Check if we can use the cache for this line
+                let line_top = line_y;
                 if !is_dimensions_only
                     && self.line_cache.has_cache(rich_text_id, line_idx)
-                    && self
-                        .line_cache
-                        .apply_cache(rich_text_id, line_idx, comp, graphics)
+                    && self.line_cache.apply_cache(
+                        rich_text_id,
+                        line_idx,
+                        line_top,
+                        comp,
+                        graphics,
+                    )
                 {
                     // Cache was applied successfully, skip to next line
+                    self.stats.drawn_lines.hits += 1;
                     line_y += line_height;
                     continue;
                 }
 
+                if !is_dimensions_only {
+                    self.stats.drawn_lines.misses += 1;
+                }
+
                 let mut px = x;
 
                 // Calculate baseline differently based on mode
@@ -432,7 +480,12 @@ impl RichTextBrush {
 
                 // Calculate padding
                 let padding_y = if line_height_mod > 1.0 {
-                    (line_height - line_height_without_mod) / 2.0
+                    let padding_y = (line_height - line_height_without_mod) / 2.0;
+                    if snap_to_pixels {
+                        padding_y.round()
+                    } else {
+                        padding_y
+                    }
                 } else {
                     0.0
                 };
@@ -445,10 +498,22 @@ impl RichTextBrush {
                     let font = run.span.font_id;
                     let char_width = run.span.width;
 
+                    // Emoji glyphs are drawn to take two cells, narrower spans
+                    // (e.g. with one cell emoji) scale them down to fit
+                    if emoji_font.0 != font {
+                        emoji_font =
+                            (font, font_library.inner.lock().get(&font).is_emoji);
+                    }
+                    let px_scale = if emoji_font.1 && char_width < 2.0 {
+                        char_width / 2.0
+                    } else {
+                        1.0
+                    };
+
                     let run_x = px;
                     for glyph in &run.glyphs {
                         let x = px;
-                        let y = py + padding_y;
+                        let y = py + padding_y + baseline_offset;
 
                         // Different advance calculation based on mode
                         if is_dimensions_only {
@@ -459,7 +524,7 @@ impl RichTextBrush {
 
                         glyphs.push(Glyph {
                             id: glyph.simple_data().0,
-                            x,
+                            x: x + glyph_offset,
                             y,
                         });
                     }
@@ -467,7 +532,7 @@ impl RichTextBrush {
                     // Create style with appropriate defaults
                     let style = TextRunStyle {
                         font_coords,
-                        font_size: run.size,
+                        font_size: run.size * px_scale,
                         color: run.span.color,
                         cursor: run.span.cursor,
                         drawable_char: run.span.drawable_char,
@@ -515,6 +580,7 @@ impl RichTextBrush {
                                     pos_y: style.topline - offset_y,
                                     width: None,
                                     height: None,
+                                    z_index: graphic.z_index,
                                 };
 
                                 graphics.top_layer.push(graphic_render_request);
@@ -553,8 +619,12 @@ impl RichTextBrush {
 
                 // Store line in cache if we're not in dimensions mode
                 if !is_dimensions_only {
-                    self.line_cache
-                        .store(rich_text_id, line_idx, line_operations);
+                    self.line_cache.store(
+                        rich_text_id,
+                        line_idx,
+                        line_top,
+                        line_operations,
+                    );
                 }
 
                 // Update line_y for line height modifier
@@ -581,6 +651,19 @@ impl RichTextBrush {
         self.glyphs = GlyphCache::new();
     }
 
+    #[inline]
+    pub fn take_stats(&mut self) -> RichTextStats {
+        RichTextStats {
+            atlas_overflows: std::mem::take(&mut self.images.overflows),
+            ..std::mem::take(&mut self.stats)
+        }
+    }
+
+    #[inline]
+    pub fn glyph_cache_stats(&self) -> GlyphCacheStats {
+        self.glyphs.stats()
+    }
+
     #[inline]
     pub fn render<'pass>(
         &'pass mut self,
@@ -1,18 +1,182 @@
 use crate::sugarloaf::{SugarloafWindow, SugarloafWindowSize};
-use crate::SugarloafRenderer;
+use crate::{Colorspace, SugarloafRenderer};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Failure to initialize the GPU context.
+#[derive(Debug)]
+pub enum ContextError {
+    Surface(wgpu::CreateSurfaceError),
+    Adapter(wgpu::RequestAdapterError),
+    Device(wgpu::RequestDeviceError),
+    /// The selected adapter is not able to present to the surface.
+    UnsupportedSurface(wgpu::AdapterInfo),
+}
+
+impl Display for ContextError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextError::Surface(err) => write!(f, "Unable to create surface: {err}"),
+            ContextError::Adapter(err) => {
+                write!(f, "Unable to find a graphics adapter: {err}")
+            }
+            ContextError::Device(err) => {
+                write!(f, "Unable to request a graphics device: {err}")
+            }
+            ContextError::UnsupportedSurface(info) => write!(
+                f,
+                "Graphics adapter {} ({:?}) does not support the window surface",
+                info.name, info.backend
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ContextError::Surface(err) => Some(err),
+            ContextError::Adapter(err) => Some(err),
+            ContextError::Device(err) => Some(err),
+            ContextError::UnsupportedSurface(_) => None,
+        }
+    }
+}
 
 pub struct Context<'a> {
     pub device: wgpu::Device,
-    pub surface: wgpu::Surface<'a>,
+    /// Surface of the window, `None` on headless contexts.
+    pub surface: Option<wgpu::Surface<'a>>,
     pub queue: wgpu::Queue,
+    /// Format used by the render pipelines.
     pub format: wgpu::TextureFormat,
+    /// Format of the surface, differs from `format` when rendering
+    /// to an extended range (HDR) surface.
+    pub surface_format: wgpu::TextureFormat,
+    pub colorspace: Colorspace,
+    /// Multiplier applied to the content on extended range surfaces.
+    pub hdr_brightness: f32,
     pub size: SugarloafWindowSize,
     pub scale: f32,
     alpha_mode: wgpu::CompositeAlphaMode,
+    present_mode: wgpu::PresentMode,
     pub adapter_info: wgpu::AdapterInfo,
     surface_caps: wgpu::SurfaceCapabilities,
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
+    power_preference: wgpu::PowerPreference,
+    adapter_name: Option<String>,
+    device_lost: Arc<AtomicBool>,
 }
 
+/// Returns the present mode if the surface supports it, otherwise `Fifo`
+/// which is always supported.
+fn supported_present_mode(
+    caps: &wgpu::SurfaceCapabilities,
+    present_mode: wgpu::PresentMode,
+) -> wgpu::PresentMode {
+    if caps.present_modes.contains(&present_mode) {
+        return present_mode;
+    }
+
+    tracing::warn!("present mode {present_mode:?} is not supported, using Fifo");
+    wgpu::PresentMode::Fifo
+}
+
+/// Requests an adapter able to present to the surface (if any), preferring
+/// the one whose name contains `adapter_name` if it is defined.
+fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface>,
+    power_preference: wgpu::PowerPreference,
+    adapter_name: Option<&str>,
+) -> Result<wgpu::Adapter, wgpu::RequestAdapterError> {
+    if let Some(name) = adapter_name {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let name = name.to_lowercase();
+            let adapter = instance
+                .enumerate_adapters(wgpu::Backends::all())
+                .into_iter()
+                .find(|adapter| {
+                    adapter.get_info().name.to_lowercase().contains(&name)
+                        && surface
+                            .is_none_or(|surface| adapter.is_surface_supported(surface))
+                });
+
+            if let Some(adapter) = adapter {
+                return Ok(adapter);
+            }
+        }
+
+        tracing::warn!(
+            "no adapter matching {name:?} is able to present, using power preference"
+        );
+    }
+
+    futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference,
+        compatible_surface: surface,
+        force_fallback_adapter: false,
+    }))
+}
+
+/// Requests a device, falling back to downlevel limits if the adapter
+/// does not support the features required by the filters.
+fn request_device(
+    adapter: &wgpu::Adapter,
+) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+    if let Ok(result) = futures::executor::block_on(adapter.request_device(
+        // ADDRESS_MODE_CLAMP_TO_BORDER is required for librashader
+        &wgpu::DeviceDescriptor {
+            required_features: wgpu::Features::empty()
+                | wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER,
+            ..Default::default()
+        },
+    )) {
+        Ok(result)
+    } else {
+        // These downlevel limits will allow the code to run on all possible hardware
+        futures::executor::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            memory_hints: wgpu::MemoryHints::Performance,
+            label: None,
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::downlevel_webgl2_defaults(),
+            ..Default::default()
+        }))
+    }
+}
+
+/// Flags the device as lost so it can be recreated on the next render.
+///
+/// Operations on a lost device report errors until it is recreated, these
+/// are logged instead of going through the default (fatal) handler.
+fn watch_device_lost(device: &wgpu::Device) -> Arc<AtomicBool> {
+    let device_lost = Arc::new(AtomicBool::new(false));
+
+    let flag = device_lost.clone();
+    device.set_device_lost_callback(move |reason, message| {
+        if reason != wgpu::DeviceLostReason::Destroyed {
+            tracing::error!("GPU device lost ({reason:?}): {message}");
+            flag.store(true, Ordering::Relaxed);
+        }
+    });
+
+    let flag = device_lost.clone();
+    device.on_uncaptured_error(Box::new(move |error| {
+        if flag.load(Ordering::Relaxed) {
+            tracing::warn!("wgpu error on lost device: {error}");
+        } else {
+            panic!("wgpu error: {error}");
+        }
+    }));
+
+    device_lost
+}
+
+const HDR_SURFACE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
 #[inline]
 #[cfg(not(target_os = "macos"))]
 fn find_best_texture_format(formats: &[wgpu::TextureFormat]) -> wgpu::TextureFormat {
@@ -56,7 +220,7 @@ impl Context<'_> {
     pub fn new<'a>(
         sugarloaf_window: SugarloafWindow,
         renderer_config: SugarloafRenderer,
-    ) -> Context<'a> {
+    ) -> Result<Context<'a>, ContextError> {
         // The backend can be configured using the `WGPU_BACKEND`
         // environment variable. If the variable is not set, the primary backend
         // will be used. The following values are allowed:
@@ -88,53 +252,46 @@ impl Context<'_> {
         let size = sugarloaf_window.size;
         let scale = sugarloaf_window.scale;
 
-        let surface: wgpu::Surface<'a> =
-            instance.create_surface(sugarloaf_window).unwrap();
-        let adapter = futures::executor::block_on(instance.request_adapter(
-            &wgpu::RequestAdapterOptions {
-                power_preference: renderer_config.power_preference,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            },
-        ))
-        .expect("Request adapter");
+        let surface: wgpu::Surface<'a> = instance
+            .create_surface(sugarloaf_window)
+            .map_err(ContextError::Surface)?;
+        let adapter = request_adapter(
+            &instance,
+            Some(&surface),
+            renderer_config.power_preference,
+            renderer_config.adapter.as_deref(),
+        )
+        .map_err(ContextError::Adapter)?;
 
         let adapter_info = adapter.get_info();
         tracing::info!("Selected adapter: {:?}", adapter_info);
 
         let surface_caps = surface.get_capabilities(&adapter);
+        if surface_caps.formats.is_empty() {
+            return Err(ContextError::UnsupportedSurface(adapter_info));
+        }
 
         #[cfg(target_os = "macos")]
-        let format = wgpu::TextureFormat::Bgra8Unorm;
+        let mut format = wgpu::TextureFormat::Bgra8Unorm;
         #[cfg(not(target_os = "macos"))]
-        let format = find_best_texture_format(surface_caps.formats.as_slice());
-
-        let (device, queue) = {
-            {
-                if let Ok(result) = futures::executor::block_on(adapter.request_device(
-                    // ADDRESS_MODE_CLAMP_TO_BORDER is required for librashader
-                    &wgpu::DeviceDescriptor {
-                        required_features: wgpu::Features::empty()
-                            | wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER,
-                        ..Default::default()
-                    },
-                )) {
-                    result
-                } else {
-                    // These downlevel limits will allow the code to run on all possible hardware
-                    futures::executor::block_on(adapter.request_device(
-                        &wgpu::DeviceDescriptor {
-                            memory_hints: wgpu::MemoryHints::Performance,
-                            label: None,
-                            required_features: wgpu::Features::empty(),
-                            required_limits: wgpu::Limits::downlevel_webgl2_defaults(),
-                            ..Default::default()
-                        },
-                    ))
-                    .expect("Request device")
-                }
+        let mut format = find_best_texture_format(surface_caps.formats.as_slice());
+        let mut surface_format = format;
+
+        // Extended range surfaces are presented as extended linear sRGB
+        // (EDR on Metal, scRGB on DX12 and Vulkan), the content is rendered
+        // into an intermediate texture and converted in a last pass.
+        if renderer_config.hdr {
+            if surface_caps.formats.contains(&HDR_SURFACE_FORMAT) {
+                format = wgpu::TextureFormat::Rgba8Unorm;
+                surface_format = HDR_SURFACE_FORMAT;
+                tracing::info!("Sugarloaf selected extended range surface");
+            } else {
+                tracing::warn!("extended range surface is not supported, ignoring hdr");
             }
-        };
+        }
+
+        let (device, queue) = request_device(&adapter).map_err(ContextError::Device)?;
+        let device_lost = watch_device_lost(&device);
 
         let alpha_mode = if surface_caps
             .alpha_modes
@@ -150,26 +307,33 @@ impl Context<'_> {
             wgpu::CompositeAlphaMode::Auto
         };
 
+        let present_mode =
+            supported_present_mode(&surface_caps, renderer_config.present_mode);
+
         surface.configure(
             &device,
             &wgpu::SurfaceConfiguration {
                 usage: Self::get_texture_usage(&surface_caps),
-                format,
+                format: surface_format,
                 width: size.width as u32,
                 height: size.height as u32,
                 view_formats: vec![],
                 alpha_mode,
-                present_mode: wgpu::PresentMode::Fifo,
+                present_mode,
                 desired_maximum_frame_latency: 2,
             },
         );
 
-        Context {
+        Ok(Context {
             device,
             queue,
-            surface,
+            surface: Some(surface),
             format,
+            surface_format,
+            colorspace: renderer_config.colorspace,
+            hdr_brightness: renderer_config.hdr_brightness,
             alpha_mode,
+            present_mode,
             size: SugarloafWindowSize {
                 width: size.width,
                 height: size.height,
@@ -177,23 +341,157 @@ impl Context<'_> {
             scale,
             adapter_info,
             surface_caps,
+            instance,
+            adapter,
+            power_preference: renderer_config.power_preference,
+            adapter_name: renderer_config.adapter,
+            device_lost,
+        })
+    }
+
+    /// Creates a context without a window surface, the content can only be
+    /// rendered into textures (e.g. for snapshot tests).
+    pub fn new_headless<'a>(
+        size: SugarloafWindowSize,
+        scale: f32,
+        renderer_config: SugarloafRenderer,
+    ) -> Result<Context<'a>, ContextError> {
+        let backend = wgpu::Backends::from_env().unwrap_or(renderer_config.backend);
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: backend,
+            ..Default::default()
+        });
+
+        let adapter = request_adapter(
+            &instance,
+            None,
+            renderer_config.power_preference,
+            renderer_config.adapter.as_deref(),
+        )
+        .map_err(ContextError::Adapter)?;
+
+        let adapter_info = adapter.get_info();
+        tracing::info!("Selected headless adapter: {:?}", adapter_info);
+
+        let (device, queue) = request_device(&adapter).map_err(ContextError::Device)?;
+        let device_lost = watch_device_lost(&device);
+
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let surface_caps = wgpu::SurfaceCapabilities {
+            formats: vec![format],
+            present_modes: vec![wgpu::PresentMode::Fifo],
+            alpha_modes: vec![wgpu::CompositeAlphaMode::Opaque],
+            usages: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+        };
+
+        Ok(Context {
+            device,
+            queue,
+            surface: None,
+            format,
+            surface_format: format,
+            colorspace: renderer_config.colorspace,
+            hdr_brightness: renderer_config.hdr_brightness,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            present_mode: wgpu::PresentMode::Fifo,
+            size,
+            scale,
+            adapter_info,
+            surface_caps,
+            instance,
+            adapter,
+            power_preference: renderer_config.power_preference,
+            adapter_name: renderer_config.adapter,
+            device_lost,
+        })
+    }
+
+    /// Returns true if the content is converted to an extended range surface.
+    #[inline]
+    pub fn is_hdr(&self) -> bool {
+        self.surface_format != self.format
+    }
+
+    /// Returns true if the device was lost, for example after a driver
+    /// reset, and needs to be recreated.
+    #[inline]
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// Creates a new device and queue, requesting a new adapter if the
+    /// current one is not able to create it anymore. All the resources
+    /// created with the previous device need to be recreated.
+    pub fn recreate_device(&mut self) -> Result<(), ContextError> {
+        let (device, queue) = match request_device(&self.adapter) {
+            Ok(result) => result,
+            Err(err) => {
+                tracing::warn!("unable to recreate device with current adapter: {err}");
+
+                let adapter = request_adapter(
+                    &self.instance,
+                    self.surface.as_ref(),
+                    self.power_preference,
+                    self.adapter_name.as_deref(),
+                )
+                .map_err(ContextError::Adapter)?;
+
+                if let Some(surface) = &self.surface {
+                    let surface_caps = surface.get_capabilities(&adapter);
+                    if !surface_caps.formats.contains(&self.surface_format) {
+                        return Err(ContextError::UnsupportedSurface(adapter.get_info()));
+                    }
+                    self.surface_caps = surface_caps;
+                }
+
+                let result = request_device(&adapter).map_err(ContextError::Device)?;
+                self.adapter_info = adapter.get_info();
+                self.adapter = adapter;
+                result
+            }
+        };
+
+        tracing::info!("GPU device recreated on {:?}", self.adapter_info);
+
+        self.device_lost = watch_device_lost(&device);
+        self.device = device;
+        self.queue = queue;
+        self.resize(self.size.width as u32, self.size.height as u32);
+
+        Ok(())
+    }
+
+    /// Reconfigures the surface with the present mode if it changed.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        let present_mode = supported_present_mode(&self.surface_caps, present_mode);
+        if self.present_mode == present_mode {
+            return;
         }
+
+        self.present_mode = present_mode;
+        self.resize(self.size.width as u32, self.size.height as u32);
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
         self.size.width = width as f32;
         self.size.height = height as f32;
 
-        self.surface.configure(
+        let Some(surface) = &self.surface else {
+            return;
+        };
+
+        surface.configure(
             &self.device,
             &wgpu::SurfaceConfiguration {
                 usage: Self::get_texture_usage(&self.surface_caps),
-                format: self.format,
+                format: self.surface_format,
                 width,
                 height,
                 view_formats: vec![],
                 alpha_mode: self.alpha_mode,
-                present_mode: wgpu::PresentMode::Fifo,
+                present_mode: self.present_mode,
                 desired_maximum_frame_latency: 2,
             },
         );
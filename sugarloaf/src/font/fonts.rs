@@ -1,7 +1,101 @@
 use crate::font::DEFAULT_FONT_FAMILY;
+use crate::schema_aliases;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+/// Space added to the width of the cells, configured as pixels (`1.5`) or
+/// as a percentage of the font advance (`"10%"`). Negative values narrow
+/// the cells.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "LetterSpacingValue", into = "LetterSpacingValue")]
+pub enum LetterSpacing {
+    Pixels(f32),
+    Percent(f32),
+}
+
+impl Default for LetterSpacing {
+    fn default() -> Self {
+        LetterSpacing::Pixels(0.0)
+    }
+}
+
+impl LetterSpacing {
+    /// Width of the cells for the advance of the font, both scaled.
+    #[inline]
+    pub fn cell_width(self, advance: f32, scale: f32) -> f32 {
+        let width = match self {
+            LetterSpacing::Pixels(pixels) => advance + pixels * scale,
+            LetterSpacing::Percent(percent) => advance * (1.0 + percent / 100.0),
+        };
+        width.round().max(1.0)
+    }
+
+    /// Offset of the glyphs to center them in cells of the given width.
+    #[inline]
+    pub fn glyph_offset(self, cell_width: f32, scale: f32) -> f32 {
+        let spacing = match self {
+            LetterSpacing::Pixels(pixels) => pixels * scale,
+            LetterSpacing::Percent(percent) => {
+                cell_width - cell_width / (1.0 + percent / 100.0)
+            }
+        };
+        (spacing / 2.0).round()
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum LetterSpacingValue {
+    Pixels(f32),
+    Text(String),
+}
+
+impl TryFrom<LetterSpacingValue> for LetterSpacing {
+    type Error = String;
+
+    fn try_from(value: LetterSpacingValue) -> Result<Self, Self::Error> {
+        match value {
+            LetterSpacingValue::Pixels(pixels) => Ok(LetterSpacing::Pixels(pixels)),
+            LetterSpacingValue::Text(text) => text
+                .trim()
+                .strip_suffix('%')
+                .and_then(|percent| percent.trim().parse().ok())
+                .filter(|percent| *percent > -100.0)
+                .map(LetterSpacing::Percent)
+                .ok_or_else(|| {
+                    format!("letter-spacing must be pixels or a percentage, got {text:?}")
+                }),
+        }
+    }
+}
+
+impl From<LetterSpacing> for LetterSpacingValue {
+    fn from(value: LetterSpacing) -> Self {
+        match value {
+            LetterSpacing::Pixels(pixels) => LetterSpacingValue::Pixels(pixels),
+            LetterSpacing::Percent(percent) => {
+                LetterSpacingValue::Text(format!("{percent}%"))
+            }
+        }
+    }
+}
+
+/// Unit of the font size, points are 1/72 of an inch and pixels 1/96, both
+/// multiplied by the scale factor of the display.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[schemars(transform = schema_aliases("px pt"))]
+pub enum FontSizeUnit {
+    #[default]
+    #[serde(alias = "px")]
+    Px,
+    #[serde(alias = "pt")]
+    Pt,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
+#[schemars(transform = schema_aliases("normal italic"))]
 pub enum SugarloafFontStyle {
     #[default]
     #[serde(alias = "normal")]
@@ -10,7 +104,7 @@ pub enum SugarloafFontStyle {
     Italic,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub enum SugarloafFontWidth {
     UltraCondensed,
     ExtraCondensed,
@@ -24,15 +118,17 @@ pub enum SugarloafFontWidth {
     UltraExpanded,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct SugarloafFont {
     #[serde(default = "default_font_family")]
     pub family: String,
     #[serde(default = "Option::default")]
+    #[schemars(default)]
     pub weight: Option<u16>,
     #[serde(default = "SugarloafFontStyle::default")]
     pub style: SugarloafFontStyle,
     #[serde(default = "Option::default")]
+    #[schemars(default)]
     pub width: Option<SugarloafFontWidth>,
 }
 
@@ -65,7 +161,7 @@ pub fn default_bool_true() -> bool {
     true
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct SymbolMap {
     pub start: String,
     pub end: String,
@@ -113,15 +209,40 @@ pub fn default_font_bold_italic() -> SugarloafFont {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub struct SugarloafFonts {
     #[serde(default = "default_font_size")]
     pub size: f32,
+    #[serde(default = "FontSizeUnit::default", rename = "size-unit")]
+    pub size_unit: FontSizeUnit,
+    /// Multiplier of the font line height, takes precedence over the top
+    /// level `line-height`.
+    #[serde(default = "Option::default", rename = "line-height")]
+    #[schemars(default)]
+    pub line_height: Option<f32>,
+    /// Pixels the glyphs are moved down within their cells, negative values
+    /// move them up.
+    #[serde(default = "f32::default", rename = "baseline-offset")]
+    pub baseline_offset: f32,
+    #[serde(default = "LetterSpacing::default", rename = "letter-spacing")]
+    pub letter_spacing: LetterSpacing,
+    /// Adjusts the line height so lines are whole device pixels tall,
+    /// keeping box drawing and powerline glyphs sharp at fractional scales.
+    #[serde(default = "default_bool_true", rename = "snap-to-pixels")]
+    pub snap_to_pixels: bool,
     #[serde(default = "default_bool_true")]
     pub hinting: bool,
     #[serde(default = "Option::default")]
+    #[schemars(default)]
     pub features: Option<Vec<String>>,
+    /// Enables the `zero` feature, drawing zero with a slash or a dot.
+    #[serde(default = "bool::default", rename = "slashed-zero")]
+    pub slashed_zero: bool,
+    /// Enables the `tnum` feature, drawing all figures with the same width.
+    #[serde(default = "bool::default", rename = "tabular-numbers")]
+    pub tabular_numbers: bool,
     #[serde(default = "Option::default")]
+    #[schemars(default)]
     pub family: Option<String>,
     #[serde(default = "default_font_regular")]
     pub regular: SugarloafFont,
@@ -132,16 +253,20 @@ pub struct SugarloafFonts {
     #[serde(default = "default_font_italic")]
     pub italic: SugarloafFont,
     #[serde(default = "Option::default")]
+    #[schemars(default)]
     pub emoji: Option<SugarloafFont>,
     #[serde(default = "Vec::default")]
+    #[schemars(default)]
     pub extras: Vec<SugarloafFont>,
     #[serde(default = "default_bool_true", rename = "use-drawable-chars")]
     pub use_drawable_chars: bool,
     #[serde(default = "Option::default", rename = "symbol-map")]
+    #[schemars(default)]
     pub symbol_map: Option<Vec<SymbolMap>>,
     #[serde(default = "bool::default", rename = "disable-warnings-not-found")]
     pub disable_warnings_not_found: bool,
     #[serde(default = "Option::default", rename = "additional-dirs")]
+    #[schemars(default)]
     pub additional_dirs: Option<Vec<String>>,
 }
 
@@ -155,12 +280,48 @@ pub fn parse_unicode(input: &str) -> Option<char> {
     None
 }
 
+impl SugarloafFonts {
+    /// Font size in pixels, before the scale factor of the display.
+    #[inline]
+    pub fn size_px(&self) -> f32 {
+        match self.size_unit {
+            FontSizeUnit::Px => self.size,
+            FontSizeUnit::Pt => self.size * 96.0 / 72.0,
+        }
+    }
+
+    /// OpenType features used for shaping, the configured ones followed by
+    /// the ones enabled by the shorthand toggles.
+    pub fn shaping_features(&self) -> Option<Vec<String>> {
+        let mut features = self.features.clone().unwrap_or_default();
+        let toggles = [("zero", self.slashed_zero), ("tnum", self.tabular_numbers)];
+        for (feature, enabled) in toggles {
+            if enabled && !features.iter().any(|existing| existing == feature) {
+                features.push(feature.to_string());
+            }
+        }
+
+        if features.is_empty() {
+            None
+        } else {
+            Some(features)
+        }
+    }
+}
+
 impl Default for SugarloafFonts {
     fn default() -> SugarloafFonts {
         SugarloafFonts {
             features: None,
+            slashed_zero: false,
+            tabular_numbers: false,
             hinting: true,
             size: default_font_size(),
+            size_unit: FontSizeUnit::default(),
+            line_height: None,
+            baseline_offset: 0.0,
+            letter_spacing: LetterSpacing::default(),
+            snap_to_pixels: true,
             family: None,
             emoji: None,
             regular: default_font_regular(),
@@ -8,6 +8,7 @@ pub const FONT_ID_REGULAR: usize = 0;
 
 use crate::font::constants::*;
 use crate::font::fonts::{parse_unicode, SugarloafFontStyle, SugarloafFontWidth};
+use crate::font_introspector::shape::ShapeContext;
 use crate::font_introspector::text::cluster::Parser;
 use crate::font_introspector::text::cluster::Token;
 use crate::font_introspector::text::cluster::{CharCluster, Status};
@@ -199,6 +200,37 @@ impl FontLibraryData {
         Some((0, false))
     }
 
+    /// Emoji font that shapes the pair of regional indicators into a single
+    /// flag glyph, `None` if no emoji font has the flag.
+    pub fn find_flag_font_match(&mut self, flag: [char; 2]) -> Option<usize> {
+        let content: String = flag.iter().collect();
+        let mut shape_context = ShapeContext::new();
+
+        for font_id in 0..self.inner.len() {
+            if !self.inner.get(&font_id).is_some_and(|font| font.is_emoji) {
+                continue;
+            }
+
+            let Some(font) = self.get_data(&font_id) else {
+                continue;
+            };
+            let charmap = font.charmap();
+            if flag.iter().any(|ch| charmap.map(*ch) == 0) {
+                continue;
+            }
+
+            let mut shaper = shape_context.builder(font).script(Script::Latin).build();
+            shaper.add_str(&content);
+            let mut glyphs = 0;
+            shaper.shape_with(|cluster| glyphs += cluster.glyphs.len());
+            if glyphs == 1 {
+                return Some(font_id);
+            }
+        }
+
+        None
+    }
+
     #[inline]
     pub fn insert(&mut self, font_data: FontData) {
         self.inner.insert(self.inner.len(), font_data);
@@ -242,6 +274,31 @@ impl FontLibraryData {
         None
     }
 
+    /// Same as `get_data` but returns an owned handle to the font data,
+    /// so it can be used without holding the library lock (e.g: shaping
+    /// on worker threads).
+    pub fn get_shared_data(
+        &mut self,
+        font_id: &usize,
+    ) -> Option<(SharedData, u32, CacheKey)> {
+        let font = self.inner.get(font_id)?;
+        if let Some(data) = &font.data {
+            return Some((data.clone(), font.offset, font.key));
+        }
+
+        if !self.stash.contains(font_id) {
+            if let Some(path) = &font.path {
+                if let Some(raw_data) = load_from_font_source(path) {
+                    self.stash.put(*font_id, SharedData::new(raw_data));
+                }
+            }
+        }
+
+        self.stash
+            .get(font_id)
+            .map(|data| (data.clone(), font.offset, font.key))
+    }
+
     #[inline]
     pub fn get_mut(&mut self, font_id: &usize) -> Option<&mut FontData> {
         self.inner.get_mut(font_id)
@@ -259,6 +316,10 @@ impl FontLibraryData {
 
     #[cfg(not(target_arch = "wasm32"))]
     pub fn load(&mut self, mut spec: SugarloafFonts) -> Vec<SugarloafFont> {
+        let span = tracing::span!(tracing::Level::INFO, "font_library_load");
+        let _guard = span.enter();
+        let started = std::time::Instant::now();
+
         // Configure hinting through spec
         self.hinting = spec.hinting;
 
@@ -449,6 +510,12 @@ impl FontLibraryData {
             self.symbol_maps = Some(symbol_maps);
         }
 
+        tracing::info!(
+            fonts = self.inner.len(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "fonts loaded"
+        );
+
         if spec.disable_warnings_not_found {
             vec![]
         } else {
@@ -11,13 +11,16 @@ use crate::font_introspector::shape::ShapeContext;
 use crate::font_introspector::text::Script;
 use crate::font_introspector::Metrics;
 use crate::layout::render_data::RenderData;
+use crate::layout::shaping_pool::{self, ShapingJob, ShapingTask};
 use crate::layout::RichTextLayout;
-use crate::Graphics;
+use crate::{CacheStats, Graphics};
 use lru::LruCache;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHasher};
 use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 use crate::font_introspector::Attributes;
 use crate::font_introspector::Setting;
@@ -35,12 +38,21 @@ impl RichTextCounter {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FragmentData {
     pub content: String,
     pub style: FragmentStyle,
 }
 
+/// Clickable text of a rich text line, the range is measured in cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RichTextLink {
+    pub line: usize,
+    pub start: f32,
+    pub end: f32,
+    pub url: String,
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct BuilderLine {
     pub fragments: Vec<FragmentData>,
@@ -61,19 +73,96 @@ pub struct BuilderState {
     pub lines: Vec<BuilderLine>,
     pub vars: FontSettingCache<f32>,
     pub last_update: BuilderStateUpdate,
+    /// Lines moved down (up when negative) by [`BuilderState::scroll`]
+    /// since the last render, the cached lines are moved along with them.
+    pub scrolled: i32,
     metrics_cache: MetricsCache,
     scaled_font_size: f32,
     pub layout: RichTextLayout,
+    pub links: Vec<RichTextLink>,
+    /// Fragment buffers released by cleared lines, reused by the next
+    /// fragments instead of allocating a new `String` for each one.
+    fragments_pool: Vec<String>,
 }
 
+/// Maximum amount of fragment buffers kept around for reuse.
+const FRAGMENTS_POOL_CAPACITY: usize = 4096;
+
 impl BuilderState {
+    #[inline]
+    fn recycle_fragments(pool: &mut Vec<String>, fragments: &mut Vec<FragmentData>) {
+        let available = FRAGMENTS_POOL_CAPACITY.saturating_sub(pool.len());
+        pool.extend(
+            fragments
+                .drain(..)
+                .take(available)
+                .map(|fragment| fragment.content),
+        );
+    }
+    #[inline]
+    pub fn push_fragment(&mut self, line_idx: usize, text: &str, style: FragmentStyle) {
+        if let Some(line) = self.lines.get_mut(line_idx) {
+            let mut content = self.fragments_pool.pop().unwrap_or_default();
+            content.clear();
+            content.push_str(text);
+            line.fragments.push(FragmentData { content, style });
+        }
+    }
+    #[inline]
+    pub fn clear_line(&mut self, line_idx: usize) {
+        if let Some(line) = self.lines.get_mut(line_idx) {
+            Self::recycle_fragments(&mut self.fragments_pool, &mut line.fragments);
+            line.render_data.clear();
+        }
+        self.links.retain(|link| link.line != line_idx);
+    }
     #[inline]
     pub fn new_line_at(&mut self, pos: usize) {
         self.lines.insert(pos, BuilderLine::default());
+        for link in self.links.iter_mut().filter(|link| link.line >= pos) {
+            link.line += 1;
+        }
     }
     #[inline]
     pub fn remove_line_at(&mut self, pos: usize) {
         self.lines.remove(pos);
+        self.links.retain(|link| link.line != pos);
+        for link in self.links.iter_mut().filter(|link| link.line > pos) {
+            link.line -= 1;
+        }
+    }
+    /// Moves the lines `delta` positions down (up when negative), the
+    /// lines moved in from the other edge are left empty to be rebuilt.
+    #[inline]
+    pub fn scroll(&mut self, delta: i32) {
+        let len = self.lines.len();
+        let shift = (delta.unsigned_abs() as usize).min(len);
+        if shift == 0 {
+            return;
+        }
+
+        let exposed = if delta > 0 {
+            self.lines.rotate_right(shift);
+            0..shift
+        } else {
+            self.lines.rotate_left(shift);
+            len - shift..len
+        };
+        for line in &mut self.lines[exposed] {
+            Self::recycle_fragments(&mut self.fragments_pool, &mut line.fragments);
+            line.render_data.clear();
+        }
+
+        self.links.retain_mut(|link| {
+            match link.line.checked_add_signed(delta as isize) {
+                Some(line) if line < len => {
+                    link.line = line;
+                    true
+                }
+                _ => false,
+            }
+        });
+        self.scrolled += delta;
     }
     #[inline]
     pub fn from_layout(layout: &RichTextLayout) -> Self {
@@ -94,6 +183,7 @@ impl BuilderState {
     #[inline]
     pub fn mark_clean(&mut self) {
         self.last_update = BuilderStateUpdate::Noop;
+        self.scrolled = 0;
     }
     #[inline]
     pub fn mark_dirty(&mut self) {
@@ -115,9 +205,14 @@ impl BuilderState {
     }
     #[inline]
     pub fn clear(&mut self) {
+        for line in &mut self.lines {
+            Self::recycle_fragments(&mut self.fragments_pool, &mut line.fragments);
+        }
         self.lines.clear();
+        self.links.clear();
         self.vars.clear();
         self.last_update = BuilderStateUpdate::Full;
+        self.scrolled = 0;
     }
     #[inline]
     pub fn rescale(&mut self, scale_factor: f32) {
@@ -295,6 +390,17 @@ impl Default for FragmentStyle {
     }
 }
 
+/// Time a build waits for the shaping pool, the lines still waiting for
+/// their fragments afterwards are built on a later frame.
+const SHAPING_BUDGET: Duration = Duration::from_millis(4);
+
+/// Fragments of a state being shaped on the shaping pool.
+struct PendingShaping {
+    state_id: usize,
+    task: ShapingTask,
+    in_flight: FxHashMap<usize, HashSet<String>>,
+}
+
 /// Context for paragraph layout.
 pub struct Content {
     fonts: FontLibrary,
@@ -302,8 +408,15 @@ pub struct Content {
     scx: ShapeContext,
     pub states: FxHashMap<usize, BuilderState>,
     word_cache: WordCache,
+    line_cache: ShapedLineCache,
+    shaped_line_stats: CacheStats,
+    shaping: Vec<PendingShaping>,
+    /// Jobs finished since the last `apply_shaped`.
+    has_received: bool,
+    shaping_budget: Duration,
     selector: Option<usize>,
     counter: RichTextCounter,
+    hovered_link: Option<String>,
 }
 
 impl Content {
@@ -314,9 +427,15 @@ impl Content {
             scx: ShapeContext::new(),
             states: FxHashMap::default(),
             word_cache: WordCache::new(),
+            line_cache: ShapedLineCache::new(),
+            shaped_line_stats: CacheStats::default(),
+            shaping: vec![],
+            has_received: false,
+            shaping_budget: SHAPING_BUDGET,
             font_features: vec![],
             selector: None,
             counter: RichTextCounter::new(),
+            hovered_link: None,
         }
     }
 
@@ -336,6 +455,8 @@ impl Content {
     pub fn set_font_library(&mut self, font_library: &FontLibrary) {
         self.fonts = font_library.clone();
         self.word_cache = WordCache::new();
+        self.shaping.clear();
+        self.line_cache.clear();
         for line in self.states.values_mut() {
             line.metrics_cache = MetricsCache::default();
         }
@@ -357,6 +478,7 @@ impl Content {
         font_features: Vec<crate::font_introspector::Setting<u16>>,
     ) {
         self.font_features = font_features;
+        self.line_cache.clear();
     }
 
     #[inline]
@@ -372,6 +494,13 @@ impl Content {
         self.states.remove(rich_text_id);
     }
 
+    /// Lines shaped since the last call, hits reused the shaping of an
+    /// identical line.
+    #[inline]
+    pub fn take_shaped_line_stats(&mut self) -> CacheStats {
+        std::mem::take(&mut self.shaped_line_stats)
+    }
+
     #[inline]
     pub fn mark_states_clean(&mut self) {
         for state in self.states.values_mut() {
@@ -401,7 +530,10 @@ impl Content {
                 &mut Graphics::default(),
             ) {
                 rte.layout.dimensions.height = dimension.height;
-                rte.layout.dimensions.width = dimension.width;
+                rte.layout.dimensions.width = rte
+                    .layout
+                    .letter_spacing
+                    .cell_width(dimension.width, rte.layout.dimensions.scale);
             }
         }
     }
@@ -454,14 +586,22 @@ impl Content {
         self
     }
 
+    #[inline]
+    pub fn scroll_lines(&mut self, delta: i32) -> &mut Content {
+        if let Some(selector) = self.selector {
+            if let Some(state) = self.states.get_mut(&selector) {
+                state.scroll(delta);
+            }
+        }
+
+        self
+    }
+
     #[inline]
     pub fn clear_line(&mut self, line_to_clear: usize) -> &mut Content {
         if let Some(selector) = self.selector {
             if let Some(state) = self.states.get_mut(&selector) {
-                if let Some(line) = state.lines.get_mut(line_to_clear) {
-                    line.fragments.clear();
-                    line.render_data.clear();
-                }
+                state.clear_line(line_to_clear);
             }
         }
 
@@ -516,12 +656,7 @@ impl Content {
         if let Some(selector) = self.selector {
             if let Some(state) = self.states.get_mut(&selector) {
                 state.mark_line_dirty(line_idx);
-                if let Some(line) = state.lines.get_mut(line_idx) {
-                    line.fragments.push(FragmentData {
-                        content: text.to_string(),
-                        style,
-                    });
-                }
+                state.push_fragment(line_idx, text, style);
             }
         }
 
@@ -537,17 +672,67 @@ impl Content {
     ) -> &mut Content {
         if let Some(state) = self.states.get_mut(id) {
             let current_line = state.current_line();
-            if let Some(line) = &mut state.lines.get_mut(current_line) {
-                line.fragments.push(FragmentData {
-                    content: text.to_string(),
-                    style,
+            state.push_fragment(current_line, text, style);
+        }
+
+        self
+    }
+
+    /// Adds a text fragment that can be clicked to open `url`, it is
+    /// underlined while the link is hovered.
+    pub fn add_link(
+        &mut self,
+        text: &str,
+        url: &str,
+        mut style: FragmentStyle,
+    ) -> &mut Content {
+        let Some(selector) = self.selector else {
+            return self;
+        };
+
+        if self.hovered_link.as_deref() == Some(url) {
+            style.decoration = Some(FragmentStyleDecoration::Underline(UnderlineInfo {
+                offset: -1.0,
+                size: 1.0,
+                is_doubled: false,
+                shape: UnderlineShape::Regular,
+            }));
+            style.decoration_color = Some(style.color);
+        }
+
+        if let Some(state) = self.states.get_mut(&selector) {
+            let current_line = state.current_line();
+            if let Some(line) = state.lines.get(current_line) {
+                let start: f32 = line
+                    .fragments
+                    .iter()
+                    .map(|fragment| {
+                        fragment.content.chars().count() as f32 * fragment.style.width
+                    })
+                    .sum();
+                state.links.push(RichTextLink {
+                    line: current_line,
+                    start,
+                    end: start + text.chars().count() as f32 * style.width,
+                    url: url.to_owned(),
                 });
             }
+            state.push_fragment(current_line, text, style);
         }
 
         self
     }
 
+    #[inline]
+    pub fn hovered_link(&self) -> Option<&str> {
+        self.hovered_link.as_deref()
+    }
+
+    #[inline]
+    pub fn set_hovered_link(&mut self, url: Option<String>) {
+        self.hovered_link = url;
+    }
+
     // Helper function to process a single line that avoids borrow issues
     fn process_line(&mut self, state_id: usize, line_number: usize) {
         // Get all needed data while borrowing parts of self separately
@@ -571,6 +756,33 @@ impl Content {
         // Process fragments in the line
         let line = &mut state.lines[line_number];
 
+        // Lines that only moved (e.g: scroll) keep the same fragments,
+        // so the shaped result can be reused as a whole
+        let line_key = ShapedLineCache::key(&line.fragments, scaled_font_size);
+        if let Some(line_key) = line_key {
+            if let Some(render_data) =
+                self.line_cache
+                    .get(line_key, &line.fragments, scaled_font_size)
+            {
+                line.render_data.clone_from(render_data);
+                for run in &mut line.render_data.runs {
+                    run.line = line_number as u32;
+                }
+                self.shaped_line_stats.hits += 1;
+                return;
+            }
+        }
+
+        // Built by `Content::apply_shaped` once the pool shaped them
+        if Self::is_shaping(&self.shaping, &line.fragments) {
+            line.render_data.clear();
+            return;
+        }
+
+        if line_key.is_some() {
+            self.shaped_line_stats.misses += 1;
+        }
+
         // Process each fragment
         for fragment_idx in 0..line.fragments.len() {
             // Get a reference to the current fragment
@@ -636,6 +848,183 @@ impl Content {
                 }
             }
         }
+
+        if let Some(line_key) = line_key {
+            self.line_cache.put(
+                line_key,
+                &line.fragments,
+                scaled_font_size,
+                &line.render_data,
+            );
+        }
+    }
+
+    #[inline]
+    fn is_shaping(shaping: &[PendingShaping], fragments: &[FragmentData]) -> bool {
+        !shaping.is_empty()
+            && fragments.iter().any(|fragment| {
+                shaping.iter().any(|pending| {
+                    pending
+                        .in_flight
+                        .get(&fragment.style.font_id)
+                        .is_some_and(|contents| contents.contains(&fragment.content))
+                })
+            })
+    }
+
+    /// Whether fragments are still being shaped on the shaping pool, the
+    /// lines waiting for them are built by [`Content::apply_shaped`].
+    #[inline]
+    pub fn has_pending_shaping(&self) -> bool {
+        !self.shaping.is_empty()
+    }
+
+    /// Stores the fragments shaped on the pool until `deadline` in the word
+    /// cache, any job finished, shaped or not, is noted in `has_received`.
+    fn receive_shaped(&mut self, deadline: Instant) {
+        for pending in &mut self.shaping {
+            for (job, shaped) in pending.task.receive(deadline) {
+                self.has_received = true;
+                if let Some(contents) = pending.in_flight.get_mut(&job.font_id) {
+                    contents.remove(&job.content);
+                }
+                // A fragment that failed is shaped again by the line itself
+                let Some(shaped) = shaped else {
+                    continue;
+                };
+
+                if let Some(state) = self.states.get_mut(&pending.state_id) {
+                    state
+                        .metrics_cache
+                        .inner
+                        .entry(job.font_id)
+                        .or_insert(shaped.metrics);
+                }
+                self.word_cache
+                    .put(job.font_id, job.content, shaped.clusters);
+            }
+        }
+        self.shaping.retain(|pending| !pending.task.is_done());
+    }
+
+    /// Builds the lines which were waiting for the shaping pool and got
+    /// all their fragments since the last frame, returns true if any was.
+    ///
+    /// Every state is checked, a line may wait on fragments from the job
+    /// of another state that had the same text.
+    pub fn apply_shaped(&mut self) -> bool {
+        self.receive_shaped(Instant::now());
+        if !std::mem::take(&mut self.has_received) {
+            return false;
+        }
+
+        let mut has_built = false;
+        let state_ids: Vec<usize> = self.states.keys().copied().collect();
+        for state_id in state_ids {
+            let Some(state) = self.states.get(&state_id) else {
+                continue;
+            };
+            let waiting: Vec<usize> = state
+                .lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| {
+                    line.render_data.is_empty()
+                        && !line.fragments.is_empty()
+                        && !Self::is_shaping(&self.shaping, &line.fragments)
+                })
+                .map(|(line_number, _)| line_number)
+                .collect();
+
+            for line_number in waiting {
+                self.process_line(state_id, line_number);
+                if let Some(state) = self.states.get_mut(&state_id) {
+                    state.mark_line_dirty(line_number);
+                }
+                has_built = true;
+            }
+        }
+        has_built
+    }
+
+    /// Shapes on the shaping pool every fragment that is not cached yet,
+    /// as long as there are enough of them to pay off the dispatch. The
+    /// results which take longer than the budget are left to a later frame.
+    fn preshape(&mut self, state_id: usize) {
+        if !shaping_pool::is_available() {
+            return;
+        }
+
+        let state = match self.states.get(&state_id) {
+            Some(state) => state,
+            None => return,
+        };
+        let scaled_font_size = state.scaled_font_size;
+
+        let mut jobs = vec![];
+        let mut seen: FxHashMap<usize, HashSet<&str>> = FxHashMap::default();
+        for line in &state.lines {
+            let line_key = ShapedLineCache::key(&line.fragments, scaled_font_size);
+            if let Some(line_key) = line_key {
+                if self
+                    .line_cache
+                    .get(line_key, &line.fragments, scaled_font_size)
+                    .is_some()
+                {
+                    continue;
+                }
+            }
+
+            for fragment in &line.fragments {
+                let font_id = fragment.style.font_id;
+                if self.word_cache.get(&font_id, &fragment.content).is_some()
+                    || Self::is_shaping(&self.shaping, std::slice::from_ref(fragment))
+                    || !seen.entry(font_id).or_default().insert(&fragment.content)
+                {
+                    continue;
+                }
+
+                jobs.push(ShapingJob {
+                    font_id,
+                    content: fragment.content.clone(),
+                    vars: state.vars.get(fragment.style.font_vars).to_vec(),
+                });
+            }
+        }
+
+        if jobs.len() < shaping_pool::PARALLEL_SHAPING_THRESHOLD {
+            return;
+        }
+
+        let mut fonts = FxHashMap::default();
+        {
+            let font_library = &mut self.fonts.inner.lock();
+            for font_id in seen.keys() {
+                if let Some(font) = font_library.get_shared_data(font_id) {
+                    fonts.insert(*font_id, font);
+                }
+            }
+        }
+
+        let mut in_flight: FxHashMap<usize, HashSet<String>> = FxHashMap::default();
+        for job in &jobs {
+            in_flight
+                .entry(job.font_id)
+                .or_default()
+                .insert(job.content.clone());
+        }
+        let task = shaping_pool::spawn(
+            jobs,
+            fonts,
+            scaled_font_size,
+            self.font_features.clone(),
+        );
+        self.shaping.push(PendingShaping {
+            state_id,
+            task,
+            in_flight,
+        });
+        self.receive_shaped(Instant::now() + self.shaping_budget);
     }
 
     #[inline]
@@ -646,7 +1035,9 @@ impl Content {
 
             if let Some(state) = self.states.get_mut(&state_id) {
                 state.mark_dirty();
-                for line_number in 0..state.lines.len() {
+                let lines_len = state.lines.len();
+                self.preshape(state_id);
+                for line_number in 0..lines_len {
                     self.process_line(state_id, line_number);
                 }
             }
@@ -700,24 +1091,30 @@ impl WordCache {
         self.stash.push(glyph_cluster.into());
     }
 
+    #[inline]
+    pub fn put(
+        &mut self,
+        font_id: usize,
+        content: String,
+        glyph_clusters: Vec<OwnedGlyphCluster>,
+    ) {
+        if let Some(cache) = self.inner.get_mut(&font_id) {
+            cache.put(content, glyph_clusters);
+        } else {
+            // If font id is main
+            let size = if font_id == 0 { 512 } else { 128 };
+            let mut cache = LruCache::new(NonZeroUsize::new(size).unwrap());
+            cache.put(content, glyph_clusters);
+            self.inner.insert(font_id, cache);
+        }
+    }
+
     #[inline]
     pub fn finish(&mut self) {
         if !self.content.is_empty() && !self.stash.is_empty() {
-            if let Some(cache) = self.inner.get_mut(&self.font_id) {
-                cache.put(
-                    std::mem::take(&mut self.content),
-                    std::mem::take(&mut self.stash),
-                );
-            } else {
-                // If font id is main
-                let size = if self.font_id == 0 { 512 } else { 128 };
-                let mut cache = LruCache::new(NonZeroUsize::new(size).unwrap());
-                cache.put(
-                    std::mem::take(&mut self.content),
-                    std::mem::take(&mut self.stash),
-                );
-                self.inner.insert(self.font_id, cache);
-            }
+            let content = std::mem::take(&mut self.content);
+            let stash = std::mem::take(&mut self.stash);
+            self.put(self.font_id, content, stash);
 
             self.font_id = 0;
             return;
@@ -728,7 +1125,466 @@ impl WordCache {
     }
 }
 
+/// Shaped lines (glyph ids, positions and font ids) keyed by their
+/// fragments content and style.
+pub struct ShapedLineCache {
+    inner: LruCache<u64, ShapedLine>,
+}
+
+struct ShapedLine {
+    fragments: Vec<FragmentData>,
+    font_size: f32,
+    render_data: RenderData,
+}
+
+impl Default for ShapedLineCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShapedLineCache {
+    pub fn new() -> Self {
+        ShapedLineCache {
+            inner: LruCache::new(NonZeroUsize::new(1024).unwrap()),
+        }
+    }
+
+    /// Computes the cache key for a line, returns None for empty lines.
+    pub fn key(fragments: &[FragmentData], font_size: f32) -> Option<u64> {
+        if fragments.is_empty() {
+            return None;
+        }
+
+        let mut hasher = FxHasher::default();
+        font_size.to_bits().hash(&mut hasher);
+        for fragment in fragments {
+            fragment.content.hash(&mut hasher);
+            hash_fragment_style(&fragment.style, &mut hasher);
+        }
+        Some(hasher.finish())
+    }
+
+    #[inline]
+    pub fn get(
+        &mut self,
+        key: u64,
+        fragments: &[FragmentData],
+        font_size: f32,
+    ) -> Option<&RenderData> {
+        // Hash collisions are possible so fragments are always compared
+        self.inner
+            .get(&key)
+            .filter(|line| line.font_size == font_size && line.fragments == fragments)
+            .map(|line| &line.render_data)
+    }
+
+    #[inline]
+    pub fn put(
+        &mut self,
+        key: u64,
+        fragments: &[FragmentData],
+        font_size: f32,
+        render_data: &RenderData,
+    ) {
+        if render_data.is_empty() {
+            return;
+        }
+
+        self.inner.put(
+            key,
+            ShapedLine {
+                fragments: fragments.to_vec(),
+                font_size,
+                render_data: render_data.clone(),
+            },
+        );
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
+
+fn hash_color<H: Hasher>(color: &[f32; 4], state: &mut H) {
+    for channel in color {
+        channel.to_bits().hash(state);
+    }
+}
+
+fn hash_fragment_style<H: Hasher>(style: &FragmentStyle, state: &mut H) {
+    style.font_id.hash(state);
+    style.width.to_bits().hash(state);
+    style.font_attrs.0.hash(state);
+    hash_color(&style.color, state);
+    if let Some(background_color) = &style.background_color {
+        hash_color(background_color, state);
+    }
+    style.font_vars.hash(state);
+    match &style.decoration {
+        Some(FragmentStyleDecoration::Underline(info)) => {
+            1u8.hash(state);
+            info.offset.to_bits().hash(state);
+            info.size.to_bits().hash(state);
+            info.is_doubled.hash(state);
+            (info.shape as u8).hash(state);
+        }
+        Some(FragmentStyleDecoration::Strikethrough) => 2u8.hash(state),
+        None => 0u8.hash(state),
+    }
+    if let Some(decoration_color) = &style.decoration_color {
+        hash_color(decoration_color, state);
+    }
+    match &style.cursor {
+        Some(SugarCursor::Block(color)) => {
+            1u8.hash(state);
+            hash_color(color, state);
+        }
+        Some(SugarCursor::HollowBlock(color)) => {
+            2u8.hash(state);
+            hash_color(color, state);
+        }
+        Some(SugarCursor::Caret(color, width)) => {
+            3u8.hash(state);
+            hash_color(color, state);
+            width.to_bits().hash(state);
+        }
+        Some(SugarCursor::Underline(color, height)) => {
+            4u8.hash(state);
+            hash_color(color, state);
+            height.to_bits().hash(state);
+        }
+        None => 0u8.hash(state),
+    }
+    style.media.hash(state);
+    style.drawable_char.hash(state);
+}
+
 #[derive(Default)]
 struct MetricsCache {
     pub inner: FxHashMap<usize, Metrics>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_line(content: &mut Content, id: usize, text: &str) {
+        content
+            .sel(id)
+            .clear()
+            .add_text(text, FragmentStyle::default())
+            .build();
+    }
+
+    #[test]
+    fn test_effective_line_height_snaps_to_pixels() {
+        let mut layout = RichTextLayout {
+            line_height: 1.15,
+            ..RichTextLayout::default()
+        };
+        layout.dimensions.height = 17.0;
+
+        // 17 * 1.15 = 19.55 is drawn as 20 pixels tall lines
+        assert_eq!(
+            layout.dimensions.height * layout.effective_line_height(),
+            20.0
+        );
+
+        layout.snap_to_pixels = false;
+        assert_eq!(layout.effective_line_height(), 1.15);
+
+        // Unknown dimensions keep the line height as is
+        layout.snap_to_pixels = true;
+        layout.dimensions.height = 0.0;
+        assert_eq!(layout.effective_line_height(), 1.15);
+    }
+
+    #[test]
+    fn test_fragments_are_reused_after_clear() {
+        let mut content = Content::new(&FontLibrary::default());
+        let id = content.create_state(&RichTextLayout::default());
+
+        content
+            .sel(id)
+            .clear()
+            .add_text("hello", FragmentStyle::default())
+            .add_text("world", FragmentStyle::default());
+        assert!(content.get_state(&id).unwrap().fragments_pool.is_empty());
+
+        content.sel(id).clear();
+        assert_eq!(content.get_state(&id).unwrap().fragments_pool.len(), 2);
+
+        content.add_text("rio", FragmentStyle::default());
+        let state = content.get_state(&id).unwrap();
+        assert_eq!(state.fragments_pool.len(), 1);
+        assert_eq!(state.lines[0].fragments[0].content, "rio");
+    }
+
+    #[test]
+    fn test_scroll_moves_lines() {
+        let mut content = Content::new(&FontLibrary::default());
+        let id = content.create_state(&RichTextLayout::default());
+
+        content
+            .sel(id)
+            .clear()
+            .add_text("a", FragmentStyle::default())
+            .new_line()
+            .add_text("b", FragmentStyle::default())
+            .new_line()
+            .add_text("c", FragmentStyle::default());
+        let lines = |content: &Content| -> Vec<String> {
+            content
+                .get_state(&id)
+                .unwrap()
+                .lines
+                .iter()
+                .map(|line| {
+                    line.fragments
+                        .iter()
+                        .map(|fragment| fragment.content.as_str())
+                        .collect()
+                })
+                .collect()
+        };
+
+        content.scroll_lines(1);
+        assert_eq!(lines(&content), ["", "a", "b"]);
+        content.scroll_lines(-2);
+        assert_eq!(lines(&content), ["b", "", ""]);
+        assert_eq!(content.get_state(&id).unwrap().scrolled, -1);
+
+        content.mark_states_clean();
+        assert_eq!(content.get_state(&id).unwrap().scrolled, 0);
+    }
+
+    #[test]
+    fn test_shaped_line_cache_reuses_lines() {
+        let mut content = Content::new(&FontLibrary::default());
+        let layout = RichTextLayout {
+            font_size: 14.0,
+            ..RichTextLayout::default()
+        };
+        let id = content.create_state(&layout);
+
+        build_line(&mut content, id, "hello");
+        assert_eq!(content.line_cache.inner.len(), 1);
+        let first = content.get_state(&id).unwrap().lines[0].render_data.clone();
+
+        build_line(&mut content, id, "hello");
+        assert_eq!(content.line_cache.inner.len(), 1);
+        let second = &content.get_state(&id).unwrap().lines[0].render_data;
+        assert_eq!(first.runs.len(), second.runs.len());
+        assert_eq!(first.runs[0].glyphs.len(), second.runs[0].glyphs.len());
+
+        build_line(&mut content, id, "world");
+        assert_eq!(content.line_cache.inner.len(), 2);
+    }
+
+    fn finish_shaping(content: &mut Content) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while content.has_pending_shaping() && Instant::now() < deadline {
+            content.apply_shaped();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        content.apply_shaped();
+    }
+
+    fn build_parallel_and_sequential(shaping_budget: Duration) -> (Content, Content) {
+        let font_library = FontLibrary::default();
+        let layout = RichTextLayout {
+            font_size: 14.0,
+            ..RichTextLayout::default()
+        };
+
+        let mut parallel = Content::new(&font_library);
+        let mut sequential = Content::new(&font_library);
+        parallel.shaping_budget = shaping_budget;
+        let parallel_id = parallel.create_state(&layout);
+        let sequential_id = sequential.create_state(&layout);
+
+        parallel.sel(parallel_id).clear();
+        sequential.sel(sequential_id).clear();
+        for line in 0..shaping_pool::PARALLEL_SHAPING_THRESHOLD * 2 {
+            let text = format!("line {line} ->= ~~ ffi");
+            parallel
+                .add_text(&text, FragmentStyle::default())
+                .new_line();
+            sequential.add_text(&text, FragmentStyle::default());
+            sequential.process_line(sequential_id, line);
+            sequential.new_line();
+        }
+        parallel.build();
+        (parallel, sequential)
+    }
+
+    fn only_state(content: &Content) -> &BuilderState {
+        content.states.values().next().unwrap()
+    }
+
+    fn assert_same_lines(parallel: &Content, sequential: &Content) {
+        let parallel_lines = &only_state(parallel).lines;
+        let sequential_lines = &only_state(sequential).lines;
+        assert_eq!(parallel_lines.len(), sequential_lines.len());
+        for (left, right) in parallel_lines.iter().zip(sequential_lines) {
+            assert_eq!(left.render_data.runs.len(), right.render_data.runs.len());
+            for (left, right) in left.render_data.runs.iter().zip(&right.render_data.runs)
+            {
+                assert_eq!(left.line, right.line);
+                assert_eq!(left.advance, right.advance);
+                let left_glyphs: Vec<_> = left.glyphs.iter().map(|g| g.data).collect();
+                let right_glyphs: Vec<_> = right.glyphs.iter().map(|g| g.data).collect();
+                assert_eq!(left_glyphs, right_glyphs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_preshaped_lines_match_sequential_shaping() {
+        let (mut parallel, sequential) = build_parallel_and_sequential(SHAPING_BUDGET);
+        finish_shaping(&mut parallel);
+
+        assert_same_lines(&parallel, &sequential);
+        assert!(!parallel.has_pending_shaping());
+    }
+
+    #[test]
+    fn test_lines_waiting_for_the_shaping_pool_are_built_later() {
+        if !shaping_pool::is_available() {
+            return;
+        }
+
+        // Without budget the build doesn't wait for the pool
+        let (mut parallel, sequential) = build_parallel_and_sequential(Duration::ZERO);
+        let waiting: HashSet<usize> = only_state(&parallel)
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.render_data.is_empty() && !line.fragments.is_empty())
+            .map(|(line_number, _)| line_number)
+            .collect();
+        parallel.mark_states_clean();
+        finish_shaping(&mut parallel);
+
+        assert_same_lines(&parallel, &sequential);
+        let last_update = &only_state(&parallel).last_update;
+        if waiting.is_empty() {
+            assert!(*last_update == BuilderStateUpdate::Noop);
+        } else {
+            assert!(*last_update == BuilderStateUpdate::Partial(waiting));
+        }
+    }
+
+    #[test]
+    fn test_lines_waiting_on_the_job_of_another_state_are_built() {
+        let font_library = FontLibrary::default();
+        let layout = RichTextLayout {
+            font_size: 14.0,
+            ..RichTextLayout::default()
+        };
+        let mut content = Content::new(&font_library);
+        content.shaping_budget = Duration::ZERO;
+        let first = content.create_state(&layout);
+        let second = content.create_state(&layout);
+
+        // The second state has the same fragments, which are already in
+        // flight for the first one, so it doesn't shape them itself
+        for state_id in [first, second] {
+            content.sel(state_id).clear();
+            for line in 0..shaping_pool::PARALLEL_SHAPING_THRESHOLD * 2 {
+                content
+                    .add_text(&format!("shared {line} ->= ffi"), FragmentStyle::default())
+                    .new_line();
+            }
+            content.build();
+        }
+        finish_shaping(&mut content);
+
+        for state_id in [first, second] {
+            let lines = &content.get_state(&state_id).unwrap().lines;
+            assert!(lines
+                .iter()
+                .filter(|line| !line.fragments.is_empty())
+                .all(|line| !line.render_data.is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_shaped_line_cache_key_depends_on_style() {
+        let text = FragmentData {
+            content: String::from("rio"),
+            style: FragmentStyle::default(),
+        };
+        let colored = FragmentData {
+            content: String::from("rio"),
+            style: FragmentStyle {
+                color: [1.0, 0.0, 0.0, 1.0],
+                ..FragmentStyle::default()
+            },
+        };
+
+        assert_eq!(ShapedLineCache::key(&[], 14.0), None);
+        let text = std::slice::from_ref(&text);
+        assert_eq!(
+            ShapedLineCache::key(text, 14.0),
+            ShapedLineCache::key(text, 14.0)
+        );
+        assert_ne!(
+            ShapedLineCache::key(text, 14.0),
+            ShapedLineCache::key(&[colored], 14.0)
+        );
+        assert_ne!(
+            ShapedLineCache::key(text, 14.0),
+            ShapedLineCache::key(text, 16.0)
+        );
+    }
+
+    #[test]
+    fn test_links_are_measured_in_cells() {
+        let mut content = Content::new(&FontLibrary::default());
+        let id = content.create_state(&RichTextLayout::default());
+
+        content
+            .sel(id)
+            .clear()
+            .add_text("More info in ", FragmentStyle::default())
+            .add_link(
+                "rioterm.com",
+                "https://rioterm.com",
+                FragmentStyle::default(),
+            )
+            .new_line()
+            .add_link(
+                "docs",
+                "https://rioterm.com/docs",
+                FragmentStyle {
+                    width: 2.0,
+                    ..FragmentStyle::default()
+                },
+            );
+
+        let links = &content.get_state(&id).unwrap().links;
+        assert_eq!(
+            links,
+            &vec![
+                RichTextLink {
+                    line: 0,
+                    start: 13.0,
+                    end: 24.0,
+                    url: String::from("https://rioterm.com"),
+                },
+                RichTextLink {
+                    line: 1,
+                    start: 0.0,
+                    end: 8.0,
+                    url: String::from("https://rioterm.com/docs"),
+                },
+            ]
+        );
+
+        content.sel(id).clear();
+        assert!(content.get_state(&id).unwrap().links.is_empty());
+    }
+}
@@ -10,13 +10,15 @@
 mod content;
 mod glyph;
 mod render_data;
+mod shaping_pool;
 
+use crate::font::fonts::LetterSpacing;
 pub use glyph::Glyph;
 pub use render_data::RenderData;
 
 pub use content::{
     BuilderLine, BuilderState, BuilderStateUpdate, Content, FragmentStyle,
-    FragmentStyleDecoration, UnderlineInfo, UnderlineShape,
+    FragmentStyleDecoration, RichTextLink, UnderlineInfo, UnderlineShape,
 };
 pub use render_data::Run;
 
@@ -48,6 +50,9 @@ pub struct SugarDimensions {
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct RichTextLayout {
     pub line_height: f32,
+    pub baseline_offset: f32,
+    pub letter_spacing: LetterSpacing,
+    pub snap_to_pixels: bool,
     pub font_size: f32,
     pub original_font_size: f32,
     pub dimensions: SugarDimensions,
@@ -62,9 +67,24 @@ impl RichTextLayout {
         self
     }
 
+    /// Line height multiplier applied to the cells, adjusted for lines to
+    /// be whole device pixels tall when snapping.
+    #[inline]
+    pub fn effective_line_height(&self) -> f32 {
+        let height = self.dimensions.height;
+        if !self.snap_to_pixels || height <= 0.0 {
+            return self.line_height;
+        }
+
+        (height * self.line_height).round().max(1.0) / height
+    }
+
     pub fn from_default_layout(default_layout: &RootStyle) -> Self {
         Self {
             line_height: default_layout.line_height,
+            baseline_offset: default_layout.baseline_offset,
+            letter_spacing: default_layout.letter_spacing,
+            snap_to_pixels: default_layout.snap_to_pixels,
             font_size: default_layout.font_size,
             original_font_size: default_layout.font_size,
             dimensions: SugarDimensions {
@@ -79,6 +99,9 @@ impl Default for RichTextLayout {
     fn default() -> Self {
         Self {
             line_height: 1.0,
+            baseline_offset: 0.0,
+            letter_spacing: LetterSpacing::default(),
+            snap_to_pixels: true,
             font_size: 0.0,
             original_font_size: 0.0,
             dimensions: SugarDimensions::default(),
@@ -91,6 +114,12 @@ pub struct RootStyle {
     pub scale_factor: f32,
     pub font_size: f32,
     pub line_height: f32,
+    /// Pixels the glyphs are moved down within their lines.
+    pub baseline_offset: f32,
+    /// Space added to the width of the cells.
+    pub letter_spacing: LetterSpacing,
+    /// Rounds the height of the lines to whole device pixels.
+    pub snap_to_pixels: bool,
 }
 
 impl Default for RootStyle {
@@ -99,6 +128,9 @@ impl Default for RootStyle {
             line_height: 1.0,
             scale_factor: 1.0,
             font_size: 14.,
+            baseline_offset: 0.0,
+            letter_spacing: LetterSpacing::default(),
+            snap_to_pixels: true,
         }
     }
 }
@@ -112,6 +144,9 @@ impl RootStyle {
             scale_factor,
             font_size,
             line_height,
+            baseline_offset: 0.0,
+            letter_spacing: LetterSpacing::default(),
+            snap_to_pixels: true,
         }
     }
 }
@@ -0,0 +1,219 @@
+// Copyright (c) 2023-present, Raphael Amorim.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+//! Shaping of text fragments on a dedicated pool of worker threads.
+//!
+//! Shaping a full viewport of heavy Unicode content on very wide windows
+//! can take long enough to delay key handling on the event loop. Whenever
+//! a build has many fragments that are not cached yet, these are shaped
+//! in parallel while the event loop keeps going, `Content` takes the
+//! results it gets within a short budget and builds the remaining lines
+//! on a later frame.
+
+use crate::font::SharedData;
+use crate::font_introspector::shape::cluster::OwnedGlyphCluster;
+use crate::font_introspector::shape::ShapeContext;
+use crate::font_introspector::text::Script;
+use crate::font_introspector::{CacheKey, FontRef, Metrics, Setting};
+use rustc_hash::FxHashMap;
+use std::cell::RefCell;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Instant;
+
+/// Minimum amount of fragments to shape before using the pool,
+/// below it the cost of dispatching is higher than shaping itself.
+pub const PARALLEL_SHAPING_THRESHOLD: usize = 32;
+
+pub type FontHandle = (SharedData, u32, CacheKey);
+
+pub struct ShapingJob {
+    pub font_id: usize,
+    pub content: String,
+    pub vars: Vec<Setting<f32>>,
+}
+
+pub struct ShapedFragment {
+    pub clusters: Vec<OwnedGlyphCluster>,
+    pub metrics: Metrics,
+}
+
+thread_local! {
+    static SHAPE_CONTEXT: RefCell<ShapeContext> = RefCell::new(ShapeContext::new());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn pool() -> Option<&'static rayon::ThreadPool> {
+    static POOL: std::sync::OnceLock<Option<rayon::ThreadPool>> =
+        std::sync::OnceLock::new();
+
+    POOL.get_or_init(|| {
+        // A single worker still keeps the shaping off the event loop
+        let threads = std::thread::available_parallelism()
+            .map(|threads| threads.get().min(4))
+            .unwrap_or(1);
+
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("sugarloaf-shaper-{index}"))
+            .build()
+            .map_err(|err| tracing::warn!("unable to create shaping pool: {err}"))
+            .ok()
+    })
+    .as_ref()
+}
+
+/// Returns true if shaping can be moved to worker threads.
+#[inline]
+pub fn is_available() -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    return pool().is_some();
+
+    #[cfg(target_arch = "wasm32")]
+    false
+}
+
+fn shape(
+    job: &ShapingJob,
+    fonts: &FxHashMap<usize, FontHandle>,
+    font_size: f32,
+    features: &[Setting<u16>],
+) -> Option<ShapedFragment> {
+    let (data, offset, key) = fonts.get(&job.font_id)?;
+    let font = FontRef {
+        data: data.as_ref(),
+        offset: *offset,
+        key: *key,
+    };
+
+    SHAPE_CONTEXT.with(|scx| {
+        let mut scx = scx.borrow_mut();
+        let mut shaper = scx
+            .builder(font)
+            .script(Script::Latin)
+            .size(font_size)
+            .features(features.iter().copied())
+            .variations(job.vars.iter().copied())
+            .build();
+
+        shaper.add_str(&job.content);
+        let metrics = shaper.metrics();
+        let mut clusters = vec![];
+        shaper.shape_with(|cluster| clusters.push(cluster.into()));
+
+        Some(ShapedFragment { clusters, metrics })
+    })
+}
+
+/// Jobs being shaped on the pool, the results are received as they
+/// are ready.
+pub struct ShapingTask {
+    receiver: Receiver<(ShapingJob, Option<ShapedFragment>)>,
+    remaining: usize,
+}
+
+impl ShapingTask {
+    /// Whether every job was received.
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Receives the jobs finished until `deadline`, without a fragment for
+    /// the ones that couldn't be shaped. It doesn't wait when the deadline
+    /// has passed.
+    pub fn receive(
+        &mut self,
+        deadline: Instant,
+    ) -> Vec<(ShapingJob, Option<ShapedFragment>)> {
+        let mut shaped = vec![];
+        while self.remaining > 0 {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let (job, fragment) = match self.receiver.recv_timeout(timeout) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => break,
+                // The pool is gone, nothing else will arrive
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.remaining = 0;
+                    break;
+                }
+            };
+            self.remaining -= 1;
+            shaped.push((job, fragment));
+        }
+        shaped
+    }
+}
+
+/// Starts shaping the jobs on the pool without waiting for them.
+pub fn spawn(
+    jobs: Vec<ShapingJob>,
+    fonts: FxHashMap<usize, FontHandle>,
+    font_size: f32,
+    features: Vec<Setting<u16>>,
+) -> ShapingTask {
+    let (sender, receiver) = mpsc::channel();
+    let remaining = jobs.len();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(pool) = pool() {
+        pool.spawn(move || {
+            use rayon::prelude::*;
+
+            jobs.into_par_iter().for_each_with(sender, |sender, job| {
+                let shaped = shape(&job, &fonts, font_size, &features);
+                // A dropped task doesn't need the results anymore
+                let _ = sender.send((job, shaped));
+            });
+        });
+
+        return ShapingTask {
+            receiver,
+            remaining,
+        };
+    }
+
+    for job in jobs {
+        let shaped = shape(&job, &fonts, font_size, &features);
+        let _ = sender.send((job, shaped));
+    }
+
+    ShapingTask {
+        receiver,
+        remaining,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::FontLibrary;
+    use std::time::Duration;
+
+    #[test]
+    fn test_spawn_receives_every_job() {
+        let font_library = FontLibrary::default();
+        let mut fonts = FxHashMap::default();
+        fonts.insert(0, font_library.inner.lock().get_shared_data(&0).unwrap());
+
+        let jobs = (0..PARALLEL_SHAPING_THRESHOLD * 2)
+            .map(|index| ShapingJob {
+                font_id: 0,
+                content: format!("fragment {index}"),
+                vars: vec![],
+            })
+            .collect();
+        let mut task = spawn(jobs, fonts, 14.0, vec![]);
+
+        let shaped = task.receive(Instant::now() + Duration::from_secs(10));
+        assert!(task.is_done());
+        assert_eq!(shaped.len(), PARALLEL_SHAPING_THRESHOLD * 2);
+        for (job, fragment) in &shaped {
+            assert_eq!(fragment.as_ref().unwrap().clusters.len(), job.content.len());
+        }
+
+        // Nothing is left to wait for
+        assert!(task.receive(Instant::now()).is_empty());
+    }
+}
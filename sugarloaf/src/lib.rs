@@ -10,18 +10,22 @@ pub use wgpu;
 
 pub use font_introspector::{Stretch, Style, Weight};
 
+#[allow(deprecated)]
+pub use crate::sugarloaf::SugarloafWithErrors;
 pub use crate::sugarloaf::{
     graphics::{
         ColorType, Graphic, GraphicData, GraphicId, Graphics, ResizeCommand,
         ResizeParameter, MAX_GRAPHIC_DIMENSIONS,
     },
     primitives::*,
+    stats::{CacheStats, FrameObserver, FrameStats},
     Sugarloaf, SugarloafErrors, SugarloafRenderer, SugarloafWindow, SugarloafWindowSize,
-    SugarloafWithErrors,
 };
 pub use components::filters::Filter;
 pub use components::quad::Quad;
+pub use components::rich_text::GlyphCacheStats;
+pub use context::ContextError;
 pub use layout::{
-    Content, FragmentStyle, FragmentStyleDecoration, SugarDimensions, UnderlineInfo,
-    UnderlineShape,
+    Content, FragmentStyle, FragmentStyleDecoration, RichTextLink, SugarDimensions,
+    UnderlineInfo, UnderlineShape,
 };
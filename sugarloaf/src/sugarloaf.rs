@@ -1,25 +1,34 @@
 pub mod graphics;
 pub mod primitives;
 pub mod state;
+pub mod stats;
 
 use crate::components::core::{image::Handle, shapes::Rectangle};
 use crate::components::filters::{Filter, FiltersBrush};
+use crate::components::hdr::HdrBrush;
 use crate::components::layer::{self, LayerBrush};
 use crate::components::quad::QuadBrush;
 use crate::components::rich_text::RichTextBrush;
-use crate::font::{fonts::SugarloafFont, FontLibrary};
+use crate::font::{
+    fonts::{LetterSpacing, SugarloafFont},
+    FontLibrary,
+};
 use crate::layout::{RichTextLayout, RootStyle};
-use crate::sugarloaf::graphics::{BottomLayer, Graphics};
+use crate::sugarloaf::graphics::{BottomLayer, GraphicRenderRequest, Graphics};
 use crate::sugarloaf::layer::types;
 use crate::Content;
 use crate::SugarDimensions;
-use crate::{context::Context, Object};
-use core::fmt::{Debug, Formatter};
-use primitives::ImageProperties;
+use crate::{
+    context::{Context, ContextError},
+    Object,
+};
+use primitives::{Colorspace, ImageProperties};
 use raw_window_handle::{
     DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle,
 };
 use state::SugarState;
+use stats::{FrameObserver, FrameStats};
+use std::time::Instant;
 
 pub struct Sugarloaf<'a> {
     pub ctx: Context<'a>,
@@ -31,6 +40,13 @@ pub struct Sugarloaf<'a> {
     pub background_image: Option<ImageProperties>,
     pub graphics: Graphics,
     filters_brush: FiltersBrush,
+    hdr_brush: Option<HdrBrush>,
+    // Whether images were prepared by the layer brush since the frame began
+    has_layers_in_frame: bool,
+    frame_stats: FrameStats,
+    // Glyph cache counters at the end of the last frame
+    glyph_cache_totals: crate::GlyphCacheStats,
+    frame_observer: Option<Box<dyn FrameObserver>>,
 }
 
 #[derive(Debug)]
@@ -38,13 +54,15 @@ pub struct SugarloafErrors {
     pub fonts_not_found: Vec<SugarloafFont>,
 }
 
+#[deprecated = "`Sugarloaf::new` returns a `ContextError` instead"]
 pub struct SugarloafWithErrors<'a> {
     pub instance: Sugarloaf<'a>,
     pub errors: SugarloafErrors,
 }
 
-impl Debug for SugarloafWithErrors<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+#[allow(deprecated)]
+impl std::fmt::Debug for SugarloafWithErrors<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.errors)
     }
 }
@@ -65,7 +83,19 @@ pub struct SugarloafWindow {
 pub struct SugarloafRenderer {
     pub power_preference: wgpu::PowerPreference,
     pub backend: wgpu::Backends,
+    /// Selects the first adapter whose name contains this value (case
+    /// insensitive), falling back to `power_preference` if none matches.
+    pub adapter: Option<String>,
     pub font_features: Option<Vec<String>>,
+    /// Requests an extended range surface, ignored if the surface
+    /// does not support it.
+    pub hdr: bool,
+    pub colorspace: Colorspace,
+    /// Multiplier of the content brightness on extended range surfaces,
+    /// values above 1.0 go beyond the SDR white.
+    pub hdr_brightness: f32,
+    /// Falls back to `Fifo` if the surface does not support it.
+    pub present_mode: wgpu::PresentMode,
 }
 
 impl Default for SugarloafRenderer {
@@ -78,7 +108,12 @@ impl Default for SugarloafRenderer {
         SugarloafRenderer {
             power_preference: wgpu::PowerPreference::HighPerformance,
             backend: default_backend,
+            adapter: None,
             font_features: None,
+            hdr: false,
+            colorspace: Colorspace::default(),
+            hdr_brightness: 1.0,
+            present_mode: wgpu::PresentMode::Fifo,
         }
     }
 }
@@ -116,17 +151,59 @@ impl Sugarloaf<'_> {
         renderer: SugarloafRenderer,
         font_library: &FontLibrary,
         layout: RootStyle,
-    ) -> Result<Sugarloaf<'a>, SugarloafWithErrors<'a>> {
+    ) -> Result<Sugarloaf<'a>, ContextError> {
+        let span = tracing::span!(tracing::Level::INFO, "sugarloaf_new");
+        let _guard = span.enter();
+        let font_features = renderer.font_features.to_owned();
+        let started = std::time::Instant::now();
+        let ctx = Context::new(window, renderer)?;
+        tracing::info!(
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "gpu context created"
+        );
+
+        Ok(Self::with_context(
+            ctx,
+            font_library,
+            layout,
+            &font_features,
+        ))
+    }
+
+    /// Creates an instance without a window, the content is rendered
+    /// with [`Sugarloaf::render_to_rgba`].
+    pub fn new_headless<'a>(
+        size: SugarloafWindowSize,
+        scale: f32,
+        renderer: SugarloafRenderer,
+        font_library: &FontLibrary,
+        layout: RootStyle,
+    ) -> Result<Sugarloaf<'a>, ContextError> {
         let font_features = renderer.font_features.to_owned();
-        let ctx = Context::new(window, renderer);
+        let ctx = Context::new_headless(size, scale, renderer)?;
 
+        Ok(Self::with_context(
+            ctx,
+            font_library,
+            layout,
+            &font_features,
+        ))
+    }
+
+    fn with_context<'a>(
+        ctx: Context<'a>,
+        font_library: &FontLibrary,
+        layout: RootStyle,
+        font_features: &Option<Vec<String>>,
+    ) -> Sugarloaf<'a> {
         let layer_brush = LayerBrush::new(&ctx);
         let quad_brush = QuadBrush::new(&ctx);
         let rich_text_brush = RichTextBrush::new(&ctx);
-        let state = SugarState::new(layout, font_library, &font_features);
+        let state = SugarState::new(layout, font_library, font_features);
         let filters_brush = FiltersBrush::default();
+        let hdr_brush = ctx.is_hdr().then(|| HdrBrush::new(&ctx));
 
-        let instance = Sugarloaf {
+        Sugarloaf {
             state,
             layer_brush,
             quad_brush,
@@ -136,9 +213,12 @@ impl Sugarloaf<'_> {
             rich_text_brush,
             graphics: Graphics::default(),
             filters_brush,
-        };
-
-        Ok(instance)
+            hdr_brush,
+            has_layers_in_frame: false,
+            frame_stats: FrameStats::default(),
+            glyph_cache_totals: crate::GlyphCacheStats::default(),
+            frame_observer: None,
+        }
     }
 
     #[inline]
@@ -194,6 +274,31 @@ impl Sugarloaf<'_> {
         self.state.set_rich_text_line_height(rt_id, line_height);
     }
 
+    #[inline]
+    pub fn set_rich_text_letter_spacing(
+        &mut self,
+        rt_id: &usize,
+        letter_spacing: LetterSpacing,
+    ) {
+        self.state.set_rich_text_letter_spacing(
+            rt_id,
+            letter_spacing,
+            &mut self.rich_text_brush,
+        );
+    }
+
+    #[inline]
+    pub fn set_rich_text_baseline_offset(&mut self, rt_id: &usize, baseline_offset: f32) {
+        self.state
+            .set_rich_text_baseline_offset(rt_id, baseline_offset);
+    }
+
+    #[inline]
+    pub fn set_rich_text_snap_to_pixels(&mut self, rt_id: &usize, snap_to_pixels: bool) {
+        self.state
+            .set_rich_text_snap_to_pixels(rt_id, snap_to_pixels);
+    }
+
     #[inline]
     pub fn update_filters(&mut self, filters: &[Filter]) {
         self.filters_brush.update_filters(&self.ctx, filters);
@@ -210,6 +315,7 @@ impl Sugarloaf<'_> {
         let handle = Handle::from_path(image.path.to_owned());
         self.graphics.bottom_layer = Some(BottomLayer {
             should_fit: image.width.is_none() && image.height.is_none(),
+            fit: image.fit,
             data: types::Raster {
                 handle,
                 bounds: Rectangle {
@@ -218,6 +324,7 @@ impl Sugarloaf<'_> {
                     x: image.x,
                     y: image.y,
                 },
+                opacity: image.opacity.clamp(0.0, 1.0),
             },
         });
         self
@@ -260,12 +367,100 @@ impl Sugarloaf<'_> {
         self.state.get_state_layout(id)
     }
 
+    /// Url of the rich text link rendered at the position (physical pixels).
+    #[inline]
+    pub fn link_at(&self, x: f32, y: f32) -> Option<&str> {
+        self.state.link_at(x, y)
+    }
+
+    #[inline]
+    pub fn hovered_link(&self) -> Option<&str> {
+        self.state.content.hovered_link()
+    }
+
+    #[inline]
+    pub fn set_hovered_link(&mut self, url: Option<String>) {
+        self.state.content.set_hovered_link(url);
+    }
+
+    #[inline]
+    pub fn glyph_cache_stats(&self) -> crate::GlyphCacheStats {
+        self.rich_text_brush.glyph_cache_stats()
+    }
+
+    /// Whether text is still being shaped in the background, rendering
+    /// again draws the lines that were waiting for it.
+    #[inline]
+    pub fn has_pending_shaping(&self) -> bool {
+        self.state.content.has_pending_shaping()
+    }
+
+    /// Statistics of the last rendered frame.
+    #[inline]
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats
+    }
+
+    /// Sets the observer called with the statistics after every frame.
+    #[inline]
+    pub fn set_frame_observer(&mut self, observer: Option<Box<dyn FrameObserver>>) {
+        self.frame_observer = observer;
+    }
+
     #[inline]
     pub fn get_rich_text_dimensions(&mut self, id: &usize) -> SugarDimensions {
         self.state
             .get_rich_text_dimensions(id, &mut self.rich_text_brush)
     }
 
+    /// Width of the longest line of the rich text in logical pixels,
+    /// measured in cells like links.
+    pub fn rich_text_width(&mut self, id: &usize) -> f32 {
+        let dimensions = self.get_rich_text_dimensions(id);
+        let Some(state) = self.state.content.get_state(id) else {
+            return 0.0;
+        };
+
+        let cells = state
+            .lines
+            .iter()
+            .map(|line| {
+                line.fragments
+                    .iter()
+                    .map(|fragment| {
+                        fragment.content.chars().count() as f32 * fragment.style.width
+                    })
+                    .sum::<f32>()
+            })
+            .fold(0.0, f32::max);
+        cells * dimensions.width / self.ctx.scale
+    }
+
+    /// Mirrors the objects horizontally within the window, so interfaces
+    /// laid out from left to right can be shown from right to left.
+    pub fn mirror_objects(&mut self, objects: &mut [Object]) {
+        let window_width = self.ctx.size.width / self.ctx.scale;
+        for object in objects {
+            match object {
+                Object::Quad(quad) => {
+                    quad.position[0] = window_width - quad.position[0] - quad.size[0];
+                    let [top_left, top_right, bottom_right, bottom_left] =
+                        quad.border_radius;
+                    quad.border_radius = [top_right, top_left, bottom_left, bottom_right];
+                    quad.shadow_offset[0] = -quad.shadow_offset[0];
+                }
+                Object::Image(image) => {
+                    image.position[0] = window_width - image.position[0] - image.size[0];
+                }
+                Object::RichText(rich_text) => {
+                    let text_width = self.rich_text_width(&rich_text.id);
+                    rich_text.position[0] =
+                        window_width - rich_text.position[0] - text_width;
+                }
+            }
+        }
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.state.clean_screen();
@@ -281,6 +476,11 @@ impl Sugarloaf<'_> {
         self.state.style.scale_factor
     }
 
+    #[inline]
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        self.ctx.set_present_mode(present_mode);
+    }
+
     #[inline]
     pub fn resize(&mut self, width: u32, height: u32) {
         self.ctx.resize(width, height);
@@ -313,108 +513,102 @@ impl Sugarloaf<'_> {
         self.state.reset();
     }
 
+    /// Recreates the device and every GPU resource after a device loss,
+    /// glyphs and images are uploaded again on the next render.
+    fn recover_lost_device(&mut self) -> bool {
+        if let Err(err) = self.ctx.recreate_device() {
+            tracing::error!("unable to recover from device loss: {err}");
+            return false;
+        }
+
+        self.layer_brush = LayerBrush::new(&self.ctx);
+        self.quad_brush = QuadBrush::new(&self.ctx);
+        self.rich_text_brush = RichTextBrush::new(&self.ctx);
+        self.filters_brush.reload(&self.ctx);
+        if self.hdr_brush.is_some() {
+            self.hdr_brush = Some(HdrBrush::new(&self.ctx));
+        }
+        true
+    }
+
+    /// Acquires the next frame, reconfiguring the surface once if it was
+    /// lost or became outdated (e.g. after sleep/wake).
     #[inline]
-    pub fn render(&mut self) {
+    fn acquire_frame(&mut self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
+        let Some(surface) = &self.ctx.surface else {
+            return Err(wgpu::SurfaceError::Lost);
+        };
+
+        match surface.get_current_texture() {
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                tracing::warn!("surface lost or outdated, reconfiguring");
+                self.ctx
+                    .resize(self.ctx.size.width as u32, self.ctx.size.height as u32);
+                self.ctx
+                    .surface
+                    .as_ref()
+                    .ok_or(wgpu::SurfaceError::Lost)?
+                    .get_current_texture()
+            }
+            result => result,
+        }
+    }
+
+    /// Recovers the device if needed and computes the content updates,
+    /// returns false if nothing can be rendered.
+    #[inline]
+    fn prepare_render(&mut self) -> bool {
+        if self.ctx.is_device_lost() && !self.recover_lost_device() {
+            self.reset();
+            return false;
+        }
+
         self.state.compute_dimensions(&mut self.rich_text_brush);
+        self.state.compute_links();
         self.state.compute_updates(
             &mut self.rich_text_brush,
             &mut self.quad_brush,
             &mut self.ctx,
             &mut self.graphics,
         );
+        true
+    }
 
-        match self.ctx.surface.get_current_texture() {
-            Ok(frame) => {
-                let mut encoder = self.ctx.device.create_command_encoder(
-                    &wgpu::CommandEncoderDescriptor { label: None },
-                );
-
-                let view = frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default());
+    #[inline]
+    pub fn render(&mut self) {
+        self.render_frame(false);
+    }
 
-                if let Some(layer) = &self.graphics.bottom_layer {
-                    self.layer_brush
-                        .prepare(&mut encoder, &mut self.ctx, &[&layer.data]);
-                }
+    /// Renders like [`Sugarloaf::render`] and returns the pixels of the
+    /// presented frame as RGBA, row by row.
+    pub fn render_and_capture(&mut self) -> Vec<u8> {
+        self.render_frame(true)
+    }
 
-                if self.graphics.has_graphics_on_top_layer() {
-                    for request in &self.graphics.top_layer {
-                        if let Some(entry) = self.graphics.get(&request.id) {
-                            self.layer_brush.prepare_with_handle(
-                                &mut encoder,
-                                &mut self.ctx,
-                                &entry.handle,
-                                &Rectangle {
-                                    width: request.width.unwrap_or(entry.width),
-                                    height: request.height.unwrap_or(entry.height),
-                                    x: request.pos_x,
-                                    y: request.pos_y,
-                                },
-                            );
-                        }
-                    }
-                }
+    fn render_frame(&mut self, capture: bool) -> Vec<u8> {
+        let frame_started = Instant::now();
+        if !self.prepare_render() {
+            return Vec::new();
+        }
+        let draw_started = Instant::now();
 
-                {
-                    let load = if let Some(background_color) = self.background_color {
-                        wgpu::LoadOp::Clear(background_color)
-                    } else {
-                        wgpu::LoadOp::Load
-                    };
-
-                    let mut rpass =
-                        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                            timestamp_writes: None,
-                            occlusion_query_set: None,
-                            label: None,
-                            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                                view: &view,
-                                resolve_target: None,
-                                ops: wgpu::Operations {
-                                    load,
-                                    store: wgpu::StoreOp::Store,
-                                },
-                            })],
-                            depth_stencil_attachment: None,
-                        });
-
-                    if self.graphics.bottom_layer.is_some() {
-                        self.layer_brush.render(0, &mut rpass, None);
-                    }
-
-                    if self.graphics.has_graphics_on_top_layer() {
-                        let range_request = if self.graphics.bottom_layer.is_some() {
-                            1..(self.graphics.top_layer.len() + 1)
-                        } else {
-                            0..self.graphics.top_layer.len()
-                        };
-                        for request in range_request {
-                            self.layer_brush.render(request, &mut rpass, None);
-                        }
-                    }
-
-                    self.quad_brush
-                        .render(&mut self.ctx, &self.state, &mut rpass);
-
-                    self.rich_text_brush.render(&mut self.ctx, &mut rpass);
+        let mut pixels = Vec::new();
+        match self.acquire_frame() {
+            Ok(frame) => {
+                // The frame is read back when the surface allows it,
+                // otherwise the content is also drawn offscreen
+                let is_readable = frame
+                    .texture
+                    .usage()
+                    .contains(wgpu::TextureUsages::COPY_SRC);
+                if capture && !is_readable {
+                    pixels = self.draw_offscreen();
                 }
 
-                if self.graphics.bottom_layer.is_some()
-                    || self.graphics.has_graphics_on_top_layer()
-                {
-                    self.layer_brush.end_frame();
-                    self.graphics.clear_top_layer();
+                self.draw(&frame.texture);
+                if capture && is_readable {
+                    pixels = read_texture_rgba(&self.ctx, &frame.texture);
                 }
-
-                self.filters_brush.render(
-                    &self.ctx,
-                    &mut encoder,
-                    &frame.texture,
-                    &frame.texture,
-                );
-
-                self.ctx.queue.submit(Some(encoder.finish()));
                 frame.present();
             }
             Err(error) => {
@@ -423,6 +617,347 @@ impl Sugarloaf<'_> {
                 }
             }
         }
+        self.end_frame(frame_started, draw_started);
+        pixels
+    }
+
+    /// Renders into an offscreen texture and returns its pixels as RGBA,
+    /// row by row. Meant for headless instances, e.g. snapshot tests.
+    pub fn render_to_rgba(&mut self) -> Vec<u8> {
+        let frame_started = Instant::now();
+        if !self.prepare_render() {
+            return Vec::new();
+        }
+        let draw_started = Instant::now();
+
+        let pixels = self.draw_offscreen();
+        self.end_frame(frame_started, draw_started);
+        pixels
+    }
+
+    fn draw_offscreen(&mut self) -> Vec<u8> {
+        let texture = self.ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("sugarloaf::offscreen texture"),
+            size: wgpu::Extent3d {
+                width: (self.ctx.size.width as u32).max(1),
+                height: (self.ctx.size.height as u32).max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.ctx.surface_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        self.draw(&texture);
+        read_texture_rgba(&self.ctx, &texture)
+    }
+
+    fn end_frame(&mut self, frame_started: Instant, draw_started: Instant) {
+        // The glyph cache is recreated when fonts change, so the
+        // counters can go backwards
+        let glyph_cache_totals = self.rich_text_brush.glyph_cache_stats();
+        self.frame_stats.glyph_cache = crate::GlyphCacheStats {
+            hits: glyph_cache_totals
+                .hits
+                .saturating_sub(self.glyph_cache_totals.hits),
+            misses: glyph_cache_totals
+                .misses
+                .saturating_sub(self.glyph_cache_totals.misses),
+        };
+        self.glyph_cache_totals = glyph_cache_totals;
+        let rich_text_stats = self.rich_text_brush.take_stats();
+        self.frame_stats.shaped_lines = self.state.content.take_shaped_line_stats();
+        self.frame_stats.drawn_lines = rich_text_stats.drawn_lines;
+        self.frame_stats.full_rebuilds = rich_text_stats.full_rebuilds;
+        self.frame_stats.partial_rebuilds = rich_text_stats.partial_rebuilds;
+        self.frame_stats.atlas_overflows = rich_text_stats.atlas_overflows;
+        self.frame_stats.prepare_time = draw_started - frame_started;
+        self.frame_stats.draw_time = draw_started.elapsed();
+        self.frame_stats.rich_texts = self.state.rich_texts.len();
+        self.frame_stats.quads = self.state.quads.len();
+        if let Some(observer) = &mut self.frame_observer {
+            observer.on_frame(&self.frame_stats);
+        }
+
+        if std::mem::take(&mut self.has_layers_in_frame) {
+            self.layer_brush.end_frame();
+            self.graphics.clear_top_layer();
+        }
         self.reset();
     }
+
+    /// Draws the prepared content into dst_texture, it can be called more
+    /// than once per frame.
+    fn draw(&mut self, dst_texture: &wgpu::Texture) {
+        let mut encoder = self
+            .ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        // A captured frame can be drawn twice, offscreen and to the surface
+        self.layer_brush.begin_draw();
+
+        // Extended range surfaces get the content converted at the end
+        let hdr_texture = self
+            .hdr_brush
+            .as_mut()
+            .map(|hdr_brush| hdr_brush.texture(&self.ctx));
+        let texture = hdr_texture.as_ref().unwrap_or(dst_texture);
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        if let Some(layer) = &self.graphics.bottom_layer {
+            let image_size = self.layer_brush.dimensions(&layer.data.handle);
+            let rasters =
+                layer.rasters(image_size, self.ctx.size.width, self.ctx.size.height);
+            let rasters: Vec<&types::Raster> = rasters.iter().collect();
+            self.layer_brush
+                .prepare(&mut encoder, &mut self.ctx, &rasters);
+        }
+
+        // Graphics are layered by z-index, the negative ones below the text
+        self.graphics
+            .top_layer
+            .sort_by_key(|request| request.z_index);
+        let below_text = self
+            .graphics
+            .top_layer
+            .partition_point(|request| request.z_index < 0);
+        let mut graphics_below_text = 0;
+        for request in &self.graphics.top_layer[..below_text] {
+            if prepare_graphic(
+                &mut self.layer_brush,
+                &mut encoder,
+                &mut self.ctx,
+                &self.graphics,
+                request,
+            ) {
+                graphics_below_text += 1;
+            }
+        }
+
+        let scale = self.ctx.scale;
+        let mut images = 0;
+        for image in self.state.images() {
+            let is_prepared = self.layer_brush.prepare_with_handle(
+                &mut encoder,
+                &mut self.ctx,
+                &image.handle,
+                &Rectangle {
+                    width: image.size[0] * scale,
+                    height: image.size[1] * scale,
+                    x: image.position[0] * scale,
+                    y: image.position[1] * scale,
+                },
+            );
+            if is_prepared {
+                images += 1;
+            }
+        }
+
+        let mut graphics_above_text = 0;
+        for request in &self.graphics.top_layer[below_text..] {
+            if prepare_graphic(
+                &mut self.layer_brush,
+                &mut encoder,
+                &mut self.ctx,
+                &self.graphics,
+                request,
+            ) {
+                graphics_above_text += 1;
+            }
+        }
+
+        {
+            let load = if let Some(background_color) = self.background_color {
+                wgpu::LoadOp::Clear(background_color)
+            } else {
+                wgpu::LoadOp::Load
+            };
+
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            if self.graphics.bottom_layer.is_some() {
+                self.layer_brush.render(0, &mut rpass, None);
+            }
+
+            let first_layer = usize::from(self.graphics.bottom_layer.is_some());
+            let text_layer = first_layer + graphics_below_text + images;
+            for layer in first_layer..text_layer {
+                self.layer_brush.render(layer, &mut rpass, None);
+            }
+
+            self.quad_brush
+                .render(&mut self.ctx, &self.state, &mut rpass);
+
+            self.rich_text_brush.render(&mut self.ctx, &mut rpass);
+
+            for layer in text_layer..text_layer + graphics_above_text {
+                self.layer_brush.render(layer, &mut rpass, None);
+            }
+        }
+
+        self.frame_stats.images = usize::from(self.graphics.bottom_layer.is_some())
+            + graphics_below_text
+            + images
+            + graphics_above_text;
+        if self.graphics.bottom_layer.is_some()
+            || self.graphics.has_graphics_on_top_layer()
+            || images > 0
+        {
+            self.has_layers_in_frame = true;
+        }
+
+        self.filters_brush
+            .render(&self.ctx, &mut encoder, texture, texture);
+
+        if let Some(hdr_brush) = &self.hdr_brush {
+            hdr_brush.render(&self.ctx, &mut encoder, dst_texture);
+        }
+
+        self.ctx.queue.submit(Some(encoder.finish()));
+    }
+}
+
+/// Copies the texture into a buffer and reads it back as RGBA pixels.
+fn read_texture_rgba(ctx: &Context, texture: &wgpu::Texture) -> Vec<u8> {
+    let width = texture.width();
+    let height = texture.height();
+    let is_extended_range = texture.format() == wgpu::TextureFormat::Rgba16Float;
+    let bytes_per_pixel = if is_extended_range { 8 } else { 4 };
+    let unpadded_bytes_per_row = width * bytes_per_pixel;
+    let padded_bytes_per_row = unpadded_bytes_per_row
+        .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("sugarloaf::offscreen buffer"),
+        size: (padded_bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    ctx.queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| {});
+    if let Err(err) = ctx.device.poll(wgpu::PollType::Wait) {
+        tracing::error!("unable to read the offscreen texture: {err}");
+        return Vec::new();
+    }
+
+    let is_bgra = matches!(
+        texture.format(),
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    );
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for row in slice
+        .get_mapped_range()
+        .chunks(padded_bytes_per_row as usize)
+    {
+        let row = &row[..unpadded_bytes_per_row as usize];
+        for pixel in row.chunks_exact(bytes_per_pixel as usize) {
+            if is_extended_range {
+                let channel = |index: usize| {
+                    f16_to_f32(u16::from_le_bytes([pixel[index], pixel[index + 1]]))
+                };
+                pixels.extend_from_slice(&[
+                    linear_to_srgb_u8(channel(0)),
+                    linear_to_srgb_u8(channel(2)),
+                    linear_to_srgb_u8(channel(4)),
+                    (channel(6).clamp(0.0, 1.0) * 255.0).round() as u8,
+                ]);
+            } else if is_bgra {
+                pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+            } else {
+                pixels.extend_from_slice(pixel);
+            }
+        }
+    }
+    buffer.unmap();
+
+    pixels
+}
+
+/// Converts a half precision float, as stored by `Rgba16Float` textures.
+pub(crate) fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// Encodes a linear channel with the sRGB transfer function, values out
+/// of the standard range are clipped.
+fn linear_to_srgb_u8(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let encoded = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+fn prepare_graphic(
+    layer_brush: &mut LayerBrush,
+    encoder: &mut wgpu::CommandEncoder,
+    ctx: &mut Context,
+    graphics: &Graphics,
+    request: &GraphicRenderRequest,
+) -> bool {
+    let Some(entry) = graphics.get(&request.id) else {
+        return false;
+    };
+
+    layer_brush.prepare_with_handle(
+        encoder,
+        ctx,
+        &entry.handle,
+        &Rectangle {
+            width: request.width.unwrap_or(entry.width),
+            height: request.height.unwrap_or(entry.height),
+            x: request.pos_x,
+            y: request.pos_y,
+        },
+    )
 }
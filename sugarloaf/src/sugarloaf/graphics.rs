@@ -3,6 +3,8 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use crate::components::core::shapes::{Rectangle, Size};
+use crate::sugarloaf::primitives::ImageFit;
 use crate::sugarloaf::types;
 use crate::sugarloaf::Handle;
 use image_rs::DynamicImage;
@@ -25,11 +27,87 @@ pub struct GraphicRenderRequest {
     pub pos_y: f32,
     pub width: Option<f32>,
     pub height: Option<f32>,
+    pub z_index: i32,
 }
 
+/// Maximum amount of copies of a tiled background image.
+const MAX_BOTTOM_LAYER_TILES: usize = 4096;
+
 pub struct BottomLayer {
     pub data: types::Raster,
     pub should_fit: bool,
+    pub fit: ImageFit,
+}
+
+impl BottomLayer {
+    /// Rasters to draw for a window of the given size, based on the
+    /// image size and the fit mode.
+    pub fn rasters(
+        &self,
+        image: Size<u32>,
+        width: f32,
+        height: f32,
+    ) -> Vec<types::Raster> {
+        let bounds = self.data.bounds;
+        let (image_width, image_height) = (image.width as f32, image.height as f32);
+
+        if !self.should_fit || image_width < 1.0 || image_height < 1.0 {
+            return vec![self.data.clone()];
+        }
+
+        let raster = |x: f32, y: f32, width: f32, height: f32| types::Raster {
+            handle: self.data.handle.clone(),
+            bounds: Rectangle {
+                x,
+                y,
+                width,
+                height,
+            },
+            opacity: self.data.opacity,
+        };
+
+        match self.fit {
+            ImageFit::Stretch => vec![self.data.clone()],
+            ImageFit::Contain | ImageFit::Cover => {
+                let scale_x = width / image_width;
+                let scale_y = height / image_height;
+                let scale = if self.fit == ImageFit::Contain {
+                    scale_x.min(scale_y)
+                } else {
+                    scale_x.max(scale_y)
+                };
+
+                let (fitted_width, fitted_height) =
+                    (image_width * scale, image_height * scale);
+                vec![raster(
+                    bounds.x + (width - fitted_width) / 2.0,
+                    bounds.y + (height - fitted_height) / 2.0,
+                    fitted_width,
+                    fitted_height,
+                )]
+            }
+            ImageFit::Tile => {
+                // Tiles start before the window when the image is offset
+                let start_x = bounds.x.rem_euclid(image_width) - image_width;
+                let start_y = bounds.y.rem_euclid(image_height) - image_height;
+
+                let mut rasters = vec![];
+                let mut y = start_y;
+                while y < height && rasters.len() < MAX_BOTTOM_LAYER_TILES {
+                    let mut x = start_x;
+                    while x < width && rasters.len() < MAX_BOTTOM_LAYER_TILES {
+                        if x + image_width > 0.0 && y + image_height > 0.0 {
+                            rasters.push(raster(x, y, image_width, image_height));
+                        }
+                        x += image_width;
+                    }
+                    y += image_height;
+                }
+
+                rasters
+            }
+        }
+    }
 }
 
 #[derive(Default)]
@@ -75,6 +153,22 @@ impl Graphics {
         );
     }
 
+    /// Show a frame of an animated graphic in place of its current one.
+    #[inline]
+    pub fn update(&mut self, graphic_data: GraphicData) {
+        if let Some(entry) = self.inner.get_mut(&graphic_data.id) {
+            *entry = GraphicDataEntry {
+                handle: Handle::from_pixels(
+                    graphic_data.width as u32,
+                    graphic_data.height as u32,
+                    graphic_data.pixels,
+                ),
+                width: graphic_data.width as f32,
+                height: graphic_data.height as f32,
+            };
+        }
+    }
+
     #[inline]
     pub fn remove(&mut self, graphic_id: &GraphicId) {
         self.inner.remove(graphic_id);
@@ -86,6 +180,9 @@ pub struct Graphic {
     pub id: GraphicId,
     pub offset_x: u16,
     pub offset_y: u16,
+    /// Graphics with a negative z-index are drawn below the text, the
+    /// others above it.
+    pub z_index: i32,
 }
 
 /// Unique identifier for every graphic added to a grid.
@@ -363,3 +460,84 @@ fn check_opaque_region() {
     assert!(graphic.is_filled(0, 0, 3, 3));
     assert!(!graphic.is_filled(1, 1, 4, 4));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bottom_layer(fit: ImageFit) -> BottomLayer {
+        BottomLayer {
+            data: types::Raster {
+                handle: Handle::from_path("background.png"),
+                bounds: Rectangle {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 800.0,
+                    height: 600.0,
+                },
+                opacity: 0.5,
+            },
+            should_fit: true,
+            fit,
+        }
+    }
+
+    fn image_size(width: u32, height: u32) -> Size<u32> {
+        Size { width, height }
+    }
+
+    #[test]
+    fn test_bottom_layer_contain_and_cover() {
+        let image = image_size(400, 400);
+
+        let contain = bottom_layer(ImageFit::Contain).rasters(image, 800.0, 600.0);
+        assert_eq!(contain.len(), 1);
+        assert_eq!(
+            contain[0].bounds,
+            Rectangle {
+                x: 100.0,
+                y: 0.0,
+                width: 600.0,
+                height: 600.0,
+            }
+        );
+        assert_eq!(contain[0].opacity, 0.5);
+
+        let cover = bottom_layer(ImageFit::Cover).rasters(image, 800.0, 600.0);
+        assert_eq!(
+            cover[0].bounds,
+            Rectangle {
+                x: 0.0,
+                y: -100.0,
+                width: 800.0,
+                height: 800.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_bottom_layer_tile() {
+        let rasters =
+            bottom_layer(ImageFit::Tile).rasters(image_size(300, 300), 800.0, 600.0);
+        // 3 columns (0, 300 and 600) by 2 rows (0 and 300)
+        assert_eq!(rasters.len(), 6);
+        assert!(rasters
+            .iter()
+            .all(|raster| raster.bounds.width == 300.0 && raster.bounds.height == 300.0));
+
+        let mut offset = bottom_layer(ImageFit::Tile);
+        offset.data.bounds.x = 100.0;
+        let rasters = offset.rasters(image_size(300, 300), 800.0, 600.0);
+        assert_eq!(rasters[0].bounds.x, -200.0);
+        assert_eq!(rasters.len(), 8);
+    }
+
+    #[test]
+    fn test_bottom_layer_with_explicit_size() {
+        let mut layer = bottom_layer(ImageFit::Cover);
+        layer.should_fit = false;
+        let rasters = layer.rasters(image_size(400, 400), 800.0, 600.0);
+        assert_eq!(rasters.len(), 1);
+        assert_eq!(rasters[0].bounds.width, 800.0);
+    }
+}
@@ -3,29 +3,103 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
+use crate::components::core::image::Handle;
 use crate::Quad;
+use schemars::JsonSchema;
 use serde::Deserialize;
+use std::path::PathBuf;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum SugarCursor {
     Block([f32; 4]),
     HollowBlock([f32; 4]),
-    Caret([f32; 4]),
-    Underline([f32; 4]),
+    /// Beam cursor with its width.
+    Caret([f32; 4], f32),
+    /// Underline cursor with its height.
+    Underline([f32; 4], f32),
 }
 
-#[derive(Default, Clone, Deserialize, Debug, PartialEq)]
+/// Color space used to interpret colors when rendering to an
+/// extended range (HDR) surface.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Colorspace {
+    #[default]
+    Srgb,
+    DisplayP3,
+}
+
+/// Adds the names accepted besides the canonical ones for the variants of a
+/// config enum to its JSON Schema. `aliases` are separated by spaces, as
+/// `#[schemars(transform = ...)]` does not take array expressions.
+pub fn schema_aliases(aliases: &'static str) -> impl FnMut(&mut schemars::Schema) {
+    move |schema| {
+        let aliases: Vec<&str> = aliases.split_whitespace().collect();
+        if let Some(values) = schema.get_mut("enum").and_then(|e| e.as_array_mut()) {
+            values.extend(aliases.iter().map(|alias| (*alias).into()));
+        } else if let Some(variants) =
+            schema.get_mut("oneOf").and_then(|o| o.as_array_mut())
+        {
+            variants.push(schemars::json_schema!({ "enum": aliases }).into());
+        }
+    }
+}
+
+/// How an image without explicit width and height fills the window.
+#[derive(Default, Clone, Copy, Deserialize, Debug, PartialEq, JsonSchema)]
+#[schemars(transform = schema_aliases("stretch contain cover tile"))]
+pub enum ImageFit {
+    /// Stretches the image to the window size.
+    #[default]
+    #[serde(alias = "stretch")]
+    Stretch,
+    /// Scales the image to fit inside the window keeping the aspect ratio.
+    #[serde(alias = "contain")]
+    Contain,
+    /// Scales the image to fill the window keeping the aspect ratio.
+    #[serde(alias = "cover")]
+    Cover,
+    /// Repeats the image with its original size.
+    #[serde(alias = "tile")]
+    Tile,
+}
+
+#[inline]
+fn default_image_opacity() -> f32 {
+    1.0
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq, JsonSchema)]
 pub struct ImageProperties {
     #[serde(default = "String::default")]
     pub path: String,
     #[serde(default = "Option::default")]
+    #[schemars(default)]
     pub width: Option<f32>,
     #[serde(default = "Option::default")]
+    #[schemars(default)]
     pub height: Option<f32>,
     #[serde(default = "f32::default")]
     pub x: f32,
     #[serde(default = "f32::default")]
     pub y: f32,
+    #[serde(default = "ImageFit::default")]
+    pub fit: ImageFit,
+    #[serde(default = "default_image_opacity")]
+    pub opacity: f32,
+}
+
+impl Default for ImageProperties {
+    fn default() -> Self {
+        Self {
+            path: String::default(),
+            width: None,
+            height: None,
+            x: 0.0,
+            y: 0.0,
+            fit: ImageFit::default(),
+            opacity: default_image_opacity(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -41,10 +115,52 @@ pub struct RichText {
     pub lines: Option<RichTextLinesRange>,
 }
 
+/// Image drawn as an object, independent of the terminal graphics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub handle: Handle,
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+}
+
+impl Image {
+    /// Creates an [`Image`] from a file, the format is guessed from its content.
+    #[inline]
+    pub fn from_path<T: Into<PathBuf>>(
+        path: T,
+        position: [f32; 2],
+        size: [f32; 2],
+    ) -> Self {
+        Self {
+            handle: Handle::from_path(path),
+            position,
+            size,
+        }
+    }
+
+    /// Creates an [`Image`] from encoded bytes (e.g: PNG or JPEG).
+    ///
+    /// Bytes are hashed to identify the image, so prefer to keep and clone
+    /// the [`Image`] instead of creating it on every frame.
+    #[inline]
+    pub fn from_bytes(
+        bytes: impl AsRef<[u8]> + Send + Sync + 'static,
+        position: [f32; 2],
+        size: [f32; 2],
+    ) -> Self {
+        Self {
+            handle: Handle::from_memory(bytes),
+            position,
+            size,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Object {
     Quad(Quad),
     RichText(RichText),
+    Image(Image),
 }
 
 pub enum CornerType {
@@ -76,7 +192,7 @@ pub fn drawable_character(character: char) -> Option<DrawableChar> {
     None
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DrawableChar {
     // Original box-drawing characters
     Horizontal,       // ─
@@ -237,9 +353,12 @@ pub enum DrawableChar {
     // First row (no dot 7, no dot 8)
     BrailleBlank, // ⠀ U+2800 BRAILLE PATTERN BLANK
     Braille(Braille),
+
+    // Invisible characters, drawn as a box with the code point
+    HexBox(u32),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Braille {
     Dots1,      // ⠁ U+2801 BRAILLE PATTERN DOTS-1
     Dots2,      // ⠂ U+2802 BRAILLE PATTERN DOTS-2
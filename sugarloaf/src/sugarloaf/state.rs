@@ -3,12 +3,12 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::font::FontLibrary;
+use crate::font::{fonts::LetterSpacing, FontLibrary};
 use crate::layout::RootStyle;
 use crate::sugarloaf::QuadBrush;
 use crate::sugarloaf::{RichTextBrush, RichTextLayout};
 use crate::Graphics;
-use crate::{Content, Object, Quad, RichText, SugarDimensions};
+use crate::{Content, Image, Object, Quad, RichText, SugarDimensions};
 use std::collections::HashSet;
 
 pub struct SugarState {
@@ -19,6 +19,25 @@ pub struct SugarState {
     pub style: RootStyle,
     pub content: Content,
     pub quads: Vec<Quad>,
+    /// Clickable regions of the rendered rich texts, in physical pixels.
+    pub links: Vec<LinkRegion>,
+}
+
+/// Area of the screen covered by a rich text link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkRegion {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub url: String,
+}
+
+impl LinkRegion {
+    #[inline]
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
 }
 
 impl SugarState {
@@ -34,6 +53,7 @@ impl SugarState {
         SugarState {
             content: Content::new(font_library),
             quads: vec![],
+            links: vec![],
             style,
             objects: vec![],
             rich_texts: vec![],
@@ -138,6 +158,49 @@ impl SugarState {
         }
     }
 
+    #[inline]
+    pub fn set_rich_text_letter_spacing(
+        &mut self,
+        rich_text_id: &usize,
+        letter_spacing: LetterSpacing,
+        advance_brush: &mut RichTextBrush,
+    ) {
+        if let Some(rte) = self.content.get_state_mut(rich_text_id) {
+            if rte.layout.letter_spacing == letter_spacing {
+                return;
+            }
+            rte.layout.letter_spacing = letter_spacing;
+
+            rte.layout.dimensions.height = 0.0;
+            rte.layout.dimensions.width = 0.0;
+            self.rich_text_repaint.insert(*rich_text_id);
+        }
+
+        self.process_rich_text_repaint(advance_brush);
+    }
+
+    #[inline]
+    pub fn set_rich_text_baseline_offset(
+        &mut self,
+        rich_text_id: &usize,
+        baseline_offset: f32,
+    ) {
+        if let Some(rte) = self.content.get_state_mut(rich_text_id) {
+            rte.layout.baseline_offset = baseline_offset;
+        }
+    }
+
+    #[inline]
+    pub fn set_rich_text_snap_to_pixels(
+        &mut self,
+        rich_text_id: &usize,
+        snap_to_pixels: bool,
+    ) {
+        if let Some(rte) = self.content.get_state_mut(rich_text_id) {
+            rte.layout.snap_to_pixels = snap_to_pixels;
+        }
+    }
+
     fn process_rich_text_repaint(&mut self, advance_brush: &mut RichTextBrush) {
         for rich_text in &self.rich_text_repaint {
             self.content.update_dimensions(rich_text, advance_brush);
@@ -183,6 +246,14 @@ impl SugarState {
         self.rich_texts = rich_texts
     }
 
+    #[inline]
+    pub fn images(&self) -> impl Iterator<Item = &Image> {
+        self.objects.iter().filter_map(|object| match object {
+            Object::Image(image) => Some(image),
+            _ => None,
+        })
+    }
+
     #[inline]
     pub fn reset(&mut self) {
         self.quads.clear();
@@ -226,6 +297,7 @@ impl SugarState {
         context: &mut super::Context,
         graphics: &mut Graphics,
     ) {
+        self.content.apply_shaped();
         advance_brush.prepare(context, self, graphics);
         quad_brush.resize(context);
 
@@ -240,6 +312,9 @@ impl SugarState {
                 Object::RichText(_rich_text) => {
                     // self.rich_texts.push(*rich_text);
                 }
+                Object::Image(_image) => {
+                    // Images are prepared by the layer brush
+                }
             }
         }
     }
@@ -282,4 +357,43 @@ impl SugarState {
 
         self.rich_text_repaint.clear();
     }
+
+    /// Maps the links of the rich texts being rendered to screen regions,
+    /// it needs to run before temporary rich texts are removed.
+    pub fn compute_links(&mut self) {
+        self.links.clear();
+        for rich_text in &self.rich_texts {
+            let Some(rte) = self.content.get_state(&rich_text.id) else {
+                continue;
+            };
+
+            let dimensions = rte.layout.dimensions;
+            let cell_height = dimensions.height * rte.layout.effective_line_height();
+            let first_line = rich_text.lines.map_or(0, |range| range.start);
+            let last_line = rich_text.lines.map_or(usize::MAX, |range| range.end);
+            for link in &rte.links {
+                if link.line < first_line || link.line >= last_line {
+                    continue;
+                }
+
+                self.links.push(LinkRegion {
+                    x: rich_text.position[0] * self.style.scale_factor
+                        + link.start * dimensions.width,
+                    y: rich_text.position[1] * self.style.scale_factor
+                        + (link.line - first_line) as f32 * cell_height,
+                    width: (link.end - link.start) * dimensions.width,
+                    height: cell_height,
+                    url: link.url.clone(),
+                });
+            }
+        }
+    }
+
+    #[inline]
+    pub fn link_at(&self, x: f32, y: f32) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|link| link.contains(x, y))
+            .map(|link| link.url.as_str())
+    }
 }
@@ -0,0 +1,69 @@
+use crate::GlyphCacheStats;
+use std::time::Duration;
+
+/// What was drawn in a frame and how long it took.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    /// Time spent laying out the content and preparing the brushes.
+    pub prepare_time: Duration,
+    /// Time spent encoding and submitting the draw commands.
+    pub draw_time: Duration,
+    /// Rich texts drawn in the frame.
+    pub rich_texts: usize,
+    /// Quads drawn in the frame.
+    pub quads: usize,
+    /// Images and graphics drawn in the frame.
+    pub images: usize,
+    /// Glyph lookups of this frame only, see [`GlyphCacheStats::hit_rate`].
+    pub glyph_cache: GlyphCacheStats,
+    /// Lines shaped in the frame, hits reuse the shaping of an identical line.
+    pub shaped_lines: CacheStats,
+    /// Lines drawn in the frame, hits reuse the vertices of a previous frame.
+    pub drawn_lines: CacheStats,
+    /// Rich texts with all of their lines rebuilt.
+    pub full_rebuilds: usize,
+    /// Lines rebuilt in the rich texts which were partially updated.
+    pub partial_rebuilds: usize,
+    /// Glyphs left undrawn because the atlas was full, it doesn't evict.
+    pub atlas_overflows: u64,
+}
+
+/// Lookups served by a cache and the ones computed again.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Ratio of lookups served by the cache, from 0 to 1.
+    #[inline]
+    pub fn hit_rate(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 0.0;
+        }
+
+        self.hits as f32 / total as f32
+    }
+}
+
+impl std::ops::AddAssign for CacheStats {
+    fn add_assign(&mut self, other: Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+    }
+}
+
+/// Receives the statistics of every rendered frame, see
+/// [`crate::Sugarloaf::set_frame_observer`].
+pub trait FrameObserver {
+    fn on_frame(&mut self, stats: &FrameStats);
+}
+
+impl<F: FnMut(&FrameStats)> FrameObserver for F {
+    #[inline]
+    fn on_frame(&mut self, stats: &FrameStats) {
+        self(stats)
+    }
+}
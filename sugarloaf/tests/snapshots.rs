@@ -0,0 +1,299 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+//! Rendering regression tests, each scene is rendered with a headless
+//! Sugarloaf and compared against its golden image in `tests/snapshots`.
+//!
+//! Missing golden images are written on the first run, set
+//! `SUGARLOAF_UPDATE_SNAPSHOTS=1` to overwrite them after an intended
+//! change. The tests are skipped when no graphics adapter is available.
+
+use std::path::PathBuf;
+use sugarloaf::font::FontLibrary;
+use sugarloaf::layout::RootStyle;
+use sugarloaf::{
+    Content, FragmentStyle, FragmentStyleDecoration, FrameStats, Object, RichText,
+    SugarCursor, Sugarloaf, SugarloafRenderer, SugarloafWindowSize, UnderlineInfo,
+    UnderlineShape,
+};
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 120;
+
+/// Maximum difference allowed per channel, drivers may round differently.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Maximum ratio of pixels allowed to exceed the channel tolerance.
+const PIXEL_TOLERANCE: f32 = 0.005;
+
+const FOREGROUND: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const CURSOR: [f32; 4] = [0.97, 0.07, 1.0, 1.0];
+
+fn headless(font_library: &FontLibrary) -> Option<Sugarloaf<'static>> {
+    let size = SugarloafWindowSize {
+        width: WIDTH as f32,
+        height: HEIGHT as f32,
+    };
+
+    match Sugarloaf::new_headless(
+        size,
+        1.0,
+        SugarloafRenderer::default(),
+        font_library,
+        RootStyle::new(1.0, 18.0, 1.0),
+    ) {
+        Ok(sugarloaf) => Some(sugarloaf),
+        Err(err) => {
+            eprintln!("skipping snapshot test, no graphics adapter: {err}");
+            None
+        }
+    }
+}
+
+fn render(build: impl FnOnce(&mut Content, usize, &FontLibrary)) -> Option<Vec<u8>> {
+    let font_library = FontLibrary::default();
+    let mut sugarloaf = headless(&font_library)?;
+    let id = sugarloaf.create_rich_text();
+    build(sugarloaf.content(), id, &font_library);
+    sugarloaf.set_objects(vec![Object::RichText(RichText {
+        id,
+        position: [10., 10.],
+        lines: None,
+    })]);
+    Some(sugarloaf.render_to_rgba())
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.png"))
+}
+
+fn read_png(path: &PathBuf) -> Option<Vec<u8>> {
+    let decoder = png::Decoder::new(std::fs::File::open(path).ok()?);
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    if info.width != WIDTH
+        || info.height != HEIGHT
+        || info.color_type != png::ColorType::Rgba
+        || info.bit_depth != png::BitDepth::Eight
+    {
+        return None;
+    }
+
+    buffer.truncate(info.buffer_size());
+    Some(buffer)
+}
+
+fn write_png(path: &PathBuf, pixels: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let file = std::io::BufWriter::new(std::fs::File::create(path).unwrap());
+    let mut encoder = png::Encoder::new(file, WIDTH, HEIGHT);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(pixels).unwrap();
+}
+
+fn assert_snapshot(name: &str, pixels: &[u8]) {
+    assert_eq!(pixels.len(), (WIDTH * HEIGHT * 4) as usize);
+
+    let path = snapshot_path(name);
+    let golden = if std::env::var_os("SUGARLOAF_UPDATE_SNAPSHOTS").is_some() {
+        None
+    } else {
+        read_png(&path)
+    };
+
+    let Some(golden) = golden else {
+        write_png(&path, pixels);
+        return;
+    };
+
+    let different = golden
+        .chunks_exact(4)
+        .zip(pixels.chunks_exact(4))
+        .filter(|(expected, actual)| {
+            expected
+                .iter()
+                .zip(actual.iter())
+                .any(|(e, a)| e.abs_diff(*a) > CHANNEL_TOLERANCE)
+        })
+        .count();
+
+    let ratio = different as f32 / (WIDTH * HEIGHT) as f32;
+    if ratio > PIXEL_TOLERANCE {
+        let actual = std::env::temp_dir().join(format!("sugarloaf-{name}.actual.png"));
+        write_png(&actual, pixels);
+        panic!(
+            "snapshot {name} differs in {different} pixels, the result was written to {}",
+            actual.display()
+        );
+    }
+}
+
+fn cursor_style(cursor: SugarCursor) -> FragmentStyle {
+    FragmentStyle {
+        color: FOREGROUND,
+        cursor: Some(cursor),
+        ..FragmentStyle::default()
+    }
+}
+
+fn decoration_style(decoration: FragmentStyleDecoration) -> FragmentStyle {
+    FragmentStyle {
+        color: FOREGROUND,
+        decoration: Some(decoration),
+        decoration_color: Some(CURSOR),
+        ..FragmentStyle::default()
+    }
+}
+
+fn underline(shape: UnderlineShape, is_doubled: bool) -> FragmentStyleDecoration {
+    FragmentStyleDecoration::Underline(UnderlineInfo {
+        offset: -1.0,
+        size: 1.0,
+        is_doubled,
+        shape,
+    })
+}
+
+#[test]
+fn test_snapshot_cursor_shapes() {
+    let Some(pixels) = render(|content, id, _| {
+        let text = FragmentStyle {
+            color: FOREGROUND,
+            ..FragmentStyle::default()
+        };
+
+        content
+            .sel(id)
+            .clear()
+            .new_line()
+            .add_text("block ", text)
+            .add_text("a", cursor_style(SugarCursor::Block(CURSOR)))
+            .new_line()
+            .add_text("hollow ", text)
+            .add_text("b", cursor_style(SugarCursor::HollowBlock(CURSOR)))
+            .new_line()
+            .add_text("caret ", text)
+            .add_text("c", cursor_style(SugarCursor::Caret(CURSOR, 3.0)))
+            .new_line()
+            .add_text("underline ", text)
+            .add_text("d", cursor_style(SugarCursor::Underline(CURSOR, 0.1)))
+            .build();
+    }) else {
+        return;
+    };
+
+    assert_snapshot("cursor_shapes", &pixels);
+}
+
+#[test]
+fn test_snapshot_decorations() {
+    let Some(pixels) = render(|content, id, _| {
+        content
+            .sel(id)
+            .clear()
+            .new_line()
+            .add_text(
+                "regular",
+                decoration_style(underline(UnderlineShape::Regular, false)),
+            )
+            .new_line()
+            .add_text(
+                "doubled",
+                decoration_style(underline(UnderlineShape::Regular, true)),
+            )
+            .new_line()
+            .add_text(
+                "dotted",
+                decoration_style(underline(UnderlineShape::Dotted, false)),
+            )
+            .add_text(
+                " dashed",
+                decoration_style(underline(UnderlineShape::Dashed, false)),
+            )
+            .new_line()
+            .add_text(
+                "curly",
+                decoration_style(underline(UnderlineShape::Curly, false)),
+            )
+            .add_text(
+                " strikethrough",
+                decoration_style(FragmentStyleDecoration::Strikethrough),
+            )
+            .build();
+    }) else {
+        return;
+    };
+
+    assert_snapshot("decorations", &pixels);
+}
+
+#[test]
+fn test_snapshot_emoji() {
+    let Some(pixels) = render(|content, id, font_library| {
+        let text = FragmentStyle {
+            color: FOREGROUND,
+            ..FragmentStyle::default()
+        };
+
+        content.sel(id).clear().new_line().add_text("emoji ", text);
+        for emoji in ['🦀', '🎉', '👍'] {
+            // Emoji are resolved to their font the same way the terminal does
+            let mut style = text;
+            if let Some((font_id, is_emoji)) = font_library
+                .inner
+                .lock()
+                .find_best_font_match(emoji, &style)
+            {
+                style.font_id = font_id;
+                if is_emoji {
+                    style.width = 2.0;
+                }
+            }
+            content.add_text(&emoji.to_string(), style);
+        }
+        content.build();
+    }) else {
+        return;
+    };
+
+    assert_snapshot("emoji", &pixels);
+}
+
+#[test]
+fn test_frame_stats_reach_the_observer() {
+    let font_library = FontLibrary::default();
+    let Some(mut sugarloaf) = headless(&font_library) else {
+        return;
+    };
+
+    let frames = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let observed = frames.clone();
+    sugarloaf.set_frame_observer(Some(Box::new(move |stats: &FrameStats| {
+        observed.borrow_mut().push(*stats);
+    })));
+
+    let id = sugarloaf.create_rich_text();
+    sugarloaf
+        .content()
+        .sel(id)
+        .clear()
+        .new_line()
+        .add_text("stats", FragmentStyle::default())
+        .build();
+    sugarloaf.set_objects(vec![Object::RichText(RichText {
+        id,
+        position: [10., 10.],
+        lines: None,
+    })]);
+    sugarloaf.render_to_rgba();
+
+    let frames = frames.borrow();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0], sugarloaf.frame_stats());
+    assert_eq!(frames[0].rich_texts, 1);
+    assert!(frames[0].glyph_cache.misses > 0);
+}
@@ -398,6 +398,7 @@ pub fn create_pty_with_spawn(
     shell: &str,
     args: Vec<String>,
     working_directory: &Option<String>,
+    env: &[(String, String)],
     columns: u16,
     rows: u16,
 ) -> Result<Pty, Error> {
@@ -466,6 +467,10 @@ pub fn create_pty_with_spawn(
                 "--env=COLORTERM=truecolor".to_string(),
                 "--env=TERM=rio".to_string(),
             ];
+            with_args.extend(
+                env.iter()
+                    .map(|(key, value)| format!("--env={key}={value}")),
+            );
 
             if let Some(directory) = working_directory {
                 with_args.push(format!(
@@ -500,6 +505,7 @@ pub fn create_pty_with_spawn(
 
     builder.env("USER", user.user);
     builder.env("HOME", user.home);
+    builder.envs(env.iter().cloned());
 
     unsafe {
         builder.pre_exec(move || {
@@ -896,6 +902,17 @@ pub fn foreground_process_name(main_fd: RawFd, shell_pid: u32) -> String {
     name
 }
 
+/// Name of the program in the foreground of the terminal when it isn't the
+/// shell, like an editor or a build still running.
+pub fn foreground_child_process_name(main_fd: RawFd, shell_pid: u32) -> Option<String> {
+    let pid = unsafe { libc::tcgetpgrp(main_fd) };
+    if pid < 0 || pid as u32 == shell_pid {
+        return None;
+    }
+
+    Some(foreground_process_name(main_fd, shell_pid))
+}
+
 pub fn foreground_process_path(
     main_fd: RawFd,
     shell_pid: u32,
@@ -919,6 +936,18 @@ pub fn foreground_process_path(
     Ok(cwd)
 }
 
+/// Returns true when the foreground program disabled echo while keeping the
+/// canonical mode, which is what password prompts (e.g `sudo`, `ssh`) do.
+pub fn is_echo_disabled(main_fd: RawFd) -> bool {
+    let mut termios = MaybeUninit::<libc::termios>::uninit();
+    if unsafe { libc::tcgetattr(main_fd, termios.as_mut_ptr()) } != 0 {
+        return false;
+    }
+
+    let termios = unsafe { termios.assume_init() };
+    termios.c_lflag & libc::ECHO == 0 && termios.c_lflag & libc::ICANON != 0
+}
+
 /// Start a new process in the background.
 pub fn spawn_daemon<I, S>(
     program: &str,
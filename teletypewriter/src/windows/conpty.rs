@@ -1,5 +1,7 @@
 use crate::Winsize;
+use std::ffi::OsString;
 use std::io::{Error, Result};
+use std::os::windows::ffi::OsStrExt;
 use std::os::windows::io::IntoRawHandle;
 use std::{mem, ptr};
 use tracing::*;
@@ -16,7 +18,7 @@ use windows_sys::{s, w};
 
 use windows_sys::Win32::System::Threading::{
     CreateProcessW, InitializeProcThreadAttributeList, UpdateProcThreadAttribute,
-    EXTENDED_STARTUPINFO_PRESENT, PROCESS_INFORMATION,
+    CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT, PROCESS_INFORMATION,
     PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE, STARTF_USESTDHANDLES, STARTUPINFOEXW,
     STARTUPINFOW,
 };
@@ -106,6 +108,7 @@ unsafe impl Send for Conpty {}
 pub fn new(
     shell: &str,
     working_directory: &Option<String>,
+    env: &[(String, String)],
     columns: u16,
     rows: u16,
 ) -> Result<Pty> {
@@ -215,6 +218,7 @@ pub fn new(
 
     let cmdline = win32_string(&cmdline(shell));
     let cwd = working_directory.as_ref().map(win32_string);
+    let environment = environment_block(env);
 
     let mut proc_info: PROCESS_INFORMATION = unsafe { mem::zeroed() };
     unsafe {
@@ -224,8 +228,10 @@ pub fn new(
             ptr::null_mut(),
             ptr::null_mut(),
             false as i32,
-            EXTENDED_STARTUPINFO_PRESENT,
-            ptr::null_mut(),
+            EXTENDED_STARTUPINFO_PRESENT | CREATE_UNICODE_ENVIRONMENT,
+            environment
+                .as_ref()
+                .map_or_else(ptr::null_mut, |block| block.as_ptr() as *mut _),
             cwd.as_ref().map_or_else(ptr::null, |s| s.as_ptr()),
             &mut startup_info_ex.StartupInfo as *mut STARTUPINFOW,
             &mut proc_info as *mut PROCESS_INFORMATION,
@@ -265,3 +271,63 @@ impl From<Winsize> for COORD {
         }
     }
 }
+
+/// Environment block of the child, the variables of Rio with `env` on top.
+/// `None` when there is nothing to add, so the child inherits them.
+fn environment_block(env: &[(String, String)]) -> Option<Vec<u16>> {
+    if env.is_empty() {
+        return None;
+    }
+
+    Some(encode_environment(std::env::vars_os().collect(), env))
+}
+
+/// Encodes `vars` with `env` on top, sorted by name without case like
+/// `CreateProcessW` expects.
+fn encode_environment(
+    mut vars: Vec<(OsString, OsString)>,
+    env: &[(String, String)],
+) -> Vec<u16> {
+    for (key, value) in env {
+        // Variable names are case insensitive on Windows
+        vars.retain(|(name, _)| !name.eq_ignore_ascii_case(key));
+        vars.push((key.into(), value.into()));
+    }
+    vars.sort_by_cached_key(|(name, _)| name.to_string_lossy().to_uppercase());
+
+    let mut block = Vec::new();
+    for (key, value) in vars {
+        block.extend(key.encode_wide());
+        block.push('=' as u16);
+        block.extend(value.encode_wide());
+        block.push(0);
+    }
+    block.push(0);
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_environment() {
+        let vars = vec![
+            (OsString::from("Path"), OsString::from("C:\\Windows")),
+            (OsString::from("TERM"), OsString::from("dumb")),
+            (OsString::from("ComSpec"), OsString::from("cmd.exe")),
+        ];
+        let env = [
+            (String::from("term"), String::from("xterm-256color")),
+            (String::from("COLORTERM"), String::from("truecolor")),
+        ];
+
+        let block = encode_environment(vars, &env);
+        let block = String::from_utf16(&block).unwrap();
+        assert_eq!(
+            block,
+            "COLORTERM=truecolor\0ComSpec=cmd.exe\0Path=C:\\Windows\0\
+             term=xterm-256color\0\0"
+        );
+    }
+}
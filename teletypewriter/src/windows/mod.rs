@@ -36,6 +36,7 @@ pub fn create_pty(
     shell: &str,
     args: Vec<String>,
     working_directory: &Option<String>,
+    env: &[(String, String)],
     columns: u16,
     rows: u16,
 ) -> Result<Pty, std::io::Error> {
@@ -45,7 +46,7 @@ pub fn create_pty(
     } else {
         shell
     };
-    conpty::new(exec, working_directory, columns, rows)
+    conpty::new(exec, working_directory, env, columns, rows)
 }
 
 impl Pty {